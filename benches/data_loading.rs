@@ -0,0 +1,39 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use i_ching::IChingData;
+use i_ching::core::data::Hexagram;
+use std::collections::HashMap;
+use std::hint::black_box;
+
+fn data_loading(c: &mut Criterion) {
+    // What every command used to pay: parse the whole uncompressed corpus up front
+    c.bench_function("eager_parse_uncompressed", |b| {
+        b.iter(|| {
+            let hexagrams: HashMap<String, Hexagram> =
+                serde_json::from_str(include_str!("../data/hexagrams.json")).unwrap();
+            black_box(hexagrams)
+        })
+    });
+
+    c.bench_function("load", |b| {
+        b.iter(|| black_box(IChingData::load().unwrap()))
+    });
+
+    // A typical reading touches at most two hexagrams
+    c.bench_function("load_and_get_two", |b| {
+        b.iter(|| {
+            let data = IChingData::load().unwrap();
+            black_box(data.get_hexagram(black_box(32)).is_some());
+            black_box(data.get_hexagram(black_box(34)).is_some());
+        })
+    });
+
+    c.bench_function("load_and_get_all", |b| {
+        b.iter(|| {
+            let data = IChingData::load().unwrap();
+            black_box(data.hexagrams().count())
+        })
+    });
+}
+
+criterion_group!(benches, data_loading);
+criterion_main!(benches);
@@ -0,0 +1,42 @@
+use criterion::{Criterion, criterion_group, criterion_main};
+use i_ching::format::{FullOptions, format_full_with_data};
+use i_ching::glyphs::Glyphs;
+use i_ching::json::json_reading_with_data;
+use i_ching::{IChingData, Reading};
+use std::hint::black_box;
+
+fn formatting(c: &mut Criterion) {
+    let data = IChingData::load().unwrap();
+    data.warm();
+    // Three changing lines, so every section of full output has something in it
+    let reading: Reading = "9,8,6,7,9,8".parse().unwrap();
+    let reading = reading.with_question(Some("Should I take the job?".to_string()));
+    let options = FullOptions {
+        include_pair: true,
+        include_wings: true,
+        ..FullOptions::default()
+    };
+
+    c.bench_function("format_full", |b| {
+        b.iter(|| {
+            black_box(
+                format_full_with_data(black_box(&reading), &data, Glyphs::Unicode, &options)
+                    .unwrap(),
+            )
+        })
+    });
+
+    c.bench_function("json_reading", |b| {
+        b.iter(|| black_box(json_reading_with_data(black_box(&reading), &data).unwrap()))
+    });
+
+    c.bench_function("json_reading_to_string", |b| {
+        b.iter(|| {
+            let json = json_reading_with_data(black_box(&reading), &data).unwrap();
+            black_box(serde_json::to_string_pretty(&json).unwrap())
+        })
+    });
+}
+
+criterion_group!(benches, formatting);
+criterion_main!(benches);
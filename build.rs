@@ -0,0 +1,92 @@
+use flate2::write::DeflateEncoder;
+use flate2::{Compression, Crc};
+use std::collections::BTreeMap;
+use std::env;
+use std::fmt::Write as _;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+/// Data files embedded as they are, checksummed so that a manifest notices
+/// when any text the output can draw on has changed
+const UNCOMPRESSED_DATA_FILES: [&str; 5] = [
+    "crossrefs.json",
+    "glossary.json",
+    "history.json",
+    "provenance.json",
+    "worksheet.json",
+];
+
+/// Compress the embedded data files so the binaries carry deflate streams
+/// instead of the raw JSON text. Each hexagram gets its own stream so a
+/// lookup only has to inflate the entry it needs. Alongside them go CRC-32
+/// checksums of the data files and of each stream, checked at load.
+fn main() {
+    let out_dir = env::var_os("OUT_DIR").expect("OUT_DIR is set by cargo");
+    let out_dir = Path::new(&out_dir);
+
+    let trigrams = read_data_file("trigrams.json");
+    write_compressed(&out_dir.join("trigrams.json.deflate"), trigrams.as_bytes());
+
+    let hexagrams_content = read_data_file("hexagrams.json");
+    let hexagrams: BTreeMap<String, serde_json::Value> = serde_json::from_str(&hexagrams_content)
+        .unwrap_or_else(|e| panic!("Invalid hexagrams.json: {}", e));
+
+    let mut index = String::from("static DATA_CHECKSUMS: &[(&str, u32)] = &[\n");
+    let mut data_file = |name: &str, content: &str| {
+        writeln!(
+            index,
+            "    ({:?}, {:#010x}),",
+            name,
+            checksum(content.as_bytes())
+        )
+        .unwrap();
+    };
+    data_file("trigrams.json", &trigrams);
+    data_file("hexagrams.json", &hexagrams_content);
+    for name in UNCOMPRESSED_DATA_FILES {
+        data_file(name, &read_data_file(name));
+    }
+    index.push_str("];\n\n");
+    index.push_str("static EMBEDDED_HEXAGRAMS: &[(&str, u32, &[u8])] = &[\n");
+    for (number, hexagram) in &hexagrams {
+        let file_name = format!("hexagram_{}.json.deflate", number);
+        let compressed =
+            write_compressed(&out_dir.join(&file_name), hexagram.to_string().as_bytes());
+        writeln!(
+            index,
+            "    ({:?}, {:#010x}, include_bytes!(concat!(env!(\"OUT_DIR\"), \"/{}\"))),",
+            number,
+            checksum(&compressed),
+            file_name
+        )
+        .unwrap();
+    }
+    index.push_str("];\n");
+
+    fs::write(out_dir.join("embedded_hexagrams.rs"), index)
+        .unwrap_or_else(|e| panic!("Failed to write hexagram index: {}", e));
+}
+
+fn read_data_file(name: &str) -> String {
+    let source = Path::new("data").join(name);
+    println!("cargo:rerun-if-changed={}", source.display());
+
+    fs::read_to_string(&source)
+        .unwrap_or_else(|e| panic!("Failed to read {}: {}", source.display(), e))
+}
+
+fn write_compressed(path: &Path, content: &[u8]) -> Vec<u8> {
+    let mut encoder = DeflateEncoder::new(Vec::new(), Compression::best());
+    encoder
+        .write_all(content)
+        .and_then(|_| encoder.finish())
+        .and_then(|compressed| fs::write(path, &compressed).map(|_| compressed))
+        .unwrap_or_else(|e| panic!("Failed to write {}: {}", path.display(), e))
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
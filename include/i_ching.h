@@ -0,0 +1,47 @@
+/* I Ching divination readings: the C interface of the i_ching library */
+
+#ifndef I_CHING_H
+#define I_CHING_H
+
+/* Generated by cbindgen from src/ffi.rs with cbindgen.toml. Do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+/*
+ Cast a reading with three coins and return it as JSON, in the schema of
+ `i-ching --format json`
+
+ `question` may be null for a reading without one. Returns null if the
+ question isn't UTF-8 or the data set can't be loaded.
+
+ # Safety
+
+ `question` must be null or point to a NUL-terminated string that stays
+ valid for the call.
+ */
+char *i_ching_cast_reading(const char *question);
+
+/*
+ Hexagram `number` (1-64) as JSON: its names, texts, trigrams, and line
+ texts, as the data set holds them
+
+ Returns null if the number is out of range or the data set can't be
+ loaded.
+ */
+char *i_ching_lookup_hexagram(uint8_t number);
+
+/*
+ Free a string returned by this library; null is ignored
+
+ # Safety
+
+ `string` must be null or a pointer this library returned that hasn't
+ been freed yet.
+ */
+void i_ching_free_string(char *string);
+
+#endif  /* I_CHING_H */
@@ -0,0 +1,282 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::f64::consts::PI;
+use std::fmt;
+
+/// Mean length of a lunation in days
+const SYNODIC_MONTH: f64 = 29.530588853;
+
+/// Julian date of a known new moon (2000-01-06 18:14 UTC)
+const REFERENCE_NEW_MOON: f64 = 2451550.1;
+
+/// Julian date of the J2000.0 epoch
+const J2000: f64 = 2451545.0;
+
+/// Moon and season at the moment a reading was cast
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Almanac {
+    pub moon_phase: MoonPhase,
+    /// Days since the last new moon
+    pub moon_age: f64,
+    /// Lit fraction of the moon's disc, 0 to 1
+    pub illumination: f64,
+    pub solar_term: SolarTerm,
+}
+
+impl Almanac {
+    pub fn at(time: DateTime<Utc>) -> Self {
+        let jd = julian_date(time);
+        let moon_age = (jd - REFERENCE_NEW_MOON).rem_euclid(SYNODIC_MONTH);
+        let illumination = (1.0 - (2.0 * PI * moon_age / SYNODIC_MONTH).cos()) / 2.0;
+
+        Self {
+            moon_phase: MoonPhase::from_age(moon_age),
+            moon_age,
+            illumination,
+            solar_term: SolarTerm::from_longitude(sun_longitude(jd)),
+        }
+    }
+}
+
+/// e.g. "Waxing Gibbous moon (78% lit), Grain Rain (Gǔyǔ)"
+impl fmt::Display for Almanac {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} moon ({:.0}% lit), {} ({})",
+            self.moon_phase.name(),
+            self.illumination * 100.0,
+            self.solar_term.name(),
+            self.solar_term.pinyin()
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MoonPhase {
+    New,
+    WaxingCrescent,
+    FirstQuarter,
+    WaxingGibbous,
+    Full,
+    WaningGibbous,
+    LastQuarter,
+    WaningCrescent,
+}
+
+impl MoonPhase {
+    const ALL: [MoonPhase; 8] = [
+        MoonPhase::New,
+        MoonPhase::WaxingCrescent,
+        MoonPhase::FirstQuarter,
+        MoonPhase::WaxingGibbous,
+        MoonPhase::Full,
+        MoonPhase::WaningGibbous,
+        MoonPhase::LastQuarter,
+        MoonPhase::WaningCrescent,
+    ];
+
+    /// Phase for a moon age in days, each named phase centred on its moment
+    fn from_age(age: f64) -> Self {
+        let eighth = SYNODIC_MONTH / 8.0;
+        let index = ((age + eighth / 2.0) / eighth) as usize % 8;
+        Self::ALL[index]
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            MoonPhase::New => "New",
+            MoonPhase::WaxingCrescent => "Waxing Crescent",
+            MoonPhase::FirstQuarter => "First Quarter",
+            MoonPhase::WaxingGibbous => "Waxing Gibbous",
+            MoonPhase::Full => "Full",
+            MoonPhase::WaningGibbous => "Waning Gibbous",
+            MoonPhase::LastQuarter => "Last Quarter",
+            MoonPhase::WaningCrescent => "Waning Crescent",
+        }
+    }
+}
+
+/// The 24 solar terms (jieqi) of the Chinese calendar, from the Start of
+/// Spring; each spans 15° of the sun's ecliptic longitude
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SolarTerm {
+    Lichun,
+    Yushui,
+    Jingzhe,
+    Chunfen,
+    Qingming,
+    Guyu,
+    Lixia,
+    Xiaoman,
+    Mangzhong,
+    Xiazhi,
+    Xiaoshu,
+    Dashu,
+    Liqiu,
+    Chushu,
+    Bailu,
+    Qiufen,
+    Hanlu,
+    Shuangjiang,
+    Lidong,
+    Xiaoxue,
+    Daxue,
+    Dongzhi,
+    Xiaohan,
+    Dahan,
+}
+
+impl SolarTerm {
+    const ALL: [SolarTerm; 24] = [
+        SolarTerm::Lichun,
+        SolarTerm::Yushui,
+        SolarTerm::Jingzhe,
+        SolarTerm::Chunfen,
+        SolarTerm::Qingming,
+        SolarTerm::Guyu,
+        SolarTerm::Lixia,
+        SolarTerm::Xiaoman,
+        SolarTerm::Mangzhong,
+        SolarTerm::Xiazhi,
+        SolarTerm::Xiaoshu,
+        SolarTerm::Dashu,
+        SolarTerm::Liqiu,
+        SolarTerm::Chushu,
+        SolarTerm::Bailu,
+        SolarTerm::Qiufen,
+        SolarTerm::Hanlu,
+        SolarTerm::Shuangjiang,
+        SolarTerm::Lidong,
+        SolarTerm::Xiaoxue,
+        SolarTerm::Daxue,
+        SolarTerm::Dongzhi,
+        SolarTerm::Xiaohan,
+        SolarTerm::Dahan,
+    ];
+
+    /// Term for the sun's ecliptic longitude in degrees (Start of Spring at 315°)
+    fn from_longitude(longitude: f64) -> Self {
+        let index = ((longitude - 315.0).rem_euclid(360.0) / 15.0) as usize;
+        Self::ALL[index.min(23)]
+    }
+
+    /// English name
+    pub fn name(&self) -> &'static str {
+        match self {
+            SolarTerm::Lichun => "Start of Spring",
+            SolarTerm::Yushui => "Rain Water",
+            SolarTerm::Jingzhe => "Awakening of Insects",
+            SolarTerm::Chunfen => "Spring Equinox",
+            SolarTerm::Qingming => "Clear and Bright",
+            SolarTerm::Guyu => "Grain Rain",
+            SolarTerm::Lixia => "Start of Summer",
+            SolarTerm::Xiaoman => "Grain Buds",
+            SolarTerm::Mangzhong => "Grain in Ear",
+            SolarTerm::Xiazhi => "Summer Solstice",
+            SolarTerm::Xiaoshu => "Minor Heat",
+            SolarTerm::Dashu => "Major Heat",
+            SolarTerm::Liqiu => "Start of Autumn",
+            SolarTerm::Chushu => "End of Heat",
+            SolarTerm::Bailu => "White Dew",
+            SolarTerm::Qiufen => "Autumn Equinox",
+            SolarTerm::Hanlu => "Cold Dew",
+            SolarTerm::Shuangjiang => "Frost's Descent",
+            SolarTerm::Lidong => "Start of Winter",
+            SolarTerm::Xiaoxue => "Minor Snow",
+            SolarTerm::Daxue => "Major Snow",
+            SolarTerm::Dongzhi => "Winter Solstice",
+            SolarTerm::Xiaohan => "Minor Cold",
+            SolarTerm::Dahan => "Major Cold",
+        }
+    }
+
+    pub fn pinyin(&self) -> &'static str {
+        match self {
+            SolarTerm::Lichun => "Lìchūn",
+            SolarTerm::Yushui => "Yǔshuǐ",
+            SolarTerm::Jingzhe => "Jīngzhé",
+            SolarTerm::Chunfen => "Chūnfēn",
+            SolarTerm::Qingming => "Qīngmíng",
+            SolarTerm::Guyu => "Gǔyǔ",
+            SolarTerm::Lixia => "Lìxià",
+            SolarTerm::Xiaoman => "Xiǎomǎn",
+            SolarTerm::Mangzhong => "Mángzhòng",
+            SolarTerm::Xiazhi => "Xiàzhì",
+            SolarTerm::Xiaoshu => "Xiǎoshǔ",
+            SolarTerm::Dashu => "Dàshǔ",
+            SolarTerm::Liqiu => "Lìqiū",
+            SolarTerm::Chushu => "Chǔshǔ",
+            SolarTerm::Bailu => "Báilù",
+            SolarTerm::Qiufen => "Qiūfēn",
+            SolarTerm::Hanlu => "Hánlù",
+            SolarTerm::Shuangjiang => "Shuāngjiàng",
+            SolarTerm::Lidong => "Lìdōng",
+            SolarTerm::Xiaoxue => "Xiǎoxuě",
+            SolarTerm::Daxue => "Dàxuě",
+            SolarTerm::Dongzhi => "Dōngzhì",
+            SolarTerm::Xiaohan => "Xiǎohán",
+            SolarTerm::Dahan => "Dàhán",
+        }
+    }
+}
+
+fn julian_date(time: DateTime<Utc>) -> f64 {
+    // The Unix epoch is JD 2440587.5
+    time.timestamp_millis() as f64 / 86_400_000.0 + 2440587.5
+}
+
+/// Apparent ecliptic longitude of the sun in degrees, good to about 0.01°
+/// (low-precision formula from the Astronomical Almanac)
+fn sun_longitude(jd: f64) -> f64 {
+    let n = jd - J2000;
+    let mean_longitude = 280.460 + 0.9856474 * n;
+    let mean_anomaly = (357.528 + 0.9856003 * n).to_radians();
+    (mean_longitude + 1.915 * mean_anomaly.sin() + 0.020 * (2.0 * mean_anomaly).sin())
+        .rem_euclid(360.0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn at(y: i32, m: u32, d: u32, h: u32) -> Almanac {
+        Almanac::at(Utc.with_ymd_and_hms(y, m, d, h, 0, 0).unwrap())
+    }
+
+    #[test]
+    fn test_moon_phase() {
+        // New moon 2024-04-08 18:21 UTC, full moon 2024-04-23 23:49 UTC
+        let new = at(2024, 4, 8, 18);
+        assert_eq!(new.moon_phase, MoonPhase::New);
+        assert!(new.illumination < 0.01);
+
+        let full = at(2024, 4, 23, 23);
+        assert_eq!(full.moon_phase, MoonPhase::Full);
+        assert!(full.illumination > 0.99);
+
+        assert_eq!(at(2024, 4, 15, 12).moon_phase, MoonPhase::FirstQuarter);
+    }
+
+    #[test]
+    fn test_solar_term() {
+        // Summer solstice 2024-06-20 20:51 UTC; winter solstice 2024-12-21 09:20 UTC
+        assert_eq!(at(2024, 6, 21, 0).solar_term, SolarTerm::Xiazhi);
+        assert_eq!(at(2024, 6, 20, 12).solar_term, SolarTerm::Mangzhong);
+        assert_eq!(at(2024, 12, 22, 0).solar_term, SolarTerm::Dongzhi);
+        // Start of Spring 2025-02-03 14:10 UTC
+        assert_eq!(at(2025, 2, 4, 0).solar_term, SolarTerm::Lichun);
+    }
+
+    #[test]
+    fn test_display() {
+        let almanac = at(2024, 4, 23, 23);
+        assert_eq!(
+            almanac.to_string(),
+            "Full moon (100% lit), Grain Rain (Gǔyǔ)"
+        );
+    }
+}
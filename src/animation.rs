@@ -0,0 +1,129 @@
+use crate::core::divination::{Coin, Diviner};
+use crate::core::reading::{Age, Line, Polarity, Reading};
+use crate::glyphs::Glyphs;
+use clap::ValueEnum;
+use std::io::{self, Write};
+use std::thread;
+use std::time::Duration;
+
+/// Spinning frames drawn before each toss resolves
+const SPIN_FRAMES: usize = 12;
+
+/// How quickly the coins spin before each line resolves
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum AnimationSpeed {
+    Slow,
+    Normal,
+    Fast,
+}
+
+impl AnimationSpeed {
+    /// Delay between spinning frames
+    pub fn frame_delay(&self) -> Duration {
+        match self {
+            AnimationSpeed::Slow => Duration::from_millis(120),
+            AnimationSpeed::Normal => Duration::from_millis(70),
+            AnimationSpeed::Fast => Duration::from_millis(30),
+        }
+    }
+}
+
+/// Cast a reading with the three coins method, drawing each toss to `out`
+///
+/// Every line shows its coins spinning, then the faces they landed on, the
+/// line total, and the drawn line.
+pub fn animated_cast(
+    diviner: &mut Diviner,
+    question: Option<String>,
+    glyphs: Glyphs,
+    frame_delay: Duration,
+    out: &mut impl Write,
+) -> io::Result<Reading> {
+    let mut lines = [Line::new(Age::Young, Polarity::Yang); 6];
+
+    for (i, slot) in lines.iter_mut().enumerate() {
+        let position = i + 1;
+        let coins = diviner.toss_coins();
+
+        for frame in 0..SPIN_FRAMES {
+            write!(out, "\r{}", spinning_frame(position, frame, glyphs))?;
+            out.flush()?;
+            thread::sleep(frame_delay);
+        }
+
+        let line = Diviner::line_from_coins(coins);
+        writeln!(out, "\r{}", resolved_frame(position, coins, &line, glyphs))?;
+        *slot = line;
+    }
+    writeln!(out)?;
+
+    Ok(Reading::new(lines, question))
+}
+
+/// Three coins mid-spin, each a little out of phase with the last
+fn spinning_frame(position: usize, frame: usize, glyphs: Glyphs) -> String {
+    let faces: &[&str] = match glyphs {
+        Glyphs::Unicode => &["◐", "◓", "◑", "◒"],
+        Glyphs::Ascii => &["|", "/", "-", "\\"],
+    };
+    let coins: Vec<&str> = (0..3).map(|c| faces[(frame + c) % faces.len()]).collect();
+    format!("Line {}  {}", position, coins.join(" "))
+}
+
+fn resolved_frame(position: usize, coins: [Coin; 3], line: &Line, glyphs: Glyphs) -> String {
+    let faces: Vec<&str> = coins
+        .iter()
+        .map(|coin| match coin {
+            Coin::Heads => "H",
+            Coin::Tails => "T",
+        })
+        .collect();
+    format!(
+        "Line {}  {}  = {}  {}",
+        position,
+        faces.join(" "),
+        line.traditional_number(),
+        glyphs.line(line)
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_animated_cast_draws_every_line() {
+        let mut diviner = Diviner::new();
+        let mut out = Vec::new();
+
+        let reading = animated_cast(
+            &mut diviner,
+            Some("Q".to_string()),
+            Glyphs::Ascii,
+            Duration::ZERO,
+            &mut out,
+        )
+        .unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.is_ascii());
+        for (i, number) in reading.traditional_numbers().iter().enumerate() {
+            assert!(out.contains(&format!("Line {}  ", i + 1)));
+            assert!(out.contains(&format!("= {}", number)));
+        }
+        assert_eq!(reading.question(), Some("Q"));
+    }
+
+    #[test]
+    fn test_frames() {
+        assert_eq!(spinning_frame(1, 0, Glyphs::Ascii), "Line 1  | / -");
+        assert_eq!(spinning_frame(1, 1, Glyphs::Ascii), "Line 1  / - \\");
+
+        let coins = [Coin::Heads, Coin::Tails, Coin::Tails];
+        let line = Diviner::line_from_coins(coins);
+        assert_eq!(
+            resolved_frame(2, coins, &line, Glyphs::Ascii),
+            "Line 2  H T T  = 7  ------"
+        );
+    }
+}
@@ -0,0 +1,377 @@
+//! The eight trigrams laid out by compass direction, for `i-ching bagua`
+//!
+//! Drawn the traditional way up, with south at the top and east on the left.
+
+use crate::core::data::IChingData;
+use crate::core::{Reading, Trigram};
+use crate::format::display_width;
+use crate::glyphs::Glyphs;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+
+/// Width of a cell of the text compass, in columns
+const CELL_WIDTH: usize = 18;
+
+/// Side of the square SVG drawing
+const SVG_SIZE: f64 = 480.0;
+
+/// Distance of the trigrams from the SVG's center
+const SVG_RADIUS: f64 = 165.0;
+
+/// How the trigrams are arranged around the compass
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Arrangement {
+    /// King Wen's Later Heaven order, which follows the seasons
+    Later,
+    /// Fu Xi's Earlier Heaven order, each trigram facing its complement
+    Earlier,
+}
+
+impl Arrangement {
+    fn label(&self) -> &'static str {
+        match self {
+            Arrangement::Later => "Later Heaven",
+            Arrangement::Earlier => "Earlier Heaven",
+        }
+    }
+}
+
+/// A compass point, clockwise from south at the top
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Direction {
+    South,
+    Southwest,
+    West,
+    Northwest,
+    North,
+    Northeast,
+    East,
+    Southeast,
+}
+
+impl Direction {
+    const ALL: [Direction; 8] = [
+        Direction::South,
+        Direction::Southwest,
+        Direction::West,
+        Direction::Northwest,
+        Direction::North,
+        Direction::Northeast,
+        Direction::East,
+        Direction::Southeast,
+    ];
+
+    fn parse(name: &str) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|direction| direction.name().eq_ignore_ascii_case(name.trim()))
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Direction::South => "south",
+            Direction::Southwest => "southwest",
+            Direction::West => "west",
+            Direction::Northwest => "northwest",
+            Direction::North => "north",
+            Direction::Northeast => "northeast",
+            Direction::East => "east",
+            Direction::Southeast => "southeast",
+        }
+    }
+
+    fn abbreviation(&self) -> &'static str {
+        match self {
+            Direction::South => "S",
+            Direction::Southwest => "SW",
+            Direction::West => "W",
+            Direction::Northwest => "NW",
+            Direction::North => "N",
+            Direction::Northeast => "NE",
+            Direction::East => "E",
+            Direction::Southeast => "SE",
+        }
+    }
+
+    /// Row and column in a 3×3 grid with south at the top
+    fn cell(&self) -> (usize, usize) {
+        match self {
+            Direction::Southeast => (0, 0),
+            Direction::South => (0, 1),
+            Direction::Southwest => (0, 2),
+            Direction::East => (1, 0),
+            Direction::West => (1, 2),
+            Direction::Northeast => (2, 0),
+            Direction::North => (2, 1),
+            Direction::Northwest => (2, 2),
+        }
+    }
+
+    /// Angle clockwise from the top, in degrees
+    fn angle(&self) -> f64 {
+        Self::ALL
+            .iter()
+            .position(|direction| direction == self)
+            .unwrap_or_default() as f64
+            * 45.0
+    }
+}
+
+/// Each trigram at its direction in `arrangement`, and what the data says of
+/// its place
+pub struct Compass {
+    arrangement: Arrangement,
+    points: Vec<Point>,
+    /// The reading's upper and lower trigrams and its hexagram, if one is shown
+    reading: Option<(Trigram, Trigram, String)>,
+}
+
+struct Point {
+    trigram: Trigram,
+    direction: Direction,
+    /// Season in the Later Heaven order, family member in the Earlier
+    note: String,
+}
+
+impl Compass {
+    pub fn new(data: &IChingData, arrangement: Arrangement) -> Result<Self> {
+        let points = Trigram::ALL
+            .into_iter()
+            .map(|trigram| {
+                let discussion = data
+                    .get_trigram(trigram)
+                    .and_then(|data| data.discussion.as_ref())
+                    .with_context(|| format!("No direction data for {}", trigram))?;
+                let (direction, note) = match arrangement {
+                    Arrangement::Later => (&discussion.direction, &discussion.season),
+                    Arrangement::Earlier => (&discussion.earlier_direction, &discussion.family),
+                };
+                let direction = Direction::parse(direction).with_context(|| {
+                    format!("Unknown direction '{}' for {}", direction, trigram)
+                })?;
+                Ok(Point {
+                    trigram,
+                    direction,
+                    note: note.clone(),
+                })
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self {
+            arrangement,
+            points,
+            reading: None,
+        })
+    }
+
+    /// Mark the reading's trigrams and put its hexagram at the center
+    pub fn with_reading(mut self, reading: &Reading, data: &IChingData, glyphs: Glyphs) -> Self {
+        let number = reading.primary_hexagram();
+        let center = match data.get_hexagram(number) {
+            Some(hexagram) => format!("{}{}", glyphs.prefix(&hexagram.unicode), number),
+            None => number.to_string(),
+        };
+        self.reading = Some((reading.upper_trigram(), reading.lower_trigram(), center));
+        self
+    }
+
+    /// "upper", "lower", or both, for the reading's trigrams
+    fn role(&self, trigram: Trigram) -> Option<&'static str> {
+        let (upper, lower, _) = self.reading.as_ref()?;
+        match (trigram == *upper, trigram == *lower) {
+            (true, true) => Some("upper+lower"),
+            (true, false) => Some("upper"),
+            (false, true) => Some("lower"),
+            (false, false) => None,
+        }
+    }
+
+    /// A 3×3 grid of the trigrams with south at the top, the reading's
+    /// trigrams bracketed
+    pub fn to_text(&self, glyphs: Glyphs) -> String {
+        let mut cells: [[Vec<String>; 3]; 3] = Default::default();
+        for point in &self.points {
+            let (row, column) = point.direction.cell();
+            let role = self.role(point.trigram);
+            let name = format!("{}{}", glyphs.prefix(point.trigram.symbol()), point.trigram);
+            cells[row][column] = vec![
+                point.direction.abbreviation().to_string(),
+                match role {
+                    Some(_) => format!("[{}]", name),
+                    None => name,
+                },
+                point.trigram.image().to_string(),
+                point.note.clone(),
+                role.unwrap_or_default().to_string(),
+            ];
+        }
+        cells[1][1] = match &self.reading {
+            Some((_, _, center)) => vec![String::new(), String::new(), center.clone()],
+            None => Vec::new(),
+        };
+
+        let mut text = format!("{} bagua (south at top)\n", self.arrangement.label());
+        for row in &cells {
+            text.push('\n');
+            let height = row.iter().map(Vec::len).max().unwrap_or_default();
+            for i in 0..height {
+                let line: String = row
+                    .iter()
+                    .map(|cell| center(cell.get(i).map_or("", String::as_str), CELL_WIDTH))
+                    .collect();
+                text.push_str(line.trim_end());
+                text.push('\n');
+            }
+        }
+        text
+    }
+
+    /// An SVG drawing of the compass, the reading's trigrams highlighted
+    pub fn to_svg(&self) -> String {
+        let middle = SVG_SIZE / 2.0;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {size} {size}\" width=\"{size}\" height=\"{size}\" font-family=\"sans-serif\" text-anchor=\"middle\">\n\
+             <title>{} bagua</title>\n\
+             <rect width=\"{size}\" height=\"{size}\" fill=\"white\"/>\n\
+             <circle cx=\"{middle}\" cy=\"{middle}\" r=\"{}\" fill=\"none\" stroke=\"#ccc\"/>\n",
+            self.arrangement.label(),
+            SVG_RADIUS,
+            size = SVG_SIZE,
+            middle = middle,
+        );
+
+        for point in &self.points {
+            let radians = point.direction.angle().to_radians();
+            let x = middle + SVG_RADIUS * radians.sin();
+            let y = middle - SVG_RADIUS * radians.cos();
+            let role = self.role(point.trigram);
+            if let Some(role) = role {
+                let fill = match role {
+                    "upper" => "#fde68a",
+                    "lower" => "#bfdbfe",
+                    _ => "#bbf7d0",
+                };
+                svg.push_str(&format!(
+                    "<circle cx=\"{:.1}\" cy=\"{:.1}\" r=\"52\" fill=\"{}\"/>\n",
+                    x, y, fill
+                ));
+            }
+            svg.push_str(&trigram_bars(point.trigram, x, y - 22.0));
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"14\">{} ({})</text>\n",
+                x,
+                y + 22.0,
+                point.trigram,
+                point.trigram.image()
+            ));
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"11\" fill=\"#555\">{}</text>\n",
+                x,
+                y + 37.0,
+                role.unwrap_or(&point.note)
+            ));
+
+            let (label_x, label_y) = (
+                middle + (SVG_RADIUS + 65.0) * radians.sin(),
+                middle - (SVG_RADIUS + 65.0) * radians.cos() + 5.0,
+            );
+            svg.push_str(&format!(
+                "<text x=\"{:.1}\" y=\"{:.1}\" font-size=\"13\" font-weight=\"bold\">{}</text>\n",
+                label_x,
+                label_y,
+                point.direction.abbreviation()
+            ));
+        }
+
+        if let Some((_, _, center)) = &self.reading {
+            svg.push_str(&format!(
+                "<text x=\"{middle}\" y=\"{:.1}\" font-size=\"28\">{}</text>\n",
+                middle + 10.0,
+                center,
+                middle = middle
+            ));
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+}
+
+/// A trigram's three lines as bars, bottom line lowest, centered on `x` with
+/// the top line at `y`
+fn trigram_bars(trigram: Trigram, x: f64, y: f64) -> String {
+    let (width, height, gap) = (44.0, 5.0, 9.0);
+    let mut bars = String::new();
+    for (i, bit) in (0..3).rev().map(|i| (trigram.bits() >> i) & 1).enumerate() {
+        let top = y + i as f64 * gap;
+        if bit == 1 {
+            bars.push_str(&format!(
+                "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{}\" height=\"{}\"/>\n",
+                x - width / 2.0,
+                top,
+                width,
+                height
+            ));
+        } else {
+            for left in [x - width / 2.0, x + 4.0] {
+                bars.push_str(&format!(
+                    "<rect x=\"{:.1}\" y=\"{:.1}\" width=\"{}\" height=\"{}\"/>\n",
+                    left,
+                    top,
+                    width / 2.0 - 4.0,
+                    height
+                ));
+            }
+        }
+    }
+    bars
+}
+
+/// `text` centered in `width` columns
+fn center(text: &str, width: usize) -> String {
+    let length = display_width(text);
+    let left = width.saturating_sub(length) / 2;
+    let right = width.saturating_sub(length + left);
+    format!("{}{}{}", " ".repeat(left), text, " ".repeat(right))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compass() {
+        let data = IChingData::load().unwrap();
+        let compass = Compass::new(&data, Arrangement::Later).unwrap();
+        let text = compass.to_text(Glyphs::Ascii);
+        let rows: Vec<&str> = text.lines().collect();
+        assert_eq!(rows[0], "Later Heaven bagua (south at top)");
+        // Xun, Li, and Kun across the top
+        assert_eq!(
+            rows[2].split_whitespace().collect::<Vec<_>>(),
+            ["SE", "S", "SW"]
+        );
+        assert_eq!(
+            rows[3].split_whitespace().collect::<Vec<_>>(),
+            ["Xun", "Li", "Kun"]
+        );
+        assert!(!text.contains('['));
+
+        // Water over fire: Kan above, Li below
+        let reading: Reading = "63".parse().unwrap();
+        let compass = Compass::new(&data, Arrangement::Earlier)
+            .unwrap()
+            .with_reading(&reading, &data, Glyphs::Ascii);
+        let text = compass.to_text(Glyphs::Ascii);
+        assert!(text.starts_with("Earlier Heaven bagua"));
+        // In the Earlier Heaven order Li is east and Kan west
+        let middle = text.lines().find(|row| row.contains("[Li]")).unwrap();
+        assert!(middle.find("[Li]") < middle.find("[Kan]"));
+        assert!(text.contains("upper") && text.contains("lower"));
+
+        let svg = compass.to_svg();
+        assert!(svg.starts_with("<svg "));
+        assert!(svg.ends_with("</svg>\n"));
+        assert_eq!(svg.matches("fill=\"#fde68a\"").count(), 1);
+        assert_eq!(svg.matches("fill=\"#bfdbfe\"").count(), 1);
+    }
+}
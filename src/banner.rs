@@ -0,0 +1,249 @@
+use crate::core::Reading;
+use crate::core::reading::{Age, Polarity};
+use crate::glyphs::Glyphs;
+use std::env;
+
+/// Terminal width assumed when `$COLUMNS` isn't set
+pub const DEFAULT_WIDTH: usize = 80;
+
+/// Rows in a big letter
+pub(crate) const LETTER_HEIGHT: usize = 5;
+
+/// Columns between the figure and the title when they sit side by side
+const GUTTER: usize = 4;
+
+/// Width to lay a banner out in: `$COLUMNS`, or 80
+pub fn terminal_width() -> usize {
+    env::var("COLUMNS")
+        .ok()
+        .and_then(|columns| columns.parse().ok())
+        .filter(|&columns| columns > 0)
+        .unwrap_or(DEFAULT_WIDTH)
+}
+
+/// A large hexagram figure with its title in block letters
+///
+/// The title sits beside the figure when both fit in `width`, below it when
+/// only the title fits (wrapped at word boundaries), and falls back to plain
+/// text when even one word is too wide for big letters.
+pub fn render(reading: &Reading, title: &str, glyphs: Glyphs, width: usize) -> String {
+    let figure = big_figure(reading, glyphs);
+    let figure_width = figure
+        .iter()
+        .map(|row| row.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let beside = width.saturating_sub(figure_width + GUTTER);
+    let rows = match big_title(title, glyphs, beside) {
+        Some(title) => side_by_side(&figure, figure_width, &title),
+        None => {
+            let mut rows = figure;
+            rows.push(String::new());
+            rows.extend(
+                big_title(title, glyphs, width).unwrap_or_else(|| vec![title.to_uppercase()]),
+            );
+            rows
+        }
+    };
+
+    rows.iter()
+        .map(|row| row.trim_end())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The six lines drawn in blocks, top line first, changing lines marked
+fn big_figure(reading: &Reading, glyphs: Glyphs) -> Vec<String> {
+    let block = block_char(glyphs).to_string();
+    let mut rows = Vec::new();
+
+    for line in reading.lines().iter().rev() {
+        let bar = match line.polarity {
+            Polarity::Yang => block.repeat(13),
+            Polarity::Yin => format!("{}   {}", block.repeat(5), block.repeat(5)),
+        };
+        let mark = match (line.age, line.polarity) {
+            (Age::Old, Polarity::Yang) => " o",
+            (Age::Old, Polarity::Yin) => " x",
+            (Age::Young, _) => "",
+        };
+        if !rows.is_empty() {
+            rows.push(String::new());
+        }
+        rows.push(format!("{}{}", bar, mark));
+    }
+
+    rows
+}
+
+/// Figure on the left, title centred vertically on the right
+fn side_by_side(figure: &[String], figure_width: usize, title: &[String]) -> Vec<String> {
+    let height = figure.len().max(title.len());
+    let offset = (height - title.len()) / 2;
+
+    (0..height)
+        .map(|row| {
+            let left = figure.get(row).map(String::as_str).unwrap_or("");
+            let right = row
+                .checked_sub(offset)
+                .and_then(|row| title.get(row))
+                .map(String::as_str)
+                .unwrap_or("");
+            let padding = figure_width + GUTTER - left.chars().count();
+            format!("{}{}{}", left, " ".repeat(padding), right)
+        })
+        .collect()
+}
+
+/// The title in block letters wrapped to `width`, or `None` if a word won't fit
+/// or has a character the font lacks
+fn big_title(title: &str, glyphs: Glyphs, width: usize) -> Option<Vec<String>> {
+    let words: Vec<String> = title.split_whitespace().map(str::to_uppercase).collect();
+    let mut lines: Vec<Vec<&str>> = Vec::new();
+
+    for word in &words {
+        if big_width(word)? > width {
+            return None;
+        }
+        match lines.last_mut() {
+            Some(line) if big_width(&format!("{} {}", line.join(" "), word))? <= width => {
+                line.push(word)
+            }
+            _ => lines.push(vec![word]),
+        }
+    }
+
+    let block = block_char(glyphs);
+    let mut rows = Vec::new();
+    for line in lines {
+        if !rows.is_empty() {
+            rows.push(String::new());
+        }
+        rows.extend(big_text(&line.join(" "), block)?);
+    }
+
+    Some(rows)
+}
+
+/// Columns `text` takes in block letters
+fn big_width(text: &str) -> Option<usize> {
+    let letters = text
+        .chars()
+        .map(|c| letter(c).map(|rows| rows[0].len()))
+        .collect::<Option<Vec<_>>>()?;
+    Some(letters.iter().sum::<usize>() + letters.len().saturating_sub(1))
+}
+
+/// One line of text in block letters, a space between letters
+fn big_text(text: &str, block: char) -> Option<Vec<String>> {
+    let letters = text.chars().map(letter).collect::<Option<Vec<_>>>()?;
+
+    Some(
+        (0..LETTER_HEIGHT)
+            .map(|row| {
+                letters
+                    .iter()
+                    .map(|letter| letter[row].replace('#', &block.to_string()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            })
+            .collect(),
+    )
+}
+
+fn block_char(glyphs: Glyphs) -> char {
+    match glyphs {
+        Glyphs::Unicode => '█',
+        Glyphs::Ascii => '#',
+    }
+}
+
+/// A 5x5 block letter (narrower for a space and punctuation), or `None` for
+/// characters the font lacks
+pub(crate) fn letter(c: char) -> Option<[&'static str; LETTER_HEIGHT]> {
+    Some(match c {
+        'A' => [" ### ", "#   #", "#####", "#   #", "#   #"],
+        'B' => ["#### ", "#   #", "#### ", "#   #", "#### "],
+        'C' => [" ####", "#    ", "#    ", "#    ", " ####"],
+        'D' => ["#### ", "#   #", "#   #", "#   #", "#### "],
+        'E' => ["#####", "#    ", "#### ", "#    ", "#####"],
+        'F' => ["#####", "#    ", "#### ", "#    ", "#    "],
+        'G' => [" ####", "#    ", "#  ##", "#   #", " ####"],
+        'H' => ["#   #", "#   #", "#####", "#   #", "#   #"],
+        'I' => ["#####", "  #  ", "  #  ", "  #  ", "#####"],
+        'J' => ["#####", "   # ", "   # ", "#  # ", " ##  "],
+        'K' => ["#   #", "#  # ", "###  ", "#  # ", "#   #"],
+        'L' => ["#    ", "#    ", "#    ", "#    ", "#####"],
+        'M' => ["#   #", "## ##", "# # #", "#   #", "#   #"],
+        'N' => ["#   #", "##  #", "# # #", "#  ##", "#   #"],
+        'O' => [" ### ", "#   #", "#   #", "#   #", " ### "],
+        'P' => ["#### ", "#   #", "#### ", "#    ", "#    "],
+        'Q' => [" ### ", "#   #", "# # #", "#  # ", " ## #"],
+        'R' => ["#### ", "#   #", "#### ", "#  # ", "#   #"],
+        'S' => [" ####", "#    ", " ### ", "    #", "#### "],
+        'T' => ["#####", "  #  ", "  #  ", "  #  ", "  #  "],
+        'U' => ["#   #", "#   #", "#   #", "#   #", " ### "],
+        'V' => ["#   #", "#   #", "#   #", " # # ", "  #  "],
+        'W' => ["#   #", "#   #", "# # #", "## ##", "#   #"],
+        'X' => ["#   #", " # # ", "  #  ", " # # ", "#   #"],
+        'Y' => ["#   #", " # # ", "  #  ", "  #  ", "  #  "],
+        'Z' => ["#####", "   # ", "  #  ", " #   ", "#####"],
+        '0' => [" ### ", "#  ##", "# # #", "##  #", " ### "],
+        '1' => ["  #  ", " ##  ", "  #  ", "  #  ", " ### "],
+        '2' => [" ### ", "#   #", "  ## ", " #   ", "#####"],
+        '3' => ["#### ", "    #", " ### ", "    #", "#### "],
+        '4' => ["#   #", "#   #", "#####", "    #", "    #"],
+        '5' => ["#####", "#    ", "#### ", "    #", "#### "],
+        '6' => [" ### ", "#    ", "#### ", "#   #", " ### "],
+        '7' => ["#####", "    #", "   # ", "  #  ", "  #  "],
+        '8' => [" ### ", "#   #", " ### ", "#   #", " ### "],
+        '9' => [" ### ", "#   #", " ####", "    #", " ### "],
+        ' ' => ["   ", "   ", "   ", "   ", "   "],
+        '.' => [" ", " ", " ", " ", "#"],
+        ',' => ["  ", "  ", "  ", " #", "# "],
+        ':' => [" ", "#", " ", "#", " "],
+        ';' => ["  ", " #", "  ", " #", "# "],
+        '-' => ["   ", "   ", "###", "   ", "   "],
+        '\'' => ["#", "#", " ", " ", " "],
+        '!' => ["#", "#", "#", " ", "#"],
+        '?' => ["### ", "   #", " ## ", "    ", " #  "],
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widest(banner: &str) -> usize {
+        banner.lines().map(|row| row.chars().count()).max().unwrap()
+    }
+
+    #[test]
+    fn test_layouts() {
+        let reading: Reading = "9,8,8,8,8,8".parse().unwrap();
+
+        // Wide enough to sit the title beside the figure
+        let wide = render(&reading, "24 Returning", Glyphs::Ascii, 120);
+        assert_eq!(wide.lines().count(), 11);
+        assert!(wide.lines().last().unwrap().starts_with("############# o"));
+        assert!(widest(&wide) <= 120);
+
+        // Narrower: title below the figure, wrapped a word at a time
+        let narrow = render(&reading, "24 Returning", Glyphs::Ascii, 60);
+        assert_eq!(narrow.lines().count(), 11 + 1 + 5 + 1 + 5);
+        assert!(widest(&narrow) <= 60);
+
+        // Too narrow for big letters at all
+        let tiny = render(&reading, "24 Returning", Glyphs::Ascii, 20);
+        assert!(tiny.ends_with("\n24 RETURNING"));
+    }
+
+    #[test]
+    fn test_glyphs() {
+        let reading: Reading = "1".parse().unwrap();
+        assert!(render(&reading, "1 Initiating", Glyphs::Ascii, 80).is_ascii());
+        assert!(render(&reading, "1 Initiating", Glyphs::Unicode, 80).contains('█'));
+    }
+}
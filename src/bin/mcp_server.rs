@@ -135,7 +135,7 @@ async fn handle_cast_hexagram(params: Value, data: &Arc<IChingData>) -> JsonRpcR
                 .as_u64()
                 .ok_or_else(|| JsonRpcError::invalid_params("Line values must be numbers"))?;
 
-            if num < 6 || num > 9 {
+            if !(6..=9).contains(&num) {
                 return Err(JsonRpcError::invalid_params(
                     "Line values must be 6, 7, 8, or 9",
                 ));
@@ -151,13 +151,11 @@ async fn handle_cast_hexagram(params: Value, data: &Arc<IChingData>) -> JsonRpcR
         diviner.cast_reading(None)
     };
 
-    let transformed = reading.transformed_hexagram();
-
     Ok(json!({
         "content": [
             {
                 "type": "text",
-                "text": format_reading_for_goose(&reading, transformed.as_ref(), data)
+                "text": format_reading_for_goose(&reading, data)
             }
         ]
     }))
@@ -170,7 +168,7 @@ async fn handle_interpret_reading(params: Value, data: &Arc<IChingData>) -> Json
         .as_u64()
         .ok_or_else(|| JsonRpcError::invalid_params("Missing hexagram number"))?;
 
-    if hexagram_num < 1 || hexagram_num > 64 {
+    if !(1..=64).contains(&hexagram_num) {
         return Err(JsonRpcError::invalid_params(
             "Hexagram number must be between 1 and 64",
         ));
@@ -204,11 +202,7 @@ async fn handle_interpret_reading(params: Value, data: &Arc<IChingData>) -> Json
     }))
 }
 
-fn format_reading_for_goose(
-    reading: &Reading,
-    transformed: Option<&Reading>,
-    data: &Arc<IChingData>,
-) -> String {
+fn format_reading_for_goose(reading: &Reading, data: &Arc<IChingData>) -> String {
     let mut result = String::new();
 
     let hexagram_num = reading.primary_hexagram();
@@ -221,7 +215,7 @@ fn format_reading_for_goose(
         result.push_str(&format!("**Pinyin:** {}\n\n", hexagram.pinyin));
 
         // Add trigram information
-        if let Some(upper_trigram) = data.get_trigram(&hexagram.upper_trigram) {
+        if let Some(upper_trigram) = data.get_trigram(hexagram.upper_trigram) {
             result.push_str(&format!(
                 "**Upper Trigram:** {} {} - {} ({})\n",
                 upper_trigram.unicode,
@@ -230,7 +224,7 @@ fn format_reading_for_goose(
                 upper_trigram.element
             ));
         }
-        if let Some(lower_trigram) = data.get_trigram(&hexagram.lower_trigram) {
+        if let Some(lower_trigram) = data.get_trigram(hexagram.lower_trigram) {
             result.push_str(&format!(
                 "**Lower Trigram:** {} {} - {} ({})\n\n",
                 lower_trigram.unicode,
@@ -245,7 +239,7 @@ fn format_reading_for_goose(
 
     // Visual representation
     result.push_str("**Lines:**\n```\n");
-    for (i, line) in reading.lines.iter().enumerate().rev() {
+    for (i, line) in reading.lines().iter().enumerate().rev() {
         result.push_str(&format!("{}: {}\n", i + 1, line.to_symbol()));
     }
     result.push_str("```\n\n");
@@ -256,8 +250,7 @@ fn format_reading_for_goose(
             reading.changing_line_positions()
         ));
 
-        if let Some(transformed) = transformed {
-            let transformed_num = transformed.primary_hexagram();
+        if let Some(transformed_num) = reading.transformed_hexagram_number() {
             result.push_str(&format!("**Transforms to Hexagram:** {}", transformed_num));
             if let Some(transformed_hex) = data.get_hexagram(transformed_num) {
                 result.push_str(&format!(
@@ -301,7 +294,7 @@ fn format_interpretation(
         ));
 
         // Add trigram information
-        if let Some(upper_trigram) = data.get_trigram(&hex_data.upper_trigram) {
+        if let Some(upper_trigram) = data.get_trigram(hex_data.upper_trigram) {
             result.push_str(&format!(
                 "**Upper Trigram:** {} {} - {} ({})\n",
                 upper_trigram.unicode,
@@ -310,7 +303,7 @@ fn format_interpretation(
                 upper_trigram.element
             ));
         }
-        if let Some(lower_trigram) = data.get_trigram(&hex_data.lower_trigram) {
+        if let Some(lower_trigram) = data.get_trigram(hex_data.lower_trigram) {
             result.push_str(&format!(
                 "**Lower Trigram:** {} {} - {} ({})\n\n",
                 lower_trigram.unicode,
@@ -359,9 +352,7 @@ fn format_interpretation(
         if let Some(transformed_num) = transformed_hexagram {
             if let Some(transformed_hex) = data.get_hexagram(transformed_num) {
                 result.push_str("## Transformation\n\n");
-                result.push_str(&format!(
-                    "When the changing lines transform, this hexagram becomes:\n\n"
-                ));
+                result.push_str("When the changing lines transform, this hexagram becomes:\n\n");
                 result.push_str(&format!(
                     "**Hexagram {} - {} ({})**\n\n",
                     transformed_num, transformed_hex.name, transformed_hex.chinese
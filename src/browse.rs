@@ -0,0 +1,465 @@
+//! `i-ching browse`: a full-screen browser of the 64 hexagrams and 8
+//! trigrams
+//!
+//! The list on the left narrows to the entries holding every word of the
+//! filter, and to the hexagrams built on one trigram; the pane on the right
+//! shows the selected entry's texts. [`Browser`] holds the state and lays
+//! out each screen as plain rows, so only [`run`] touches the terminal.
+
+use crate::format::{format_trigram, format_trigram_discussion};
+use crate::core::data::{Hexagram, IChingData, TrigramData};
+use crate::core::trigram::Trigram;
+use crate::format::{display_width, pad};
+use crate::glyphs::Glyphs;
+use crate::translations::wrap;
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use crossterm::style::{Attribute, Print, SetAttribute};
+use crossterm::{cursor, queue, terminal};
+use std::io::{self, IsTerminal, Write};
+use unicode_width::UnicodeWidthChar;
+
+/// Columns of the list pane, before the divider
+const LIST_WIDTH: usize = 30;
+
+const HELP: &str = "j/k move  / filter  t trigram  J/K scroll  Esc clear  q quit";
+
+/// A row of the list
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Entry {
+    Hexagram(u8),
+    Trigram(Trigram),
+}
+
+/// What the browser shows: the filters, the entries they leave, and where
+/// the selection and the detail pane stand
+#[derive(Debug, Clone)]
+pub struct Browser {
+    /// Words that must each appear in an entry's names or texts, ignoring
+    /// case
+    filter: String,
+    /// Only the hexagrams with this trigram above or below, and the trigram
+    trigram: Option<Trigram>,
+    /// Whether keys are being typed into the filter
+    editing: bool,
+    visible: Vec<Entry>,
+    selected: usize,
+    /// First line of the detail pane in view
+    scroll: usize,
+}
+
+impl Browser {
+    pub fn new(data: &IChingData) -> Self {
+        let mut browser = Browser {
+            filter: String::new(),
+            trigram: None,
+            editing: false,
+            visible: Vec::new(),
+            selected: 0,
+            scroll: 0,
+        };
+        browser.refilter(data);
+        browser
+    }
+
+    /// Entries the filters leave, hexagrams in King Wen order then trigrams
+    pub fn visible(&self) -> &[Entry] {
+        &self.visible
+    }
+
+    pub fn selected(&self) -> Option<Entry> {
+        self.visible.get(self.selected).copied()
+    }
+
+    pub fn set_filter(&mut self, filter: &str, data: &IChingData) {
+        self.filter = filter.to_string();
+        self.refilter(data);
+    }
+
+    /// Show only `trigram` and the hexagrams built on it, or everything
+    pub fn set_trigram(&mut self, trigram: Option<Trigram>, data: &IChingData) {
+        self.trigram = trigram;
+        self.refilter(data);
+    }
+
+    /// Step the trigram filter through the eight trigrams and back to none
+    pub fn next_trigram(&mut self, data: &IChingData) {
+        let next = match self.trigram {
+            None => Some(Trigram::ALL[0]),
+            Some(trigram) => Trigram::ALL
+                .iter()
+                .position(|&t| t == trigram)
+                .and_then(|i| Trigram::ALL.get(i + 1))
+                .copied(),
+        };
+        self.set_trigram(next, data);
+    }
+
+    /// Move the selection by `delta` rows, stopping at either end
+    pub fn move_by(&mut self, delta: isize) {
+        let last = self.visible.len().saturating_sub(1);
+        self.selected = self.selected.saturating_add_signed(delta).min(last);
+        self.scroll = 0;
+    }
+
+    /// Apply one key press; false once the browser should close
+    pub fn handle_key(&mut self, key: KeyEvent, data: &IChingData, page: usize) -> bool {
+        let page = page.max(1) as isize;
+        if key.modifiers.contains(KeyModifiers::CONTROL) && key.code == KeyCode::Char('c') {
+            return false;
+        }
+        if self.editing {
+            match key.code {
+                KeyCode::Enter | KeyCode::Esc => self.editing = false,
+                KeyCode::Backspace => {
+                    let mut filter = self.filter.clone();
+                    filter.pop();
+                    self.set_filter(&filter, data);
+                }
+                KeyCode::Char(c) => {
+                    let filter = format!("{}{}", self.filter, c);
+                    self.set_filter(&filter, data);
+                }
+                _ => {}
+            }
+            return true;
+        }
+        match key.code {
+            KeyCode::Char('q') => return false,
+            KeyCode::Char('/') => self.editing = true,
+            KeyCode::Char('t') => self.next_trigram(data),
+            KeyCode::Esc => {
+                self.filter.clear();
+                self.set_trigram(None, data);
+            }
+            KeyCode::Enter => {
+                if let Some(Entry::Trigram(trigram)) = self.selected() {
+                    self.set_trigram(Some(trigram), data);
+                }
+            }
+            KeyCode::Down | KeyCode::Char('j') => self.move_by(1),
+            KeyCode::Up | KeyCode::Char('k') => self.move_by(-1),
+            KeyCode::PageDown => self.move_by(page),
+            KeyCode::PageUp => self.move_by(-page),
+            KeyCode::Home | KeyCode::Char('g') => self.move_by(isize::MIN),
+            KeyCode::End | KeyCode::Char('G') => self.move_by(isize::MAX),
+            KeyCode::Char('J') => self.scroll += 1,
+            KeyCode::Char('K') => self.scroll = self.scroll.saturating_sub(1),
+            _ => {}
+        }
+        true
+    }
+
+    /// The screen as `height` rows of `width` columns: a status row, the
+    /// list and the detail pane side by side, and a row of help or the
+    /// filter being typed
+    ///
+    /// Scrolling stops once the end of the detail is in view.
+    pub fn rows(
+        &mut self,
+        data: &IChingData,
+        glyphs: Glyphs,
+        width: usize,
+        height: usize,
+    ) -> Vec<String> {
+        let body = height.saturating_sub(2);
+        let detail_width = width.saturating_sub(LIST_WIDTH + 3).max(1);
+        let offset = (self.selected + 1).saturating_sub(body);
+        let detail = self
+            .selected()
+            .map(|entry| wrap(&detail(entry, data, glyphs), detail_width))
+            .unwrap_or_default();
+        self.scroll = self.scroll.min(detail.len().saturating_sub(body));
+        let scroll = self.scroll;
+        let divider = match glyphs {
+            Glyphs::Unicode => "│",
+            Glyphs::Ascii => "|",
+        };
+
+        let mut rows = vec![fit(&self.status(), width)];
+        for row in 0..body {
+            let index = offset + row;
+            let item = match self.visible.get(index) {
+                Some(&entry) => format!(
+                    "{}{}",
+                    if index == self.selected { "> " } else { "  " },
+                    label(entry, data, glyphs)
+                ),
+                None => String::new(),
+            };
+            let text = detail.get(scroll + row).map_or("", String::as_str);
+            rows.push(fit(
+                &format!("{} {} {}", fit(&item, LIST_WIDTH), divider, text),
+                width,
+            ));
+        }
+        let footer = if self.editing {
+            format!("/{}", self.filter)
+        } else {
+            HELP.to_string()
+        };
+        rows.push(fit(&footer, width));
+        rows
+    }
+
+    /// Counts and filters, e.g. "12 of 72  trigram: Kan  filter: danger"
+    fn status(&self) -> String {
+        let mut status = format!("{} of 72", self.visible.len());
+        if let Some(trigram) = self.trigram {
+            status.push_str(&format!("  trigram: {}", trigram));
+        }
+        if !self.filter.is_empty() {
+            status.push_str(&format!("  filter: {}", self.filter));
+        }
+        status
+    }
+
+    fn refilter(&mut self, data: &IChingData) {
+        let words: Vec<String> = self
+            .filter
+            .split_whitespace()
+            .map(str::to_lowercase)
+            .collect();
+        let matches = |text: String| {
+            let text = text.to_lowercase();
+            words.iter().all(|word| text.contains(word.as_str()))
+        };
+        let hexagrams = data
+            .hexagrams()
+            .filter(|hexagram| {
+                self.trigram.is_none_or(|trigram| {
+                    hexagram.upper_trigram == trigram || hexagram.lower_trigram == trigram
+                })
+            })
+            .filter(|hexagram| matches(hexagram_text(hexagram)))
+            .map(|hexagram| Entry::Hexagram(hexagram.number));
+        let trigrams = Trigram::ALL
+            .into_iter()
+            .filter(|&trigram| self.trigram.is_none_or(|t| t == trigram))
+            .filter(|&trigram| matches(trigram_text(trigram, data.get_trigram(trigram))))
+            .map(Entry::Trigram);
+        let previous = self.selected();
+        self.visible = hexagrams.chain(trigrams).collect();
+        self.selected = previous
+            .and_then(|entry| self.visible.iter().position(|&e| e == entry))
+            .unwrap_or(0);
+        self.scroll = 0;
+    }
+}
+
+/// Run the browser until `q` or Ctrl-C
+pub fn run(data: &IChingData, glyphs: Glyphs) -> Result<()> {
+    let mut stdout = io::stdout();
+    if !stdout.is_terminal() {
+        return Err(anyhow::anyhow!("browse needs a terminal"));
+    }
+    let _screen = Screen::enter(&mut stdout)?;
+    let mut browser = Browser::new(data);
+    loop {
+        let (width, height) = terminal::size().context("Failed to read the terminal size")?;
+        let (width, height) = (usize::from(width), usize::from(height));
+        queue!(stdout, terminal::Clear(terminal::ClearType::All))?;
+        for (y, row) in browser.rows(data, glyphs, width, height).iter().enumerate() {
+            queue!(stdout, cursor::MoveTo(0, y as u16))?;
+            // The status row stands out from the rest
+            if y == 0 {
+                queue!(
+                    stdout,
+                    SetAttribute(Attribute::Reverse),
+                    Print(row),
+                    SetAttribute(Attribute::Reset)
+                )?;
+            } else {
+                queue!(stdout, Print(row))?;
+            }
+        }
+        stdout.flush()?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind == KeyEventKind::Press
+            && !browser.handle_key(key, data, height.saturating_sub(2))
+        {
+            return Ok(());
+        }
+    }
+}
+
+/// Raw mode on the alternate screen, left again when dropped
+struct Screen;
+
+impl Screen {
+    fn enter(stdout: &mut io::Stdout) -> Result<Self> {
+        terminal::enable_raw_mode().context("Failed to put the terminal in raw mode")?;
+        queue!(stdout, terminal::EnterAlternateScreen, cursor::Hide)?;
+        Ok(Screen)
+    }
+}
+
+impl Drop for Screen {
+    fn drop(&mut self) {
+        let mut stdout = io::stdout();
+        let _ = queue!(stdout, cursor::Show, terminal::LeaveAlternateScreen);
+        let _ = stdout.flush();
+        let _ = terminal::disable_raw_mode();
+    }
+}
+
+/// An entry as the list shows it, e.g. "䷟ 32 Long Lasting" or "☵ Kan (Water)"
+fn label(entry: Entry, data: &IChingData, glyphs: Glyphs) -> String {
+    match entry {
+        Entry::Hexagram(number) => data.get_hexagram(number).map_or_else(
+            || number.to_string(),
+            |hexagram| {
+                format!(
+                    "{}{:>2} {}",
+                    glyphs.hexagram_prefix(&hexagram.unicode),
+                    number,
+                    hexagram.name
+                )
+            },
+        ),
+        Entry::Trigram(trigram) => format_trigram(trigram, glyphs).to_string(),
+    }
+}
+
+/// The detail pane's text for an entry, before wrapping
+fn detail(entry: Entry, data: &IChingData, glyphs: Glyphs) -> String {
+    match entry {
+        Entry::Hexagram(number) => data
+            .get_hexagram(number)
+            .map(|hexagram| hexagram_detail(hexagram, glyphs))
+            .unwrap_or_default(),
+        Entry::Trigram(trigram) => {
+            let mut text = format!("{}\n", format_trigram(trigram, glyphs));
+            if let Some(trigram_data) = data.get_trigram(trigram) {
+                text.push_str(&format!(
+                    "{} {}, {}\nElement: {}\nAttribute: {}\n",
+                    trigram_data.chinese,
+                    trigram_data.name,
+                    trigram_data.symbolic,
+                    trigram_data.element,
+                    trigram_data.attribute
+                ));
+                if let Some(discussion) = &trigram_data.discussion {
+                    text.push_str(&format!("\n{}\n", format_trigram_discussion(discussion)));
+                }
+            }
+            let hexagrams = data
+                .hexagrams()
+                .filter(|h| h.upper_trigram == trigram || h.lower_trigram == trigram)
+                .count();
+            text.push_str(&format!(
+                "\nIn {} hexagrams; press Enter to list them.\n",
+                hexagrams
+            ));
+            text
+        }
+    }
+}
+
+fn hexagram_detail(hexagram: &Hexagram, glyphs: Glyphs) -> String {
+    let mut text = format!(
+        "{}{} {} ({} {})\n{} over {}\n\n{}\n\nJudgment: {}\n\nImage: {}\n",
+        glyphs.hexagram_prefix(&hexagram.unicode),
+        hexagram.number,
+        hexagram.name,
+        hexagram.chinese,
+        hexagram.pinyin,
+        format_trigram(hexagram.upper_trigram, glyphs),
+        format_trigram(hexagram.lower_trigram, glyphs),
+        hexagram.description,
+        hexagram.judgment.text,
+        hexagram.image.text
+    );
+    for position in 1..=6 {
+        if let Some(line) = hexagram.lines.get(&position.to_string()) {
+            text.push_str(&format!("\nLine {}: {}\n", position, line.text));
+        }
+    }
+    text
+}
+
+/// What a hexagram's filter words are looked for in
+fn hexagram_text(hexagram: &Hexagram) -> String {
+    [
+        &hexagram.number.to_string(),
+        hexagram.name.as_str(),
+        &hexagram.chinese,
+        &hexagram.pinyin,
+        &hexagram.description,
+        &hexagram.judgment.text,
+        &hexagram.image.text,
+    ]
+    .join(" ")
+}
+
+/// What a trigram's filter words are looked for in
+fn trigram_text(trigram: Trigram, data: Option<&TrigramData>) -> String {
+    let mut text = format!("{} {}", trigram, trigram.image());
+    if let Some(data) = data {
+        text.push_str(&format!(
+            " {} {} {} {}",
+            data.chinese, data.symbolic, data.element, data.attribute
+        ));
+    }
+    text
+}
+
+/// `text` cut or padded to exactly `width` columns
+fn fit(text: &str, width: usize) -> String {
+    if display_width(text) <= width {
+        return pad(text, width);
+    }
+    let mut fitted = String::new();
+    let mut used = 0;
+    for c in text.chars() {
+        let columns = c.width().unwrap_or(0);
+        if used + columns > width {
+            break;
+        }
+        fitted.push(c);
+        used += columns;
+    }
+    pad(&fitted, width)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_browser() {
+        let data = IChingData::load().unwrap();
+        let mut browser = Browser::new(&data);
+        assert_eq!(browser.visible().len(), 72);
+        assert_eq!(browser.selected(), Some(Entry::Hexagram(1)));
+
+        browser.set_trigram(Some(Trigram::Kan), &data);
+        // 15 hexagrams hold Kan above or below, and the trigram itself
+        assert_eq!(browser.visible().len(), 16);
+        assert_eq!(
+            browser.visible().last(),
+            Some(&Entry::Trigram(Trigram::Kan))
+        );
+
+        browser.set_trigram(None, &data);
+        browser.set_filter("long LASTING", &data);
+        assert_eq!(browser.visible(), [Entry::Hexagram(32)]);
+        browser.set_filter("", &data);
+        // The selection stays on the same entry as the list regrows
+        assert_eq!(browser.selected(), Some(Entry::Hexagram(32)));
+
+        browser.move_by(isize::MAX);
+        assert_eq!(browser.selected(), Some(Entry::Trigram(Trigram::ALL[7])));
+        browser.move_by(-100);
+        assert_eq!(browser.selected(), Some(Entry::Hexagram(1)));
+
+        let rows = browser.rows(&data, Glyphs::Ascii, 80, 10);
+        assert_eq!(rows.len(), 10);
+        assert!(rows.iter().all(|row| display_width(row) == 80));
+        assert!(rows[1].starts_with(">  1 Initiating"));
+        assert!(rows[1].contains(" | 1 Initiating ("));
+    }
+}
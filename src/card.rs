@@ -0,0 +1,445 @@
+//! Square share cards for `i-ching card`: the figure, its name, a line of the
+//! judgment, and the date, as SVG or PNG
+//!
+//! The PNG is drawn without a font library: its lettering is the block
+//! font of `--format banner`, so it comes out in capitals and drops any
+//! character that font lacks. The SVG keeps the text as written.
+
+use crate::banner;
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::core::reading::{Age, Polarity};
+use anyhow::{Context, Result};
+use flate2::Compression;
+use flate2::write::ZlibEncoder;
+use serde::Deserialize;
+use std::io::Write;
+use std::str::FromStr;
+
+/// Side of the card unless `--size` says otherwise, in pixels
+pub const DEFAULT_SIZE: u32 = 1080;
+
+/// Longest judgment excerpt, in characters
+const EXCERPT_LENGTH: usize = 48;
+
+/// Layout units across the card; positions below are in these
+const UNITS: f64 = 1000.0;
+
+/// A color written `#rrggbb`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Color(pub u8, pub u8, pub u8);
+
+impl FromStr for Color {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let hex = s.trim().trim_start_matches('#');
+        let channel = |i: usize| {
+            hex.get(i..i + 2)
+                .and_then(|digits| u8::from_str_radix(digits, 16).ok())
+        };
+        match (hex.len(), channel(0), channel(2), channel(4)) {
+            (6, Some(red), Some(green), Some(blue)) => Ok(Color(red, green, blue)),
+            _ => Err(anyhow::anyhow!("Invalid color '{}': expected #rrggbb", s)),
+        }
+    }
+}
+
+impl TryFrom<String> for Color {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self> {
+        s.parse()
+    }
+}
+
+impl std::fmt::Display for Color {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "#{:02x}{:02x}{:02x}", self.0, self.1, self.2)
+    }
+}
+
+/// Card colors, from `[card]` in config.toml and the command line
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Theme {
+    pub background: Color,
+    /// Lines and text
+    pub foreground: Color,
+    /// Changing lines and the date
+    pub accent: Color,
+}
+
+impl Default for Theme {
+    /// Ink on paper with a cinnabar accent
+    fn default() -> Self {
+        Self {
+            background: Color(0xfa, 0xf7, 0xf0),
+            foreground: Color(0x1f, 0x29, 0x33),
+            accent: Color(0xb2, 0x3a, 0x48),
+        }
+    }
+}
+
+/// Which theme color a shape is painted in
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Ink {
+    Foreground,
+    Accent,
+}
+
+/// Something drawn on the card, in layout units
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Bar {
+        x: f64,
+        y: f64,
+        width: f64,
+        height: f64,
+        ink: Ink,
+    },
+    /// A line of text centered across the card, `y` at its top
+    Text {
+        y: f64,
+        size: f64,
+        text: String,
+        ink: Ink,
+    },
+}
+
+/// What a card shows, laid out once for either image format
+pub struct Card {
+    shapes: Vec<Shape>,
+    theme: Theme,
+    /// Spoken description for the SVG's title
+    title: String,
+}
+
+impl Card {
+    pub fn new(
+        reading: &Reading,
+        data: &IChingData,
+        date: chrono::NaiveDate,
+        theme: Theme,
+    ) -> Self {
+        let number = reading.primary_hexagram();
+        let hexagram = data.get_hexagram(number);
+        let name = match hexagram {
+            Some(hexagram) => format!("{} {}", number, hexagram.name),
+            None => number.to_string(),
+        };
+
+        let mut shapes = Vec::new();
+        // The figure, top line first, changing lines in the accent color
+        let (left, width, height, gap) = (300.0, 400.0, 34.0, 22.0);
+        for (row, line) in reading.lines().iter().rev().enumerate() {
+            let y = 110.0 + row as f64 * (height + gap);
+            let ink = match line.age {
+                Age::Old => Ink::Accent,
+                Age::Young => Ink::Foreground,
+            };
+            if line.polarity == Polarity::Yang {
+                shapes.push(Shape::Bar {
+                    x: left,
+                    y,
+                    width,
+                    height,
+                    ink,
+                });
+            } else {
+                let half = (width - 50.0) / 2.0;
+                for x in [left, left + width - half] {
+                    shapes.push(Shape::Bar {
+                        x,
+                        y,
+                        width: half,
+                        height,
+                        ink,
+                    });
+                }
+            }
+        }
+
+        shapes.push(Shape::Text {
+            y: 510.0,
+            size: 70.0,
+            text: name.clone(),
+            ink: Ink::Foreground,
+        });
+        let mut y = 620.0;
+        if let Some(transformed) = reading.transformed_hexagram_number() {
+            let becoming = match data.get_hexagram(transformed) {
+                Some(hexagram) => format!("becoming {} {}", transformed, hexagram.name),
+                None => format!("becoming {}", transformed),
+            };
+            shapes.push(Shape::Text {
+                y,
+                size: 36.0,
+                text: becoming,
+                ink: Ink::Foreground,
+            });
+            y += 80.0;
+        }
+        if let Some(hexagram) = hexagram {
+            shapes.push(Shape::Text {
+                y,
+                size: 36.0,
+                text: excerpt(&hexagram.judgment.text),
+                ink: Ink::Foreground,
+            });
+        }
+        shapes.push(Shape::Text {
+            y: 880.0,
+            size: 30.0,
+            text: date.format("%Y-%m-%d").to_string(),
+            ink: Ink::Accent,
+        });
+
+        Self {
+            shapes,
+            theme,
+            title: format!("I Ching reading: {}", name),
+        }
+    }
+
+    fn color(&self, ink: Ink) -> Color {
+        match ink {
+            Ink::Foreground => self.theme.foreground,
+            Ink::Accent => self.theme.accent,
+        }
+    }
+
+    /// The card as an SVG image `size` pixels square
+    pub fn to_svg(&self, size: u32) -> String {
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" viewBox=\"0 0 {units} {units}\" width=\"{size}\" height=\"{size}\" font-family=\"Georgia, serif\" text-anchor=\"middle\">\n\
+             <title>{}</title>\n\
+             <rect width=\"{units}\" height=\"{units}\" fill=\"{}\"/>\n",
+            escape(&self.title),
+            self.theme.background,
+            units = UNITS,
+            size = size,
+        );
+        for shape in &self.shapes {
+            match shape {
+                Shape::Bar {
+                    x,
+                    y,
+                    width,
+                    height,
+                    ink,
+                } => svg.push_str(&format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" fill=\"{}\"/>\n",
+                    x,
+                    y,
+                    width,
+                    height,
+                    self.color(*ink)
+                )),
+                Shape::Text { y, size, text, ink } => svg.push_str(&format!(
+                    "<text x=\"{}\" y=\"{}\" font-size=\"{}\" fill=\"{}\">{}</text>\n",
+                    UNITS / 2.0,
+                    y + size * 0.8,
+                    size,
+                    self.color(*ink),
+                    escape(text)
+                )),
+            }
+        }
+        svg.push_str("</svg>\n");
+        svg
+    }
+
+    /// The card as a PNG image `size` pixels square
+    pub fn to_png(&self, size: u32) -> Result<Vec<u8>> {
+        let mut canvas = Canvas::new(size, self.theme.background);
+        let scale = f64::from(size) / UNITS;
+        for shape in &self.shapes {
+            match shape {
+                Shape::Bar {
+                    x,
+                    y,
+                    width,
+                    height,
+                    ink,
+                } => canvas.fill(
+                    x * scale,
+                    y * scale,
+                    width * scale,
+                    height * scale,
+                    self.color(*ink),
+                ),
+                Shape::Text { y, size, text, ink } => {
+                    canvas.letters(text, y * scale, size * scale, self.color(*ink))
+                }
+            }
+        }
+        canvas.encode()
+    }
+}
+
+/// The first line of a judgment, cut at a word if it runs long
+fn excerpt(judgment: &str) -> String {
+    let first = judgment.lines().next().unwrap_or_default().trim();
+    if first.chars().count() <= EXCERPT_LENGTH {
+        return first.to_string();
+    }
+    let mut cut = String::new();
+    for word in first.split_whitespace() {
+        if cut.chars().count() + word.chars().count() + 1 > EXCERPT_LENGTH - 3 {
+            break;
+        }
+        if !cut.is_empty() {
+            cut.push(' ');
+        }
+        cut.push_str(word);
+    }
+    format!("{}...", cut.trim_end_matches([',', ';', ':', '.']))
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+/// RGB pixels, row by row
+struct Canvas {
+    size: u32,
+    pixels: Vec<u8>,
+}
+
+impl Canvas {
+    fn new(size: u32, background: Color) -> Self {
+        let pixels = [background.0, background.1, background.2].repeat((size * size) as usize);
+        Self { size, pixels }
+    }
+
+    /// Paint a rectangle, clipped to the canvas
+    fn fill(&mut self, x: f64, y: f64, width: f64, height: f64, color: Color) {
+        let clip = |value: f64| (value.round().max(0.0) as u32).min(self.size);
+        let (left, right) = (clip(x), clip(x + width));
+        for row in clip(y)..clip(y + height) {
+            for column in left..right {
+                let i = ((row * self.size + column) * 3) as usize;
+                self.pixels[i..i + 3].copy_from_slice(&[color.0, color.1, color.2]);
+            }
+        }
+    }
+
+    /// A line of block letters `height` pixels tall, centered across the
+    /// canvas and shrunk to fit it
+    fn letters(&mut self, text: &str, top: f64, height: f64, color: Color) {
+        let glyphs: Vec<_> = text
+            .to_uppercase()
+            .chars()
+            .filter_map(banner::letter)
+            .collect();
+        let columns = glyphs.iter().map(|rows| rows[0].len() + 1).sum::<usize>();
+        let columns = columns.saturating_sub(1).max(1) as f64;
+        let margin = f64::from(self.size) * 0.06;
+        let cell = (height / banner::LETTER_HEIGHT as f64)
+            .min((f64::from(self.size) - 2.0 * margin) / columns)
+            .floor()
+            .max(1.0);
+
+        let mut x = (f64::from(self.size) - columns * cell) / 2.0;
+        for rows in glyphs {
+            for (row, pattern) in rows.iter().enumerate() {
+                for (column, pixel) in pattern.chars().enumerate() {
+                    if pixel == '#' {
+                        self.fill(
+                            x + column as f64 * cell,
+                            top + row as f64 * cell,
+                            cell,
+                            cell,
+                            color,
+                        );
+                    }
+                }
+            }
+            x += (rows[0].len() + 1) as f64 * cell;
+        }
+    }
+
+    /// A truecolor PNG with each row unfiltered
+    fn encode(&self) -> Result<Vec<u8>> {
+        let row_length = (self.size * 3) as usize;
+        let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+        for row in self.pixels.chunks(row_length) {
+            encoder.write_all(&[0])?;
+            encoder.write_all(row)?;
+        }
+        let compressed = encoder.finish().context("Failed to compress the image")?;
+
+        let mut header = Vec::with_capacity(13);
+        header.extend_from_slice(&self.size.to_be_bytes());
+        header.extend_from_slice(&self.size.to_be_bytes());
+        // 8 bits per channel, RGB, no interlacing
+        header.extend_from_slice(&[8, 2, 0, 0, 0]);
+
+        let mut png = b"\x89PNG\r\n\x1a\n".to_vec();
+        push_chunk(&mut png, b"IHDR", &header);
+        push_chunk(&mut png, b"IDAT", &compressed);
+        push_chunk(&mut png, b"IEND", &[]);
+        Ok(png)
+    }
+}
+
+fn push_chunk(png: &mut Vec<u8>, kind: &[u8; 4], data: &[u8]) {
+    let mut crc = flate2::Crc::new();
+    crc.update(kind);
+    crc.update(data);
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(kind);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc.sum().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use flate2::read::ZlibDecoder;
+    use std::io::Read;
+
+    #[test]
+    fn test_card() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "32->34".parse().unwrap();
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 10, 17).unwrap();
+        let theme = Theme {
+            background: "#000000".parse().unwrap(),
+            ..Theme::default()
+        };
+        assert!("#12345".parse::<Color>().is_err());
+        let card = Card::new(&reading, &data, date, theme);
+
+        let svg = card.to_svg(600);
+        assert!(svg.contains("width=\"600\""));
+        assert!(svg.contains(">32 Long Lasting</text>"));
+        assert!(svg.contains(">becoming 34 Great Strength</text>"));
+        assert!(svg.contains(">2026-10-17</text>"));
+        // Both halves of the changing first line, and the date, are in the accent color
+        assert_eq!(svg.matches("fill=\"#b23a48\"").count(), 2 + 1);
+
+        let png = card.to_png(200).unwrap();
+        assert!(png.starts_with(b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR\0\0\0\xc8\0\0\0\xc8"));
+        assert!(png.ends_with(b"IEND\xae\x42\x60\x82"));
+        let idat_length = u32::from_be_bytes(png[33..37].try_into().unwrap()) as usize;
+        let mut pixels = Vec::new();
+        ZlibDecoder::new(&png[41..41 + idat_length])
+            .read_to_end(&mut pixels)
+            .unwrap();
+        assert_eq!(pixels.len(), 200 * (1 + 200 * 3));
+        // Each row starts with its filter byte, then the black background
+        assert_eq!(pixels[..4], [0, 0, 0, 0]);
+
+        assert_eq!(
+            excerpt(
+                "Waiting. If you are sincere, you have light and success. Perseverance brings good fortune.\nMore"
+            ),
+            "Waiting. If you are sincere, you have light..."
+        );
+    }
+}
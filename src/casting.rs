@@ -0,0 +1,308 @@
+//! Ways of casting lines, each with its own odds, for `--method` and
+//! `i-ching stats`
+//!
+//! Every method turns chance into the line values 6 to 9, and all of them
+//! change a line a quarter of the time. Three coins give the two old lines
+//! evenly; yarrow stalks give an old yang three times as often as an old
+//! yin, and the two-coin and sixteen-token methods were made to match them.
+//! Marbles are drawn from one bag without being put back, so a reading's
+//! lines aren't independent: it holds at most one old yin and three old
+//! yang.
+
+use crate::core::Diviner;
+use crate::core::reading::{Line, Reading};
+use clap::ValueEnum;
+use rand::{Rng, RngCore};
+use rayon::prelude::*;
+
+/// Stalks in a yarrow cast, after the one set aside
+const YARROW_STALKS: u32 = 49;
+
+/// The sixteen tokens (or marbles): how many stand for a 6, 7, 8, and 9
+const TOKENS: [u8; 4] = [1, 5, 7, 3];
+
+/// A way of turning chance into the six lines of a reading
+pub trait CastingMethod: Send + Sync {
+    fn name(&self) -> &'static str;
+
+    /// Chance of a 6, 7, 8, and 9 on any one line
+    fn line_probabilities(&self) -> [f64; 4];
+
+    /// Chance of a reading having 0 to 6 changing lines
+    ///
+    /// By default each line changes on its own odds, so the count is
+    /// binomial.
+    fn changing_probabilities(&self) -> [f64; 7] {
+        let [old_yin, _, _, old_yang] = self.line_probabilities();
+        let p = old_yin + old_yang;
+        std::array::from_fn(|k| {
+            binomial(6, k as u32) * p.powi(k as i32) * (1.0 - p).powi(6 - k as i32)
+        })
+    }
+
+    /// Six lines, bottom first
+    fn cast_lines(&self, rng: &mut dyn RngCore) -> [Line; 6];
+
+    fn cast(&self, rng: &mut dyn RngCore, question: Option<String>) -> Reading {
+        Reading::new(self.cast_lines(rng), question)
+    }
+}
+
+/// Three coins for each line, heads counting 3 and tails 2
+pub struct ThreeCoins;
+
+impl CastingMethod for ThreeCoins {
+    fn name(&self) -> &'static str {
+        "Three coins"
+    }
+
+    fn line_probabilities(&self) -> [f64; 4] {
+        [1.0 / 8.0, 3.0 / 8.0, 3.0 / 8.0, 1.0 / 8.0]
+    }
+
+    fn cast_lines(&self, mut rng: &mut dyn RngCore) -> [Line; 6] {
+        std::array::from_fn(|_| Diviner::line_from_coins(Diviner::toss_coins_with(&mut rng)))
+    }
+}
+
+/// Forty-nine yarrow stalks, divided three times for each line
+pub struct YarrowStalks;
+
+impl CastingMethod for YarrowStalks {
+    fn name(&self) -> &'static str {
+        "Yarrow stalks"
+    }
+
+    fn line_probabilities(&self) -> [f64; 4] {
+        [1.0 / 16.0, 5.0 / 16.0, 7.0 / 16.0, 3.0 / 16.0]
+    }
+
+    fn cast_lines(&self, mut rng: &mut dyn RngCore) -> [Line; 6] {
+        std::array::from_fn(|_| yarrow_line(&mut rng))
+    }
+}
+
+/// Two coins tossed twice for each line, with the yarrow stalks' odds
+///
+/// The first toss sets the polarity: yang if the first coin is heads. The
+/// second says whether the line is old: after two heads, unless it's two
+/// tails; after tails then heads, only if it's two heads; after heads then
+/// tails, or two tails, never.
+pub struct TwoCoins;
+
+impl CastingMethod for TwoCoins {
+    fn name(&self) -> &'static str {
+        "Two coins"
+    }
+
+    fn line_probabilities(&self) -> [f64; 4] {
+        YarrowStalks.line_probabilities()
+    }
+
+    fn cast_lines(&self, rng: &mut dyn RngCore) -> [Line; 6] {
+        std::array::from_fn(|_| {
+            let first = (rng.random_bool(0.5), rng.random_bool(0.5));
+            let second = (rng.random_bool(0.5), rng.random_bool(0.5));
+            let number = match first {
+                (true, true) if second != (false, false) => 9,
+                (true, _) => 7,
+                (false, true) if second == (true, true) => 6,
+                (false, _) => 8,
+            };
+            line(number)
+        })
+    }
+}
+
+/// One of sixteen tokens drawn for each line and put back: one for a 6, five
+/// for a 7, seven for an 8, and three for a 9
+pub struct SixteenTokens;
+
+impl CastingMethod for SixteenTokens {
+    fn name(&self) -> &'static str {
+        "Sixteen tokens"
+    }
+
+    fn line_probabilities(&self) -> [f64; 4] {
+        TOKENS.map(|count| f64::from(count) / 16.0)
+    }
+
+    fn cast_lines(&self, rng: &mut dyn RngCore) -> [Line; 6] {
+        let bag = bag();
+        std::array::from_fn(|_| line(bag[rng.random_range(0..bag.len())]))
+    }
+}
+
+/// Six of the sixteen tokens drawn as marbles from a bag, one for each line,
+/// without putting them back
+pub struct Marbles;
+
+impl CastingMethod for Marbles {
+    fn name(&self) -> &'static str {
+        "Marbles"
+    }
+
+    fn line_probabilities(&self) -> [f64; 4] {
+        SixteenTokens.line_probabilities()
+    }
+
+    /// Hypergeometric: how many of the four old marbles are among the six
+    /// drawn
+    fn changing_probabilities(&self) -> [f64; 7] {
+        let old = u32::from(TOKENS[0] + TOKENS[3]);
+        std::array::from_fn(|k| {
+            let k = k as u32;
+            if k > old {
+                return 0.0;
+            }
+            binomial(old, k) * binomial(16 - old, 6 - k) / binomial(16, 6)
+        })
+    }
+
+    fn cast_lines(&self, rng: &mut dyn RngCore) -> [Line; 6] {
+        let mut bag = bag();
+        std::array::from_fn(|_| line(bag.swap_remove(rng.random_range(0..bag.len()))))
+    }
+}
+
+/// A method `--method` and `stats --method` can name
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Method {
+    /// Three coins for each line
+    Coins,
+    /// Forty-nine yarrow stalks, divided three times for each line
+    Yarrow,
+    /// Two coins tossed twice for each line, with the yarrow stalks' odds
+    TwoCoins,
+    /// One of sixteen tokens drawn for each line and put back, with the yarrow stalks' odds
+    Tokens,
+    /// Six of the sixteen tokens drawn as marbles without putting them back: at most one old yin
+    Marbles,
+}
+
+impl Method {
+    pub const ALL: [Method; 5] = [
+        Method::Coins,
+        Method::Yarrow,
+        Method::TwoCoins,
+        Method::Tokens,
+        Method::Marbles,
+    ];
+
+    pub fn casting(self) -> &'static dyn CastingMethod {
+        match self {
+            Method::Coins => &ThreeCoins,
+            Method::Yarrow => &YarrowStalks,
+            Method::TwoCoins => &TwoCoins,
+            Method::Tokens => &SixteenTokens,
+            Method::Marbles => &Marbles,
+        }
+    }
+
+    pub fn name(self) -> &'static str {
+        self.casting().name()
+    }
+
+    /// Chance of a 6, 7, 8, and 9
+    pub fn line_probabilities(self) -> [f64; 4] {
+        self.casting().line_probabilities()
+    }
+
+    /// Chance of a reading having 0 to 6 changing lines
+    pub fn changing_probabilities(self) -> [f64; 7] {
+        self.casting().changing_probabilities()
+    }
+
+    /// Cast one reading with the thread-local generator
+    pub fn cast(self, question: Option<String>) -> Reading {
+        self.casting().cast(&mut rand::rng(), question)
+    }
+
+    /// Cast `count` readings by this method, in parallel
+    pub fn simulate(self, count: usize) -> Vec<Reading> {
+        let casting = self.casting();
+        (0..count)
+            .into_par_iter()
+            .map_init(rand::rng, |rng, _| casting.cast(rng, None))
+            .collect()
+    }
+}
+
+/// One line by the yarrow stalk method: divide the stalks, set one aside,
+/// and count off the remainders by fours, three times over
+pub fn yarrow_line(rng: &mut impl Rng) -> Line {
+    let mut stalks = YARROW_STALKS;
+    for _ in 0..3 {
+        let left = rng.random_range(1..stalks);
+        let right = stalks - left - 1;
+        let remainder = |heap: u32| if heap % 4 == 0 { 4 } else { heap % 4 };
+        stalks -= 1 + remainder(left) + remainder(right);
+    }
+    // 24, 28, 32, or 36 stalks are left: 6, 7, 8, or 9
+    line((stalks / 4) as u8)
+}
+
+/// A reading cast with yarrow stalks
+pub fn cast_yarrow(rng: &mut impl Rng) -> Reading {
+    Reading::new(std::array::from_fn(|_| yarrow_line(rng)), None)
+}
+
+/// The line for a value of 6 to 9
+fn line(number: u8) -> Line {
+    Line::from_traditional_number(number).expect("methods only give 6 to 9")
+}
+
+/// The sixteen tokens' values
+fn bag() -> Vec<u8> {
+    (6..=9)
+        .zip(TOKENS)
+        .flat_map(|(number, count)| std::iter::repeat_n(number, usize::from(count)))
+        .collect()
+}
+
+fn binomial(n: u32, k: u32) -> f64 {
+    (0..k).fold(1.0, |acc, i| acc * f64::from(n - i) / f64::from(i + 1))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::Tally;
+
+    #[test]
+    fn test_methods() {
+        for method in Method::ALL {
+            let probabilities = method.line_probabilities();
+            assert!((probabilities.iter().sum::<f64>() - 1.0).abs() < 1e-12);
+            // Every method changes a line a quarter of the time
+            assert!((probabilities[0] + probabilities[3] - 0.25).abs() < 1e-12);
+
+            let tally = Tally::new(&method.simulate(8000));
+            for (i, p) in probabilities.iter().enumerate() {
+                let share = tally.lines[i] as f64 / 48000.0;
+                assert!((share - p).abs() < 0.015, "{}: {:?}", method.name(), tally);
+            }
+        }
+
+        let [none, one, .., four, five, six] = Method::Marbles.changing_probabilities();
+        assert!((none - 924.0 / 8008.0).abs() < 1e-12);
+        assert!((one - 3168.0 / 8008.0).abs() < 1e-12);
+        assert!(four > 0.0 && five == 0.0 && six == 0.0);
+        let readings = Method::Marbles.simulate(2000);
+        let most = |number: u8| {
+            readings
+                .iter()
+                .map(|reading| {
+                    reading
+                        .traditional_numbers()
+                        .iter()
+                        .filter(|&&n| n == number)
+                        .count()
+                })
+                .max()
+                .unwrap()
+        };
+        assert_eq!(most(6), 1);
+        assert!(most(9) <= 3);
+    }
+}
@@ -1,643 +1,3973 @@
-use crate::core::data::IChingData;
-use crate::core::{Diviner, Reading};
+use crate::animation::{self, AnimationSpeed};
+use crate::bagua::{Arrangement, Compass};
+use crate::banner;
+use crate::card::{self, Card, Color, Theme};
+use crate::casting;
+#[cfg(feature = "llm")]
+use crate::config::LlmProvider;
+use crate::config::{Config, Profile};
+use crate::contemplation;
+use crate::core::data::{self, HEXAGRAM_CHARS, IChingData, LineRef, Provenance};
+use crate::core::king_wen;
+use crate::core::parse;
+use crate::core::partial::PartialReading;
+use crate::core::permalink::Permalink;
+use crate::core::query::Query;
+use crate::core::trigram::Element;
+use crate::core::verify;
+use crate::core::{Diviner, Reading, Trigram};
+use crate::daemon::{self, Daemon};
+use crate::dashboard::{self, Dashboard};
+use crate::diff::ReadingDiff;
+use crate::drafts::Drafts;
+use crate::dump::Corpus;
+use crate::embedding;
+#[cfg(feature = "encryption")]
+use crate::encryption::{self, Key};
+use crate::format::{
+    Format, FullOptions, RenderOptions, TextOnly, describe_pair_relation, display_width,
+    escape_delimited_field, format_brief_summary, format_brief_with_data, format_delimited,
+    format_motd_with_data, format_notes, format_pair_heading, format_trigram,
+    format_trigram_discussion, pad, write_reading,
+};
+use crate::fortune;
+use crate::glossary::Glossary;
+use crate::glyphs::Glyphs;
+use crate::group;
+use crate::ical;
+use crate::importer::ImportFormat;
+use crate::interpretation::framing::Topic;
+use crate::interpretation::rules::Rule;
+use crate::journal::{Journal, JournalEntry, Outcome};
+use crate::json::{
+    JsonCase, JsonOptions, JsonReading, JsonStyle, json_reading_with_data, reading_from_json,
+};
+use crate::lang::Lang;
+#[cfg(feature = "llm")]
+use crate::llm::{self, Interpreter};
+use crate::manifest::{Manifest, Rendering};
+use crate::motd;
+use crate::notes::{HexagramNotes, Note, Notes};
+use crate::notify::Notification;
+use crate::output::Output;
+use crate::pdf;
+use crate::relations::Matrix;
+use crate::remote::{self, CastRequest, Remote};
+use crate::rendered::{Part, Rendered, SectionKind};
+use crate::report::{Accuracy, OutcomeCounts, Report};
+use crate::selftest;
+use crate::shichen::HourGua;
+use crate::speech::{self, Engine};
+use crate::stats::{self, Tally};
+use crate::status::{self, Refresh};
+#[cfg(feature = "sqlite")]
+use crate::store::{self, Store};
+use crate::system_log::{LogTarget, Record};
+use crate::systemd;
+use crate::tones::{self, Sound};
+use crate::translations::{self, Layout, Translation};
+#[cfg(feature = "self-update")]
+use crate::update::{self, Release};
+use crate::watchlist::{self, Watchlist};
+use crate::wizard;
+use crate::worksheet::Prompts;
 use anyhow::Result;
-use clap::{Parser, ValueEnum};
+use clap::{Parser, Subcommand, ValueEnum};
 use serde::{Deserialize, Serialize};
+use std::fmt::Write as _;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use std::time::Duration;
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JsonHexagram {
-    pub number: u8,
-    pub name: String,
-    pub chinese: String,
-    pub pinyin: String,
-    pub unicode: String,
-    pub description: String,
-    pub judgment: JsonJudgment,
-    pub image: JsonImage,
+#[derive(Parser)]
+#[command(name = "i-ching")]
+#[command(about = "I Ching divination readings")]
+#[command(version = env!("CARGO_PKG_VERSION"))]
+pub struct Cli {
+    #[command(subcommand)]
+    pub command: Option<Command>,
+
+    /// Named set of defaults from `[profiles.NAME]` in config.toml; flags given here still win
+    #[arg(long, value_name = "NAME")]
+    pub profile: Option<String>,
+
+    /// Output format [default: full]
+    #[arg(short, long)]
+    pub format: Option<Format>,
+
+    /// Input for reading: hexagram number (1-64), Unicode character (䷀ to ䷿), six line numbers (6,7,8,9) separated by commas or spaces, changing format (32→34, 32=>34, "32 to 34", or ䷟→䷡), trigrams upper over lower (kan/li or "water over fire"), binary lines top to bottom (0b010001 or 010001), or a permalink token (iching:v1:...)
+    #[arg(short, long)]
+    pub input: Option<String>,
+
+    /// Show the texts in several translations side by side, e.g. wilhelm,legge (see README for adding translations)
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    pub compare_translations: Option<Vec<String>>,
+
+    /// How --compare-translations lays the texts out
+    #[arg(long, default_value = "columns", requires = "compare_translations")]
+    pub compare_layout: Layout,
+
+    /// Show the texts in English and Chinese, interleaved (needs the Chinese corpus, zh.json)
+    #[arg(long, conflicts_with = "compare_translations")]
+    pub bilingual: bool,
+
+    /// Refuse input that could mean two things (e.g. 7 as hexagram 7 or a line number) instead of noting which was chosen
+    #[arg(long, global = true)]
+    pub strict_input: bool,
+
+    /// Question being asked of the oracle
+    #[arg(short, long)]
+    pub question: Option<String>,
+
+    /// File describing the situation behind the question in more detail (`-` for stdin)
+    #[arg(long)]
+    pub context_file: Option<String>,
+
+    /// Record this reading in the journal
+    #[arg(long)]
+    pub save: bool,
+
+    /// Who the reading is for, when consulting for someone else; kept in the journal
+    #[arg(long = "for", value_name = "NAME")]
+    pub recipient: Option<String>,
+
+    /// Changing line (1-6) central to the question, whose text comes first; with no number, choose one at a prompt after casting
+    #[arg(long, value_name = "LINE", num_args = 0..=1, value_parser = clap::value_parser!(u8).range(1..=6))]
+    pub focus_line: Option<Option<u8>>,
+
+    /// Also record the cast in a system log, with the hexagrams and changing lines as structured fields
+    #[arg(long)]
+    pub log_to: Option<LogTarget>,
+
+    /// Include the question in the --log-to message and fields (left out by default, since system logs are often readable by other users)
+    #[arg(long, requires = "log_to")]
+    pub log_question: bool,
+
+    /// Include the King Wen partner hexagram in full output
+    #[arg(long)]
+    pub pair: bool,
+
+    /// Include Ten Wings commentary (Sequence, Miscellaneous Notes, trigram discussion) in full output
+    #[arg(long)]
+    pub wings: bool,
+
+    /// Add a short Guidance list of the text's advice to brief and motd output
+    #[arg(long)]
+    pub guidance: bool,
+
+    /// Frame full output for the question's topic
+    #[arg(long)]
+    pub topic: Option<Topic>,
+
+    /// Reading tradition for full output: liuyao adds the six spirits for the casting day [default: standard]
+    #[arg(long)]
+    pub style: Option<Style>,
+
+    /// Language of hexagram names and line positions in full and brief output: zh puts the Chinese name first and names lines 初九, 六二, ..., and uses the Chinese corpus's texts if installed [default: en, or zh for a question in Chinese]
+    #[arg(long)]
+    pub lang: Option<Lang>,
+
+    /// Rule for which text governs when two or more lines change, noted in brief output [default: zhuxi]
+    #[arg(long)]
+    pub rule: Option<Rule>,
+
+    /// Replace full output with the bare received text: the judgments and changing line statements, without commentary
+    #[arg(long)]
+    pub text_only: Option<TextOnly>,
+
+    /// Show only these sections of full, markdown, html, ssml, and org output, e.g. judgment,image,lines (commentary adds Wilhelm's commentary to them)
+    #[arg(long, value_delimiter = ',', value_name = "SECTIONS")]
+    pub sections: Option<Vec<Part>>,
+
+    /// Casting date (YYYY-MM-DD) to take the day stem from with --style liuyao, or to hash with --method question-hash; defaults to today
+    #[arg(long)]
+    pub date: Option<chrono::NaiveDate>,
+
+    /// Hexagram glyphs: detect terminal support, or force Unicode or ASCII line art [default: auto]
+    #[arg(long)]
+    pub glyphs: Option<GlyphMode>,
+
+    /// Layout of JSON output [default: pretty]
+    #[arg(long)]
+    pub json_style: Option<JsonStyle>,
+
+    /// Casing of JSON field names: camel for JavaScript consumers [default: snake]
+    #[arg(long)]
+    pub json_case: Option<JsonCase>,
+
+    /// Print straight to stdout instead of paging long output
+    #[arg(long, global = true)]
+    pub no_pager: bool,
+
+    /// Cross-check the hexagram and trigram data for consistency before anything else (always done in debug builds)
+    #[arg(long, global = true)]
+    pub verify_data: bool,
+
+    /// Note the moon phase and solar term at casting time
+    #[arg(long)]
+    pub almanac: bool,
+
+    /// Show the coins spinning before each line of a random cast resolves
+    #[arg(long, conflicts_with = "input")]
+    pub animate: bool,
+
+    /// How fast the coins spin with --animate
+    #[arg(long, default_value = "normal")]
+    pub animate_speed: AnimationSpeed,
+
+    /// Cast from coins you tossed yourself: six groups of three faces, bottom line first (e.g. HHT,TTH,HTH,TTT,HHH,THT)
+    #[arg(long, conflicts_with_all = ["input", "animate", "dice"])]
+    pub coins: Option<String>,
+
+    /// Cast from six-sided dice you rolled yourself, three per line, bottom line first (odd counts as heads, even as tails)
+    #[arg(long, conflicts_with_all = ["input", "animate"])]
+    pub dice: Option<String>,
+
+    /// Pause this many seconds with the question before a random cast; the pause's timing jitter helps seed the coins
+    #[arg(long, value_name = "SECONDS", conflicts_with_all = ["input", "animate", "coins", "dice"])]
+    pub contemplation: Option<f64>,
+
+    /// Exit with status 3 when the reading's primary or transformed hexagram is on the watchlist (see `i-ching watch`)
+    #[arg(long)]
+    pub exit_if_watched: bool,
+
+    /// Write the reading to this file instead of printing it: a printable one-page sheet for .pdf, or a calendar event at the cast time for .ics
+    #[arg(short, long, value_name = "FILE")]
+    pub output: Option<PathBuf>,
+
+    /// With --output FILE.ics, add an all-day event with a reminder on the reading's revisit date
+    #[arg(long, requires = "output")]
+    pub remind: bool,
+
+    /// How a reading is arrived at without --input, --coins, or --dice: a casting method with its own odds (see `i-ching stats`), or question-hash, which derives the lines from the question and date instead of casting, so they can be reproduced [default: coins]
+    #[arg(long, default_value = "coins", conflicts_with_all = ["input", "animate", "coins", "dice", "contemplation", "rng"])]
+    pub method: Method,
+
+    /// Random source for a random cast
+    #[arg(long, default_value = "thread", conflicts_with_all = ["input", "animate", "coins", "dice", "contemplation"])]
+    pub rng: RngSource,
+
+    /// Cast from a fresh seed and write a manifest of it (version, data checksums, method, seed, time, and output options) to this file, for `i-ching reproduce`
+    #[arg(long, value_name = "FILE", conflicts_with_all = ["input", "animate", "coins", "dice", "contemplation", "rng", "compare_translations", "bilingual", "output"])]
+    pub manifest: Option<PathBuf>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JsonJudgment {
-    pub text: String,
-    pub commentary: String,
+#[derive(Subcommand)]
+pub enum Command {
+    /// Show a hexagram alongside its King Wen partner (e.g. 1/2, 3/4)
+    Pair {
+        /// Hexagram number (1-64)
+        hexagram: u8,
+    },
+    /// Cast a reading for each question in a file (one per line, `-` for stdin)
+    Batch {
+        /// File of questions; blank lines and lines starting with `#` are skipped
+        #[arg(default_value = "-")]
+        file: String,
+
+        /// Report format
+        #[arg(short, long, default_value = "markdown")]
+        format: BatchFormat,
+
+        /// Record each reading in the journal
+        #[arg(long)]
+        save: bool,
+    },
+    /// Review and export saved readings
+    Journal {
+        /// Only readings cast for this person (with --for when casting)
+        #[arg(long = "for", value_name = "NAME", global = true)]
+        recipient: Option<String>,
+
+        #[command(subcommand)]
+        action: JournalCommand,
+    },
+    /// Follow the Sequence (Xu Gua) commentary through a run of hexagrams
+    #[command(alias = "story")]
+    Sequence {
+        /// Hexagrams as an inclusive range (`11..20`) or a list (`1,24,64`)
+        hexagrams: String,
+    },
+    /// List the hexagrams with the given trigrams, element, yang lines, or keywords
+    Find {
+        /// Upper trigram, by name, image, or symbol (e.g. kan, water, ☵)
+        #[arg(long)]
+        upper: Option<Trigram>,
+
+        /// Lower trigram, by name, image, or symbol
+        #[arg(long)]
+        lower: Option<Trigram>,
+
+        /// One of the five phases (wood, fire, earth, metal, water), in either trigram
+        #[arg(long)]
+        element: Option<Element>,
+
+        /// Number of yang lines (0-6)
+        #[arg(long)]
+        yang: Option<u32>,
+
+        /// Also show what each match becomes with these lines (1-6) changing, e.g. 2 or 2,5
+        #[arg(long, value_delimiter = ',')]
+        changing: Vec<usize>,
+
+        /// Words that must all appear in the name, description, judgment, or image
+        keywords: Vec<String>,
+    },
+    /// Browse the hexagrams and trigrams full-screen, filtering by trigram or keyword
+    #[cfg(feature = "tui")]
+    Browse,
+    /// Draw the eight trigrams around the compass, marking a reading's upper and lower trigrams
+    Bagua {
+        /// Reading whose trigrams to mark, in any form --input accepts
+        #[arg(short, long)]
+        reading: Option<String>,
+
+        /// Which order to lay the trigrams out in
+        #[arg(long, default_value = "later")]
+        arrangement: Arrangement,
+
+        /// Draw it as an SVG image instead of text
+        #[arg(long)]
+        svg: bool,
+    },
+    /// Draw a square share card of a reading as PNG or SVG (cast one if no input is given)
+    Card {
+        /// Reading to draw, in any form --input accepts
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// File to write, ending in .png or .svg
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+
+        /// Width and height in pixels
+        #[arg(long, default_value_t = card::DEFAULT_SIZE)]
+        size: u32,
+
+        /// Background color as #rrggbb (default from [card] in config.toml)
+        #[arg(long, value_name = "COLOR")]
+        background: Option<Color>,
+
+        /// Color of the lines and text
+        #[arg(long, value_name = "COLOR")]
+        foreground: Option<Color>,
+
+        /// Color of changing lines and the date
+        #[arg(long, value_name = "COLOR")]
+        accent: Option<Color>,
+    },
+    /// Print a shareable permalink token for a reading (cast one if no input is given)
+    Encode {
+        /// Reading to encode, in any form --input accepts
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Question the reading is for; only a hash of it goes in the token
+        #[arg(short, long)]
+        question: Option<String>,
+    },
+    /// Rebuild and show a reading from a permalink token
+    Decode {
+        /// Token from `i-ching encode`, e.g. iching:v1:978677
+        token: String,
+
+        /// The question, checked against the hash in the token
+        #[arg(short, long)]
+        question: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "full")]
+        format: Format,
+    },
+    /// Show the hour hexagram: the sovereign hexagram of the current double hour, with a moving line from the day
+    Hour {
+        /// Moment to take instead of now, e.g. "2026-03-01 14:30" (local time)
+        #[arg(long, value_name = "YYYY-MM-DD HH:MM", value_parser = parse_local_time)]
+        at: Option<chrono::NaiveDateTime>,
+
+        /// Output format
+        #[arg(short, long, default_value = "full")]
+        format: Format,
+    },
+    /// Show the day's hexagram, the hour hexagram, and your latest saved reading on one screen
+    Dashboard {
+        /// Moment to take instead of now, e.g. "2026-03-01 14:30" (local time)
+        #[arg(long, value_name = "YYYY-MM-DD HH:MM", value_parser = parse_local_time)]
+        at: Option<chrono::NaiveDateTime>,
+
+        #[arg(short, long, default_value = "text")]
+        format: DashboardFormat,
+    },
+    /// Show what changed from one saved reading to another: the lines cast differently and how the hexagrams relate
+    Diff {
+        /// First reading: a `--format json` reading or a journal entry, as a JSON file (`-` for stdin)
+        a: String,
+        /// Second reading, in either form
+        b: String,
+
+        #[arg(short, long, default_value = "text")]
+        format: DiffFormat,
+    },
+    /// Cast a reading again from the manifest `--manifest` wrote, checking it comes out byte for byte the same
+    Reproduce {
+        /// Manifest file
+        manifest: PathBuf,
+    },
+    /// Print a section of the Ten Wings commentaries
+    Wings {
+        /// Section to print
+        section: WingsSection,
+    },
+    /// List the 64 hexagram characters with their numbers, code points, and names
+    Unicode,
+    /// Cast only some of a reading's lines now, saved as a draft to finish with `resume`
+    Cast {
+        /// Lines to cast now (1-6), from the bottom up
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=6))]
+        lines: u8,
+
+        /// Name to save the draft under
+        #[arg(long, value_name = "NAME")]
+        save: String,
+
+        /// Question being asked of the oracle
+        #[arg(short, long)]
+        question: Option<String>,
+    },
+    /// Cast the rest of a draft's lines and show the reading, or list drafts if no name is given
+    Resume {
+        /// Draft to resume
+        name: Option<String>,
+
+        /// Lines to cast now, instead of all that remain
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=6))]
+        lines: Option<u8>,
+
+        /// Record the finished reading in the journal
+        #[arg(long)]
+        save: bool,
+    },
+    /// Convert hexagrams between numberings, e.g. `convert --from binary --to king-wen 101010`
+    Convert {
+        /// Numbering the values are given in
+        #[arg(long)]
+        from: Numbering,
+
+        /// Numbering to print them in
+        #[arg(long)]
+        to: Numbering,
+
+        /// Hexagrams to convert; reads one per line from stdin if omitted
+        values: Vec<String>,
+    },
+    /// Say a hexagram's Chinese name aloud (espeak-ng, espeak, or macOS say), or print it as SSML
+    Say {
+        /// Hexagram number (1-64)
+        hexagram: u8,
+
+        /// Print SSML with the name's IPA for another synthesizer instead of speaking
+        #[arg(long)]
+        ssml: bool,
+
+        /// Speech engine to use instead of the first one found on PATH
+        #[arg(long, conflicts_with = "ssml")]
+        engine: Option<Engine>,
+    },
+    /// Play a reading's lines as a tune: terminal bells, ABC notation, or a MIDI file
+    Play {
+        /// Reading to play, in any --input format (cast randomly if omitted)
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Question being asked of the oracle
+        #[arg(short, long)]
+        question: Option<String>,
+
+        /// How to sound it
+        #[arg(long = "as", value_name = "SOUND", default_value = "beep")]
+        sound: Sound,
+
+        /// File to write ABC or MIDI to instead of stdout
+        #[arg(short, long, value_name = "FILE")]
+        output: Option<PathBuf>,
+    },
+    /// Cast a reading and send it as a desktop notification or to a webhook (see config.toml)
+    Notify {
+        /// Question to cast for, overriding the configured one
+        #[arg(short, long)]
+        question: Option<String>,
+
+        /// Record the reading in the journal
+        #[arg(long)]
+        save: bool,
+
+        /// Also record the cast in a system log
+        #[arg(long)]
+        log_to: Option<LogTarget>,
+
+        /// Include the question in the --log-to message and fields
+        #[arg(long, requires = "log_to")]
+        log_question: bool,
+
+        /// Print the notification instead of sending it
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Keep your own commentary on hexagrams, shown in full output
+    Notes {
+        #[command(subcommand)]
+        action: NotesCommand,
+    },
+    /// Watch hexagrams: casts that turn one up say so (and can exit with status 3); with no numbers, list them
+    Watch {
+        /// Hexagram numbers (1-64)
+        hexagrams: Vec<u8>,
+
+        /// Stop watching these hexagrams instead
+        #[arg(long)]
+        remove: bool,
+    },
+    /// Print a compact reading for tmux, starship, or polybar, cached for the period
+    Status {
+        /// How often a new reading is cast
+        #[arg(long, default_value = "daily")]
+        refresh: Refresh,
+    },
+    /// Show the hexagram of the day in the login message
+    Motd {
+        #[command(subcommand)]
+        action: MotdCommand,
+    },
+    /// Serve casts and lookups over a Unix socket, keeping the data loaded
+    Daemon {
+        /// Socket path (default: $I_CHING_SOCKET or i-ching.sock in the runtime directory)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+
+        /// Take the listening socket from systemd instead of binding one
+        #[arg(long, conflicts_with = "socket")]
+        systemd_socket: bool,
+
+        /// Print systemd user units that socket-activate the daemon, and exit
+        #[arg(long, conflicts_with_all = ["socket", "systemd_socket"])]
+        print_unit: bool,
+    },
+    /// Send newline-delimited JSON requests to a running daemon
+    Client {
+        /// A single request, e.g. '{"op": "cast", "question": "..."}'; reads stdin if omitted
+        request: Option<String>,
+
+        /// Socket path (default: $I_CHING_SOCKET or i-ching.sock in the runtime directory)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+    /// Serve casts and lookups over HTTP as JSON, for `i-ching remote`
+    Serve {
+        /// Address and port to listen on
+        #[arg(long, default_value = remote::DEFAULT_LISTEN)]
+        listen: String,
+
+        /// Take the listening socket from systemd instead of binding --listen
+        #[arg(long)]
+        systemd_socket: bool,
+
+        /// Print systemd units that socket-activate the server on --listen, and exit
+        #[arg(long, conflicts_with = "systemd_socket")]
+        print_unit: bool,
+    },
+    /// Cast on an `i-ching serve` instance and show the reading from its answer alone
+    Remote {
+        /// The server's base URL, e.g. https://oracle.example.com
+        #[arg(long)]
+        url: String,
+
+        /// Reading for the server to build, in any --input format (cast randomly if omitted)
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Question being asked of the oracle
+        #[arg(short, long)]
+        question: Option<String>,
+
+        /// Output format
+        #[arg(short, long, default_value = "full")]
+        format: RemoteFormat,
+    },
+    /// Ask a chat model to interpret a reading (see [llm] in config.toml)
+    #[cfg(feature = "llm")]
+    Interpret {
+        /// Reading to interpret, in any --input format (cast randomly if omitted)
+        #[arg(short, long)]
+        input: Option<String>,
+
+        /// Question being asked of the oracle
+        #[arg(short, long)]
+        question: Option<String>,
+
+        /// File describing the situation behind the question (`-` for stdin)
+        #[arg(long)]
+        context_file: Option<String>,
+
+        /// Chat API to use
+        #[arg(long)]
+        provider: Option<LlmProvider>,
+
+        /// Model name
+        #[arg(long)]
+        model: Option<String>,
+
+        /// Base URL overriding the provider's default
+        #[arg(long)]
+        endpoint: Option<String>,
+
+        /// File with a prompt template using {question}, {context}, and {reading} placeholders
+        #[arg(long)]
+        prompt_template: Option<PathBuf>,
+    },
+    /// Replace this binary with the latest GitHub release, if newer
+    #[cfg(feature = "self-update")]
+    SelfUpdate {
+        /// Only report whether a newer release exists
+        #[arg(long)]
+        check: bool,
+
+        /// Replace the binary even if `cargo install` put it there
+        #[arg(long)]
+        force: bool,
+    },
+    /// Check that random casts match the traditional probabilities
+    Selftest {
+        /// Number of lines to cast
+        #[arg(long, default_value = "100000")]
+        lines: usize,
+    },
+    /// List every possible reading, each hexagram with each set of changing lines
+    Enumerate {
+        #[arg(short, long, default_value = "jsonl")]
+        format: EnumerateFormat,
+
+        /// Only the 64 readings of this hexagram (1-64)
+        #[arg(long)]
+        hexagram: Option<u8>,
+    },
+    /// Compare the odds of each number of changing lines by casting method
+    Stats {
+        /// Method to report on (default: all)
+        #[arg(long, value_enum)]
+        method: Option<stats::Method>,
+
+        /// Readings to cast by each method for the observed column
+        #[arg(long, default_value = "10000")]
+        casts: usize,
+
+        /// Add a column of the journal's own readings to the three coins table
+        #[arg(long)]
+        journal: bool,
+    },
+    /// Work out a well-posed question step by step, then cast and read it
+    Wizard {
+        /// Record the reading, with the wizard's answers, in the journal
+        #[arg(long)]
+        save: bool,
+    },
+    /// Run a group session: cast each participant's question in turn
+    Group {
+        #[command(subcommand)]
+        action: GroupCommand,
+    },
+    /// Export every hexagram, trigram, line text, and relation in a documented schema
+    Dump {
+        /// Export format (sqlite needs the `sqlite` feature)
+        #[arg(short, long, default_value = "json")]
+        format: DumpFormat,
+
+        /// File to write (required for sqlite; json and toml go to stdout otherwise)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Write a fortune(6) file of hexagram texts, and its strfile index when writing to a file
+    FortuneDb {
+        /// Texts to include
+        #[arg(long, value_delimiter = ',', default_value = "judgment,image,lines")]
+        texts: Vec<fortune::Text>,
+
+        /// Leave off the "-- I Ching, ..." line under each text
+        #[arg(long)]
+        no_attribution: bool,
+
+        /// File to write, with its index beside it as FILE.dat (stdout, unindexed, otherwise)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
+    /// Export how every hexagram relates to every other (line-change neighbors, complements, inverses, nuclear hexagrams) as a 64×64 matrix
+    Relations {
+        /// Export format: a JSON matrix, or a Graphviz DOT graph
+        #[arg(short, long, default_value = "json")]
+        format: RelationsFormat,
+    },
+    /// Show where the bundled texts and installed translations come from, and their licenses
+    Sources {
+        #[arg(short, long, default_value = "text")]
+        format: SourcesFormat,
+    },
+    /// Show consultations from the classical histories that cast or changed to a hexagram
+    History {
+        /// Hexagram number (1-64)
+        hexagram: u8,
+    },
+    /// Show the line texts that quote or mirror a line, e.g. `xref 1.6`
+    Xref {
+        /// Hexagram and line, e.g. 1.6 for the top line of hexagram 1
+        line: LineRef,
+    },
+    /// Define a term used in readings, e.g. `explain "nuclear hexagram"`; with no term, list them
+    Explain {
+        /// Term or alias to define, e.g. "changing line" or "Kan"
+        term: Option<String>,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JsonImage {
-    pub text: String,
-    pub commentary: String,
+#[derive(Subcommand)]
+pub enum JournalCommand {
+    /// List saved readings, oldest first, numbered
+    List,
+    /// List saved readings whose revisit date (set by their changing lines) has arrived
+    Due,
+    /// Find saved readings by words in their question, context, outcome note, or hexagram names, best match first
+    Search {
+        /// Words to look for, e.g. "job" or "Peace"; inflections and small typos still match
+        #[arg(required = true)]
+        query: Vec<String>,
+    },
+    /// Dump the entire journal for analysis elsewhere
+    Export {
+        /// Export format
+        #[arg(short, long, default_value = "csv")]
+        format: ExportFormat,
+    },
+    /// Add readings saved by other I Ching software to the journal, skipping ones already in it
+    Import {
+        /// File to read (`-` for stdin)
+        #[arg(default_value = "-")]
+        file: String,
+
+        /// Format of the file
+        #[arg(short, long, default_value = "auto")]
+        format: ImportFormat,
+    },
+    /// Summarize the journal: hexagram frequencies, changing-line counts, and topics by month
+    Report {
+        /// Report format
+        #[arg(short, long, default_value = "text")]
+        format: ReportFormat,
+
+        /// Summarize recorded outcomes by month instead: how many resolved, and how accurate
+        #[arg(long, conflicts_with = "format")]
+        accuracy: bool,
+    },
+    /// Record how a saved reading turned out
+    Resolve {
+        /// Entry number, as `journal list` shows it (default: the latest)
+        number: Option<usize>,
+
+        /// Whether the situation has played out
+        #[arg(long, default_value_t = true, action = clap::ArgAction::Set)]
+        resolved: bool,
+
+        /// How well the reading fit what happened, 1 (not at all) to 5 (closely)
+        #[arg(long, value_parser = clap::value_parser!(u8).range(1..=5))]
+        accuracy: Option<u8>,
+
+        /// When it played out (default: today, if resolved)
+        #[arg(long, value_name = "YYYY-MM-DD")]
+        on: Option<chrono::NaiveDate>,
+
+        /// What happened
+        #[arg(long)]
+        note: Option<String>,
+    },
+    /// Rewrite entries saved by older releases in the current reading format
+    Migrate {
+        /// Count the entries that need upgrading without rewriting anything
+        #[arg(long)]
+        dry_run: bool,
+    },
+    /// Encrypt the journal file with a passphrase, or to an age key file
+    #[cfg(feature = "encryption")]
+    Lock {
+        /// Key file to encrypt to instead of a passphrase; a new key is written here if none exists
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
+    /// Decrypt a locked journal back to plain JSON lines
+    #[cfg(feature = "encryption")]
+    Unlock {
+        /// Key file the journal was locked with (default: $I_CHING_JOURNAL_KEY, else a passphrase)
+        #[arg(long)]
+        key_file: Option<PathBuf>,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JsonLineInterpretation {
-    pub position: u8,
-    pub text: String,
-    pub comments: String,
+#[derive(Subcommand)]
+pub enum MotdCommand {
+    /// Install an update-motd.d script (or systemd timer) that shows the hexagram of the day
+    Install {
+        /// Write a systemd service and timer that rewrite /etc/motd daily instead
+        #[arg(long)]
+        systemd: bool,
+        /// Print the files and where they go instead of writing them
+        #[arg(long)]
+        print: bool,
+        /// Columns the message may use
+        #[arg(long, default_value = "80")]
+        width: usize,
+    },
+    /// Print the hexagram of the day, its name and judgment fitted to a width
+    Render {
+        /// Columns available (default: $COLUMNS, or 80)
+        #[arg(long)]
+        width: Option<usize>,
+        /// Day (YYYY-MM-DD) to render; defaults to today
+        #[arg(long)]
+        date: Option<chrono::NaiveDate>,
+    },
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct JsonReading {
-    pub question: Option<String>,
-    pub lines: [u8; 6],
-    pub primary_hexagram: JsonHexagram,
-    pub changing_lines: Vec<JsonLineInterpretation>,
-    pub transformed_hexagram: Option<JsonHexagram>,
-    pub upper_trigram: [String; 3],
-    pub lower_trigram: [String; 3],
+#[derive(Subcommand)]
+pub enum GroupCommand {
+    /// Collect questions, cast them one by one, and write up the session
+    Start {
+        /// Session title, for the top of the document
+        title: String,
+
+        /// Read participants from a file of `Name: question` lines instead of asking
+        #[arg(long)]
+        file: Option<PathBuf>,
+
+        /// Seconds to pause in contemplation before each cast
+        #[arg(long, default_value_t = 10.0)]
+        pause: f64,
+
+        /// Write the session document (Markdown) here instead of stdout
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Record each reading in the journal, for its participant
+        #[arg(long)]
+        save: bool,
+    },
 }
 
-#[derive(Parser)]
-#[command(name = "i-ching")]
-#[command(about = "I Ching divination readings")]
-#[command(version = env!("CARGO_PKG_VERSION"))]
-pub struct Cli {
-    /// Output format
-    #[arg(short, long, default_value = "full")]
-    pub format: Format,
+#[derive(Subcommand)]
+pub enum NotesCommand {
+    /// Add a note to a hexagram
+    Add {
+        /// Hexagram number (1-64)
+        hexagram: u8,
+        /// Your interpretation
+        text: String,
+    },
+    /// List your notes, for one hexagram or all of them
+    List {
+        /// Hexagram number (1-64)
+        hexagram: Option<u8>,
+    },
+    /// Remove a note by the number `notes list` shows for it
+    Remove {
+        /// Hexagram number (1-64)
+        hexagram: u8,
+        /// Note number
+        number: usize,
+    },
+}
 
-    /// Input for reading: hexagram number (1-64), Unicode character (䷀ to ䷿), line numbers (6,7,8,9) comma separated, or changing format (32→34 or ䷟→䷡)
-    #[arg(short, long)]
-    pub input: Option<String>,
+/// Ten Wings sections available in the data set
+#[derive(ValueEnum, Clone, Copy)]
+pub enum WingsSection {
+    /// Discussion of the Trigrams: family, animal, body, direction, images
+    #[value(alias = "trigrams")]
+    Shuogua,
+    /// Sequence of the Hexagrams: why each follows the last
+    #[value(alias = "sequence")]
+    XuGua,
+    /// Miscellaneous Notes: a terse word on each hexagram
+    #[value(alias = "miscellaneous")]
+    ZaGua,
+}
+
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum GlyphMode {
+    Auto,
+    Unicode,
+    Ascii,
+}
+
+impl GlyphMode {
+    pub fn resolve(self) -> Glyphs {
+        match self {
+            GlyphMode::Auto => Glyphs::detect(),
+            GlyphMode::Unicode => Glyphs::Unicode,
+            GlyphMode::Ascii => Glyphs::Ascii,
+        }
+    }
+}
+
+/// Ways of numbering or writing a hexagram, for `convert`
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Numbering {
+    /// King Wen sequence number, 1-64
+    KingWen,
+    /// King Wen position counted from zero, 0-63
+    Index,
+    /// Fu Xi (Shao Yong) binary sequence position, from Kun (0) to Qian (63)
+    Fuxi,
+    /// Six 0/1 digits, top line first and yang as 1, as `--input` takes them
+    Binary,
+    /// Yijing hexagram character, ䷀-䷿
+    Unicode,
+}
+
+impl Numbering {
+    /// The King Wen number of a hexagram written in this numbering
+    pub fn parse(self, value: &str) -> Result<u8> {
+        let number = match self {
+            Numbering::KingWen => value.parse::<u8>().ok().filter(|n| (1..=64).contains(n)),
+            Numbering::Index => value.parse::<u8>().ok().filter(|&n| n < 64).map(|n| n + 1),
+            Numbering::Fuxi => value.parse::<u8>().ok().and_then(king_wen::from_fuxi),
+            Numbering::Binary => parse::bits(value).map(king_wen::from_binary),
+            Numbering::Unicode => {
+                let mut chars = value.chars();
+                match (chars.next(), chars.next()) {
+                    (Some(c), None) => data::unicode_to_hexagram_number(c),
+                    _ => None,
+                }
+            }
+        };
+        number.ok_or_else(|| {
+            anyhow::anyhow!(
+                "Invalid {} value: '{}'. Expected {}",
+                self.to_possible_value()
+                    .map_or_else(String::new, |v| v.get_name().to_string()),
+                value,
+                self.expected()
+            )
+        })
+    }
+
+    /// A King Wen number written in this numbering
+    pub fn format(self, number: u8) -> String {
+        match self {
+            Numbering::KingWen => number.to_string(),
+            Numbering::Index => (number - 1).to_string(),
+            Numbering::Fuxi => king_wen::to_fuxi(number).unwrap_or_default().to_string(),
+            Numbering::Binary => format!("{:06b}", king_wen::to_binary(number).unwrap_or_default()),
+            Numbering::Unicode => data::hexagram_number_to_unicode(number)
+                .map(String::from)
+                .unwrap_or_default(),
+        }
+    }
+
+    fn expected(self) -> &'static str {
+        match self {
+            Numbering::KingWen => "1-64",
+            Numbering::Index | Numbering::Fuxi => "0-63",
+            Numbering::Binary => "six 0/1 digits, top line first (e.g. 010001)",
+            Numbering::Unicode => "one character from ䷀ to ䷿",
+        }
+    }
+}
+
+/// How a reading is arrived at when no lines are given
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Method {
+    /// Toss three coins for each line
+    Coins,
+    /// Divide forty-nine yarrow stalks three times for each line
+    Yarrow,
+    /// Toss two coins twice for each line, with the yarrow stalks' odds
+    TwoCoins,
+    /// Draw one of sixteen tokens for each line and put it back, with the yarrow stalks' odds
+    Tokens,
+    /// Draw six of the sixteen tokens as marbles without putting them back: at most one old yin
+    Marbles,
+    /// Hash the question and date: not a cast, but the same every time
+    QuestionHash,
+}
+
+impl Method {
+    /// The casting method, unless the lines aren't cast at all
+    pub(crate) fn casting(self) -> Option<casting::Method> {
+        match self {
+            Method::Coins => Some(casting::Method::Coins),
+            Method::Yarrow => Some(casting::Method::Yarrow),
+            Method::TwoCoins => Some(casting::Method::TwoCoins),
+            Method::Tokens => Some(casting::Method::Tokens),
+            Method::Marbles => Some(casting::Method::Marbles),
+            Method::QuestionHash => None,
+        }
+    }
+}
+
+/// Where a random cast's coin tosses come from
+#[derive(ValueEnum, Clone, Copy, PartialEq, Eq)]
+pub enum RngSource {
+    /// The thread-local generator, seeded from the OS
+    Thread,
+    /// The operating system's random source (getrandom), read for every cast
+    Os,
+}
+
+/// Tradition full output is laid out for
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Style {
+    Standard,
+    /// Six Lines (Liu Yao): assign the six spirits by the casting day's stem
+    Liuyao,
 }
 
 #[derive(ValueEnum, Clone)]
-pub enum Format {
+pub enum BatchFormat {
+    Json,
+    Markdown,
+    /// fortune(6) cookies separated by `%` lines
+    Fortune,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum DumpFormat {
+    Json,
+    Toml,
+    Sqlite,
+}
+
+/// What `i-ching remote` can show from a served reading
+#[derive(ValueEnum, Clone, Copy)]
+pub enum RemoteFormat {
     Brief,
     Full,
     Json,
-    Numbers,
-    Motd,
 }
 
-pub fn run_cli() -> Result<()> {
-    let cli = Cli::parse();
-    let mut diviner = Diviner::new();
-
-    let reading = if let Some(input) = cli.input {
-        parse_input_and_create_reading(&mut diviner, &input)?
+#[derive(ValueEnum, Clone)]
+pub enum RelationsFormat {
+    Json,
+    Dot,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum EnumerateFormat {
+    /// One JSON reading per line, as `--format json` gives it
+    Jsonl,
+    /// One brief summary per line
+    Brief,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum DashboardFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum DiffFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum SourcesFormat {
+    Text,
+    Json,
+}
+
+#[derive(ValueEnum, Clone)]
+pub enum ExportFormat {
+    Csv,
+    Tsv,
+    Json,
+    /// One JSON line per reading, of text to embed and metadata
+    EmbeddingInput,
+}
+
+#[derive(ValueEnum, Clone, Copy)]
+pub enum ReportFormat {
+    Text,
+    Csv,
+    Html,
+}
+
+pub fn run_cli() -> Result<()> {
+    let cli = Cli::parse();
+    let profile = match &cli.profile {
+        Some(name) => Config::load()?.profile(name)?.clone(),
+        None => Profile::default(),
+    };
+    let glyphs = cli
+        .glyphs
+        .or(profile.glyphs)
+        .unwrap_or(GlyphMode::Auto)
+        .resolve();
+    let json = JsonOptions {
+        style: cli.json_style.or(profile.json_style).unwrap_or_default(),
+        case: cli.json_case.or(profile.json_case).unwrap_or_default(),
+    };
+    let mut output = Output::new(!cli.no_pager);
+    let out = &mut output;
+
+    if cli.verify_data || cfg!(debug_assertions) {
+        verify_data(cli.verify_data)?;
+    }
+
+    if let Some(command) = cli.command {
+        return run_command(command, cli.strict_input, glyphs, json, out);
+    }
+    if let Some(path) = &cli.output {
+        let known = path.extension().is_some_and(|extension| {
+            extension.eq_ignore_ascii_case("pdf") || extension.eq_ignore_ascii_case("ics")
+        });
+        if !known {
+            return Err(anyhow::anyhow!(
+                "--output writes a PDF reading sheet or an iCalendar event; give a file ending in .pdf or .ics"
+            ));
+        }
+    }
+
+    let mut diviner = Diviner::new();
+    // What a --manifest cast is seeded with
+    let seed: u64 = rand::random();
+    let hashed_on = cli
+        .date
+        .unwrap_or_else(|| chrono::Local::now().date_naive());
+
+    let reading = if let Some(input) = cli.input {
+        parse_input(&input, cli.strict_input)?
+    } else if let Some(tosses) = cli.coins {
+        Diviner::cast_reading_from_coins(&tosses, None)?
+    } else if let Some(rolls) = cli.dice {
+        Diviner::cast_reading_from_dice(&rolls, None)?
+    } else if cli.manifest.is_some() {
+        Manifest::cast(cli.method, seed, cli.question.as_deref(), hashed_on)?
+    } else if cli.method == Method::QuestionHash {
+        let question = cli
+            .question
+            .as_deref()
+            .ok_or_else(|| anyhow::anyhow!("--method question-hash needs a --question to hash"))?;
+        Diviner::cast_reading_from_question(question, hashed_on)
+    } else if let Some(method) = cli
+        .method
+        .casting()
+        .filter(|&method| method != casting::Method::Coins)
+    {
+        method.cast(None)
+    } else if cli.rng == RngSource::Os {
+        Diviner::cast_reading_from_os(None)?
+    } else if let Some(seconds) = cli.contemplation {
+        let duration = Duration::try_from_secs_f64(seconds)
+            .map_err(|_| anyhow::anyhow!("Invalid contemplation time: {}", seconds))?;
+        // Like the animation, the indicator goes to stderr
+        let mut rng = if io::stderr().is_terminal() {
+            contemplation::contemplate(duration, glyphs, &mut io::stderr())?
+        } else {
+            contemplation::contemplate(duration, glyphs, &mut io::sink())?
+        };
+        Diviner::cast_reading_with(&mut rng, None)
+    } else if cli.animate && io::stderr().is_terminal() {
+        // The animation goes to stderr so piped output stays clean
+        animation::animated_cast(
+            &mut diviner,
+            None,
+            glyphs,
+            cli.animate_speed.frame_delay(),
+            &mut io::stderr(),
+        )?
+    } else {
+        // No input provided, cast randomly using coins method
+        diviner.cast_reading(None)
+    }
+    .with_question(cli.question)
+    .with_context(read_context(cli.context_file.as_deref())?);
+
+    let focus_line = match cli.focus_line {
+        Some(line) => choose_focus_line(&reading, line)?,
+        None => None,
+    };
+    let topic = cli.topic.or(profile.topic);
+    let mut entry = JournalEntry::new(reading)
+        .with_focus_line(focus_line)
+        .with_recipient(cli.recipient)
+        .with_topic(topic)
+        .with_question_hash((cli.method == Method::QuestionHash).then_some(hashed_on));
+    if cli.almanac || profile.almanac {
+        entry = entry.with_almanac();
+    }
+    if cli.save {
+        Journal::open_default()?.append(&entry)?;
+    }
+    let mut data = load_data()?;
+    let lang = cli.lang.or(profile.lang).unwrap_or_else(|| {
+        entry
+            .reading
+            .question()
+            .map(Lang::detect)
+            .unwrap_or_default()
+    });
+    if lang == Lang::Zh && !cli.bilingual {
+        if let Some(chinese) = Translation::chinese(&data)? {
+            chinese.apply(&mut data);
+        }
+    }
+    if let Some(target) = cli.log_to {
+        let mut record = Record::for_reading(&entry.reading, &data);
+        if cli.log_question {
+            record = record.with_question(&entry.reading);
+        }
+        target.sink().send(&record)?;
+    }
+    let watched = watchlist::matches(&Watchlist::open_default()?.load()?, &entry.reading);
+    if let Some(path) = cli.output {
+        let is_ics = path
+            .extension()
+            .is_some_and(|extension| extension.eq_ignore_ascii_case("ics"));
+        let content = if is_ics {
+            ical::reading_calendar(&entry, &data, cli.remind).into_bytes()
+        } else {
+            let cast_on = entry.timestamp.with_timezone(&chrono::Local).date_naive();
+            pdf::reading_sheet(&entry.reading, &data, cast_on)
+        };
+        std::fs::write(&path, content)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+        if cli.exit_if_watched && !watched.is_empty() {
+            std::process::exit(watchlist::WATCHED_EXIT_CODE);
+        }
+        return Ok(());
+    }
+    if cli.bilingual {
+        let chinese = Translation::chinese(&data)?.ok_or_else(|| {
+            anyhow::anyhow!(
+                "--bilingual needs the Chinese corpus: add {}.json to the translations directory (see README)",
+                translations::CHINESE
+            )
+        })?;
+        let bilingual = translations::compare(
+            &entry.reading,
+            &data,
+            &[Translation::wilhelm(&data), chinese],
+            Layout::Interleaved,
+            banner::terminal_width(),
+            glyphs,
+        );
+        write!(out, "{}", bilingual)?;
+        return Ok(());
+    }
+    if let Some(names) = &cli.compare_translations {
+        let translations = names
+            .iter()
+            .map(|name| Translation::load(name, &data))
+            .collect::<Result<Vec<_>>>()?;
+        let comparison = translations::compare(
+            &entry.reading,
+            &data,
+            &translations,
+            cli.compare_layout,
+            banner::terminal_width(),
+            glyphs,
+        );
+        write!(out, "{}", comparison)?;
+        return Ok(());
+    }
+    let format = cli.format.or(profile.format).unwrap_or(Format::Full);
+    let style = cli.style.or(profile.style).unwrap_or(Style::Standard);
+    let only = cli.sections.or(profile.sections);
+    let selects = |part| only.as_ref().is_some_and(|parts| parts.contains(&part));
+    let sections = FullOptions {
+        include_guidance: cli.guidance || profile.guidance,
+        include_pair: cli.pair || profile.pair || selects(Part::Pair),
+        include_wings: cli.wings || profile.wings || selects(Part::Wings),
+        topic,
+        notes: match format {
+            Format::Full => Notes::open_default()?.load()?,
+            _ => HexagramNotes::new(),
+        },
+        spirits_date: (style == Style::Liuyao).then(|| {
+            cli.date
+                .unwrap_or_else(|| entry.timestamp.with_timezone(&chrono::Local).date_naive())
+        }),
+        text_only: cli.text_only.or(profile.text_only),
+        lang,
+        links: match format {
+            Format::Full => Config::load()?.links.active(),
+            _ => None,
+        },
+        rule: cli.rule.or(profile.rule).unwrap_or_default(),
+        focus_line,
+        only,
+        prompts: match format {
+            Format::Worksheet => Prompts::load_default()?,
+            _ => Prompts::default(),
+        },
+    };
+    let options = RenderOptions {
+        glyphs,
+        color: false,
+        sections,
+        width: banner::terminal_width(),
+        json,
+    };
+    // The watchlist isn't in a manifest, so a --manifest cast doesn't print
+    // the label, keeping the output to what `reproduce` can check
+    let labels_watched = cli.manifest.is_none() && matches!(format, Format::Full | Format::Brief);
+    if let Some(path) = &cli.manifest {
+        let rendering = Rendering::of(format.clone(), &options);
+        let mut printed = Vec::new();
+        write_reading(
+            &mut printed,
+            &entry,
+            format,
+            &data,
+            &rendering.options(&entry),
+        )?;
+        Manifest::new(&entry, cli.method, seed, rendering, &printed).save(path)?;
+        out.write_all(&printed)?;
+    } else {
+        write_reading(out, &entry, format, &data, &options)?;
+    }
+
+    if !watched.is_empty() {
+        if labels_watched {
+            writeln!(out, "{}", format_watched(&watched, &data, glyphs)?)?;
+        }
+        if cli.exit_if_watched {
+            // Let the pager finish before exiting
+            drop(output);
+            std::process::exit(watchlist::WATCHED_EXIT_CODE);
+        }
+    }
+    Ok(())
+}
+
+/// The changing line to focus on: `line` if it's one of the reading's
+/// changing lines, or else one chosen at a prompt on stderr; none if the
+/// prompt is left blank
+fn choose_focus_line(reading: &Reading, line: Option<u8>) -> Result<Option<u8>> {
+    let changing = reading.changing_line_positions();
+    if changing.is_empty() {
+        return Err(anyhow::anyhow!(
+            "--focus-line needs a changing line, and this reading has none"
+        ));
+    }
+    match line {
+        Some(line) if changing.contains(&line) => Ok(Some(line)),
+        Some(line) => Err(anyhow::anyhow!(
+            "Line {} isn't changing; the changing lines are {}",
+            line,
+            join_positions(&changing)
+        )),
+        None if changing.len() == 1 => Ok(Some(changing[0])),
+        None => Ok(prompt_focus_line(
+            &changing,
+            &mut io::stdin().lock(),
+            &mut io::stderr(),
+        )?),
+    }
+}
+
+/// Ask which of the `changing` lines is central until one is named; a blank
+/// answer or the end of input chooses none
+fn prompt_focus_line(
+    changing: &[u8],
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<Option<u8>> {
+    loop {
+        write!(
+            out,
+            "Changing lines: {}. Which is central to your question? ",
+            join_positions(changing)
+        )?;
+        out.flush()?;
+        let mut answer = String::new();
+        if input.read_line(&mut answer)? == 0 || answer.trim().is_empty() {
+            return Ok(None);
+        }
+        match answer.trim().parse() {
+            Ok(line) if changing.contains(&line) => return Ok(Some(line)),
+            _ => writeln!(out, "Give one of the changing lines, or nothing to skip.")?,
+        }
+    }
+}
+
+/// Line positions for a message, e.g. "2, 5"
+fn join_positions(positions: &[u8]) -> String {
+    positions
+        .iter()
+        .map(u8::to_string)
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// The line marking watched hexagrams in a reading, e.g.
+/// "Watched: ䷜ 29 Darkness (primary)"
+fn format_watched(watched: &[(u8, bool)], data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let labels: Vec<String> = watched
+        .iter()
+        .map(|&(number, primary)| {
+            let name = data.get_hexagram(number).map_or_else(
+                || number.to_string(),
+                |hexagram| {
+                    format!(
+                        "{}{} {}",
+                        glyphs.prefix(&hexagram.unicode),
+                        number,
+                        hexagram.name
+                    )
+                },
+            );
+            let role = if primary { "primary" } else { "transformed" };
+            format!("{} ({})", name, role)
+        })
+        .collect();
+    Ok(format!("Watched: {}", labels.join(", ")))
+}
+
+fn run_command(
+    command: Command,
+    strict: bool,
+    glyphs: Glyphs,
+    json: JsonOptions,
+    out: &mut Output,
+) -> Result<()> {
+    let data = load_data()?;
+    match command {
+        Command::Pair { hexagram } => {
+            writeln!(out, "{}", format_pair(hexagram, &data, glyphs)?)?;
+        }
+        Command::Batch { file, format, save } => run_batch(&file, format, save, &data, json, out)?,
+        Command::Journal { recipient, action } => {
+            run_journal_command(action, recipient.as_deref(), &data, glyphs, out)?
+        }
+        Command::Sequence { hexagrams } => {
+            let numbers = parse_hexagram_list(&hexagrams)?;
+            writeln!(out, "{}", format_sequence(&numbers, &data, glyphs)?)?;
+        }
+        Command::Find {
+            upper,
+            lower,
+            element,
+            yang,
+            changing,
+            keywords,
+        } => {
+            let query = Query {
+                upper,
+                lower,
+                element,
+                yang,
+                keywords,
+            };
+            writeln!(out, "{}", format_find(&query, &changing, &data, glyphs)?)?;
+        }
+        #[cfg(feature = "tui")]
+        Command::Browse => crate::browse::run(&data, glyphs)?,
+        Command::Bagua {
+            reading,
+            arrangement,
+            svg,
+        } => {
+            let mut compass = Compass::new(&data, arrangement)?;
+            if let Some(reading) = reading {
+                compass = compass.with_reading(&parse_input(&reading, strict)?, &data, glyphs);
+            }
+            if svg {
+                write!(out, "{}", compass.to_svg())?;
+            } else {
+                write!(out, "{}", compass.to_text(glyphs))?;
+            }
+        }
+        Command::Card {
+            input,
+            output,
+            size,
+            background,
+            foreground,
+            accent,
+        } => {
+            let reading = match input {
+                Some(input) => parse_input(&input, strict)?,
+                None => Diviner::new().cast_reading(None),
+            };
+            let configured = Config::load()?.card;
+            let theme = Theme {
+                background: background.unwrap_or(configured.background),
+                foreground: foreground.unwrap_or(configured.foreground),
+                accent: accent.unwrap_or(configured.accent),
+            };
+            run_card(&reading, &data, &output, size, theme)?;
+        }
+        Command::Encode { input, question } => {
+            let reading = match input {
+                Some(input) => parse_input(&input, strict)?,
+                None => Diviner::new().cast_reading(None),
+            }
+            .with_question(question);
+            writeln!(out, "{}", Permalink::new(&reading))?;
+        }
+        Command::Decode {
+            token,
+            question,
+            format,
+        } => {
+            let permalink: Permalink = token.parse()?;
+            if let Some(question) = &question {
+                if !permalink.matches_question(question) {
+                    return Err(anyhow::anyhow!(
+                        "The question doesn't match the one this reading was cast for"
+                    ));
+                }
+            }
+            let entry = JournalEntry::new(permalink.to_reading(question)?);
+            let options = RenderOptions {
+                glyphs,
+                width: banner::terminal_width(),
+                json,
+                ..RenderOptions::default()
+            };
+            write_reading(out, &entry, format, &data, &options)?;
+        }
+        Command::Hour { at, format } => {
+            let hour = HourGua::at(at.unwrap_or_else(|| chrono::Local::now().naive_local()))?;
+            writeln!(out, "{}\n", hour.describe())?;
+            let options = RenderOptions {
+                glyphs,
+                width: banner::terminal_width(),
+                json,
+                ..RenderOptions::default()
+            };
+            write_reading(
+                out,
+                &JournalEntry::new(hour.reading),
+                format,
+                &data,
+                &options,
+            )?;
+        }
+        Command::Dashboard { at, format } => {
+            let entries = Journal::open_default()?.entries()?;
+            let dashboard = Dashboard::new(
+                at.unwrap_or_else(|| chrono::Local::now().naive_local()),
+                dashboard::latest_personal(&entries),
+                &data,
+                glyphs,
+            )?;
+            match format {
+                DashboardFormat::Text => {
+                    write!(out, "{}", dashboard.to_text(banner::terminal_width()))?
+                }
+                DashboardFormat::Json => writeln!(out, "{}", json.to_string(&dashboard)?)?,
+            }
+        }
+        Command::Diff { a, b, format } => {
+            let diff = ReadingDiff::new(&read_reading_file(&a)?, &read_reading_file(&b)?);
+            match format {
+                DiffFormat::Text => write!(out, "{}", diff.to_text(&data, glyphs)?)?,
+                DiffFormat::Json => writeln!(out, "{}", json.to_string(&diff)?)?,
+            }
+        }
+        Command::Reproduce { manifest } => {
+            let manifest = Manifest::load(&manifest)?;
+            // The same translation as the cast, which applied it for Chinese
+            let mut data = data;
+            if manifest.rendering.lang == Lang::Zh {
+                if let Some(chinese) = Translation::chinese(&data)? {
+                    chinese.apply(&mut data);
+                }
+            }
+            write!(out, "{}", manifest.reproduce(&data)?)?
+        }
+        Command::Wings { section } => writeln!(out, "{}", format_wings(section, &data, glyphs)?)?,
+        Command::Unicode => writeln!(out, "{}", format_unicode_table(&data)?)?,
+        Command::Convert { from, to, values } => {
+            let values = if values.is_empty() {
+                io::stdin().lines().collect::<io::Result<Vec<_>>>()?
+            } else {
+                values
+            };
+            for value in values.iter().map(|value| value.trim()) {
+                if value.is_empty() {
+                    continue;
+                }
+                writeln!(out, "{}", to.format(from.parse(value)?))?;
+            }
+        }
+        Command::Say {
+            hexagram,
+            ssml,
+            engine,
+        } => run_say(hexagram, ssml, engine, &data, glyphs, out)?,
+        Command::Serve {
+            listen,
+            systemd_socket,
+            print_unit,
+        } => {
+            if print_unit {
+                write_units(systemd::Service::Http, &listen, out)?;
+            } else if systemd_socket {
+                remote::Server::with_data(Arc::new(data))
+                    .serve_listener(systemd::tcp_listener()?)?;
+            } else {
+                remote::Server::with_data(Arc::new(data)).serve(&listen)?;
+            }
+        }
+        Command::Remote {
+            url,
+            input,
+            question,
+            format,
+        } => {
+            let reading = Remote::new(&url).cast(&CastRequest { input, question })?;
+            match format {
+                RemoteFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&reading)?)?,
+                RemoteFormat::Brief => {
+                    write!(out, "{}", format_brief_from_json(&reading, glyphs)?)?
+                }
+                RemoteFormat::Full => write!(out, "{}", format_full_from_json(&reading, glyphs)?)?,
+            }
+        }
+        Command::Play {
+            input,
+            question,
+            sound,
+            output,
+        } => {
+            let reading = match input {
+                Some(input) => parse_input(&input, strict)?,
+                None => Diviner::new().cast_reading(None),
+            }
+            .with_question(question);
+            run_play(&reading, sound, output, &data, glyphs, out)?
+        }
+        Command::Cast {
+            lines,
+            save,
+            question,
+        } => {
+            let drafts = Drafts::open_default()?;
+            if drafts.names()?.contains(&save) {
+                return Err(anyhow::anyhow!(
+                    "A draft named '{}' already exists; finish it with `i-ching resume {}` or pick another name",
+                    save,
+                    save
+                ));
+            }
+            let mut draft = PartialReading::new(question);
+            draft.cast(lines.into());
+            drafts.save(&save, &draft)?;
+            write!(out, "{}", format_draft(&save, &draft, glyphs))?;
+        }
+        Command::Resume { name, lines, save } => run_resume(name, lines, save, &data, glyphs, out)?,
+        Command::Notify {
+            question,
+            save,
+            log_to,
+            log_question,
+            dry_run,
+        } => run_notify(question, save, log_to, log_question, dry_run, &data, out)?,
+        Command::Notes { action } => run_notes_command(action, &data, glyphs, out)?,
+        Command::Watch { hexagrams, remove } => run_watch(&hexagrams, remove, &data, glyphs, out)?,
+        Command::Motd { action } => run_motd_command(action, &data, glyphs, out)?,
+        Command::Status { refresh } => {
+            let reading =
+                status::reading(&status::default_cache_path(), refresh, chrono::Local::now())?;
+            writeln!(out, "{}", status::segment(&reading, glyphs))?;
+        }
+        Command::Daemon {
+            socket,
+            systemd_socket,
+            print_unit,
+        } => {
+            if print_unit {
+                write_units(systemd::Service::Daemon, "", out)?;
+            } else if systemd_socket {
+                serve_daemon_from_systemd(Daemon::with_data(Arc::new(data)))?;
+            } else {
+                let socket = match socket {
+                    Some(socket) => socket,
+                    None => daemon::default_socket_path()?,
+                };
+                Daemon::with_data(Arc::new(data)).serve(&socket)?;
+            }
+        }
+        Command::Client { request, socket } => {
+            let socket = match socket {
+                Some(socket) => socket,
+                None => daemon::default_socket_path()?,
+            };
+            match request {
+                Some(request) => daemon::send(&socket, request.as_bytes(), out)?,
+                None => daemon::send(&socket, io::stdin().lock(), out)?,
+            }
+        }
+        #[cfg(feature = "llm")]
+        Command::Interpret {
+            input,
+            question,
+            context_file,
+            provider,
+            model,
+            endpoint,
+            prompt_template,
+        } => {
+            let reading = match input {
+                Some(input) => parse_input(&input, strict)?,
+                None => Diviner::new().cast_reading(None),
+            }
+            .with_question(question)
+            .with_context(read_context(context_file.as_deref())?);
+            let config = Config::load()?.llm;
+            let interpreter = Interpreter::new(
+                provider.or(config.provider).unwrap_or_default(),
+                model.or(config.model),
+                endpoint.or(config.endpoint),
+            );
+            let template = match prompt_template.or(config.prompt_template) {
+                Some(path) => std::fs::read_to_string(&path).map_err(|e| {
+                    anyhow::anyhow!("Failed to read prompt template {}: {}", path.display(), e)
+                })?,
+                None => llm::DEFAULT_PROMPT.to_string(),
+            };
+
+            let prompt = llm::render_prompt(
+                &template,
+                reading.question(),
+                reading.context(),
+                &serde_json::to_string_pretty(&json_reading_with_data(&reading, &data)?)?,
+            );
+            let interpretation = interpreter.interpret(&prompt)?;
+            writeln!(
+                out,
+                "{}\n",
+                format_brief_with_data(&reading, &data, glyphs)?
+            )?;
+            writeln!(out, "{}", interpretation)?;
+        }
+        #[cfg(feature = "self-update")]
+        Command::SelfUpdate { check, force } => run_self_update(check, force, out)?,
+        Command::Selftest { lines } => run_selftest(lines, out)?,
+        Command::Enumerate { format, hexagram } => {
+            run_enumerate(format, hexagram, &data, glyphs, out)?
+        }
+        Command::Stats {
+            method,
+            casts,
+            journal,
+        } => run_stats(method, casts, journal, out)?,
+        Command::Wizard { save } => run_wizard(save, &data, glyphs, out)?,
+        Command::Group { action } => run_group_command(action, &data, glyphs, out)?,
+        Command::Dump { format, output } => run_dump(format, output.as_deref(), &data, out)?,
+        Command::Relations { format } => {
+            let matrix = Matrix::new(&data)?;
+            match format {
+                RelationsFormat::Json => writeln!(out, "{}", matrix.to_json()?)?,
+                RelationsFormat::Dot => write!(out, "{}", matrix.to_dot())?,
+            }
+        }
+        Command::History { hexagram } => {
+            write!(out, "{}", format_history(hexagram, &data, glyphs)?)?
+        }
+        Command::Xref { line } => write!(out, "{}", format_xref(line, &data, glyphs)?)?,
+        Command::Explain { term } => write!(out, "{}", explain(term.as_deref())?)?,
+        Command::Sources { format } => {
+            let translations: Vec<(String, Option<Provenance>)> = translations::available()
+                .into_iter()
+                .filter(|name| name != translations::BUILT_IN)
+                .map(|name| {
+                    let provenance = Translation::load(&name, &data)
+                        .ok()
+                        .and_then(|translation| translation.provenance);
+                    (name, provenance)
+                })
+                .collect();
+            match format {
+                SourcesFormat::Text => {
+                    write!(out, "{}", format_sources(data.provenance(), &translations))?
+                }
+                SourcesFormat::Json => {
+                    let translations: Vec<_> = translations
+                        .iter()
+                        .map(|(name, provenance)| {
+                            serde_json::json!({"name": name, "provenance": provenance})
+                        })
+                        .collect();
+                    let json = serde_json::json!({
+                        "corpora": data.provenance(),
+                        "translations": translations,
+                    });
+                    writeln!(out, "{}", serde_json::to_string_pretty(&json)?)?;
+                }
+            }
+        }
+        Command::FortuneDb {
+            texts,
+            no_attribution,
+            output,
+        } => {
+            let file = fortune::database(&data, &texts, !no_attribution);
+            match output {
+                Some(path) => {
+                    let mut index_path = path.clone().into_os_string();
+                    index_path.push(".dat");
+                    let index_path = PathBuf::from(index_path);
+                    std::fs::write(&path, &file).map_err(|e| {
+                        anyhow::anyhow!("Failed to write {}: {}", path.display(), e)
+                    })?;
+                    std::fs::write(&index_path, fortune::strfile(&file)).map_err(|e| {
+                        anyhow::anyhow!("Failed to write {}: {}", index_path.display(), e)
+                    })?;
+                }
+                None => write!(out, "{}", file)?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// The data readings and commands draw on, with hexagrams looked up in the
+/// SQLite store when built with the `sqlite` feature
+fn load_data() -> Result<IChingData> {
+    #[cfg(feature = "sqlite")]
+    {
+        Store::open(&store::default_path()?)?.data()
+    }
+    #[cfg(not(feature = "sqlite"))]
+    {
+        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))
+    }
+}
+
+/// Load the data, checksummed on the way in, and refuse to go on if its
+/// records contradict each other; `report` says on stderr what was checked
+fn verify_data(report: bool) -> Result<()> {
+    let data =
+        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+    let problems = verify::verify(&data);
+    if !problems.is_empty() {
+        return Err(anyhow::anyhow!(
+            "The I Ching data is inconsistent:\n  {}",
+            problems.join("\n  ")
+        ));
+    }
+    if report {
+        let checksums: Vec<String> = data::checksums()
+            .iter()
+            .map(|(name, sum)| format!("{} crc32 {:08x}", name, sum))
+            .collect();
+        eprintln!(
+            "Data verified: 8 trigrams and 64 hexagrams consistent; {}",
+            checksums.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Write a share card, its format chosen by the file's extension
+fn run_card(
+    reading: &Reading,
+    data: &IChingData,
+    output: &Path,
+    size: u32,
+    theme: Theme,
+) -> Result<()> {
+    if !(16..=4096).contains(&size) {
+        return Err(anyhow::anyhow!("--size must be 16-4096 pixels"));
+    }
+    let card = Card::new(reading, data, chrono::Local::now().date_naive(), theme);
+    let extension = output
+        .extension()
+        .and_then(|extension| extension.to_str())
+        .map(str::to_lowercase);
+    let content = match extension.as_deref() {
+        Some("png") => card.to_png(size)?,
+        Some("svg") => card.to_svg(size).into_bytes(),
+        _ => {
+            return Err(anyhow::anyhow!(
+                "Cards are PNG or SVG; give a file ending in .png or .svg"
+            ));
+        }
+    };
+    std::fs::write(output, content)
+        .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", output.display(), e))
+}
+
+/// A reading from `--input`, saying on stderr which meaning an ambiguous input
+/// was given, or refusing it when `strict`
+fn parse_input(input: &str, strict: bool) -> Result<Reading> {
+    match parse::ambiguity(input) {
+        Some(ambiguity) if strict => {
+            return Err(anyhow::anyhow!(
+                "Ambiguous input '{}': {}",
+                input.trim(),
+                ambiguity
+            ));
+        }
+        Some(ambiguity) => eprintln!(
+            "Note: reading '{}' as {}, not {} (--strict-input makes this an error)",
+            input.trim(),
+            ambiguity.chosen,
+            ambiguity.alternative
+        ),
+        None => {}
+    }
+    parse::parse(input)?.to_reading()
+}
+
+/// The reading in a JSON file (`-` for stdin): `--format json` output, a
+/// journal entry, or a bare stored reading, in any schema version
+fn read_reading_file(file: &str) -> Result<Reading> {
+    let content = if file == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Failed to read a reading from {}: {}", file, e))?
+    };
+    let mut value: serde_json::Value = serde_json::from_str(&content)
+        .map_err(|e| anyhow::anyhow!("{} is not JSON: {}", file, e))?;
+    if value.get("primary_hexagram").is_some() {
+        let json: JsonReading = serde_json::from_value(value)?;
+        return reading_from_json(&json);
+    }
+    if let Some(reading) = value.get_mut("reading") {
+        value = reading.take();
+    }
+    Reading::try_from(value).map_err(|e| anyhow::anyhow!("{} doesn't hold a reading: {}", file, e))
+}
+
+/// The context paragraph in a file (`-` for stdin), or `None` if no file was
+/// given or it's blank
+fn read_context(file: Option<&str>) -> Result<Option<String>> {
+    let Some(file) = file else {
+        return Ok(None);
+    };
+    let content = if file == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Failed to read context from {}: {}", file, e))?
+    };
+
+    let context = content.trim();
+    Ok((!context.is_empty()).then(|| context.to_string()))
+}
+
+fn run_wizard(save: bool, data: &IChingData, glyphs: Glyphs, out: &mut Output) -> Result<()> {
+    // Prompts go to stderr so only the reading reaches stdout
+    let (structured, question) = wizard::ask(&mut io::stdin().lock(), &mut io::stderr())?;
+    let options = RenderOptions {
+        glyphs,
+        sections: FullOptions {
+            topic: structured.topic,
+            notes: Notes::open_default()?.load()?,
+            ..Default::default()
+        },
+        ..RenderOptions::default()
+    };
+
+    let reading = Diviner::new().cast_reading(Some(question));
+    let entry = JournalEntry::new(reading).with_structured_question(structured);
+    if save {
+        Journal::open_default()?.append(&entry)?;
+    }
+    write_reading(out, &entry, Format::Full, data, &options)
+}
+
+fn run_group_command(
+    action: GroupCommand,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let GroupCommand::Start {
+        title,
+        file,
+        pause,
+        output,
+        save,
+    } = action;
+    let pause = Duration::try_from_secs_f64(pause)
+        .map_err(|_| anyhow::anyhow!("Invalid pause: {}", pause))?;
+    let participants = match file {
+        Some(path) => group::parse(
+            &std::fs::read_to_string(&path)
+                .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", path.display(), e))?,
+        )?,
+        // Prompts and progress go to stderr so only the document reaches stdout
+        None => group::ask(&mut io::stdin().lock(), &mut io::stderr())?,
+    };
+    if participants.is_empty() {
+        return Err(anyhow::anyhow!("No participants, so nothing to cast"));
+    }
+    let journal = if save {
+        Some(Journal::open_default()?)
+    } else {
+        None
+    };
+
+    let total = participants.len();
+    let mut readings = Vec::new();
+    for (i, participant) in participants.into_iter().enumerate() {
+        eprintln!(
+            "\n{}/{} {}: {}",
+            i + 1,
+            total,
+            participant.name,
+            participant.question
+        );
+        let mut rng = if io::stderr().is_terminal() {
+            contemplation::contemplate(pause, glyphs, &mut io::stderr())?
+        } else {
+            contemplation::contemplate(pause, glyphs, &mut io::sink())?
+        };
+        let reading = Diviner::cast_reading_with(&mut rng, Some(participant.question));
+        eprintln!("{}", format_brief_summary(&reading, data, glyphs));
+        if let Some(journal) = &journal {
+            journal.append(
+                &JournalEntry::new(reading.clone()).with_recipient(Some(participant.name.clone())),
+            )?;
+        }
+        readings.push((participant.name, reading));
+    }
+
+    let session = group::Session {
+        title,
+        date: chrono::Local::now().date_naive(),
+        readings,
+    };
+    let document = session.to_markdown(data)?;
+    match output {
+        Some(path) => {
+            std::fs::write(&path, document)
+                .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?;
+            eprintln!("\nSession written to {}", path.display());
+        }
+        None => write!(out, "{}", document)?,
+    }
+    Ok(())
+}
+
+/// A local date and time as `--at` takes it
+fn parse_local_time(text: &str) -> Result<chrono::NaiveDateTime, String> {
+    chrono::NaiveDateTime::parse_from_str(text.trim(), "%Y-%m-%d %H:%M")
+        .map_err(|_| format!("expected YYYY-MM-DD HH:MM, got '{}'", text))
+}
+
+fn run_dump(
+    format: DumpFormat,
+    output: Option<&Path>,
+    data: &IChingData,
+    out: &mut Output,
+) -> Result<()> {
+    let corpus = Corpus::new(data)?;
+
+    let text = match format {
+        DumpFormat::Json => corpus.to_json()?,
+        DumpFormat::Toml => corpus.to_toml()?,
+        DumpFormat::Sqlite => {
+            let path = output.ok_or_else(|| {
+                anyhow::anyhow!("SQLite output needs a file: pass --output <path>")
+            })?;
+            return corpus.write_sqlite(path);
+        }
+    };
+    match output {
+        Some(path) => std::fs::write(path, text)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?,
+        None => writeln!(out, "{}", text.trim_end())?,
+    }
+
+    Ok(())
+}
+
+fn run_batch(
+    file: &str,
+    format: BatchFormat,
+    save: bool,
+    data: &IChingData,
+    json: JsonOptions,
+    out: &mut Output,
+) -> Result<()> {
+    let content = if file == "-" {
+        std::io::read_to_string(std::io::stdin())?
+    } else {
+        std::fs::read_to_string(file)
+            .map_err(|e| anyhow::anyhow!("Failed to read questions from {}: {}", file, e))?
+    };
+
+    let questions = parse_question_list(&content);
+    if questions.is_empty() {
+        return Err(anyhow::anyhow!("No questions found in {}", file));
+    }
+
+    let readings: Vec<Reading> = Diviner::new()
+        .cast_many(questions.len())
+        .into_iter()
+        .zip(questions)
+        .map(|(reading, question)| reading.with_question(Some(question)))
+        .collect();
+
+    if save {
+        let journal = Journal::open_default()?;
+        for reading in &readings {
+            journal.append(&JournalEntry::new(reading.clone()))?;
+        }
+    }
+
+    match format {
+        BatchFormat::Json => {
+            let json_readings = readings
+                .iter()
+                .map(|reading| json_reading_with_data(reading, data))
+                .collect::<Result<Vec<_>>>()?;
+            writeln!(out, "{}", json.to_string(&json_readings)?)?;
+        }
+        BatchFormat::Markdown => {
+            writeln!(out, "{}", format_markdown_report(&readings, data)?)?;
+        }
+        BatchFormat::Fortune => {
+            for reading in &readings {
+                write!(
+                    out,
+                    "{}{}",
+                    fortune::fortune(reading, data),
+                    fortune::SEPARATOR
+                )?;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Questions from a list, one per line, ignoring blank lines and `#` comments
+fn parse_question_list(content: &str) -> Vec<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(str::to_string)
+        .collect()
+}
+
+fn run_journal_command(
+    action: JournalCommand,
+    recipient: Option<&str>,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let journal = Journal::open_default()?;
+    let for_recipient = |mut entries: Vec<JournalEntry>| {
+        if let Some(name) = recipient {
+            entries.retain(|entry| entry.is_for(name));
+        }
+        entries
+    };
+
+    match action {
+        JournalCommand::List => {
+            // Numbered across the whole journal, so `resolve` finds the same entry
+            let entries = journal.entries()?;
+            let numbered: Vec<(usize, &JournalEntry)> = (1..)
+                .zip(&entries)
+                .filter(|(_, entry)| recipient.is_none_or(|name| entry.is_for(name)))
+                .collect();
+            if numbered.is_empty() {
+                writeln!(
+                    out,
+                    "No saved readings in {} (cast with --save to record one)",
+                    journal.path().display()
+                )?;
+            }
+            for (number, entry) in numbered {
+                writeln!(
+                    out,
+                    "{:>4}  {}",
+                    number,
+                    format_journal_line(entry, data, glyphs)?
+                )?;
+            }
+        }
+        JournalCommand::Due => {
+            let due = for_recipient(journal.due(chrono::Local::now().date_naive())?);
+            if due.is_empty() {
+                writeln!(out, "No saved readings are due for a second look")?;
+            }
+            for entry in &due {
+                let revisit_on = entry.revisit_on.unwrap_or_default();
+                writeln!(
+                    out,
+                    "{}  (revisit {})",
+                    format_journal_line(entry, data, glyphs)?,
+                    revisit_on
+                )?;
+            }
+        }
+        JournalCommand::Search { query } => {
+            let query = query.join(" ");
+            let mut hits = journal.search(&query)?;
+            if let Some(name) = recipient {
+                hits.retain(|hit| hit.entry.is_for(name));
+            }
+            if hits.is_empty() {
+                writeln!(out, "No saved readings match \"{}\"", query)?;
+            }
+            let (open, close) = if io::stdout().is_terminal() {
+                (MATCH_STYLE, RESET_STYLE)
+            } else {
+                ("[", "]")
+            };
+            for hit in &hits {
+                writeln!(out, "{}", format_journal_line(&hit.entry, data, glyphs)?)?;
+                if let Some(snippet) = &hit.snippet {
+                    writeln!(out, "    {}", snippet.highlight(open, close))?;
+                }
+            }
+        }
+        JournalCommand::Export { format } => {
+            let entries = for_recipient(journal.entries()?);
+            match format {
+                ExportFormat::Csv => write!(out, "{}", format_delimited(&entries, ','))?,
+                ExportFormat::Tsv => write!(out, "{}", format_delimited(&entries, '\t'))?,
+                ExportFormat::Json => writeln!(out, "{}", serde_json::to_string_pretty(&entries)?)?,
+                ExportFormat::EmbeddingInput => {
+                    write!(out, "{}", embedding::to_jsonl(&entries, data)?)?
+                }
+            }
+        }
+        JournalCommand::Import { file, format } => {
+            let content = if file == "-" {
+                io::read_to_string(io::stdin())?
+            } else {
+                std::fs::read_to_string(&file)
+                    .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file, e))?
+            };
+            let entries = format.importer(&content).import(&content)?;
+
+            // Exports may round timestamps to the second
+            let existing: std::collections::HashSet<(i64, [u8; 6])> = journal
+                .entries()?
+                .iter()
+                .map(|entry| {
+                    (
+                        entry.timestamp.timestamp(),
+                        entry.reading.traditional_numbers(),
+                    )
+                })
+                .collect();
+            let mut imported = 0;
+            for entry in &entries {
+                let key = (
+                    entry.timestamp.timestamp(),
+                    entry.reading.traditional_numbers(),
+                );
+                if existing.contains(&key) {
+                    continue;
+                }
+                let entry = match (&entry.recipient, recipient) {
+                    (None, Some(name)) => entry.clone().with_recipient(Some(name.to_string())),
+                    _ => entry.clone(),
+                };
+                journal.append(&entry)?;
+                imported += 1;
+            }
+            writeln!(
+                out,
+                "Imported {} of {} readings into {}",
+                imported,
+                entries.len(),
+                journal.path().display()
+            )?;
+        }
+        JournalCommand::Report { accuracy: true, .. } => {
+            let accuracy = Accuracy::new(&for_recipient(journal.entries()?));
+            write!(out, "{}", format_accuracy(&accuracy))?;
+        }
+        JournalCommand::Resolve {
+            number,
+            resolved,
+            accuracy,
+            on,
+            note,
+        } => {
+            let number = match number {
+                Some(number) => number,
+                None => journal.entries()?.len(),
+            };
+            let outcome = Outcome {
+                resolved,
+                accuracy,
+                resolved_on: on.or_else(|| resolved.then(|| chrono::Local::now().date_naive())),
+                note,
+            };
+            let entry = journal.set_outcome(number, outcome)?;
+            writeln!(
+                out,
+                "{:>4}  {}",
+                number,
+                format_journal_line(&entry, data, glyphs)?
+            )?;
+        }
+        JournalCommand::Migrate { dry_run } => {
+            let migration = journal.migrate(dry_run)?;
+            let version = crate::core::schema::CURRENT;
+            if migration.upgraded == 0 {
+                writeln!(
+                    out,
+                    "All {} entries are already in reading format version {}",
+                    migration.entries, version
+                )?;
+            } else if dry_run {
+                writeln!(
+                    out,
+                    "{} of {} entries would be upgraded to reading format version {}",
+                    migration.upgraded, migration.entries, version
+                )?;
+            } else {
+                writeln!(
+                    out,
+                    "Upgraded {} of {} entries to reading format version {}",
+                    migration.upgraded, migration.entries, version
+                )?;
+            }
+        }
+        JournalCommand::Report { format, .. } => {
+            let report = Report::new(&for_recipient(journal.entries()?));
+            match format {
+                ReportFormat::Text => write!(out, "{}", format_report(&report, data, glyphs))?,
+                ReportFormat::Csv => write!(out, "{}", format_report_csv(&report, data))?,
+                ReportFormat::Html => write!(out, "{}", format_report_html(&report, data))?,
+            }
+        }
+        #[cfg(feature = "encryption")]
+        JournalCommand::Lock { key_file } => {
+            let key = match &key_file {
+                Some(path) if path.exists() => Key::read_key_file(path)?,
+                Some(path) => {
+                    let key = Key::create_key_file(path)?;
+                    writeln!(
+                        out,
+                        "Wrote a new key to {}; the journal can't be read without it",
+                        path.display()
+                    )?;
+                    key
+                }
+                None => Key::passphrase(true)?,
+            };
+            journal.lock(&key)?;
+            writeln!(out, "Locked {}", journal.path().display())?;
+            if let Some(path) = key_file {
+                writeln!(
+                    out,
+                    "Set {}={} to read and save readings",
+                    encryption::KEY_FILE_ENV,
+                    path.display()
+                )?;
+            }
+        }
+        #[cfg(feature = "encryption")]
+        JournalCommand::Unlock { key_file } => {
+            let key = match key_file {
+                Some(path) => Key::read_key_file(&path)?,
+                None => Key::from_env()?,
+            };
+            journal.unlock(&key)?;
+            writeln!(out, "Unlocked {}", journal.path().display())?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_motd_command(
+    action: MotdCommand,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    match action {
+        MotdCommand::Render { width, date } => {
+            let reading =
+                motd::daily_reading(date.unwrap_or_else(|| chrono::Local::now().date_naive()));
+            let judgment = data
+                .get_hexagram(reading.primary_hexagram())
+                .map(|hexagram| hexagram.judgment.text.as_str())
+                .unwrap_or_default();
+            let width = width.unwrap_or_else(banner::terminal_width);
+            writeln!(
+                out,
+                "{}",
+                motd::fit(
+                    &format_motd_with_data(&reading, data, glyphs)?,
+                    judgment,
+                    width
+                )
+            )?;
+        }
+        MotdCommand::Install {
+            systemd,
+            print,
+            width,
+        } => {
+            let exe = std::env::current_exe()?;
+            let (files, next_step) = if systemd {
+                let (service, timer) = motd::systemd_units(&exe, width);
+                let dir = Path::new(motd::SYSTEMD_DIR);
+                (
+                    vec![
+                        (dir.join("i-ching-motd.service"), service),
+                        (dir.join("i-ching-motd.timer"), timer),
+                    ],
+                    "Then run: sudo systemctl daemon-reload && sudo systemctl enable --now i-ching-motd.timer",
+                )
+            } else {
+                (
+                    vec![(
+                        PathBuf::from(motd::UPDATE_MOTD_SCRIPT),
+                        motd::update_script(&exe, width),
+                    )],
+                    "The hexagram of the day appears at your next login.",
+                )
+            };
+
+            if !print {
+                match write_motd_files(&files) {
+                    Ok(()) => {
+                        for (path, _) in &files {
+                            writeln!(out, "Wrote {}", path.display())?;
+                        }
+                        writeln!(out, "{}", next_step)?;
+                        return Ok(());
+                    }
+                    Err(e) => writeln!(out, "Couldn't install ({}); as root, create:\n", e)?,
+                }
+            }
+            for (path, content) in &files {
+                writeln!(out, "=== {} ===\n{}", path.display(), content)?;
+            }
+            if !systemd {
+                writeln!(
+                    out,
+                    "Make it executable: sudo chmod 755 {}",
+                    motd::UPDATE_MOTD_SCRIPT
+                )?;
+            }
+            writeln!(out, "{}", next_step)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Write the MOTD script or units, making them world-readable and executable
+fn write_motd_files(files: &[(PathBuf, String)]) -> Result<()> {
+    for (path, content) in files {
+        std::fs::write(path, content).map_err(|e| anyhow::anyhow!("{}: {}", path.display(), e))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            std::fs::set_permissions(path, std::fs::Permissions::from_mode(0o755))?;
+        }
+    }
+    Ok(())
+}
+
+fn run_notes_command(
+    action: NotesCommand,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let notes = Notes::open_default()?;
+
+    match action {
+        NotesCommand::Add { hexagram, text } => {
+            notes.add(hexagram, &text)?;
+            writeln!(out, "Added a note to hexagram {}", hexagram)?;
+        }
+        NotesCommand::List { hexagram } => {
+            let all = notes.load()?;
+            let selected: Vec<(&u8, &Vec<Note>)> = all
+                .iter()
+                .filter(|(number, _)| hexagram.is_none_or(|hexagram| **number == hexagram))
+                .collect();
+            if selected.is_empty() {
+                writeln!(
+                    out,
+                    "No notes in {} (add one with `i-ching notes add <hexagram> \"...\"`)",
+                    notes.path().display()
+                )?;
+            }
+            for (&number, list) in selected {
+                writeln!(out, "{}", format_notes(number, list, data, glyphs))?;
+            }
+        }
+        NotesCommand::Remove { hexagram, number } => {
+            let note = notes.remove(hexagram, number)?;
+            writeln!(
+                out,
+                "Removed note from hexagram {}: {}",
+                hexagram, note.text
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+fn run_watch(
+    hexagrams: &[u8],
+    remove: bool,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let watchlist = Watchlist::open_default()?;
+    let watched = match (hexagrams.is_empty(), remove) {
+        (true, _) => watchlist.load()?,
+        (false, false) => watchlist.add(hexagrams)?,
+        (false, true) => watchlist.remove(hexagrams)?,
+    };
+
+    if watched.is_empty() {
+        writeln!(
+            out,
+            "No hexagrams watched in {} (add some with `i-ching watch <hexagram>...`)",
+            watchlist.path().display()
+        )?;
+        return Ok(());
+    }
+    for number in watched {
+        if let Some(hexagram) = data.get_hexagram(number) {
+            writeln!(
+                out,
+                "{}{} {}",
+                glyphs.hexagram_prefix(&hexagram.unicode),
+                number,
+                hexagram.name
+            )?;
+        }
+    }
+    Ok(())
+}
+
+fn run_notify(
+    question: Option<String>,
+    save: bool,
+    log_to: Option<LogTarget>,
+    log_question: bool,
+    dry_run: bool,
+    data: &IChingData,
+    out: &mut Output,
+) -> Result<()> {
+    let config_path = Config::default_path()?;
+    let config = Config::load_from(&config_path)?.notify;
+    if !dry_run && !config.desktop && config.webhook.is_none() {
+        return Err(anyhow::anyhow!(
+            "Nothing to notify: enable desktop notifications or set a webhook under [notify] in {}",
+            config_path.display()
+        ));
+    }
+
+    let reading = Diviner::new().cast_reading(question.or(config.question));
+    if save {
+        Journal::open_default()?.append(&JournalEntry::new(reading.clone()))?;
+    }
+
+    if let Some(target) = log_to {
+        let mut record = Record::for_reading(&reading, data);
+        if log_question {
+            record = record.with_question(&reading);
+        }
+        target.sink().send(&record)?;
+    }
+    let notification = format_notification(&reading, data);
+
+    if dry_run {
+        writeln!(out, "{}\n{}", notification.title, notification.body)?;
+        return Ok(());
+    }
+    if config.desktop {
+        notification.send_desktop()?;
+    }
+    if let Some(url) = &config.webhook {
+        notification.post_webhook(url)?;
+    }
+
+    Ok(())
+}
+
+/// Replace the running binary with the latest release, if it's newer
+#[cfg(feature = "self-update")]
+fn run_self_update(check: bool, force: bool, out: &mut Output) -> Result<()> {
+    let current = env!("CARGO_PKG_VERSION");
+    let release = Release::latest()?;
+    if !release.is_newer_than(current) {
+        writeln!(out, "i-ching {} is up to date", current)?;
+        return Ok(());
+    }
+    if check {
+        writeln!(
+            out,
+            "i-ching {} is available (this is {})",
+            release.version(),
+            current
+        )?;
+        return Ok(());
+    }
+
+    let exe = std::env::current_exe()?;
+    if update::installed_by_cargo(&exe) && !force {
+        return Err(anyhow::anyhow!(
+            "{} was installed by cargo; update it with `cargo install i-ching` (or pass --force)",
+            exe.display()
+        ));
+    }
+    let binary = release.download()?;
+    let exe = update::replace_current_exe(&binary)?;
+    writeln!(
+        out,
+        "Updated {} from {} to {}",
+        exe.display(),
+        current,
+        release.version()
+    )?;
+    Ok(())
+}
+
+/// Run the statistical checks on the coin method and fail if any reject it
+fn run_selftest(lines: usize, out: &mut Output) -> Result<()> {
+    if lines < 100 {
+        return Err(anyhow::anyhow!(
+            "The self-test needs at least 100 lines to be meaningful"
+        ));
+    }
+
+    writeln!(
+        out,
+        "Three coins, {} lines (significance level 0.001)",
+        lines
+    )?;
+    let results = selftest::run_coins(lines);
+    for result in &results {
+        writeln!(out, "{}", result)?;
+    }
+
+    let failures = results.iter().filter(|result| !result.passed()).count();
+    if failures > 0 {
+        return Err(anyhow::anyhow!(
+            "{} of {} checks failed; casts may not match the traditional probabilities",
+            failures,
+            results.len()
+        ));
+    }
+
+    Ok(())
+}
+
+/// The bundled corpora, then any installed translations, each with its
+/// title, year, translator, license, and source
+fn format_sources(corpora: &[Provenance], translations: &[(String, Option<Provenance>)]) -> String {
+    let describe = |provenance: &Provenance| {
+        let mut text = format!("{}: {}", provenance.corpus, provenance.title);
+        if let Some(year) = provenance.year {
+            text.push_str(&format!(" ({})", year));
+        }
+        text.push('\n');
+        if let Some(translator) = &provenance.translator {
+            text.push_str(&format!("  Translator: {}\n", translator));
+        }
+        text.push_str(&format!("  License:    {}\n", provenance.license));
+        text.push_str(&format!("  Source:     {}\n", provenance.source));
+        if !provenance.files.is_empty() {
+            text.push_str(&format!("  Files:      {}\n", provenance.files.join(", ")));
+        }
+        text
+    };
+
+    let mut text: Vec<String> = corpora.iter().map(describe).collect();
+    if !translations.is_empty() {
+        let dir = translations::translations_dir()
+            .map(|dir| dir.display().to_string())
+            .unwrap_or_default();
+        let mut installed = format!("Translations in {}:\n", dir);
+        for (name, provenance) in translations {
+            match provenance {
+                Some(provenance) => installed.push_str(&describe(provenance)),
+                None => installed.push_str(&format!("{}: no provenance given\n", name)),
+            }
+        }
+        text.push(installed);
+    }
+    text.join("\n")
+}
+
+/// The recorded consultations involving `number`, each with its source,
+/// the figure cast, how it was read, and what came of it
+fn format_history(number: u8, data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let label = |number: u8| -> Result<String> {
+        let hexagram = data
+            .get_hexagram(number)
+            .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: {}. Expected 1-64", number))?;
+        Ok(format!(
+            "{}{} {}",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            number,
+            hexagram.name
+        ))
+    };
+
+    let mut text = format!("{}\n", label(number)?);
+    let mut found = false;
+    for consultation in data.history(number) {
+        found = true;
+        let mut cast = label(consultation.hexagram)?;
+        match consultation.lines.as_slice() {
+            [] => cast.push_str(", no lines changing"),
+            [line] => cast.push_str(&format!(", line {} changing", line)),
+            lines => cast.push_str(&format!(", lines {:?} changing", lines)),
+        }
+        if let Some(becomes) = consultation.becomes {
+            cast.push_str(&format!(" {} {}", glyphs.arrow(), label(becomes)?));
+        }
+        text.push_str(&format!(
+            "\n{} ({} BCE)\n{}\nQuestion: {}\nCast: {}\nReading: {}\nOutcome: {}\n",
+            consultation.source,
+            consultation.year_bce,
+            consultation.diviner,
+            consultation.question,
+            cast,
+            consultation.reading,
+            consultation.outcome
+        ));
+    }
+    if !found {
+        text.push_str("\nNo recorded consultation involves this hexagram.\n");
+    }
+    Ok(text)
+}
+
+/// The text of `line`, then each line cross-referenced from it with its
+/// text and what the two share
+fn format_xref(line: LineRef, data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let label = |line: LineRef| -> Result<String> {
+        let hexagram = data
+            .get_hexagram(line.hexagram)
+            .ok_or_else(|| anyhow::anyhow!("Hexagram {} not found", line.hexagram))?;
+        let text = data
+            .get_line_interpretation(line.hexagram, line.line)
+            .map_or("", |interp| interp.text.as_str());
+        Ok(format!(
+            "{}{} {}, line {}\n{}\n",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            line.hexagram,
+            hexagram.name,
+            line.line,
+            text
+        ))
+    };
+
+    let mut text = label(line)?;
+    let mut found = false;
+    for (other, note) in data.cross_references(line) {
+        found = true;
+        text.push_str(&format!("\nSee also {}Note: {}\n", label(other)?, note));
+    }
+    if !found {
+        text.push_str(&format!("\nNo cross-references from line {}.\n", line));
+    }
+    Ok(text)
+}
+
+/// The glossary's definition of `term`, or the list of its terms
+fn explain(term: Option<&str>) -> Result<String> {
+    let glossary = Glossary::load()?;
+    let Some(term) = term else {
+        let mut text = String::new();
+        for entry in glossary.entries() {
+            text.push_str(&entry.term);
+            if !entry.aliases.is_empty() {
+                text.push_str(&format!(" ({})", entry.aliases.join(", ")));
+            }
+            text.push('\n');
+        }
+        return Ok(text);
+    };
+
+    let Some(entry) = glossary.lookup(term) else {
+        let suggestions = glossary.suggest(term);
+        if suggestions.is_empty() {
+            anyhow::bail!(
+                "No glossary entry for \"{}\"; run `i-ching explain` to list the terms",
+                term
+            );
+        }
+        anyhow::bail!(
+            "No glossary entry for \"{}\"; did you mean {}?",
+            term,
+            suggestions
+                .iter()
+                .map(|suggestion| format!("\"{}\"", suggestion))
+                .collect::<Vec<_>>()
+                .join(" or ")
+        );
+    };
+    let mut text = entry.term.clone();
+    if !entry.aliases.is_empty() {
+        text.push_str(&format!(" (also {})", entry.aliases.join(", ")));
+    }
+    text.push_str(&format!("\n\n{}\n", entry.definition));
+    if !entry.see_also.is_empty() {
+        text.push_str(&format!("\nSee also: {}\n", entry.see_also.join(", ")));
+    }
+    Ok(text)
+}
+
+/// Print every reading, or every reading of one hexagram, in `format`
+fn run_enumerate(
+    format: EnumerateFormat,
+    hexagram: Option<u8>,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let readings: Box<dyn Iterator<Item = Reading>> = match hexagram {
+        Some(number) => {
+            if !(1..=64).contains(&number) {
+                anyhow::bail!("Invalid hexagram number: {}. Expected 1-64", number);
+            }
+            Box::new(Reading::transitions_from(number))
+        }
+        None => Box::new(Reading::all_transitions()),
+    };
+    for reading in readings {
+        match format {
+            EnumerateFormat::Jsonl => writeln!(
+                out,
+                "{}",
+                serde_json::to_string(&json_reading_with_data(&reading, data)?)?
+            )?,
+            EnumerateFormat::Brief => {
+                writeln!(out, "{}", format_brief_with_data(&reading, data, glyphs)?)?
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Print theoretical and observed line odds for each casting method
+fn run_stats(
+    method: Option<stats::Method>,
+    casts: usize,
+    journal: bool,
+    out: &mut Output,
+) -> Result<()> {
+    // Readings from --method question-hash weren't cast, so they're left out
+    let journal = if journal {
+        let entries = Journal::open_default()?.entries()?;
+        let readings: Vec<Reading> = entries
+            .into_iter()
+            .filter(|entry| entry.question_hash.is_none())
+            .map(|entry| entry.reading)
+            .collect();
+        Some(Tally::new(&readings))
     } else {
-        // No input provided, cast randomly using coins method
-        diviner.cast_reading(None)
+        None
     };
 
-    match cli.format {
-        Format::Json => {
-            let json_reading = create_json_reading(&reading)?;
-            println!("{}", serde_json::to_string_pretty(&json_reading)?);
+    let methods = method.map_or(stats::Method::ALL.to_vec(), |method| vec![method]);
+    for (i, method) in methods.into_iter().enumerate() {
+        if i > 0 {
+            writeln!(out)?;
         }
-        Format::Numbers => {
-            println!("{:?}", reading.traditional_numbers());
-        }
-        Format::Brief => {
-            println!("{}", format_brief(&reading)?);
-        }
-        Format::Full => {
-            println!("{}", format_full(&reading)?);
-        }
-        Format::Motd => {
-            println!("{}", format_motd(&reading)?);
+        let simulated = Tally::new(&method.simulate(casts));
+        let mut observed = vec![("Simulated", &simulated)];
+        if let (stats::Method::Coins, Some(journal)) = (method, &journal) {
+            observed.push(("Journal", journal));
         }
+        write!(out, "{}", stats::table(method, &observed))?;
     }
-
     Ok(())
 }
 
-/// Parse input string and create a reading based on the input type
-fn parse_input_and_create_reading(diviner: &mut Diviner, input: &str) -> Result<Reading> {
-    let input = input.trim();
-
-    // Try to parse as changing hexagram format (Unicode or numbers)
-    // Supports: ䷟→䷡, ䷟->䷡, 32->34, 32→34
-    if let Some(reading) = try_parse_changing_hexagram(input)? {
-        return Ok(reading);
+/// Brief output for a reading in the `--format json` schema, from its own
+/// names rather than the local data
+pub(crate) fn format_brief_from_json(json: &JsonReading, glyphs: Glyphs) -> Result<String> {
+    let mut result = String::new();
+    if let Some(question) = &json.question {
+        writeln!(result, "Q: {}", question)?;
     }
-
-    // Try to parse as hexagram number (1-64)
-    if let Ok(hexagram_number) = input.parse::<u8>() {
-        if hexagram_number >= 1 && hexagram_number <= 64 {
-            return create_reading_from_hexagram_number(hexagram_number);
-        }
+    let primary = &json.primary_hexagram;
+    write!(
+        result,
+        "{}{} {}",
+        glyphs.prefix(&primary.unicode),
+        primary.number,
+        primary.name
+    )?;
+    if let Some(transformed) = &json.transformed_hexagram {
+        let positions: Vec<u8> = json
+            .changing_lines
+            .iter()
+            .map(|line| line.position)
+            .collect();
+        write!(
+            result,
+            " {} {}{} {} (lines: {:?})",
+            glyphs.arrow(),
+            glyphs.prefix(&transformed.unicode),
+            transformed.number,
+            transformed.name,
+            positions
+        )?;
     }
+    result.push('\n');
+    Ok(result)
+}
+
+/// Full output for a reading in the `--format json` schema: the figure and
+/// structure worked out from its lines, and the texts it carries
+pub(crate) fn format_full_from_json(json: &JsonReading, glyphs: Glyphs) -> Result<String> {
+    Ok(Rendered::from_json(json.clone(), glyphs)?.to_text() + "\n")
+}
 
-    // Try to parse as Unicode hexagram character
-    if input.chars().count() == 1 {
-        let unicode_char = input.chars().next().unwrap();
-        if let Some(hexagram_number) = unicode_to_hexagram_number(unicode_char)? {
-            return create_reading_from_hexagram_number(hexagram_number);
+/// Hexagram numbers from an inclusive range (`11..20`) or a comma-separated list
+fn parse_hexagram_list(spec: &str) -> Result<Vec<u8>> {
+    let parse = |s: &str| -> Result<u8> {
+        s.trim()
+            .parse::<u8>()
+            .ok()
+            .filter(|n| (1..=64).contains(n))
+            .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: '{}'. Must be 1-64", s.trim()))
+    };
+
+    if let Some((start, end)) = spec.split_once("..") {
+        let (start, end) = (parse(start)?, parse(end)?);
+        if start > end {
+            return Err(anyhow::anyhow!(
+                "Range {}..{} runs backwards; the Sequence only goes forward",
+                start,
+                end
+            ));
         }
+        return Ok((start..=end).collect());
     }
 
-    // Try to parse as comma-separated line numbers (6,7,8,9)
-    if input.contains(',') {
-        let line_numbers: Result<Vec<u8>, _> =
-            input.split(',').map(|s| s.trim().parse::<u8>()).collect();
+    spec.split(',').map(parse).collect()
+}
+
+/// Each hexagram with the Sequence text explaining how it follows the one
+/// before, marking any gaps in the King Wen order
+fn format_sequence(numbers: &[u8], data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let mut sections = Vec::new();
 
-        if let Ok(numbers) = line_numbers {
-            if numbers.len() == 6 && numbers.iter().all(|&n| [6, 7, 8, 9].contains(&n)) {
-                let lines_array: [u8; 6] = numbers
-                    .try_into()
-                    .map_err(|_| anyhow::anyhow!("Failed to convert line numbers to array"))?;
-                return diviner.cast_reading_from_numbers(lines_array, None);
+    for (i, &number) in numbers.iter().enumerate() {
+        let hexagram = data
+            .get_hexagram(number)
+            .ok_or_else(|| anyhow::anyhow!("Hexagram {} not found", number))?;
+
+        let mut section = String::new();
+        if let Some(&previous) = i.checked_sub(1).and_then(|i| numbers.get(i)) {
+            if number != previous + 1 {
+                section.push_str(&format!("{}\n\n", describe_gap(previous, number)));
             }
         }
+        section.push_str(&format!(
+            "{}{} {}\n",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            number,
+            hexagram.name
+        ));
+        section.push_str(&format!(
+            "  {}",
+            hexagram.sequence.as_deref().unwrap_or("(no Sequence text)")
+        ));
+        sections.push(section);
     }
 
-    Err(anyhow::anyhow!(
-        "Invalid input: '{}'. Expected hexagram number (1-64), Unicode character (䷀-䷿), changing format (32→34 or ䷟→䷡), or comma-separated line numbers (6,7,8,9)",
-        input
-    ))
+    Ok(sections.join("\n\n"))
 }
 
-/// Try to parse changing hexagram format like 32→34, 32->34, ䷟→䷡, ䷟->䷡
-fn try_parse_changing_hexagram(input: &str) -> Result<Option<Reading>> {
-    // Look for arrow indicators (both Unicode and ASCII)
-    let separators = ["→", "->"];
-
-    for separator in &separators {
-        if let Some(arrow_pos) = input.find(separator) {
-            let (from_part, to_part) = input.split_at(arrow_pos);
-            let to_part = &to_part[separator.len()..];
-            let from_part = from_part.trim();
-            let to_part = to_part.trim();
-
-            // Try to parse both parts as hexagram numbers
-            if let (Ok(from_num), Ok(to_num)) = (from_part.parse::<u8>(), to_part.parse::<u8>()) {
-                if from_num >= 1 && from_num <= 64 && to_num >= 1 && to_num <= 64 {
-                    return Ok(Some(create_changing_reading_from_numbers(
-                        from_num, to_num,
-                    )?));
-                }
-            }
+/// Hexagrams matching a query, one per line, e.g.
+/// "䷾ 63 After Completion: ☵ Kan (Water) over ☲ Li (Fire)", each followed
+/// by what it becomes if `changing` lines are given
+fn format_find(
+    query: &Query,
+    changing: &[usize],
+    data: &IChingData,
+    glyphs: Glyphs,
+) -> Result<String> {
+    if let Some(position) = changing.iter().find(|p| !(1..=6).contains(*p)) {
+        return Err(anyhow::anyhow!(
+            "Line {} doesn't exist; lines run from 1 (bottom) to 6",
+            position
+        ));
+    }
+    if query.yang.is_some_and(|yang| yang > 6) {
+        return Err(anyhow::anyhow!("A hexagram has at most 6 yang lines"));
+    }
 
-            // Try to parse both parts as Unicode characters
-            if from_part.chars().count() == 1 && to_part.chars().count() == 1 {
-                let from_char = from_part.chars().next().unwrap();
-                let to_char = to_part.chars().next().unwrap();
+    let matches = query.run(data);
+    if matches.is_empty() {
+        return Ok("No hexagrams match".to_string());
+    }
 
-                if let (Some(from_num), Some(to_num)) = (
-                    unicode_to_hexagram_number(from_char)?,
-                    unicode_to_hexagram_number(to_char)?,
-                ) {
-                    return Ok(Some(create_changing_reading_from_numbers(
-                        from_num, to_num,
-                    )?));
-                }
+    let name = |hexagram: &crate::core::data::Hexagram| {
+        format!(
+            "{}{} {}",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            hexagram.number,
+            hexagram.name
+        )
+    };
+    let mut lines = Vec::new();
+    for hexagram in matches {
+        let mut line = format!(
+            "{}: {} over {}",
+            name(hexagram),
+            format_trigram(hexagram.upper_trigram, glyphs),
+            format_trigram(hexagram.lower_trigram, glyphs)
+        );
+        if !changing.is_empty() {
+            let bits = changing
+                .iter()
+                .fold(hexagram.bits(), |bits, &p| king_wen::flip_line(bits, p));
+            if let Some(transformed) = crate::core::data::Hexagram::from_bits(data, bits) {
+                line.push_str(&format!(" {} {}", glyphs.arrow(), name(transformed)));
             }
         }
+        lines.push(line);
     }
 
-    Ok(None)
+    Ok(lines.join("\n"))
 }
 
-/// Convert Unicode hexagram character to hexagram number
-fn unicode_to_hexagram_number(unicode_char: char) -> Result<Option<u8>> {
-    let data =
-        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+/// Note for a jump in a story, e.g. "(14-19 skipped)" or "(back to 3)"
+fn describe_gap(from: u8, to: u8) -> String {
+    if to <= from {
+        format!("(back to {})", to)
+    } else if to == from + 2 {
+        format!("({} skipped)", from + 1)
+    } else {
+        format!("({}-{} skipped)", from + 1, to - 1)
+    }
+}
 
-    // Search through all hexagrams to find matching Unicode character
-    for i in 1..=64 {
-        if let Some(hexagram) = data.get_hexagram(i) {
-            if hexagram.unicode.chars().next() == Some(unicode_char) {
-                return Ok(Some(i));
-            }
+/// A whole Ten Wings section, one entry per trigram or hexagram
+fn format_wings(section: WingsSection, data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let entries: Vec<String> = match section {
+        WingsSection::XuGua => {
+            return format_sequence(&(1..=64).collect::<Vec<u8>>(), data, glyphs);
         }
-    }
+        WingsSection::Shuogua => Trigram::ALL
+            .iter()
+            .filter_map(|&trigram| {
+                let discussion = data.get_trigram(trigram)?.discussion.as_ref()?;
+                Some(format!(
+                    "{}\n  {}",
+                    format_trigram(trigram, glyphs),
+                    format_trigram_discussion(discussion)
+                ))
+            })
+            .collect(),
+        WingsSection::ZaGua => data
+            .hexagrams()
+            .filter_map(|hexagram| {
+                Some(format!(
+                    "{}{} {}: {}",
+                    glyphs.hexagram_prefix(&hexagram.unicode),
+                    hexagram.number,
+                    hexagram.name,
+                    hexagram.miscellaneous.as_deref()?
+                ))
+            })
+            .collect(),
+    };
 
-    Ok(None)
+    Ok(entries.join("\n"))
 }
 
-/// Create a reading from a hexagram number by generating all young lines (no changing lines)
-fn create_reading_from_hexagram_number(hexagram_number: u8) -> Result<Reading> {
-    // Convert hexagram number back to binary representation
-    // Hexagram numbers are 1-indexed, so subtract 1 to get 0-63 range
-    let binary_value = hexagram_number - 1;
+/// Table of hexagram characters in King Wen order, e.g. " 1  ䷀  U+4DC0  Initiating"
+fn format_unicode_table(data: &IChingData) -> Result<String> {
+    let rows: Vec<String> = HEXAGRAM_CHARS
+        .iter()
+        .zip(1..=64)
+        .map(|(&c, number)| {
+            let name = data
+                .get_hexagram(number)
+                .map_or("Unknown", |hexagram| hexagram.name.as_str());
+            format!("{:>2}  {}  U+{:04X}  {}", number, c, c as u32, name)
+        })
+        .collect();
 
-    let mut lines = [crate::core::reading::Line::new(
-        crate::core::reading::Age::Young,
-        crate::core::reading::Polarity::Yin,
-    ); 6];
+    Ok(rows.join("\n"))
+}
 
-    // Convert binary representation to lines (bottom to top)
-    for i in 0..6 {
-        let bit = (binary_value >> i) & 1;
-        lines[i] = crate::core::reading::Line::new(
-            crate::core::reading::Age::Young,
-            if bit == 1 {
-                crate::core::reading::Polarity::Yang
-            } else {
-                crate::core::reading::Polarity::Yin
-            },
-        );
+/// Print a hexagram's pronunciation and speak it, or print it as SSML
+fn run_resume(
+    name: Option<String>,
+    lines: Option<u8>,
+    save: bool,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let drafts = Drafts::open_default()?;
+    let Some(name) = name else {
+        let names = drafts.names()?;
+        if names.is_empty() {
+            writeln!(out, "No drafts saved")?;
+        }
+        for name in names {
+            let draft = drafts.load(&name)?;
+            write!(out, "{}  {}/6 lines", name, draft.lines().len())?;
+            match draft.question() {
+                Some(question) => writeln!(out, "  {}", question)?,
+                None => writeln!(out)?,
+            }
+        }
+        return Ok(());
+    };
+
+    let mut draft = drafts.load(&name)?;
+    draft.cast(lines.map_or(6, usize::from));
+    let Some(reading) = draft.to_reading() else {
+        drafts.save(&name, &draft)?;
+        write!(out, "{}", format_draft(&name, &draft, glyphs))?;
+        return Ok(());
+    };
+
+    let entry = JournalEntry::new(reading);
+    if save {
+        Journal::open_default()?.append(&entry)?;
+    }
+    drafts.remove(&name)?;
+    let options = RenderOptions {
+        glyphs,
+        sections: FullOptions {
+            notes: Notes::open_default()?.load()?,
+            ..Default::default()
+        },
+        ..RenderOptions::default()
+    };
+    write_reading(out, &entry, Format::Full, data, &options)
+}
+
+/// A draft's progress: the lines cast so far, top first, and how to go on
+fn format_draft(name: &str, draft: &PartialReading, glyphs: Glyphs) -> String {
+    let mut result = format!(
+        "Draft '{}': {} of 6 lines cast\n",
+        name,
+        draft.lines().len()
+    );
+    if let Some(question) = draft.question() {
+        result.push_str(&format!("Q: {}\n", question));
     }
+    for (i, line) in draft.lines().iter().enumerate().rev() {
+        result.push_str(&format!("{}: {}\n", i + 1, glyphs.line(line)));
+    }
+    if !draft.is_complete() {
+        result.push_str(&format!("Cast the rest with: i-ching resume {}\n", name));
+    }
+    result
+}
 
-    Ok(Reading::new(lines, None))
+/// Print a socket and service unit for `service`, and how to enable them
+fn write_units(service: systemd::Service, listen: &str, out: &mut Output) -> Result<()> {
+    let exe = std::env::current_exe()?;
+    for (name, content) in systemd::units(service, &exe, listen) {
+        writeln!(out, "=== {} ===\n{}", name, content)?;
+    }
+    let (directory, systemctl) = match service {
+        systemd::Service::Http => ("/etc/systemd/system", "sudo systemctl"),
+        systemd::Service::Daemon => ("~/.config/systemd/user", "systemctl --user"),
+    };
+    writeln!(
+        out,
+        "Save these in {}, then run `{} enable --now {}.socket`",
+        directory,
+        systemctl,
+        service.unit_name()
+    )?;
+    Ok(())
 }
 
-/// Create a reading that changes from one hexagram to another
-fn create_changing_reading_from_numbers(from_hexagram: u8, to_hexagram: u8) -> Result<Reading> {
-    // Convert hexagram numbers to binary representations
-    let from_binary = from_hexagram - 1;
-    let to_binary = to_hexagram - 1;
+#[cfg(unix)]
+fn serve_daemon_from_systemd(daemon: Daemon) -> Result<()> {
+    daemon.serve_listener(systemd::unix_listener()?)
+}
 
-    let mut lines = [crate::core::reading::Line::new(
-        crate::core::reading::Age::Young,
-        crate::core::reading::Polarity::Yin,
-    ); 6];
+#[cfg(not(unix))]
+fn serve_daemon_from_systemd(_daemon: Daemon) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Socket activation needs systemd, which this platform lacks"
+    ))
+}
 
-    // Create lines that will transform from_hexagram into to_hexagram
-    for i in 0..6 {
-        let from_bit = (from_binary >> i) & 1;
-        let to_bit = (to_binary >> i) & 1;
+fn run_say(
+    number: u8,
+    ssml: bool,
+    engine: Option<Engine>,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let hexagram = data
+        .get_hexagram(number)
+        .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: {}. Expected 1-64", number))?;
 
-        let from_polarity = if from_bit == 1 {
-            crate::core::reading::Polarity::Yang
-        } else {
-            crate::core::reading::Polarity::Yin
-        };
+    if ssml {
+        writeln!(out, "{}", speech::ssml(hexagram))?;
+        return Ok(());
+    }
 
-        let to_polarity = if to_bit == 1 {
-            crate::core::reading::Polarity::Yang
-        } else {
-            crate::core::reading::Polarity::Yin
-        };
+    let pronunciation = speech::pronunciation(hexagram);
+    writeln!(
+        out,
+        "{}{} {} [{}]",
+        glyphs.hexagram_prefix(&hexagram.unicode),
+        number,
+        pronunciation.pinyin,
+        pronunciation.ipa
+    )?;
+    out.flush()?;
+
+    let engine = engine.or_else(Engine::detect).ok_or_else(|| {
+        anyhow::anyhow!(
+            "No speech engine found: install espeak-ng, or use --ssml to print SSML for another synthesizer"
+        )
+    })?;
+    engine.speak(hexagram)
+}
 
-        // If the polarity changes, make it an old line (changing)
-        // If it stays the same, make it a young line (stable)
-        if from_polarity != to_polarity {
-            lines[i] =
-                crate::core::reading::Line::new(crate::core::reading::Age::Old, from_polarity);
-        } else {
-            lines[i] =
-                crate::core::reading::Line::new(crate::core::reading::Age::Young, from_polarity);
+fn run_play(
+    reading: &Reading,
+    sound: Sound,
+    output: Option<PathBuf>,
+    data: &IChingData,
+    glyphs: Glyphs,
+    out: &mut Output,
+) -> Result<()> {
+    let content = match sound {
+        Sound::Beep => {
+            if output.is_some() {
+                return Err(anyhow::anyhow!(
+                    "--output needs --as abc or --as midi; bells can't be written to a file"
+                ));
+            }
+            // Straight to the terminal, not a pager, so the bells ring as it's shown
+            let mut stdout = io::stdout();
+            writeln!(stdout, "{}", format_brief_with_data(reading, data, glyphs)?)?;
+            return tones::beep(reading, &mut stdout);
+        }
+        Sound::Abc => tones::to_abc(reading, data).into_bytes(),
+        Sound::Midi => tones::to_midi(reading),
+    };
+    match output {
+        Some(path) => std::fs::write(&path, content)
+            .map_err(|e| anyhow::anyhow!("Failed to write {}: {}", path.display(), e))?,
+        None if sound == Sound::Midi && io::stdout().is_terminal() => {
+            return Err(anyhow::anyhow!(
+                "MIDI is binary; give --output FILE.mid or redirect stdout to a file"
+            ));
         }
+        None => out.write_all(&content)?,
     }
+    Ok(())
+}
 
-    let reading = Reading::new(lines, None);
+/// Short notification: the question (or "Daily reading") as the title, then
+/// the one-line summary and the Judgment
+fn format_notification(reading: &Reading, data: &IChingData) -> Notification {
+    let title = reading.question().unwrap_or("Daily reading").to_string();
+    let mut body = format_brief_summary(reading, data, Glyphs::Unicode);
 
-    // Verify that our reading actually transforms correctly
-    if reading.primary_hexagram() != from_hexagram {
-        return Err(anyhow::anyhow!(
-            "Internal error: created reading has hexagram {} but expected {}",
-            reading.primary_hexagram(),
-            from_hexagram
-        ));
+    if let Some(hexagram) = data.get_hexagram(reading.primary_hexagram()) {
+        body.push_str(&format!("\n\n{}", hexagram.judgment.text));
     }
 
-    if let Some(transformed) = reading.transformed_hexagram() {
-        if transformed.primary_hexagram() != to_hexagram {
-            return Err(anyhow::anyhow!(
-                "Internal error: transformed reading has hexagram {} but expected {}",
-                transformed.primary_hexagram(),
-                to_hexagram
-            ));
-        }
-    } else if from_hexagram != to_hexagram {
-        return Err(anyhow::anyhow!(
-            "Internal error: reading should have changing lines but doesn't"
+    Notification { title, body }
+}
+
+/// Markdown report with a section per reading, for reviewing several questions at once
+fn format_markdown_report(readings: &[Reading], data: &IChingData) -> Result<String> {
+    let mut result = format!(
+        "# I Ching Readings ({})\n",
+        chrono::Local::now().format("%Y-%m-%d")
+    );
+
+    for (i, reading) in readings.iter().enumerate() {
+        let question = reading.question().unwrap_or("(no question)");
+        let rendered = Rendered::new(reading, data, Glyphs::Unicode, &FullOptions::default())?;
+        result.push_str(&format!(
+            "\n## {}. {}\n\n{}\n",
+            i + 1,
+            question,
+            rendered.title
         ));
+        for section in rendered.of_kind(&[SectionKind::Judgment, SectionKind::ChangingLine]) {
+            result.push_str(&format!("\n**{}:** {}\n", section.title, section.body()));
+        }
     }
 
-    Ok(reading)
+    Ok(result)
 }
 
-/// Create a JSON representation of a reading with full meanings
-fn create_json_reading(reading: &Reading) -> Result<JsonReading> {
-    let data =
-        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+/// One line of `journal list` output: local time, hexagram summary, and question
+/// Bold, for the matching words in `journal search` snippets on a terminal
+const MATCH_STYLE: &str = "\x1b[1m";
+const RESET_STYLE: &str = "\x1b[0m";
 
-    let hexagram_number = reading.primary_hexagram();
-    let hexagram = data
-        .get_hexagram(hexagram_number)
-        .ok_or_else(|| anyhow::anyhow!("Hexagram {} not found", hexagram_number))?;
-
-    let primary_hexagram = JsonHexagram {
-        number: hexagram.number,
-        name: hexagram.name.clone(),
-        chinese: hexagram.chinese.clone(),
-        pinyin: hexagram.pinyin.clone(),
-        unicode: hexagram.unicode.clone(),
-        description: hexagram.description.clone(),
-        judgment: JsonJudgment {
-            text: hexagram.judgment.text.clone(),
-            commentary: hexagram.judgment.commentary.clone(),
-        },
-        image: JsonImage {
-            text: hexagram.image.text.clone(),
-            commentary: hexagram.image.commentary.clone(),
-        },
-    };
+fn format_journal_line(entry: &JournalEntry, data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let timestamp = entry.timestamp.with_timezone(&chrono::Local);
 
-    let changing_lines: Vec<JsonLineInterpretation> = reading
-        .changing_line_positions()
-        .into_iter()
-        .filter_map(|line_pos| {
-            data.get_line_interpretation(hexagram_number, line_pos)
-                .map(|interp| JsonLineInterpretation {
-                    position: line_pos,
-                    text: interp.text.clone(),
-                    comments: interp.comments.clone(),
-                })
-        })
-        .collect();
+    let mut result = timestamp.format("%Y-%m-%d %H:%M").to_string();
+    if let Some(recipient) = &entry.recipient {
+        result.push_str(&format!("  [for {}]", recipient));
+    }
+    result.push_str(&format!(
+        "  {}",
+        format_brief_summary(&entry.reading, data, glyphs)
+    ));
+    if let Some(question) = entry.reading.question() {
+        result.push_str(&format!("  Q: {}", question));
+    }
+    match &entry.outcome {
+        Some(Outcome {
+            resolved: true,
+            accuracy: Some(accuracy),
+            ..
+        }) => result.push_str(&format!("  [resolved, {}/5]", accuracy)),
+        Some(Outcome { resolved: true, .. }) => result.push_str("  [resolved]"),
+        Some(Outcome {
+            resolved: false, ..
+        }) => result.push_str("  [unresolved]"),
+        None => {}
+    }
 
-    let transformed_hexagram = if let Some(transformed) = reading.transformed_hexagram() {
-        let transformed_number = transformed.primary_hexagram();
-        data.get_hexagram(transformed_number)
-            .map(|hex| JsonHexagram {
-                number: hex.number,
-                name: hex.name.clone(),
-                chinese: hex.chinese.clone(),
-                pinyin: hex.pinyin.clone(),
-                unicode: hex.unicode.clone(),
-                description: hex.description.clone(),
-                judgment: JsonJudgment {
-                    text: hex.judgment.text.clone(),
-                    commentary: hex.judgment.commentary.clone(),
-                },
-                image: JsonImage {
-                    text: hex.image.text.clone(),
-                    commentary: hex.image.commentary.clone(),
-                },
-            })
-    } else {
-        None
-    };
+    Ok(result)
+}
 
-    let polarity_to_string = |polarity| match polarity {
-        crate::core::reading::Polarity::Yang => "Yang".to_string(),
-        crate::core::reading::Polarity::Yin => "Yin".to_string(),
+/// Widest bar drawn in report histograms
+const REPORT_BAR_WIDTH: usize = 30;
+
+/// Bar for `count` out of a largest count of `max`, never empty for a nonzero count
+fn report_bar(count: usize, max: usize, glyphs: Glyphs) -> String {
+    let length = (count * REPORT_BAR_WIDTH).div_ceil(max.max(1));
+    let block = match glyphs {
+        Glyphs::Unicode => "█",
+        Glyphs::Ascii => "#",
     };
+    block.repeat(length)
+}
 
-    let upper_trigram = reading.upper_trigram().map(polarity_to_string);
-    let lower_trigram = reading.lower_trigram().map(polarity_to_string);
+fn report_hexagram_label(number: u8, data: &IChingData, glyphs: Glyphs) -> String {
+    match data.get_hexagram(number) {
+        Some(hexagram) => format!(
+            "{}{} {}",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            number,
+            hexagram.name
+        ),
+        None => number.to_string(),
+    }
+}
 
-    Ok(JsonReading {
-        question: reading.question.clone(),
-        lines: reading.traditional_numbers(),
-        primary_hexagram,
-        changing_lines,
-        transformed_hexagram,
-        upper_trigram,
-        lower_trigram,
-    })
+/// Column labels for the topics table: each topic, then untagged readings
+fn report_topic_columns() -> Vec<(Option<Topic>, &'static str)> {
+    Topic::value_variants()
+        .iter()
+        .map(|&topic| (Some(topic), topic.label()))
+        .chain([(None, "None")])
+        .collect()
 }
 
-fn format_brief(reading: &Reading) -> Result<String> {
-    let data =
-        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
-    let mut result = String::new();
+/// Recorded outcomes by month, then overall, with the spread of ratings
+fn format_accuracy(accuracy: &Accuracy) -> String {
+    let total = &accuracy.total;
+    if total.readings == 0 {
+        return "No saved readings to report on\n".to_string();
+    }
+    let mut result = format!(
+        "{} of {} reading{} resolved\n\n",
+        total.resolved,
+        total.readings,
+        if total.readings == 1 { "" } else { "s" }
+    );
+
+    let row = |label: &str, counts: &OutcomeCounts| {
+        let mean = |value: Option<f64>| value.map_or("-".to_string(), |v| format!("{:.1}", v));
+        format!(
+            "{:<9}{:>10}{:>10}{:>10}{:>8}\n",
+            label,
+            counts.readings,
+            counts.resolved,
+            mean(counts.mean_accuracy()),
+            mean(counts.mean_days())
+        )
+    };
+    result.push_str(&format!(
+        "{:<9}{:>10}{:>10}{:>10}{:>8}\n",
+        "Month", "Readings", "Resolved", "Accuracy", "Days"
+    ));
+    for (month, counts) in &accuracy.months {
+        result.push_str(&row(month, counts));
+    }
+    result.push_str(&row("Overall", total));
 
-    if let Some(ref question) = reading.question {
-        result.push_str(&format!("Q: {}\n", question));
+    result.push_str("\nRatings ");
+    for (rating, count) in (1..).zip(total.ratings) {
+        result.push_str(&format!("  {}: {}", rating, count));
     }
+    result.push('\n');
+    result
+}
 
-    let hexagram_number = reading.primary_hexagram();
-    if let Some(hexagram) = data.get_hexagram(hexagram_number) {
+/// Plain-text journal report with bar charts
+fn format_report(report: &Report, data: &IChingData, glyphs: Glyphs) -> String {
+    let Some((first, last)) = report.span else {
+        return "No saved readings to report on\n".to_string();
+    };
+    let date =
+        |time: chrono::DateTime<chrono::Utc>| time.with_timezone(&chrono::Local).date_naive();
+    let mut result = format!(
+        "{} reading{}, {} to {}\n",
+        report.readings,
+        if report.readings == 1 { "" } else { "s" },
+        date(first),
+        date(last)
+    );
+
+    result.push_str("\nHexagrams\n");
+    let hexagrams = report.most_frequent();
+    let labels: Vec<String> = hexagrams
+        .iter()
+        .map(|&(number, _)| report_hexagram_label(number, data, glyphs))
+        .collect();
+    let width = labels
+        .iter()
+        .map(|label| display_width(label))
+        .max()
+        .unwrap_or(0);
+    let max = hexagrams.first().map_or(0, |&(_, count)| count);
+    for (label, &(_, count)) in labels.iter().zip(&hexagrams) {
         result.push_str(&format!(
-            "{} {} {}",
-            hexagram.unicode, hexagram_number, hexagram.name
+            "{}  {} {}\n",
+            pad(label, width),
+            report_bar(count, max, glyphs),
+            count
         ));
+    }
 
-        if reading.has_changing_lines() {
-            if let Some(transformed) = reading.transformed_hexagram() {
-                let transformed_number = transformed.primary_hexagram();
-                if let Some(transformed_hex) = data.get_hexagram(transformed_number) {
-                    result.push_str(&format!(
-                        " → {} {} {}",
-                        transformed_hex.unicode, transformed_number, transformed_hex.name
-                    ));
-                } else {
-                    result.push_str(&format!(" → {} {}", transformed_number, "Unknown"));
-                }
-            }
-            result.push_str(&format!(
-                " (lines: {:?})",
-                reading.changing_line_positions()
-            ));
+    result.push_str("\nChanging lines\n");
+    let counts = report.changing_line_counts();
+    let max = counts.first().map_or(0, |&(_, count)| count);
+    for (lines, count) in counts {
+        result.push_str(&format!(
+            "{}  {} {} ({}%)\n",
+            lines,
+            report_bar(count, max, glyphs),
+            count,
+            count * 100 / report.readings
+        ));
+    }
+
+    result.push_str("\nTopics by month\n");
+    let columns = report_topic_columns();
+    result.push_str("Month  ");
+    for (_, label) in &columns {
+        result.push_str(&format!("  {}", label));
+    }
+    result.push('\n');
+    for (month, topics) in &report.topics {
+        result.push_str(month);
+        for (topic, label) in &columns {
+            let count = topics.get(topic).copied().unwrap_or(0);
+            result.push_str(&format!("  {:>width$}", count, width = label.len()));
         }
-    } else {
-        result.push_str(&format!("Hexagram {} (Unknown)", hexagram_number));
+        result.push('\n');
     }
 
-    Ok(result)
+    result
 }
 
-fn format_full(reading: &Reading) -> Result<String> {
-    let data =
-        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
-    let mut result = reading.display();
-
-    // Add traditional numbers for reference
-    result.push_str(&format!(
-        "\nTraditional numbers: {:?}\n",
-        reading.traditional_numbers()
-    ));
+/// Journal report as CSV rows of `section,key,month,count`
+fn format_report_csv(report: &Report, data: &IChingData) -> String {
+    let mut rows = vec![["section", "key", "month", "count"].map(String::from)];
+    for (number, count) in report.most_frequent() {
+        rows.push([
+            "hexagram".to_string(),
+            report_hexagram_label(number, data, Glyphs::Ascii),
+            String::new(),
+            count.to_string(),
+        ]);
+    }
+    for (lines, count) in report.changing_line_counts() {
+        rows.push([
+            "changing_lines".to_string(),
+            lines.to_string(),
+            String::new(),
+            count.to_string(),
+        ]);
+    }
+    for (month, topics) in &report.topics {
+        for (topic, count) in topics {
+            rows.push([
+                "topic".to_string(),
+                topic.as_ref().map_or("none", Topic::label).to_lowercase(),
+                month.clone(),
+                count.to_string(),
+            ]);
+        }
+    }
 
-    // Add trigram information
-    result.push_str(&format!("Upper trigram: {:?}\n", reading.upper_trigram()));
-    result.push_str(&format!("Lower trigram: {:?}\n", reading.lower_trigram()));
+    rows.iter()
+        .map(|row| {
+            let fields: Vec<String> = row
+                .iter()
+                .map(|field| escape_delimited_field(field, ','))
+                .collect();
+            fields.join(",") + "\n"
+        })
+        .collect()
+}
 
-    // Add hexagram meanings
-    let hexagram_number = reading.primary_hexagram();
-    if let Some(hexagram) = data.get_hexagram(hexagram_number) {
-        result.push_str(&format!(
-            "\n=== {} {} ===\n",
-            hexagram.unicode, hexagram.name
-        ));
-        result.push_str(&format!(
-            "Chinese: {} ({})\n",
-            hexagram.chinese, hexagram.pinyin
-        ));
-        result.push_str(&format!("Description: {}\n", hexagram.description));
+/// Journal report as a standalone HTML page, with the histograms as bars
+fn format_report_html(report: &Report, data: &IChingData) -> String {
+    let escape = |text: &str| {
+        text.replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+    };
+    let bar = |count: usize, max: usize| {
+        format!(
+            "<div class=\"bar\" style=\"width: {}px\"></div>",
+            count * 240 / max.max(1)
+        )
+    };
 
-        result.push_str(&format!("\nJudgment: {}\n", hexagram.judgment.text));
-        result.push_str(&format!("Commentary: {}\n", hexagram.judgment.commentary));
+    let mut html = String::from(
+        "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+         <title>I Ching journal report</title>\n<style>\n\
+         body { font-family: sans-serif; margin: 2em; }\n\
+         table { border-collapse: collapse; margin-bottom: 2em; }\n\
+         th, td { padding: 0.2em 0.8em; text-align: left; }\n\
+         td.count { text-align: right; }\n\
+         .bar { background: #555; height: 0.8em; }\n\
+         </style>\n</head>\n<body>\n<h1>I Ching journal report</h1>\n",
+    );
+    let Some((first, last)) = report.span else {
+        html.push_str("<p>No saved readings to report on.</p>\n</body>\n</html>\n");
+        return html;
+    };
+    let date =
+        |time: chrono::DateTime<chrono::Utc>| time.with_timezone(&chrono::Local).date_naive();
+    html.push_str(&format!(
+        "<p>{} readings, {} to {}</p>\n",
+        report.readings,
+        date(first),
+        date(last)
+    ));
 
-        result.push_str(&format!("\nImage: {}\n", hexagram.image.text));
-        result.push_str(&format!(
-            "Image Commentary: {}\n",
-            hexagram.image.commentary
+    html.push_str(
+        "<h2>Hexagrams</h2>\n<table>\n<tr><th>Hexagram</th><th>Readings</th><th></th></tr>\n",
+    );
+    let hexagrams = report.most_frequent();
+    let max = hexagrams.first().map_or(0, |&(_, count)| count);
+    for (number, count) in hexagrams {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"count\">{}</td><td>{}</td></tr>\n",
+            escape(&report_hexagram_label(number, data, Glyphs::Unicode)),
+            count,
+            bar(count, max)
         ));
+    }
+    html.push_str("</table>\n");
+
+    html.push_str(
+        "<h2>Changing lines</h2>\n<table>\n<tr><th>Lines</th><th>Readings</th><th></th></tr>\n",
+    );
+    let counts = report.changing_line_counts();
+    let max = counts.first().map_or(0, |&(_, count)| count);
+    for (lines, count) in counts {
+        html.push_str(&format!(
+            "<tr><td>{}</td><td class=\"count\">{}</td><td>{}</td></tr>\n",
+            lines,
+            count,
+            bar(count, max)
+        ));
+    }
+    html.push_str("</table>\n");
 
-        // Add changing line interpretations
-        if reading.has_changing_lines() {
-            result.push_str("\n=== Changing Lines ===\n");
-            for &line_pos in &reading.changing_line_positions() {
-                if let Some(line_interp) = data.get_line_interpretation(hexagram_number, line_pos) {
-                    result.push_str(&format!("Line {}: {}\n", line_pos, line_interp.text));
-                    result.push_str(&format!("Comments: {}\n\n", line_interp.comments));
-                }
-            }
-
-            // Add transformed hexagram meaning
-            if let Some(transformed) = reading.transformed_hexagram() {
-                let transformed_number = transformed.primary_hexagram();
-                if let Some(transformed_hex) = data.get_hexagram(transformed_number) {
-                    result.push_str(&format!(
-                        "\n=== Transforms to {} {} ===\n",
-                        transformed_hex.unicode, transformed_hex.name
-                    ));
-                    result.push_str(&format!(
-                        "Chinese: {} ({})\n",
-                        transformed_hex.chinese, transformed_hex.pinyin
-                    ));
-                    result.push_str(&format!("Description: {}\n", transformed_hex.description));
-                    result.push_str(&format!("Judgment: {}\n", transformed_hex.judgment.text));
-                }
-            }
+    html.push_str("<h2>Topics by month</h2>\n<table>\n<tr><th>Month</th>");
+    let columns = report_topic_columns();
+    for (_, label) in &columns {
+        html.push_str(&format!("<th>{}</th>", label));
+    }
+    html.push_str("</tr>\n");
+    for (month, topics) in &report.topics {
+        html.push_str(&format!("<tr><td>{}</td>", month));
+        for (topic, _) in &columns {
+            let count = topics.get(topic).copied().unwrap_or(0);
+            html.push_str(&format!("<td class=\"count\">{}</td>", count));
         }
+        html.push_str("</tr>\n");
     }
+    html.push_str("</table>\n</body>\n</html>\n");
 
-    Ok(result)
+    html
 }
 
-fn format_motd(reading: &Reading) -> Result<String> {
-    let data =
-        IChingData::load().map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
-    let hexagram_number = reading.primary_hexagram();
-
-    if let Some(hexagram) = data.get_hexagram(hexagram_number) {
-        if reading.has_changing_lines() {
-            if let Some(transformed) = reading.transformed_hexagram() {
-                let transformed_number = transformed.primary_hexagram();
-                if let Some(transformed_hex) = data.get_hexagram(transformed_number) {
-                    Ok(format!(
-                        "{}→{} {} {} CHANGING INTO {} {}",
-                        hexagram.unicode,
-                        transformed_hex.unicode,
-                        hexagram_number,
-                        hexagram.name.to_uppercase(),
-                        transformed_number,
-                        transformed_hex.name.to_uppercase()
-                    ))
-                } else {
-                    Ok(format!(
-                        "{}→䷜ {} {} CHANGING INTO {} UNKNOWN",
-                        hexagram.unicode,
-                        hexagram_number,
-                        hexagram.name.to_uppercase(),
-                        transformed_number
-                    ))
-                }
-            } else {
-                // This shouldn't happen if has_changing_lines() is true, but just in case
-                Ok(format!(
-                    "{} {} {}",
-                    hexagram.unicode,
-                    hexagram_number,
-                    hexagram.name.to_uppercase()
-                ))
-            }
-        } else {
-            Ok(format!(
-                "{} {} {}",
-                hexagram.unicode,
-                hexagram_number,
-                hexagram.name.to_uppercase()
-            ))
+/// Show both members of a King Wen pair with their judgments and images
+fn format_pair(hexagram_number: u8, data: &IChingData, glyphs: Glyphs) -> Result<String> {
+    let pair = king_wen::pair(hexagram_number).ok_or_else(|| {
+        anyhow::anyhow!(
+            "Invalid hexagram number: {}. Expected 1-64",
+            hexagram_number
+        )
+    })?;
+
+    let mut result = format!("{}\n", format_pair_heading(&pair, data, glyphs));
+    result.push_str(&format!("{}\n", describe_pair_relation(&pair)));
+
+    for number in [pair.first, pair.second] {
+        if let Some(hexagram) = data.get_hexagram(number) {
+            result.push_str(&format!(
+                "\n=== {}{} {} ===\n",
+                glyphs.hexagram_prefix(&hexagram.unicode),
+                number,
+                hexagram.name
+            ));
+            result.push_str(&format!(
+                "Chinese: {} ({})\n",
+                hexagram.chinese, hexagram.pinyin
+            ));
+            result.push_str(&format!("Judgment: {}\n", hexagram.judgment.text));
+            result.push_str(&format!("Commentary: {}\n", hexagram.judgment.commentary));
+            result.push_str(&format!("Image: {}\n", hexagram.image.text));
         }
-    } else {
-        Ok(format!("䷜ {} UNKNOWN", hexagram_number))
     }
+
+    Ok(result)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::format::{self, format_full_with_data};
 
     #[test]
-    fn test_format_brief() {
-        let diviner = Diviner::new();
-        let reading = diviner
-            .cast_reading_from_numbers([7, 8, 9, 6, 7, 8], Some("Test question".to_string()))
-            .unwrap();
+    fn test_cast_with_method() {
+        use casting::CastingMethod;
+        use rand::SeedableRng;
+
+        let cli = Cli::try_parse_from(["i-ching", "--method", "yarrow"]).unwrap();
+        assert_eq!(cli.method, Method::Yarrow);
+        let date = chrono::NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let reading = Manifest::cast(cli.method, 7, None, date).unwrap();
+        let stalks = casting::YarrowStalks.cast(&mut rand::rngs::StdRng::seed_from_u64(7), None);
+        assert_eq!(reading.traditional_numbers(), stalks.traditional_numbers());
+    }
+
+    #[test]
+    fn test_format_report() {
+        let data = IChingData::load().unwrap();
+        let entries: Vec<JournalEntry> = ["11", "32->34", "11"]
+            .iter()
+            .map(|input| JournalEntry::new(input.parse().unwrap()).with_topic(Some(Topic::Career)))
+            .collect();
+        let report = Report::new(&entries);
+
+        let text = format_report(&report, &data, Glyphs::Ascii);
+        assert!(text.starts_with("3 readings, "));
+        assert!(text.contains(&format!("11 Advance       {} 2\n", "#".repeat(30))));
+        assert!(text.contains(&format!("0  {} 2 (66%)\n", "#".repeat(30))));
+
+        let csv = format_report_csv(&report, &data);
+        assert!(csv.starts_with("section,key,month,count\nhexagram,11 Advance,,2\n"));
+        assert!(csv.contains("\ntopic,career,"));
+
+        let html = format_report_html(&report, &data);
+        assert!(html.contains("<td>䷊ 11 Advance</td><td class=\"count\">2</td>"));
+        assert!(html.ends_with("</html>\n"));
+    }
+
+    #[test]
+    fn test_format_accuracy() {
+        let data = IChingData::load().unwrap();
+        let mut entries: Vec<JournalEntry> = ["11", "32->34"]
+            .iter()
+            .map(|input| JournalEntry::new(input.parse().unwrap()))
+            .collect();
+        entries[0].outcome = Some(Outcome {
+            resolved: true,
+            accuracy: Some(5),
+            resolved_on: None,
+            note: None,
+        });
+
+        let text = format_accuracy(&Accuracy::new(&entries));
+        assert!(text.starts_with("1 of 2 readings resolved\n"));
+        assert!(text.contains("\nOverall           2         1       5.0       -\n"));
+        assert!(text.ends_with("\nRatings   1: 0  2: 0  3: 0  4: 0  5: 1\n"));
+
+        let line = format_journal_line(&entries[0], &data, Glyphs::Ascii).unwrap();
+        assert!(line.ends_with("11 Advance  [resolved, 5/5]"));
+        assert_eq!(
+            format_accuracy(&Accuracy::new(&[])),
+            "No saved readings to report on\n"
+        );
+    }
+
+    #[test]
+    fn test_convert_numberings() {
+        let conversions = [
+            (Numbering::Binary, "010001", Numbering::KingWen, "3"),
+            (Numbering::Binary, "0b101010", Numbering::KingWen, "64"),
+            (Numbering::KingWen, "3", Numbering::Binary, "010001"),
+            (Numbering::KingWen, "1", Numbering::Index, "0"),
+            (Numbering::Index, "63", Numbering::Unicode, "䷿"),
+            (Numbering::Unicode, "䷖", Numbering::Fuxi, "1"),
+            (Numbering::Fuxi, "63", Numbering::KingWen, "1"),
+        ];
+        for (from, value, to, expected) in conversions {
+            assert_eq!(to.format(from.parse(value).unwrap()), expected, "{}", value);
+        }
+        for (from, value) in [
+            (Numbering::KingWen, "0"),
+            (Numbering::Index, "64"),
+            (Numbering::Fuxi, "64"),
+            (Numbering::Binary, "0101"),
+            (Numbering::Unicode, "䷀䷁"),
+        ] {
+            assert!(from.parse(value).is_err(), "{}", value);
+        }
+    }
 
-        let brief = format_brief(&reading).unwrap();
-        println!("Brief output: '{}'", brief);
-        assert!(brief.contains("Q: Test question"));
-        // Just check that it has some content - the specific format may vary
-        assert!(!brief.is_empty());
+    #[test]
+    fn test_parse_question_list() {
+        let content = "# Weekly review\nShould I move?\n\n  How is the project going?  \n";
+        assert_eq!(
+            parse_question_list(content),
+            vec!["Should I move?", "How is the project going?"]
+        );
     }
 
     #[test]
-    fn test_format_full() {
+    fn test_format_markdown_report() {
         let diviner = Diviner::new();
-        let reading = diviner
-            .cast_reading_from_numbers([7, 8, 7, 8, 7, 8], Some("Test question".to_string()))
-            .unwrap();
+        let readings = vec![
+            diviner
+                .cast_reading_from_numbers([7, 7, 7, 7, 7, 9], Some("First".to_string()))
+                .unwrap(),
+            diviner.cast_reading_from_numbers([8; 6], None).unwrap(),
+        ];
+
+        let report = format_markdown_report(&readings, &IChingData::load().unwrap()).unwrap();
+        assert!(report.contains("## 1. First"));
+        assert!(report.contains("## 2. (no question)"));
+        assert!(report.contains("**Line 6:**"));
+    }
 
-        let full = format_full(&reading).unwrap();
-        assert!(full.contains("Question: Test question"));
-        assert!(full.contains("Traditional numbers"));
-        assert!(full.contains("Upper trigram"));
-        assert!(full.contains("Lower trigram"));
+    #[test]
+    fn test_parse_hexagram_list() {
+        assert_eq!(parse_hexagram_list("11..14").unwrap(), vec![11, 12, 13, 14]);
+        assert_eq!(parse_hexagram_list("1, 24,64").unwrap(), vec![1, 24, 64]);
+        assert!(parse_hexagram_list("20..12").is_err());
+        assert!(parse_hexagram_list("0..3").is_err());
+        assert!(parse_hexagram_list("1,65").is_err());
     }
 
     #[test]
-    fn test_parse_hexagram_number() {
-        let mut diviner = Diviner::new();
-        let reading = parse_input_and_create_reading(&mut diviner, "1").unwrap();
-        assert_eq!(reading.primary_hexagram(), 1);
+    fn test_format_sequence() {
+        let data = IChingData::load().unwrap();
+        let story = format_sequence(&[11, 12, 20, 3], &data, Glyphs::Ascii).unwrap();
+        assert!(story.starts_with("11 Advance\n  "));
+        assert!(story.contains("so Hindrance (standstill) follows."));
+        assert!(story.contains("(13-19 skipped)\n\n20 Watching"));
+        assert!(story.contains("(back to 3)\n\n3 Beginning"));
     }
 
     #[test]
-    fn test_parse_line_numbers() {
-        let mut diviner = Diviner::new();
-        let reading = parse_input_and_create_reading(&mut diviner, "7,8,9,6,7,8").unwrap();
-        assert_eq!(reading.traditional_numbers(), [7, 8, 9, 6, 7, 8]);
+    fn test_format_wings() {
+        let shuogua = format_wings(
+            WingsSection::Shuogua,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+        )
+        .unwrap();
+        assert_eq!(shuogua.lines().count(), 16);
+        assert!(shuogua.starts_with("☰ Qian (Heaven)\n  father, horse, head, northwest; "));
+
+        let za_gua = format_wings(
+            WingsSection::ZaGua,
+            &IChingData::load().unwrap(),
+            Glyphs::Ascii,
+        )
+        .unwrap();
+        assert_eq!(za_gua.lines().count(), 64);
+        assert!(za_gua.starts_with("1 Initiating: Initiating is firm."));
+
+        let reading: Reading = "63".parse().unwrap();
+        let full = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+            &FullOptions {
+                include_wings: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(full.contains("=== Ten Wings ===\nSequence: "));
+        assert!(full.contains("Upper trigram, Kan: middle son, pig, ear, north; "));
     }
 
     #[test]
-    fn test_parse_unicode_character() {
-        let mut diviner = Diviner::new();
-        let reading = parse_input_and_create_reading(&mut diviner, "䷀").unwrap();
-        assert_eq!(reading.primary_hexagram(), 1);
+    fn test_format_history() {
+        let data = IChingData::load().unwrap();
+        let history = format_history(12, &data, Glyphs::Ascii).unwrap();
+        assert!(history.starts_with("12 Hindrance"));
+        assert!(history.contains("\nZuo Zhuan, Duke Zhuang year 22 (672 BCE)\n"));
+        assert!(history.contains("\nCast: 20 Watching, line 4 changing -> 12 Hindrance\n"));
+
+        let history = format_history(64, &data, Glyphs::Ascii).unwrap();
+        assert!(history.ends_with("\nNo recorded consultation involves this hexagram.\n"));
+        assert!(format_history(65, &data, Glyphs::Ascii).is_err());
     }
 
     #[test]
-    fn test_invalid_input() {
-        let mut diviner = Diviner::new();
-        assert!(parse_input_and_create_reading(&mut diviner, "65").is_err());
-        assert!(parse_input_and_create_reading(&mut diviner, "7,8,5,6,7,8").is_err());
-        assert!(parse_input_and_create_reading(&mut diviner, "invalid").is_err());
+    fn test_format_xref() {
+        let data = IChingData::load().unwrap();
+        let xref = format_xref("1.6".parse().unwrap(), &data, Glyphs::Ascii).unwrap();
+        assert!(xref.starts_with("1 Initiating, line 6\nArrogant dragon"));
+        assert!(xref.contains("\n\nSee also 2 Responding, line 6\n"));
+        assert!(xref.contains("\nNote: Yang and yin"));
+        let xref = format_xref("4.1".parse().unwrap(), &data, Glyphs::Ascii).unwrap();
+        assert!(xref.ends_with("\nNo cross-references from line 4.1.\n"));
+
+        // Full output points to the linked lines
+        let reading: Reading = "7,7,7,7,7,9".parse().unwrap();
+        let full =
+            format_full_with_data(&reading, &data, Glyphs::Ascii, &FullOptions::default()).unwrap();
+        assert!(full.contains("\nSee also: 2 Responding, line 6\n"));
     }
 
     #[test]
-    fn test_parse_changing_hexagram_numbers() {
-        let mut diviner = Diviner::new();
-        let reading = parse_input_and_create_reading(&mut diviner, "32→34").unwrap();
-        assert_eq!(reading.primary_hexagram(), 32);
-        assert!(reading.has_changing_lines());
-        if let Some(transformed) = reading.transformed_hexagram() {
-            assert_eq!(transformed.primary_hexagram(), 34);
-        } else {
-            panic!("Expected transformed hexagram");
-        }
+    fn test_focus_line() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "9,7,7,7,6,7".parse().unwrap();
+        let options = FullOptions {
+            focus_line: Some(5),
+            ..FullOptions::default()
+        };
+        let full = format_full_with_data(&reading, &data, Glyphs::Ascii, &options).unwrap();
+        assert!(full.starts_with("=== Focus: Line 5 ===\nLine 5: He whose truth"));
+        // The rest of the changing lines follow as usual
+        let changing = &full[full.find("=== Changing Lines ===").unwrap()..];
+        assert!(changing.contains("Line 1: "));
+        assert!(!changing.contains("Line 5: "));
+
+        let brief = format::render(
+            &reading,
+            &data,
+            Format::Brief,
+            &RenderOptions {
+                sections: options,
+                ..RenderOptions::default()
+            },
+        )
+        .unwrap();
+        assert!(
+            brief
+                .lines()
+                .nth(1)
+                .unwrap()
+                .starts_with("Focus (line 5): ")
+        );
+
+        assert!(choose_focus_line(&reading, Some(3)).is_err());
+        assert_eq!(choose_focus_line(&reading, Some(1)).unwrap(), Some(1));
+        let mut prompts = Vec::new();
+        let chosen = prompt_focus_line(&[1, 5], &mut "2\n5\n".as_bytes(), &mut prompts);
+        assert_eq!(chosen.unwrap(), Some(5));
+        assert!(String::from_utf8(prompts).unwrap().contains("Give one of"));
+        assert_eq!(
+            prompt_focus_line(&[1, 5], &mut "\n".as_bytes(), &mut io::sink()).unwrap(),
+            None
+        );
     }
 
     #[test]
-    fn test_parse_changing_hexagram_ascii_arrow() {
-        let mut diviner = Diviner::new();
-        let reading = parse_input_and_create_reading(&mut diviner, "1->2").unwrap();
-        assert_eq!(reading.primary_hexagram(), 1);
-        assert!(reading.has_changing_lines());
-        if let Some(transformed) = reading.transformed_hexagram() {
-            assert_eq!(transformed.primary_hexagram(), 2);
-        } else {
-            panic!("Expected transformed hexagram");
-        }
+    fn test_explain() {
+        let text = explain(Some("relating hexagram")).unwrap();
+        assert!(text.starts_with("transformed hexagram (also relating hexagram, "));
+        assert!(text.ends_with("\nSee also: primary hexagram, changing line\n"));
+        let error = explain(Some("nuclaer")).unwrap_err().to_string();
+        assert!(error.ends_with("did you mean \"nuclear hexagram\"?"));
+        assert!(
+            explain(None)
+                .unwrap()
+                .contains("\nKan (water, the abysmal, ☵)\n")
+        );
     }
 
     #[test]
-    fn test_parse_changing_hexagram_unicode() {
-        let mut diviner = Diviner::new();
-        let reading = parse_input_and_create_reading(&mut diviner, "䷀→䷁").unwrap();
-        assert_eq!(reading.primary_hexagram(), 1);
-        assert!(reading.has_changing_lines());
-        if let Some(transformed) = reading.transformed_hexagram() {
-            assert_eq!(transformed.primary_hexagram(), 2);
-        } else {
-            panic!("Expected transformed hexagram");
-        }
+    fn test_format_unicode_table() {
+        let table = format_unicode_table(&IChingData::load().unwrap()).unwrap();
+        let rows: Vec<&str> = table.lines().collect();
+        assert_eq!(rows.len(), 64);
+        assert!(rows[0].starts_with(" 1  ䷀  U+4DC0  "));
+        assert!(rows[63].starts_with("64  ䷿  U+4DFF  "));
+    }
+
+    #[test]
+    fn test_format_notification() {
+        let data = IChingData::load().unwrap();
+        let diviner = Diviner::new();
+
+        let reading = diviner.cast_reading_from_numbers([7; 6], None).unwrap();
+        let notification = format_notification(&reading, &data);
+        assert_eq!(notification.title, "Daily reading");
+        assert!(notification.body.starts_with("䷀ 1 "));
+        assert!(
+            notification
+                .body
+                .contains(&data.get_hexagram(1).unwrap().judgment.text)
+        );
+
+        let reading = reading.with_question(Some("Today?".to_string()));
+        assert_eq!(format_notification(&reading, &data).title, "Today?");
+    }
+
+    #[test]
+    fn test_format_from_json() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "7,8,9,6,7,8".parse().unwrap();
+        let reading = reading.with_question(Some("Stay?".to_string()));
+        let json = json_reading_with_data(&reading, &data).unwrap();
+
+        // A served reading shows as it would have locally
+        assert_eq!(
+            format_full_from_json(&json, Glyphs::Unicode).unwrap(),
+            format_full_with_data(&reading, &data, Glyphs::Unicode, &FullOptions::default())
+                .unwrap()
+                + "\n"
+        );
+        assert_eq!(
+            format_brief_from_json(&json, Glyphs::Ascii).unwrap(),
+            format_brief_with_data(&reading, &data, Glyphs::Ascii).unwrap() + "\n"
+        );
     }
 }
@@ -0,0 +1,239 @@
+use crate::card::Theme;
+use crate::cli::{GlyphMode, Style};
+use crate::format::{Format, TextOnly};
+use crate::interpretation::framing::Topic;
+use crate::interpretation::rules::Rule;
+use crate::json::{JsonCase, JsonStyle};
+use crate::lang::Lang;
+use crate::links::Links;
+use crate::rendered::Part;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the config file location
+pub const CONFIG_PATH_ENV: &str = "I_CHING_CONFIG";
+
+/// User settings read from `config.toml`
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    pub notify: NotifyConfig,
+    pub llm: LlmConfig,
+    /// Named sets of reading defaults, chosen with `--profile`
+    pub profiles: BTreeMap<String, Profile>,
+    /// Colors for `i-ching card`
+    pub card: Theme,
+    /// Hyperlinks from hexagram names and lines in full output
+    pub links: Links,
+}
+
+/// Reading defaults for one `[profiles.NAME]` table; flags on the command line override them
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Profile {
+    pub format: Option<Format>,
+    pub glyphs: Option<GlyphMode>,
+    pub json_style: Option<JsonStyle>,
+    pub json_case: Option<JsonCase>,
+    pub style: Option<Style>,
+    pub lang: Option<Lang>,
+    pub rule: Option<Rule>,
+    pub topic: Option<Topic>,
+    pub text_only: Option<TextOnly>,
+    /// Show only these parts of full output
+    pub sections: Option<Vec<Part>>,
+    /// Include the King Wen partner hexagram
+    pub pair: bool,
+    /// Include the Ten Wings commentary
+    pub wings: bool,
+    /// Add the Guidance list
+    pub guidance: bool,
+    /// Note the moon phase and solar term
+    pub almanac: bool,
+}
+
+/// Where `i-ching notify` sends its reading
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct NotifyConfig {
+    /// Show a desktop notification
+    pub desktop: bool,
+    /// Slack- or Discord-compatible incoming webhook URL
+    pub webhook: Option<String>,
+    /// Question to cast the reading for
+    pub question: Option<String>,
+}
+
+impl Default for NotifyConfig {
+    fn default() -> Self {
+        Self {
+            desktop: true,
+            webhook: None,
+            question: None,
+        }
+    }
+}
+
+/// Where `i-ching interpret` (the `llm` feature) sends readings
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct LlmConfig {
+    /// Service to ask (default: ollama)
+    pub provider: Option<LlmProvider>,
+    /// Model name (default depends on the provider)
+    pub model: Option<String>,
+    /// Base URL overriding the provider's default
+    pub endpoint: Option<String>,
+    /// File with a prompt template using `{question}` and `{reading}` placeholders
+    pub prompt_template: Option<PathBuf>,
+}
+
+/// Chat API an interpretation request is sent to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize, ValueEnum)]
+#[serde(rename_all = "lowercase")]
+pub enum LlmProvider {
+    /// OpenAI-compatible chat completions (reads `OPENAI_API_KEY`)
+    Openai,
+    /// A local Ollama server
+    #[default]
+    Ollama,
+}
+
+impl Config {
+    /// `$I_CHING_CONFIG`, or `i-ching/config.toml` in the user config directory
+    pub fn default_path() -> Result<PathBuf> {
+        if let Some(path) = env::var_os(CONFIG_PATH_ENV) {
+            return Ok(PathBuf::from(path));
+        }
+
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine the user config directory"))?;
+        Ok(config_dir.join("i-ching").join("config.toml"))
+    }
+
+    /// Load the config from its default location (defaults if there is no file)
+    pub fn load() -> Result<Self> {
+        Self::load_from(&Self::default_path()?)
+    }
+
+    /// Load the config from a specific file (defaults if it doesn't exist)
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read config {}", path.display()))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config {}", path.display()))
+    }
+
+    /// The profile called `name`, or an error listing the ones that are defined
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            if self.profiles.is_empty() {
+                anyhow::anyhow!("Unknown profile '{}': no [profiles] in the config", name)
+            } else {
+                let names: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+                anyhow::anyhow!("Unknown profile '{}' (defined: {})", name, names.join(", "))
+            }
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_notify_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [notify]
+            desktop = false
+            webhook = "https://example.com/hook"
+            "#,
+        )
+        .unwrap();
+
+        assert!(!config.notify.desktop);
+        assert_eq!(
+            config.notify.webhook.as_deref(),
+            Some("https://example.com/hook")
+        );
+        assert_eq!(config.notify.question, None);
+    }
+
+    #[test]
+    fn test_parse_llm_section() {
+        let config: Config = toml::from_str(
+            r#"
+            [llm]
+            provider = "openai"
+            model = "gpt-4o-mini"
+            prompt_template = "/home/me/i-ching-prompt.txt"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config.llm.provider, Some(LlmProvider::Openai));
+        assert_eq!(config.llm.model.as_deref(), Some("gpt-4o-mini"));
+        assert_eq!(config.llm.endpoint, None);
+        assert!(config.llm.prompt_template.is_some());
+    }
+
+    #[test]
+    fn test_parse_profiles() {
+        let config: Config = toml::from_str(
+            r#"
+            [profiles.scholarly]
+            wings = true
+            pair = true
+            style = "liuyao"
+
+            [profiles.quick]
+            format = "brief"
+            glyphs = "ascii"
+            rule = "all"
+            "#,
+        )
+        .unwrap();
+
+        let scholarly = config.profile("scholarly").unwrap();
+        assert!(scholarly.wings && scholarly.pair && !scholarly.guidance);
+        assert_eq!(scholarly.style, Some(Style::Liuyao));
+        assert_eq!(scholarly.format, None);
+
+        let quick = config.profile("quick").unwrap();
+        assert_eq!(quick.format, Some(Format::Brief));
+        assert_eq!(quick.glyphs, Some(GlyphMode::Ascii));
+        assert_eq!(quick.rule, Some(Rule::All));
+
+        let err = config.profile("verbose").unwrap_err().to_string();
+        assert!(err.contains("defined: quick, scholarly"), "{}", err);
+        assert!(
+            toml::from_str::<Config>(
+                "[profiles.x]
+format = \"long\""
+            )
+            .is_err()
+        );
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() {
+        let path = env::temp_dir().join("i-ching-config-test-missing.toml");
+        let config = Config::load_from(&path).unwrap();
+        assert!(config.notify.desktop);
+        assert!(config.notify.webhook.is_none());
+    }
+
+    #[test]
+    fn test_unknown_keys_rejected() {
+        assert!(toml::from_str::<Config>("[notify]\nwebhok = \"x\"").is_err());
+    }
+}
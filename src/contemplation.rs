@@ -0,0 +1,90 @@
+use crate::glyphs::Glyphs;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::{self, Write};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Pause between indicator updates
+const TICK: Duration = Duration::from_millis(250);
+
+/// Width of the progress indicator in dots
+const DOTS: usize = 12;
+
+/// Sit with the question for `duration`, drawing a row of dots to `out`, and
+/// return a generator for the cast
+///
+/// Each tick oversleeps by a slightly different number of nanoseconds. Those
+/// overshoots are hashed into the seed along with bytes from the thread-local
+/// generator, so the cast depends partly on the pause itself.
+pub fn contemplate(duration: Duration, glyphs: Glyphs, out: &mut impl Write) -> io::Result<StdRng> {
+    let dot = match glyphs {
+        Glyphs::Unicode => "·",
+        Glyphs::Ascii => ".",
+    };
+    let start = Instant::now();
+    let mut jitter = Vec::new();
+
+    loop {
+        let elapsed = start.elapsed();
+        let shown = if duration.is_zero() {
+            DOTS
+        } else {
+            (DOTS as f64 * elapsed.as_secs_f64() / duration.as_secs_f64()).min(DOTS as f64) as usize
+        };
+        write!(out, "\rContemplating {}", dot.repeat(shown))?;
+        out.flush()?;
+        if elapsed >= duration {
+            break;
+        }
+
+        let nap = TICK.min(duration - elapsed);
+        let before = Instant::now();
+        thread::sleep(nap);
+        jitter.push(before.elapsed().saturating_sub(nap).as_nanos());
+    }
+    // Clear the indicator so the reading starts on a clean line
+    write!(out, "\r{}\r", " ".repeat("Contemplating ".len() + DOTS))?;
+    out.flush()?;
+
+    Ok(StdRng::from_seed(seed(&jitter, rand::rng().random())))
+}
+
+/// Fold the timing jitter into a random seed, a 64-bit word at a time
+fn seed(jitter: &[u128], random: [u8; 32]) -> [u8; 32] {
+    let mut seed = random;
+    for (i, chunk) in seed.chunks_exact_mut(8).enumerate() {
+        let mut hasher = DefaultHasher::new();
+        (i, jitter).hash(&mut hasher);
+        for (byte, mixed) in chunk.iter_mut().zip(hasher.finish().to_le_bytes()) {
+            *byte ^= mixed;
+        }
+    }
+    seed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Diviner;
+
+    #[test]
+    fn test_contemplate() {
+        let mut out = Vec::new();
+        let mut rng = contemplate(Duration::from_millis(30), Glyphs::Ascii, &mut out).unwrap();
+
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\rContemplating ............"));
+        assert!(out.ends_with('\r'));
+        let reading = Diviner::cast_reading_with(&mut rng, None);
+        assert!((1..=64).contains(&reading.primary_hexagram()));
+    }
+
+    #[test]
+    fn test_seed_mixes_in_jitter() {
+        let random = [7; 32];
+        assert_ne!(seed(&[1, 2, 3], random), seed(&[1, 2, 4], random));
+        assert_eq!(seed(&[1, 2, 3], random), seed(&[1, 2, 3], random));
+    }
+}
@@ -1,11 +1,19 @@
+use crate::core::king_wen;
+use crate::core::trigram::Trigram;
+use flate2::read::DeflateDecoder;
 use serde::{Deserialize, Serialize};
+use serde_json::value::RawValue;
 use std::collections::HashMap;
 use std::env;
+use std::fmt;
 use std::fs;
+use std::io::Read;
 use std::path::PathBuf;
+use std::str::FromStr;
+use std::sync::OnceLock;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
-pub struct Trigram {
+pub struct TrigramData {
     pub name: String,
     pub chinese: String,
     pub unicode: String,
@@ -13,11 +21,33 @@ pub struct Trigram {
     pub element: String,
     pub attribute: String,
     pub lines: String,
+    #[serde(default)]
+    pub discussion: Option<TrigramDiscussion>,
+}
+
+/// Associations from the Discussion of the Trigrams (Shuo Gua)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrigramDiscussion {
+    /// Place in the trigram family (father, eldest son, ...)
+    pub family: String,
+    pub animal: String,
+    /// Part of the body
+    pub body: String,
+    /// Compass direction in the Later Heaven arrangement
+    pub direction: String,
+    /// Season at that point of the Later Heaven cycle
+    pub season: String,
+    /// Compass direction in the Earlier Heaven arrangement
+    pub earlier_direction: String,
+    /// Further images listed for the trigram
+    pub images: Vec<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct HexagramJudgment {
+    /// The received judgment text alone
     pub text: String,
+    /// Wilhelm's explanation of it
     pub commentary: String,
 }
 
@@ -29,30 +59,281 @@ pub struct HexagramImage {
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LineInterpretation {
+    /// The received line statement alone
     pub text: String,
+    /// Wilhelm's explanation of it
     pub comments: String,
 }
 
+/// How a hexagram's name is said in Mandarin
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Pronunciation {
+    /// Tone-marked pinyin, syllables separated by spaces
+    pub pinyin: String,
+    /// IPA with tone diacritics, one word per pinyin syllable
+    pub ipa: String,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Hexagram {
     pub number: u8,
     pub name: String,
     pub chinese: String,
     pub pinyin: String,
+    #[serde(default)]
+    pub pronunciation: Option<Pronunciation>,
     pub unicode: String,
     pub binary: String,
     pub opposite: String,
-    pub upper_trigram: String,
-    pub lower_trigram: String,
+    pub upper_trigram: Trigram,
+    pub lower_trigram: Trigram,
     pub description: String,
+    /// Why this hexagram follows the previous one, after the Sequence (Xu Gua)
+    #[serde(default)]
+    pub sequence: Option<String>,
+    /// Terse characterization from the Miscellaneous Notes (Za Gua)
+    #[serde(default)]
+    pub miscellaneous: Option<String>,
     pub judgment: HexagramJudgment,
     pub image: HexagramImage,
     pub lines: HashMap<String, LineInterpretation>,
 }
 
+impl Hexagram {
+    /// Line pattern as bits: bit 0 is the bottom line, yang = 1
+    pub fn bits(&self) -> u8 {
+        king_wen::to_binary(self.number).unwrap_or_default()
+    }
+
+    /// The hexagram with the given line pattern (bit 0 is the bottom line)
+    pub fn from_bits(data: &IChingData, bits: u8) -> Option<&Hexagram> {
+        data.get_hexagram(king_wen::from_binary(bits))
+    }
+
+    /// Number of the hexagram with one line (1-6, bottom first) changed
+    pub fn flip_line(&self, position: usize) -> u8 {
+        king_wen::from_binary(king_wen::flip_line(self.bits(), position))
+    }
+
+    /// Number of the hexagram with the upper and lower trigrams exchanged
+    pub fn swap_trigrams(&self) -> u8 {
+        king_wen::from_binary(king_wen::swap_trigrams(self.bits()))
+    }
+
+    /// Number of the hexagram turned upside down
+    pub fn mirror(&self) -> u8 {
+        king_wen::from_binary(king_wen::mirror(self.bits()))
+    }
+}
+
+/// e.g. "䷀ 1 The Creative"
+impl fmt::Display for Hexagram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} {} {}", self.unicode, self.number, self.name)
+    }
+}
+
+// Generated by build.rs: DATA_CHECKSUMS, the CRC-32 of each data file, and
+// EMBEDDED_HEXAGRAMS, (hexagram number, CRC-32, deflate-compressed JSON) triples
+include!(concat!(env!("OUT_DIR"), "/embedded_hexagrams.rs"));
+
+/// Finds a hexagram by number outside the data files, e.g. in the SQLite
+/// store; `None` if it can't
+#[cfg(feature = "sqlite")]
+pub type HexagramLookup = std::sync::Arc<dyn Fn(u8) -> Option<Hexagram> + Send + Sync>;
+
+/// Where an unparsed hexagram's JSON lives
+enum HexagramSource {
+    /// Deflate stream embedded in the binary
+    Compressed(&'static [u8]),
+    /// JSON read from a data file
+    Json(Box<RawValue>),
+    /// Looked up instead of parsed, reading the other source if that fails
+    #[cfg(feature = "sqlite")]
+    Lookup(HexagramLookup, Box<HexagramSource>),
+}
+
+/// Hexagram kept unparsed until it is first looked up
+struct LazyHexagram {
+    source: HexagramSource,
+    parsed: OnceLock<Hexagram>,
+}
+
+impl LazyHexagram {
+    fn new(source: HexagramSource) -> Self {
+        Self {
+            source,
+            parsed: OnceLock::new(),
+        }
+    }
+
+    /// The parsed hexagram
+    ///
+    /// Its source passed its checksum at load, so a failure here means the
+    /// build wrote data this version can't read, and panics rather than
+    /// passing the hexagram off as missing.
+    fn get(&self, number: &str) -> &Hexagram {
+        self.parsed.get_or_init(|| self.source.read(number))
+    }
+}
+
+impl HexagramSource {
+    fn read(&self, number: &str) -> Hexagram {
+        let (parsed, verified) = match self {
+            HexagramSource::Compressed(bytes) => (
+                inflate(bytes)
+                    .map_err(|e| e.to_string())
+                    .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string())),
+                format!("embedded stream crc32 {:08x}", checksum(bytes)),
+            ),
+            HexagramSource::Json(raw) => (
+                serde_json::from_str(raw.get()).map_err(|e| e.to_string()),
+                "hexagrams.json".to_string(),
+            ),
+            #[cfg(feature = "sqlite")]
+            HexagramSource::Lookup(lookup, fallback) => {
+                return number
+                    .parse()
+                    .ok()
+                    .and_then(|number| lookup(number))
+                    .unwrap_or_else(|| fallback.read(number));
+            }
+        };
+        parsed.unwrap_or_else(|e| {
+            panic!(
+                "Hexagram {} can't be read ({}), though its data passed its checksum ({}); the build is broken",
+                number, e, verified
+            )
+        })
+    }
+}
+
+/// Where one body of text in the data files comes from, and on what terms
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Provenance {
+    /// Short name, e.g. "wilhelm"
+    pub corpus: String,
+    pub title: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub translator: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub year: Option<u16>,
+    pub license: String,
+    /// A URL, or a note when there is none
+    pub source: String,
+    /// The data files the corpus fills
+    #[serde(default)]
+    pub files: Vec<String>,
+}
+
+/// Provenance of the embedded corpora, from `data/provenance.json`
+const PROVENANCE: &str = include_str!("../../data/provenance.json");
+
+/// A consultation of the Changes recorded in the classical histories
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Consultation {
+    /// Book and chapter, e.g. "Zuo Zhuan, Duke Xi year 15"
+    pub source: String,
+    /// Year of the entry it's recorded under
+    pub year_bce: u16,
+    /// Who consulted, or read the figure
+    pub diviner: String,
+    pub question: String,
+    pub hexagram: u8,
+    /// Changing lines, 1 (bottom) to 6
+    #[serde(default)]
+    pub lines: Vec<u8>,
+    /// The hexagram those lines change it to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub becomes: Option<u8>,
+    /// How the figure was read at the time
+    pub reading: String,
+    /// What the history says came of it
+    pub outcome: String,
+}
+
+impl Consultation {
+    /// Whether `number` is the hexagram consulted or the one it became
+    pub fn involves(&self, number: u8) -> bool {
+        self.hexagram == number || self.becomes == Some(number)
+    }
+}
+
+/// Recorded consultations, from `data/history.json`
+const HISTORY: &str = include_str!("../../data/history.json");
+
+/// One line of one hexagram, written `<hexagram>.<line>`, e.g. "1.6"
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct LineRef {
+    pub hexagram: u8,
+    /// 1 (bottom) to 6
+    pub line: u8,
+}
+
+impl fmt::Display for LineRef {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}.{}", self.hexagram, self.line)
+    }
+}
+
+impl FromStr for LineRef {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = || {
+            anyhow::anyhow!(
+                "Invalid line: '{}'. Expected <hexagram>.<line>, e.g. 1.6",
+                s.trim()
+            )
+        };
+        let (hexagram, line) = s.trim().split_once('.').ok_or_else(invalid)?;
+        let hexagram: u8 = hexagram.parse().map_err(|_| invalid())?;
+        let line: u8 = line.parse().map_err(|_| invalid())?;
+        if !(1..=64).contains(&hexagram) || !(1..=6).contains(&line) {
+            return Err(anyhow::anyhow!(
+                "Invalid line: '{}'. Hexagrams run 1 to 64 and lines 1 to 6",
+                s.trim()
+            ));
+        }
+        Ok(LineRef { hexagram, line })
+    }
+}
+
+impl TryFrom<String> for LineRef {
+    type Error = anyhow::Error;
+
+    fn try_from(s: String) -> Result<Self, Self::Error> {
+        s.parse()
+    }
+}
+
+impl From<LineRef> for String {
+    fn from(line: LineRef) -> Self {
+        line.to_string()
+    }
+}
+
+/// Line texts that quote or mirror each other
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CrossReference {
+    pub lines: Vec<LineRef>,
+    /// What the lines share
+    pub note: String,
+}
+
+/// Cross-references between line texts, from `data/crossrefs.json`
+const CROSS_REFERENCES: &str = include_str!("../../data/crossrefs.json");
+
 pub struct IChingData {
-    pub trigrams: HashMap<String, Trigram>,
-    pub hexagrams: HashMap<String, Hexagram>,
+    pub trigrams: HashMap<Trigram, TrigramData>,
+    hexagrams: HashMap<String, LazyHexagram>,
+    provenance: Vec<Provenance>,
+    history: Vec<Consultation>,
+    cross_references: Vec<CrossReference>,
 }
 
 impl IChingData {
@@ -63,16 +344,34 @@ impl IChingData {
 
     /// Load data embedded in the binary at compile time
     fn load_embedded() -> Result<Self, Box<dyn std::error::Error>> {
-        // Embed the JSON files at compile time
-        let trigrams_content = include_str!("../../data/trigrams.json");
-        let hexagrams_content = include_str!("../../data/hexagrams.json");
+        // The JSON files are deflate-compressed by build.rs
+        let trigrams_content = inflate(include_bytes!(concat!(
+            env!("OUT_DIR"),
+            "/trigrams.json.deflate"
+        )))?;
+        verify_checksum("trigrams.json", trigrams_content.as_bytes())?;
+        let trigrams: HashMap<Trigram, TrigramData> = serde_json::from_str(&trigrams_content)?;
 
-        let trigrams: HashMap<String, Trigram> = serde_json::from_str(trigrams_content)?;
-        let hexagrams: HashMap<String, Hexagram> = serde_json::from_str(hexagrams_content)?;
+        // Checking the compressed streams keeps hexagrams unparsed until looked up
+        let hexagrams = EMBEDDED_HEXAGRAMS
+            .iter()
+            .map(|&(number, expected, bytes)| {
+                if checksum(bytes) != expected {
+                    return Err(format!("Embedded hexagram {} failed its checksum", number));
+                }
+                Ok((
+                    number.to_string(),
+                    LazyHexagram::new(HexagramSource::Compressed(bytes)),
+                ))
+            })
+            .collect::<Result<_, _>>()?;
 
         Ok(IChingData {
             trigrams,
             hexagrams,
+            provenance: serde_json::from_str(PROVENANCE)?,
+            history: serde_json::from_str(HISTORY)?,
+            cross_references: serde_json::from_str(CROSS_REFERENCES)?,
         })
     }
 
@@ -89,7 +388,6 @@ impl IChingData {
                 e
             )
         })?;
-        let trigrams: HashMap<String, Trigram> = serde_json::from_str(&trigrams_content)?;
 
         // Load hexagrams
         let hexagrams_path = data_dir.join("hexagrams.json");
@@ -100,11 +398,55 @@ impl IChingData {
                 e
             )
         })?;
-        let hexagrams: HashMap<String, Hexagram> = serde_json::from_str(&hexagrams_content)?;
+
+        verify_checksum("trigrams.json", trigrams_content.as_bytes())?;
+        verify_checksum("hexagrams.json", hexagrams_content.as_bytes())?;
+        Ok(Self::from_json(&trigrams_content, &hexagrams_content)?)
+    }
+
+    /// Load data without blocking the async runtime: embedded data needs no
+    /// I/O, and the data files fallback is read with `tokio::fs`
+    #[cfg(feature = "async")]
+    pub async fn load_async() -> Result<Self, Box<dyn std::error::Error + Send + Sync>> {
+        if let Ok(data) = Self::load_embedded() {
+            return Ok(data);
+        }
+
+        let data_dir = Self::find_data_directory().map_err(|e| e.to_string())?;
+        let read = |name: &'static str| {
+            let path = data_dir.join(name);
+            async move {
+                tokio::fs::read_to_string(&path)
+                    .await
+                    .map_err(|e| format!("Failed to read {} from {}: {}", name, path.display(), e))
+            }
+        };
+        let (trigrams_content, hexagrams_content) =
+            tokio::try_join!(read("trigrams.json"), read("hexagrams.json"))?;
+
+        verify_checksum("trigrams.json", trigrams_content.as_bytes())?;
+        verify_checksum("hexagrams.json", hexagrams_content.as_bytes())?;
+        Ok(Self::from_json(&trigrams_content, &hexagrams_content)?)
+    }
+
+    pub(crate) fn from_json(
+        trigrams_content: &str,
+        hexagrams_content: &str,
+    ) -> serde_json::Result<Self> {
+        let trigrams: HashMap<Trigram, TrigramData> = serde_json::from_str(trigrams_content)?;
+        let raw_hexagrams: HashMap<String, Box<RawValue>> =
+            serde_json::from_str(hexagrams_content)?;
+        let hexagrams = raw_hexagrams
+            .into_iter()
+            .map(|(number, raw)| (number, LazyHexagram::new(HexagramSource::Json(raw))))
+            .collect();
 
         Ok(IChingData {
             trigrams,
             hexagrams,
+            provenance: serde_json::from_str(PROVENANCE)?,
+            history: serde_json::from_str(HISTORY)?,
+            cross_references: serde_json::from_str(CROSS_REFERENCES)?,
         })
     }
 
@@ -136,12 +478,73 @@ impl IChingData {
         Err("Could not find data directory with trigrams.json and hexagrams.json. Please ensure the data files are in one of these locations: ./data/, next to the executable, or in the parent directory.".into())
     }
 
+    /// Look hexagrams up with `lookup` instead of parsing their JSON, keeping
+    /// the data as the fallback for any it can't find
+    #[cfg(feature = "sqlite")]
+    pub fn with_lookup(mut self, lookup: HexagramLookup) -> Self {
+        for lazy in self.hexagrams.values_mut() {
+            let source = std::mem::replace(&mut lazy.source, HexagramSource::Compressed(&[]));
+            lazy.source = HexagramSource::Lookup(lookup.clone(), Box::new(source));
+        }
+        self
+    }
+
+    /// Parse every hexagram now rather than on first lookup, so a
+    /// long-running process pays the cost once at startup
+    pub fn warm(&self) {
+        for (number, lazy) in &self.hexagrams {
+            lazy.get(number);
+        }
+    }
+
     pub fn get_hexagram(&self, number: u8) -> Option<&Hexagram> {
-        self.hexagrams.get(&number.to_string())
+        let number = number.to_string();
+        Some(self.hexagrams.get(&number)?.get(&number))
+    }
+
+    /// A hexagram to edit in place, e.g. to swap in another translation's texts
+    pub fn hexagram_mut(&mut self, number: u8) -> Option<&mut Hexagram> {
+        let number = number.to_string();
+        let lazy = self.hexagrams.get_mut(&number)?;
+        lazy.get(&number);
+        lazy.parsed.get_mut()
     }
 
-    pub fn get_trigram(&self, name: &str) -> Option<&Trigram> {
-        self.trigrams.get(name)
+    /// All hexagrams in King Wen order (parsing any not yet looked up)
+    pub fn hexagrams(&self) -> impl Iterator<Item = &Hexagram> {
+        (1..=64).filter_map(|number| self.get_hexagram(number))
+    }
+
+    pub fn get_trigram(&self, trigram: Trigram) -> Option<&TrigramData> {
+        self.trigrams.get(&trigram)
+    }
+
+    /// Source, translator, license, and year of each corpus in the data
+    pub fn provenance(&self) -> &[Provenance] {
+        &self.provenance
+    }
+
+    /// Recorded consultations in which hexagram `number` was cast or
+    /// changed to, oldest first
+    pub fn history(&self, number: u8) -> impl Iterator<Item = &Consultation> {
+        self.history
+            .iter()
+            .filter(move |consultation| consultation.involves(number))
+    }
+
+    /// Lines whose texts quote or mirror `line`, each with the note on
+    /// what they share
+    pub fn cross_references(&self, line: LineRef) -> impl Iterator<Item = (LineRef, &str)> {
+        self.cross_references
+            .iter()
+            .filter(move |reference| reference.lines.contains(&line))
+            .flat_map(move |reference| {
+                reference
+                    .lines
+                    .iter()
+                    .filter(move |&&other| other != line)
+                    .map(move |&other| (other, reference.note.as_str()))
+            })
     }
 
     pub fn get_line_interpretation(
@@ -154,3 +557,255 @@ impl IChingData {
             .get(&line_position.to_string())
     }
 }
+
+/// First character of the Yijing Hexagram Symbols block, hexagram 1
+const FIRST_HEXAGRAM_CHAR: u32 = 0x4DC0;
+
+/// Hexagram characters (䷀-䷿) in King Wen order; index 0 is hexagram 1
+pub const HEXAGRAM_CHARS: [char; 64] = {
+    let mut chars = ['\0'; 64];
+    let mut i = 0;
+    while i < 64 {
+        chars[i] = match char::from_u32(FIRST_HEXAGRAM_CHAR + i as u32) {
+            Some(c) => c,
+            None => panic!("hexagram block is valid Unicode"),
+        };
+        i += 1;
+    }
+    chars
+};
+
+/// Unicode character for a hexagram number (1-64)
+pub fn hexagram_number_to_unicode(number: u8) -> Option<char> {
+    HEXAGRAM_CHARS
+        .get(usize::from(number).checked_sub(1)?)
+        .copied()
+}
+
+/// Hexagram number for a Yijing Unicode character (䷀-䷿)
+pub fn unicode_to_hexagram_number(unicode_char: char) -> Option<u8> {
+    let offset = (unicode_char as u32).checked_sub(FIRST_HEXAGRAM_CHAR)?;
+    (offset < 64).then(|| offset as u8 + 1)
+}
+
+/// CRC-32 checksums of the data files this build embeds, by file name
+pub fn checksums() -> &'static [(&'static str, u32)] {
+    DATA_CHECKSUMS
+}
+
+fn checksum(bytes: &[u8]) -> u32 {
+    let mut crc = flate2::Crc::new();
+    crc.update(bytes);
+    crc.sum()
+}
+
+/// Check a data file's content against the checksum recorded at build time,
+/// so a damaged binary or data file is refused rather than half read
+fn verify_checksum(name: &str, content: &[u8]) -> Result<(), String> {
+    let expected = DATA_CHECKSUMS
+        .iter()
+        .find(|(file, _)| *file == name)
+        .map(|&(_, sum)| sum);
+    match expected {
+        Some(expected) if checksum(content) != expected => Err(format!(
+            "{} failed its checksum (crc32 {:08x}, expected {:08x}); rebuild, or restore the data file",
+            name,
+            checksum(content),
+            expected
+        )),
+        _ => Ok(()),
+    }
+}
+
+/// Decompress a deflate stream produced by build.rs
+fn inflate(compressed: &[u8]) -> Result<String, Box<dyn std::error::Error>> {
+    let mut content = String::new();
+    DeflateDecoder::new(compressed).read_to_string(&mut content)?;
+    Ok(content)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[should_panic(expected = "Hexagram 1 can't be read")]
+    fn test_unreadable_hexagram_panics() {
+        LazyHexagram::new(HexagramSource::Compressed(b"not deflate")).get("1");
+    }
+
+    #[test]
+    fn test_unicode_round_trip() {
+        assert_eq!(hexagram_number_to_unicode(1), Some('䷀'));
+        assert_eq!(hexagram_number_to_unicode(64), Some('䷿'));
+        assert_eq!(hexagram_number_to_unicode(0), None);
+        assert_eq!(hexagram_number_to_unicode(65), None);
+        assert_eq!(unicode_to_hexagram_number('䷟'), Some(32));
+        assert_eq!(unicode_to_hexagram_number('☵'), None);
+
+        // The table must agree with the characters in the data set
+        let data = IChingData::load_embedded().unwrap();
+        for hexagram in data.hexagrams() {
+            let c = hexagram_number_to_unicode(hexagram.number).unwrap();
+            assert_eq!(hexagram.unicode, c.to_string());
+            assert_eq!(unicode_to_hexagram_number(c), Some(hexagram.number));
+        }
+    }
+
+    #[test]
+    fn test_provenance() {
+        let data = IChingData::load_embedded().unwrap();
+        let wilhelm = &data.provenance()[0];
+        assert_eq!(wilhelm.corpus, "wilhelm");
+        assert_eq!(wilhelm.year, Some(1950));
+        for file in [
+            "hexagrams.json",
+            "trigrams.json",
+            "worksheet.json",
+            "history.json",
+            "glossary.json",
+            "crossrefs.json",
+        ] {
+            assert!(
+                data.provenance()
+                    .iter()
+                    .any(|provenance| provenance.files.iter().any(|f| f == file)),
+                "{} has no provenance",
+                file
+            );
+        }
+    }
+
+    #[test]
+    fn test_history() {
+        let data = IChingData::load_embedded().unwrap();
+        let sources: Vec<&str> = data.history(14).map(|c| c.source.as_str()).collect();
+        assert_eq!(
+            sources,
+            ["Zuo Zhuan, Duke Min year 2", "Zuo Zhuan, Duke Xi year 25"]
+        );
+        assert_eq!(data.history(12).count(), 1);
+
+        // Each recorded change must be the one its lines make
+        for consultation in &data.history {
+            let primary = data.get_hexagram(consultation.hexagram).unwrap();
+            let mut binary: Vec<u8> = primary.binary.bytes().collect();
+            for &line in &consultation.lines {
+                // Binary strings run top to bottom
+                let bit = &mut binary[6 - usize::from(line)];
+                *bit = if *bit == b'1' { b'0' } else { b'1' };
+            }
+            let becomes = consultation.becomes.unwrap_or(consultation.hexagram);
+            assert_eq!(
+                binary,
+                data.get_hexagram(becomes).unwrap().binary.as_bytes(),
+                "{}",
+                consultation.source
+            );
+        }
+    }
+
+    #[test]
+    fn test_cross_references() {
+        let data = IChingData::load_embedded().unwrap();
+        let line = |s: &str| s.parse::<LineRef>().unwrap();
+        let linked: Vec<LineRef> = data.cross_references(line("1.6")).map(|(l, _)| l).collect();
+        assert_eq!(linked, [line("2.6")]);
+        assert_eq!(data.cross_references(line("54.1")).count(), 2);
+        assert_eq!(data.cross_references(line("4.1")).count(), 0);
+        assert!("65.1".parse::<LineRef>().is_err());
+        assert!("1.7".parse::<LineRef>().is_err());
+        assert!("1-6".parse::<LineRef>().is_err());
+
+        // Each reference links distinct lines
+        for reference in &data.cross_references {
+            let distinct: std::collections::HashSet<_> = reference.lines.iter().collect();
+            assert!(
+                reference.lines.len() >= 2 && distinct.len() == reference.lines.len(),
+                "{}",
+                reference.note
+            );
+        }
+    }
+
+    #[test]
+    fn test_embedded_data_parses() {
+        let data = IChingData::load_embedded().unwrap();
+        assert_eq!(data.trigrams.len(), 8);
+
+        // Every hexagram must survive its deferred parse
+        let numbers: Vec<u8> = data.hexagrams().map(|h| h.number).collect();
+        assert_eq!(numbers, (1..=64).collect::<Vec<u8>>());
+    }
+
+    #[test]
+    fn test_trigram_data_matches_enum() {
+        let data = IChingData::load_embedded().unwrap();
+        for trigram in Trigram::ALL {
+            // Data line strings run top to bottom, so the bottom line is the low bit
+            let lines = &data.get_trigram(trigram).unwrap().lines;
+            assert_eq!(u8::from_str_radix(lines, 2).unwrap(), trigram.bits());
+        }
+
+        let hexagram = data.get_hexagram(63).unwrap();
+        assert_eq!(hexagram.upper_trigram, Trigram::Kan);
+        assert_eq!(hexagram.lower_trigram, Trigram::Li);
+    }
+
+    #[test]
+    fn test_texts_hold_no_commentary() {
+        // The received texts are short verse lines; Wilhelm's explanatory
+        // prose belongs in the commentary fields
+        let data = IChingData::load_embedded().unwrap();
+        for hexagram in data.hexagrams() {
+            let texts = std::iter::once(&hexagram.judgment.text)
+                .chain(hexagram.lines.values().map(|line| &line.text));
+            for text in texts {
+                for line in text.lines() {
+                    assert!(line.len() <= 72, "{}: {}", hexagram.number, line);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_hexagram_bits() {
+        let data = IChingData::load_embedded().unwrap();
+        for hexagram in data.hexagrams() {
+            // The data's binary strings run top to bottom, so line 1 is the low bit
+            assert_eq!(
+                hexagram.bits(),
+                u8::from_str_radix(&hexagram.binary, 2).unwrap()
+            );
+            let found = Hexagram::from_bits(&data, hexagram.bits()).unwrap();
+            assert_eq!(found.number, hexagram.number);
+        }
+
+        let zhun = data.get_hexagram(3).unwrap();
+        assert_eq!(zhun.mirror(), 4);
+        assert_eq!(zhun.flip_line(5), 24);
+        assert_eq!(zhun.swap_trigrams(), 40);
+    }
+
+    #[test]
+    fn test_lookup_is_cached() {
+        let data = IChingData::load_embedded().unwrap();
+        let first = data.get_hexagram(29).unwrap() as *const Hexagram;
+        let second = data.get_hexagram(29).unwrap() as *const Hexagram;
+        assert_eq!(first, second);
+        assert!(data.get_hexagram(65).is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_load_async() {
+        // Embedding in a multi-threaded runtime needs a Send future
+        fn assert_send<T: Send>(value: T) -> T {
+            value
+        }
+
+        let data = assert_send(IChingData::load_async()).await.unwrap();
+        assert_eq!(data.get_hexagram(1).unwrap().number, 1);
+        assert_eq!(data.trigrams.len(), 8);
+    }
+}
@@ -1,31 +1,175 @@
 use crate::core::reading::{Age, Line, Polarity, Reading};
+use chrono::NaiveDate;
 use rand::Rng;
+use rand::TryRngCore;
+use rand::rngs::OsRng;
+use rayon::prelude::*;
 
-pub struct Diviner {
-    rng: rand::rngs::ThreadRng,
+/// One side of a tossed coin
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Coin {
+    Heads,
+    Tails,
 }
 
+impl Coin {
+    /// Value counted toward the line: heads 3, tails 2
+    pub fn value(&self) -> u8 {
+        match self {
+            Coin::Heads => 3,
+            Coin::Tails => 2,
+        }
+    }
+
+    /// The face written as H or T (either case)
+    fn from_face(face: char) -> Option<Self> {
+        match face.to_ascii_uppercase() {
+            'H' => Some(Coin::Heads),
+            'T' => Some(Coin::Tails),
+            _ => None,
+        }
+    }
+
+    /// A six-sided die standing in for a coin: odd counts as heads, even as tails
+    fn from_die(face: char) -> Option<Self> {
+        match face {
+            '1' | '3' | '5' => Some(Coin::Heads),
+            '2' | '4' | '6' => Some(Coin::Tails),
+            _ => None,
+        }
+    }
+}
+
+/// Casts readings with the three coins method
+///
+/// Holds no RNG of its own (each toss draws from the thread-local RNG, or one
+/// passed to the `_with` methods), so a `Diviner` is `Send + Sync` and can be
+/// shared across threads and async tasks.
+pub struct Diviner;
+
 impl Diviner {
     pub fn new() -> Self {
-        Self {
-            rng: rand::rng(),
-        }
+        Self
     }
 
     /// Cast a complete reading using the three coins method
     pub fn cast_reading(&mut self, question: Option<String>) -> Reading {
-        let lines = [
-            self.cast_line(),
-            self.cast_line(),
-            self.cast_line(),
-            self.cast_line(),
-            self.cast_line(),
-            self.cast_line(),
-        ];
+        Self::cast_reading_with(&mut rand::rng(), question)
+    }
 
+    /// Cast a reading with the given RNG, e.g. a seeded one for repeatable casts
+    pub fn cast_reading_with(rng: &mut impl Rng, question: Option<String>) -> Reading {
+        let lines = std::array::from_fn(|_| Self::line_from_coins(Self::toss_coins_with(rng)));
         Reading::new(lines, question)
     }
 
+    /// Cast a reading with bytes drawn straight from the operating system's
+    /// random source (getrandom) rather than the thread-local generator
+    pub fn cast_reading_from_os(question: Option<String>) -> Result<Reading, anyhow::Error> {
+        let mut bytes = [0u8; 18];
+        OsRng
+            .try_fill_bytes(&mut bytes)
+            .map_err(|e| anyhow::anyhow!("Failed to read OS randomness: {}", e))?;
+
+        let lines = std::array::from_fn(|i| {
+            Self::line_from_coins(std::array::from_fn(|j| {
+                if bytes[3 * i + j] & 1 == 1 {
+                    Coin::Heads
+                } else {
+                    Coin::Tails
+                }
+            }))
+        });
+        Ok(Reading::new(lines, question))
+    }
+
+    /// A reading fixed by the question and date instead of cast: the same
+    /// question on the same day always gives the same lines
+    ///
+    /// Surrounding and repeated whitespace in the question is ignored. Each
+    /// line's three coins are three bits of a 64-bit FNV-1a hash of the date
+    /// and question (mixed with the SplitMix64 finalizer), which doesn't
+    /// depend on the `rand` version, so a reading stays reproducible.
+    pub fn cast_reading_from_question(question: &str, date: NaiveDate) -> Reading {
+        let question = question.split_whitespace().collect::<Vec<_>>().join(" ");
+        let hash = question_hash(&format!("{}\n{}", date.format("%Y-%m-%d"), question));
+
+        let lines = std::array::from_fn(|i| {
+            Self::line_from_coins(std::array::from_fn(|j| {
+                if hash >> (3 * i + j) & 1 == 1 {
+                    Coin::Heads
+                } else {
+                    Coin::Tails
+                }
+            }))
+        });
+        Reading::new(lines, Some(question))
+    }
+
+    /// Cast a reading from coins tossed by hand, written bottom line first as
+    /// six comma-separated groups of three faces, e.g. `HHT,TTH,HTH,TTT,HHH,THT`
+    pub fn cast_reading_from_coins(
+        tosses: &str,
+        question: Option<String>,
+    ) -> Result<Reading, anyhow::Error> {
+        Self::cast_reading_from_faces(tosses, question, "coin faces (H or T)", Coin::from_face)
+    }
+
+    /// Cast a reading from six-sided dice rolled by hand, three per line and
+    /// bottom line first, e.g. `153,246,...`
+    ///
+    /// Each die stands in for a coin, odd for heads and even for tails, so the
+    /// lines come out with the same odds as the three coins method.
+    pub fn cast_reading_from_dice(
+        rolls: &str,
+        question: Option<String>,
+    ) -> Result<Reading, anyhow::Error> {
+        Self::cast_reading_from_faces(rolls, question, "dice (1-6)", Coin::from_die)
+    }
+
+    fn cast_reading_from_faces(
+        input: &str,
+        question: Option<String>,
+        expected: &str,
+        to_coin: fn(char) -> Option<Coin>,
+    ) -> Result<Reading, anyhow::Error> {
+        let groups: Vec<&str> = input.split(',').map(str::trim).collect();
+        if groups.len() != 6 {
+            return Err(anyhow::anyhow!(
+                "Expected 6 lines of three {}, bottom line first, but got {}",
+                expected,
+                groups.len()
+            ));
+        }
+
+        let mut lines = [Line::new(Age::Young, Polarity::Yang); 6];
+        for (i, group) in groups.iter().enumerate() {
+            let coins: Option<Vec<Coin>> = group.chars().map(to_coin).collect();
+            let coins: [Coin; 3] =
+                coins
+                    .and_then(|coins| coins.try_into().ok())
+                    .ok_or_else(|| {
+                        anyhow::anyhow!(
+                            "Line {}: expected three {}, got \"{}\"",
+                            i + 1,
+                            expected,
+                            group
+                        )
+                    })?;
+            lines[i] = Self::line_from_coins(coins);
+        }
+
+        Ok(Reading::new(lines, question))
+    }
+
+    /// Cast `count` readings in parallel, without questions
+    pub fn cast_many(&self, count: usize) -> Vec<Reading> {
+        (0..count)
+            .into_par_iter()
+            .map_init(rand::rng, |rng, _| Self::cast_reading_with(rng, None))
+            .collect()
+    }
+
     /// Convert a traditional line number (6-9) to a Line
     ///
     /// Traditional interpretation:
@@ -43,16 +187,28 @@ impl Diviner {
         }
     }
 
-    /// Cast a single line using three coins
+    /// Toss the three coins for one line
     ///
     /// Each coin contributes 2 (tails) or 3 (heads), giving totals of 6-9.
     /// See `number_to_line` for probability details.
-    fn cast_line(&mut self) -> Line {
-        let coin_sum: u8 = (0..3)
-            .map(|_| if self.rng.random_bool(0.5) { 3 } else { 2 })
-            .sum();
+    pub fn toss_coins(&mut self) -> [Coin; 3] {
+        Self::toss_coins_with(&mut rand::rng())
+    }
 
-        Self::number_to_line(coin_sum)
+    /// Toss the three coins for one line with the given RNG
+    pub fn toss_coins_with(rng: &mut impl Rng) -> [Coin; 3] {
+        std::array::from_fn(|_| {
+            if rng.random_bool(0.5) {
+                Coin::Heads
+            } else {
+                Coin::Tails
+            }
+        })
+    }
+
+    /// The line given by a toss of three coins
+    pub fn line_from_coins(coins: [Coin; 3]) -> Line {
+        Self::number_to_line(coins.iter().map(Coin::value).sum())
     }
 
     /// Cast a reading from specific line numbers (6, 7, 8, 9)
@@ -88,6 +244,21 @@ impl Default for Diviner {
     }
 }
 
+/// FNV-1a over the text's bytes, finished with SplitMix64 so every bit
+/// depends on the whole input
+fn question_hash(text: &str) -> u64 {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in text.bytes() {
+        hash ^= u64::from(byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash ^= hash >> 30;
+    hash = hash.wrapping_mul(0xbf58_476d_1ce4_e5b9);
+    hash ^= hash >> 27;
+    hash = hash.wrapping_mul(0x94d0_49bb_1331_11eb);
+    hash ^ (hash >> 31)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -106,6 +277,34 @@ mod tests {
         assert_eq!(reading.changing_line_positions(), vec![1, 4, 5]); // Lines 1, 4, 5 are changing (6, 9, 6)
     }
 
+    #[test]
+    fn test_diviner_is_send_sync() {
+        fn assert_send_sync<T: Send + Sync>() {}
+        assert_send_sync::<Diviner>();
+    }
+
+    #[test]
+    fn test_line_from_coins() {
+        use Coin::{Heads, Tails};
+
+        assert_eq!(
+            Diviner::line_from_coins([Tails, Tails, Tails]),
+            Line::new(Age::Old, Polarity::Yin)
+        );
+        assert_eq!(
+            Diviner::line_from_coins([Heads, Tails, Tails]),
+            Line::new(Age::Young, Polarity::Yang)
+        );
+        assert_eq!(
+            Diviner::line_from_coins([Heads, Heads, Tails]),
+            Line::new(Age::Young, Polarity::Yin)
+        );
+        assert_eq!(
+            Diviner::line_from_coins([Heads, Heads, Heads]),
+            Line::new(Age::Old, Polarity::Yang)
+        );
+    }
+
     #[test]
     fn test_cast_from_numbers() {
         let diviner = Diviner::new();
@@ -127,10 +326,73 @@ mod tests {
 
         let result = diviner.cast_reading_from_numbers(invalid_numbers, None);
         assert!(result.is_err());
-        assert!(result
-            .unwrap_err()
-            .to_string()
-            .contains("Invalid line number: 5"));
+        assert!(
+            result
+                .unwrap_err()
+                .to_string()
+                .contains("Invalid line number: 5")
+        );
+    }
+
+    #[test]
+    fn test_cast_from_coins_and_dice() {
+        let reading = Diviner::cast_reading_from_coins("TTT, HTT, hht,HHH,THT,tth", None).unwrap();
+        assert_eq!(reading.traditional_numbers(), [6, 7, 8, 9, 7, 7]);
+
+        let reading = Diviner::cast_reading_from_dice("246,135,351,412,662,531", None).unwrap();
+        assert_eq!(reading.traditional_numbers(), [6, 9, 9, 7, 6, 9]);
+
+        let err = Diviner::cast_reading_from_coins("HHT,TTH", None).unwrap_err();
+        assert!(err.to_string().contains("but got 2"));
+        let err = Diviner::cast_reading_from_coins("HHT,TTH,HXT,TTT,HHH,THT", None).unwrap_err();
+        assert!(err.to_string().starts_with("Line 3:"));
+        assert!(Diviner::cast_reading_from_coins("HHT,TTH,HHTT,TTT,HHH,THT", None).is_err());
+        assert!(Diviner::cast_reading_from_dice("123,456,789,123,456,123", None).is_err());
+    }
+
+    #[test]
+    fn test_cast_from_os() {
+        let reading = Diviner::cast_reading_from_os(None).unwrap();
+        assert!((1..=64).contains(&reading.primary_hexagram()));
+    }
+
+    #[test]
+    fn test_cast_from_question() {
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let reading = Diviner::cast_reading_from_question("  Should I   move? ", date);
+        assert_eq!(reading.question(), Some("Should I move?"));
+        // Pinned, so a change to the hash that would alter published readings fails here
+        assert_eq!(reading.traditional_numbers(), [7, 7, 8, 8, 8, 7]);
+        assert_eq!(
+            Diviner::cast_reading_from_question("Should I move?", date).traditional_numbers(),
+            reading.traditional_numbers()
+        );
+
+        let next_day = date.succ_opt().unwrap();
+        let others = [
+            Diviner::cast_reading_from_question("Should I move?", next_day),
+            Diviner::cast_reading_from_question("Should I stay?", date),
+        ];
+        for other in others {
+            assert_ne!(other.traditional_numbers(), reading.traditional_numbers());
+        }
+    }
+
+    #[test]
+    fn test_cast_with_seeded_rng() {
+        use rand::SeedableRng;
+        use rand::rngs::StdRng;
+
+        let first = Diviner::cast_reading_with(&mut StdRng::seed_from_u64(9), None);
+        let second = Diviner::cast_reading_with(&mut StdRng::seed_from_u64(9), None);
+        assert_eq!(first.traditional_numbers(), second.traditional_numbers());
+    }
+
+    #[test]
+    fn test_cast_many() {
+        let readings = Diviner::new().cast_many(500);
+        assert_eq!(readings.len(), 500);
+        assert!(readings.iter().all(|reading| reading.question().is_none()));
     }
 
     #[test]
@@ -148,7 +410,7 @@ mod tests {
 
             // Verify hexagram number is in valid range
             let hexagram = reading.primary_hexagram();
-            assert!(hexagram >= 1 && hexagram <= 64);
+            assert!((1..=64).contains(&hexagram));
         }
     }
 }
@@ -0,0 +1,217 @@
+use serde::{Deserialize, Serialize};
+
+/// King Wen hexagram numbers indexed by line pattern.
+///
+/// The index is the hexagram's six lines read as bits, with line 1 (bottom)
+/// as the least significant bit and yang as 1.
+const KING_WEN_BY_BINARY: [u8; 64] = [
+    2, 24, 7, 19, 15, 36, 46, 11, 16, 51, 40, 54, 62, 55, 32, 34, 8, 3, 29, 60, 39, 63, 48, 5, 45,
+    17, 47, 58, 31, 49, 28, 43, 23, 27, 4, 41, 52, 22, 18, 26, 35, 21, 64, 38, 56, 30, 50, 14, 20,
+    42, 59, 61, 53, 37, 57, 9, 12, 25, 6, 10, 33, 13, 44, 1,
+];
+
+/// Convert a line pattern (bit 0 = line 1, yang = 1) to its King Wen number
+pub fn from_binary(bits: u8) -> u8 {
+    KING_WEN_BY_BINARY[(bits & 0b11_1111) as usize]
+}
+
+/// Convert a King Wen number (1-64) to its line pattern (bit 0 = line 1, yang = 1)
+pub fn to_binary(number: u8) -> Option<u8> {
+    KING_WEN_BY_BINARY
+        .iter()
+        .position(|&n| n == number)
+        .map(|bits| bits as u8)
+}
+
+/// A King Wen number's position (0-63) in the Fu Xi sequence of Shao Yong,
+/// which counts in binary from Kun (0) to Qian (63) with line 1 as the
+/// highest bit
+pub fn to_fuxi(number: u8) -> Option<u8> {
+    to_binary(number).map(mirror)
+}
+
+/// The King Wen number at a Fu Xi sequence position (0-63)
+pub fn from_fuxi(index: u8) -> Option<u8> {
+    (index < 64).then(|| from_binary(mirror(index)))
+}
+
+/// Turn a line pattern upside down (line 1 becomes line 6)
+pub fn mirror(bits: u8) -> u8 {
+    (0..6).fold(0, |acc, i| acc | (((bits >> i) & 1) << (5 - i)))
+}
+
+/// Change one line (1-6, bottom first) of a line pattern
+pub fn flip_line(bits: u8, position: usize) -> u8 {
+    match position {
+        1..=6 => bits ^ (1 << (position - 1)),
+        _ => bits,
+    }
+}
+
+/// Exchange the lower and upper trigrams of a line pattern
+pub fn swap_trigrams(bits: u8) -> u8 {
+    ((bits & 0b111) << 3) | ((bits >> 3) & 0b111)
+}
+
+/// The hexagram turned upside down (1-64)
+pub fn inverse(number: u8) -> Option<u8> {
+    to_binary(number).map(|bits| from_binary(mirror(bits)))
+}
+
+/// The hexagram with every line changed (1-64)
+pub fn complement(number: u8) -> Option<u8> {
+    to_binary(number).map(|bits| from_binary(!bits))
+}
+
+/// The nuclear hexagram (1-64), from lines 2-3-4 below and 3-4-5 above
+pub fn nuclear(number: u8) -> Option<u8> {
+    to_binary(number).map(|bits| from_binary(((bits >> 1) & 0b111) | ((bits >> 2) & 0b111) << 3))
+}
+
+/// How the two hexagrams of a King Wen pair relate to each other
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PairRelation {
+    /// The partner is the same figure turned upside down (e.g. 3/4)
+    Inverse,
+    /// The figure is symmetric, so the partner swaps every line instead (e.g. 1/2)
+    Complement,
+}
+
+impl PairRelation {
+    pub fn label(&self) -> &'static str {
+        match self {
+            PairRelation::Inverse => "inverse",
+            PairRelation::Complement => "complementary",
+        }
+    }
+}
+
+/// One of the 32 pairs that make up the King Wen sequence
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HexagramPair {
+    /// Odd-numbered hexagram that opens the pair
+    pub first: u8,
+    /// Even-numbered hexagram that closes the pair
+    pub second: u8,
+    pub relation: PairRelation,
+}
+
+impl HexagramPair {
+    /// The other member of the pair for a given hexagram number
+    pub fn partner_of(&self, number: u8) -> u8 {
+        if number == self.first {
+            self.second
+        } else {
+            self.first
+        }
+    }
+}
+
+/// Find the King Wen pair containing the given hexagram (1-64)
+pub fn pair(number: u8) -> Option<HexagramPair> {
+    if !(1..=64).contains(&number) {
+        return None;
+    }
+
+    let first = if number % 2 == 1 { number } else { number - 1 };
+    let second = first + 1;
+
+    let first_bits = to_binary(first)?;
+    let second_bits = to_binary(second)?;
+
+    let relation = if mirror(first_bits) == second_bits {
+        PairRelation::Inverse
+    } else {
+        PairRelation::Complement
+    };
+
+    Some(HexagramPair {
+        first,
+        second,
+        relation,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_binary_round_trip() {
+        for number in 1..=64 {
+            let bits = to_binary(number).unwrap();
+            assert_eq!(from_binary(bits), number);
+        }
+        assert_eq!(to_binary(0), None);
+        assert_eq!(to_binary(65), None);
+    }
+
+    #[test]
+    fn test_known_patterns() {
+        assert_eq!(from_binary(0b111111), 1); // Qian
+        assert_eq!(from_binary(0b000000), 2); // Kun
+        assert_eq!(from_binary(0b010001), 3); // Zhun: yang in lines 1 and 5
+        assert_eq!(from_binary(0b010101), 63); // Water over fire
+    }
+
+    #[test]
+    fn test_fuxi_sequence() {
+        // Kun, Bo, Bi, Guan, Yu open the sequence; Qian closes it
+        let opening: Vec<u8> = (0..5).map(|index| from_fuxi(index).unwrap()).collect();
+        assert_eq!(opening, [2, 23, 8, 20, 16]);
+        assert_eq!(from_fuxi(63), Some(1));
+        assert_eq!(from_fuxi(64), None);
+        for number in 1..=64 {
+            assert_eq!(from_fuxi(to_fuxi(number).unwrap()), Some(number));
+        }
+    }
+
+    #[test]
+    fn test_inverse_and_complement() {
+        assert_eq!(inverse(3), Some(4));
+        assert_eq!(inverse(1), Some(1));
+        assert_eq!(complement(1), Some(2));
+        assert_eq!(complement(63), Some(64));
+        assert_eq!(inverse(0), None);
+    }
+
+    #[test]
+    fn test_nuclear() {
+        assert_eq!(nuclear(1), Some(1));
+        assert_eq!(nuclear(63), Some(64));
+        for number in 1..=64 {
+            let reading = crate::core::Reading::from_hexagram_number(number).unwrap();
+            assert_eq!(nuclear(number), Some(reading.nuclear_hexagram()));
+        }
+    }
+
+    #[test]
+    fn test_line_operations() {
+        let zhun = to_binary(3).unwrap();
+        assert_eq!(from_binary(mirror(zhun)), 4);
+        assert_eq!(from_binary(flip_line(zhun, 1)), 8);
+        assert_eq!(flip_line(zhun, 7), zhun);
+        // Water over thunder becomes thunder over water
+        assert_eq!(from_binary(swap_trigrams(zhun)), 40);
+    }
+
+    #[test]
+    fn test_pairs() {
+        let qian = pair(1).unwrap();
+        assert_eq!((qian.first, qian.second), (1, 2));
+        assert_eq!(qian.relation, PairRelation::Complement);
+
+        let zhun = pair(4).unwrap();
+        assert_eq!((zhun.first, zhun.second), (3, 4));
+        assert_eq!(zhun.relation, PairRelation::Inverse);
+        assert_eq!(zhun.partner_of(4), 3);
+
+        // The four symmetric figures pair by complement
+        for n in [27, 29, 61] {
+            assert_eq!(pair(n).unwrap().relation, PairRelation::Complement);
+        }
+
+        assert!(pair(0).is_none());
+        assert!(pair(65).is_none());
+    }
+}
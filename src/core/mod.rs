@@ -1,6 +1,15 @@
 pub mod data;
 pub mod divination;
+pub mod king_wen;
+pub mod parse;
+pub mod partial;
+pub mod permalink;
+pub mod query;
 pub mod reading;
+pub mod schema;
+pub mod trigram;
+pub mod verify;
 
 pub use divination::Diviner;
 pub use reading::Reading;
+pub use trigram::Trigram;
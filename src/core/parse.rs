@@ -0,0 +1,319 @@
+//! Reading input, as `--input` and `Reading::from_str` accept it
+//!
+//! Surrounding whitespace is ignored, and the forms are tried in this order:
+//!
+//! ```text
+//! input     = permalink | change | binary | hexagram | trigrams | lines
+//! permalink = "iching:v1:" token                    iching:v1:789677
+//! change    = hexagram arrow hexagram               32→34, ䷟ => ䷡, 32 to 34
+//! arrow     = "→" | "⇒" | "->" | "=>" | ":" | "to"
+//! binary    = ["0b"] six of "0" "1", line 6 first    0b010001, 010001
+//! hexagram  = 1-64 | ䷀-䷿                           32, ䷟
+//! trigrams  = trigram ("/" | "over") trigram        kan/li, water over fire
+//! lines     = six of 6 7 8 9, line 1 first,         7,8,9,6,7,8 or 7 8 9 6 7 8
+//!             separated by commas and/or spaces
+//! ```
+//!
+//! `to` must stand between spaces, and `0b` patterns may group digits with
+//! `_`. Binary comes before hexagram numbers so `000001` is a pattern, not 1;
+//! [`ambiguity`] reports inputs like that one, which `--strict-input` refuses.
+
+use crate::core::data;
+use crate::core::king_wen;
+use crate::core::permalink::{self, Permalink};
+use crate::core::reading::{Line, Reading};
+use crate::core::trigram::Trigram;
+use std::fmt;
+
+/// Separators between the two hexagrams of a change, besides the word `to`
+const ARROWS: [&str; 5] = ["→", "⇒", "->", "=>", ":"];
+
+/// Which form of the grammar an input was read as, and what it said
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParsedInput {
+    Permalink(Permalink),
+    Change {
+        from: u8,
+        to: u8,
+    },
+    /// A line pattern, line 6 in the high bit
+    Binary(u8),
+    Hexagram(u8),
+    Trigrams {
+        upper: Trigram,
+        lower: Trigram,
+    },
+    Lines([Line; 6]),
+}
+
+impl ParsedInput {
+    pub fn to_reading(&self) -> Result<Reading, anyhow::Error> {
+        match self {
+            ParsedInput::Permalink(permalink) => permalink.to_reading(None),
+            ParsedInput::Change { from, to } => Reading::from_change(*from, *to),
+            ParsedInput::Binary(bits) => {
+                Reading::from_hexagram_number(king_wen::from_binary(*bits))
+            }
+            ParsedInput::Hexagram(number) => Reading::from_hexagram_number(*number),
+            ParsedInput::Trigrams { upper, lower } => Ok(Reading::from_trigrams(*upper, *lower)),
+            ParsedInput::Lines(lines) => Ok(Reading::new(*lines, None)),
+        }
+    }
+}
+
+impl fmt::Display for ParsedInput {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ParsedInput::Permalink(permalink) => write!(f, "permalink {}", permalink),
+            ParsedInput::Change { from, to } => {
+                write!(f, "hexagram {} changing to {}", from, to)
+            }
+            ParsedInput::Binary(bits) => write!(
+                f,
+                "line pattern {:06b} (hexagram {})",
+                bits,
+                king_wen::from_binary(*bits)
+            ),
+            ParsedInput::Hexagram(number) => write!(f, "hexagram {}", number),
+            ParsedInput::Trigrams { upper, lower } => {
+                write!(f, "{} over {}", upper.image(), lower.image())
+            }
+            ParsedInput::Lines(lines) => {
+                let numbers: Vec<String> = lines
+                    .iter()
+                    .map(|line| line.traditional_number().to_string())
+                    .collect();
+                write!(f, "lines {}", numbers.join(","))
+            }
+        }
+    }
+}
+
+/// An input that fits the grammar one way but looks like it meant another
+#[derive(Debug, Clone, PartialEq)]
+pub struct Ambiguity {
+    /// How the input was read
+    pub chosen: ParsedInput,
+    /// What else it could have meant
+    pub alternative: String,
+    /// The chosen reading written so it can only mean that
+    pub unambiguous: String,
+}
+
+impl fmt::Display for Ambiguity {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "read as {}, but it could be {}; write {} to mean the former",
+            self.chosen, self.alternative, self.unambiguous
+        )
+    }
+}
+
+/// Parse a reading from any form in the grammar above
+pub fn reading(input: &str) -> Result<Reading, anyhow::Error> {
+    parse(input)?.to_reading()
+}
+
+/// The form an input takes in the grammar above, trying the forms in order
+pub fn parse(input: &str) -> Result<ParsedInput, anyhow::Error> {
+    let input = input.trim();
+
+    if input.starts_with(permalink::PREFIX) {
+        return Ok(ParsedInput::Permalink(input.parse()?));
+    }
+
+    if let Some((from, to)) = change(input) {
+        return Ok(ParsedInput::Change { from, to });
+    }
+
+    if let Some(bits) = bits(input) {
+        return Ok(ParsedInput::Binary(bits));
+    }
+
+    if let Some(number) = hexagram(input) {
+        return Ok(ParsedInput::Hexagram(number));
+    }
+
+    if let Some((upper, lower)) = trigrams(input) {
+        return Ok(ParsedInput::Trigrams { upper, lower });
+    }
+
+    if let Some(lines) = lines(input) {
+        return Ok(ParsedInput::Lines(lines));
+    }
+
+    Err(anyhow::anyhow!(
+        "Invalid input: '{}'. Expected hexagram number (1-64), Unicode character (䷀-䷿), changing format (32→34, 32 => 34, 32 to 34, ䷟→䷡), trigrams (kan/li or water over fire), binary (0b010001 or 010001), or six line numbers (7,8,9,6,7,8 or 7 8 9 6 7 8)",
+        input
+    ))
+}
+
+/// Whether the input could have meant something other than what [`parse`]
+/// makes of it: six bare binary digits that are also a hexagram number, or
+/// a lone 6-9 that may be the first of six line numbers
+pub fn ambiguity(input: &str) -> Option<Ambiguity> {
+    let input = input.trim();
+    let chosen = parse(input).ok()?;
+    match chosen {
+        ParsedInput::Binary(bits) => {
+            let number = hexagram(input)?;
+            Some(Ambiguity {
+                chosen,
+                alternative: format!("hexagram {}", number),
+                unambiguous: format!("0b{:06b}", bits),
+            })
+        }
+        ParsedInput::Hexagram(number @ 6..=9) if input.parse::<u8>().is_ok() => Some(Ambiguity {
+            chosen,
+            alternative: "a line number (6-9)".to_string(),
+            unambiguous: data::hexagram_number_to_unicode(number)?.to_string(),
+        }),
+        _ => None,
+    }
+}
+
+/// A hexagram given by number (1-64) or Unicode character
+pub fn hexagram(input: &str) -> Option<u8> {
+    if let Ok(number) = input.parse::<u8>() {
+        return (1..=64).contains(&number).then_some(number);
+    }
+
+    let mut chars = input.chars();
+    match (chars.next(), chars.next()) {
+        (Some(unicode_char), None) => data::unicode_to_hexagram_number(unicode_char),
+        _ => None,
+    }
+}
+
+/// A line pattern written as six binary digits, line 6 first and line 1
+/// last, as in a Rust literal: `0b010001` (or `010001`) is yang in lines 1
+/// and 5
+pub fn bits(input: &str) -> Option<u8> {
+    let digits = match input
+        .strip_prefix("0b")
+        .or_else(|| input.strip_prefix("0B"))
+    {
+        Some(digits) => digits.replace('_', ""),
+        None => input.to_string(),
+    };
+    if digits.len() != 6 || !digits.bytes().all(|b| b == b'0' || b == b'1') {
+        return None;
+    }
+    u8::from_str_radix(&digits, 2).ok()
+}
+
+/// The two sides of a change, split at the first arrow or a lone `to`
+fn split_change(input: &str) -> Option<(&str, &str)> {
+    if let Some(sides) = ARROWS.iter().find_map(|arrow| input.split_once(arrow)) {
+        return Some(sides);
+    }
+
+    let words: Vec<&str> = input.split_whitespace().collect();
+    match words[..] {
+        [from, to, into] if to.eq_ignore_ascii_case("to") => Some((from, into)),
+        _ => None,
+    }
+}
+
+/// A change between two hexagrams, like 32→34 or ䷟ to ䷡
+fn change(input: &str) -> Option<(u8, u8)> {
+    let (from, to) = split_change(input)?;
+    Some((hexagram(from.trim())?, hexagram(to.trim())?))
+}
+
+/// Trigrams given upper over lower, like kan/li or "water over fire"
+fn trigrams(input: &str) -> Option<(Trigram, Trigram)> {
+    let lowercase = input.to_lowercase();
+    let (upper, lower) = lowercase
+        .split_once('/')
+        .or_else(|| lowercase.split_once(" over "))?;
+
+    let upper = upper.parse::<Trigram>().ok()?;
+    let lower = lower.parse::<Trigram>().ok()?;
+    Some((upper, lower))
+}
+
+/// Six traditional line numbers, bottom first, split by commas or whitespace
+fn lines(input: &str) -> Option<[Line; 6]> {
+    let lines: Vec<Line> = input
+        .split(|c: char| c == ',' || c.is_whitespace())
+        .filter(|number| !number.is_empty())
+        .map(|number| {
+            number
+                .parse::<u8>()
+                .ok()
+                .and_then(|number| Line::from_traditional_number(number).ok())
+        })
+        .collect::<Option<_>>()?;
+    lines.try_into().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_change_arrows() {
+        for input in [
+            "32→34",
+            "32->34",
+            "32⇒34",
+            "32=>34",
+            "32:34",
+            "32 to 34",
+            "32 TO 34",
+            "䷟ => ䷡",
+            " 32 : ䷡ ",
+        ] {
+            let reading = reading(input).unwrap();
+            assert_eq!(reading.primary_hexagram(), 32, "input: {}", input);
+            assert_eq!(reading.transformed_hexagram_number(), Some(34));
+        }
+        assert!(reading("32 into 34").is_err());
+        assert!(reading("32to34").is_err());
+    }
+
+    #[test]
+    fn test_line_separators() {
+        for input in ["7 8 9 6 7 8", "7, 8, 9, 6, 7, 8", "7\t8  9,6 7 8"] {
+            let reading = reading(input).unwrap();
+            assert_eq!(reading.traditional_numbers(), [7, 8, 9, 6, 7, 8]);
+        }
+        assert!(reading("7 8 9 6 7").is_err());
+        assert!(reading("7 8 9 6 7 8 9").is_err());
+    }
+
+    #[test]
+    fn test_bare_binary() {
+        assert_eq!(reading("010001").unwrap().primary_hexagram(), 3);
+        // Six binary digits are a line pattern even when they read as 1-64
+        assert_eq!(reading("000001").unwrap().primary_hexagram(), 24);
+        assert_eq!(bits("0b01_0001"), Some(0b010001));
+        assert_eq!(bits("01_0001"), None);
+        assert_eq!(bits("0100011"), None);
+    }
+
+    #[test]
+    fn test_ambiguity() {
+        assert_eq!(parse("kan/li").unwrap().to_string(), "Water over Fire");
+        assert_eq!(
+            parse("32 to 34").unwrap(),
+            ParsedInput::Change { from: 32, to: 34 }
+        );
+
+        let binary = ambiguity("000001").unwrap();
+        assert_eq!(binary.chosen, ParsedInput::Binary(1));
+        assert_eq!(binary.alternative, "hexagram 1");
+        assert_eq!(binary.unambiguous, "0b000001");
+        assert_eq!(binary.chosen.to_reading().unwrap().primary_hexagram(), 24);
+
+        let lone = ambiguity("7").unwrap();
+        assert_eq!(lone.chosen, ParsedInput::Hexagram(7));
+        assert_eq!(lone.unambiguous, "䷆");
+
+        for input in ["0b000001", "010001", "5", "10", "䷆", "7,8,9,6,7,8"] {
+            assert_eq!(ambiguity(input), None, "input: {}", input);
+        }
+    }
+}
@@ -0,0 +1,106 @@
+use crate::core::divination::Diviner;
+use crate::core::reading::{Line, Reading};
+use rand::Rng;
+use serde::{Deserialize, Serialize};
+
+/// A reading cast part of the way, to be finished later
+///
+/// Lines are cast bottom first, as in a full cast, so the lines already here
+/// are the lower ones of the eventual hexagram.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct PartialReading {
+    lines: Vec<Line>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    question: Option<String>,
+}
+
+impl PartialReading {
+    pub fn new(question: Option<String>) -> Self {
+        Self {
+            lines: Vec::with_capacity(6),
+            question,
+        }
+    }
+
+    /// Lines cast so far, bottom first
+    pub fn lines(&self) -> &[Line] {
+        &self.lines
+    }
+
+    pub fn question(&self) -> Option<&str> {
+        self.question.as_deref()
+    }
+
+    /// Lines still to cast
+    pub fn remaining(&self) -> usize {
+        6 - self.lines.len()
+    }
+
+    pub fn is_complete(&self) -> bool {
+        self.lines.len() == 6
+    }
+
+    /// Add a line above those already cast
+    pub fn push(&mut self, line: Line) -> Result<(), anyhow::Error> {
+        if self.is_complete() {
+            return Err(anyhow::anyhow!("All six lines are already cast"));
+        }
+        self.lines.push(line);
+        Ok(())
+    }
+
+    /// Toss the coins for up to `count` more lines, returning how many were cast
+    pub fn cast_with(&mut self, rng: &mut impl Rng, count: usize) -> usize {
+        let count = count.min(self.remaining());
+        for _ in 0..count {
+            self.lines
+                .push(Diviner::line_from_coins(Diviner::toss_coins_with(rng)));
+        }
+        count
+    }
+
+    /// Cast up to `count` more lines with the thread-local RNG
+    pub fn cast(&mut self, count: usize) -> usize {
+        self.cast_with(&mut rand::rng(), count)
+    }
+
+    /// The finished reading, once all six lines are cast
+    pub fn to_reading(&self) -> Option<Reading> {
+        let lines: [Line; 6] = self.lines.clone().try_into().ok()?;
+        Some(Reading::new(lines, self.question.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::SeedableRng;
+    use rand::rngs::StdRng;
+
+    #[test]
+    fn test_cast_in_parts() {
+        let mut rng = StdRng::seed_from_u64(7);
+        let mut partial = PartialReading::new(Some("Move?".to_string()));
+        assert_eq!(partial.cast_with(&mut rng, 3), 3);
+        assert_eq!(partial.remaining(), 3);
+        assert!(partial.to_reading().is_none());
+
+        let json = serde_json::to_string(&partial).unwrap();
+        let mut resumed: PartialReading = serde_json::from_str(&json).unwrap();
+        assert_eq!(resumed, partial);
+
+        // Asking for more lines than remain casts only the rest
+        assert_eq!(resumed.cast_with(&mut rng, 6), 3);
+        assert!(resumed.is_complete());
+        assert_eq!(resumed.cast_with(&mut rng, 1), 0);
+        assert!(
+            resumed
+                .push(Line::from_traditional_number(7).unwrap())
+                .is_err()
+        );
+
+        let reading = resumed.to_reading().unwrap();
+        assert_eq!(reading.question(), Some("Move?"));
+        assert_eq!(reading.lines()[..3], partial.lines()[..]);
+    }
+}
@@ -0,0 +1,159 @@
+use crate::core::reading::{Line, Reading};
+use std::fmt;
+use std::str::FromStr;
+
+/// Prefix shared by every permalink token, including its format version
+pub const PREFIX: &str = "iching:v1:";
+
+/// Compact, URL-safe encoding of a reading for sharing
+///
+/// Tokens look like `iching:v1:978677` or `iching:v1:978677-1f2e3d4c`: the
+/// traditional line numbers bottom to top, then an optional hash of the
+/// question. The hash lets a recipient who knows the question confirm it
+/// belongs to the reading without the question appearing in the link.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Permalink {
+    pub lines: [u8; 6],
+    pub question_hash: Option<u32>,
+}
+
+impl Permalink {
+    pub fn new(reading: &Reading) -> Self {
+        Self {
+            lines: reading.traditional_numbers(),
+            question_hash: reading.question().map(question_hash),
+        }
+    }
+
+    /// Whether `question` is the one the reading was cast for (true when the
+    /// token carries no question hash)
+    pub fn matches_question(&self, question: &str) -> bool {
+        self.question_hash
+            .is_none_or(|hash| hash == question_hash(question))
+    }
+
+    /// Rebuild the reading, attaching the question if one is given
+    pub fn to_reading(&self, question: Option<String>) -> Result<Reading, anyhow::Error> {
+        let lines = self
+            .lines
+            .iter()
+            .map(|&n| Line::from_traditional_number(n))
+            .collect::<Result<Vec<Line>, _>>()?;
+        let lines: [Line; 6] = lines
+            .try_into()
+            .map_err(|_| anyhow::anyhow!("A reading needs exactly six lines"))?;
+        Ok(Reading::new(lines, question))
+    }
+}
+
+impl fmt::Display for Permalink {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(PREFIX)?;
+        for n in self.lines {
+            write!(f, "{}", n)?;
+        }
+        if let Some(hash) = self.question_hash {
+            write!(f, "-{:08x}", hash)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for Permalink {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let s = s.trim();
+        let body = s.strip_prefix(PREFIX).ok_or_else(|| {
+            anyhow::anyhow!("Not a reading permalink: '{}' (expected {}...)", s, PREFIX)
+        })?;
+        let (digits, hash) = match body.split_once('-') {
+            Some((digits, hash)) => (digits, Some(hash)),
+            None => (body, None),
+        };
+
+        let numbers: Vec<u8> = digits
+            .chars()
+            .map(|c| match c {
+                '6'..='9' => Ok(c as u8 - b'0'),
+                _ => Err(anyhow::anyhow!(
+                    "Invalid line '{}' in permalink; lines are 6, 7, 8, or 9",
+                    c
+                )),
+            })
+            .collect::<Result<_, _>>()?;
+        let lines: [u8; 6] = numbers.try_into().map_err(|numbers: Vec<u8>| {
+            anyhow::anyhow!("A permalink needs six lines, found {}", numbers.len())
+        })?;
+
+        let question_hash = hash
+            .map(|hash| {
+                u32::from_str_radix(hash, 16)
+                    .map_err(|_| anyhow::anyhow!("Invalid question hash '{}' in permalink", hash))
+            })
+            .transpose()?;
+
+        Ok(Self {
+            lines,
+            question_hash,
+        })
+    }
+}
+
+/// 32-bit FNV-1a hash of the trimmed question, stable across platforms and
+/// releases so old links keep verifying
+fn question_hash(question: &str) -> u32 {
+    question.trim().bytes().fold(0x811c9dc5, |hash, byte| {
+        (hash ^ u32::from(byte)).wrapping_mul(0x01000193)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        let reading: Reading = "9,7,6,8,7,7".parse().unwrap();
+        let reading = reading.with_question(Some("Should I go?".to_string()));
+
+        let token = Permalink::new(&reading).to_string();
+        assert!(token.starts_with("iching:v1:976877-"));
+        assert_eq!(token.len(), PREFIX.len() + 6 + 1 + 8);
+
+        let permalink: Permalink = token.parse().unwrap();
+        assert!(permalink.matches_question("Should I go?"));
+        assert!(!permalink.matches_question("Should I stay?"));
+
+        let decoded = permalink.to_reading(None).unwrap();
+        assert_eq!(decoded.lines(), reading.lines());
+    }
+
+    #[test]
+    fn test_without_question() {
+        let reading: Reading = "1".parse().unwrap();
+        let token = Permalink::new(&reading).to_string();
+        assert_eq!(token, "iching:v1:777777");
+
+        let permalink: Permalink = token.parse().unwrap();
+        assert_eq!(permalink.question_hash, None);
+        assert!(permalink.matches_question("anything"));
+    }
+
+    #[test]
+    fn test_invalid_tokens() {
+        assert!("978677".parse::<Permalink>().is_err());
+        assert!("iching:v1:97867".parse::<Permalink>().is_err());
+        assert!("iching:v1:978675".parse::<Permalink>().is_err());
+        assert!("iching:v1:978677-xyz".parse::<Permalink>().is_err());
+        assert!("iching:v2:978677".parse::<Permalink>().is_err());
+    }
+
+    #[test]
+    fn test_question_hash_is_stable() {
+        // Changing the hash would break every link already shared
+        assert_eq!(question_hash(""), 0x811c9dc5);
+        assert_eq!(question_hash("a"), 0xe40c292c);
+        assert_eq!(question_hash("  a \n"), 0xe40c292c);
+    }
+}
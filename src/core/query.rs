@@ -0,0 +1,123 @@
+use crate::core::data::{Hexagram, IChingData};
+use crate::core::trigram::{Element, Trigram};
+
+/// Properties a hexagram must have; fields left unset match anything
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Query {
+    pub upper: Option<Trigram>,
+    pub lower: Option<Trigram>,
+    /// Phase of either trigram
+    pub element: Option<Element>,
+    /// Number of yang lines (0-6)
+    pub yang: Option<u32>,
+    /// Words that must all appear in the name, description, judgment, or
+    /// image, ignoring case
+    pub keywords: Vec<String>,
+}
+
+impl Query {
+    pub fn matches(&self, hexagram: &Hexagram) -> bool {
+        let trigrams = [hexagram.upper_trigram, hexagram.lower_trigram];
+
+        self.upper
+            .is_none_or(|upper| upper == hexagram.upper_trigram)
+            && self
+                .lower
+                .is_none_or(|lower| lower == hexagram.lower_trigram)
+            && self
+                .element
+                .is_none_or(|element| trigrams.iter().any(|t| t.element() == element))
+            && self
+                .yang
+                .is_none_or(|yang| hexagram.bits().count_ones() == yang)
+            && self.matches_keywords(hexagram)
+    }
+
+    fn matches_keywords(&self, hexagram: &Hexagram) -> bool {
+        if self.keywords.is_empty() {
+            return true;
+        }
+
+        let text = [
+            hexagram.name.as_str(),
+            &hexagram.pinyin,
+            &hexagram.description,
+            &hexagram.judgment.text,
+            &hexagram.image.text,
+        ]
+        .join(" ")
+        .to_lowercase();
+        let words: Vec<&str> = text.split(|c: char| !c.is_alphanumeric()).collect();
+
+        self.keywords
+            .iter()
+            .all(|keyword| words.contains(&keyword.to_lowercase().as_str()))
+    }
+
+    /// Matching hexagrams in King Wen order
+    pub fn run<'a>(&self, data: &'a IChingData) -> Vec<&'a Hexagram> {
+        data.hexagrams()
+            .filter(|hexagram| self.matches(hexagram))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn numbers(query: Query) -> Vec<u8> {
+        let data = IChingData::load().unwrap();
+        query.run(&data).iter().map(|h| h.number).collect()
+    }
+
+    #[test]
+    fn test_trigrams() {
+        let query = Query {
+            upper: Some(Trigram::Kan),
+            lower: Some(Trigram::Li),
+            ..Query::default()
+        };
+        assert_eq!(numbers(query), [63]);
+
+        let query = Query {
+            upper: Some(Trigram::Kan),
+            ..Query::default()
+        };
+        assert_eq!(numbers(query), [3, 5, 8, 29, 39, 48, 60, 63]);
+    }
+
+    #[test]
+    fn test_element_and_yang() {
+        let query = Query {
+            element: Some(Element::Water),
+            yang: Some(2),
+            ..Query::default()
+        };
+        assert_eq!(numbers(query), [3, 4, 29, 39, 40]);
+
+        let query = Query {
+            yang: Some(6),
+            ..Query::default()
+        };
+        assert_eq!(numbers(query), [1]);
+        assert_eq!(numbers(Query::default()).len(), 64);
+    }
+
+    #[test]
+    fn test_keywords() {
+        let query = Query {
+            keywords: vec!["WATER".to_string()],
+            upper: Some(Trigram::Kan),
+            lower: Some(Trigram::Kan),
+            ..Query::default()
+        };
+        assert_eq!(numbers(query), [29]);
+
+        let query = Query {
+            keywords: vec!["zzyzx".to_string()],
+            ..Query::default()
+        };
+        assert!(numbers(query).is_empty());
+    }
+}
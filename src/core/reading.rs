@@ -1,4 +1,10 @@
+use crate::core::king_wen;
+use crate::core::parse;
+use crate::core::schema::ReadingV1;
+use crate::core::trigram::Trigram;
 use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
 
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 pub enum Age {
@@ -18,10 +24,20 @@ pub struct Line {
     pub polarity: Polarity,
 }
 
+/// A cast hexagram with its question
+///
+/// Lines are fixed at construction so the hexagram numbers derived from them
+/// can be computed once and read back cheaply. Readings serialize as the
+/// current [`schema`](crate::core::schema) version and load from any.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(try_from = "serde_json::Value", into = "ReadingV1")]
 pub struct Reading {
-    pub lines: [Line; 6], // Bottom to top (traditional order)
-    pub question: Option<String>,
+    lines: [Line; 6], // Bottom to top (traditional order)
+    question: Option<String>,
+    context: Option<String>,
+    primary: u8,
+    transformed: Option<u8>,
+    nuclear: u8,
 }
 
 impl Line {
@@ -80,40 +96,153 @@ impl Line {
 
 impl Reading {
     pub fn new(lines: [Line; 6], question: Option<String>) -> Self {
-        Self { lines, question }
+        let polarities = lines.map(|line| line.polarity);
+        let transformed = lines
+            .iter()
+            .any(|line| line.age == Age::Old)
+            .then(|| hexagram_number(lines.map(|line| line.transform().polarity)));
+        // The nuclear hexagram is built from the inner lines 2-3-4 and 3-4-5
+        let [_, l2, l3, l4, l5, _] = polarities;
+
+        Self {
+            lines,
+            question,
+            context: None,
+            primary: hexagram_number(polarities),
+            transformed,
+            nuclear: hexagram_number([l2, l3, l4, l3, l4, l5]),
+        }
+    }
+
+    /// Replace the question, keeping the cast lines
+    pub fn with_question(mut self, question: Option<String>) -> Self {
+        self.question = question;
+        self
+    }
+
+    /// Attach a longer description of the situation behind the question
+    pub fn with_context(mut self, context: Option<String>) -> Self {
+        self.context = context;
+        self
+    }
+
+    /// Lines from bottom to top
+    pub fn lines(&self) -> &[Line; 6] {
+        &self.lines
+    }
+
+    pub fn question(&self) -> Option<&str> {
+        self.question.as_deref()
+    }
+
+    pub fn context(&self) -> Option<&str> {
+        self.context.as_deref()
+    }
+
+    /// Reading of a single hexagram with no changing lines
+    pub fn from_hexagram_number(number: u8) -> Result<Self, anyhow::Error> {
+        let bits = king_wen::to_binary(number)
+            .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: {}", number))?;
+        let lines = std::array::from_fn(|i| Line::new(Age::Young, polarity_of_bit(bits >> i)));
+        Ok(Self::new(lines, None))
+    }
+
+    /// Reading that changes from one hexagram into another: the lines that
+    /// differ between them are old, the rest young
+    pub fn from_change(from: u8, to: u8) -> Result<Self, anyhow::Error> {
+        let from_bits = king_wen::to_binary(from)
+            .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: {}", from))?;
+        let to_bits = king_wen::to_binary(to)
+            .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: {}", to))?;
+
+        let lines = std::array::from_fn(|i| {
+            let age = if (from_bits ^ to_bits) >> i & 1 == 1 {
+                Age::Old
+            } else {
+                Age::Young
+            };
+            Line::new(age, polarity_of_bit(from_bits >> i))
+        });
+        Ok(Self::new(lines, None))
+    }
+
+    /// Reading of hexagram `number` with the lines set in `changing` old: bit
+    /// 0 is line 1, bit 5 line 6
+    pub fn with_changing_lines(number: u8, changing: u8) -> Result<Self, anyhow::Error> {
+        if changing >= 64 {
+            anyhow::bail!("Invalid changing-line set: {:#b}", changing);
+        }
+        let bits = king_wen::to_binary(number)
+            .ok_or_else(|| anyhow::anyhow!("Invalid hexagram number: {}", number))?;
+        let lines = std::array::from_fn(|i| {
+            let age = if changing >> i & 1 == 1 {
+                Age::Old
+            } else {
+                Age::Young
+            };
+            Line::new(age, polarity_of_bit(bits >> i))
+        });
+        Ok(Self::new(lines, None))
+    }
+
+    /// Every possible reading, 4096 in all: hexagrams 1 to 64 in King Wen
+    /// order, each with its 64 sets of changing lines from none to all six
+    pub fn all_transitions() -> impl Iterator<Item = Reading> {
+        (1..=64).flat_map(Self::transitions_from)
+    }
+
+    /// The 64 readings of hexagram `number`, one per set of changing lines,
+    /// in the order of [`Reading::with_changing_lines`]; none if `number`
+    /// isn't 1-64
+    pub fn transitions_from(number: u8) -> impl Iterator<Item = Reading> {
+        (0..64).map_while(move |changing| Self::with_changing_lines(number, changing).ok())
     }
 
-    /// Generate primary hexagram number (1-64)
+    /// Build a reading with no changing lines from its two trigrams, the way
+    /// most books describe hexagrams ("water over fire")
+    pub fn from_trigrams(upper: Trigram, lower: Trigram) -> Self {
+        let [l1, l2, l3] = lower.lines();
+        let [l4, l5, l6] = upper.lines();
+        let lines = [l1, l2, l3, l4, l5, l6].map(|polarity| Line::new(Age::Young, polarity));
+        Self::new(lines, None)
+    }
+
+    /// Generate primary hexagram number (1-64, King Wen sequence)
     pub fn primary_hexagram(&self) -> u8 {
-        self.lines.iter().enumerate().fold(0u8, |acc, (i, line)| {
-            acc + match line.polarity {
-                Polarity::Yang => 2_u8.pow(i as u32),
-                Polarity::Yin => 0,
-            }
-        }) + 1
+        self.primary
+    }
+
+    /// Number of the hexagram the reading changes into, if any lines change
+    pub fn transformed_hexagram_number(&self) -> Option<u8> {
+        self.transformed
+    }
+
+    /// Number of the nuclear (inner) hexagram formed by lines 2-5
+    pub fn nuclear_hexagram(&self) -> u8 {
+        self.nuclear
     }
 
     /// Get upper trigram (lines 4, 5, 6 - positions 3, 4, 5 in array)
-    pub fn upper_trigram(&self) -> [Polarity; 3] {
-        [
+    pub fn upper_trigram(&self) -> Trigram {
+        Trigram::from_lines([
             self.lines[3].polarity,
             self.lines[4].polarity,
             self.lines[5].polarity,
-        ]
+        ])
     }
 
     /// Get lower trigram (lines 1, 2, 3 - positions 0, 1, 2 in array)
-    pub fn lower_trigram(&self) -> [Polarity; 3] {
-        [
+    pub fn lower_trigram(&self) -> Trigram {
+        Trigram::from_lines([
             self.lines[0].polarity,
             self.lines[1].polarity,
             self.lines[2].polarity,
-        ]
+        ])
     }
 
     /// Check if there are changing lines
     pub fn has_changing_lines(&self) -> bool {
-        self.lines.iter().any(|line| line.age == Age::Old)
+        self.transformed.is_some()
     }
 
     /// Get positions of changing lines (1-indexed, traditional bottom-to-top)
@@ -133,7 +262,10 @@ impl Reading {
         }
 
         let transformed_lines = self.lines.map(|line| line.transform());
-        Some(Reading::new(transformed_lines, self.question.clone()))
+        Some(
+            Reading::new(transformed_lines, self.question.clone())
+                .with_context(self.context.clone()),
+        )
     }
 
     /// Get traditional numbers for all lines
@@ -142,42 +274,106 @@ impl Reading {
     }
 
     /// Display the hexagram visually
+    #[deprecated(
+        since = "2.0.0",
+        note = "use the `Display` impl (`to_string()` or `{}`)"
+    )]
     pub fn display(&self) -> String {
-        let mut result = String::new();
+        self.to_string()
+    }
+}
+
+impl fmt::Display for Line {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.to_symbol())
+    }
+}
 
+/// The question, hexagram number, and figure drawn top to bottom, followed by
+/// any changing lines and the hexagram they transform into
+impl fmt::Display for Reading {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         if let Some(ref question) = self.question {
-            result.push_str(&format!("Question: {}\n\n", question));
+            write!(f, "Question: {}\n\n", question)?;
         }
 
-        result.push_str(&format!("Hexagram {}\n", self.primary_hexagram()));
+        writeln!(f, "Hexagram {}", self.primary)?;
 
         // Display lines from top to bottom (reverse array order)
         for (i, line) in self.lines.iter().enumerate().rev() {
-            result.push_str(&format!("{}: {}\n", i + 1, line.to_symbol()));
+            writeln!(f, "{}: {}", i + 1, line)?;
         }
 
         if self.has_changing_lines() {
-            result.push_str(&format!(
+            write!(
+                f,
                 "\nChanging lines: {:?}\n",
                 self.changing_line_positions()
-            ));
+            )?;
 
-            if let Some(transformed) = self.transformed_hexagram() {
-                result.push_str(&format!(
-                    "Transforms to hexagram {}\n",
-                    transformed.primary_hexagram()
-                ));
+            if let Some(transformed) = self.transformed {
+                writeln!(f, "Transforms to hexagram {}", transformed)?;
             }
         }
 
-        result
+        Ok(())
     }
 }
 
+/// Parses the same input the CLI accepts: hexagram numbers and characters,
+/// changes (`32→34`), trigrams, binary patterns, line numbers, and permalink
+/// tokens; see [`crate::core::parse`] for the full grammar
+impl FromStr for Reading {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        parse::reading(s)
+    }
+}
+
+fn polarity_of_bit(bit: u8) -> Polarity {
+    if bit & 1 == 1 {
+        Polarity::Yang
+    } else {
+        Polarity::Yin
+    }
+}
+
+/// King Wen number for six polarities given bottom to top
+fn hexagram_number(polarities: [Polarity; 6]) -> u8 {
+    let bits = polarities
+        .iter()
+        .enumerate()
+        .fold(0u8, |acc, (i, polarity)| match polarity {
+            Polarity::Yang => acc | (1 << i),
+            Polarity::Yin => acc,
+        });
+    king_wen::from_binary(bits)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_all_transitions() {
+        let readings: Vec<Reading> = Reading::all_transitions().collect();
+        assert_eq!(readings.len(), 4096);
+        let distinct: std::collections::HashSet<[u8; 6]> =
+            readings.iter().map(Reading::traditional_numbers).collect();
+        assert_eq!(distinct.len(), 4096);
+
+        assert_eq!(readings[0].traditional_numbers(), [7; 6]);
+        assert_eq!(readings[63].traditional_numbers(), [9; 6]);
+        assert_eq!(readings[64].primary_hexagram(), 2);
+
+        let reading = Reading::with_changing_lines(32, 0b000001).unwrap();
+        assert_eq!(reading.traditional_numbers(), [6, 7, 7, 7, 8, 8]);
+        assert_eq!(reading.transformed_hexagram_number(), Some(34));
+        assert!(Reading::with_changing_lines(32, 64).is_err());
+        assert_eq!(Reading::transitions_from(65).count(), 0);
+    }
+
     #[test]
     fn test_line_traditional_numbers() {
         assert_eq!(Line::new(Age::Old, Polarity::Yin).traditional_number(), 6);
@@ -237,18 +433,16 @@ mod tests {
 
     #[test]
     fn test_hexagram_calculation() {
-        // Test hexagram 1 (all yang lines) - should be 63 + 1 = 64
-        // But wait, let's think about this: all yang = 111111 binary = 63, + 1 = 64
-        // Actually, hexagram 1 (Qian) should be all yang lines
-        // Let me check the traditional numbering...
-
+        // Hexagram 1 (Qian) is all yang lines, hexagram 2 (Kun) all yin
         let all_yang = [Line::new(Age::Young, Polarity::Yang); 6];
-        let reading = Reading::new(all_yang, None);
+        assert_eq!(Reading::new(all_yang, None).primary_hexagram(), 1);
+
+        let all_yin = [Line::new(Age::Young, Polarity::Yin); 6];
+        assert_eq!(Reading::new(all_yin, None).primary_hexagram(), 2);
 
-        // All yang lines: 111111 binary = 63, + 1 = 64
-        // But traditionally, Hexagram 1 (Qian) is all yang lines
-        // The issue is in our calculation - let me verify what we expect
-        assert_eq!(reading.primary_hexagram(), 64); // This is actually correct for our binary calculation
+        // Hexagram 3 (Zhun): yang in lines 1 and 5
+        let zhun = [7, 8, 8, 8, 7, 8].map(|n| Line::from_traditional_number(n).unwrap());
+        assert_eq!(Reading::new(zhun, None).primary_hexagram(), 3);
     }
 
     #[test]
@@ -269,5 +463,173 @@ mod tests {
         let transformed = reading.transformed_hexagram().unwrap();
         assert_eq!(transformed.lines[1], Line::new(Age::Young, Polarity::Yin));
         assert_eq!(transformed.lines[3], Line::new(Age::Young, Polarity::Yang));
+        assert_eq!(
+            reading.transformed_hexagram_number(),
+            Some(transformed.primary_hexagram())
+        );
+    }
+
+    #[test]
+    fn test_nuclear_hexagram() {
+        // Hexagram 1 and 2 are their own nuclear hexagrams
+        let all_yang = [Line::new(Age::Young, Polarity::Yang); 6];
+        assert_eq!(Reading::new(all_yang, None).nuclear_hexagram(), 1);
+        // 63 (water over fire) has 64 as its nuclear hexagram
+        let lines = [7, 8, 7, 8, 7, 8].map(|n| Line::from_traditional_number(n).unwrap());
+        assert_eq!(Reading::new(lines, None).nuclear_hexagram(), 64);
+    }
+
+    #[test]
+    fn test_serde_recomputes_derived_numbers() {
+        let lines = [9, 7, 8, 8, 7, 6].map(|n| Line::from_traditional_number(n).unwrap());
+        let reading = Reading::new(lines, Some("Q".to_string()));
+
+        let json = serde_json::to_string(&reading).unwrap();
+        assert!(!json.contains("primary"));
+
+        let loaded: Reading = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.primary_hexagram(), reading.primary_hexagram());
+        assert_eq!(
+            loaded.transformed_hexagram_number(),
+            reading.transformed_hexagram_number()
+        );
+        assert_eq!(loaded.nuclear_hexagram(), reading.nuclear_hexagram());
+        assert_eq!(loaded.question(), Some("Q"));
+        assert!(!json.contains("context"));
+    }
+
+    #[test]
+    fn test_context_round_trips() {
+        let reading: Reading = "9,8,8,8,8,8".parse().unwrap();
+        let reading = reading.with_context(Some("Two offers, one month".to_string()));
+
+        let json = serde_json::to_string(&reading).unwrap();
+        let loaded: Reading = serde_json::from_str(&json).unwrap();
+        assert_eq!(loaded.context(), Some("Two offers, one month"));
+        assert_eq!(
+            loaded.transformed_hexagram().unwrap().context(),
+            Some("Two offers, one month")
+        );
+    }
+
+    #[test]
+    fn test_trigrams() {
+        // Hexagram 63: water over fire
+        let lines = [7, 8, 7, 8, 7, 8].map(|n| Line::from_traditional_number(n).unwrap());
+        let reading = Reading::new(lines, None);
+        assert_eq!(reading.upper_trigram(), Trigram::Kan);
+        assert_eq!(reading.lower_trigram(), Trigram::Li);
+        assert_eq!(reading.primary_hexagram(), 63);
+
+        let composed = Reading::from_trigrams(Trigram::Kan, Trigram::Li);
+        assert_eq!(composed.lines, reading.lines);
+        assert!(!composed.has_changing_lines());
+        assert_eq!(
+            Reading::from_trigrams(Trigram::Zhen, Trigram::Xun).primary_hexagram(),
+            32
+        );
+    }
+
+    #[test]
+    fn test_parse_hexagram_number() {
+        let reading = "1".parse::<Reading>().unwrap();
+        assert_eq!(reading.primary_hexagram(), 1);
+    }
+
+    #[test]
+    fn test_parse_line_numbers() {
+        let reading = "7,8,9,6,7,8".parse::<Reading>().unwrap();
+        assert_eq!(reading.traditional_numbers(), [7, 8, 9, 6, 7, 8]);
+    }
+
+    #[test]
+    fn test_parse_unicode_character() {
+        let reading = "䷀".parse::<Reading>().unwrap();
+        assert_eq!(reading.primary_hexagram(), 1);
+    }
+
+    #[test]
+    fn test_parse_trigrams() {
+        for input in ["kan/li", "Water over Fire", "☵/☲", "k'an / li"] {
+            let reading = input.parse::<Reading>().unwrap();
+            assert_eq!(reading.primary_hexagram(), 63, "input: {}", input);
+        }
+        assert!("kan/moon".parse::<Reading>().is_err());
+    }
+
+    #[test]
+    fn test_parse_binary() {
+        assert_eq!("0b010001".parse::<Reading>().unwrap().primary_hexagram(), 3);
+        assert_eq!(
+            "0b111_111".parse::<Reading>().unwrap().primary_hexagram(),
+            1
+        );
+        assert!("0b10001".parse::<Reading>().is_err());
+        assert!("0b010201".parse::<Reading>().is_err());
+    }
+
+    #[test]
+    fn test_parse_permalink() {
+        let reading = "iching:v1:789677-0badf00d".parse::<Reading>().unwrap();
+        assert_eq!(reading.traditional_numbers(), [7, 8, 9, 6, 7, 7]);
+        assert!("iching:v1:78967".parse::<Reading>().is_err());
+    }
+
+    #[test]
+    fn test_invalid_input() {
+        assert!("65".parse::<Reading>().is_err());
+        assert!("7,8,5,6,7,8".parse::<Reading>().is_err());
+        assert!("invalid".parse::<Reading>().is_err());
+    }
+
+    #[test]
+    fn test_parse_changing_hexagram_numbers() {
+        let reading = "32→34".parse::<Reading>().unwrap();
+        assert_eq!(reading.primary_hexagram(), 32);
+        assert!(reading.has_changing_lines());
+        if let Some(transformed) = reading.transformed_hexagram() {
+            assert_eq!(transformed.primary_hexagram(), 34);
+        } else {
+            panic!("Expected transformed hexagram");
+        }
+    }
+
+    #[test]
+    fn test_parse_changing_hexagram_ascii_arrow() {
+        let reading = "1->2".parse::<Reading>().unwrap();
+        assert_eq!(reading.primary_hexagram(), 1);
+        assert!(reading.has_changing_lines());
+        if let Some(transformed) = reading.transformed_hexagram() {
+            assert_eq!(transformed.primary_hexagram(), 2);
+        } else {
+            panic!("Expected transformed hexagram");
+        }
+    }
+
+    #[test]
+    fn test_parse_changing_hexagram_unicode() {
+        let reading = "䷀→䷁".parse::<Reading>().unwrap();
+        assert_eq!(reading.primary_hexagram(), 1);
+        assert!(reading.has_changing_lines());
+        if let Some(transformed) = reading.transformed_hexagram() {
+            assert_eq!(transformed.primary_hexagram(), 2);
+        } else {
+            panic!("Expected transformed hexagram");
+        }
+    }
+
+    #[test]
+    fn test_display() {
+        let reading: Reading = "32->34".parse().unwrap();
+        let reading = reading.with_question(Some("Stay?".to_string()));
+        let shown = reading.to_string();
+
+        assert!(shown.starts_with("Question: Stay?\n\nHexagram 32\n6: ━━  ━━\n"));
+        assert!(shown.ends_with("Changing lines: [1]\nTransforms to hexagram 34\n"));
+        #[allow(deprecated)]
+        let legacy = reading.display();
+        assert_eq!(legacy, shown);
+
+        assert_eq!(Line::new(Age::Old, Polarity::Yin).to_string(), "━━  ━━ ×");
     }
 }
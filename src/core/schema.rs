@@ -0,0 +1,135 @@
+//! Versioned serialized forms of a reading
+//!
+//! Readings are written in the current version, tagged with its number, and
+//! loaded from any version an earlier release wrote by migrating one version
+//! at a time. A new format adds a `ReadingV{n}` and a migration from the one
+//! before it; existing versions never change, so journals and exports from
+//! any earlier release keep loading.
+
+use crate::core::reading::{Line, Reading};
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Version readings are written in
+pub const CURRENT: u32 = 1;
+
+/// A reading as written before readings carried a version
+#[derive(Debug, Clone, Deserialize)]
+pub struct ReadingV0 {
+    pub lines: [Line; 6],
+    pub question: Option<String>,
+    #[serde(default)]
+    pub context: Option<String>,
+}
+
+/// Version 1: the lines bottom first, with the question and any context
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReadingV1 {
+    pub version: u32,
+    pub lines: [Line; 6],
+    pub question: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+}
+
+impl ReadingV0 {
+    /// Tag the untagged form; the fields are unchanged
+    pub fn migrate(self) -> ReadingV1 {
+        ReadingV1 {
+            version: 1,
+            lines: self.lines,
+            question: self.question,
+            context: self.context,
+        }
+    }
+}
+
+impl From<ReadingV1> for Reading {
+    fn from(stored: ReadingV1) -> Self {
+        Reading::new(stored.lines, stored.question).with_context(stored.context)
+    }
+}
+
+impl From<Reading> for ReadingV1 {
+    fn from(reading: Reading) -> Self {
+        ReadingV1 {
+            version: CURRENT,
+            lines: *reading.lines(),
+            question: reading.question().map(str::to_string),
+            context: reading.context().map(str::to_string),
+        }
+    }
+}
+
+impl TryFrom<Value> for Reading {
+    type Error = anyhow::Error;
+
+    fn try_from(value: Value) -> Result<Self> {
+        migrate(value).map(Reading::from)
+    }
+}
+
+/// The version a serialized reading was written in; 0 if it has no tag
+pub fn version(value: &Value) -> Result<u32> {
+    match value.get("version") {
+        None => Ok(0),
+        Some(version) => version
+            .as_u64()
+            .and_then(|version| u32::try_from(version).ok())
+            .ok_or_else(|| anyhow::anyhow!("Reading version must be a number, not {}", version)),
+    }
+}
+
+/// A serialized reading of any version, migrated to the current one
+pub fn migrate(value: Value) -> Result<ReadingV1> {
+    match version(&value)? {
+        0 => Ok(serde_json::from_value::<ReadingV0>(value)?.migrate()),
+        1 => Ok(serde_json::from_value(value)?),
+        version => Err(anyhow::anyhow!(
+            "Reading is version {}, newer than this release reads (up to {}); upgrade i-ching to load it",
+            version,
+            CURRENT
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_migrate() {
+        let v0 = r#"{"lines":[{"age":"Old","polarity":"Yang"},{"age":"Young","polarity":"Yang"},{"age":"Young","polarity":"Yin"},{"age":"Young","polarity":"Yin"},{"age":"Young","polarity":"Yang"},{"age":"Old","polarity":"Yin"}],"question":"Q"}"#;
+        let reading: Reading = serde_json::from_str(v0).unwrap();
+        assert_eq!(reading.traditional_numbers(), [9, 7, 8, 8, 7, 6]);
+        assert_eq!(reading.question(), Some("Q"));
+
+        let written = serde_json::to_value(&reading).unwrap();
+        assert_eq!(version(&written).unwrap(), CURRENT);
+        assert_eq!(
+            written,
+            serde_json::to_value(
+                ReadingV0 {
+                    lines: *reading.lines(),
+                    question: Some("Q".to_string()),
+                    context: None,
+                }
+                .migrate()
+            )
+            .unwrap()
+        );
+
+        let mut future = written.clone();
+        future["version"] = 2.into();
+        assert!(
+            migrate(future)
+                .unwrap_err()
+                .to_string()
+                .contains("version 2")
+        );
+        future = written;
+        future["version"] = "one".into();
+        assert!(migrate(future).is_err());
+    }
+}
@@ -0,0 +1,318 @@
+use crate::core::reading::Polarity;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+
+/// The eight trigrams (bagua), in Earlier Heaven order
+///
+/// Deserialization also accepts the Wade-Giles keys used by the data files
+/// (`CH_IEN`, `K_AN`, ...).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Deserialize)]
+pub enum Trigram {
+    #[serde(alias = "CH_IEN")]
+    Qian,
+    #[serde(alias = "TUI")]
+    Dui,
+    #[serde(alias = "LI")]
+    Li,
+    #[serde(alias = "CHEN")]
+    Zhen,
+    #[serde(alias = "SUN")]
+    Xun,
+    #[serde(alias = "K_AN")]
+    Kan,
+    #[serde(alias = "KEN")]
+    Gen,
+    #[serde(alias = "K_UN")]
+    Kun,
+}
+
+impl Trigram {
+    pub const ALL: [Trigram; 8] = [
+        Trigram::Qian,
+        Trigram::Dui,
+        Trigram::Li,
+        Trigram::Zhen,
+        Trigram::Xun,
+        Trigram::Kan,
+        Trigram::Gen,
+        Trigram::Kun,
+    ];
+
+    /// Line pattern as bits: bit 0 is the bottom line, yang = 1
+    pub fn bits(&self) -> u8 {
+        match self {
+            Trigram::Qian => 0b111,
+            Trigram::Dui => 0b011,
+            Trigram::Li => 0b101,
+            Trigram::Zhen => 0b001,
+            Trigram::Xun => 0b110,
+            Trigram::Kan => 0b010,
+            Trigram::Gen => 0b100,
+            Trigram::Kun => 0b000,
+        }
+    }
+
+    /// Trigram for a line pattern (bit 0 is the bottom line, yang = 1)
+    pub fn from_bits(bits: u8) -> Self {
+        let bits = bits & 0b111;
+        Self::ALL
+            .into_iter()
+            .find(|trigram| trigram.bits() == bits)
+            .expect("every 3-bit pattern is a trigram")
+    }
+
+    /// Polarities from bottom to top
+    pub fn lines(&self) -> [Polarity; 3] {
+        std::array::from_fn(|i| {
+            if (self.bits() >> i) & 1 == 1 {
+                Polarity::Yang
+            } else {
+                Polarity::Yin
+            }
+        })
+    }
+
+    /// Trigram for three polarities given bottom to top
+    pub fn from_lines(lines: [Polarity; 3]) -> Self {
+        let bits = lines
+            .iter()
+            .enumerate()
+            .fold(0, |acc, (i, polarity)| match polarity {
+                Polarity::Yang => acc | (1 << i),
+                Polarity::Yin => acc,
+            });
+        Self::from_bits(bits)
+    }
+
+    /// Pinyin name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Trigram::Qian => "Qian",
+            Trigram::Dui => "Dui",
+            Trigram::Li => "Li",
+            Trigram::Zhen => "Zhen",
+            Trigram::Xun => "Xun",
+            Trigram::Kan => "Kan",
+            Trigram::Gen => "Gen",
+            Trigram::Kun => "Kun",
+        }
+    }
+
+    /// Natural image associated with the trigram
+    pub fn image(&self) -> &'static str {
+        match self {
+            Trigram::Qian => "Heaven",
+            Trigram::Dui => "Lake",
+            Trigram::Li => "Fire",
+            Trigram::Zhen => "Thunder",
+            Trigram::Xun => "Wind",
+            Trigram::Kan => "Water",
+            Trigram::Gen => "Mountain",
+            Trigram::Kun => "Earth",
+        }
+    }
+
+    /// Phase in the Later Heaven arrangement: Qian and Dui are metal, Zhen
+    /// and Xun wood, Li fire, Kan water, Gen and Kun earth
+    pub fn element(&self) -> Element {
+        match self {
+            Trigram::Qian | Trigram::Dui => Element::Metal,
+            Trigram::Li => Element::Fire,
+            Trigram::Zhen | Trigram::Xun => Element::Wood,
+            Trigram::Kan => Element::Water,
+            Trigram::Gen | Trigram::Kun => Element::Earth,
+        }
+    }
+
+    /// Unicode trigram symbol (☰-☷)
+    pub fn symbol(&self) -> char {
+        match self {
+            Trigram::Qian => '☰',
+            Trigram::Dui => '☱',
+            Trigram::Li => '☲',
+            Trigram::Zhen => '☳',
+            Trigram::Xun => '☴',
+            Trigram::Kan => '☵',
+            Trigram::Gen => '☶',
+            Trigram::Kun => '☷',
+        }
+    }
+}
+
+/// One of the five phases (wuxing) the trigrams are assigned to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Element {
+    Wood,
+    Fire,
+    Earth,
+    Metal,
+    Water,
+}
+
+impl Element {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Element::Wood => "Wood",
+            Element::Fire => "Fire",
+            Element::Earth => "Earth",
+            Element::Metal => "Metal",
+            Element::Water => "Water",
+        }
+    }
+
+    pub fn chinese(&self) -> &'static str {
+        match self {
+            Element::Wood => "木",
+            Element::Fire => "火",
+            Element::Earth => "土",
+            Element::Metal => "金",
+            Element::Water => "水",
+        }
+    }
+
+    /// The phase this one feeds in the generating (sheng) cycle: wood feeds
+    /// fire, fire makes earth, earth bears metal, metal carries water, water
+    /// nourishes wood
+    pub fn generates(&self) -> Element {
+        match self {
+            Element::Wood => Element::Fire,
+            Element::Fire => Element::Earth,
+            Element::Earth => Element::Metal,
+            Element::Metal => Element::Water,
+            Element::Water => Element::Wood,
+        }
+    }
+
+    /// The phase this one restrains in the controlling (ke) cycle: wood
+    /// parts earth, earth dams water, water quenches fire, fire melts metal,
+    /// metal cuts wood
+    pub fn controls(&self) -> Element {
+        match self {
+            Element::Wood => Element::Earth,
+            Element::Earth => Element::Water,
+            Element::Water => Element::Fire,
+            Element::Fire => Element::Metal,
+            Element::Metal => Element::Wood,
+        }
+    }
+}
+
+impl fmt::Display for Element {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses English (`metal`) or pinyin (`jin`) names, ignoring case
+impl FromStr for Element {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let element = match s.trim().to_lowercase().as_str() {
+            "wood" | "mu" => Element::Wood,
+            "fire" | "huo" => Element::Fire,
+            "earth" | "tu" => Element::Earth,
+            "metal" | "jin" => Element::Metal,
+            "water" | "shui" => Element::Water,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unknown element: '{}'. Expected wood, fire, earth, metal, or water",
+                    s.trim()
+                ));
+            }
+        };
+
+        Ok(element)
+    }
+}
+
+impl fmt::Display for Trigram {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+/// Parses pinyin names (`kan`), Wade-Giles names (`k'an`, `K_AN`), natural
+/// images (`water`), or the Unicode symbol (`☵`), ignoring case
+impl FromStr for Trigram {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim().to_lowercase().replace(['\'', '’', '_'], "");
+
+        let trigram = match name.as_str() {
+            "qian" | "chien" | "heaven" | "☰" => Trigram::Qian,
+            "dui" | "tui" | "lake" | "☱" => Trigram::Dui,
+            "li" | "fire" | "☲" => Trigram::Li,
+            "zhen" | "chen" | "thunder" | "☳" => Trigram::Zhen,
+            "xun" | "sun" | "wind" | "☴" => Trigram::Xun,
+            "kan" | "water" | "☵" => Trigram::Kan,
+            "gen" | "ken" | "mountain" | "☶" => Trigram::Gen,
+            "kun" | "earth" | "☷" => Trigram::Kun,
+            _ => {
+                return Err(anyhow::anyhow!(
+                    "Unknown trigram: '{}'. Expected a name (qian, dui, li, zhen, xun, kan, gen, kun) or image (heaven, lake, fire, thunder, wind, water, mountain, earth)",
+                    s.trim()
+                ));
+            }
+        };
+
+        Ok(trigram)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bits_round_trip() {
+        for trigram in Trigram::ALL {
+            assert_eq!(Trigram::from_bits(trigram.bits()), trigram);
+            assert_eq!(Trigram::from_lines(trigram.lines()), trigram);
+        }
+    }
+
+    #[test]
+    fn test_line_patterns() {
+        // Zhen (thunder): a yang line beneath two yin lines
+        assert_eq!(
+            Trigram::Zhen.lines(),
+            [Polarity::Yang, Polarity::Yin, Polarity::Yin]
+        );
+        assert_eq!(
+            Trigram::from_lines([Polarity::Yin, Polarity::Yang, Polarity::Yang]),
+            Trigram::Xun
+        );
+    }
+
+    #[test]
+    fn test_deserialize_data_keys() {
+        let trigram: Trigram = serde_json::from_str("\"K_AN\"").unwrap();
+        assert_eq!(trigram, Trigram::Kan);
+        let trigram: Trigram = serde_json::from_str("\"Kan\"").unwrap();
+        assert_eq!(trigram, Trigram::Kan);
+        assert_eq!(Trigram::Kan.to_string(), "Kan");
+    }
+
+    #[test]
+    fn test_parse() {
+        assert_eq!("kan".parse::<Trigram>().unwrap(), Trigram::Kan);
+        assert_eq!("K'an".parse::<Trigram>().unwrap(), Trigram::Kan);
+        assert_eq!("Water".parse::<Trigram>().unwrap(), Trigram::Kan);
+        assert_eq!("☵".parse::<Trigram>().unwrap(), Trigram::Kan);
+        assert_eq!("sun".parse::<Trigram>().unwrap(), Trigram::Xun);
+        assert_eq!("CH_IEN".parse::<Trigram>().unwrap(), Trigram::Qian);
+        assert!("moon".parse::<Trigram>().is_err());
+
+        assert_eq!("Jin".parse::<Element>().unwrap(), Element::Metal);
+        assert_eq!("water".parse::<Element>().unwrap(), Trigram::Kan.element());
+        assert!("heaven".parse::<Element>().is_err());
+
+        for trigram in Trigram::ALL {
+            assert_eq!(trigram.name().parse::<Trigram>().unwrap(), trigram);
+            assert_eq!(trigram.image().parse::<Trigram>().unwrap(), trigram);
+        }
+    }
+}
@@ -0,0 +1,130 @@
+//! Cross-checks of the data set's redundant fields, for `--verify-data`
+//!
+//! The hexagram and trigram records repeat facts the code can derive: the
+//! binary string is the two trigrams' lines, the opposite is the complement,
+//! and the Unicode character follows from the number. A hand edit that
+//! breaks one of these would otherwise show up as a quietly wrong reading.
+
+use crate::core::data::{self, IChingData};
+use crate::core::king_wen;
+use crate::core::trigram::Trigram;
+
+/// Every inconsistency found in `data`, or none
+pub fn verify(data: &IChingData) -> Vec<String> {
+    let mut problems = Vec::new();
+
+    for trigram in Trigram::ALL {
+        let Some(record) = data.get_trigram(trigram) else {
+            problems.push(format!("Trigram {} is missing", trigram));
+            continue;
+        };
+        // Line strings run top to bottom, so the bottom line is the low bit
+        if u8::from_str_radix(&record.lines, 2).ok() != Some(trigram.bits()) {
+            problems.push(format!(
+                "Trigram {} has lines {}, expected {:03b}",
+                trigram,
+                record.lines,
+                trigram.bits()
+            ));
+        }
+        if record.unicode != trigram.symbol().to_string() {
+            problems.push(format!(
+                "Trigram {} has symbol {}, expected {}",
+                trigram,
+                record.unicode,
+                trigram.symbol()
+            ));
+        }
+    }
+
+    for number in 1..=64 {
+        let Some(hexagram) = data.get_hexagram(number) else {
+            problems.push(format!("Hexagram {} is missing or doesn't parse", number));
+            continue;
+        };
+        let mut problem = |message: String| {
+            problems.push(format!("Hexagram {}: {}", number, message));
+        };
+
+        if hexagram.number != number {
+            problem(format!(
+                "filed under {} but numbered {}",
+                number, hexagram.number
+            ));
+        }
+
+        let expected = data::hexagram_number_to_unicode(number).unwrap_or_default();
+        if hexagram.unicode != expected.to_string() {
+            problem(format!(
+                "character {} should be {} (U+{:04X})",
+                hexagram.unicode, expected, expected as u32
+            ));
+        }
+
+        let bits = u8::from_str_radix(&hexagram.binary, 2).ok();
+        if bits.map(king_wen::from_binary) != Some(number) || hexagram.binary.len() != 6 {
+            problem(format!(
+                "binary {} is not this hexagram's pattern",
+                hexagram.binary
+            ));
+        }
+        let trigram_lines = format!(
+            "{:03b}{:03b}",
+            hexagram.upper_trigram.bits(),
+            hexagram.lower_trigram.bits()
+        );
+        if hexagram.binary != trigram_lines {
+            problem(format!(
+                "binary {} doesn't match {} over {} ({})",
+                hexagram.binary, hexagram.upper_trigram, hexagram.lower_trigram, trigram_lines
+            ));
+        }
+
+        let complement = king_wen::complement(number).unwrap_or_default();
+        if hexagram.opposite.parse::<u8>().ok() != Some(complement) {
+            problem(format!(
+                "opposite {} should be {:02}, every line changed",
+                hexagram.opposite, complement
+            ));
+        }
+
+        for position in 1..=6 {
+            if !hexagram.lines.contains_key(&position.to_string()) {
+                problem(format!("line {} has no text", position));
+            }
+        }
+    }
+
+    problems
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_verify() {
+        let data = IChingData::load().unwrap();
+        assert_eq!(verify(&data), Vec::<String>::new());
+
+        // Hexagram 3 with the wrong pattern, opposite, and character
+        let mut hexagrams: serde_json::Value =
+            serde_json::from_str(include_str!("../../data/hexagrams.json")).unwrap();
+        hexagrams["3"]["binary"] = "010010".into();
+        hexagrams["3"]["opposite"] = "49".into();
+        hexagrams["3"]["unicode"] = "䷃".into();
+        let data = IChingData::from_json(
+            include_str!("../../data/trigrams.json"),
+            &hexagrams.to_string(),
+        )
+        .unwrap();
+        let problems = verify(&data);
+        assert_eq!(problems.len(), 4, "{:#?}", problems);
+        assert!(
+            problems
+                .iter()
+                .all(|problem| problem.starts_with("Hexagram 3: "))
+        );
+        assert!(problems[2].contains("doesn't match Kan over Zhen (010001)"));
+    }
+}
@@ -0,0 +1,406 @@
+use crate::core::data::IChingData;
+use crate::core::{Diviner, Reading};
+use crate::format::{self, Block, RenderOptions};
+use crate::json::json_reading_with_data;
+use anyhow::Result;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::env;
+use std::io::{BufRead, Write};
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Environment variable that overrides the daemon socket location
+pub const SOCKET_PATH_ENV: &str = "I_CHING_SOCKET";
+
+/// One line of the daemon protocol, e.g. `{"op": "cast", "question": "..."}`
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "op", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Request {
+    /// Cast a reading, or build one from any `--input` format
+    Cast {
+        #[serde(default)]
+        input: Option<String>,
+        #[serde(default)]
+        question: Option<String>,
+        /// Send the full text a section at a time instead of JSON
+        #[serde(default)]
+        stream: bool,
+    },
+    /// The reading for a hexagram with no changing lines
+    Lookup {
+        hexagram: u8,
+        #[serde(default)]
+        stream: bool,
+    },
+    Ping,
+}
+
+/// Reply to a request: `{"ok": true, "result": ...}` or `{"ok": false, "error": "..."}`
+///
+/// A streamed reading is instead a `{"ok": true, "section": {"kind": ...,
+/// "text": ...}}` line per section of full output, then `{"ok": true, "done": true}`.
+#[derive(Debug, Clone, PartialEq, Default, Serialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub section: Option<Block>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    pub done: bool,
+}
+
+/// What a request produced, before it is framed as response lines
+enum Reply {
+    Value(Value),
+    /// A reading's sections, laid out one at a time as they're sent
+    Sections(Box<dyn Iterator<Item = Block>>),
+}
+
+impl Response {
+    fn from_result(result: Result<Reply>) -> Box<dyn Iterator<Item = Self>> {
+        match result {
+            Ok(Reply::Value(value)) => Box::new(std::iter::once(Self {
+                ok: true,
+                result: Some(value),
+                ..Self::default()
+            })),
+            Ok(Reply::Sections(blocks)) => Box::new(
+                blocks
+                    .map(|block| Self {
+                        ok: true,
+                        section: Some(block),
+                        ..Self::default()
+                    })
+                    .chain([Self {
+                        ok: true,
+                        done: true,
+                        ..Self::default()
+                    }]),
+            ),
+            Err(e) => Box::new(std::iter::once(Self {
+                ok: false,
+                error: Some(e.to_string()),
+                ..Self::default()
+            })),
+        }
+    }
+
+    /// Whether more lines follow this one for the same request
+    fn continues(line: &str) -> bool {
+        serde_json::from_str::<Value>(line).is_ok_and(|value| value.get("section").is_some())
+    }
+}
+
+/// `$I_CHING_SOCKET`, or `i-ching.sock` in the user runtime directory
+///
+/// Without a runtime directory (`$XDG_RUNTIME_DIR`), the socket goes in an
+/// `i-ching` directory under the user cache directory, readable only by the
+/// user, rather than the shared temporary directory, where another user
+/// could take the path first.
+pub fn default_socket_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os(SOCKET_PATH_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+    if let Some(dir) = dirs::runtime_dir() {
+        return Ok(dir.join("i-ching.sock"));
+    }
+
+    let dir = dirs::cache_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the user cache directory"))?
+        .join("i-ching");
+    private_dir(&dir)?;
+    Ok(dir.join("i-ching.sock"))
+}
+
+/// Create `dir` if need be, readable only by the user
+#[cfg(unix)]
+fn private_dir(dir: &Path) -> Result<()> {
+    use anyhow::Context;
+    use std::os::unix::fs::PermissionsExt;
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    std::fs::set_permissions(dir, std::fs::Permissions::from_mode(0o700))
+        .with_context(|| format!("Failed to restrict {}", dir.display()))
+}
+
+#[cfg(not(unix))]
+fn private_dir(dir: &Path) -> Result<()> {
+    use anyhow::Context;
+
+    std::fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))
+}
+
+/// Long-running server that keeps the I Ching data parsed between requests
+///
+/// Clones share the one copy of the data, so each connection's thread gets
+/// its own handle without loading anything.
+#[derive(Clone)]
+pub struct Daemon {
+    data: Arc<IChingData>,
+}
+
+impl Daemon {
+    /// Load the data and parse every hexagram up front
+    pub fn new() -> Result<Self> {
+        let data = IChingData::load()
+            .map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+        Ok(Self::with_data(Arc::new(data)))
+    }
+
+    /// Serve data already loaded, parsing every hexagram up front
+    pub fn with_data(data: Arc<IChingData>) -> Self {
+        data.warm();
+        Self { data }
+    }
+
+    /// Answer one request line: a single response, or a streamed reading's
+    /// sections and closing line, each section laid out as it's taken
+    pub fn handle(&self, line: &str, diviner: &mut Diviner) -> impl Iterator<Item = Response> {
+        Response::from_result(self.respond(line, diviner))
+    }
+
+    fn respond(&self, line: &str, diviner: &mut Diviner) -> Result<Reply> {
+        let request: Request =
+            serde_json::from_str(line).map_err(|e| anyhow::anyhow!("Invalid request: {}", e))?;
+
+        let (reading, stream) = match request {
+            Request::Ping => return Ok(Reply::Value(Value::from("pong"))),
+            Request::Cast {
+                input,
+                question,
+                stream,
+            } => {
+                let reading = match input {
+                    Some(input) => input.parse::<Reading>()?,
+                    None => diviner.cast_reading(None),
+                };
+                (reading.with_question(question), stream)
+            }
+            Request::Lookup { hexagram, stream } => {
+                (Reading::from_hexagram_number(hexagram)?, stream)
+            }
+        };
+
+        if stream {
+            let blocks = format::blocks(&reading, &self.data, &RenderOptions::default())?;
+            return Ok(Reply::Sections(Box::new(blocks)));
+        }
+        Ok(Reply::Value(serde_json::to_value(json_reading_with_data(
+            &reading, &self.data,
+        )?)?))
+    }
+
+    /// Answer newline-delimited requests from one connection until it closes
+    pub fn serve_connection(&self, reader: impl BufRead, mut writer: impl Write) -> Result<()> {
+        let mut diviner = Diviner::new();
+        for line in reader.lines() {
+            let line = line?;
+            if line.trim().is_empty() {
+                continue;
+            }
+            // Flushed line by line, so a streamed reading arrives a section at a time
+            for response in self.handle(&line, &mut diviner) {
+                writeln!(writer, "{}", serde_json::to_string(&response)?)?;
+                writer.flush()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Listen on a Unix socket, one thread per connection
+    #[cfg(unix)]
+    pub fn serve(self, path: &Path) -> Result<()> {
+        use anyhow::Context;
+        use std::os::unix::fs::FileTypeExt;
+        use std::os::unix::net::{UnixListener, UnixStream};
+
+        if let Ok(metadata) = std::fs::symlink_metadata(path) {
+            if !metadata.file_type().is_socket() {
+                return Err(anyhow::anyhow!(
+                    "{} exists and isn't a socket; give --socket a path to create",
+                    path.display()
+                ));
+            }
+            if UnixStream::connect(path).is_ok() {
+                return Err(anyhow::anyhow!(
+                    "A daemon is already listening on {}",
+                    path.display()
+                ));
+            }
+            // Left behind by a daemon that didn't shut down cleanly
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}", path.display()))?;
+        }
+
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", path.display()))?;
+        eprintln!("Listening on {}", path.display());
+        self.serve_listener(listener)
+    }
+
+    /// Accept connections on a Unix socket that's already listening, such
+    /// as one passed by systemd
+    #[cfg(unix)]
+    pub fn serve_listener(self, listener: std::os::unix::net::UnixListener) -> Result<()> {
+        use std::io::BufReader;
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let daemon = self.clone();
+            std::thread::spawn(move || {
+                let reader = match stream.try_clone() {
+                    Ok(reader) => BufReader::new(reader),
+                    Err(e) => return eprintln!("Failed to read connection: {}", e),
+                };
+                if let Err(e) = daemon.serve_connection(reader, stream) {
+                    eprintln!("Connection closed with error: {}", e);
+                }
+            });
+        }
+
+        Ok(())
+    }
+
+    #[cfg(not(unix))]
+    pub fn serve(self, _path: &Path) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "The daemon needs Unix domain sockets, which this platform lacks"
+        ))
+    }
+}
+
+/// Forward request lines to the daemon, writing each response line to `out`
+/// (all of a streamed reading's lines before the next request goes out)
+#[cfg(unix)]
+pub fn send(path: &Path, requests: impl BufRead, out: &mut impl Write) -> Result<()> {
+    use anyhow::Context;
+    use std::io::BufReader;
+    use std::os::unix::net::UnixStream;
+
+    let stream = UnixStream::connect(path).with_context(|| {
+        format!(
+            "Failed to connect to {} (is `i-ching daemon` running?)",
+            path.display()
+        )
+    })?;
+    let mut responses = BufReader::new(stream.try_clone()?).lines();
+    let mut stream = stream;
+
+    for request in requests.lines() {
+        let request = request?;
+        if request.trim().is_empty() {
+            continue;
+        }
+        writeln!(stream, "{}", request.trim())?;
+        loop {
+            let response = responses
+                .next()
+                .ok_or_else(|| anyhow::anyhow!("The daemon closed the connection"))??;
+            writeln!(out, "{}", response)?;
+            out.flush()?;
+            if !Response::continues(&response) {
+                break;
+            }
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _requests: impl BufRead, _out: &mut impl Write) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "The daemon needs Unix domain sockets, which this platform lacks"
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_serve_connection() {
+        let daemon = Daemon::new().unwrap();
+        // Each connection's handle shares the one copy of the data
+        assert!(Arc::ptr_eq(&daemon.data, &daemon.clone().data));
+        let requests = concat!(
+            r#"{"op": "ping"}"#,
+            "\n\n",
+            r#"{"op": "cast", "input": "1→2", "question": "Now?"}"#,
+            "\n",
+            r#"{"op": "lookup", "hexagram": 65}"#,
+            "\n",
+            r#"{"op": "divine"}"#,
+            "\n",
+        );
+
+        let mut output = Vec::new();
+        daemon
+            .serve_connection(requests.as_bytes(), &mut output)
+            .unwrap();
+        let responses: Vec<Value> = String::from_utf8(output)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+
+        assert_eq!(responses.len(), 4);
+        assert_eq!(responses[0]["result"], "pong");
+        assert_eq!(responses[1]["ok"], true);
+        assert_eq!(responses[1]["result"]["question"], "Now?");
+        assert_eq!(responses[1]["result"]["transformed_hexagram"]["number"], 2);
+        assert_eq!(responses[2]["ok"], false);
+        assert!(
+            responses[3]["error"]
+                .as_str()
+                .unwrap()
+                .starts_with("Invalid request")
+        );
+    }
+
+    #[test]
+    fn test_streamed_reading() {
+        let daemon = Daemon::new().unwrap();
+        let mut diviner = Diviner::new();
+        let lines: Vec<String> = daemon
+            .handle(
+                r#"{"op": "cast", "input": "32→34", "stream": true}"#,
+                &mut diviner,
+            )
+            .map(|response| serde_json::to_string(&response).unwrap())
+            .collect();
+
+        let (last, sections) = lines.split_last().unwrap();
+        assert_eq!(last, r#"{"ok":true,"done":true}"#);
+        assert!(!Response::continues(last));
+        assert!(sections.iter().all(|line| Response::continues(line)));
+        let first: Value = serde_json::from_str(&sections[0]).unwrap();
+        assert_eq!(first["section"]["kind"], "figure");
+        assert!(
+            sections
+                .iter()
+                .any(|line| line.contains(r#""kind":"changing_line""#))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_serve_refuses_other_files() {
+        let path = env::temp_dir().join(format!("i-ching-socket-{}.txt", std::process::id()));
+        std::fs::write(&path, "keep me").unwrap();
+        let err = Daemon::new().unwrap().serve(&path).unwrap_err();
+        assert!(err.to_string().contains("isn't a socket"));
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "keep me");
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,180 @@
+//! `i-ching dashboard`: the day's readings on one compact screen
+//!
+//! Three panels, each built from a reading's [`Rendered`] sections: the
+//! hexagram of the day (the login message's), the hour hexagram, and the
+//! latest reading saved to the journal for yourself rather than `--for`
+//! someone else.
+
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::format::FullOptions;
+use crate::glyphs::Glyphs;
+use crate::journal::JournalEntry;
+use crate::motd;
+use crate::rendered::{Part, Rendered, SectionKind};
+use crate::shichen::HourGua;
+use anyhow::Result;
+use chrono::{Local, NaiveDateTime};
+use serde::Serialize;
+
+/// Label of the journaled reading's panel
+const LATEST: &str = "Latest";
+
+/// One reading on the dashboard
+#[derive(Debug, Clone, Serialize)]
+pub struct Panel {
+    /// e.g. "Today"
+    pub label: String,
+    /// Where the reading comes from, e.g. the date
+    pub detail: String,
+    pub reading: Rendered,
+}
+
+impl Panel {
+    fn new(
+        label: &str,
+        detail: String,
+        reading: &Reading,
+        data: &IChingData,
+        glyphs: Glyphs,
+    ) -> Result<Self> {
+        let options = FullOptions {
+            only: Some(vec![Part::Question, Part::Judgment, Part::Lines]),
+            ..FullOptions::default()
+        };
+        Ok(Panel {
+            label: label.to_string(),
+            detail,
+            reading: Rendered::new(reading, data, glyphs, &options)?,
+        })
+    }
+
+    /// The label line, then the reading's summary over its first changing
+    /// line (or its judgment, if no line changes), fitted to `width`
+    pub fn to_text(&self, width: usize) -> String {
+        let text = self
+            .reading
+            .of_kind(&[SectionKind::ChangingLine])
+            .next()
+            .or_else(|| self.reading.of_kind(&[SectionKind::Judgment]).next())
+            .map(|section| format!("{}: {}", section.title, section.body()))
+            .unwrap_or_default();
+        let mut result = motd::fit(&format!("{}: {}", self.label, self.detail), "", width);
+        if let Some(question) = self.reading.of_kind(&[SectionKind::Question]).next() {
+            result.push('\n');
+            result.push_str(&motd::fit(&format!("Q: {}", question.body()), "", width));
+        }
+        result.push('\n');
+        result.push_str(&motd::fit(&self.reading.title, &text, width));
+        result
+    }
+}
+
+/// The day's panels
+#[derive(Debug, Clone, Serialize)]
+pub struct Dashboard {
+    pub panels: Vec<Panel>,
+}
+
+impl Dashboard {
+    /// Panels for `now`, with `latest` as the journaled reading if there is
+    /// one
+    pub fn new(
+        now: NaiveDateTime,
+        latest: Option<&JournalEntry>,
+        data: &IChingData,
+        glyphs: Glyphs,
+    ) -> Result<Self> {
+        let hour = HourGua::at(now)?;
+        let mut panels = vec![
+            Panel::new(
+                "Today",
+                now.format("%A %-d %B %Y").to_string(),
+                &motd::daily_reading(now.date()),
+                data,
+                glyphs,
+            )?,
+            Panel::new("Hour", hour.describe(), &hour.reading, data, glyphs)?,
+        ];
+        if let Some(entry) = latest {
+            panels.push(Panel::new(
+                LATEST,
+                format!(
+                    "saved {}",
+                    entry
+                        .timestamp
+                        .with_timezone(&Local)
+                        .format("%Y-%m-%d %H:%M")
+                ),
+                &entry.reading,
+                data,
+                glyphs,
+            )?);
+        }
+        Ok(Dashboard { panels })
+    }
+
+    /// The panels one after another, each fitted to `width`
+    pub fn to_text(&self, width: usize) -> String {
+        let mut panels: Vec<String> = self
+            .panels
+            .iter()
+            .map(|panel| panel.to_text(width))
+            .collect();
+        if !self.panels.iter().any(|panel| panel.label == LATEST) {
+            panels.push(motd::fit(
+                &format!(
+                    "{}: nothing saved to the journal yet (cast with --save)",
+                    LATEST
+                ),
+                "",
+                width,
+            ));
+        }
+        panels.join("\n\n") + "\n"
+    }
+}
+
+/// The most recent entry cast for yourself, not `--for` someone else
+pub fn latest_personal(entries: &[JournalEntry]) -> Option<&JournalEntry> {
+    entries
+        .iter()
+        .filter(|entry| entry.recipient.is_none())
+        .max_by_key(|entry| entry.timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dashboard() {
+        let data = IChingData::load().unwrap();
+        let now = NaiveDateTime::parse_from_str("2026-03-01 12:30", "%Y-%m-%d %H:%M").unwrap();
+
+        let mine = JournalEntry::new(
+            "32->34"
+                .parse::<Reading>()
+                .unwrap()
+                .with_question(Some("Should I stay?".to_string())),
+        );
+        let theirs =
+            JournalEntry::new("1".parse().unwrap()).with_recipient(Some("Ada".to_string()));
+        let entries = [mine, theirs];
+        let latest = latest_personal(&entries);
+        assert_eq!(latest.unwrap().reading.primary_hexagram(), 32);
+
+        let dashboard = Dashboard::new(now, latest, &data, Glyphs::Ascii).unwrap();
+        let text = dashboard.to_text(60);
+        let panels: Vec<&str> = text.trim_end().split("\n\n").collect();
+        assert_eq!(panels.len(), 3);
+        assert!(panels[0].starts_with("Today: Sunday 1 March 2026\n"));
+        assert!(panels[1].starts_with("Hour: Hour of the Horse"));
+        assert!(panels[2].contains("\nQ: Should I stay?\n32 Long Lasting -> 34 Great Strength"));
+        assert!(panels[2].contains("\nLine 1: "));
+        assert!(text.lines().all(|line| line.chars().count() <= 60));
+
+        let empty = Dashboard::new(now, None, &data, Glyphs::Ascii).unwrap();
+        assert!(empty.to_text(60).contains("\n\nLatest: nothing saved"));
+    }
+}
@@ -0,0 +1,270 @@
+//! `i-ching diff`: what changed from one reading to another
+//!
+//! The delta covers each line whose value differs and how the two primary
+//! (and transformed) hexagrams relate by their figures, as
+//! [`relations`](crate::relations) finds them.
+
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::core::king_wen;
+use crate::glyphs::Glyphs;
+use crate::relations::{self, Relation};
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// One line cast differently in the two readings
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct LineChange {
+    /// 1 (bottom) to 6
+    pub position: u8,
+    /// Value in the first reading, 6 to 9
+    pub from: u8,
+    /// Value in the second reading, 6 to 9
+    pub to: u8,
+    /// Whether the line turned from yang to yin or back, which changes the
+    /// primary hexagram
+    pub polarity_changed: bool,
+}
+
+/// How the hexagram in one place of the first reading relates to the one in
+/// the same place of the second
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct HexagramChange {
+    pub from: Option<u8>,
+    pub to: Option<u8>,
+    /// Lines in which the two figures differ
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub differing_lines: Option<u32>,
+    /// How `from` relates to `to`; empty if they're the same or unrelated
+    pub relations: Vec<Relation>,
+    /// Whether the two make a King Wen pair
+    pub pair: bool,
+}
+
+impl HexagramChange {
+    fn new(from: Option<u8>, to: Option<u8>) -> Self {
+        let (differing_lines, relations, pair) = match (from, to) {
+            (Some(from), Some(to)) => (
+                king_wen::to_binary(from)
+                    .zip(king_wen::to_binary(to))
+                    .map(|(a, b)| (a ^ b).count_ones()),
+                relations::between(from, to),
+                from != to && king_wen::pair(from).is_some_and(|pair| pair.partner_of(from) == to),
+            ),
+            _ => (None, Vec::new(), false),
+        };
+        HexagramChange {
+            from,
+            to,
+            differing_lines,
+            relations,
+            pair,
+        }
+    }
+
+    pub fn is_same(&self) -> bool {
+        self.from == self.to
+    }
+}
+
+/// The delta from one reading to another
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ReadingDiff {
+    /// Lines cast differently, bottom first
+    pub lines: Vec<LineChange>,
+    pub primary: HexagramChange,
+    pub transformed: HexagramChange,
+    /// Changing lines only the second reading has
+    pub changing_added: Vec<u8>,
+    /// Changing lines only the first reading has
+    pub changing_removed: Vec<u8>,
+}
+
+impl ReadingDiff {
+    pub fn new(a: &Reading, b: &Reading) -> Self {
+        let lines = a
+            .lines()
+            .iter()
+            .zip(b.lines())
+            .zip(1..)
+            .filter(|((from, to), _)| from != to)
+            .map(|((from, to), position)| LineChange {
+                position,
+                from: from.traditional_number(),
+                to: to.traditional_number(),
+                polarity_changed: from.polarity != to.polarity,
+            })
+            .collect();
+        let (a_changing, b_changing) = (a.changing_line_positions(), b.changing_line_positions());
+        ReadingDiff {
+            lines,
+            primary: HexagramChange::new(Some(a.primary_hexagram()), Some(b.primary_hexagram())),
+            transformed: HexagramChange::new(
+                a.transformed_hexagram_number(),
+                b.transformed_hexagram_number(),
+            ),
+            changing_added: only_in(&b_changing, &a_changing),
+            changing_removed: only_in(&a_changing, &b_changing),
+        }
+    }
+
+    /// Whether the two readings cast every line alike
+    pub fn is_empty(&self) -> bool {
+        self.lines.is_empty()
+    }
+
+    /// The delta for reading: the two hexagrams in each place and how they
+    /// relate, then each line that differs
+    pub fn to_text(&self, data: &IChingData, glyphs: Glyphs) -> Result<String> {
+        let name = |number: Option<u8>| -> Result<String> {
+            let Some(number) = number else {
+                return Ok("none".to_string());
+            };
+            let hexagram = data
+                .get_hexagram(number)
+                .with_context(|| format!("Hexagram {} not found", number))?;
+            Ok(format!(
+                "{}{} {}",
+                glyphs.hexagram_prefix(&hexagram.unicode),
+                number,
+                hexagram.name
+            ))
+        };
+        let describe = |label: &str, change: &HexagramChange| -> Result<String> {
+            if change.is_same() {
+                return Ok(format!("{}: {} in both\n", label, name(change.from)?));
+            }
+            let mut how: Vec<String> = change
+                .differing_lines
+                .map(|count| format!("{} line{} apart", count, if count == 1 { "" } else { "s" }))
+                .into_iter()
+                .chain(change.relations.iter().map(|r| r.label().to_string()))
+                .collect();
+            if change.pair {
+                how.push("King Wen pair".to_string());
+            }
+            let how = if how.is_empty() {
+                String::new()
+            } else {
+                format!(" ({})", how.join(", "))
+            };
+            Ok(format!(
+                "{}: {} {} {}{}\n",
+                label,
+                name(change.from)?,
+                glyphs.arrow(),
+                name(change.to)?,
+                how
+            ))
+        };
+
+        if self.is_empty() {
+            return Ok(format!(
+                "{}The readings cast every line alike.\n",
+                describe("Primary", &self.primary)?
+            ));
+        }
+        let mut text = describe("Primary", &self.primary)?;
+        // Neither reading has changing lines if neither has a transformed
+        // hexagram
+        if self.transformed.from.is_some() || self.transformed.to.is_some() {
+            text.push_str(&describe("Transformed", &self.transformed)?);
+        }
+        text.push_str("\nLines:\n");
+        for line in &self.lines {
+            let mut notes = Vec::new();
+            if line.polarity_changed {
+                notes.push("yang and yin swapped");
+            }
+            match (is_changing(line.from), is_changing(line.to)) {
+                (false, true) => notes.push("now changing"),
+                (true, false) => notes.push("no longer changing"),
+                _ => {}
+            }
+            text.push_str(&format!(
+                "  Line {}: {} {} {} ({})\n",
+                line.position,
+                line.from,
+                glyphs.arrow(),
+                line.to,
+                notes.join(", ")
+            ));
+        }
+        Ok(text)
+    }
+}
+
+/// Whether a line value is an old line, 6 or 9
+fn is_changing(value: u8) -> bool {
+    value == 6 || value == 9
+}
+
+fn only_in(these: &[u8], those: &[u8]) -> Vec<u8> {
+    these
+        .iter()
+        .copied()
+        .filter(|position| !those.contains(position))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_diff() {
+        let data = IChingData::load().unwrap();
+        let a: Reading = "32->34".parse().unwrap();
+        let b: Reading = "34".parse().unwrap();
+        let diff = ReadingDiff::new(&a, &b);
+        assert_eq!(diff.primary.from, Some(32));
+        assert_eq!(diff.primary.to, Some(34));
+        assert_eq!(diff.primary.differing_lines, Some(1));
+        assert_eq!(diff.transformed.to, None);
+        assert!(diff.changing_added.is_empty());
+        assert_eq!(diff.changing_removed, a.changing_line_positions());
+        assert_eq!(
+            diff.lines,
+            [LineChange {
+                position: 1,
+                from: 6,
+                to: 7,
+                polarity_changed: true,
+            }]
+        );
+
+        // A line that swaps polarity is told apart from one that only starts
+        // changing
+        let c: Reading = "8,7,7,7,7,7".parse().unwrap();
+        let d: Reading = "9,7,7,7,7,7".parse().unwrap();
+        let diff = ReadingDiff::new(&c, &d);
+        assert_eq!(
+            diff.lines,
+            [LineChange {
+                position: 1,
+                from: 8,
+                to: 9,
+                polarity_changed: true,
+            }]
+        );
+        // 1 is also 44's nuclear hexagram
+        assert_eq!(
+            diff.primary.relations,
+            [Relation::Adjacent, Relation::Nuclear]
+        );
+        assert_eq!(diff.changing_added, [1]);
+        let text = diff.to_text(&data, Glyphs::Ascii).unwrap();
+        assert!(text.starts_with(
+            "Primary: 44 Encountering -> 1 Initiating (1 line apart, adjacent, nuclear)\n"
+        ));
+        assert!(text.contains("\n  Line 1: 8 -> 9 (yang and yin swapped, now changing)\n"));
+
+        let same = ReadingDiff::new(&a, &a);
+        assert!(same.is_empty());
+        assert!(
+            same.to_text(&data, Glyphs::Ascii)
+                .unwrap()
+                .ends_with("cast every line alike.\n")
+        );
+    }
+}
@@ -0,0 +1,127 @@
+use crate::core::partial::PartialReading;
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the drafts directory
+pub const DRAFTS_DIR_ENV: &str = "I_CHING_DRAFTS";
+
+/// Partly cast readings saved by name, one JSON file each
+pub struct Drafts {
+    dir: PathBuf,
+}
+
+impl Drafts {
+    /// Open the drafts in `$I_CHING_DRAFTS`, or in the user data directory
+    pub fn open_default() -> Result<Self> {
+        if let Some(dir) = env::var_os(DRAFTS_DIR_ENV) {
+            return Ok(Self::at(dir));
+        }
+
+        let data_dir = dirs::data_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine the user data directory"))?;
+        Ok(Self::at(data_dir.join("i-ching").join("drafts")))
+    }
+
+    /// Open drafts stored in a specific directory
+    pub fn at(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    fn path(&self, name: &str) -> Result<PathBuf> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if !valid {
+            return Err(anyhow::anyhow!(
+                "Invalid draft name '{}': use letters, digits, '-', and '_'",
+                name
+            ));
+        }
+        Ok(self.dir.join(format!("{}.json", name)))
+    }
+
+    /// Save a draft, replacing any with the same name
+    pub fn save(&self, name: &str, draft: &PartialReading) -> Result<()> {
+        let path = self.path(name)?;
+        fs::create_dir_all(&self.dir)
+            .with_context(|| format!("Failed to create {}", self.dir.display()))?;
+        let content = serde_json::to_string_pretty(draft)?;
+        fs::write(&path, content + "\n")
+            .with_context(|| format!("Failed to write draft {}", path.display()))
+    }
+
+    pub fn load(&self, name: &str) -> Result<PartialReading> {
+        let path = self.path(name)?;
+        if !path.exists() {
+            return Err(anyhow::anyhow!("No draft named '{}'", name));
+        }
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read draft {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid draft file {}", path.display()))
+    }
+
+    pub fn remove(&self, name: &str) -> Result<()> {
+        let path = self.path(name)?;
+        fs::remove_file(&path).with_context(|| format!("Failed to remove draft {}", path.display()))
+    }
+
+    /// Names of the saved drafts, sorted
+    pub fn names(&self) -> Result<Vec<String>> {
+        if !self.dir.exists() {
+            return Ok(Vec::new());
+        }
+
+        let mut names = Vec::new();
+        for entry in fs::read_dir(&self.dir)
+            .with_context(|| format!("Failed to read {}", self.dir.display()))?
+        {
+            let path = entry?.path();
+            if path
+                .extension()
+                .is_some_and(|extension| extension == "json")
+            {
+                if let Some(stem) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    names.push(stem.to_string());
+                }
+            }
+        }
+        names.sort();
+        Ok(names)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_save_load_remove() {
+        let drafts = Drafts::at(env::temp_dir().join(format!(
+            "i-ching-drafts-{}-{}",
+            std::process::id(),
+            chrono::Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        )));
+        assert!(drafts.names().unwrap().is_empty());
+
+        let mut draft = PartialReading::new(None);
+        draft.cast(2);
+        drafts.save("morning", &draft).unwrap();
+        assert_eq!(drafts.load("morning").unwrap(), draft);
+        assert_eq!(drafts.names().unwrap(), ["morning"]);
+
+        assert!(drafts.save("../escape", &draft).is_err());
+        assert!(drafts.load("evening").is_err());
+
+        drafts.remove("morning").unwrap();
+        assert!(drafts.names().unwrap().is_empty());
+        fs::remove_dir(drafts.dir()).unwrap();
+    }
+}
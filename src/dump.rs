@@ -0,0 +1,398 @@
+use crate::core::Trigram;
+use crate::core::data::{
+    HexagramImage, HexagramJudgment, IChingData, Pronunciation, TrigramDiscussion,
+};
+use crate::core::king_wen::{self, PairRelation};
+use crate::core::reading::{Polarity, Reading};
+use anyhow::{Context, Result};
+use serde::Serialize;
+use std::path::Path;
+
+/// Bumped whenever a field is renamed or removed; new fields keep the version
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// The whole embedded corpus in the export schema
+#[derive(Debug, Serialize)]
+pub struct Corpus<'a> {
+    pub schema_version: u32,
+    /// The eight trigrams in Trigram::ALL order
+    pub trigrams: Vec<TrigramRecord<'a>>,
+    /// The 64 hexagrams in King Wen order
+    pub hexagrams: Vec<HexagramRecord<'a>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct TrigramRecord<'a> {
+    /// Pinyin key used by `upper_trigram`/`lower_trigram` ("Qian")
+    pub key: &'static str,
+    pub symbol: char,
+    pub chinese: &'a str,
+    pub name: &'a str,
+    pub image: &'static str,
+    pub attribute: &'a str,
+    /// Lines bottom to top, 1 for yang and 0 for yin
+    pub lines: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub discussion: Option<&'a TrigramDiscussion>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct HexagramRecord<'a> {
+    pub number: u8,
+    pub symbol: &'a str,
+    pub name: &'a str,
+    pub chinese: &'a str,
+    pub pinyin: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pronunciation: Option<&'a Pronunciation>,
+    /// Lines bottom to top, 1 for yang and 0 for yin
+    pub lines: String,
+    pub upper_trigram: &'static str,
+    pub lower_trigram: &'static str,
+    pub description: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub sequence: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub miscellaneous: Option<&'a str>,
+    pub judgment: &'a HexagramJudgment,
+    pub image: &'a HexagramImage,
+    /// Line texts, position 1 (bottom) to 6
+    pub line_texts: Vec<LineRecord<'a>>,
+    pub relations: Relations,
+}
+
+#[derive(Debug, Serialize)]
+pub struct LineRecord<'a> {
+    pub position: u8,
+    pub text: &'a str,
+    pub comments: &'a str,
+}
+
+/// Hexagram numbers related to a hexagram by its figure
+#[derive(Debug, Serialize)]
+pub struct Relations {
+    /// The other hexagram of its King Wen pair
+    pub partner: u8,
+    /// "inverse" or "complement": how the pair relates
+    pub pair_relation: &'static str,
+    /// Formed from lines 2-3-4 and 3-4-5
+    pub nuclear: u8,
+    /// The figure turned upside down
+    pub inverse: u8,
+    /// Every line changed
+    pub complement: u8,
+}
+
+impl<'a> Corpus<'a> {
+    pub fn new(data: &'a IChingData) -> Result<Self> {
+        let trigrams = Trigram::ALL
+            .iter()
+            .map(|&trigram| {
+                let record = data
+                    .get_trigram(trigram)
+                    .with_context(|| format!("Trigram {} not found", trigram.name()))?;
+                Ok(TrigramRecord {
+                    key: trigram.name(),
+                    symbol: trigram.symbol(),
+                    chinese: &record.chinese,
+                    name: &record.symbolic,
+                    image: trigram.image(),
+                    attribute: &record.attribute,
+                    lines: bit_string(&trigram.lines()),
+                    discussion: record.discussion.as_ref(),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let hexagrams = (1..=64)
+            .map(|number| hexagram_record(data, number))
+            .collect::<Result<_>>()?;
+
+        Ok(Self {
+            schema_version: SCHEMA_VERSION,
+            trigrams,
+            hexagrams,
+        })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    pub fn to_toml(&self) -> Result<String> {
+        Ok(toml::to_string(self)?)
+    }
+
+    /// Write the corpus to a new SQLite database at `path`, replacing any file there
+    #[cfg(feature = "sqlite")]
+    pub fn write_sqlite(&self, path: &Path) -> Result<()> {
+        use rusqlite::Connection;
+
+        if path.exists() {
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to replace {}", path.display()))?;
+        }
+        let mut conn = Connection::open(path)
+            .with_context(|| format!("Failed to create {}", path.display()))?;
+        let tx = conn.transaction()?;
+        tx.execute_batch(SQLITE_SCHEMA)?;
+        self.insert_into(&tx)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// Fill the tables of `SQLITE_SCHEMA`, which must be empty
+    #[cfg(feature = "sqlite")]
+    pub(crate) fn insert_into(&self, tx: &rusqlite::Connection) -> Result<()> {
+        use rusqlite::params;
+
+        tx.pragma_update(None, "user_version", self.schema_version)?;
+        for trigram in &self.trigrams {
+            tx.execute(
+                "INSERT INTO trigrams (key, symbol, chinese, name, image, attribute, lines, discussion)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8)",
+                params![
+                    trigram.key,
+                    trigram.symbol.to_string(),
+                    trigram.chinese,
+                    trigram.name,
+                    trigram.image,
+                    trigram.attribute,
+                    trigram.lines,
+                    trigram.discussion.map(serde_json::to_string).transpose()?,
+                ],
+            )?;
+        }
+
+        for hexagram in &self.hexagrams {
+            tx.execute(
+                "INSERT INTO hexagrams (number, symbol, name, chinese, pinyin,
+                     pronunciation_pinyin, pronunciation_ipa, lines, upper_trigram,
+                     lower_trigram, description, sequence, miscellaneous, judgment,
+                     judgment_commentary, image, image_commentary)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9, ?10, ?11, ?12, ?13, ?14, ?15,
+                     ?16, ?17)",
+                params![
+                    hexagram.number,
+                    hexagram.symbol,
+                    hexagram.name,
+                    hexagram.chinese,
+                    hexagram.pinyin,
+                    hexagram.pronunciation.map(|p| &p.pinyin),
+                    hexagram.pronunciation.map(|p| &p.ipa),
+                    hexagram.lines,
+                    hexagram.upper_trigram,
+                    hexagram.lower_trigram,
+                    hexagram.description,
+                    hexagram.sequence,
+                    hexagram.miscellaneous,
+                    hexagram.judgment.text,
+                    hexagram.judgment.commentary,
+                    hexagram.image.text,
+                    hexagram.image.commentary,
+                ],
+            )?;
+            for line in &hexagram.line_texts {
+                tx.execute(
+                    "INSERT INTO lines (hexagram, position, text, comments) VALUES (?1, ?2, ?3, ?4)",
+                    params![hexagram.number, line.position, line.text, line.comments],
+                )?;
+            }
+            let relations = &hexagram.relations;
+            tx.execute(
+                "INSERT INTO relations (hexagram, partner, pair_relation, nuclear, inverse, complement)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6)",
+                params![
+                    hexagram.number,
+                    relations.partner,
+                    relations.pair_relation,
+                    relations.nuclear,
+                    relations.inverse,
+                    relations.complement,
+                ],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Tables written by `write_sqlite`; the schema version is in `PRAGMA user_version`
+#[cfg(feature = "sqlite")]
+pub(crate) const SQLITE_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS trigrams (
+    key TEXT PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    chinese TEXT NOT NULL,
+    name TEXT NOT NULL,
+    image TEXT NOT NULL,
+    attribute TEXT NOT NULL,
+    lines TEXT NOT NULL,
+    discussion TEXT
+);
+CREATE TABLE IF NOT EXISTS hexagrams (
+    number INTEGER PRIMARY KEY,
+    symbol TEXT NOT NULL,
+    name TEXT NOT NULL,
+    chinese TEXT NOT NULL,
+    pinyin TEXT NOT NULL,
+    pronunciation_pinyin TEXT,
+    pronunciation_ipa TEXT,
+    lines TEXT NOT NULL UNIQUE,
+    upper_trigram TEXT NOT NULL REFERENCES trigrams (key),
+    lower_trigram TEXT NOT NULL REFERENCES trigrams (key),
+    description TEXT NOT NULL,
+    sequence TEXT,
+    miscellaneous TEXT,
+    judgment TEXT NOT NULL,
+    judgment_commentary TEXT NOT NULL,
+    image TEXT NOT NULL,
+    image_commentary TEXT NOT NULL
+);
+CREATE TABLE IF NOT EXISTS lines (
+    hexagram INTEGER NOT NULL REFERENCES hexagrams (number),
+    position INTEGER NOT NULL,
+    text TEXT NOT NULL,
+    comments TEXT NOT NULL,
+    PRIMARY KEY (hexagram, position)
+);
+CREATE TABLE IF NOT EXISTS relations (
+    hexagram INTEGER PRIMARY KEY REFERENCES hexagrams (number),
+    partner INTEGER NOT NULL,
+    pair_relation TEXT NOT NULL,
+    nuclear INTEGER NOT NULL,
+    inverse INTEGER NOT NULL,
+    complement INTEGER NOT NULL
+);
+";
+
+/// Without the `sqlite` feature there is no database to write
+#[cfg(not(feature = "sqlite"))]
+impl Corpus<'_> {
+    pub fn write_sqlite(&self, _path: &Path) -> Result<()> {
+        Err(anyhow::anyhow!(
+            "SQLite export needs the `sqlite` feature (cargo install --path . --features sqlite)"
+        ))
+    }
+}
+
+fn hexagram_record(data: &IChingData, number: u8) -> Result<HexagramRecord<'_>> {
+    let hexagram = data
+        .get_hexagram(number)
+        .with_context(|| format!("Hexagram {} not found", number))?;
+    let reading = Reading::from_hexagram_number(number)?;
+    let pair = king_wen::pair(number).context("Hexagram out of range")?;
+
+    let line_texts = (1..=6)
+        .map(|position| {
+            let line = data
+                .get_line_interpretation(number, position)
+                .with_context(|| format!("Hexagram {} line {} not found", number, position))?;
+            Ok(LineRecord {
+                position,
+                text: &line.text,
+                comments: &line.comments,
+            })
+        })
+        .collect::<Result<_>>()?;
+
+    Ok(HexagramRecord {
+        number,
+        symbol: &hexagram.unicode,
+        name: &hexagram.name,
+        chinese: &hexagram.chinese,
+        pinyin: &hexagram.pinyin,
+        pronunciation: hexagram.pronunciation.as_ref(),
+        lines: bit_string(&reading.lines().map(|line| line.polarity)),
+        upper_trigram: reading.upper_trigram().name(),
+        lower_trigram: reading.lower_trigram().name(),
+        description: &hexagram.description,
+        sequence: hexagram.sequence.as_deref(),
+        miscellaneous: hexagram.miscellaneous.as_deref(),
+        judgment: &hexagram.judgment,
+        image: &hexagram.image,
+        line_texts,
+        relations: Relations {
+            partner: pair.partner_of(number),
+            pair_relation: match pair.relation {
+                PairRelation::Inverse => "inverse",
+                PairRelation::Complement => "complement",
+            },
+            nuclear: reading.nuclear_hexagram(),
+            inverse: king_wen::inverse(number).context("Hexagram out of range")?,
+            complement: king_wen::complement(number).context("Hexagram out of range")?,
+        },
+    })
+}
+
+/// Lines as a string of bits, yang 1 and yin 0, in the given order
+fn bit_string(lines: &[Polarity]) -> String {
+    lines
+        .iter()
+        .map(|polarity| match polarity {
+            Polarity::Yang => '1',
+            Polarity::Yin => '0',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_corpus() {
+        let data = IChingData::load().unwrap();
+        let corpus = Corpus::new(&data).unwrap();
+        assert_eq!(corpus.trigrams.len(), 8);
+        assert_eq!(corpus.hexagrams.len(), 64);
+
+        let zhun = &corpus.hexagrams[2];
+        assert_eq!(zhun.lines, "100010");
+        assert_eq!((zhun.upper_trigram, zhun.lower_trigram), ("Kan", "Zhen"));
+        assert_eq!(zhun.line_texts.len(), 6);
+        assert_eq!(zhun.relations.partner, 4);
+        assert_eq!(zhun.relations.complement, 50);
+
+        let json: serde_json::Value = serde_json::from_str(&corpus.to_json().unwrap()).unwrap();
+        assert_eq!(json["schema_version"], SCHEMA_VERSION);
+        assert_eq!(json["hexagrams"][63]["number"], 64);
+        assert!(json["hexagrams"][0]["pronunciation"]["ipa"].is_string());
+        let toml: toml::Table = toml::from_str(&corpus.to_toml().unwrap()).unwrap();
+        assert_eq!(
+            toml["hexagrams"][0]["relations"]["pair_relation"].as_str(),
+            Some("complement")
+        );
+    }
+
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_sqlite() {
+        let data = IChingData::load().unwrap();
+        let path = std::env::temp_dir().join(format!("i-ching-dump-{}.db", std::process::id()));
+        Corpus::new(&data).unwrap().write_sqlite(&path).unwrap();
+
+        let conn = rusqlite::Connection::open(&path).unwrap();
+        let count: u32 = conn
+            .query_row("SELECT count(*) FROM lines", [], |row| row.get(0))
+            .unwrap();
+        assert_eq!(count, 64 * 6);
+        let name: String = conn
+            .query_row(
+                "SELECT pinyin FROM hexagrams WHERE lines = '100010'",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(!name.is_empty());
+        let ipa: Option<String> = conn
+            .query_row(
+                "SELECT pronunciation_ipa FROM hexagrams WHERE number = 3",
+                [],
+                |row| row.get(0),
+            )
+            .unwrap();
+        assert!(ipa.is_some());
+        std::fs::remove_file(path).unwrap();
+    }
+}
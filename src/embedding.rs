@@ -0,0 +1,144 @@
+//! `--format embedding-input`: readings as text to embed for semantic search
+//!
+//! Each reading becomes one JSON line holding a stable `id`, the `text` to
+//! embed (question, hexagram, judgment, and changing lines, flattened to one
+//! line of single-spaced prose), and `metadata` to store beside the vector
+//! and filter on. The layout fits the usual upsert shape of vector databases
+//! (Chroma, Qdrant, pgvector loaders), and `journal export --format
+//! embedding-input` writes one line per saved reading.
+
+use crate::core::data::IChingData;
+use crate::interpretation::framing::Topic;
+use crate::journal::JournalEntry;
+use chrono::{DateTime, SecondsFormat, Utc};
+use serde::Serialize;
+
+/// One reading ready to embed
+#[derive(Debug, Clone, Serialize)]
+pub struct EmbeddingInput {
+    /// Cast time and lines, so re-exporting a journal upserts the same records
+    pub id: String,
+    pub text: String,
+    pub metadata: Metadata,
+}
+
+/// Fields to store and filter on beside the vector
+#[derive(Debug, Clone, Serialize)]
+pub struct Metadata {
+    pub timestamp: DateTime<Utc>,
+    pub question: Option<String>,
+    pub lines: [u8; 6],
+    pub primary_hexagram: u8,
+    pub primary_name: String,
+    pub transformed_hexagram: Option<u8>,
+    pub transformed_name: Option<String>,
+    pub changing_lines: Vec<u8>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub topic: Option<Topic>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+}
+
+impl EmbeddingInput {
+    pub fn new(entry: &JournalEntry, data: &IChingData) -> Self {
+        let reading = &entry.reading;
+        let name = |number: u8| {
+            data.get_hexagram(number)
+                .map(|hexagram| hexagram.name.clone())
+                .unwrap_or_default()
+        };
+        let primary = reading.primary_hexagram();
+        let transformed = reading.transformed_hexagram_number();
+        let lines = reading.traditional_numbers();
+
+        let mut parts = Vec::new();
+        if let Some(question) = reading.question() {
+            parts.push(format!("Question: {}", question));
+        }
+        if let Some(context) = reading.context() {
+            parts.push(format!("Context: {}", context));
+        }
+        parts.push(format!("Hexagram {} {}.", primary, name(primary)));
+        if let Some(hexagram) = data.get_hexagram(primary) {
+            parts.push(format!("Judgment: {}", hexagram.judgment.text));
+        }
+        for position in reading.changing_line_positions() {
+            if let Some(line) = data.get_line_interpretation(primary, position) {
+                parts.push(format!("Line {}: {}", position, line.text));
+            }
+        }
+        if let Some(number) = transformed {
+            parts.push(format!("Changing to {} {}.", number, name(number)));
+        }
+
+        EmbeddingInput {
+            id: format!(
+                "{}/{}",
+                entry.timestamp.to_rfc3339_opts(SecondsFormat::Secs, true),
+                lines.iter().map(u8::to_string).collect::<String>()
+            ),
+            text: normalize(&parts.join(" ")),
+            metadata: Metadata {
+                timestamp: entry.timestamp,
+                question: reading.question().map(str::to_string),
+                lines,
+                primary_hexagram: primary,
+                primary_name: name(primary),
+                transformed_hexagram: transformed,
+                transformed_name: transformed.map(name),
+                changing_lines: reading.changing_line_positions(),
+                topic: entry.topic,
+                recipient: entry.recipient.clone(),
+            },
+        }
+    }
+}
+
+/// `text` on one line: every run of whitespace (line breaks in the
+/// translation included) becomes a single space
+pub fn normalize(text: &str) -> String {
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// One JSON line per entry
+pub fn to_jsonl(entries: &[JournalEntry], data: &IChingData) -> serde_json::Result<String> {
+    let mut result = String::new();
+    for entry in entries {
+        result.push_str(&serde_json::to_string(&EmbeddingInput::new(entry, data))?);
+        result.push('\n');
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Reading;
+
+    #[test]
+    fn test_embedding_input() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "32->34".parse().unwrap();
+        let mut entry =
+            JournalEntry::new(reading.with_question(Some("Should I  stay?\nOr go?".to_string())));
+        entry.timestamp = "2026-03-01T09:30:00Z".parse().unwrap();
+
+        let input = EmbeddingInput::new(&entry, &data);
+        assert_eq!(input.id, "2026-03-01T09:30:00Z/677788");
+        assert!(
+            input.text.starts_with(
+                "Question: Should I stay? Or go? Hexagram 32 Long Lasting. Judgment: "
+            )
+        );
+        assert!(input.text.contains(" Line 1: "));
+        assert!(input.text.ends_with(" Changing to 34 Great Strength."));
+        assert!(!input.text.contains('\n') && !input.text.contains("  "));
+        assert_eq!(input.metadata.changing_lines, vec![1]);
+        assert_eq!(input.metadata.transformed_hexagram, Some(34));
+
+        let jsonl = to_jsonl(&[entry.clone(), entry], &data).unwrap();
+        assert_eq!(jsonl.lines().count(), 2);
+        let value: serde_json::Value = serde_json::from_str(jsonl.lines().next().unwrap()).unwrap();
+        assert_eq!(value["metadata"]["primary_name"], "Long Lasting");
+    }
+}
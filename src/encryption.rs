@@ -0,0 +1,152 @@
+use age::secrecy::{ExposeSecret, SecretString};
+use anyhow::{Context, Result};
+use std::env;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+/// Environment variable naming an age key file that unlocks the journal
+pub const KEY_FILE_ENV: &str = "I_CHING_JOURNAL_KEY";
+
+/// Environment variable holding the journal passphrase, for scripts
+pub const PASSPHRASE_ENV: &str = "I_CHING_JOURNAL_PASSPHRASE";
+
+/// What a locked journal is encrypted with
+#[derive(Clone)]
+pub enum Key {
+    /// A passphrase, stretched with scrypt
+    Passphrase(SecretString),
+    /// An X25519 identity from an age key file
+    Identity(age::x25519::Identity),
+}
+
+impl Key {
+    /// The key file named by `$I_CHING_JOURNAL_KEY`, or else a passphrase
+    pub fn from_env() -> Result<Self> {
+        match env::var_os(KEY_FILE_ENV) {
+            Some(path) => Self::read_key_file(Path::new(&path)),
+            None => Self::passphrase(false),
+        }
+    }
+
+    /// The passphrase in `$I_CHING_JOURNAL_PASSPHRASE`, or one typed at the
+    /// terminal (twice, when `confirm` is set)
+    pub fn passphrase(confirm: bool) -> Result<Self> {
+        if let Ok(passphrase) = env::var(PASSPHRASE_ENV) {
+            return Ok(Key::Passphrase(passphrase.into()));
+        }
+
+        let passphrase = rpassword::prompt_password("Journal passphrase: ")
+            .context("Failed to read the journal passphrase")?;
+        if passphrase.is_empty() {
+            return Err(anyhow::anyhow!("The journal passphrase can't be empty"));
+        }
+        if confirm {
+            let repeated = rpassword::prompt_password("Repeat the passphrase: ")
+                .context("Failed to read the journal passphrase")?;
+            if repeated != passphrase {
+                return Err(anyhow::anyhow!("The passphrases don't match"));
+            }
+        }
+        Ok(Key::Passphrase(passphrase.into()))
+    }
+
+    /// The identity (`AGE-SECRET-KEY-1...`) in an age key file, such as one
+    /// written by `age-keygen`; `#` lines are comments
+    pub fn read_key_file(path: &Path) -> Result<Self> {
+        let content = fs::read_to_string(path)
+            .with_context(|| format!("Failed to read key file {}", path.display()))?;
+        let line = content
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with('#'))
+            .ok_or_else(|| anyhow::anyhow!("No key in {}", path.display()))?;
+        age::x25519::Identity::from_str(line)
+            .map(Key::Identity)
+            .map_err(|e| anyhow::anyhow!("Invalid key in {}: {}", path.display(), e))
+    }
+
+    /// A new identity written to `path` in `age-keygen`'s format, readable
+    /// only by the owner
+    pub fn create_key_file(path: &Path) -> Result<Self> {
+        let identity = age::x25519::Identity::generate();
+        let content = format!(
+            "# i-ching journal key\n# public key: {}\n{}\n",
+            identity.to_public(),
+            identity.to_string().expose_secret()
+        );
+
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut options = fs::OpenOptions::new();
+        options.write(true).create_new(true);
+        #[cfg(unix)]
+        std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+        let mut file = options
+            .open(path)
+            .with_context(|| format!("Failed to create key file {}", path.display()))?;
+        std::io::Write::write_all(&mut file, content.as_bytes())?;
+
+        Ok(Key::Identity(identity))
+    }
+
+    pub fn encrypt(&self, plaintext: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Key::Passphrase(passphrase) => {
+                age::encrypt(&age::scrypt::Recipient::new(passphrase.clone()), plaintext)
+            }
+            Key::Identity(identity) => age::encrypt(&identity.to_public(), plaintext),
+        }
+        .context("Failed to encrypt the journal")
+    }
+
+    pub fn decrypt(&self, ciphertext: &[u8]) -> Result<Vec<u8>> {
+        match self {
+            Key::Passphrase(passphrase) => {
+                age::decrypt(&age::scrypt::Identity::new(passphrase.clone()), ciphertext)
+            }
+            Key::Identity(identity) => age::decrypt(identity, ciphertext),
+        }
+        .map_err(|e| anyhow::anyhow!("Failed to decrypt the journal: {}", e))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_key_file_round_trip() {
+        let path = env::temp_dir().join(format!("i-ching-key-{}.txt", std::process::id()));
+        let _ = fs::remove_file(&path);
+
+        let created = Key::create_key_file(&path).unwrap();
+        assert!(
+            Key::create_key_file(&path).is_err(),
+            "never overwrites a key"
+        );
+        let read = Key::read_key_file(&path).unwrap();
+
+        let ciphertext = created.encrypt(b"question").unwrap();
+        assert!(ciphertext.starts_with(b"age-encryption.org/v1\n"));
+        assert_eq!(read.decrypt(&ciphertext).unwrap(), b"question");
+
+        let other = Key::Identity(age::x25519::Identity::generate());
+        assert!(other.decrypt(&ciphertext).is_err());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_passphrase_round_trip() {
+        let key = Key::Passphrase("correct horse".into());
+        let ciphertext = key.encrypt(b"question").unwrap();
+        assert_eq!(key.decrypt(&ciphertext).unwrap(), b"question");
+        assert!(
+            Key::Passphrase("wrong horse".into())
+                .decrypt(&ciphertext)
+                .is_err()
+        );
+    }
+}
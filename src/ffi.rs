@@ -0,0 +1,127 @@
+//! A C ABI for desktop apps that link the crate directly
+//!
+//! Every call hands back a NUL-terminated UTF-8 JSON string that the caller
+//! owns and returns with [`i_ching_free_string`], or null on failure. The
+//! data set is loaded on the first call and kept for the life of the
+//! process. The C declarations are in `include/i_ching.h`, generated from
+//! this file with cbindgen.
+
+use crate::json::json_reading_with_data;
+use crate::core::Diviner;
+use crate::core::data::IChingData;
+use std::ffi::{CStr, CString, c_char};
+use std::ptr;
+use std::sync::OnceLock;
+
+/// The data set, or `None` if it failed to load
+fn data() -> Option<&'static IChingData> {
+    static DATA: OnceLock<Option<IChingData>> = OnceLock::new();
+    DATA.get_or_init(|| IChingData::load().ok()).as_ref()
+}
+
+/// `json` as a string for the caller to free, or null if it holds a NUL
+fn into_raw(json: String) -> *mut c_char {
+    CString::new(json).map_or(ptr::null_mut(), CString::into_raw)
+}
+
+/// Cast a reading with three coins and return it as JSON, in the schema of
+/// `i-ching --format json`
+///
+/// `question` may be null for a reading without one. Returns null if the
+/// question isn't UTF-8 or the data set can't be loaded.
+///
+/// # Safety
+///
+/// `question` must be null or point to a NUL-terminated string that stays
+/// valid for the call.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn i_ching_cast_reading(question: *const c_char) -> *mut c_char {
+    let question = if question.is_null() {
+        None
+    } else {
+        // SAFETY: the caller promises a valid NUL-terminated string
+        match unsafe { CStr::from_ptr(question) }.to_str() {
+            Ok(question) => Some(question.to_string()),
+            Err(_) => return ptr::null_mut(),
+        }
+    };
+    let Some(data) = data() else {
+        return ptr::null_mut();
+    };
+    let reading = Diviner::new().cast_reading(question);
+    json_reading_with_data(&reading, data)
+        .ok()
+        .and_then(|json| serde_json::to_string(&json).ok())
+        .map_or(ptr::null_mut(), into_raw)
+}
+
+/// Hexagram `number` (1-64) as JSON: its names, texts, trigrams, and line
+/// texts, as the data set holds them
+///
+/// Returns null if the number is out of range or the data set can't be
+/// loaded.
+#[unsafe(no_mangle)]
+pub extern "C" fn i_ching_lookup_hexagram(number: u8) -> *mut c_char {
+    data()
+        .and_then(|data| data.get_hexagram(number))
+        .and_then(|hexagram| serde_json::to_string(hexagram).ok())
+        .map_or(ptr::null_mut(), into_raw)
+}
+
+/// Free a string returned by this library; null is ignored
+///
+/// # Safety
+///
+/// `string` must be null or a pointer this library returned that hasn't
+/// been freed yet.
+#[unsafe(no_mangle)]
+pub unsafe extern "C" fn i_ching_free_string(string: *mut c_char) {
+    if !string.is_null() {
+        // SAFETY: the caller promises the pointer came from `into_raw` and is
+        // freed only once
+        drop(unsafe { CString::from_raw(string) });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// The JSON a returned string holds, freeing it
+    fn take(string: *mut c_char) -> serde_json::Value {
+        assert!(!string.is_null());
+        let json = unsafe { CStr::from_ptr(string) }
+            .to_str()
+            .unwrap()
+            .to_string();
+        unsafe { i_ching_free_string(string) };
+        serde_json::from_str(&json).unwrap()
+    }
+
+    #[test]
+    fn test_ffi() {
+        let question = CString::new("Should I stay?").unwrap();
+        let reading = take(unsafe { i_ching_cast_reading(question.as_ptr()) });
+        assert_eq!(reading["question"], "Should I stay?");
+        assert_eq!(reading["lines"].as_array().unwrap().len(), 6);
+        assert!(take(unsafe { i_ching_cast_reading(ptr::null()) })["question"].is_null());
+
+        let hexagram = take(i_ching_lookup_hexagram(32));
+        assert_eq!(hexagram["name"], "Long Lasting");
+        assert_eq!(hexagram["lines"].as_object().unwrap().len(), 6);
+        assert!(i_ching_lookup_hexagram(65).is_null());
+
+        unsafe { i_ching_free_string(ptr::null_mut()) };
+
+        // The committed header declares every function, so regenerating it
+        // after a change isn't forgotten
+        let header = include_str!("../include/i_ching.h");
+        for function in [
+            "char *i_ching_cast_reading(const char *question);",
+            "char *i_ching_lookup_hexagram(uint8_t number);",
+            "void i_ching_free_string(char *string);",
+        ] {
+            assert!(header.contains(function), "{} is not in i_ching.h", function);
+        }
+    }
+}
@@ -0,0 +1,1313 @@
+//! Rendering readings as text, for library users
+//!
+//! The CLI's formats are all reachable through [`render`], which takes the
+//! hexagram data as an argument so callers can load (or override) it once and
+//! reuse it across readings. Full output can also be had as [`blocks`], one
+//! per section, for clients that show a reading as it arrives.
+
+use crate::banner;
+use crate::core::data::{Hexagram, IChingData, TrigramDiscussion};
+use crate::core::king_wen::{HexagramPair, PairRelation};
+use crate::core::permalink::Permalink;
+use crate::core::reading::Line;
+use crate::core::{Reading, Trigram};
+use crate::embedding;
+use crate::fortune;
+use crate::glyphs::Glyphs;
+use crate::interpretation::framing::Topic;
+use crate::interpretation::guidance;
+use crate::interpretation::horizon::Horizon;
+use crate::interpretation::liuyao::{self, Stem};
+use crate::interpretation::rules::{Governing, Rule};
+use crate::interpretation::structure;
+use crate::interpretation::wuxing;
+use crate::journal::JournalEntry;
+use crate::json::{JsonCrossReference, JsonOptions, JsonReading};
+use crate::lang::Lang;
+use crate::links::Links;
+use crate::notes::{HexagramNotes, Note};
+use crate::rendered::{Part, Rendered};
+use crate::tones;
+use crate::worksheet::{Prompts, format_worksheet};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt::{self, Write as _};
+use std::io::Write;
+use unicode_width::UnicodeWidthStr;
+
+pub use crate::rendered::Block;
+
+/// ANSI bold cyan, for section headings when color is on
+const HEADING_STYLE: &str = "\x1b[1;36m";
+const RESET: &str = "\x1b[0m";
+
+/// How to render a reading
+#[derive(Debug, Clone)]
+pub struct RenderOptions {
+    /// Unicode or ASCII figures and symbols
+    pub glyphs: Glyphs,
+    /// Highlight `=== Heading ===` lines with ANSI escapes
+    pub color: bool,
+    /// Optional sections for full output, guidance for brief and motd, and
+    /// the governing-line rule for brief
+    pub sections: FullOptions,
+    /// Columns the banner format fits its lettering to
+    pub width: usize,
+    /// Layout and key casing for json
+    pub json: JsonOptions,
+}
+
+impl Default for RenderOptions {
+    fn default() -> Self {
+        RenderOptions {
+            glyphs: Glyphs::Unicode,
+            color: false,
+            sections: FullOptions::default(),
+            width: banner::DEFAULT_WIDTH,
+            json: JsonOptions::default(),
+        }
+    }
+}
+
+/// A reading in `format`, as the CLI prints it, ending in a newline
+///
+/// Formats that describe a journal entry (csv, tsv, org) treat the reading as
+/// one cast now.
+pub fn render(
+    reading: &Reading,
+    data: &IChingData,
+    format: Format,
+    options: &RenderOptions,
+) -> Result<String> {
+    let glyphs = options.glyphs;
+    let sections = &options.sections;
+    let mut result = match format {
+        Format::Full => Rendered::new(reading, data, glyphs, sections)?.to_text() + "\n",
+        Format::Brief => {
+            let mut result = format_brief_in(reading, data, glyphs, sections.lang)? + "\n";
+            if let Some((line, text)) = sections
+                .focus_line
+                .filter(|line| reading.changing_line_positions().contains(line))
+                .and_then(|line| {
+                    Some((
+                        line,
+                        data.get_line_interpretation(reading.primary_hexagram(), line)?,
+                    ))
+                })
+            {
+                result.push_str(&format!("Focus (line {}): {}\n", line, text.text));
+            }
+            if reading.changing_line_positions().len() >= 2 {
+                if let Some(governing) = Governing::for_reading(sections.rule, reading) {
+                    result.push_str(&format!(
+                        "Governing text ({}): {}\n",
+                        sections.rule.label(),
+                        governing
+                    ));
+                }
+            }
+            if sections.include_guidance {
+                result.push_str(&format_guidance_with_data(reading, data));
+            }
+            result
+        }
+        Format::Motd => {
+            let mut result = format_motd_with_data(reading, data, glyphs)? + "\n";
+            if sections.include_guidance {
+                result.push_str(&format_guidance_with_data(reading, data));
+            }
+            result
+        }
+        Format::Banner => format_banner_with_data(reading, data, glyphs, options.width)? + "\n",
+        Format::Json => {
+            let rendered = Rendered::new(reading, data, glyphs, sections)?;
+            options.json.to_string(&rendered.json)? + "\n"
+        }
+        Format::Numbers => format!("{:?}\n", reading.traditional_numbers()),
+        Format::Markdown => Rendered::new(reading, data, glyphs, sections)?.to_markdown(),
+        Format::Html => Rendered::new(reading, data, glyphs, sections)?.to_html(),
+        Format::Ssml => Rendered::new(reading, data, glyphs, sections)?.to_ssml(),
+        Format::Tones => tones::to_abc(reading, data),
+        Format::Fortune => fortune::fortune(reading, data),
+        Format::Worksheet => format_worksheet(
+            reading,
+            data,
+            glyphs,
+            &sections.prompts,
+            sections.focus_line,
+        ),
+        Format::Org => format_org_with_data(
+            &JournalEntry::new(reading.clone()).with_focus_line(sections.focus_line),
+            data,
+            glyphs,
+        )?,
+        Format::Csv => format_delimited(&[JournalEntry::new(reading.clone())], ','),
+        Format::Tsv => format_delimited(&[JournalEntry::new(reading.clone())], '\t'),
+        Format::EmbeddingInput => embedding::to_jsonl(&[JournalEntry::new(reading.clone())], data)?,
+    };
+
+    if options.color {
+        result = highlight_headings(&result);
+    }
+    Ok(result)
+}
+
+/// Full output split into blocks, in the order `render` would write them
+///
+/// The reading is gathered up front, so any error comes back here; each
+/// block is laid out only as the iterator reaches it. The last block ends
+/// without the newline `render` adds.
+pub fn blocks(
+    reading: &Reading,
+    data: &IChingData,
+    options: &RenderOptions,
+) -> Result<impl Iterator<Item = Block> + use<>> {
+    let rendered = Rendered::new(reading, data, options.glyphs, &options.sections)?;
+    let color = options.color;
+    Ok(rendered.into_blocks().map(move |mut block| {
+        if color {
+            block.text = highlight_headings(&block.text);
+        }
+        block
+    }))
+}
+
+/// Columns `text` takes up in a terminal: two for each full-width (CJK)
+/// character, one for the rest
+pub fn display_width(text: &str) -> usize {
+    UnicodeWidthStr::width(text)
+}
+
+/// `text` padded with spaces on the right to `width` columns
+pub fn pad(text: &str, width: usize) -> String {
+    let padding = width.saturating_sub(display_width(text));
+    format!("{}{}", text, " ".repeat(padding))
+}
+
+/// Wrap each `=== Heading ===` line in ANSI color
+fn highlight_headings(text: &str) -> String {
+    let mut result = String::with_capacity(text.len() + 64);
+    for line in text.split_inclusive('\n') {
+        let content = line.trim_end_matches('\n');
+        if content.starts_with("=== ") && content.ends_with(" ===") {
+            result.push_str(HEADING_STYLE);
+            result.push_str(content);
+            result.push_str(RESET);
+            result.push_str(&line[content.len()..]);
+        } else {
+            result.push_str(line);
+        }
+    }
+    result
+}
+
+/// Bare-text rendering of full output
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextOnly {
+    /// The Zhouyi core: judgments and line statements only, no Ten Wings or Wilhelm commentary
+    Zhouyi,
+}
+
+/// Optional sections for full output (and guidance for brief and motd)
+#[derive(Debug, Clone, Default)]
+pub struct FullOptions {
+    pub include_guidance: bool,
+    pub include_pair: bool,
+    pub include_wings: bool,
+    pub topic: Option<Topic>,
+    /// The user's notes, shown for the primary and transformed hexagrams
+    pub notes: HexagramNotes,
+    /// Casting date to assign the Liu Yao six spirits from, if shown
+    pub spirits_date: Option<chrono::NaiveDate>,
+    /// Render only the received text instead
+    pub text_only: Option<TextOnly>,
+    /// Language of hexagram names and line positions
+    pub lang: Lang,
+    /// Hyperlinks for hexagram names and lines, when the terminal shows them
+    pub links: Option<Links>,
+    /// Rule for the governing text noted in brief output
+    pub rule: Rule,
+    /// Changing line marked as central, whose text full and brief output give
+    /// first
+    pub focus_line: Option<u8>,
+    /// Show only these parts of full and sectioned output, if given
+    pub only: Option<Vec<Part>>,
+    /// Questions for worksheet output
+    pub prompts: Prompts,
+}
+
+impl FullOptions {
+    /// Whether `part` is among the selected sections (all are, by default)
+    pub fn shows(&self, part: Part) -> bool {
+        self.only.as_ref().is_none_or(|parts| parts.contains(&part))
+    }
+
+    /// `hexagram`'s name in the chosen language, linked if links are on
+    pub(crate) fn hexagram_name(&self, hexagram: &Hexagram) -> String {
+        let name = self.lang.hexagram_name(hexagram);
+        match &self.links {
+            Some(links) => links.hexagram(hexagram, &name),
+            None => name,
+        }
+    }
+
+    /// Heading for line `position` of `hexagram`, linked if links are on
+    pub(crate) fn line_label(&self, hexagram: &Hexagram, position: u8, line: &Line) -> String {
+        let label = self.lang.line_label(position, line);
+        match &self.links {
+            Some(links) => links.line(hexagram, position, &label),
+            None => label,
+        }
+    }
+}
+
+#[derive(ValueEnum, Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Format {
+    Brief,
+    Full,
+    Json,
+    Numbers,
+    Motd,
+    Csv,
+    Tsv,
+    Banner,
+    Org,
+    Markdown,
+    Html,
+    /// Speech Synthesis Markup, for reading aloud
+    Ssml,
+    /// The lines as a tune in ABC notation (see `i-ching play`)
+    Tones,
+    /// The reading with prompts and space for journaling about it
+    Worksheet,
+    /// The judgment as a fortune(6) cookie, with an attribution line
+    Fortune,
+    /// One JSON line of text to embed, with metadata, for vector databases
+    #[serde(rename = "embedding-input")]
+    EmbeddingInput,
+}
+
+pub(crate) fn format_brief_with_data(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+) -> Result<String> {
+    format_brief_in(reading, data, glyphs, Lang::En)
+}
+
+/// Brief output with names and line positions in `lang`
+pub(crate) fn format_brief_in(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+    lang: Lang,
+) -> Result<String> {
+    let mut result = String::new();
+
+    if let Some(question) = reading.question() {
+        writeln!(result, "Q: {}", question)?;
+    }
+    write_brief_summary(&mut result, reading, data, glyphs, lang)?;
+
+    Ok(result)
+}
+
+/// One-line hexagram summary, e.g. "䷟ 32 Long Lasting → ䷡ 34 Great Strength (lines: [1])"
+pub(crate) fn format_brief_summary(reading: &Reading, data: &IChingData, glyphs: Glyphs) -> String {
+    let mut result = String::new();
+    // Writing to a String can't fail
+    let _ = write_brief_summary(&mut result, reading, data, glyphs, Lang::En);
+    result
+}
+
+/// `format_brief_summary` written into `out`
+fn write_brief_summary(
+    out: &mut impl fmt::Write,
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+    lang: Lang,
+) -> fmt::Result {
+    let hexagram_number = reading.primary_hexagram();
+    let Some(hexagram) = data.get_hexagram(hexagram_number) else {
+        return write!(out, "Hexagram {} (Unknown)", hexagram_number);
+    };
+    write!(
+        out,
+        "{}{} {}",
+        glyphs.prefix(&hexagram.unicode),
+        hexagram_number,
+        lang.hexagram_name(hexagram)
+    )?;
+
+    if reading.has_changing_lines() {
+        if let Some(transformed_number) = reading.transformed_hexagram_number() {
+            match data.get_hexagram(transformed_number) {
+                Some(transformed_hex) => write!(
+                    out,
+                    " {} {}{} {}",
+                    glyphs.arrow(),
+                    glyphs.prefix(&transformed_hex.unicode),
+                    transformed_number,
+                    lang.hexagram_name(transformed_hex)
+                )?,
+                None => write!(out, " {} {} Unknown", glyphs.arrow(), transformed_number)?,
+            }
+        }
+        write!(out, " (lines: {})", lang.changing_lines(reading))?;
+    }
+
+    Ok(())
+}
+
+/// "Guidance:" and a bullet per piece of advice in the text, or nothing if
+/// the judgment and changing lines give none
+pub(crate) fn format_guidance_with_data(reading: &Reading, data: &IChingData) -> String {
+    let guidance = guidance::for_reading(reading, data);
+    if guidance.is_empty() {
+        return String::new();
+    }
+
+    let mut result = String::from("\nGuidance:\n");
+    for advice in guidance {
+        result.push_str(&format!("- {}\n", advice));
+    }
+    result
+}
+
+/// One line of Shuo Gua associations, e.g. "father, horse, head, northwest; roundness, the ruler, ..."
+pub(crate) fn format_trigram_discussion(discussion: &TrigramDiscussion) -> String {
+    format!(
+        "{}, {}, {}, {}; {}",
+        discussion.family,
+        discussion.animal,
+        discussion.body,
+        discussion.direction,
+        discussion.images.join(", ")
+    )
+}
+
+const DELIMITED_HEADER: [&str; 7] = [
+    "timestamp",
+    "question",
+    "lines",
+    "primary",
+    "transformed",
+    "changing",
+    "for",
+];
+
+/// Render entries as CSV (`,`) or TSV (`\t`) with a header row, one row per reading
+pub(crate) fn format_delimited(entries: &[JournalEntry], delimiter: char) -> String {
+    let separator = delimiter.to_string();
+    let mut result = DELIMITED_HEADER.join(&separator);
+    result.push('\n');
+
+    for entry in entries {
+        let reading = &entry.reading;
+        let join_numbers = |numbers: &[u8]| {
+            numbers
+                .iter()
+                .map(|n| n.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let fields = [
+            entry
+                .timestamp
+                .to_rfc3339_opts(chrono::SecondsFormat::Secs, true),
+            reading.question().unwrap_or_default().to_string(),
+            join_numbers(&reading.traditional_numbers()),
+            reading.primary_hexagram().to_string(),
+            reading
+                .transformed_hexagram_number()
+                .map(|n| n.to_string())
+                .unwrap_or_default(),
+            join_numbers(&reading.changing_line_positions()),
+            entry.recipient.clone().unwrap_or_default(),
+        ];
+
+        let row: Vec<String> = fields
+            .iter()
+            .map(|field| escape_delimited_field(field, delimiter))
+            .collect();
+        result.push_str(&row.join(&separator));
+        result.push('\n');
+    }
+
+    result
+}
+
+/// Quote CSV fields that need it; TSV has no quoting, so tabs and newlines become spaces
+pub(crate) fn escape_delimited_field(field: &str, delimiter: char) -> String {
+    if delimiter == '\t' {
+        return field.replace(['\t', '\n', '\r'], " ");
+    }
+
+    if field.contains([delimiter, '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+/// Each line with the Liu Yao spirit the casting day's stem assigns it, top line first
+pub(crate) fn write_spirits(
+    out: &mut impl fmt::Write,
+    reading: &Reading,
+    date: chrono::NaiveDate,
+    glyphs: Glyphs,
+) -> fmt::Result {
+    let stem = Stem::of_day(date);
+    out.write_str("\n=== Six Spirits ===\nDay stem: ")?;
+    match glyphs {
+        Glyphs::Unicode => write!(out, "{} {}", stem.pinyin(), stem.chinese())?,
+        Glyphs::Ascii => out.write_str(stem.name())?,
+    }
+    writeln!(out, " ({})", date)?;
+
+    let spirits = liuyao::spirits(date);
+    for (i, line) in reading.lines().iter().enumerate().rev() {
+        let spirit = spirits[i];
+        write!(out, "{}: {:<8} ", i + 1, glyphs.line(line))?;
+        match glyphs {
+            Glyphs::Unicode => writeln!(
+                out,
+                "{} {} ({})",
+                spirit.pinyin(),
+                spirit.chinese(),
+                spirit.name()
+            )?,
+            Glyphs::Ascii => writeln!(out, "{}", spirit.name())?,
+        }
+    }
+
+    Ok(())
+}
+
+/// Table of each line's place: correct or not, central, and whether it
+/// responds to its partner in the other trigram
+pub(crate) fn write_structure(
+    out: &mut impl fmt::Write,
+    reading: &Reading,
+    glyphs: Glyphs,
+) -> fmt::Result {
+    out.write_str("\n=== Structure ===\n")?;
+    let places = structure::analyze(reading);
+
+    for (line, place) in reading.lines().iter().zip(places).rev() {
+        writeln!(
+            out,
+            "{}: {:<8} {:<9}  {:<7}  {} {}",
+            place.position,
+            glyphs.line(line),
+            if place.correct {
+                "correct"
+            } else {
+                "incorrect"
+            },
+            if place.central { "central" } else { "" },
+            if place.corresponds {
+                "responds to"
+            } else {
+                "no response from"
+            },
+            place.partner
+        )?;
+    }
+
+    Ok(())
+}
+
+/// A changing line's "See also" pointers to the lines that quote or mirror
+/// it; nothing if there are none
+pub(crate) fn write_see_also(
+    out: &mut impl fmt::Write,
+    links: &[JsonCrossReference],
+) -> fmt::Result {
+    if links.is_empty() {
+        return Ok(());
+    }
+    let pointers: Vec<String> = links
+        .iter()
+        .map(|link| format!("{} {}, line {}", link.hexagram, link.name, link.line))
+        .collect();
+    writeln!(out, "See also: {}", pointers.join("; "))
+}
+
+pub(crate) fn write_dynamics(out: &mut impl fmt::Write, reading: &Reading) -> fmt::Result {
+    write!(
+        out,
+        "\n=== Elemental Dynamics ===\n{}\n",
+        wuxing::Dynamics::of(reading).describe()
+    )
+}
+
+/// The hexagram figure drawn with the given glyphs, like `Reading::display`
+/// without the question
+pub(crate) fn write_figure(
+    out: &mut impl fmt::Write,
+    reading: &Reading,
+    glyphs: Glyphs,
+    lang: Lang,
+) -> fmt::Result {
+    writeln!(out, "Hexagram {}", reading.primary_hexagram())?;
+
+    // Display lines from top to bottom (reverse array order)
+    for (position, line) in (1..=6).zip(reading.lines()).rev() {
+        writeln!(
+            out,
+            "{}: {}",
+            lang.line_position(position, line),
+            glyphs.line(line)
+        )?;
+    }
+
+    if reading.has_changing_lines() {
+        writeln!(out, "\nChanging lines: {}", lang.changing_lines(reading))?;
+
+        if let Some(transformed) = reading.transformed_hexagram_number() {
+            writeln!(out, "Transforms to hexagram {}", transformed)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Trigram with its symbol and image, e.g. "☵ Kan (Water)"
+pub(crate) fn format_trigram(trigram: Trigram, glyphs: Glyphs) -> impl fmt::Display {
+    TrigramLabel(trigram, glyphs)
+}
+
+struct TrigramLabel(Trigram, Glyphs);
+
+impl fmt::Display for TrigramLabel {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let TrigramLabel(trigram, glyphs) = self;
+        write!(
+            f,
+            "{}{} ({})",
+            glyphs.prefix(trigram.symbol()),
+            trigram,
+            trigram.image()
+        )
+    }
+}
+
+/// The full text of a reading, with the sections `options` asks for
+pub fn format_full_with_data(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+    options: &FullOptions,
+) -> Result<String> {
+    Ok(Rendered::new(reading, data, glyphs, options)?.to_text())
+}
+
+/// The received text of a reading and nothing else: each hexagram's name
+/// and judgment, with the changing line statements between them
+pub(crate) fn format_zhouyi(reading: &Reading, data: &IChingData, glyphs: Glyphs) -> String {
+    let mut result = String::new();
+    if let Some(question) = reading.question() {
+        result.push_str(&format!("Question: {}\n\n", question));
+    }
+
+    let heading = |hexagram: &crate::core::data::Hexagram| {
+        format!(
+            "{}{} {} {}\n",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            hexagram.number,
+            hexagram.chinese,
+            hexagram.name
+        )
+    };
+    // The source splits some verses with stray blank lines
+    let verse = |text: &str| {
+        text.lines()
+            .filter(|line| !line.trim().is_empty())
+            .collect::<Vec<_>>()
+            .join("\n")
+    };
+
+    let number = reading.primary_hexagram();
+    if let Some(hexagram) = data.get_hexagram(number) {
+        result.push_str(&heading(hexagram));
+        result.push_str(&format!("{}\n", verse(&hexagram.judgment.text)));
+    }
+    for position in reading.changing_line_positions() {
+        if let Some(line) = data.get_line_interpretation(number, position) {
+            result.push_str(&format!("\nLine {}:\n{}\n", position, verse(&line.text)));
+        }
+    }
+    if let Some(hexagram) = reading
+        .transformed_hexagram_number()
+        .and_then(|number| data.get_hexagram(number))
+    {
+        result.push_str(&format!("\n{}", heading(hexagram)));
+        result.push_str(&format!("{}\n", verse(&hexagram.judgment.text)));
+    }
+
+    result
+}
+
+/// Heading line for a pair, e.g. "䷂ 3 Beginning / ䷃ 4 Youthful Folly (inverse pair)"
+pub(crate) fn format_pair_heading(
+    pair: &HexagramPair,
+    data: &IChingData,
+    glyphs: Glyphs,
+) -> String {
+    let label = |number: u8| match data.get_hexagram(number) {
+        Some(hexagram) => format!(
+            "{}{} {}",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            number,
+            hexagram.name
+        ),
+        None => format!("{} Unknown", number),
+    };
+
+    format!(
+        "{} / {} ({} pair)",
+        label(pair.first),
+        label(pair.second),
+        pair.relation.label()
+    )
+}
+
+pub(crate) fn describe_pair_relation(pair: &HexagramPair) -> String {
+    match pair.relation {
+        PairRelation::Inverse => format!(
+            "Hexagram {} is hexagram {} turned upside down.",
+            pair.second, pair.first
+        ),
+        PairRelation::Complement => format!(
+            "Hexagram {} reads the same upside down, so its partner {} reverses every line instead.",
+            pair.first, pair.second
+        ),
+    }
+}
+
+/// An Org-mode entry: a headline with a properties drawer (including an
+/// org-roam `:ID:`) and a subheading per section of the reading
+pub(crate) fn format_org_with_data(
+    entry: &JournalEntry,
+    data: &IChingData,
+    glyphs: Glyphs,
+) -> Result<String> {
+    let reading = &entry.reading;
+    let hexagram_number = reading.primary_hexagram();
+    data.get_hexagram(hexagram_number)
+        .ok_or_else(|| anyhow::anyhow!("Hexagram {} not found", hexagram_number))?;
+    let join = |numbers: &[u8], separator: &str| {
+        numbers
+            .iter()
+            .map(|n| n.to_string())
+            .collect::<Vec<_>>()
+            .join(separator)
+    };
+
+    let mut result = format!(
+        "* {}  :iching:\n",
+        format_brief_summary(reading, data, glyphs)
+    );
+    result.push_str(":PROPERTIES:\n");
+    let mut property = |name: &str, value: String| {
+        result.push_str(&format!(":{}: {}\n", name, value));
+    };
+    property("ID", uuid::Uuid::new_v4().to_string());
+    property(
+        "TIMESTAMP",
+        entry
+            .timestamp
+            .with_timezone(&chrono::Local)
+            .format("[%Y-%m-%d %a %H:%M]")
+            .to_string(),
+    );
+    if let Some(recipient) = &entry.recipient {
+        property("FOR", recipient.clone());
+    }
+    if let Some(question) = reading.question() {
+        property("QUESTION", question.to_string());
+    }
+    property("LINES", join(&reading.traditional_numbers(), " "));
+    property("HEXAGRAM", hexagram_number.to_string());
+    if let Some(transformed_number) = reading.transformed_hexagram_number() {
+        property("TRANSFORMED", transformed_number.to_string());
+        property(
+            "CHANGING_LINES",
+            join(&reading.changing_line_positions(), " "),
+        );
+    }
+    if let Some(line) = entry.focus_line {
+        property("FOCUS_LINE", line.to_string());
+    }
+    property("PERMALINK", Permalink::new(reading).to_string());
+    result.push_str(":END:\n");
+
+    let options = FullOptions {
+        focus_line: entry.focus_line,
+        ..FullOptions::default()
+    };
+    result.push_str(&Rendered::new(reading, data, glyphs, &options)?.to_org(2));
+
+    Ok(result)
+}
+
+/// The hexagram figure and name in big block letters, for terminal splash screens
+pub(crate) fn format_banner_with_data(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+    width: usize,
+) -> Result<String> {
+    let hexagram_number = reading.primary_hexagram();
+    let hexagram = data
+        .get_hexagram(hexagram_number)
+        .ok_or_else(|| anyhow::anyhow!("Hexagram {} not found", hexagram_number))?;
+
+    let title = format!("{} {}", hexagram_number, hexagram.name);
+    let mut result = banner::render(reading, &title, glyphs, width);
+
+    if let Some(transformed_number) = reading.transformed_hexagram_number() {
+        let name = data
+            .get_hexagram(transformed_number)
+            .map_or("Unknown", |hexagram| hexagram.name.as_str());
+        result.push_str(&format!(
+            "\n\nChanging into {} {}",
+            transformed_number, name
+        ));
+    }
+
+    Ok(result)
+}
+
+/// A reading compact enough for a login banner
+pub(crate) fn format_motd_with_data(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+) -> Result<String> {
+    let hexagram_number = reading.primary_hexagram();
+
+    // "䷟→䷡ " ahead of the names, dropped entirely in ASCII mode
+    let change_prefix = |from: &str, to: &str| match glyphs {
+        Glyphs::Unicode => format!("{}→{} ", from, to),
+        Glyphs::Ascii => String::new(),
+    };
+
+    if let Some(hexagram) = data.get_hexagram(hexagram_number) {
+        if reading.has_changing_lines() {
+            if let Some(transformed_number) = reading.transformed_hexagram_number() {
+                if let Some(transformed_hex) = data.get_hexagram(transformed_number) {
+                    Ok(format!(
+                        "{}{} {} CHANGING INTO {} {}",
+                        change_prefix(&hexagram.unicode, &transformed_hex.unicode),
+                        hexagram_number,
+                        hexagram.name.to_uppercase(),
+                        transformed_number,
+                        transformed_hex.name.to_uppercase()
+                    ))
+                } else {
+                    Ok(format!(
+                        "{}{} {} CHANGING INTO {} UNKNOWN",
+                        change_prefix(&hexagram.unicode, "䷜"),
+                        hexagram_number,
+                        hexagram.name.to_uppercase(),
+                        transformed_number
+                    ))
+                }
+            } else {
+                // This shouldn't happen if has_changing_lines() is true, but just in case
+                Ok(format!(
+                    "{}{} {}",
+                    glyphs.hexagram_prefix(&hexagram.unicode),
+                    hexagram_number,
+                    hexagram.name.to_uppercase()
+                ))
+            }
+        } else {
+            Ok(format!(
+                "{}{} {}",
+                glyphs.hexagram_prefix(&hexagram.unicode),
+                hexagram_number,
+                hexagram.name.to_uppercase()
+            ))
+        }
+    } else {
+        Ok(format!(
+            "{}{} UNKNOWN",
+            glyphs.hexagram_prefix("䷜"),
+            hexagram_number
+        ))
+    }
+}
+
+/// Print a reading in the chosen format, with what the journal entry adds
+pub(crate) fn write_reading(
+    out: &mut impl Write,
+    entry: &JournalEntry,
+    format: Format,
+    data: &IChingData,
+    options: &RenderOptions,
+) -> Result<()> {
+    let reading = &entry.reading;
+
+    match format {
+        Format::Json => {
+            let json_reading = JsonReading {
+                almanac: entry.almanac,
+                recipient: entry.recipient.clone(),
+                question_hash: entry.question_hash,
+                focus_line: entry.focus_line,
+                ..Rendered::new(reading, data, options.glyphs, &options.sections)?.json
+            };
+            writeln!(out, "{}", options.json.to_string(&json_reading)?)?;
+        }
+        Format::Org => write!(
+            out,
+            "{}",
+            format_org_with_data(entry, data, options.glyphs)?
+        )?,
+        Format::Csv => write!(
+            out,
+            "{}",
+            format_delimited(std::slice::from_ref(entry), ',')
+        )?,
+        Format::Tsv => write!(
+            out,
+            "{}",
+            format_delimited(std::slice::from_ref(entry), '\t')
+        )?,
+        Format::EmbeddingInput => write!(
+            out,
+            "{}",
+            embedding::to_jsonl(std::slice::from_ref(entry), data)?
+        )?,
+        _ => write!(out, "{}", render(reading, data, format.clone(), options)?)?,
+    }
+
+    match format {
+        Format::Brief => {
+            if let Some(almanac) = &entry.almanac {
+                writeln!(out, "Cast under: {}", almanac)?;
+            }
+            if let Some(date) = entry.question_hash {
+                writeln!(out, "{}", format_question_hash(date))?;
+            }
+        }
+        Format::Full if options.sections.text_only.is_none() => {
+            if let Some(almanac) = &entry.almanac {
+                writeln!(out, "Cast under: {}", almanac)?;
+            }
+            if let Some(date) = entry.question_hash {
+                writeln!(out, "{}", format_question_hash(date))?;
+            }
+            if let Some(revisit_on) = entry.revisit_on {
+                writeln!(
+                    out,
+                    "Revisit on: {} (about {}, from the changing lines)",
+                    revisit_on,
+                    Horizon::for_reading(reading)
+                )?;
+            }
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+/// The label on a reading derived with `--method question-hash`
+fn format_question_hash(date: chrono::NaiveDate) -> String {
+    format!(
+        "Not cast: lines derived from a hash of the question and {} (--method question-hash)",
+        date
+    )
+}
+
+/// A hexagram's notes under its heading, numbered for `notes remove`
+pub(crate) fn format_notes(
+    number: u8,
+    notes: &[Note],
+    data: &IChingData,
+    glyphs: Glyphs,
+) -> String {
+    let mut result = match data.get_hexagram(number) {
+        Some(hexagram) => format!(
+            "{}{} {}\n",
+            glyphs.hexagram_prefix(&hexagram.unicode),
+            number,
+            hexagram.name
+        ),
+        None => format!("{}\n", number),
+    };
+
+    for (i, note) in notes.iter().enumerate() {
+        result.push_str(&format!(
+            "  {}. {} ({})\n",
+            i + 1,
+            note.text,
+            note.added.with_timezone(&chrono::Local).format("%Y-%m-%d")
+        ));
+    }
+
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Diviner;
+    use crate::json::json_reading_with_data;
+    use crate::rendered::SectionKind;
+
+    #[test]
+    fn test_render_with_injected_data() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "32->34".parse().unwrap();
+        let options = RenderOptions {
+            glyphs: Glyphs::Ascii,
+            ..RenderOptions::default()
+        };
+
+        let brief = render(&reading, &data, Format::Brief, &options).unwrap();
+        assert_eq!(brief, "32 Long Lasting -> 34 Great Strength (lines: [1])\n");
+        let numbers = render(&reading, &data, Format::Numbers, &options).unwrap();
+        assert_eq!(numbers, "[6, 7, 7, 7, 8, 8]\n");
+
+        let full = render(&reading, &data, Format::Full, &options).unwrap();
+        assert!(full.contains("\n=== Long Lasting ===\n"));
+        assert!(!full.contains('\x1b'));
+        assert!(!full.contains("䷟"));
+    }
+
+    #[test]
+    fn test_display_width() {
+        assert_eq!(display_width("恆 Long Lasting"), 15);
+        assert_eq!(display_width("初六、九四"), 10);
+        assert_eq!(pad("九三", 6), "九三  ");
+        assert_eq!(pad("Line 3", 4), "Line 3");
+    }
+
+    #[test]
+    fn test_blocks_join_to_full_output() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "9,8,6,7,9,8".parse().unwrap();
+        let options = RenderOptions {
+            sections: FullOptions {
+                include_pair: true,
+                include_wings: true,
+                ..FullOptions::default()
+            },
+            ..RenderOptions::default()
+        };
+
+        let blocks: Vec<Block> = blocks(&reading, &data, &options).unwrap().collect();
+        let kinds: Vec<SectionKind> = blocks.iter().map(|block| block.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SectionKind::Figure,
+                SectionKind::Hexagram,
+                SectionKind::Judgment,
+                SectionKind::Image,
+                SectionKind::ChangingLine,
+                SectionKind::ChangingLine,
+                SectionKind::ChangingLine,
+                SectionKind::Transformation,
+                SectionKind::Wings,
+                SectionKind::Pair,
+            ]
+        );
+        assert!(
+            blocks[4]
+                .text
+                .starts_with("\n=== Changing Lines ===\nLine 1: ")
+        );
+        assert!(blocks[5].text.starts_with("Line 3: "));
+
+        let joined: String = blocks.iter().map(|block| block.text.as_str()).collect();
+        let full = render(&reading, &data, Format::Full, &options).unwrap();
+        assert_eq!(joined + "\n", full);
+    }
+
+    #[test]
+    fn test_color_headings() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "32->34".parse().unwrap();
+        let options = RenderOptions {
+            color: true,
+            ..RenderOptions::default()
+        };
+
+        let full = render(&reading, &data, Format::Full, &options).unwrap();
+        assert!(full.contains("\n\x1b[1;36m=== Changing Lines ===\x1b[0m\n"));
+        assert!(!full.contains("\x1b[1;36mJudgment"));
+    }
+
+    #[test]
+    fn test_format_brief() {
+        let diviner = Diviner::new();
+        let reading = diviner
+            .cast_reading_from_numbers([7, 8, 9, 6, 7, 8], Some("Test question".to_string()))
+            .unwrap();
+
+        let brief = format_brief_with_data(&reading, &IChingData::load().unwrap(), Glyphs::Unicode)
+            .unwrap();
+        println!("Brief output: '{}'", brief);
+        assert!(brief.contains("Q: Test question"));
+        // Just check that it has some content - the specific format may vary
+        assert!(!brief.is_empty());
+    }
+
+    #[test]
+    fn test_format_full() {
+        let diviner = Diviner::new();
+        let reading = diviner
+            .cast_reading_from_numbers([7, 8, 7, 8, 7, 8], Some("Test question".to_string()))
+            .unwrap();
+
+        let full = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+            &FullOptions::default(),
+        )
+        .unwrap();
+        assert!(full.contains("Question: Test question"));
+        assert!(full.contains("Traditional numbers"));
+        assert!(full.contains("Upper trigram"));
+        assert!(full.contains("Lower trigram"));
+    }
+
+    #[test]
+    fn test_format_full_with_topic() {
+        let diviner = Diviner::new();
+        let reading = diviner
+            .cast_reading_from_numbers([9, 7, 7, 7, 6, 7], None)
+            .unwrap();
+
+        let framed = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+            &FullOptions {
+                topic: Some(Topic::Leadership),
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(framed.starts_with("=== Framing: Leadership ==="));
+
+        // Leadership puts the changing lines first, ruler line leading
+        let lines_at = framed.find("=== Changing Lines ===").unwrap();
+        assert!(lines_at < framed.find("Judgment:").unwrap());
+        assert!(framed.find("Line 5 (ruler):").unwrap() < framed.find("Line 1:").unwrap());
+    }
+
+    #[test]
+    fn test_format_full_sections() {
+        let reading: Reading = "32->34".parse().unwrap();
+        let reading = reading.with_question(Some("Stay?".to_string()));
+        let only = |parts: &[Part]| FullOptions {
+            only: Some(parts.to_vec()),
+            ..Default::default()
+        };
+
+        let text = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Ascii,
+            &only(&[Part::Judgment, Part::Lines]),
+        )
+        .unwrap();
+        assert!(text.starts_with("Judgment: DURATION."));
+        assert!(text.contains("\n=== Changing Lines ===\nLine 1: "));
+        for left_out in [
+            "Stay?",
+            "Image",
+            "Commentary",
+            "Comments",
+            "Hexagram 32",
+            "Transforms",
+        ] {
+            assert!(!text.contains(left_out), "has {}", left_out);
+        }
+
+        let text = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Ascii,
+            &only(&[Part::Question, Part::Image, Part::Commentary]),
+        )
+        .unwrap();
+        assert!(text.starts_with("Question: Stay?\n\nImage: "));
+        assert!(text.contains("\nImage Commentary: "));
+    }
+
+    #[test]
+    fn test_format_zhouyi() {
+        let reading: Reading = "8,9,8,8,8,8".parse().unwrap();
+        let options = FullOptions {
+            text_only: Some(TextOnly::Zhouyi),
+            ..Default::default()
+        };
+        let text = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+            &options,
+        )
+        .unwrap();
+
+        assert!(text.starts_with("䷆ 7 師 "));
+        assert!(text.contains("Good fortune without blame.\n"));
+        assert!(text.contains("\nLine 2:\nIn the midst of the army."));
+        assert!(text.contains("\n䷁ 2 "));
+        for commentary in ["An army is a mass", "Commentary", "Image", "Description"] {
+            assert!(!text.contains(commentary), "has {}", commentary);
+        }
+    }
+
+    #[test]
+    fn test_org_output() {
+        let reading: Reading = "9,7,7,7,7,7".parse().unwrap();
+        let entry = JournalEntry::new(reading.with_question(Some("Begin?".to_string())));
+        let org =
+            format_org_with_data(&entry, &IChingData::load().unwrap(), Glyphs::Ascii).unwrap();
+
+        assert!(org.starts_with("* 1 "));
+        assert!(org.lines().next().unwrap().ends_with(":iching:"));
+        let drawer = &org[org.find(":PROPERTIES:").unwrap()..org.find(":END:").unwrap()];
+        assert!(drawer.contains(":ID: "));
+        assert!(drawer.contains(":QUESTION: Begin?"));
+        assert!(drawer.contains(":LINES: 9 7 7 7 7 7"));
+        assert!(drawer.contains(":TRANSFORMED: 44"));
+        assert!(drawer.contains(":CHANGING_LINES: 1"));
+        for heading in [
+            "** Question",
+            "** Judgment",
+            "** Image",
+            "*** Line 1",
+            "** Transforms to 44",
+        ] {
+            assert!(org.contains(heading), "missing {}", heading);
+        }
+    }
+
+    #[test]
+    fn test_full_output_with_notes() {
+        let reading: Reading = "1→2".parse().unwrap();
+        let note = |text: &str| Note {
+            added: chrono::Utc::now(),
+            text: text.to_string(),
+        };
+        let notes = HexagramNotes::from([
+            (2, vec![note("Receive before acting")]),
+            (3, vec![note("Not in this reading")]),
+        ]);
+
+        let full = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+            &FullOptions {
+                notes,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let section = &full[full.find("=== Your Notes ===").unwrap()..];
+        assert!(section.contains("䷁ 2 "));
+        assert!(section.contains("1. Receive before acting"));
+        assert!(!section.contains("Not in this reading"));
+
+        let plain = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Unicode,
+            &FullOptions::default(),
+        )
+        .unwrap();
+        assert!(!plain.contains("Your Notes"));
+    }
+
+    #[test]
+    fn test_context_in_output() {
+        let reading: Reading = "9,7,7,7,7,7".parse().unwrap();
+        let reading = reading
+            .with_question(Some("Take the job?".to_string()))
+            .with_context(Some("It means moving to another city.".to_string()));
+
+        let full = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Ascii,
+            &FullOptions::default(),
+        )
+        .unwrap();
+        assert!(full.contains("Question: Take the job?\n\nContext:\nIt means moving"));
+        let json = json_reading_with_data(&reading, &IChingData::load().unwrap()).unwrap();
+        assert_eq!(json.context.as_deref(), reading.context());
+        let org = format_org_with_data(
+            &JournalEntry::new(reading),
+            &IChingData::load().unwrap(),
+            Glyphs::Ascii,
+        )
+        .unwrap();
+        assert!(org.contains("** Context\nIt means moving to another city."));
+    }
+
+    #[test]
+    fn test_ascii_glyphs() {
+        let diviner = Diviner::new();
+        let reading = diviner
+            .cast_reading_from_numbers([7, 8, 9, 6, 7, 8], None)
+            .unwrap();
+
+        let full = format_full_with_data(
+            &reading,
+            &IChingData::load().unwrap(),
+            Glyphs::Ascii,
+            &FullOptions {
+                include_pair: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        assert!(full.contains("------ o"));
+        assert!(full.contains("--  -- x"));
+
+        for output in [
+            format_brief_with_data(&reading, &IChingData::load().unwrap(), Glyphs::Ascii).unwrap(),
+            format_motd_with_data(&reading, &IChingData::load().unwrap(), Glyphs::Ascii).unwrap(),
+        ] {
+            assert!(output.is_ascii(), "not ASCII: {}", output);
+        }
+    }
+
+    #[test]
+    fn test_format_delimited() {
+        let diviner = Diviner::new();
+        let reading = diviner
+            .cast_reading_from_numbers([7, 8, 9, 6, 7, 8], Some("Move, or \"stay\"?".to_string()))
+            .unwrap();
+        let entries = [JournalEntry::new(reading)];
+
+        let csv = format_delimited(&entries, ',');
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(
+            rows[0],
+            "timestamp,question,lines,primary,transformed,changing,for"
+        );
+        assert!(rows[1].contains(",\"Move, or \"\"stay\"\"?\",7 8 9 6 7 8,"));
+        assert!(rows[1].ends_with(",3 4,"));
+
+        let tsv = format_delimited(&entries, '\t');
+        assert_eq!(tsv.lines().nth(1).unwrap().split('\t').count(), 7);
+    }
+}
@@ -0,0 +1,155 @@
+//! Output for fortune(6): `--format fortune`, fortune-style batches, and
+//! `i-ching fortune-db`
+//!
+//! A fortune file holds short texts, each ended by a line holding only `%`.
+//! `strfile` indexes one into a `.dat` file so `fortune` can pick a text at
+//! random; `fortune-db` writes that index itself.
+
+use crate::core::Reading;
+use crate::core::data::{Hexagram, IChingData};
+use clap::ValueEnum;
+
+/// Line that ends each text in a fortune file
+pub const SEPARATOR: &str = "%\n";
+
+/// The strfile version the index is written in
+const STRFILE_VERSION: u32 = 2;
+
+/// Which texts `fortune-db` makes fortunes of
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Text {
+    Judgment,
+    Image,
+    /// Each of the six line statements
+    Lines,
+}
+
+/// The reading's judgment, attributed to its hexagram (and the one it
+/// becomes)
+pub fn fortune(reading: &Reading, data: &IChingData) -> String {
+    let number = reading.primary_hexagram();
+    let Some(hexagram) = data.get_hexagram(number) else {
+        return format!("\t\t-- I Ching, {}\n", number);
+    };
+    let mut source = name(hexagram);
+    if let Some(transformed) = reading
+        .transformed_hexagram_number()
+        .and_then(|number| data.get_hexagram(number))
+    {
+        source.push_str(&format!(", changing to {}", name(transformed)));
+    }
+    cookie(&hexagram.judgment.text, &source)
+}
+
+/// A fortune file of the chosen texts of every hexagram, in King Wen order,
+/// each attributed to its hexagram unless `attribution` is off
+pub fn database(data: &IChingData, texts: &[Text], attribution: bool) -> String {
+    let cookie = |text: &str, source: &str| {
+        if attribution {
+            cookie(text, source)
+        } else {
+            format!("{}\n", text.trim_end())
+        }
+    };
+    let mut file = String::new();
+    for hexagram in data.hexagrams() {
+        for text in texts {
+            match text {
+                Text::Judgment => {
+                    file.push_str(&cookie(&hexagram.judgment.text, &name(hexagram)));
+                    file.push_str(SEPARATOR);
+                }
+                Text::Image => {
+                    let source = format!("{}, the Image", name(hexagram));
+                    file.push_str(&cookie(&hexagram.image.text, &source));
+                    file.push_str(SEPARATOR);
+                }
+                Text::Lines => {
+                    for position in 1..=6 {
+                        if let Some(line) = hexagram.lines.get(&position.to_string()) {
+                            let source = format!("{}, line {}", name(hexagram), position);
+                            file.push_str(&cookie(&line.text, &source));
+                            file.push_str(SEPARATOR);
+                        }
+                    }
+                }
+            }
+        }
+    }
+    file
+}
+
+/// The index `strfile` would make of a fortune file: a header of version,
+/// count, longest and shortest text, flags, and delimiter, then where each
+/// text starts and where the file ends, all big-endian
+pub fn strfile(file: &str) -> Vec<u8> {
+    let mut offsets = vec![0u32];
+    let mut lengths = Vec::new();
+    let mut position = 0;
+    for line in file.split_inclusive('\n') {
+        position += line.len() as u32;
+        if line == SEPARATOR {
+            let start = offsets.last().copied().unwrap_or_default();
+            lengths.push(position - SEPARATOR.len() as u32 - start);
+            offsets.push(position);
+        }
+    }
+
+    let mut index = Vec::new();
+    for field in [
+        STRFILE_VERSION,
+        lengths.len() as u32,
+        lengths.iter().copied().max().unwrap_or_default(),
+        lengths.iter().copied().min().unwrap_or_default(),
+        0,
+    ] {
+        index.extend_from_slice(&field.to_be_bytes());
+    }
+    index.extend_from_slice(&[b'%', 0, 0, 0]);
+    for offset in offsets {
+        index.extend_from_slice(&offset.to_be_bytes());
+    }
+    index
+}
+
+/// e.g. "32 Long Lasting"
+fn name(hexagram: &Hexagram) -> String {
+    format!("{} {}", hexagram.number, hexagram.name)
+}
+
+/// A text with its attribution under it in the fortune manner
+fn cookie(text: &str, source: &str) -> String {
+    format!("{}\n\t\t-- I Ching, {}\n", text.trim_end(), source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fortune() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "32->34".parse().unwrap();
+        let text = fortune(&reading, &data);
+        assert!(text.starts_with("DURATION. Success."));
+        assert!(
+            text.ends_with("\n\t\t-- I Ching, 32 Long Lasting, changing to 34 Great Strength\n")
+        );
+        assert!(!text.contains("\n%\n"));
+
+        let file = database(&data, &[Text::Judgment, Text::Lines], true);
+        assert_eq!(file.matches("\n%\n").count(), 64 * 7);
+        assert!(file.contains("\t\t-- I Ching, 32 Long Lasting, line 1\n%\n"));
+        assert!(!database(&data, &[Text::Image], false).contains("-- I Ching"));
+
+        let index = strfile("one\n%\ntwo two\n%\n");
+        let fields: Vec<u32> = index
+            .chunks(4)
+            .map(|bytes| u32::from_be_bytes(bytes.try_into().unwrap()))
+            .collect();
+        // Version, two texts of 8 and 4 bytes, no flags, then the offsets
+        assert_eq!(fields[..5], [2, 2, 8, 4, 0]);
+        assert_eq!(index[20], b'%');
+        assert_eq!(fields[6..], [0, 6, 16]);
+    }
+}
@@ -0,0 +1,133 @@
+//! Definitions of the terms readings use, for `i-ching explain`
+//!
+//! The entries come from `data/glossary.json`. Terms are looked up by name
+//! or alias, ignoring case, hyphens, and a trailing plural `s`.
+
+use crate::search::edit_distance;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+const GLOSSARY: &str = include_str!("../data/glossary.json");
+
+/// Most suggestions offered for a term not in the glossary
+const MAX_SUGGESTIONS: usize = 3;
+
+/// One defined term
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Entry {
+    pub term: String,
+    /// Other names the term goes by
+    pub aliases: Vec<String>,
+    pub definition: String,
+    /// Related terms, each defined in the glossary
+    pub see_also: Vec<String>,
+}
+
+impl Entry {
+    fn names(&self) -> impl Iterator<Item = &str> {
+        std::iter::once(self.term.as_str()).chain(self.aliases.iter().map(String::as_str))
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Glossary {
+    entries: Vec<Entry>,
+}
+
+impl Glossary {
+    /// The glossary embedded in the binary
+    pub fn load() -> Result<Self> {
+        let entries = serde_json::from_str(GLOSSARY).context("Invalid data/glossary.json")?;
+        Ok(Glossary { entries })
+    }
+
+    pub fn entries(&self) -> &[Entry] {
+        &self.entries
+    }
+
+    /// The entry `term` names, as itself or an alias
+    pub fn lookup(&self, term: &str) -> Option<&Entry> {
+        let key = key(term);
+        let find = |key: &str| {
+            self.entries
+                .iter()
+                .find(|entry| entry.names().any(|name| self::key(name) == key))
+        };
+        find(&key).or_else(|| key.strip_suffix('s').and_then(find))
+    }
+
+    /// Terms close to `term`, for when it isn't in the glossary: names (or
+    /// words of names) it misspells, or names it is part of, nearest first
+    pub fn suggest(&self, term: &str) -> Vec<&str> {
+        let key = key(term);
+        let mut close: Vec<(usize, &str)> = self
+            .entries
+            .iter()
+            .filter_map(|entry| {
+                let distance = entry
+                    .names()
+                    .map(|name| {
+                        let name = self::key(name);
+                        if key.len() >= 3 && name.contains(&key) {
+                            return 0;
+                        }
+                        name.split(' ')
+                            .map(|word| edit_distance(&key, word))
+                            .fold(edit_distance(&key, &name), usize::min)
+                    })
+                    .min()?;
+                (distance <= 2).then_some((distance, entry.term.as_str()))
+            })
+            .collect();
+        close.sort_by_key(|&(distance, _)| distance);
+        close
+            .into_iter()
+            .take(MAX_SUGGESTIONS)
+            .map(|(_, term)| term)
+            .collect()
+    }
+}
+
+/// `text` lowercased, with hyphens and runs of spaces as single spaces
+fn key(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| c.is_whitespace() || c == '-' || c == '_')
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glossary() {
+        let glossary = Glossary::load().unwrap();
+        assert_eq!(
+            glossary.lookup("Moving-Lines").unwrap().term,
+            "changing line"
+        );
+        assert_eq!(
+            glossary.lookup("mutual hexagram").unwrap().term,
+            "nuclear hexagram"
+        );
+        assert_eq!(glossary.lookup("☵").unwrap().term, "Kan");
+        assert_eq!(glossary.lookup("the creative").unwrap().term, "Qian");
+        assert!(glossary.lookup("oracle bone").is_none());
+        assert_eq!(glossary.suggest("nuclaer"), vec!["nuclear hexagram"]);
+        assert!(glossary.suggest("zzzzzz").is_empty());
+
+        // Every cross-reference leads somewhere, and no name is used twice
+        let mut names = std::collections::HashSet::new();
+        for entry in glossary.entries() {
+            for see in &entry.see_also {
+                assert!(glossary.lookup(see).is_some(), "{}: {}", entry.term, see);
+            }
+            for name in entry.names() {
+                assert!(names.insert(key(name)), "{} is defined twice", name);
+            }
+        }
+    }
+}
@@ -0,0 +1,151 @@
+use crate::core::reading::{Age, Line, Polarity};
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fmt;
+
+/// How hexagram figures and symbols are drawn in terminal output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Glyphs {
+    /// Yijing hexagram characters (䷀-䷿), box-drawing lines, and arrows
+    Unicode,
+    /// Plain ASCII line art for terminals and fonts without those blocks
+    Ascii,
+}
+
+impl Glyphs {
+    /// Guess whether the terminal can render the Yijing Unicode block
+    pub fn detect() -> Self {
+        Self::detect_from(|name| env::var(name).ok())
+    }
+
+    /// Detection heuristics over an environment lookup (split out for testing)
+    ///
+    /// - A locale that is set but not UTF-8 can't carry the characters at all
+    /// - The Linux VT console, `dumb`, and old VT terminals have tiny fonts
+    /// - The legacy Windows console lacks the block; Windows Terminal has it
+    fn detect_from(var: impl Fn(&str) -> Option<String>) -> Self {
+        let locale = ["LC_ALL", "LC_CTYPE", "LANG"]
+            .iter()
+            .find_map(|name| var(name).filter(|value| !value.is_empty()));
+        if let Some(locale) = locale {
+            let locale = locale.to_lowercase();
+            if !locale.contains("utf-8") && !locale.contains("utf8") {
+                return Glyphs::Ascii;
+            }
+        }
+
+        if let Some(term) = var("TERM") {
+            if ["linux", "dumb", "vt100", "vt102", "vt220", "cons25"].contains(&term.as_str()) {
+                return Glyphs::Ascii;
+            }
+        }
+
+        if cfg!(windows) && var("WT_SESSION").is_none() && var("TERM_PROGRAM").is_none() {
+            return Glyphs::Ascii;
+        }
+
+        Glyphs::Unicode
+    }
+
+    /// A hexagram character followed by a space, or nothing in ASCII mode
+    pub fn hexagram_prefix(&self, unicode: &str) -> String {
+        self.prefix(unicode).to_string()
+    }
+
+    /// `hexagram_prefix` as a value to format in place, without allocating
+    pub fn prefix<T: fmt::Display>(&self, symbol: T) -> Prefix<T> {
+        Prefix {
+            glyphs: *self,
+            symbol,
+        }
+    }
+
+    pub fn arrow(&self) -> &'static str {
+        match self {
+            Glyphs::Unicode => "→",
+            Glyphs::Ascii => "->",
+        }
+    }
+
+    /// Draw a single line of a hexagram figure
+    pub fn line(&self, line: &Line) -> &'static str {
+        match self {
+            Glyphs::Unicode => line.to_symbol(),
+            Glyphs::Ascii => match (line.age, line.polarity) {
+                (Age::Young, Polarity::Yang) => "------",
+                (Age::Young, Polarity::Yin) => "--  --",
+                (Age::Old, Polarity::Yang) => "------ o",
+                (Age::Old, Polarity::Yin) => "--  -- x",
+            },
+        }
+    }
+}
+
+/// A symbol followed by a space, or nothing in ASCII mode; see `Glyphs::prefix`
+pub struct Prefix<T> {
+    glyphs: Glyphs,
+    symbol: T,
+}
+
+impl<T: fmt::Display> fmt::Display for Prefix<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.glyphs {
+            Glyphs::Unicode => write!(f, "{} ", self.symbol),
+            Glyphs::Ascii => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn detect(vars: &[(&str, &str)]) -> Glyphs {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        Glyphs::detect_from(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_detect_locale() {
+        assert_eq!(
+            detect(&[("LANG", "en_US.UTF-8"), ("TERM", "xterm-256color")]),
+            if cfg!(windows) {
+                Glyphs::Ascii
+            } else {
+                Glyphs::Unicode
+            }
+        );
+        assert_eq!(detect(&[("LANG", "C"), ("TERM", "xterm")]), Glyphs::Ascii);
+        // LC_ALL takes precedence over LANG
+        assert_eq!(
+            detect(&[("LC_ALL", "POSIX"), ("LANG", "en_US.UTF-8")]),
+            Glyphs::Ascii
+        );
+    }
+
+    #[test]
+    fn test_detect_terminal() {
+        assert_eq!(
+            detect(&[("LANG", "en_US.UTF-8"), ("TERM", "linux")]),
+            Glyphs::Ascii
+        );
+        assert_eq!(detect(&[("TERM", "dumb")]), Glyphs::Ascii);
+    }
+
+    #[test]
+    fn test_ascii_lines() {
+        let old_yin = Line::new(Age::Old, Polarity::Yin);
+        assert_eq!(Glyphs::Ascii.line(&old_yin), "--  -- x");
+        assert_eq!(Glyphs::Unicode.line(&old_yin), old_yin.to_symbol());
+        assert!(Glyphs::Ascii.line(&old_yin).is_ascii());
+    }
+
+    #[test]
+    fn test_prefix() {
+        assert_eq!(format!("{}1", Glyphs::Unicode.prefix('䷀')), "䷀ 1");
+        assert_eq!(format!("{}1", Glyphs::Ascii.prefix('䷀')), "1");
+        assert_eq!(Glyphs::Unicode.hexagram_prefix("䷀"), "䷀ ");
+    }
+}
@@ -0,0 +1,170 @@
+//! Group sessions: several participants' questions cast in turn, for
+//! workshops, and written up as one document
+
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::format::FullOptions;
+use crate::glyphs::Glyphs;
+use crate::rendered::Rendered;
+use anyhow::Result;
+use chrono::NaiveDate;
+use std::io::{self, BufRead, Write};
+
+/// Someone asking a question in the session
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Participant {
+    pub name: String,
+    pub question: String,
+}
+
+/// A finished session: each participant's name and reading, which carries
+/// their question, in casting order
+#[derive(Debug, Clone)]
+pub struct Session {
+    pub title: String,
+    pub date: NaiveDate,
+    pub readings: Vec<(String, Reading)>,
+}
+
+/// Participants from a questions file, one `Name: question` per line
+///
+/// A line without a name is numbered, e.g. "Participant 3". Blank lines and
+/// lines starting with `#` are skipped.
+pub fn parse(text: &str) -> Result<Vec<Participant>> {
+    let participants: Vec<Participant> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .enumerate()
+        .map(|(i, line)| match line.split_once(": ") {
+            Some((name, question)) => Participant {
+                name: name.trim().to_string(),
+                question: question.trim().to_string(),
+            },
+            None => Participant {
+                name: format!("Participant {}", i + 1),
+                question: line.to_string(),
+            },
+        })
+        .collect();
+    if participants.is_empty() {
+        return Err(anyhow::anyhow!("The questions file has no questions"));
+    }
+    Ok(participants)
+}
+
+/// Ask for each participant's name and question until a blank name
+pub fn ask(input: &mut impl BufRead, out: &mut impl Write) -> io::Result<Vec<Participant>> {
+    writeln!(
+        out,
+        "Enter each participant's name and question; leave the name blank when everyone's in.\n"
+    )?;
+    let mut participants = Vec::new();
+    loop {
+        write!(out, "Name {}: ", participants.len() + 1)?;
+        let name = read_line(input, out)?;
+        if name.is_empty() {
+            break;
+        }
+        let question = loop {
+            write!(out, "Question: ")?;
+            let question = read_line(input, out)?;
+            if !question.is_empty() {
+                break question;
+            }
+        };
+        participants.push(Participant { name, question });
+    }
+    Ok(participants)
+}
+
+/// One trimmed line; end of input counts as a blank line
+fn read_line(input: &mut impl BufRead, out: &mut impl Write) -> io::Result<String> {
+    out.flush()?;
+    let mut line = String::new();
+    input.read_line(&mut line)?;
+    Ok(line.trim().to_string())
+}
+
+impl Session {
+    /// Markdown with a contents list and a section per participant, each
+    /// holding the full reading
+    pub fn to_markdown(&self, data: &IChingData) -> Result<String> {
+        let mut result = format!(
+            "# {}\n\n{}, {} participants\n\n## Contents\n\n",
+            self.title,
+            self.date.format("%Y-%m-%d"),
+            self.readings.len()
+        );
+        let rendered: Vec<Rendered> = self
+            .readings
+            .iter()
+            .map(|(_, reading)| {
+                Rendered::new(reading, data, Glyphs::Unicode, &FullOptions::default())
+            })
+            .collect::<Result<_>>()?;
+        for (i, ((name, _), rendered)) in self.readings.iter().zip(&rendered).enumerate() {
+            result.push_str(&format!("{}. {}: {}\n", i + 1, name, rendered.title));
+        }
+        for (i, ((name, _), rendered)) in self.readings.iter().zip(&rendered).enumerate() {
+            result.push_str(&format!("\n## {}. {}\n\n", i + 1, name));
+            // Each reading's headings sit two levels under the participant's
+            for line in rendered.to_markdown().lines() {
+                if line.starts_with('#') {
+                    result.push_str("##");
+                }
+                result.push_str(line);
+                result.push('\n');
+            }
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_group_session() {
+        let participants =
+            parse("# Tuesday workshop\nAna: Should I move?\n\nWhat does the year hold?\n").unwrap();
+        assert_eq!(participants[0].name, "Ana");
+        assert_eq!(participants[0].question, "Should I move?");
+        assert_eq!(participants[1].name, "Participant 2");
+        assert!(parse("# nothing\n").is_err());
+
+        let asked = ask(
+            &mut "Ana\nShould I move?\nBo\n\nWhen?\n\n".as_bytes(),
+            &mut Vec::new(),
+        )
+        .unwrap();
+        assert_eq!(asked.len(), 2);
+        assert_eq!(asked[1].question, "When?");
+
+        let data = IChingData::load().unwrap();
+        let session = Session {
+            title: "Workshop".to_string(),
+            date: NaiveDate::from_ymd_opt(2026, 3, 1).unwrap(),
+            readings: participants
+                .iter()
+                .zip(["32->34", "1"])
+                .map(|(participant, input)| {
+                    let reading: Reading = input.parse().unwrap();
+                    (
+                        participant.name.clone(),
+                        reading.with_question(Some(participant.question.clone())),
+                    )
+                })
+                .collect(),
+        };
+        let markdown = session.to_markdown(&data).unwrap();
+        assert!(markdown.starts_with("# Workshop\n\n2026-03-01, 2 participants\n"));
+        assert!(markdown.contains("\n## 1. Ana\n\n### "));
+        assert!(markdown.contains("\n#### Question\n\nShould I move?\n"));
+        assert!(markdown.contains("\n## 2. Participant 2\n"));
+        assert!(markdown.contains("\n1. Ana: ䷟ 32 Long Lasting → ䷡ 34 "));
+        assert!(markdown.contains("\n#### Judgment\n"));
+        assert_eq!(markdown.matches("\n# ").count(), 0);
+    }
+}
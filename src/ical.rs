@@ -0,0 +1,161 @@
+use crate::format::format_brief_summary;
+use crate::core::data::IChingData;
+use crate::glyphs::Glyphs;
+use crate::journal::JournalEntry;
+use chrono::{DateTime, Days, NaiveDate, Utc};
+
+/// Longest content line iCalendar allows before folding, in octets
+const LINE_LIMIT: usize = 75;
+
+/// How long the consultation event lasts
+const EVENT_DURATION: &str = "PT15M";
+
+/// When the revisit reminder goes off: 9:00 on the day
+const REMINDER_TRIGGER: &str = "PT9H";
+
+/// An iCalendar file with an event at the reading's cast time, and, if
+/// `remind` is set and the reading has a revisit date, an all-day event on
+/// that date with a reminder
+///
+/// The summary names the hexagrams; the description holds the question, the
+/// judgment, and the changing lines.
+pub fn reading_calendar(entry: &JournalEntry, data: &IChingData, remind: bool) -> String {
+    let reading = &entry.reading;
+    let summary = format_brief_summary(reading, data, Glyphs::Ascii);
+    let uid = uuid::Uuid::new_v4();
+    let stamp = format_utc(Utc::now());
+
+    let mut description = Vec::new();
+    if let Some(question) = reading.question() {
+        description.push(format!("Q: {}", question));
+    }
+    description.push(summary.clone());
+    if let Some(hexagram) = data.get_hexagram(reading.primary_hexagram()) {
+        description.push(format!("Judgment: {}", hexagram.judgment.text));
+        for position in reading.changing_line_positions() {
+            if let Some(line) = data.get_line_interpretation(hexagram.number, position) {
+                description.push(format!("Line {}: {}", position, line.text));
+            }
+        }
+    }
+
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_string(),
+        "VERSION:2.0".to_string(),
+        format!(
+            "PRODID:-//i-ching//i-ching {}//EN",
+            env!("CARGO_PKG_VERSION")
+        ),
+        "CALSCALE:GREGORIAN".to_string(),
+        "BEGIN:VEVENT".to_string(),
+        format!("UID:{}@i-ching", uid),
+        format!("DTSTAMP:{}", stamp),
+        format!("DTSTART:{}", format_utc(entry.timestamp)),
+        format!("DURATION:{}", EVENT_DURATION),
+        format!("SUMMARY:{}", escape(&format!("I Ching: {}", summary))),
+        format!("DESCRIPTION:{}", escape(&description.join("\n\n"))),
+        "END:VEVENT".to_string(),
+    ];
+
+    if let Some(revisit_on) = entry.revisit_on.filter(|_| remind) {
+        let text = format!("Revisit I Ching reading: {}", summary);
+        lines.extend([
+            "BEGIN:VEVENT".to_string(),
+            format!("UID:{}-revisit@i-ching", uid),
+            format!("DTSTAMP:{}", stamp),
+            format!("DTSTART;VALUE=DATE:{}", format_date(revisit_on)),
+            format!(
+                "DTEND;VALUE=DATE:{}",
+                format_date(revisit_on + Days::new(1))
+            ),
+            format!("SUMMARY:{}", escape(&text)),
+            "TRANSP:TRANSPARENT".to_string(),
+            "BEGIN:VALARM".to_string(),
+            "ACTION:DISPLAY".to_string(),
+            format!("TRIGGER:{}", REMINDER_TRIGGER),
+            format!("DESCRIPTION:{}", escape(&text)),
+            "END:VALARM".to_string(),
+            "END:VEVENT".to_string(),
+        ]);
+    }
+    lines.push("END:VCALENDAR".to_string());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+fn format_utc(timestamp: DateTime<Utc>) -> String {
+    timestamp.format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+fn format_date(date: NaiveDate) -> String {
+    date.format("%Y%m%d").to_string()
+}
+
+/// Text with backslashes, semicolons, commas, and newlines escaped
+fn escape(text: &str) -> String {
+    text.replace('\\', "\\\\")
+        .replace(';', "\\;")
+        .replace(',', "\\,")
+        .replace('\n', "\\n")
+}
+
+/// A content line split into lines of at most 75 octets, each continuation
+/// starting with a space, without breaking a UTF-8 character
+fn fold(line: &str) -> String {
+    let mut result = String::with_capacity(line.len() + line.len() / LINE_LIMIT * 3);
+    let mut length = 0;
+    for c in line.chars() {
+        if length + c.len_utf8() > LINE_LIMIT {
+            result.push_str("\r\n ");
+            length = 1;
+        }
+        result.push(c);
+        length += c.len_utf8();
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reading_calendar() {
+        let data = IChingData::load().unwrap();
+        let reading: crate::core::Reading = "32->34".parse().unwrap();
+        let reading = reading.with_question(Some("Stay, or go?".to_string()));
+        let timestamp = Utc.with_ymd_and_hms(2026, 3, 1, 9, 30, 0).unwrap();
+        let entry = JournalEntry::cast_at(reading, timestamp);
+
+        let calendar = reading_calendar(&entry, &data, false);
+        assert!(calendar.starts_with("BEGIN:VCALENDAR\r\nVERSION:2.0\r\n"));
+        assert!(calendar.ends_with("END:VEVENT\r\nEND:VCALENDAR\r\n"));
+        assert!(calendar.contains("\r\nDTSTART:20260301T093000Z\r\n"));
+        assert!(calendar.contains(
+            "\r\nSUMMARY:I Ching: 32 Long Lasting -> 34 Great Strength (lines: [1])\r\n"
+        ));
+        assert!(calendar.contains("DESCRIPTION:Q: Stay\\, or go?\\n\\n32 Long Lasting"));
+        assert!(calendar.lines().all(|line| line.len() <= LINE_LIMIT));
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 1);
+
+        let calendar = reading_calendar(&entry, &data, true);
+        assert_eq!(calendar.matches("BEGIN:VEVENT").count(), 2);
+        let revisit_on = format_date(entry.revisit_on.unwrap());
+        assert!(calendar.contains(&format!("\r\nDTSTART;VALUE=DATE:{}\r\n", revisit_on)));
+        assert!(calendar.contains("\r\nBEGIN:VALARM\r\n"));
+    }
+
+    #[test]
+    fn test_fold() {
+        let long = "a".repeat(80);
+        assert_eq!(
+            fold(&long),
+            format!("{}\r\n {}", "a".repeat(75), "a".repeat(5))
+        );
+        let chinese = "恆".repeat(30);
+        let folded = fold(&chinese);
+        assert!(folded.split("\r\n").all(|line| line.len() <= LINE_LIMIT));
+        assert_eq!(folded.replace("\r\n ", ""), chinese);
+    }
+}
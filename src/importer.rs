@@ -0,0 +1,380 @@
+//! Reading journals kept by other I Ching software, for `i-ching journal import`
+//!
+//! Each format has an [`Importer`]; [`ImportFormat::importer`] picks one,
+//! guessing from the content when asked to. The text and CSV importers know
+//! fields by the names other apps tend to give them (`lines`, `hexagram`,
+//! `changing`, `question`, `date`, ...), so one table of names serves both.
+
+use crate::core::king_wen;
+use crate::core::parse;
+use crate::core::reading::{Line, Reading};
+use crate::journal::JournalEntry;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use clap::ValueEnum;
+
+/// Date and time layouts tried, after RFC 3339, for times without a zone
+const LOCAL_TIME_FORMATS: [&str; 4] = [
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M",
+];
+
+/// A format readings can be imported from
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum ImportFormat {
+    /// Guess from the content
+    Auto,
+    /// `key=value` (or `key: value`) lines, with a blank line between
+    /// readings, e.g. `hexagram.lines=799688`
+    Text,
+    /// CSV, TSV, or semicolon-separated values with a header row
+    Csv,
+    /// This journal's own JSON export
+    Json,
+}
+
+impl ImportFormat {
+    /// The importer for this format, or for the format `content` looks like
+    pub fn importer(&self, content: &str) -> Box<dyn Importer> {
+        let format = match self {
+            ImportFormat::Auto => detect(content),
+            format => *format,
+        };
+        match format {
+            ImportFormat::Text => Box::new(KeyValue),
+            ImportFormat::Json => Box::new(Json),
+            _ => Box::new(Delimited),
+        }
+    }
+}
+
+/// Turns another app's saved readings into journal entries
+pub trait Importer {
+    /// Every reading in `content`, or an error naming the first bad one
+    fn import(&self, content: &str) -> Result<Vec<JournalEntry>>;
+}
+
+/// `key=value` text, one block of lines per reading
+pub struct KeyValue;
+
+impl Importer for KeyValue {
+    fn import(&self, content: &str) -> Result<Vec<JournalEntry>> {
+        let mut blocks: Vec<Vec<(&str, &str)>> = vec![Vec::new()];
+        for line in content.lines().map(str::trim) {
+            if line.is_empty() {
+                if blocks.last().is_some_and(|block| !block.is_empty()) {
+                    blocks.push(Vec::new());
+                }
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            let (key, value) = line
+                .split_once(['=', ':'])
+                .with_context(|| format!("Expected key=value, found '{}'", line))?;
+            blocks
+                .last_mut()
+                .expect("blocks starts non-empty")
+                .push((key, value.trim()));
+        }
+
+        blocks
+            .iter()
+            .filter(|block| !block.is_empty())
+            .enumerate()
+            .map(|(i, block)| entry(block).with_context(|| format!("Reading {}", i + 1)))
+            .collect()
+    }
+}
+
+/// Delimited values with a header row naming the columns
+pub struct Delimited;
+
+impl Importer for Delimited {
+    fn import(&self, content: &str) -> Result<Vec<JournalEntry>> {
+        let header = content.lines().next().unwrap_or_default();
+        let delimiter = [',', '\t', ';']
+            .into_iter()
+            .max_by_key(|&delimiter| header.matches(delimiter).count())
+            .unwrap_or(',');
+
+        let mut rows = split_delimited(content, delimiter).into_iter();
+        let Some(columns) = rows.next() else {
+            return Ok(Vec::new());
+        };
+        rows.filter(|row| row.iter().any(|field| !field.trim().is_empty()))
+            .enumerate()
+            .map(|(i, row)| {
+                let fields: Vec<(&str, &str)> = columns
+                    .iter()
+                    .zip(&row)
+                    .map(|(column, value)| (column.as_str(), value.trim()))
+                    .collect();
+                // Row 1 is the header
+                entry(&fields).with_context(|| format!("Row {}", i + 2))
+            })
+            .collect()
+    }
+}
+
+/// Entries as `i-ching journal export --format json` writes them
+pub struct Json;
+
+impl Importer for Json {
+    fn import(&self, content: &str) -> Result<Vec<JournalEntry>> {
+        serde_json::from_str(content).context("Invalid journal JSON")
+    }
+}
+
+/// JSON if it opens an array, text if it opens with `key=value` or
+/// `key: value`, and otherwise CSV
+fn detect(content: &str) -> ImportFormat {
+    let Some(first) = content
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty() && !line.starts_with('#'))
+    else {
+        return ImportFormat::Csv;
+    };
+    if first.starts_with('[') {
+        return ImportFormat::Json;
+    }
+    match first.split_once(['=', ':']) {
+        Some((key, _)) if field(key).is_some() => ImportFormat::Text,
+        _ => ImportFormat::Csv,
+    }
+}
+
+/// What a key or column holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Field {
+    /// Six traditional numbers (6-9), line 1 first
+    Lines,
+    /// A hexagram, or a change between two, in `--input` form
+    Hexagram,
+    Transformed,
+    /// Changing line positions (1-6)
+    Changing,
+    Question,
+    Date,
+    Recipient,
+}
+
+/// The field a key or column name refers to, ignoring case, a `hexagram.`
+/// prefix or `_hexagram` suffix, and spaces or dashes for underscores
+fn field(name: &str) -> Option<Field> {
+    let name = name.trim().to_lowercase().replace([' ', '-'], "_");
+    let name = name.strip_prefix("hexagram.").unwrap_or(&name);
+    let name = name.strip_suffix("_hexagram").unwrap_or(name);
+    let field = match name {
+        "lines" | "cast" | "numbers" => Field::Lines,
+        "hexagram" | "number" | "primary" | "reading" => Field::Hexagram,
+        "transformed" | "resulting" | "relating" | "to" => Field::Transformed,
+        "changing" | "changing_lines" | "moving" | "moving_lines" => Field::Changing,
+        "question" | "q" => Field::Question,
+        "date" | "time" | "timestamp" | "datetime" | "cast_at" => Field::Date,
+        "for" | "recipient" => Field::Recipient,
+        _ => return None,
+    };
+    Some(field)
+}
+
+/// A journal entry from named values; unknown names and empty values are
+/// ignored, and a reading with no date is taken as cast now
+fn entry(fields: &[(&str, &str)]) -> Result<JournalEntry> {
+    let value = |wanted: Field| {
+        fields
+            .iter()
+            .find(|&&(name, value)| !value.is_empty() && field(name) == Some(wanted))
+            .map(|&(_, value)| value)
+    };
+
+    let reading = if let Some(lines) = value(Field::Lines) {
+        Reading::new(traditional_lines(lines)?, None)
+    } else if let Some(hexagram) = value(Field::Hexagram) {
+        let reading = parse::reading(hexagram)?;
+        let primary = reading.primary_hexagram();
+        if let Some(transformed) = value(Field::Transformed) {
+            let transformed = parse::reading(transformed)?.primary_hexagram();
+            Reading::from_change(primary, transformed)?
+        } else if let Some(changing) = value(Field::Changing) {
+            let bits = king_wen::to_binary(primary).context("Hexagram out of range")?;
+            let changed = positions(changing)?
+                .into_iter()
+                .fold(bits, king_wen::flip_line);
+            Reading::from_change(primary, king_wen::from_binary(changed))?
+        } else {
+            reading
+        }
+    } else {
+        return Err(anyhow::anyhow!("No lines or hexagram"));
+    };
+
+    let timestamp = match value(Field::Date) {
+        Some(date) => timestamp(date)?,
+        None => Utc::now(),
+    };
+    let question = value(Field::Question).map(str::to_string);
+    Ok(
+        JournalEntry::cast_at(reading.with_question(question), timestamp)
+            .with_recipient(value(Field::Recipient).map(str::to_string)),
+    )
+}
+
+/// Six traditional numbers, written together (`799688`) or separated by
+/// commas or spaces
+fn traditional_lines(value: &str) -> Result<[Line; 6]> {
+    let digits: Vec<char> = value
+        .chars()
+        .filter(|c| !c.is_whitespace() && *c != ',')
+        .collect();
+    let lines = digits
+        .iter()
+        .map(|&digit| {
+            digit
+                .to_digit(10)
+                .and_then(|number| Line::from_traditional_number(number as u8).ok())
+        })
+        .collect::<Option<Vec<Line>>>();
+    lines
+        .and_then(|lines| lines.try_into().ok())
+        .with_context(|| format!("Expected six line numbers (6-9), found '{}'", value))
+}
+
+/// Line positions (1-6) separated by commas or spaces
+fn positions(value: &str) -> Result<Vec<usize>> {
+    value
+        .split([',', ' '])
+        .filter(|part| !part.is_empty())
+        .map(|part| match part.parse() {
+            Ok(position @ 1..=6) => Ok(position),
+            _ => Err(anyhow::anyhow!("Invalid changing line '{}'", part)),
+        })
+        .collect()
+}
+
+/// An RFC 3339 timestamp, or a local date with or without a time
+fn timestamp(value: &str) -> Result<DateTime<Utc>> {
+    if let Ok(timestamp) = DateTime::parse_from_rfc3339(value) {
+        return Ok(timestamp.with_timezone(&Utc));
+    }
+    let naive = LOCAL_TIME_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .with_context(|| format!("Unrecognized date '{}'", value))?;
+    Local
+        .from_local_datetime(&naive)
+        .earliest()
+        .map(|local| local.with_timezone(&Utc))
+        .with_context(|| format!("No such local time '{}'", value))
+}
+
+/// Rows of fields, with `"..."` quoting (and `""` for a quote) as in CSV
+fn split_delimited(content: &str, delimiter: char) -> Vec<Vec<String>> {
+    let mut rows = Vec::new();
+    let mut row = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = content.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            c if quoted => field.push(c),
+            c if c == delimiter => row.push(std::mem::take(&mut field)),
+            '\r' => {}
+            '\n' => {
+                row.push(std::mem::take(&mut field));
+                rows.push(std::mem::take(&mut row));
+            }
+            c => field.push(c),
+        }
+    }
+    if !field.is_empty() || !row.is_empty() {
+        row.push(field);
+        rows.push(row);
+    }
+    rows
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_import_text() {
+        let content = "\
+# Exported readings
+hexagram.lines=799688
+question=Should I move?
+date=2024-03-01 09:30
+
+hexagram: 11
+changing: 1, 3
+for: Sam
+";
+        let entries = ImportFormat::Auto
+            .importer(content)
+            .import(content)
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reading.traditional_numbers(), [7, 9, 9, 6, 8, 8]);
+        assert_eq!(entries[0].reading.question(), Some("Should I move?"));
+        assert_eq!(
+            entries[0]
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m-%d %H:%M")
+                .to_string(),
+            "2024-03-01 09:30"
+        );
+        assert_eq!(entries[1].reading.changing_line_positions(), [1, 3]);
+        assert_eq!(entries[1].reading.primary_hexagram(), 11);
+        assert!(entries[1].is_for("sam"));
+
+        let error = KeyValue.import("lines=7999\n").unwrap_err();
+        assert!(format!("{:#}", error).starts_with("Reading 1: Expected six line numbers"));
+    }
+
+    #[test]
+    fn test_import_csv() {
+        let content = "\
+Date;Question;Hexagram;Resulting Hexagram
+2024-03-01;\"Stay; or go?\";32;34
+2024-03-02;;1;
+";
+        let entries = ImportFormat::Auto
+            .importer(content)
+            .import(content)
+            .unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reading.question(), Some("Stay; or go?"));
+        assert_eq!(entries[0].reading.transformed_hexagram_number(), Some(34));
+        assert_eq!(entries[1].reading.transformed_hexagram_number(), None);
+
+        // The journal's own CSV export reads back
+        let entry = JournalEntry::new("32->34".parse().unwrap());
+        let export = crate::format::format_delimited(std::slice::from_ref(&entry), ',');
+        let imported = Delimited.import(&export).unwrap();
+        assert_eq!(
+            imported[0].timestamp.timestamp(),
+            entry.timestamp.timestamp()
+        );
+        assert_eq!(
+            imported[0].reading.traditional_numbers(),
+            entry.reading.traditional_numbers()
+        );
+    }
+}
@@ -0,0 +1,236 @@
+use crate::core::Reading;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// What a question is about, used to frame the reading
+#[derive(
+    Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Serialize, Deserialize, ValueEnum,
+)]
+#[serde(rename_all = "lowercase")]
+pub enum Topic {
+    Career,
+    Leadership,
+    Relationship,
+    Timing,
+}
+
+impl Topic {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Topic::Career => "Career",
+            Topic::Leadership => "Leadership",
+            Topic::Relationship => "Relationship",
+            Topic::Timing => "Timing",
+        }
+    }
+}
+
+/// Sections of a full reading that framing can reorder
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Section {
+    Judgment,
+    Image,
+    ChangingLines,
+    Transformation,
+}
+
+/// Order used when no topic is given
+pub const DEFAULT_ORDER: [Section; 4] = [
+    Section::Judgment,
+    Section::Image,
+    Section::ChangingLines,
+    Section::Transformation,
+];
+
+/// A line position singled out for a topic, with the traditional reason
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Emphasis {
+    pub position: u8,
+    pub label: &'static str,
+}
+
+/// How to present a reading for a particular topic
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Framing {
+    pub topic: Topic,
+    /// Short paragraph to open the reading with
+    pub intro: String,
+    /// Sections in the order they should be shown
+    pub order: [Section; 4],
+    /// Line positions to put first and highlight, most important first
+    pub emphasis: Vec<Emphasis>,
+}
+
+impl Framing {
+    /// Emphasis for a line position, if the topic singles it out
+    pub fn emphasis_for(&self, position: u8) -> Option<&Emphasis> {
+        self.emphasis.iter().find(|e| e.position == position)
+    }
+
+    /// Changing line positions with emphasized lines first
+    pub fn order_lines(&self, positions: &[u8]) -> Vec<u8> {
+        let (mut emphasized, rest): (Vec<u8>, Vec<u8>) = positions
+            .iter()
+            .partition(|&&p| self.emphasis_for(p).is_some());
+        emphasized.sort_by_key(|&p| self.emphasis.iter().position(|e| e.position == p));
+        emphasized.extend(rest);
+        emphasized
+    }
+}
+
+/// Build the framing for a reading asked about the given topic
+pub fn frame(topic: Topic, reading: &Reading) -> Framing {
+    let changing = reading.changing_line_positions();
+
+    let (order, emphasis, mut intro) = match topic {
+        Topic::Career => (
+            [
+                Section::Judgment,
+                Section::ChangingLines,
+                Section::Image,
+                Section::Transformation,
+            ],
+            vec![
+                Emphasis {
+                    position: 2,
+                    label: "official",
+                },
+                Emphasis {
+                    position: 4,
+                    label: "minister",
+                },
+            ],
+            String::from(
+                "Read as a question of work and vocation. The second line is the official \
+                 in the field and the fourth the minister close to the ruler; their texts speak \
+                 to your standing and how to advance.",
+            ),
+        ),
+        Topic::Leadership => (
+            [
+                Section::ChangingLines,
+                Section::Judgment,
+                Section::Image,
+                Section::Transformation,
+            ],
+            vec![Emphasis {
+                position: 5,
+                label: "ruler",
+            }],
+            String::from(
+                "Read as a question of leadership. The fifth line is the ruler of the hexagram, \
+                 the place of the one who leads.",
+            ),
+        ),
+        Topic::Relationship => (
+            [
+                Section::Image,
+                Section::Judgment,
+                Section::ChangingLines,
+                Section::Transformation,
+            ],
+            vec![
+                Emphasis {
+                    position: 2,
+                    label: "inner partner",
+                },
+                Emphasis {
+                    position: 5,
+                    label: "outer partner",
+                },
+            ],
+            String::from(
+                "Read as a question about a relationship. The second and fifth lines are the \
+                 centres of the lower and upper trigrams and answer one another; the Image shows \
+                 how the two trigrams meet.",
+            ),
+        ),
+        Topic::Timing => (
+            [
+                Section::Judgment,
+                Section::Transformation,
+                Section::ChangingLines,
+                Section::Image,
+            ],
+            vec![
+                Emphasis {
+                    position: 1,
+                    label: "beginning",
+                },
+                Emphasis {
+                    position: 6,
+                    label: "end",
+                },
+            ],
+            String::from(
+                "Read as a question of timing. The first line is the beginning of the situation \
+                 and the top line its end; the transformed hexagram shows where events are heading.",
+            ),
+        ),
+    };
+
+    let emphasized_changing: Vec<u8> = emphasis
+        .iter()
+        .map(|e| e.position)
+        .filter(|p| changing.contains(p))
+        .collect();
+
+    match (changing.len(), emphasized_changing.as_slice()) {
+        (0, _) => intro.push_str(
+            " No lines are changing, so the situation is settled: rely on the Judgment and Image.",
+        ),
+        (_, []) => intro.push_str(
+            " None of those lines is changing here, so weigh the changing lines as the forces \
+             acting on that position.",
+        ),
+        (_, [position]) => intro.push_str(&format!(
+            " Line {} is changing in this reading and is shown first.",
+            position
+        )),
+        (_, positions) => intro.push_str(&format!(
+            " Lines {} are changing in this reading and are shown first.",
+            positions
+                .iter()
+                .map(|p| p.to_string())
+                .collect::<Vec<_>>()
+                .join(" and ")
+        )),
+    }
+
+    Framing {
+        topic,
+        intro,
+        order,
+        emphasis,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Diviner;
+
+    #[test]
+    fn test_leadership_emphasizes_ruler() {
+        let diviner = Diviner::new();
+        let reading = diviner
+            .cast_reading_from_numbers([9, 7, 7, 7, 6, 7], None)
+            .unwrap();
+
+        let framing = frame(Topic::Leadership, &reading);
+        assert_eq!(framing.order[0], Section::ChangingLines);
+        assert_eq!(framing.emphasis_for(5).unwrap().label, "ruler");
+        assert_eq!(framing.order_lines(&[1, 5]), vec![5, 1]);
+        assert!(framing.intro.contains("Line 5 is changing"));
+    }
+
+    #[test]
+    fn test_settled_reading() {
+        let diviner = Diviner::new();
+        let reading = diviner.cast_reading_from_numbers([7; 6], None).unwrap();
+
+        let framing = frame(Topic::Timing, &reading);
+        assert!(framing.intro.contains("No lines are changing"));
+        assert!(framing.order_lines(&[]).is_empty());
+    }
+}
@@ -0,0 +1,193 @@
+use crate::core::Reading;
+use crate::core::data::IChingData;
+
+/// Most bullets a guidance list will hold
+pub const MAX_GUIDANCE: usize = 5;
+
+/// Phrases that mark a sentence as advice in the Wilhelm-Baynes text
+const ADVICE_MARKERS: [&str; 9] = [
+    "furthers",
+    "does not further",
+    "must",
+    "should",
+    "it is necessary",
+    "do not",
+    "fear not",
+    "fear nothing",
+    "one ought",
+];
+
+/// Sentences that are advice when they open with one of these verbs
+const IMPERATIVE_VERBS: [&str; 12] = [
+    "be", "keep", "let", "go", "wait", "pause", "avoid", "hold", "remain", "seek", "take", "make",
+];
+
+/// The advice in a reading: imperative sentences from the judgment, then
+/// from each changing line, deduplicated and capped at `MAX_GUIDANCE`
+pub fn for_reading(reading: &Reading, data: &IChingData) -> Vec<String> {
+    let hexagram_number = reading.primary_hexagram();
+    let Some(hexagram) = data.get_hexagram(hexagram_number) else {
+        return Vec::new();
+    };
+
+    let line_texts = reading
+        .changing_line_positions()
+        .into_iter()
+        .filter_map(|position| data.get_line_interpretation(hexagram_number, position))
+        .map(|line| line.text.as_str());
+
+    extract(std::iter::once(hexagram.judgment.text.as_str()).chain(line_texts))
+}
+
+/// Advice sentences from the given texts, in order
+pub fn extract<'a>(texts: impl IntoIterator<Item = &'a str>) -> Vec<String> {
+    let mut guidance: Vec<String> = Vec::new();
+
+    for sentence in texts.into_iter().flat_map(sentences) {
+        if guidance.len() == MAX_GUIDANCE {
+            break;
+        }
+        if is_advice(&sentence)
+            && !guidance
+                .iter()
+                .any(|existing| existing.eq_ignore_ascii_case(&sentence))
+        {
+            guidance.push(sentence);
+        }
+    }
+
+    guidance
+}
+
+/// Split verse into sentences, joining lines that run on and capitalizing
+/// the first letter
+fn sentences(text: &str) -> Vec<String> {
+    let mut sentences = Vec::new();
+    let mut current = String::new();
+
+    for verse in text
+        .lines()
+        .map(str::trim)
+        .filter(|verse| !verse.is_empty())
+    {
+        if current.is_empty() {
+            current.push_str(verse);
+        } else {
+            // Each verse line is capitalized; a sentence running on shouldn't be
+            current.push(' ');
+            current.push_str(&decapitalize(verse));
+        }
+        current.push(' ');
+    }
+
+    let text = current.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut current = String::new();
+    for c in text.chars() {
+        current.push(c);
+        if matches!(c, '.' | '!' | '?' | ';') {
+            push_sentence(&mut sentences, &current);
+            current.clear();
+        }
+    }
+    push_sentence(&mut sentences, &current);
+
+    sentences
+}
+
+/// Lowercase a verse line's first letter, leaving "I" and words in capitals
+fn decapitalize(verse: &str) -> String {
+    let first_word = verse.split_whitespace().next().unwrap_or_default();
+    let shouting = first_word.chars().filter(|c| c.is_alphabetic()).count() > 1
+        && !first_word.chars().any(char::is_lowercase);
+    if first_word == "I" || first_word.starts_with("I'") || shouting {
+        return verse.to_string();
+    }
+
+    let mut chars = verse.chars();
+    match chars.next() {
+        Some(first) => first.to_lowercase().chain(chars).collect(),
+        None => String::new(),
+    }
+}
+
+fn push_sentence(sentences: &mut Vec<String>, sentence: &str) {
+    let sentence = sentence.trim().trim_end_matches(';');
+    let mut chars = sentence.chars();
+    if let Some(first) = chars.next() {
+        let sentence = first.to_uppercase().chain(chars).collect::<String>();
+        // A clause cut off at a semicolon gets a full stop
+        if sentence.ends_with(['.', '!', '?']) {
+            sentences.push(sentence);
+        } else {
+            sentences.push(format!("{}.", sentence));
+        }
+    }
+}
+
+fn is_advice(sentence: &str) -> bool {
+    let lower = sentence.to_lowercase();
+    let first_word = lower
+        .split(|c: char| !c.is_alphabetic())
+        .next()
+        .unwrap_or_default();
+
+    ADVICE_MARKERS
+        .iter()
+        .any(|marker| contains_phrase(&lower, marker))
+        || IMPERATIVE_VERBS.contains(&first_word)
+}
+
+/// Whether `phrase` appears in `text` as whole words
+fn contains_phrase(text: &str, phrase: &str) -> bool {
+    text.match_indices(phrase).any(|(start, _)| {
+        let before = text[..start].chars().next_back();
+        let after = text[start + phrase.len()..].chars().next();
+        !before.is_some_and(char::is_alphabetic) && !after.is_some_and(char::is_alphabetic)
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_extract() {
+        let guidance = extract([
+            "DIFFICULTY AT THE BEGINNING works supreme success,\nFurthering through perseverance.\nNothing should be undertaken.\nIt furthers one to appoint helpers.",
+            "Hesitation and hindrance.\nIt furthers one to remain persevering.\nIt furthers one to appoint helpers.",
+            "Forward and backward, abyss on abyss.\nDo not act this way.",
+        ]);
+
+        assert_eq!(
+            guidance,
+            [
+                "Nothing should be undertaken.",
+                "It furthers one to appoint helpers.",
+                "It furthers one to remain persevering.",
+                "Do not act this way.",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_verse_and_limit() {
+        // Lines of verse run on into one sentence; semicolons end clauses
+        assert_eq!(
+            extract(["Be like the sun\nAt midday;\nkeep still."]),
+            ["Be like the sun at midday.", "Keep still."]
+        );
+
+        // "Musty" is not "must", and the list stops at the cap
+        assert!(extract(["A musty room."]).is_empty());
+        let many = "Wait. Go. Hold. Seek. Take. Make. Keep.";
+        assert_eq!(extract([many]).len(), MAX_GUIDANCE);
+    }
+
+    #[test]
+    fn test_for_reading() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "7,8,8,8,7,8".parse().unwrap(); // 3, no changing lines
+        let guidance = for_reading(&reading, &data);
+        assert!(guidance.contains(&"It furthers one to appoint helpers.".to_string()));
+    }
+}
@@ -0,0 +1,98 @@
+use crate::core::Reading;
+use chrono::{Days, Months, NaiveDate};
+use std::fmt;
+
+/// Unit of time a changing line's place in the hexagram stands for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Unit {
+    /// Lines 1-2, the place of earth: close at hand
+    Days,
+    /// Lines 3-4, the place of man
+    Weeks,
+    /// Lines 5-6, the place of heaven: furthest off
+    Months,
+}
+
+/// A rough time horizon for a reading, after the folk practice of counting
+/// the changing lines in units set by how high they reach
+///
+/// The number of changing lines is the count and the highest changing line
+/// picks the unit from the three places (earth, man, heaven). A reading
+/// with no changing lines describes a settled situation, looked at again
+/// after a month.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Horizon {
+    pub count: u32,
+    pub unit: Unit,
+}
+
+impl Horizon {
+    pub fn for_reading(reading: &Reading) -> Self {
+        let positions = reading.changing_line_positions();
+        let unit = match positions.iter().max() {
+            None => Unit::Months,
+            Some(1..=2) => Unit::Days,
+            Some(3..=4) => Unit::Weeks,
+            Some(_) => Unit::Months,
+        };
+
+        Self {
+            count: positions.len().max(1) as u32,
+            unit,
+        }
+    }
+
+    /// The date the horizon reaches from a reading cast on `cast`
+    pub fn revisit_on(&self, cast: NaiveDate) -> NaiveDate {
+        let later = match self.unit {
+            Unit::Days => cast.checked_add_days(Days::new(self.count.into())),
+            Unit::Weeks => cast.checked_add_days(Days::new(7 * u64::from(self.count))),
+            Unit::Months => cast.checked_add_months(Months::new(self.count)),
+        };
+        later.unwrap_or(cast)
+    }
+}
+
+/// e.g. "2 weeks", "1 month"
+impl fmt::Display for Horizon {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let unit = match self.unit {
+            Unit::Days => "day",
+            Unit::Weeks => "week",
+            Unit::Months => "month",
+        };
+        let plural = if self.count == 1 { "" } else { "s" };
+        write!(f, "{} {}{}", self.count, unit, plural)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn horizon(lines: &str) -> Horizon {
+        Horizon::for_reading(&lines.parse().unwrap())
+    }
+
+    #[test]
+    fn test_horizon() {
+        assert_eq!(horizon("6,7,7,7,7,7").to_string(), "1 day");
+        assert_eq!(horizon("9,7,6,7,7,7").to_string(), "2 weeks");
+        assert_eq!(horizon("9,9,7,7,6,7").to_string(), "3 months");
+        assert_eq!(horizon("7,7,7,7,7,7").to_string(), "1 month");
+    }
+
+    #[test]
+    fn test_revisit_on() {
+        let cast = NaiveDate::from_ymd_opt(2026, 1, 31).unwrap();
+        assert_eq!(
+            horizon("9,7,6,7,7,7").revisit_on(cast),
+            NaiveDate::from_ymd_opt(2026, 2, 14).unwrap()
+        );
+        // Month arithmetic clamps to the end of a shorter month
+        assert_eq!(
+            horizon("7,7,7,7,7,7").revisit_on(cast),
+            NaiveDate::from_ymd_opt(2026, 2, 28).unwrap()
+        );
+    }
+}
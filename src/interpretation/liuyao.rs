@@ -0,0 +1,200 @@
+use chrono::NaiveDate;
+
+/// One of the ten Heavenly Stems (tiangan) that count the days
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stem {
+    Jia,
+    Yi,
+    Bing,
+    Ding,
+    Wu,
+    Ji,
+    Geng,
+    Xin,
+    Ren,
+    Gui,
+}
+
+const STEMS: [Stem; 10] = [
+    Stem::Jia,
+    Stem::Yi,
+    Stem::Bing,
+    Stem::Ding,
+    Stem::Wu,
+    Stem::Ji,
+    Stem::Geng,
+    Stem::Xin,
+    Stem::Ren,
+    Stem::Gui,
+];
+
+impl Stem {
+    /// Pinyin without tone marks
+    pub fn name(&self) -> &'static str {
+        match self {
+            Stem::Jia => "Jia",
+            Stem::Yi => "Yi",
+            Stem::Bing => "Bing",
+            Stem::Ding => "Ding",
+            Stem::Wu => "Wu",
+            Stem::Ji => "Ji",
+            Stem::Geng => "Geng",
+            Stem::Xin => "Xin",
+            Stem::Ren => "Ren",
+            Stem::Gui => "Gui",
+        }
+    }
+
+    /// The stem of a calendar day in the unbroken sexagenary day count
+    pub fn of_day(date: NaiveDate) -> Self {
+        // 1 January 2000 was a wuwu (戊午) day
+        let reference = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let days = (date - reference).num_days();
+        STEMS[(days + 4).rem_euclid(10) as usize]
+    }
+
+    pub fn pinyin(&self) -> &'static str {
+        match self {
+            Stem::Jia => "Jiǎ",
+            Stem::Yi => "Yǐ",
+            Stem::Bing => "Bǐng",
+            Stem::Ding => "Dīng",
+            Stem::Wu => "Wù",
+            Stem::Ji => "Jǐ",
+            Stem::Geng => "Gēng",
+            Stem::Xin => "Xīn",
+            Stem::Ren => "Rén",
+            Stem::Gui => "Guǐ",
+        }
+    }
+
+    pub fn chinese(&self) -> &'static str {
+        match self {
+            Stem::Jia => "甲",
+            Stem::Yi => "乙",
+            Stem::Bing => "丙",
+            Stem::Ding => "丁",
+            Stem::Wu => "戊",
+            Stem::Ji => "己",
+            Stem::Geng => "庚",
+            Stem::Xin => "辛",
+            Stem::Ren => "壬",
+            Stem::Gui => "癸",
+        }
+    }
+}
+
+/// The six spirits (liushen) of Six Lines divination, in the order they
+/// climb the hexagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Spirit {
+    Qinglong,
+    Zhuque,
+    Gouchen,
+    Tengshe,
+    Baihu,
+    Xuanwu,
+}
+
+const SPIRITS: [Spirit; 6] = [
+    Spirit::Qinglong,
+    Spirit::Zhuque,
+    Spirit::Gouchen,
+    Spirit::Tengshe,
+    Spirit::Baihu,
+    Spirit::Xuanwu,
+];
+
+impl Spirit {
+    /// The spirit on the bottom line: wood days start with the Green Dragon,
+    /// fire with the Vermilion Bird, wu and ji earth with the Hooked Snake and
+    /// Soaring Serpent, metal with the White Tiger, water with the Black Tortoise
+    pub fn first_for(stem: Stem) -> Self {
+        match stem {
+            Stem::Jia | Stem::Yi => Spirit::Qinglong,
+            Stem::Bing | Stem::Ding => Spirit::Zhuque,
+            Stem::Wu => Spirit::Gouchen,
+            Stem::Ji => Spirit::Tengshe,
+            Stem::Geng | Stem::Xin => Spirit::Baihu,
+            Stem::Ren | Stem::Gui => Spirit::Xuanwu,
+        }
+    }
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            Spirit::Qinglong => "Green Dragon",
+            Spirit::Zhuque => "Vermilion Bird",
+            Spirit::Gouchen => "Hooked Snake",
+            Spirit::Tengshe => "Soaring Serpent",
+            Spirit::Baihu => "White Tiger",
+            Spirit::Xuanwu => "Black Tortoise",
+        }
+    }
+
+    pub fn pinyin(&self) -> &'static str {
+        match self {
+            Spirit::Qinglong => "Qīnglóng",
+            Spirit::Zhuque => "Zhūquè",
+            Spirit::Gouchen => "Gōuchén",
+            Spirit::Tengshe => "Téngshé",
+            Spirit::Baihu => "Báihǔ",
+            Spirit::Xuanwu => "Xuánwǔ",
+        }
+    }
+
+    pub fn chinese(&self) -> &'static str {
+        match self {
+            Spirit::Qinglong => "青龍",
+            Spirit::Zhuque => "朱雀",
+            Spirit::Gouchen => "勾陳",
+            Spirit::Tengshe => "螣蛇",
+            Spirit::Baihu => "白虎",
+            Spirit::Xuanwu => "玄武",
+        }
+    }
+}
+
+/// The spirit on each line, bottom to top, for a reading cast on `date`
+pub fn spirits(date: NaiveDate) -> [Spirit; 6] {
+    let first = SPIRITS
+        .iter()
+        .position(|&spirit| spirit == Spirit::first_for(Stem::of_day(date)))
+        .unwrap_or(0);
+    std::array::from_fn(|line| SPIRITS[(first + line) % SPIRITS.len()])
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_day_stems() {
+        // 1 January 1900 was a jiaxu (甲戌) day
+        assert_eq!(Stem::of_day(date(1900, 1, 1)), Stem::Jia);
+        assert_eq!(Stem::of_day(date(2000, 1, 1)), Stem::Wu);
+        assert_eq!(Stem::of_day(date(2000, 1, 7)), Stem::Jia);
+        assert_eq!(Stem::of_day(date(1999, 12, 31)), Stem::Ding);
+    }
+
+    #[test]
+    fn test_spirits() {
+        // A jia day starts from the Green Dragon and runs in order
+        assert_eq!(spirits(date(2000, 1, 7)), SPIRITS);
+        // A gui day starts from the Black Tortoise and wraps around
+        assert_eq!(
+            spirits(date(2000, 1, 16)),
+            [
+                Spirit::Xuanwu,
+                Spirit::Qinglong,
+                Spirit::Zhuque,
+                Spirit::Gouchen,
+                Spirit::Tengshe,
+                Spirit::Baihu,
+            ]
+        );
+    }
+}
@@ -0,0 +1,7 @@
+pub mod framing;
+pub mod guidance;
+pub mod horizon;
+pub mod liuyao;
+pub mod rules;
+pub mod structure;
+pub mod wuxing;
@@ -0,0 +1,183 @@
+use crate::core::Reading;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::fmt;
+
+/// Convention for which text to read when lines change
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Rule {
+    /// Zhu Xi's rules from the Yixue Qimeng: the number of changing lines decides
+    #[default]
+    Zhuxi,
+    /// Read every changing line of the primary hexagram
+    All,
+    /// Leave the choice to the reader
+    None,
+}
+
+impl Rule {
+    pub fn label(&self) -> &'static str {
+        match self {
+            Rule::Zhuxi => "Zhu Xi",
+            Rule::All => "all changing lines",
+            Rule::None => "none",
+        }
+    }
+}
+
+/// Which of a reading's two hexagrams a text comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Source {
+    Primary,
+    Transformed,
+}
+
+impl fmt::Display for Source {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Source::Primary => write!(f, "primary"),
+            Source::Transformed => write!(f, "transformed"),
+        }
+    }
+}
+
+/// The text a rule says to read for a reading
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Governing {
+    /// The judgment of one hexagram
+    Judgment(Source),
+    /// Both judgments, the primary's leading
+    Judgments,
+    /// One line statement
+    Line(Source, u8),
+    /// Several line statements, bottom first
+    Lines(Source, Vec<u8>),
+}
+
+impl Governing {
+    /// The governing text under `rule`, or `None` if the rule doesn't say
+    ///
+    /// Zhu Xi's rules, by the number of changing lines:
+    /// - none: the primary judgment
+    /// - one: that line of the primary
+    /// - two: the upper of the two lines of the primary
+    /// - three: both judgments, the primary's leading
+    /// - four: the lower of the two unchanging lines of the transformed hexagram
+    /// - five: the one unchanging line of the transformed hexagram
+    /// - six: the judgment of the transformed hexagram
+    pub fn for_reading(rule: Rule, reading: &Reading) -> Option<Self> {
+        let changing = reading.changing_line_positions();
+        let unchanging: Vec<u8> = (1..=6).filter(|p| !changing.contains(p)).collect();
+
+        match rule {
+            Rule::None => None,
+            Rule::All => Some(match changing.len() {
+                0 => Governing::Judgment(Source::Primary),
+                1 => Governing::Line(Source::Primary, changing[0]),
+                _ => Governing::Lines(Source::Primary, changing),
+            }),
+            Rule::Zhuxi => Some(match changing.len() {
+                0 => Governing::Judgment(Source::Primary),
+                1 | 2 => Governing::Line(Source::Primary, *changing.last()?),
+                3 => Governing::Judgments,
+                4 | 5 => Governing::Line(Source::Transformed, unchanging[0]),
+                _ => Governing::Judgment(Source::Transformed),
+            }),
+        }
+    }
+}
+
+/// e.g. "line 5 of the primary hexagram"
+impl fmt::Display for Governing {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Governing::Judgment(source) => write!(f, "the judgment of the {} hexagram", source),
+            Governing::Judgments => write!(f, "both judgments, the primary hexagram's first"),
+            Governing::Line(source, position) => {
+                write!(f, "line {} of the {} hexagram", position, source)
+            }
+            Governing::Lines(source, positions) => {
+                let positions: Vec<String> = positions.iter().map(|p| p.to_string()).collect();
+                let (last, rest) = positions.split_last().expect("at least two lines");
+                write!(
+                    f,
+                    "lines {} and {} of the {} hexagram",
+                    rest.join(", "),
+                    last,
+                    source
+                )
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn governing(rule: Rule, lines: &str) -> String {
+        Governing::for_reading(rule, &lines.parse().unwrap())
+            .map(|governing| governing.to_string())
+            .unwrap_or_default()
+    }
+
+    #[test]
+    fn test_zhuxi() {
+        let rule = Rule::Zhuxi;
+        assert_eq!(
+            governing(rule, "7,7,7,7,7,7"),
+            "the judgment of the primary hexagram"
+        );
+        assert_eq!(
+            governing(rule, "9,7,7,7,6,7"),
+            "line 5 of the primary hexagram"
+        );
+        assert_eq!(
+            governing(rule, "9,6,9,7,7,7"),
+            "both judgments, the primary hexagram's first"
+        );
+        assert_eq!(
+            governing(rule, "9,7,6,7,9,6"),
+            "line 2 of the transformed hexagram"
+        );
+        assert_eq!(
+            governing(rule, "9,9,9,9,7,9"),
+            "line 5 of the transformed hexagram"
+        );
+        assert_eq!(
+            governing(rule, "9,9,9,9,9,9"),
+            "the judgment of the transformed hexagram"
+        );
+    }
+
+    #[test]
+    fn test_zhuxi_over_every_reading() {
+        for reading in Reading::all_transitions() {
+            let changing = reading.changing_line_positions();
+            let governing = Governing::for_reading(Rule::Zhuxi, &reading).unwrap();
+            match &governing {
+                Governing::Line(Source::Primary, position) => {
+                    assert!(changing.contains(position))
+                }
+                Governing::Line(Source::Transformed, position) => {
+                    assert!(!changing.contains(position))
+                }
+                Governing::Judgment(Source::Primary) => assert!(changing.is_empty()),
+                Governing::Judgment(Source::Transformed) => assert_eq!(changing.len(), 6),
+                Governing::Judgments => assert_eq!(changing.len(), 3),
+                Governing::Lines(..) => panic!("Zhu Xi names one text: {}", reading),
+            }
+            assert!(!governing.to_string().is_empty());
+        }
+    }
+
+    #[test]
+    fn test_all_and_none() {
+        assert_eq!(
+            governing(Rule::All, "9,7,6,7,9,7"),
+            "lines 1, 3 and 5 of the primary hexagram"
+        );
+        assert_eq!(governing(Rule::None, "9,7,6,7,9,7"), "");
+    }
+}
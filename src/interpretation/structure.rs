@@ -0,0 +1,77 @@
+use crate::core::Reading;
+use crate::core::reading::Polarity;
+use serde::{Deserialize, Serialize};
+
+/// How a line sits in the structure of its hexagram
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LinePlace {
+    /// 1-6, bottom first
+    pub position: u8,
+    /// Yang in an odd place or yin in an even one
+    pub correct: bool,
+    /// Line 2 or 5, the middle of its trigram
+    pub central: bool,
+    /// The line in the same place of the other trigram (1 with 4, 2 with 5,
+    /// 3 with 6)
+    pub partner: u8,
+    /// The partner has the opposite polarity, so the two respond to each other
+    pub corresponds: bool,
+}
+
+/// Place, centrality, and correspondence of each line of the primary
+/// hexagram, bottom first
+pub fn analyze(reading: &Reading) -> [LinePlace; 6] {
+    let lines = reading.lines();
+
+    std::array::from_fn(|i| {
+        let position = i as u8 + 1;
+        let partner = (i + 3) % 6;
+        let odd = position % 2 == 1;
+        LinePlace {
+            position,
+            correct: (lines[i].polarity == Polarity::Yang) == odd,
+            central: position == 2 || position == 5,
+            partner: partner as u8 + 1,
+            corresponds: lines[i].polarity != lines[partner].polarity,
+        }
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_correct_and_corresponding() {
+        // 63 After Completion: every line in its correct place, every pair responding
+        let places = analyze(&"63".parse().unwrap());
+        assert!(
+            places
+                .iter()
+                .all(|place| place.correct && place.corresponds)
+        );
+
+        // 64 Before Completion: the reverse
+        let places = analyze(&"64".parse().unwrap());
+        assert!(
+            places
+                .iter()
+                .all(|place| !place.correct && place.corresponds)
+        );
+    }
+
+    #[test]
+    fn test_partners_and_centres() {
+        let places = analyze(&"1".parse().unwrap());
+        let partners: Vec<u8> = places.iter().map(|place| place.partner).collect();
+        assert_eq!(partners, [4, 5, 6, 1, 2, 3]);
+        let central: Vec<u8> = places
+            .iter()
+            .filter(|place| place.central)
+            .map(|place| place.position)
+            .collect();
+        assert_eq!(central, [2, 5]);
+        assert!(places.iter().all(|place| !place.corresponds));
+        assert_eq!(places.iter().filter(|place| place.correct).count(), 3);
+    }
+}
@@ -0,0 +1,172 @@
+//! Five-phase (wuxing) dynamics between a hexagram's trigrams
+//!
+//! Each trigram has a phase (see `Trigram::element`), and each hexagram the
+//! phase of its palace in Jing Fang's eight palaces: the eight doubled
+//! trigrams head the palaces, and each palace holds its head, the five
+//! hexagrams reached by changing one more of the head's lines at a time from
+//! the bottom up, and the "wandering soul" and "returning soul" hexagrams
+//! that turn back. How the
+//! lower (inner) trigram's phase stands to the upper (outer) one's in the
+//! generating and controlling cycles colours the situation.
+
+use crate::core::Reading;
+use crate::core::trigram::{Element, Trigram};
+
+/// How the lower trigram's phase stands to the upper's
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Relation {
+    /// Both trigrams are of one phase
+    Same,
+    LowerGeneratesUpper,
+    UpperGeneratesLower,
+    LowerControlsUpper,
+    UpperControlsLower,
+}
+
+impl Relation {
+    pub fn between(lower: Element, upper: Element) -> Self {
+        if lower == upper {
+            Relation::Same
+        } else if lower.generates() == upper {
+            Relation::LowerGeneratesUpper
+        } else if upper.generates() == lower {
+            Relation::UpperGeneratesLower
+        } else if lower.controls() == upper {
+            Relation::LowerControlsUpper
+        } else {
+            Relation::UpperControlsLower
+        }
+    }
+}
+
+/// The phases of a hexagram's trigrams and palace
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Dynamics {
+    pub lower: Trigram,
+    pub upper: Trigram,
+    pub relation: Relation,
+    /// The palace the hexagram belongs to, named for its head trigram
+    pub palace: Trigram,
+}
+
+impl Dynamics {
+    /// The dynamics of the reading's primary hexagram
+    pub fn of(reading: &Reading) -> Self {
+        let lower = reading.lower_trigram();
+        let upper = reading.upper_trigram();
+        Dynamics {
+            lower,
+            upper,
+            relation: Relation::between(lower.element(), upper.element()),
+            palace: palace(lower.bits() | upper.bits() << 3),
+        }
+    }
+
+    /// The hexagram's phase, its palace's
+    pub fn element(&self) -> Element {
+        self.palace.element()
+    }
+
+    /// A paragraph on the two phases and the hexagram's palace
+    pub fn describe(&self) -> String {
+        let (lower, upper) = (self.lower.element(), self.upper.element());
+        let phase = |element: Element| format!("{} ({})", element, element.chinese());
+        let relation = match self.relation {
+            Relation::Same => format!(
+                "Inner and outer trigrams are both {}: the situation is all of a piece, its nature doubled, with nothing within it to feed or check it.",
+                phase(lower)
+            ),
+            Relation::LowerGeneratesUpper => format!(
+                "The inner {} generates the outer {}: what is held within feeds what shows outwardly, a productive flow, though the giving spends the inner.",
+                phase(lower),
+                phase(upper)
+            ),
+            Relation::UpperGeneratesLower => format!(
+                "The outer {} generates the inner {}: circumstances feed what is within; support arrives from outside and can be accepted.",
+                phase(upper),
+                phase(lower)
+            ),
+            Relation::LowerControlsUpper => format!(
+                "The inner {} controls the outer {}: circumstances can be mastered from within, but only with effort, and the struggle wears on both.",
+                phase(lower),
+                phase(upper)
+            ),
+            Relation::UpperControlsLower => format!(
+                "The outer {} controls the inner {}: circumstances press on and restrain what is within; yield where you must and keep the core intact.",
+                phase(upper),
+                phase(lower)
+            ),
+        };
+        format!(
+            "{} ({}) over {} ({}). {} The hexagram belongs to the palace of {} ({}), of the {} phase.",
+            upper.name(),
+            self.upper,
+            lower.name(),
+            self.lower,
+            relation,
+            self.palace,
+            self.palace.image(),
+            self.element()
+        )
+    }
+}
+
+/// The palace holding a hexagram, from its line pattern (bit 0 the bottom
+/// line, yang = 1)
+pub fn palace(bits: u8) -> Trigram {
+    // Lines each palace member changes from its head: the bottom one, two,
+    // three, four, and five lines, then line 4 back (wandering soul), then
+    // the lower trigram back (returning soul)
+    const CHANGES: [u8; 8] = [
+        0b000000, 0b000001, 0b000011, 0b000111, 0b001111, 0b011111, 0b010111, 0b010000,
+    ];
+    Trigram::ALL
+        .into_iter()
+        .find(|head| {
+            let doubled = head.bits() | head.bits() << 3;
+            CHANGES.iter().any(|changes| doubled ^ changes == bits)
+        })
+        .expect("the eight palaces hold all 64 hexagrams")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn dynamics(input: &str) -> Dynamics {
+        Dynamics::of(&input.parse().unwrap())
+    }
+
+    #[test]
+    fn test_dynamics() {
+        // The Qian palace: 1, 44, 33, 12, 20, 23, then 35 and 14
+        for number in [1, 44, 33, 12, 20, 23, 35, 14] {
+            assert_eq!(dynamics(&number.to_string()).palace, Trigram::Qian);
+        }
+        assert_eq!(dynamics("2").palace, Trigram::Kun);
+        assert_eq!(dynamics("63").palace, Trigram::Kan);
+        // Every hexagram has exactly one palace
+        for head in Trigram::ALL {
+            let members = (0..64u8).filter(|&bits| palace(bits) == head).count();
+            assert_eq!(members, 8, "{}", head);
+        }
+
+        // 11 Peace: Kun's Earth above feeds Qian's Metal below
+        let peace = dynamics("11");
+        assert_eq!(peace.relation, Relation::UpperGeneratesLower);
+        assert_eq!(peace.element(), Element::Earth);
+        assert_eq!(dynamics("32").relation, Relation::Same);
+        assert_eq!(dynamics("63").relation, Relation::UpperControlsLower);
+        assert_eq!(dynamics("64").relation, Relation::LowerControlsUpper);
+        assert_eq!(dynamics("50").relation, Relation::LowerGeneratesUpper);
+        let text = peace.describe();
+        assert!(text.starts_with(
+            "Earth (Kun) over Metal (Qian). The outer Earth (土) generates the inner Metal (金): "
+        ));
+        assert!(
+            text.ends_with(
+                " The hexagram belongs to the palace of Kun (Earth), of the Earth phase."
+            )
+        );
+    }
+}
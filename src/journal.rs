@@ -0,0 +1,992 @@
+use crate::almanac::Almanac;
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::core::schema;
+#[cfg(feature = "encryption")]
+use crate::encryption::Key;
+use crate::interpretation::framing::Topic;
+use crate::interpretation::horizon::Horizon;
+use crate::search::{self, Hit};
+#[cfg(feature = "sqlite")]
+use crate::store::Store;
+use crate::wizard::StructuredQuestion;
+use anyhow::{Context, Result};
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use fs4::FileExt;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// Environment variable that overrides the journal location
+pub const JOURNAL_PATH_ENV: &str = "I_CHING_JOURNAL";
+
+/// How an age-encrypted (locked) journal file begins
+const AGE_HEADER: &[u8] = b"age-encryption.org/";
+
+/// How long a write waits for another process to finish writing the journal
+const WRITE_WAIT: Duration = Duration::from_secs(10);
+
+/// A reading recorded in the journal
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalEntry {
+    pub timestamp: DateTime<Utc>,
+    pub reading: Reading,
+    /// Moon phase and solar term when the reading was cast, if noted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub almanac: Option<Almanac>,
+    /// Topic, timeframe, and agency, if the question came from `i-ching wizard`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub structured_question: Option<StructuredQuestion>,
+    /// When to look at the reading again, from its changing lines (entries
+    /// saved before revisit dates were recorded have none)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub revisit_on: Option<NaiveDate>,
+    /// Who the reading was cast for, when consulting for someone else
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    /// What the question was about, from `--topic`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<Topic>,
+    /// The date hashed with the question, when the lines were derived with
+    /// `--method question-hash` instead of cast
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question_hash: Option<NaiveDate>,
+    /// The changing line (1-6) marked as central to the question, from
+    /// `--focus-line`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_line: Option<u8>,
+    /// How things turned out, once recorded with `journal resolve`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub outcome: Option<Outcome>,
+}
+
+/// How a reading's situation turned out
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Outcome {
+    /// Whether the situation asked about has played out
+    pub resolved: bool,
+    /// How well the reading fit what happened, 1 (not at all) to 5 (closely)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub accuracy: Option<u8>,
+    /// When it played out
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub resolved_on: Option<NaiveDate>,
+    /// What happened, in your words
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub note: Option<String>,
+}
+
+impl JournalEntry {
+    pub fn new(reading: Reading) -> Self {
+        Self::cast_at(reading, Utc::now())
+    }
+
+    /// An entry for a reading cast at an earlier time, such as one imported
+    /// from elsewhere
+    pub fn cast_at(reading: Reading, timestamp: DateTime<Utc>) -> Self {
+        let revisit_on =
+            Horizon::for_reading(&reading).revisit_on(timestamp.with_timezone(&Local).date_naive());
+
+        Self {
+            timestamp,
+            reading,
+            almanac: None,
+            structured_question: None,
+            revisit_on: Some(revisit_on),
+            recipient: None,
+            topic: None,
+            question_hash: None,
+            focus_line: None,
+            outcome: None,
+        }
+    }
+
+    /// Note the moon phase and solar term at the entry's timestamp
+    pub fn with_almanac(mut self) -> Self {
+        self.almanac = Some(Almanac::at(self.timestamp));
+        self
+    }
+
+    /// Note who the reading is for; a blank name counts as none
+    pub fn with_recipient(mut self, recipient: Option<String>) -> Self {
+        self.recipient = recipient
+            .map(|name| name.trim().to_string())
+            .filter(|name| !name.is_empty());
+        self
+    }
+
+    /// Whether the reading was cast for `name`, ignoring case
+    pub fn is_for(&self, name: &str) -> bool {
+        self.recipient
+            .as_deref()
+            .is_some_and(|recipient| recipient.eq_ignore_ascii_case(name.trim()))
+    }
+
+    /// Note what the question is about
+    pub fn with_topic(mut self, topic: Option<Topic>) -> Self {
+        self.topic = topic;
+        self
+    }
+
+    /// The question's topic, from `--topic` or else the wizard's answers
+    pub fn topic(&self) -> Option<Topic> {
+        self.topic
+            .or_else(|| self.structured_question.as_ref()?.topic)
+    }
+
+    /// Note that the lines came from the question and `date`, not a cast
+    pub fn with_question_hash(mut self, date: Option<NaiveDate>) -> Self {
+        self.question_hash = date;
+        self
+    }
+
+    /// Mark the changing line the reading turns on
+    pub fn with_focus_line(mut self, line: Option<u8>) -> Self {
+        self.focus_line = line;
+        self
+    }
+
+    /// Keep the wizard's answers the question was composed from
+    pub fn with_structured_question(mut self, structured: StructuredQuestion) -> Self {
+        self.structured_question = Some(structured);
+        self
+    }
+}
+
+/// Where a journal keeps its entries
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Backend {
+    /// One JSON entry per line
+    Lines,
+    /// The journal tables of a SQLite store
+    #[cfg(feature = "sqlite")]
+    Sqlite,
+}
+
+/// Append-only journal of readings, stored as one JSON entry per line (or,
+/// with the `sqlite` feature, in the SQLite store)
+///
+/// A JSON lines journal can be locked (age-encrypted) with the `encryption`
+/// feature; it is then decrypted on every read and re-encrypted on append.
+///
+/// Writes to a JSON lines journal hold an advisory lock on a `.lock` file
+/// beside it, so two processes (say, overlapping cron jobs) take turns
+/// instead of interleaving, and whole-file rewrites go through a temporary
+/// file renamed into place, so a reader or a crash never sees half a file.
+#[derive(Clone)]
+pub struct Journal {
+    path: PathBuf,
+    // Only ever `Lines` without the `sqlite` feature
+    #[cfg_attr(not(feature = "sqlite"), allow(dead_code))]
+    backend: Backend,
+    /// Unlocks a locked journal; from the environment or a prompt if unset
+    #[cfg(feature = "encryption")]
+    key: Option<Key>,
+}
+
+impl Journal {
+    /// Open the journal at `$I_CHING_JOURNAL`, or in the user data directory
+    ///
+    /// With the `sqlite` feature the journal lives in the SQLite store
+    /// instead, and a JSON lines journal left from before is imported into
+    /// it the first time.
+    pub fn open_default() -> Result<Self> {
+        let lines_path = match env::var_os(JOURNAL_PATH_ENV) {
+            Some(path) => PathBuf::from(path),
+            None => dirs::data_dir()
+                .ok_or_else(|| anyhow::anyhow!("Could not determine the user data directory"))?
+                .join("i-ching")
+                .join("journal.jsonl"),
+        };
+
+        #[cfg(feature = "sqlite")]
+        let journal = {
+            let journal = Self::sqlite(crate::store::default_path()?);
+            let mut store = Store::open(&journal.path)?;
+            if store.journal_is_empty()? {
+                store.import(&Self::at(lines_path).entries()?)?;
+            }
+            journal
+        };
+        #[cfg(not(feature = "sqlite"))]
+        let journal = Self::at(lines_path);
+
+        Ok(journal)
+    }
+
+    /// Open a journal stored at a specific path
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            backend: Backend::Lines,
+            #[cfg(feature = "encryption")]
+            key: None,
+        }
+    }
+
+    /// Open the journal in the SQLite store at a specific path
+    #[cfg(feature = "sqlite")]
+    pub fn sqlite(path: impl Into<PathBuf>) -> Self {
+        Self {
+            path: path.into(),
+            backend: Backend::Sqlite,
+            #[cfg(feature = "encryption")]
+            key: None,
+        }
+    }
+
+    /// Use `key` for a locked journal instead of asking for one
+    #[cfg(feature = "encryption")]
+    pub fn with_key(mut self, key: Key) -> Self {
+        self.key = Some(key);
+        self
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Whether the journal file is encrypted (see `lock`)
+    pub fn is_locked(&self) -> Result<bool> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            return Ok(false);
+        }
+
+        let mut file = match fs::File::open(&self.path) {
+            Ok(file) => file,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to open journal {}", self.path.display()));
+            }
+        };
+        let mut header = [0; AGE_HEADER.len()];
+        Ok(file.read_exact(&mut header).is_ok() && header == AGE_HEADER)
+    }
+
+    /// Encrypt the journal file with `key`
+    #[cfg(feature = "encryption")]
+    pub fn lock(&self, key: &Key) -> Result<()> {
+        self.require_lines("locked")?;
+        let _guard = self.write_guard()?;
+        if self.is_locked()? {
+            return Err(anyhow::anyhow!(
+                "Journal {} is already locked",
+                self.path.display()
+            ));
+        }
+
+        let content = match fs::read(&self.path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                return Err(e)
+                    .with_context(|| format!("Failed to read journal {}", self.path.display()));
+            }
+        };
+        self.replace(&key.encrypt(&content)?)
+    }
+
+    /// Decrypt the journal file with `key`, leaving it as plain JSON lines
+    #[cfg(feature = "encryption")]
+    pub fn unlock(&self, key: &Key) -> Result<()> {
+        self.require_lines("unlocked")?;
+        let _guard = self.write_guard()?;
+        if !self.is_locked()? {
+            return Err(anyhow::anyhow!(
+                "Journal {} is not locked",
+                self.path.display()
+            ));
+        }
+
+        let content = fs::read(&self.path)
+            .with_context(|| format!("Failed to read journal {}", self.path.display()))?;
+        self.replace(&key.decrypt(&content)?)
+    }
+
+    #[cfg(feature = "encryption")]
+    fn require_lines(&self, action: &str) -> Result<()> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            return Err(anyhow::anyhow!(
+                "Only a JSON lines journal can be {}, not the SQLite store",
+                action
+            ));
+        }
+        let _ = action;
+        Ok(())
+    }
+
+    /// Write the whole journal file through a temporary file, flushed to
+    /// disk and then renamed over it, so a failure leaves the old one in
+    /// place; callers hold the write guard
+    fn replace(&self, content: &[u8]) -> Result<()> {
+        self.create_parent()?;
+        let temp = self.sibling(".tmp");
+        fs::File::create(&temp)
+            .and_then(|mut file| {
+                file.write_all(content)?;
+                file.sync_all()
+            })
+            .and_then(|_| fs::rename(&temp, &self.path))
+            .with_context(|| format!("Failed to write journal {}", self.path.display()))
+    }
+
+    /// The journal's path with `suffix` added, e.g. `journal.jsonl.lock`
+    fn sibling(&self, suffix: &str) -> PathBuf {
+        let mut path = self.path.clone().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    }
+
+    fn create_parent(&self) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        Ok(())
+    }
+
+    /// Exclusive use of the journal for writing, waiting a while for another
+    /// process that has it
+    fn write_guard(&self) -> Result<WriteGuard> {
+        self.write_guard_within(WRITE_WAIT)
+    }
+
+    fn write_guard_within(&self, wait: Duration) -> Result<WriteGuard> {
+        self.create_parent()?;
+        let path = self.sibling(".lock");
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open journal lock {}", path.display()))?;
+
+        let start = Instant::now();
+        loop {
+            // Called through the trait: std's own `File::try_lock` is newer
+            // than the minimum supported Rust
+            match FileExt::try_lock(&file) {
+                Ok(()) => return Ok(WriteGuard { file }),
+                Err(fs4::TryLockError::WouldBlock) if start.elapsed() < wait => {
+                    std::thread::sleep(Duration::from_millis(50));
+                }
+                Err(fs4::TryLockError::WouldBlock) => {
+                    return Err(anyhow::anyhow!(
+                        "Journal {} is busy: another i-ching process has been writing to it for over {} seconds (it holds {}). Try again once it finishes.",
+                        self.path.display(),
+                        wait.as_secs(),
+                        path.display()
+                    ));
+                }
+                Err(fs4::TryLockError::Error(e)) => {
+                    return Err(e)
+                        .with_context(|| format!("Failed to lock journal {}", path.display()));
+                }
+            }
+        }
+    }
+
+    /// The key for a locked journal: the one given, or one from the
+    /// environment or a prompt
+    #[cfg(feature = "encryption")]
+    fn key(&self) -> Result<Key> {
+        match &self.key {
+            Some(key) => Ok(key.clone()),
+            None => Key::from_env(),
+        }
+    }
+
+    /// Append to a locked journal by re-encrypting it whole; callers hold
+    /// the write guard
+    #[cfg(feature = "encryption")]
+    fn append_locked(&self, entry: &JournalEntry) -> Result<()> {
+        let key = self.key()?;
+        let content = fs::read(&self.path)
+            .with_context(|| format!("Failed to read journal {}", self.path.display()))?;
+        let mut content = key.decrypt(&content)?;
+        content.extend_from_slice(serde_json::to_string(entry)?.as_bytes());
+        content.push(b'\n');
+        self.replace(&key.encrypt(&content)?)
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn append_locked(&self, _entry: &JournalEntry) -> Result<()> {
+        Err(self.locked_error())
+    }
+
+    /// The journal file's text, decrypted if it is locked
+    fn plaintext(&self, content: Vec<u8>) -> Result<String> {
+        let content = if content.starts_with(AGE_HEADER) {
+            #[cfg(feature = "encryption")]
+            {
+                self.key()?.decrypt(&content)?
+            }
+            #[cfg(not(feature = "encryption"))]
+            return Err(self.locked_error());
+        } else {
+            content
+        };
+        String::from_utf8(content)
+            .with_context(|| format!("Journal {} is not valid UTF-8", self.path.display()))
+    }
+
+    /// `content` encrypted again for a locked journal
+    fn seal(&self, content: Vec<u8>) -> Result<Vec<u8>> {
+        #[cfg(feature = "encryption")]
+        {
+            self.key()?.encrypt(&content)
+        }
+        #[cfg(not(feature = "encryption"))]
+        {
+            let _ = content;
+            Err(self.locked_error())
+        }
+    }
+
+    #[cfg(not(feature = "encryption"))]
+    fn locked_error(&self) -> anyhow::Error {
+        anyhow::anyhow!(
+            "Journal {} is locked; rebuild with the `encryption` feature to use it",
+            self.path.display()
+        )
+    }
+
+    /// Record an entry at the end of the journal, creating the file if needed
+    pub fn append(&self, entry: &JournalEntry) -> Result<()> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            return Store::open(&self.path)?.append(entry);
+        }
+
+        let _guard = self.write_guard()?;
+        if self.is_locked()? {
+            return self.append_locked(entry);
+        }
+
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .with_context(|| format!("Failed to open journal {}", self.path.display()))?;
+        // One write of the whole line, so a reader never sees part of it
+        file.write_all((serde_json::to_string(entry)? + "\n").as_bytes())
+            .with_context(|| format!("Failed to write journal {}", self.path.display()))?;
+
+        Ok(())
+    }
+
+    /// All entries in the order they were recorded (empty if no journal exists yet)
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            return Store::open(&self.path)?.entries();
+        }
+
+        if !self.path.exists() {
+            return Ok(Vec::new());
+        }
+
+        let content = fs::read(&self.path)
+            .with_context(|| format!("Failed to read journal {}", self.path.display()))?;
+        self.parse_entries(&self.plaintext(content)?)
+    }
+
+    /// Record `outcome` for entry `number` (counting from 1 in the order
+    /// recorded, as `journal list` numbers them), returning the entry
+    ///
+    /// A JSON lines journal is rewritten in full, re-encrypted if locked.
+    pub fn set_outcome(&self, number: usize, outcome: Outcome) -> Result<JournalEntry> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            return Store::open(&self.path)?.set_outcome(number, outcome);
+        }
+
+        let _guard = self.write_guard()?;
+        let mut entries = self.entries()?;
+        let count = entries.len();
+        let entry = number
+            .checked_sub(1)
+            .and_then(|i| entries.get_mut(i))
+            .ok_or_else(|| no_entry(number, count))?;
+        entry.outcome = Some(outcome);
+        let updated = entry.clone();
+        self.rewrite(&entries)?;
+        Ok(updated)
+    }
+
+    /// Rewrite entries whose readings were stored in an older format (see
+    /// [`schema`](crate::core::schema)) in the current one
+    ///
+    /// Nothing is written if every entry is current, or with `dry_run`.
+    pub fn migrate(&self, dry_run: bool) -> Result<Migration> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            return Store::open(&self.path)?.migrate(dry_run);
+        }
+
+        if !self.path.exists() {
+            return Ok(Migration::default());
+        }
+        let _guard = self.write_guard()?;
+        let content = fs::read(&self.path)
+            .with_context(|| format!("Failed to read journal {}", self.path.display()))?;
+        let content = self.plaintext(content)?;
+        let entries = self.parse_entries(&content)?;
+        let mut upgraded = 0;
+        for line in content.lines().filter(|line| !line.trim().is_empty()) {
+            if is_outdated(line)? {
+                upgraded += 1;
+            }
+        }
+
+        if upgraded > 0 && !dry_run {
+            self.rewrite(&entries)?;
+        }
+        Ok(Migration {
+            entries: entries.len(),
+            upgraded,
+        })
+    }
+
+    /// Write `entries` over the JSON lines journal, encrypted again if it's
+    /// locked
+    fn rewrite(&self, entries: &[JournalEntry]) -> Result<()> {
+        let mut content = String::new();
+        for entry in entries {
+            content.push_str(&serde_json::to_string(entry)?);
+            content.push('\n');
+        }
+        let content = content.into_bytes();
+        if self.is_locked()? {
+            self.replace(&self.seal(content)?)
+        } else {
+            self.replace(&content)
+        }
+    }
+
+    /// Entries whose revisit date is on or before `today`, oldest first
+    pub fn due(&self, today: NaiveDate) -> Result<Vec<JournalEntry>> {
+        let mut entries = self.entries()?;
+        entries.retain(|entry| entry.revisit_on.is_some_and(|date| date <= today));
+        Ok(entries)
+    }
+
+    /// Entries whose question, context, outcome note, or hexagram names
+    /// match every word of `query`, allowing for inflections and typos, best
+    /// match first (see `search`)
+    ///
+    /// The SQLite store's full-text index picks out the entries to rank,
+    /// unless it finds none, when every entry is ranked so a typo can still
+    /// match.
+    pub fn search(&self, query: &str) -> Result<Vec<Hit>> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            let store = Store::open(&self.path)?;
+            let mut entries = store.search(query)?;
+            if entries.is_empty() {
+                entries = store.entries()?;
+            }
+            return Ok(search::search(entries, query, &store.data()?));
+        }
+
+        let data = IChingData::load()
+            .map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+        Ok(search::search(self.entries()?, query, &data))
+    }
+
+    /// Like `append`, without blocking the async runtime
+    #[cfg(feature = "async")]
+    pub async fn append_async(&self, entry: &JournalEntry) -> Result<()> {
+        use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            let (path, entry) = (self.path.clone(), entry.clone());
+            return tokio::task::spawn_blocking(move || Store::open(&path)?.append(&entry)).await?;
+        }
+
+        // Waiting for the write guard blocks, so wait off the runtime
+        let journal = self.clone();
+        let _guard = tokio::task::spawn_blocking(move || journal.write_guard()).await??;
+
+        // Checked under the guard, so `journal lock` can't encrypt the file
+        // between the check and the write
+        let mut header = [0; AGE_HEADER.len()];
+        let locked = match tokio::fs::File::open(&self.path).await {
+            Ok(mut file) => file.read_exact(&mut header).await.is_ok() && header == AGE_HEADER,
+            Err(_) => false,
+        };
+        if locked {
+            // Re-encrypting rewrites the whole file, so do it off the runtime
+            let (journal, entry) = (self.clone(), entry.clone());
+            return tokio::task::spawn_blocking(move || journal.append_locked(&entry)).await?;
+        }
+
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await
+            .with_context(|| format!("Failed to open journal {}", self.path.display()))?;
+        let line = serde_json::to_string(entry)? + "\n";
+        file.write_all(line.as_bytes()).await?;
+
+        Ok(())
+    }
+
+    /// Like `entries`, without blocking the async runtime
+    #[cfg(feature = "async")]
+    pub async fn entries_async(&self) -> Result<Vec<JournalEntry>> {
+        #[cfg(feature = "sqlite")]
+        if self.backend == Backend::Sqlite {
+            let path = self.path.clone();
+            return tokio::task::spawn_blocking(move || Store::open(&path)?.entries()).await?;
+        }
+
+        if !tokio::fs::try_exists(&self.path).await? {
+            return Ok(Vec::new());
+        }
+
+        let content = tokio::fs::read(&self.path)
+            .await
+            .with_context(|| format!("Failed to read journal {}", self.path.display()))?;
+        self.parse_entries(&self.plaintext(content)?)
+    }
+
+    fn parse_entries(&self, content: &str) -> Result<Vec<JournalEntry>> {
+        content
+            .lines()
+            .enumerate()
+            .filter(|(_, line)| !line.trim().is_empty())
+            .map(|(i, line)| {
+                serde_json::from_str(line).with_context(|| {
+                    format!(
+                        "Invalid journal entry on line {} of {}",
+                        i + 1,
+                        self.path.display()
+                    )
+                })
+            })
+            .collect()
+    }
+}
+
+/// Exclusive use of a JSON lines journal for writing, until dropped
+///
+/// The lock is advisory and belongs to the open lock file, so the system
+/// releases it when the process exits, however it exits.
+struct WriteGuard {
+    file: fs::File,
+}
+
+impl Drop for WriteGuard {
+    fn drop(&mut self) {
+        let _ = FileExt::unlock(&self.file);
+    }
+}
+
+/// What `Journal::migrate` found
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Migration {
+    /// Entries in the journal
+    pub entries: usize,
+    /// Entries stored in an older format, and upgraded unless it was a dry run
+    pub upgraded: usize,
+}
+
+/// Whether a stored entry's reading predates the current format
+pub(crate) fn is_outdated(json: &str) -> Result<bool> {
+    let entry: serde_json::Value = serde_json::from_str(json)?;
+    Ok(schema::version(&entry["reading"])? < schema::CURRENT)
+}
+
+/// The error for an entry number outside a journal of `count` entries
+pub(crate) fn no_entry(number: usize, count: usize) -> anyhow::Error {
+    anyhow::anyhow!(
+        "No journal entry {}; the journal has {} (see `journal list`)",
+        number,
+        count
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Diviner;
+
+    fn temp_journal(name: &str) -> Journal {
+        let path = env::temp_dir().join(format!(
+            "i-ching-{}-{}-{}.jsonl",
+            name,
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        Journal::at(path)
+    }
+
+    #[test]
+    fn test_missing_journal_is_empty() {
+        let journal = temp_journal("missing");
+        assert!(journal.entries().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_append_and_read_back() {
+        let journal = temp_journal("append");
+        let diviner = Diviner::new();
+
+        for question in ["First", "Second"] {
+            let reading = diviner
+                .cast_reading_from_numbers([7, 8, 9, 6, 7, 8], Some(question.to_string()))
+                .unwrap();
+            journal.append(&JournalEntry::new(reading)).unwrap();
+        }
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reading.question(), Some("First"));
+        assert_eq!(entries[1].reading.traditional_numbers(), [7, 8, 9, 6, 7, 8]);
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[test]
+    fn test_set_outcome() {
+        let journal = temp_journal("outcome");
+        for input in ["11", "32->34"] {
+            let reading: Reading = input.parse().unwrap();
+            journal.append(&JournalEntry::new(reading)).unwrap();
+        }
+
+        let outcome = Outcome {
+            resolved: true,
+            accuracy: Some(4),
+            resolved_on: NaiveDate::from_ymd_opt(2026, 10, 1),
+            note: Some("Got the job".to_string()),
+        };
+        let entry = journal.set_outcome(2, outcome.clone()).unwrap();
+        assert_eq!(entry.reading.primary_hexagram(), 32);
+
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries[0].outcome, None);
+        assert_eq!(entries[1].outcome, Some(outcome.clone()));
+        assert!(journal.set_outcome(3, outcome.clone()).is_err());
+        assert!(journal.set_outcome(0, outcome).is_err());
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[test]
+    fn test_migrate() {
+        let journal = temp_journal("migrate");
+        journal
+            .append(&JournalEntry::new("11".parse().unwrap()))
+            .unwrap();
+        // An entry from before readings carried a version
+        let mut old = serde_json::to_value(JournalEntry::new("32->34".parse().unwrap())).unwrap();
+        old["reading"].as_object_mut().unwrap().remove("version");
+        let mut file = OpenOptions::new()
+            .append(true)
+            .open(journal.path())
+            .unwrap();
+        writeln!(file, "{}", old).unwrap();
+
+        let before = fs::read_to_string(journal.path()).unwrap();
+        let found = journal.migrate(true).unwrap();
+        assert_eq!((found.entries, found.upgraded), (2, 1));
+        assert_eq!(fs::read_to_string(journal.path()).unwrap(), before);
+
+        assert_eq!(journal.migrate(false).unwrap().upgraded, 1);
+        assert_eq!(journal.migrate(false).unwrap().upgraded, 0);
+        assert_eq!(journal.entries().unwrap()[1].reading.primary_hexagram(), 32);
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[test]
+    fn test_due() {
+        let journal = temp_journal("due");
+        let reading: Reading = "9,7,7,7,7,7".parse().unwrap(); // 1 day
+        let entry = JournalEntry::new(reading);
+        let cast = entry.timestamp.with_timezone(&Local).date_naive();
+        journal.append(&entry).unwrap();
+
+        assert!(journal.due(cast).unwrap().is_empty());
+        let tomorrow = cast.succ_opt().unwrap();
+        assert_eq!(journal.due(tomorrow).unwrap().len(), 1);
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[test]
+    fn test_search() {
+        let journal = temp_journal("search");
+        check_search(&journal);
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    // The same search through the store's full-text index
+    #[cfg(feature = "sqlite")]
+    #[test]
+    fn test_search_sqlite() {
+        let path =
+            env::temp_dir().join(format!("i-ching-journal-search-{}.db", std::process::id()));
+        check_search(&Journal::sqlite(&path));
+        fs::remove_file(path).unwrap();
+    }
+
+    fn check_search(journal: &Journal) {
+        for (input, question) in [("11", "Should I take the job?"), ("3", "Move house?")] {
+            let reading: Reading = input.parse().unwrap();
+            let reading = reading.with_question(Some(question.to_string()));
+            journal.append(&JournalEntry::new(reading)).unwrap();
+        }
+
+        let found = |query| journal.search(query).unwrap().len();
+        assert_eq!(found("JOB"), 1);
+        assert_eq!(found("job advance"), 1); // 11 is Advance
+        assert_eq!(found("job house"), 0);
+        assert_eq!(found("jo"), 0);
+        assert_eq!(found("moving houses"), 1);
+        assert_eq!(found("hause"), 1);
+    }
+
+    #[test]
+    fn test_write_guard() {
+        let journal = temp_journal("guard");
+        let guard = journal.write_guard().unwrap();
+        let err = journal
+            .write_guard_within(Duration::ZERO)
+            .err()
+            .unwrap()
+            .to_string();
+        assert!(err.contains("is busy"), "{}", err);
+
+        // Another writer waits its turn rather than failing
+        let writer = {
+            let journal = journal.clone();
+            std::thread::spawn(move || journal.append(&JournalEntry::new("1".parse().unwrap())))
+        };
+        std::thread::sleep(Duration::from_millis(100));
+        assert!(journal.entries().unwrap().is_empty());
+        drop(guard);
+        writer.join().unwrap().unwrap();
+        assert_eq!(journal.entries().unwrap().len(), 1);
+
+        fs::remove_file(journal.path()).unwrap();
+        fs::remove_file(journal.sibling(".lock")).unwrap();
+    }
+
+    #[test]
+    fn test_recipient() {
+        let reading: Reading = "11".parse().unwrap();
+        let entry = JournalEntry::new(reading.clone()).with_recipient(Some(" Alice ".to_string()));
+        assert_eq!(entry.recipient.as_deref(), Some("Alice"));
+        assert!(entry.is_for("alice"));
+        assert!(!entry.is_for("bob"));
+
+        let json = serde_json::to_string(&entry).unwrap();
+        let entry: JournalEntry = serde_json::from_str(&json).unwrap();
+        assert!(entry.is_for("ALICE"));
+
+        let own = JournalEntry::new(reading).with_recipient(Some("  ".to_string()));
+        assert_eq!(own.recipient, None);
+        assert!(!serde_json::to_string(&own).unwrap().contains("recipient"));
+    }
+
+    #[cfg(feature = "encryption")]
+    #[test]
+    fn test_lock_append_unlock() {
+        let key = Key::Identity(age::x25519::Identity::generate());
+        let journal = temp_journal("lock").with_key(key.clone());
+        let reading: Reading = "11".parse().unwrap();
+        let reading = reading.with_question(Some("Private?".to_string()));
+        journal.append(&JournalEntry::new(reading)).unwrap();
+
+        journal.lock(&key).unwrap();
+        assert!(journal.is_locked().unwrap());
+        assert!(journal.lock(&key).is_err());
+        assert!(
+            !fs::read_to_string(journal.path())
+                .unwrap_or_default()
+                .contains("Private")
+        );
+
+        journal
+            .append(&JournalEntry::new("3".parse().unwrap()))
+            .unwrap();
+        let entries = journal.entries().unwrap();
+        assert_eq!(entries.len(), 2);
+        assert_eq!(entries[0].reading.question(), Some("Private?"));
+
+        let outcome = Outcome {
+            resolved: false,
+            accuracy: None,
+            resolved_on: None,
+            note: None,
+        };
+        journal.set_outcome(1, outcome).unwrap();
+        assert!(journal.is_locked().unwrap());
+        assert!(journal.entries().unwrap()[0].outcome.is_some());
+
+        let stranger = Key::Identity(age::x25519::Identity::generate());
+        assert!(
+            Journal::at(journal.path())
+                .with_key(stranger)
+                .entries()
+                .is_err()
+        );
+
+        journal.unlock(&key).unwrap();
+        assert!(!journal.is_locked().unwrap());
+        assert_eq!(Journal::at(journal.path()).entries().unwrap().len(), 2);
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[cfg(feature = "async")]
+    #[tokio::test]
+    async fn test_async_append_and_read_back() {
+        let journal = temp_journal("async");
+        assert!(journal.entries_async().await.unwrap().is_empty());
+
+        let reading: Reading = "1".parse().unwrap();
+        journal
+            .append_async(&JournalEntry::new(reading))
+            .await
+            .unwrap();
+
+        let entries = journal.entries_async().await.unwrap();
+        assert_eq!(entries.len(), 1);
+        assert_eq!(entries[0].reading.primary_hexagram(), 1);
+        assert_eq!(journal.entries().unwrap().len(), 1);
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+
+    #[cfg(all(feature = "async", feature = "encryption"))]
+    #[tokio::test]
+    async fn test_async_append_to_locked() {
+        let key = Key::Identity(age::x25519::Identity::generate());
+        let journal = temp_journal("async-lock").with_key(key.clone());
+        journal
+            .append(&JournalEntry::new("1".parse().unwrap()))
+            .unwrap();
+        journal.lock(&key).unwrap();
+
+        journal
+            .append_async(&JournalEntry::new("2".parse().unwrap()))
+            .await
+            .unwrap();
+        assert!(journal.is_locked().unwrap());
+        assert_eq!(journal.entries_async().await.unwrap().len(), 2);
+
+        fs::remove_file(journal.path()).unwrap();
+    }
+}
@@ -0,0 +1,295 @@
+//! JSON output: the reading schema ([`JsonReading`]), and its layout and key
+//! casing for `--json-style` and `--json-case`
+//!
+//! The JSON types keep their snake_case field names; camelCase is applied to
+//! the serialized value (whose keys keep their order), so every field of
+//! every nested type follows it without a second set of types to keep in
+//! step.
+
+use crate::almanac::Almanac;
+use crate::core::Reading;
+use crate::core::data::{Hexagram, IChingData, LineRef};
+use crate::core::reading::Line;
+use crate::interpretation::structure::{self, LinePlace};
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+
+/// Whitespace in JSON output
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonStyle {
+    /// Indented, one field per line
+    #[default]
+    Pretty,
+    /// All on one line
+    Compact,
+}
+
+/// Casing of field names in JSON output
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum JsonCase {
+    /// e.g. `primary_hexagram`
+    #[default]
+    Snake,
+    /// e.g. `primaryHexagram`, as JavaScript tends to want
+    Camel,
+}
+
+/// How to write JSON output
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonOptions {
+    pub style: JsonStyle,
+    pub case: JsonCase,
+}
+
+impl JsonOptions {
+    /// `value` as JSON in this style and casing, without a trailing newline
+    pub fn to_string(&self, value: &impl Serialize) -> serde_json::Result<String> {
+        let value = match self.case {
+            JsonCase::Snake => serde_json::to_value(value)?,
+            JsonCase::Camel => camel_keys(serde_json::to_value(value)?),
+        };
+        match self.style {
+            JsonStyle::Pretty => serde_json::to_string_pretty(&value),
+            JsonStyle::Compact => serde_json::to_string(&value),
+        }
+    }
+}
+
+/// `value` with every object key in camelCase
+fn camel_keys(value: Value) -> Value {
+    match value {
+        Value::Object(object) => Value::Object(
+            object
+                .into_iter()
+                .map(|(key, value)| (camel_case(&key), camel_keys(value)))
+                .collect(),
+        ),
+        Value::Array(values) => Value::Array(values.into_iter().map(camel_keys).collect()),
+        value => value,
+    }
+}
+
+/// `snake_case` as `snakeCase`
+fn camel_case(key: &str) -> String {
+    let mut result = String::with_capacity(key.len());
+    let mut upper = false;
+    for c in key.chars() {
+        if c == '_' && !result.is_empty() {
+            upper = true;
+        } else if upper {
+            result.extend(c.to_uppercase());
+            upper = false;
+        } else {
+            result.push(c);
+        }
+    }
+    result
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonHexagram {
+    pub number: u8,
+    pub name: String,
+    pub chinese: String,
+    pub pinyin: String,
+    pub unicode: String,
+    pub description: String,
+    pub judgment: JsonJudgment,
+    pub image: JsonImage,
+}
+
+impl From<&Hexagram> for JsonHexagram {
+    fn from(hexagram: &Hexagram) -> Self {
+        JsonHexagram {
+            number: hexagram.number,
+            name: hexagram.name.clone(),
+            chinese: hexagram.chinese.clone(),
+            pinyin: hexagram.pinyin.clone(),
+            unicode: hexagram.unicode.clone(),
+            description: hexagram.description.clone(),
+            judgment: JsonJudgment {
+                text: hexagram.judgment.text.clone(),
+                commentary: hexagram.judgment.commentary.clone(),
+            },
+            image: JsonImage {
+                text: hexagram.image.text.clone(),
+                commentary: hexagram.image.commentary.clone(),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonJudgment {
+    pub text: String,
+    pub commentary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonImage {
+    pub text: String,
+    pub commentary: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonLineInterpretation {
+    pub position: u8,
+    pub text: String,
+    pub comments: String,
+    /// Lines whose texts quote or mirror this one
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub see_also: Vec<JsonCrossReference>,
+}
+
+/// A line linked to another by a cross-reference
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JsonCrossReference {
+    pub hexagram: u8,
+    pub line: u8,
+    pub name: String,
+    /// What the two lines share
+    pub note: String,
+}
+
+impl JsonCrossReference {
+    /// The lines cross-referenced from `line`
+    pub(crate) fn of(line: LineRef, data: &IChingData) -> Vec<JsonCrossReference> {
+        data.cross_references(line)
+            .filter_map(|(other, note)| {
+                let hexagram = data.get_hexagram(other.hexagram)?;
+                Some(JsonCrossReference {
+                    hexagram: other.hexagram,
+                    line: other.line,
+                    name: hexagram.name.clone(),
+                    note: note.to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JsonReading {
+    pub question: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    pub lines: [u8; 6],
+    pub primary_hexagram: JsonHexagram,
+    pub changing_lines: Vec<JsonLineInterpretation>,
+    pub transformed_hexagram: Option<JsonHexagram>,
+    pub upper_trigram: [String; 3],
+    pub lower_trigram: [String; 3],
+    /// Place, centrality, and correspondence of each primary line, bottom first
+    pub structure: [LinePlace; 6],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub almanac: Option<Almanac>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    /// The date hashed with the question, for `--method question-hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question_hash: Option<chrono::NaiveDate>,
+    /// The changing line marked as central, from `--focus-line`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_line: Option<u8>,
+}
+
+/// Create a JSON representation of a reading with full meanings
+pub fn json_reading_with_data(reading: &Reading, data: &IChingData) -> Result<JsonReading> {
+    let hexagram_number = reading.primary_hexagram();
+    let hexagram = data
+        .get_hexagram(hexagram_number)
+        .ok_or_else(|| anyhow::anyhow!("Hexagram {} not found", hexagram_number))?;
+
+    let primary_hexagram = JsonHexagram::from(hexagram);
+
+    let changing_lines: Vec<JsonLineInterpretation> = reading
+        .changing_line_positions()
+        .into_iter()
+        .filter_map(|line_pos| {
+            data.get_line_interpretation(hexagram_number, line_pos)
+                .map(|interp| JsonLineInterpretation {
+                    position: line_pos,
+                    text: interp.text.clone(),
+                    comments: interp.comments.clone(),
+                    see_also: JsonCrossReference::of(
+                        LineRef {
+                            hexagram: hexagram_number,
+                            line: line_pos,
+                        },
+                        data,
+                    ),
+                })
+        })
+        .collect();
+
+    let transformed_hexagram = reading
+        .transformed_hexagram_number()
+        .and_then(|number| data.get_hexagram(number))
+        .map(JsonHexagram::from);
+
+    let polarity_to_string = |polarity| match polarity {
+        crate::core::reading::Polarity::Yang => "Yang".to_string(),
+        crate::core::reading::Polarity::Yin => "Yin".to_string(),
+    };
+
+    let upper_trigram = reading.upper_trigram().lines().map(polarity_to_string);
+    let lower_trigram = reading.lower_trigram().lines().map(polarity_to_string);
+
+    Ok(JsonReading {
+        question: reading.question().map(str::to_string),
+        context: reading.context().map(str::to_string),
+        lines: reading.traditional_numbers(),
+        primary_hexagram,
+        changing_lines,
+        transformed_hexagram,
+        upper_trigram,
+        lower_trigram,
+        structure: structure::analyze(reading),
+        almanac: None,
+        recipient: None,
+        question_hash: None,
+        focus_line: None,
+    })
+}
+
+/// The reading a `--format json` reading describes
+pub(crate) fn reading_from_json(json: &JsonReading) -> Result<Reading> {
+    let lines = json
+        .lines
+        .iter()
+        .map(|&n| Line::from_traditional_number(n))
+        .collect::<Result<Vec<Line>>>()?;
+    let lines: [Line; 6] = lines
+        .try_into()
+        .map_err(|_| anyhow::anyhow!("A reading needs exactly six lines"))?;
+    Ok(Reading::new(lines, json.question.clone()).with_context(json.context.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_json_options() {
+        let value = serde_json::json!({
+            "primary_hexagram": {"number": 32, "upper_trigram": "Zhen"},
+            "changing_lines": [{"line_number": 1}],
+            "lines": {"1": "text"}
+        });
+        let options = JsonOptions {
+            style: JsonStyle::Compact,
+            case: JsonCase::Camel,
+        };
+        assert_eq!(
+            options.to_string(&value).unwrap(),
+            r#"{"primaryHexagram":{"number":32,"upperTrigram":"Zhen"},"changingLines":[{"lineNumber":1}],"lines":{"1":"text"}}"#
+        );
+        let pretty = JsonOptions::default().to_string(&value).unwrap();
+        assert!(pretty.contains("\n  \"primary_hexagram\": {\n"));
+        assert_eq!(camel_case("_private"), "_private");
+    }
+}
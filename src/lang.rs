@@ -0,0 +1,140 @@
+//! Hexagram names and line positions in English or Chinese, for `--lang`
+//!
+//! `zh` puts each hexagram's Chinese name first and calls lines by their
+//! traditional names, which join the line's number (九 for yang, 六 for yin)
+//! to its place: 初九 for a yang bottom line, 六二 for a yin second line, 上六
+//! for a yin top line. The texts stay in Wilhelm's English unless a Chinese
+//! corpus is installed (see [`translations::CHINESE`](crate::translations::CHINESE)).
+
+use crate::core::Reading;
+use crate::core::data::Hexagram;
+use crate::core::reading::{Line, Polarity};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+/// Chinese numerals for the middle four places, second to fifth
+const PLACES: [&str; 4] = ["二", "三", "四", "五"];
+
+/// Language of hexagram names and line positions
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Lang {
+    /// English names, lines numbered 1 (bottom) to 6
+    #[default]
+    En,
+    /// Chinese names first, lines by their traditional names
+    Zh,
+}
+
+impl Lang {
+    /// The language a question is written in: Chinese if it has at least as
+    /// many Chinese characters as other letters, else English
+    pub fn detect(text: &str) -> Lang {
+        let (han, other) =
+            text.chars()
+                .filter(|c| c.is_alphabetic())
+                .fold((0, 0), |(han, other), c| {
+                    if is_han(c) {
+                        (han + 1, other)
+                    } else {
+                        (han, other + 1)
+                    }
+                });
+        if han > 0 && han >= other {
+            Lang::Zh
+        } else {
+            Lang::En
+        }
+    }
+
+    /// e.g. "Long Lasting", or "恆 Long Lasting"
+    pub fn hexagram_name(self, hexagram: &Hexagram) -> String {
+        match self {
+            Lang::En => hexagram.name.clone(),
+            Lang::Zh => format!("{} {}", hexagram.chinese, hexagram.name),
+        }
+    }
+
+    /// The line at `position` (1-6), e.g. "3", or "九三"
+    pub fn line_position(self, position: u8, line: &Line) -> String {
+        match self {
+            Lang::En => position.to_string(),
+            Lang::Zh => line_name(position, line),
+        }
+    }
+
+    /// Heading for a line's text, e.g. "Line 3", or "九三"
+    pub fn line_label(self, position: u8, line: &Line) -> String {
+        match self {
+            Lang::En => format!("Line {}", position),
+            Lang::Zh => line_name(position, line),
+        }
+    }
+
+    /// The reading's changing lines, e.g. "[1, 4]", or "初六、九四"
+    pub fn changing_lines(self, reading: &Reading) -> String {
+        let positions = reading.changing_line_positions();
+        match self {
+            Lang::En => format!("{:?}", positions),
+            Lang::Zh => positions
+                .iter()
+                .map(|&position| line_name(position, &reading.lines()[usize::from(position - 1)]))
+                .collect::<Vec<_>>()
+                .join("、"),
+        }
+    }
+}
+
+/// Whether `c` is a CJK ideograph, in the main or an extension block
+fn is_han(c: char) -> bool {
+    matches!(c, '\u{4E00}'..='\u{9FFF}' | '\u{3400}'..='\u{4DBF}' | '\u{F900}'..='\u{FAFF}' | '\u{20000}'..='\u{2FA1F}')
+}
+
+/// Traditional name of a line: its number and its place, bottom and top
+/// places first
+fn line_name(position: u8, line: &Line) -> String {
+    let number = match line.polarity {
+        Polarity::Yang => "九",
+        Polarity::Yin => "六",
+    };
+    match position {
+        1 => format!("初{}", number),
+        6 => format!("上{}", number),
+        _ => format!("{}{}", number, PLACES[usize::from(position - 2)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::IChingData;
+
+    #[test]
+    fn test_lang() {
+        let data = IChingData::load().unwrap();
+        let hexagram = data.get_hexagram(32).unwrap();
+        assert_eq!(Lang::En.hexagram_name(hexagram), "Long Lasting");
+        assert_eq!(
+            Lang::Zh.hexagram_name(hexagram),
+            format!("{} Long Lasting", hexagram.chinese)
+        );
+
+        let reading: Reading = "6,7,8,9,7,6".parse().unwrap();
+        let names: Vec<String> = reading
+            .lines()
+            .iter()
+            .zip(1..)
+            .map(|(line, position)| Lang::Zh.line_position(position, line))
+            .collect();
+        assert_eq!(names, ["初六", "九二", "六三", "九四", "九五", "上六"]);
+        assert_eq!(Lang::Zh.changing_lines(&reading), "初六、九四、上六");
+        assert_eq!(Lang::En.changing_lines(&reading), "[1, 4, 6]");
+        assert_eq!(Lang::En.line_label(4, &reading.lines()[3]), "Line 4");
+
+        assert_eq!(Lang::detect("我应该接受这份工作吗？"), Lang::Zh);
+        assert_eq!(Lang::detect("Should I study 易经?"), Lang::En);
+        assert_eq!(Lang::detect("「易经」 job?"), Lang::En);
+        assert_eq!(Lang::detect("何时 move?"), Lang::En);
+        assert_eq!(Lang::detect(""), Lang::En);
+    }
+}
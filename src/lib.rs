@@ -1,5 +1,68 @@
+pub mod almanac;
+pub mod animation;
+pub mod bagua;
+pub mod banner;
+#[cfg(feature = "tui")]
+pub mod browse;
+pub mod card;
+pub mod casting;
 pub mod cli;
+pub mod config;
+pub mod contemplation;
 pub mod core;
+pub mod daemon;
+pub mod dashboard;
+pub mod diff;
+pub mod drafts;
+pub mod dump;
+pub mod embedding;
+#[cfg(feature = "ffi")]
+pub mod ffi;
+pub mod format;
+pub mod fortune;
+#[cfg(feature = "encryption")]
+pub mod encryption;
+pub mod glossary;
+pub mod glyphs;
+pub mod group;
+pub mod ical;
+pub mod importer;
+pub mod interpretation;
+pub mod json;
+pub mod journal;
+pub mod lang;
+pub mod links;
+#[cfg(feature = "llm")]
+pub mod llm;
+pub mod manifest;
+pub mod motd;
+pub mod notes;
+pub mod notify;
+pub mod output;
+pub mod pdf;
+pub mod relations;
+pub mod remote;
+pub mod rendered;
+pub mod report;
+pub mod search;
+pub mod selftest;
+pub mod shichen;
+pub mod speech;
+pub mod stats;
+pub mod status;
+#[cfg(feature = "sqlite")]
+pub mod store;
+pub mod system_log;
+pub mod systemd;
+#[cfg(feature = "testing")]
+pub mod testing;
+pub mod tones;
+pub mod translations;
+#[cfg(feature = "self-update")]
+pub mod update;
+pub mod watchlist;
+pub mod wizard;
+pub mod worksheet;
 
 pub use core::data::IChingData;
 pub use core::{Diviner, Reading};
@@ -0,0 +1,161 @@
+//! OSC 8 terminal hyperlinks from hexagram names and lines to reference pages
+//!
+//! Terminals that understand OSC 8 show the wrapped text as a clickable link;
+//! the rest print the text and ignore the escapes, but the CLI only emits them
+//! when stdout is a terminal that's known to support them.
+
+use crate::core::data::Hexagram;
+use serde::Deserialize;
+use std::env;
+use std::io::{self, IsTerminal};
+
+/// Wikipedia's table of hexagrams, anchored at each row
+const DEFAULT_HEXAGRAM_URL: &str =
+    "https://en.wikipedia.org/wiki/List_of_hexagrams_of_the_I_Ching#Hexagram_{number}";
+
+/// Link targets, from `[links]` in config.toml
+///
+/// Templates may use `{number}`, `{name}`, and `{chinese}`; line templates
+/// also `{line}` (1-6).
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Links {
+    /// Link names in terminals that support it
+    pub enabled: bool,
+    /// Page for a hexagram
+    pub hexagram: String,
+    /// Page for one of its lines (default: the hexagram's page)
+    pub line: Option<String>,
+}
+
+impl Default for Links {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            hexagram: DEFAULT_HEXAGRAM_URL.to_string(),
+            line: None,
+        }
+    }
+}
+
+impl Links {
+    /// These links, if they're enabled and stdout can show them
+    pub fn active(self) -> Option<Self> {
+        (self.enabled && io::stdout().is_terminal() && supported()).then_some(self)
+    }
+
+    /// `text` linked to `hexagram`'s page
+    pub fn hexagram(&self, hexagram: &Hexagram, text: &str) -> String {
+        hyperlink(&expand(&self.hexagram, hexagram, None), text)
+    }
+
+    /// `text` linked to the page for line `position` of `hexagram`
+    pub fn line(&self, hexagram: &Hexagram, position: u8, text: &str) -> String {
+        let template = self.line.as_ref().unwrap_or(&self.hexagram);
+        hyperlink(&expand(template, hexagram, Some(position)), text)
+    }
+}
+
+/// `text` wrapped in an OSC 8 hyperlink to `url`
+pub fn hyperlink(url: &str, text: &str) -> String {
+    format!("\x1b]8;;{}\x1b\\{}\x1b]8;;\x1b\\", url, text)
+}
+
+/// Guess whether the terminal renders OSC 8 hyperlinks
+pub fn supported() -> bool {
+    supported_from(|name| env::var(name).ok())
+}
+
+/// Detection over an environment lookup (split out for testing)
+///
+/// `FORCE_HYPERLINK` decides outright, as in other tools; otherwise the
+/// terminal has to identify itself as one known to support links.
+fn supported_from(var: impl Fn(&str) -> Option<String>) -> bool {
+    if let Some(force) = var("FORCE_HYPERLINK") {
+        return force != "0";
+    }
+    if var("TERM").is_some_and(|term| term == "dumb" || term == "linux") {
+        return false;
+    }
+    if var("DOMTERM").is_some() || var("KITTY_WINDOW_ID").is_some() || var("WT_SESSION").is_some() {
+        return true;
+    }
+    if var("VTE_VERSION")
+        .and_then(|version| version.parse::<u32>().ok())
+        .is_some_and(|version| version >= 5000)
+    {
+        return true;
+    }
+    var("TERM_PROGRAM").is_some_and(|program| {
+        ["iTerm.app", "WezTerm", "vscode", "ghostty", "Hyper"].contains(&program.as_str())
+    })
+}
+
+/// `template` with its placeholders filled in, the name percent-encoded
+fn expand(template: &str, hexagram: &Hexagram, line: Option<u8>) -> String {
+    let mut url = template
+        .replace("{number}", &hexagram.number.to_string())
+        .replace("{name}", &encode(&hexagram.name))
+        .replace("{chinese}", &encode(&hexagram.chinese));
+    if let Some(line) = line {
+        url = url.replace("{line}", &line.to_string());
+    }
+    url
+}
+
+/// Percent-encode everything but unreserved URL characters
+fn encode(text: &str) -> String {
+    let mut encoded = String::with_capacity(text.len());
+    for byte in text.bytes() {
+        if byte.is_ascii_alphanumeric() || b"-._~".contains(&byte) {
+            encoded.push(char::from(byte));
+        } else {
+            encoded.push_str(&format!("%{:02X}", byte));
+        }
+    }
+    encoded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::IChingData;
+    use std::collections::HashMap;
+
+    fn supported(vars: &[(&str, &str)]) -> bool {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        supported_from(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_links() {
+        let data = IChingData::load().unwrap();
+        let hexagram = data.get_hexagram(32).unwrap();
+        let links = Links::default();
+        assert_eq!(
+            links.hexagram(hexagram, "Long Lasting"),
+            "\x1b]8;;https://en.wikipedia.org/wiki/List_of_hexagrams_of_the_I_Ching#Hexagram_32\x1b\\Long Lasting\x1b]8;;\x1b\\"
+        );
+
+        let links = Links {
+            line: Some("https://example.com/{number}/{name}#line-{line}".to_string()),
+            ..Links::default()
+        };
+        assert!(
+            links
+                .line(hexagram, 3, "Line 3")
+                .starts_with("\x1b]8;;https://example.com/32/Long%20Lasting#line-3\x1b\\")
+        );
+
+        assert!(supported(&[("TERM_PROGRAM", "WezTerm")]));
+        assert!(supported(&[("VTE_VERSION", "6800")]));
+        assert!(!supported(&[("VTE_VERSION", "4600")]));
+        assert!(!supported(&[("TERM", "xterm-256color")]));
+        assert!(!supported(&[("TERM", "linux"), ("KITTY_WINDOW_ID", "1")]));
+        assert!(supported(&[("TERM", "linux"), ("FORCE_HYPERLINK", "1")]));
+        assert!(!supported(&[
+            ("TERM_PROGRAM", "iTerm.app"),
+            ("FORCE_HYPERLINK", "0")
+        ]));
+    }
+}
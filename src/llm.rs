@@ -0,0 +1,156 @@
+use crate::config::LlmProvider;
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+use std::env;
+
+/// Environment variable holding the API key for OpenAI-compatible endpoints
+pub const OPENAI_KEY_ENV: &str = "OPENAI_API_KEY";
+
+/// Prompt sent when no template is configured
+pub const DEFAULT_PROMPT: &str = "\
+You are interpreting an I Ching reading cast with the three coins method, \
+using the Wilhelm-Baynes translation.
+
+Question: {question}
+
+Context: {context}
+
+Reading (JSON: the primary hexagram, the texts of any changing lines, and the \
+hexagram they transform into):
+{reading}
+
+Interpret the reading for the question in a few short paragraphs. Ground what \
+you say in the judgment, image, and changing line texts given, and relate them \
+to the question without claiming certainty about the future.";
+
+impl LlmProvider {
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            LlmProvider::Openai => "gpt-4o-mini",
+            LlmProvider::Ollama => "llama3.1",
+        }
+    }
+
+    pub fn default_endpoint(&self) -> &'static str {
+        match self {
+            LlmProvider::Openai => "https://api.openai.com/v1",
+            LlmProvider::Ollama => "http://localhost:11434",
+        }
+    }
+
+    /// Path of the chat API under the endpoint
+    fn chat_path(&self) -> &'static str {
+        match self {
+            LlmProvider::Openai => "/chat/completions",
+            LlmProvider::Ollama => "/api/chat",
+        }
+    }
+}
+
+/// A chat model to send readings to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Interpreter {
+    pub provider: LlmProvider,
+    pub model: String,
+    pub endpoint: String,
+}
+
+impl Interpreter {
+    /// Use the provider's default model and endpoint where none is given
+    pub fn new(provider: LlmProvider, model: Option<String>, endpoint: Option<String>) -> Self {
+        Self {
+            provider,
+            model: model.unwrap_or_else(|| provider.default_model().to_string()),
+            endpoint: endpoint.unwrap_or_else(|| provider.default_endpoint().to_string()),
+        }
+    }
+
+    /// Send the prompt and return the model's reply
+    pub fn interpret(&self, prompt: &str) -> Result<String> {
+        let url = format!(
+            "{}{}",
+            self.endpoint.trim_end_matches('/'),
+            self.provider.chat_path()
+        );
+        let mut request = ureq::post(&url);
+        if self.provider == LlmProvider::Openai {
+            let key = env::var(OPENAI_KEY_ENV)
+                .with_context(|| format!("Set {} to use the OpenAI provider", OPENAI_KEY_ENV))?;
+            request = request.header("Authorization", &format!("Bearer {}", key));
+        }
+
+        let mut response = request
+            .send_json(self.request_body(prompt))
+            .with_context(|| format!("Failed to reach {}", url))?;
+        let response: Value = response
+            .body_mut()
+            .read_json()
+            .with_context(|| format!("Unexpected response from {}", url))?;
+        self.reply_text(&response)
+    }
+
+    fn request_body(&self, prompt: &str) -> Value {
+        let messages = json!([{ "role": "user", "content": prompt }]);
+        match self.provider {
+            LlmProvider::Openai => json!({ "model": self.model, "messages": messages }),
+            LlmProvider::Ollama => {
+                json!({ "model": self.model, "messages": messages, "stream": false })
+            }
+        }
+    }
+
+    fn reply_text(&self, response: &Value) -> Result<String> {
+        let content = match self.provider {
+            LlmProvider::Openai => &response["choices"][0]["message"]["content"],
+            LlmProvider::Ollama => &response["message"]["content"],
+        };
+        content
+            .as_str()
+            .map(|text| text.trim().to_string())
+            .ok_or_else(|| anyhow::anyhow!("The {} response had no message", self.model))
+    }
+}
+
+/// Fill a prompt template's `{question}`, `{context}`, and `{reading}` placeholders
+pub fn render_prompt(
+    template: &str,
+    question: Option<&str>,
+    context: Option<&str>,
+    reading_json: &str,
+) -> String {
+    template
+        .replace("{question}", question.unwrap_or("(no question given)"))
+        .replace("{context}", context.unwrap_or("(none given)"))
+        .replace("{reading}", reading_json)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render_prompt() {
+        let prompt = render_prompt("Q: {question}\n{reading}", None, None, "{\"lines\":[7]}");
+        assert_eq!(prompt, "Q: (no question given)\n{\"lines\":[7]}");
+        let prompt = render_prompt("{context}", None, Some("A new job offer"), "");
+        assert_eq!(prompt, "A new job offer");
+        assert!(DEFAULT_PROMPT.contains("{question}") && DEFAULT_PROMPT.contains("{reading}"));
+        assert!(DEFAULT_PROMPT.contains("{context}"));
+    }
+
+    #[test]
+    fn test_provider_payloads() {
+        let ollama = Interpreter::new(LlmProvider::Ollama, None, None);
+        assert_eq!(ollama.model, "llama3.1");
+        assert_eq!(ollama.request_body("hi")["stream"], false);
+        let reply = json!({ "message": { "role": "assistant", "content": " Wait. " } });
+        assert_eq!(ollama.reply_text(&reply).unwrap(), "Wait.");
+
+        let openai = Interpreter::new(LlmProvider::Openai, Some("gpt-x".to_string()), None);
+        assert_eq!(openai.request_body("hi")["model"], "gpt-x");
+        assert_eq!(openai.request_body("hi")["messages"][0]["content"], "hi");
+        let reply = json!({ "choices": [{ "message": { "content": "Persevere." } }] });
+        assert_eq!(openai.reply_text(&reply).unwrap(), "Persevere.");
+        assert!(openai.reply_text(&json!({ "error": "quota" })).is_err());
+    }
+}
@@ -0,0 +1,432 @@
+//! Manifests for reproducing a cast exactly, for `--manifest` and
+//! `i-ching reproduce`
+//!
+//! A manifest records what the lines came from (the method and the seed of
+//! the generator, or the question and date for `question-hash`) along with
+//! the crate version and data checksums, since `StdRng`'s stream and the
+//! texts can change between releases. It also records what the journal entry
+//! carried and every option the reading was printed with, and a checksum of
+//! the bytes printed, so a reproduction can be checked byte for byte.
+//!
+//! Only what the manifest holds goes into the output: a `--manifest` cast
+//! leaves out the user's notes, hyperlinks, worksheet prompts file, and
+//! watchlist label, since those come from files the manifest doesn't carry.
+
+use crate::banner;
+use crate::cli::Method;
+use crate::format::{Format, FullOptions, TextOnly, write_reading};
+use crate::core::data::{self, IChingData};
+use crate::core::{Diviner, Reading};
+use crate::format::RenderOptions;
+use crate::glyphs::Glyphs;
+use crate::interpretation::framing::Topic;
+use crate::interpretation::rules::Rule;
+use crate::journal::JournalEntry;
+use crate::json::JsonOptions;
+use crate::lang::Lang;
+use crate::rendered::Part;
+use crate::worksheet::Prompts;
+use anyhow::{Context, Result};
+use chrono::{DateTime, NaiveDate, Utc};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::Path;
+
+/// What a reading was cast from, and what it came to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Manifest {
+    /// Version of the crate that cast the reading
+    pub version: String,
+    /// CRC-32 of each embedded data file, by file name
+    pub data_checksums: BTreeMap<String, String>,
+    pub method: Method,
+    /// Seed of the generator the lines were cast with; none for
+    /// `question-hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub seed: Option<u64>,
+    /// Day hashed with the question, for `question-hash`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub date: Option<NaiveDate>,
+    pub timestamp: DateTime<Utc>,
+    pub question: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub context: Option<String>,
+    /// The lines cast, 6 to 9, bottom first
+    pub lines: [u8; 6],
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub recipient: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<Topic>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub focus_line: Option<u8>,
+    /// Whether the entry carried the almanac, which is worked out again from
+    /// `timestamp`
+    #[serde(default)]
+    pub almanac: bool,
+    #[serde(flatten)]
+    pub rendering: Rendering,
+    /// CRC-32 of the output as it was printed
+    pub output_checksum: String,
+}
+
+/// Every option a reading was printed with, besides those the entry carries
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Rendering {
+    pub format: Format,
+    pub glyphs: Glyphs,
+    #[serde(default)]
+    pub lang: Lang,
+    #[serde(default)]
+    pub rule: Rule,
+    #[serde(default)]
+    pub guidance: bool,
+    #[serde(default)]
+    pub pair: bool,
+    #[serde(default)]
+    pub wings: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text_only: Option<TextOnly>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sections: Option<Vec<Part>>,
+    /// Date the six spirits were assigned from, for `--style liuyao`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub spirits_date: Option<NaiveDate>,
+    /// Columns the banner format was fitted to
+    #[serde(default = "default_width")]
+    pub width: usize,
+    #[serde(default)]
+    pub json: JsonOptions,
+}
+
+fn default_width() -> usize {
+    banner::DEFAULT_WIDTH
+}
+
+impl Rendering {
+    /// The parts of `options` a manifest records, for output in `format`
+    pub fn of(format: Format, options: &RenderOptions) -> Self {
+        let sections = &options.sections;
+        Rendering {
+            format,
+            glyphs: options.glyphs,
+            lang: sections.lang,
+            rule: sections.rule,
+            guidance: sections.include_guidance,
+            pair: sections.include_pair,
+            wings: sections.include_wings,
+            text_only: sections.text_only,
+            sections: sections.only.clone(),
+            spirits_date: sections.spirits_date,
+            width: options.width,
+            json: options.json,
+        }
+    }
+
+    /// Options to print `entry` with as it was printed, with no notes,
+    /// links, or prompts of the user's own
+    pub fn options(&self, entry: &JournalEntry) -> RenderOptions {
+        RenderOptions {
+            glyphs: self.glyphs,
+            color: false,
+            sections: FullOptions {
+                include_guidance: self.guidance,
+                include_pair: self.pair,
+                include_wings: self.wings,
+                topic: entry.topic,
+                spirits_date: self.spirits_date,
+                text_only: self.text_only,
+                lang: self.lang,
+                rule: self.rule,
+                focus_line: entry.focus_line,
+                only: self.sections.clone(),
+                prompts: Prompts::default(),
+                ..FullOptions::default()
+            },
+            width: self.width,
+            json: self.json,
+        }
+    }
+}
+
+impl Manifest {
+    /// Cast a reading by `method`: from `seed` if it casts, or from the
+    /// question and `date` for `question-hash`
+    pub fn cast(
+        method: Method,
+        seed: u64,
+        question: Option<&str>,
+        date: NaiveDate,
+    ) -> Result<Reading> {
+        match method.casting() {
+            Some(method) => Ok(method
+                .casting()
+                .cast(&mut StdRng::seed_from_u64(seed), None)),
+            None => {
+                let question =
+                    question.context("--method question-hash needs a --question to hash")?;
+                Ok(Diviner::cast_reading_from_question(question, date))
+            }
+        }
+    }
+
+    /// The manifest of a reading just cast by `method`, from `seed` unless
+    /// the entry was hashed from its question, and printed as `output` with
+    /// `rendering`
+    pub fn new(
+        entry: &JournalEntry,
+        method: Method,
+        seed: u64,
+        rendering: Rendering,
+        output: &[u8],
+    ) -> Self {
+        let reading = &entry.reading;
+        Manifest {
+            version: env!("CARGO_PKG_VERSION").to_string(),
+            data_checksums: data_checksums(),
+            method,
+            seed: method.casting().is_some().then_some(seed),
+            date: entry.question_hash,
+            timestamp: entry.timestamp,
+            question: reading.question().map(str::to_string),
+            context: reading.context().map(str::to_string),
+            lines: reading.traditional_numbers(),
+            recipient: entry.recipient.clone(),
+            topic: entry.topic,
+            focus_line: entry.focus_line,
+            almanac: entry.almanac.is_some(),
+            rendering,
+            output_checksum: checksum(output),
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path)
+            .with_context(|| format!("Failed to read manifest {}", path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("{} is not a reading manifest", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<()> {
+        std::fs::write(path, serde_json::to_string_pretty(self)? + "\n")
+            .with_context(|| format!("Failed to write manifest {}", path.display()))
+    }
+
+    /// Cast the reading again and print it as it was, checking the lines
+    /// and the output against the manifest
+    ///
+    /// `data` should have any translation the cast applied, i.e. the Chinese
+    /// texts if they were installed and `rendering.lang` is Chinese.
+    pub fn reproduce(&self, data: &IChingData) -> Result<String> {
+        let date = self.date.unwrap_or_else(|| self.timestamp.date_naive());
+        let reading = Self::cast(
+            self.method,
+            self.seed.unwrap_or_default(),
+            self.question.as_deref(),
+            date,
+        )?
+        .with_question(self.question.clone())
+        .with_context(self.context.clone());
+        if reading.traditional_numbers() != self.lines {
+            return Err(self.mismatch(&format!(
+                "the lines came out {:?}, not {:?}",
+                reading.traditional_numbers(),
+                self.lines
+            )));
+        }
+        let mut entry = JournalEntry::cast_at(reading, self.timestamp)
+            .with_question_hash(self.date)
+            .with_recipient(self.recipient.clone())
+            .with_topic(self.topic)
+            .with_focus_line(self.focus_line);
+        if self.almanac {
+            entry = entry.with_almanac();
+        }
+        let mut output = Vec::new();
+        write_reading(
+            &mut output,
+            &entry,
+            self.rendering.format.clone(),
+            data,
+            &self.rendering.options(&entry),
+        )?;
+        if checksum(&output) != self.output_checksum {
+            return Err(self.mismatch("the output differs from what was cast"));
+        }
+        Ok(String::from_utf8(output)?)
+    }
+
+    /// Why a reproduction failed, naming what differs from the build that
+    /// cast it
+    fn mismatch(&self, what: &str) -> anyhow::Error {
+        let mut causes = Vec::new();
+        if self.version != env!("CARGO_PKG_VERSION") {
+            causes.push(format!(
+                "it was cast by version {}, and this is {}",
+                self.version,
+                env!("CARGO_PKG_VERSION")
+            ));
+        }
+        if self.data_checksums != data_checksums() {
+            causes.push("the data files differ".to_string());
+        }
+        if causes.is_empty() {
+            anyhow::anyhow!("Can't reproduce the reading: {}", what)
+        } else {
+            anyhow::anyhow!(
+                "Can't reproduce the reading: {} ({})",
+                what,
+                causes.join("; ")
+            )
+        }
+    }
+}
+
+fn data_checksums() -> BTreeMap<String, String> {
+    data::checksums()
+        .iter()
+        .map(|&(name, sum)| (name.to_string(), format!("{:08x}", sum)))
+        .collect()
+}
+
+fn checksum(bytes: &[u8]) -> String {
+    let mut crc = flate2::Crc::new();
+    crc.update(bytes);
+    format!("{:08x}", crc.sum())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::json::{JsonCase, JsonStyle};
+
+    fn rendering(format: Format, glyphs: Glyphs) -> Rendering {
+        Rendering::of(
+            format,
+            &RenderOptions {
+                glyphs,
+                ..RenderOptions::default()
+            },
+        )
+    }
+
+    /// `entry` as a `--manifest` cast prints it
+    fn print(entry: &JournalEntry, rendering: &Rendering, data: &IChingData) -> Vec<u8> {
+        let mut output = Vec::new();
+        let options = rendering.options(entry);
+        write_reading(&mut output, entry, rendering.format.clone(), data, &options).unwrap();
+        output
+    }
+
+    #[test]
+    fn test_reproduce() {
+        let data = IChingData::load().unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let reading = Manifest::cast(Method::Marbles, 42, None, date)
+            .unwrap()
+            .with_question(Some("Stay?".to_string()));
+        let entry = JournalEntry::new(reading);
+        let full = rendering(Format::Full, Glyphs::Ascii);
+        let printed = print(&entry, &full, &data);
+        let manifest = Manifest::new(&entry, Method::Marbles, 42, full, &printed);
+        assert_eq!((manifest.seed, manifest.date), (Some(42), None));
+        // The texts the full output draws on beyond the hexagrams count too
+        assert!(manifest.data_checksums.contains_key("crossrefs.json"));
+
+        let json = serde_json::to_string(&manifest).unwrap();
+        let loaded: Manifest = serde_json::from_str(&json).unwrap();
+        let output = loaded.reproduce(&data).unwrap();
+        assert_eq!(output.as_bytes(), printed);
+        assert!(output.starts_with("Question: Stay?\n"));
+        assert!(output.contains("\nRevisit on: "));
+
+        // Another seed casts other lines, or the same lines with no change
+        // to the output
+        let other = Manifest {
+            seed: Some(43),
+            ..loaded.clone()
+        };
+        let lines = Manifest::cast(Method::Marbles, 43, None, date).unwrap();
+        assert_eq!(
+            other.reproduce(&data).is_ok(),
+            lines.traditional_numbers() == manifest.lines
+        );
+
+        // Hashed readings need no seed
+        let hashed = Manifest::cast(Method::QuestionHash, 0, Some("Stay?"), date)
+            .unwrap()
+            .with_question(Some("Stay?".to_string()));
+        let entry = JournalEntry::new(hashed).with_question_hash(Some(date));
+        let json = rendering(Format::Json, Glyphs::Unicode);
+        let printed = print(&entry, &json, &data);
+        let manifest = Manifest::new(&entry, Method::QuestionHash, 7, json, &printed);
+        assert_eq!((manifest.seed, manifest.date), (None, Some(date)));
+        assert!(manifest.reproduce(&data).is_ok());
+    }
+
+    #[test]
+    fn test_reproduce_with_options() {
+        let data = IChingData::load().unwrap();
+        let date = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let (seed, reading) = (0..)
+            .map(|seed| {
+                (
+                    seed,
+                    Manifest::cast(Method::Coins, seed, None, date).unwrap(),
+                )
+            })
+            .find(|(_, reading)| !reading.changing_line_positions().is_empty())
+            .unwrap();
+        let focus = reading.changing_line_positions()[0];
+        let entry = JournalEntry::new(reading.with_question(Some("Which job?".to_string())))
+            .with_recipient(Some("Sam".to_string()))
+            .with_topic(Some(Topic::Career))
+            .with_focus_line(Some(focus))
+            .with_almanac();
+
+        let full = Rendering {
+            guidance: true,
+            pair: true,
+            wings: true,
+            rule: Rule::All,
+            spirits_date: Some(date),
+            ..rendering(Format::Full, Glyphs::Ascii)
+        };
+        let json = Rendering {
+            json: JsonOptions {
+                style: JsonStyle::Compact,
+                case: JsonCase::Camel,
+            },
+            ..rendering(Format::Json, Glyphs::Unicode)
+        };
+        let markdown = Rendering {
+            sections: Some(vec![Part::Framing, Part::Lines]),
+            ..rendering(Format::Markdown, Glyphs::Unicode)
+        };
+        for rendering in [full, json, markdown] {
+            let printed = print(&entry, &rendering, &data);
+            let manifest = Manifest::new(&entry, Method::Coins, seed, rendering, &printed);
+            let loaded: Manifest =
+                serde_json::from_str(&serde_json::to_string(&manifest).unwrap()).unwrap();
+            assert_eq!(loaded, manifest);
+            let output = loaded.reproduce(&data).unwrap();
+            assert_eq!(output.as_bytes(), printed);
+            assert!(output.contains("Career") || output.contains("almanac"));
+        }
+
+        // Without what the entry carried, the output no longer matches
+        let printed = print(&entry, &rendering(Format::Json, Glyphs::Unicode), &data);
+        let manifest = Manifest {
+            recipient: None,
+            ..Manifest::new(
+                &entry,
+                Method::Coins,
+                seed,
+                rendering(Format::Json, Glyphs::Unicode),
+                &printed,
+            )
+        };
+        assert!(manifest.reproduce(&data).is_err());
+    }
+}
@@ -0,0 +1,127 @@
+use crate::core::{Diviner, Reading};
+use chrono::{Datelike, NaiveDate};
+use rand::SeedableRng;
+use rand::rngs::StdRng;
+use std::path::Path;
+
+/// Where Debian and Ubuntu's pam_motd runs login message scripts from
+pub const UPDATE_MOTD_SCRIPT: &str = "/etc/update-motd.d/60-i-ching";
+
+/// Where the systemd units that rewrite /etc/motd each day go
+pub const SYSTEMD_DIR: &str = "/etc/systemd/system";
+
+/// Most lines of judgment text shown under the heading
+const MAX_TEXT_LINES: usize = 3;
+
+/// The hexagram of the day: the same cast for every login on a given date
+pub fn daily_reading(date: NaiveDate) -> Reading {
+    let mut rng = StdRng::seed_from_u64(date.num_days_from_ce() as u64);
+    Diviner::cast_reading_with(&mut rng, None)
+}
+
+/// Fit a heading and text into `width` columns: the heading cut short with
+/// "…" if it's too long, then the text wrapped at word boundaries onto at
+/// most three lines
+pub fn fit(heading: &str, text: &str, width: usize) -> String {
+    let mut lines = vec![truncate(heading, width)];
+
+    let words: Vec<&str> = text.split_whitespace().collect();
+    let mut wrapped: Vec<String> = Vec::new();
+    for word in &words {
+        match wrapped.last_mut() {
+            Some(line) if line.chars().count() + 1 + word.chars().count() <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => wrapped.push(truncate(word, width)),
+        }
+    }
+    if wrapped.len() > MAX_TEXT_LINES {
+        wrapped.truncate(MAX_TEXT_LINES);
+        if let Some(last) = wrapped.last_mut() {
+            *last = truncate(&format!("{} …", last), width);
+        }
+    }
+
+    lines.extend(wrapped);
+    lines.join("\n")
+}
+
+/// `text` cut to `width` columns, ending in "…" if anything was dropped
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() <= width {
+        return text.to_string();
+    }
+    let kept: String = text.chars().take(width.saturating_sub(1)).collect();
+    format!("{}…", kept.trim_end())
+}
+
+/// An update-motd.d script that prints the day's hexagram at login
+pub fn update_script(exe: &Path, width: usize) -> String {
+    format!(
+        "#!/bin/sh\n\
+         # Hexagram of the day, installed by `i-ching motd install`\n\
+         exec {} --no-pager motd render --width {}\n",
+        exe.display(),
+        width
+    )
+}
+
+/// A systemd service and timer that rewrite /etc/motd with the day's
+/// hexagram just after midnight, for systems without update-motd.d
+pub fn systemd_units(exe: &Path, width: usize) -> (String, String) {
+    let service = format!(
+        "[Unit]\n\
+         Description=Write the I Ching hexagram of the day to /etc/motd\n\
+         \n\
+         [Service]\n\
+         Type=oneshot\n\
+         ExecStart=/bin/sh -c '{} --no-pager motd render --width {} > /etc/motd'\n",
+        exe.display(),
+        width
+    );
+    let timer = "[Unit]\n\
+                 Description=Daily I Ching hexagram for /etc/motd\n\
+                 \n\
+                 [Timer]\n\
+                 OnCalendar=*-*-* 00:01:00\n\
+                 Persistent=true\n\
+                 \n\
+                 [Install]\n\
+                 WantedBy=timers.target\n"
+        .to_string();
+    (service, timer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_daily_reading_is_stable() {
+        let today = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+        let first = daily_reading(today);
+        assert_eq!(
+            first.traditional_numbers(),
+            daily_reading(today).traditional_numbers()
+        );
+    }
+
+    #[test]
+    fn test_fit() {
+        let text = "Perseverance furthers. It furthers one to cross the great water. \
+                    The superior man carries on the work of teaching.";
+        let fitted = fit("䷊ 11 ADVANCE CHANGING INTO 19 APPROACH", text, 24);
+        let lines: Vec<&str> = fitted.lines().collect();
+
+        assert_eq!(lines[0], "䷊ 11 ADVANCE CHANGING I…");
+        assert_eq!(lines.len(), 1 + MAX_TEXT_LINES);
+        assert!(lines.last().unwrap().ends_with('…'));
+        assert!(lines.iter().all(|line| line.chars().count() <= 24));
+
+        assert_eq!(
+            fit("䷀ 1 THE CREATIVE", "Sublime success.", 40),
+            "䷀ 1 THE CREATIVE\nSublime success."
+        );
+    }
+}
@@ -0,0 +1,159 @@
+use anyhow::{Context, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the notes file location
+pub const NOTES_PATH_ENV: &str = "I_CHING_NOTES";
+
+/// Personal notes keyed by hexagram number
+pub type HexagramNotes = BTreeMap<u8, Vec<Note>>;
+
+/// A piece of the user's own commentary on a hexagram
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Note {
+    pub added: DateTime<Utc>,
+    pub text: String,
+}
+
+/// The user's notes, stored as one JSON object mapping hexagrams to notes
+pub struct Notes {
+    path: PathBuf,
+}
+
+impl Notes {
+    /// Open the notes at `$I_CHING_NOTES`, or in the user config directory
+    pub fn open_default() -> Result<Self> {
+        if let Some(path) = env::var_os(NOTES_PATH_ENV) {
+            return Ok(Self::at(path));
+        }
+
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine the user config directory"))?;
+        Ok(Self::at(config_dir.join("i-ching").join("notes.json")))
+    }
+
+    /// Open notes stored at a specific path
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Every note (empty if no notes file exists yet)
+    pub fn load(&self) -> Result<HexagramNotes> {
+        if !self.path.exists() {
+            return Ok(HexagramNotes::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read notes {}", self.path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid notes file {}", self.path.display()))
+    }
+
+    /// Add a note to a hexagram, creating the file if needed
+    pub fn add(&self, hexagram: u8, text: &str) -> Result<()> {
+        check_hexagram(hexagram)?;
+        let text = text.trim();
+        if text.is_empty() {
+            return Err(anyhow::anyhow!("A note needs some text"));
+        }
+
+        let mut notes = self.load()?;
+        notes.entry(hexagram).or_default().push(Note {
+            added: Utc::now(),
+            text: text.to_string(),
+        });
+        self.save(&notes)
+    }
+
+    /// Remove a hexagram's note by its 1-based position, returning it
+    pub fn remove(&self, hexagram: u8, position: usize) -> Result<Note> {
+        check_hexagram(hexagram)?;
+        let mut notes = self.load()?;
+        let list = notes.entry(hexagram).or_default();
+        if position == 0 || position > list.len() {
+            return Err(anyhow::anyhow!(
+                "Hexagram {} has no note {} ({} saved)",
+                hexagram,
+                position,
+                list.len()
+            ));
+        }
+
+        let note = list.remove(position - 1);
+        if list.is_empty() {
+            notes.remove(&hexagram);
+        }
+        self.save(&notes)?;
+        Ok(note)
+    }
+
+    fn save(&self, notes: &HexagramNotes) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let content = serde_json::to_string_pretty(notes)?;
+        fs::write(&self.path, content + "\n")
+            .with_context(|| format!("Failed to write notes {}", self.path.display()))
+    }
+}
+
+fn check_hexagram(hexagram: u8) -> Result<()> {
+    if (1..=64).contains(&hexagram) {
+        Ok(())
+    } else {
+        Err(anyhow::anyhow!(
+            "Hexagram number must be between 1 and 64, got {}",
+            hexagram
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn temp_notes(name: &str) -> Notes {
+        let path = env::temp_dir().join(format!(
+            "i-ching-notes-{}-{}-{}.json",
+            name,
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        ));
+        Notes::at(path)
+    }
+
+    #[test]
+    fn test_add_and_remove() {
+        let notes = temp_notes("add");
+        assert!(notes.load().unwrap().is_empty());
+
+        notes.add(1, "Start before you feel ready").unwrap();
+        notes.add(1, "  Keep going  ").unwrap();
+        notes.add(29, "Water finds a way").unwrap();
+
+        let loaded = notes.load().unwrap();
+        assert_eq!(loaded[&1].len(), 2);
+        assert_eq!(loaded[&1][1].text, "Keep going");
+        assert_eq!(loaded[&29][0].text, "Water finds a way");
+
+        let removed = notes.remove(29, 1).unwrap();
+        assert_eq!(removed.text, "Water finds a way");
+        assert!(!notes.load().unwrap().contains_key(&29));
+
+        assert!(notes.remove(1, 3).is_err());
+        assert!(notes.add(65, "Out of range").is_err());
+        assert!(notes.add(2, "   ").is_err());
+
+        fs::remove_file(notes.path()).unwrap();
+    }
+}
@@ -0,0 +1,56 @@
+use anyhow::{Context, Result};
+use serde_json::{Value, json};
+
+/// A reading summarized for a desktop notification or chat webhook
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Notification {
+    pub title: String,
+    pub body: String,
+}
+
+impl Notification {
+    /// Show the notification on the desktop
+    pub fn send_desktop(&self) -> Result<()> {
+        notify_rust::Notification::new()
+            .appname("i-ching")
+            .summary(&self.title)
+            .body(&self.body)
+            .show()
+            .context("Failed to show desktop notification")?;
+        Ok(())
+    }
+
+    /// JSON accepted by both Slack (`text`) and Discord (`content`) webhooks
+    pub fn webhook_payload(&self) -> Value {
+        let message = format!("*{}*\n{}", self.title, self.body);
+        json!({
+            "text": message,
+            "content": message,
+        })
+    }
+
+    /// Post the notification to an incoming webhook
+    pub fn post_webhook(&self, url: &str) -> Result<()> {
+        ureq::post(url)
+            .send_json(self.webhook_payload())
+            .with_context(|| format!("Failed to post reading to webhook {}", url))?;
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_webhook_payload() {
+        let notification = Notification {
+            title: "Daily reading".to_string(),
+            body: "䷀ 1 The Creative".to_string(),
+        };
+
+        let payload = notification.webhook_payload();
+        assert_eq!(payload["text"], "*Daily reading*\n䷀ 1 The Creative");
+        assert_eq!(payload["content"], payload["text"]);
+    }
+}
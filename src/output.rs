@@ -0,0 +1,135 @@
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Child, Command, Stdio};
+
+/// Environment variable that picks the pager ahead of `$PAGER`
+pub const PAGER_ENV: &str = "I_CHING_PAGER";
+
+/// Where command output goes: stdout, or a pager when stdout is a terminal
+///
+/// The pager is started on the first write, so commands that print nothing
+/// never flash one, and it is waited for when the output is dropped.
+pub struct Output {
+    sink: Sink,
+}
+
+enum Sink {
+    /// Nothing written yet; page if allowed once there is something to show
+    Pending {
+        paging: bool,
+    },
+    Stdout(io::Stdout),
+    Pager(Child),
+}
+
+impl Output {
+    /// Output that pages through `$I_CHING_PAGER`, `$PAGER`, or `less` when
+    /// `paging` is set and stdout is a terminal
+    pub fn new(paging: bool) -> Self {
+        Self {
+            sink: Sink::Pending { paging },
+        }
+    }
+
+    fn sink(&mut self) -> &mut Sink {
+        if let Sink::Pending { paging } = self.sink {
+            let pager = paging && io::stdout().is_terminal();
+            self.sink = pager
+                .then(spawn_pager)
+                .flatten()
+                .map_or_else(|| Sink::Stdout(io::stdout()), Sink::Pager);
+        }
+        &mut self.sink
+    }
+}
+
+impl Write for Output {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        match self.sink() {
+            Sink::Pager(child) => {
+                let stdin = child.stdin.as_mut().expect("pager stdin is piped");
+                match stdin.write(buf) {
+                    // The reader quit the pager early; drop the rest quietly
+                    Err(e) if e.kind() == io::ErrorKind::BrokenPipe => Ok(buf.len()),
+                    result => result,
+                }
+            }
+            Sink::Stdout(stdout) => stdout.write(buf),
+            Sink::Pending { .. } => unreachable!("sink() resolves the pending state"),
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match &mut self.sink {
+            Sink::Pager(child) => match child.stdin.as_mut().map(|stdin| stdin.flush()) {
+                Some(Err(e)) if e.kind() != io::ErrorKind::BrokenPipe => Err(e),
+                _ => Ok(()),
+            },
+            Sink::Stdout(stdout) => stdout.flush(),
+            Sink::Pending { .. } => Ok(()),
+        }
+    }
+}
+
+impl Drop for Output {
+    fn drop(&mut self) {
+        if let Sink::Pager(child) = &mut self.sink {
+            // Closing stdin tells the pager the output is complete
+            drop(child.stdin.take());
+            let _ = child.wait();
+        }
+    }
+}
+
+fn spawn_pager() -> Option<Child> {
+    let command = pager_command(|name| env::var(name).ok())?;
+    let (program, args) = command.split_first()?;
+
+    let mut pager = Command::new(program);
+    pager.args(args).stdin(Stdio::piped());
+    // Like git: quit if the output fits on one screen, keep colours, don't clear
+    if env::var_os("LESS").is_none() {
+        pager.env("LESS", "FRX");
+    }
+    pager.spawn().ok()
+}
+
+/// Pager program and arguments, or `None` when paging is turned off with an
+/// empty value or `cat`
+fn pager_command(var: impl Fn(&str) -> Option<String>) -> Option<Vec<String>> {
+    let command = var(PAGER_ENV)
+        .or_else(|| var("PAGER"))
+        .unwrap_or_else(|| "less".to_string());
+    let parts: Vec<String> = command.split_whitespace().map(str::to_string).collect();
+
+    match parts.first().map(String::as_str) {
+        None | Some("cat") => None,
+        Some(_) => Some(parts),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    fn command(vars: &[(&str, &str)]) -> Option<Vec<String>> {
+        let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+        pager_command(|name| vars.get(name).map(|v| v.to_string()))
+    }
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(command(&[]), Some(vec!["less".to_string()]));
+        assert_eq!(
+            command(&[("PAGER", "more -s")]),
+            Some(vec!["more".to_string(), "-s".to_string()])
+        );
+        assert_eq!(
+            command(&[(PAGER_ENV, "most"), ("PAGER", "more")]),
+            Some(vec!["most".to_string()])
+        );
+        assert_eq!(command(&[("PAGER", "")]), None);
+        assert_eq!(command(&[(PAGER_ENV, "cat"), ("PAGER", "less")]), None);
+    }
+}
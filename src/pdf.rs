@@ -0,0 +1,362 @@
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::core::reading::{Age, Polarity};
+use chrono::NaiveDate;
+use pdf_writer::{Content, Finish, Name, Pdf, Rect, Ref, Str, TextStr};
+
+/// A4, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 56.0;
+
+const BODY_SIZE: f32 = 10.0;
+const LEADING: f32 = 13.0;
+
+/// Least room kept for handwritten notes at the foot of the page; text
+/// that would run into it is cut short
+const NOTES_HEIGHT: f32 = 170.0;
+
+/// Spacing of the ruled lines in the notes area
+const RULE_GAP: f32 = 24.0;
+
+/// Length and thickness of a line of the figure
+const BAR_WIDTH: f32 = 96.0;
+const BAR_HEIGHT: f32 = 9.0;
+const BAR_STEP: f32 = 15.0;
+
+/// Gap in the middle of a yin line
+const YIN_GAP: f32 = 14.0;
+
+const REGULAR: Name = Name(b"F1");
+const BOLD: Name = Name(b"F2");
+
+/// Helvetica advance widths, in thousandths of an em, for ' ' through '~'
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, 556, 556, 556,
+    556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, 1015, 667, 667, 722, 722, 667,
+    611, 778, 722, 278, 500, 667, 556, 833, 722, 778, 667, 778, 722, 667, 611, 722, 667, 944, 667,
+    667, 611, 278, 278, 278, 469, 556, 333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500,
+    222, 833, 556, 556, 556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584,
+];
+
+/// A one-page printable sheet for a reading: the figure, the judgment and
+/// image, the changing lines, and ruled space for notes
+///
+/// The page uses the standard Helvetica fonts, so characters outside
+/// Windows-1252 (the Chinese names, for one) are left out.
+pub fn reading_sheet(reading: &Reading, data: &IChingData, cast_on: NaiveDate) -> Vec<u8> {
+    let mut sheet = Sheet::new();
+    let primary = data.get_hexagram(reading.primary_hexagram());
+    let title = match primary {
+        Some(hexagram) => format!("{} {}", hexagram.number, hexagram.name),
+        None => format!("Hexagram {}", reading.primary_hexagram()),
+    };
+
+    let baseline = sheet.y;
+    sheet.line(BOLD, 18.0, MARGIN, "I Ching Reading");
+    let date = cast_on.format("%-d %B %Y").to_string();
+    let date_x = PAGE_WIDTH - MARGIN - text_width(&date, BODY_SIZE);
+    sheet.content.begin_text();
+    sheet
+        .content
+        .set_font(REGULAR, BODY_SIZE)
+        .next_line(date_x, baseline)
+        .show(Str(&encode(&date)))
+        .end_text();
+    sheet.y -= 10.0;
+    if let Some(question) = reading.question() {
+        sheet.paragraph(&format!("Question: {}", question), MARGIN);
+    }
+    sheet.y -= 14.0;
+
+    // The figure on the left, its names and changes beside it
+    let figure_top = sheet.y;
+    sheet.figure(reading, MARGIN, figure_top);
+    let beside = MARGIN + BAR_WIDTH + 40.0;
+    sheet.y = figure_top - 14.0;
+    sheet.line(BOLD, 16.0, beside, &title);
+    sheet.y -= 6.0;
+    sheet.line(
+        REGULAR,
+        BODY_SIZE,
+        beside,
+        &format!(
+            "{} ({}) over {} ({})",
+            reading.upper_trigram(),
+            reading.upper_trigram().image(),
+            reading.lower_trigram(),
+            reading.lower_trigram().image()
+        ),
+    );
+    let changing = reading.changing_line_positions();
+    if !changing.is_empty() {
+        let positions: Vec<String> = changing.iter().map(u8::to_string).collect();
+        sheet.line(
+            REGULAR,
+            BODY_SIZE,
+            beside,
+            &format!("Changing lines: {}", positions.join(", ")),
+        );
+    }
+    if let Some(transformed) = reading
+        .transformed_hexagram_number()
+        .and_then(|number| data.get_hexagram(number))
+    {
+        sheet.line(
+            REGULAR,
+            BODY_SIZE,
+            beside,
+            &format!("Becomes {} {}", transformed.number, transformed.name),
+        );
+    }
+    sheet.y = sheet.y.min(figure_top - 6.0 * BAR_STEP) - 12.0;
+
+    if let Some(hexagram) = primary {
+        sheet.section("Judgment");
+        sheet.paragraph(&hexagram.judgment.text, MARGIN);
+        sheet.section("Image");
+        sheet.paragraph(&hexagram.image.text, MARGIN);
+
+        if !changing.is_empty() {
+            sheet.section("Changing Lines");
+            for position in &changing {
+                if let Some(line) = data.get_line_interpretation(hexagram.number, *position) {
+                    sheet.paragraph(&format!("Line {}: {}", position, line.text), MARGIN);
+                }
+            }
+        }
+    }
+
+    sheet.notes();
+    sheet.finish(&title)
+}
+
+/// Content stream under construction, with the baseline of the next line
+struct Sheet {
+    content: Content,
+    y: f32,
+    /// Set once text has been cut short to leave room for notes
+    full: bool,
+}
+
+impl Sheet {
+    fn new() -> Self {
+        Self {
+            content: Content::new(),
+            y: PAGE_HEIGHT - MARGIN - 18.0,
+            full: false,
+        }
+    }
+
+    /// One line of text at `x`, then move down a line
+    fn line(&mut self, font: Name, size: f32, x: f32, text: &str) {
+        self.content
+            .begin_text()
+            .set_font(font, size)
+            .next_line(x, self.y)
+            .show(Str(&encode(text)))
+            .end_text();
+        self.y -= size * 1.3;
+    }
+
+    /// A heading, unless there's no longer room for it and a line of text
+    fn section(&mut self, heading: &str) {
+        if self.y - 8.0 - 2.0 * LEADING < MARGIN + NOTES_HEIGHT {
+            self.full = true;
+        }
+        if self.full {
+            return;
+        }
+        self.y -= 8.0;
+        self.line(BOLD, 11.0, MARGIN, heading);
+    }
+
+    /// Body text wrapped to the right margin, cut off with "…" where it would
+    /// reach the notes area
+    fn paragraph(&mut self, text: &str, x: f32) {
+        for line in wrap(text, PAGE_WIDTH - MARGIN - x, BODY_SIZE) {
+            if self.full {
+                return;
+            }
+            if self.y - LEADING < MARGIN + NOTES_HEIGHT {
+                self.full = true;
+                self.line(REGULAR, BODY_SIZE, x, &format!("{} …", line));
+                return;
+            }
+            self.line(REGULAR, BODY_SIZE, x, &line);
+        }
+        self.y -= 3.0;
+    }
+
+    /// The hexagram drawn bottom line first, top edge at `top`, with o or x
+    /// beside the changing lines
+    fn figure(&mut self, reading: &Reading, x: f32, top: f32) {
+        self.content.set_fill_gray(0.0);
+        for (i, line) in reading.lines().iter().enumerate() {
+            let y = top - BAR_STEP * (6 - i) as f32;
+            match line.polarity {
+                Polarity::Yang => {
+                    self.content.rect(x, y, BAR_WIDTH, BAR_HEIGHT);
+                }
+                Polarity::Yin => {
+                    let half = (BAR_WIDTH - YIN_GAP) / 2.0;
+                    self.content.rect(x, y, half, BAR_HEIGHT);
+                    self.content.rect(x + half + YIN_GAP, y, half, BAR_HEIGHT);
+                }
+            }
+            self.content.fill_nonzero();
+
+            if line.age == Age::Old {
+                let mark = match line.polarity {
+                    Polarity::Yang => "o",
+                    Polarity::Yin => "x",
+                };
+                self.content
+                    .begin_text()
+                    .set_font(BOLD, BODY_SIZE)
+                    .next_line(x + BAR_WIDTH + 8.0, y + 1.0)
+                    .show(Str(mark.as_bytes()))
+                    .end_text();
+            }
+        }
+    }
+
+    /// A "Notes" heading under the text and ruled lines down to the bottom
+    /// margin
+    fn notes(&mut self) {
+        self.y -= 10.0;
+        self.line(BOLD, 11.0, MARGIN, "Notes");
+
+        self.content.set_stroke_gray(0.6).set_line_width(0.5);
+        let mut y = self.y - RULE_GAP + 14.0;
+        while y >= MARGIN {
+            self.content
+                .move_to(MARGIN, y)
+                .line_to(PAGE_WIDTH - MARGIN, y)
+                .stroke();
+            y -= RULE_GAP;
+        }
+    }
+
+    fn finish(self, title: &str) -> Vec<u8> {
+        let catalog_id = Ref::new(1);
+        let pages_id = Ref::new(2);
+        let page_id = Ref::new(3);
+        let content_id = Ref::new(4);
+        let regular_id = Ref::new(5);
+        let bold_id = Ref::new(6);
+        let info_id = Ref::new(7);
+
+        let mut pdf = Pdf::new();
+        pdf.catalog(catalog_id).pages(pages_id);
+        pdf.pages(pages_id).kids([page_id]).count(1);
+
+        let mut page = pdf.page(page_id);
+        page.parent(pages_id)
+            .media_box(Rect::new(0.0, 0.0, PAGE_WIDTH, PAGE_HEIGHT))
+            .contents(content_id);
+        page.resources()
+            .fonts()
+            .pair(REGULAR, regular_id)
+            .pair(BOLD, bold_id);
+        page.finish();
+
+        for (id, base) in [(regular_id, "Helvetica"), (bold_id, "Helvetica-Bold")] {
+            pdf.type1_font(id)
+                .base_font(Name(base.as_bytes()))
+                .encoding_predefined(Name(b"WinAnsiEncoding"));
+        }
+        pdf.stream(content_id, &self.content.finish());
+        pdf.document_info(info_id)
+            .title(TextStr(&format!("I Ching Reading: {}", title)))
+            .creator(TextStr("i-ching"));
+
+        pdf.finish()
+    }
+}
+
+/// Width of `text` in Helvetica at `size` points
+fn text_width(text: &str, size: f32) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| {
+            let index = (c as usize).wrapping_sub(' ' as usize);
+            u32::from(HELVETICA_WIDTHS.get(index).copied().unwrap_or(556))
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Lines of at most `width` points, broken at spaces
+fn wrap(text: &str, width: f32, size: f32) -> Vec<String> {
+    let mut lines: Vec<String> = Vec::new();
+    for word in text.split_whitespace() {
+        match lines.last_mut() {
+            Some(line) if text_width(&format!("{} {}", line, word), size) <= width => {
+                line.push(' ');
+                line.push_str(word);
+            }
+            _ => lines.push(word.to_string()),
+        }
+    }
+    lines
+}
+
+/// `text` in Windows-1252 for the standard fonts, with anything outside it dropped
+fn encode(text: &str) -> Vec<u8> {
+    text.chars()
+        .filter_map(|c| match c {
+            ' '..='~' | '\u{A0}'..='\u{FF}' => Some(c as u8),
+            '…' => Some(0x85),
+            '‘' => Some(0x91),
+            '’' => Some(0x92),
+            '“' => Some(0x93),
+            '”' => Some(0x94),
+            '–' => Some(0x96),
+            '—' => Some(0x97),
+            _ => None,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reading_sheet() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "9,8,7,6,7,8".parse().unwrap();
+        let reading = reading.with_question(Some("Should I go?".to_string()));
+        let cast_on = NaiveDate::from_ymd_opt(2026, 3, 1).unwrap();
+
+        let pdf = reading_sheet(&reading, &data, cast_on);
+        let text = String::from_utf8_lossy(&pdf);
+        assert!(pdf.starts_with(b"%PDF-"));
+        assert!(text.contains("/Count 1"));
+        assert!(text.contains("(Question: Should I go?) Tj"));
+        assert!(text.contains("(1 March 2026) Tj"));
+        assert!(text.contains("(Changing Lines) Tj"));
+        assert!(text.contains("(Notes) Tj"));
+    }
+
+    #[test]
+    fn test_wrap() {
+        let lines = wrap(
+            "Perseverance furthers. It furthers one to cross the great water.",
+            150.0,
+            10.0,
+        );
+        assert!(lines.len() > 1);
+        assert!(lines.iter().all(|line| text_width(line, 10.0) <= 150.0));
+        assert_eq!(
+            lines.join(" "),
+            "Perseverance furthers. It furthers one to cross the great water."
+        );
+    }
+
+    #[test]
+    fn test_encode() {
+        assert_eq!(encode("既濟 jìjì “go”"), b" j\xecj\xec \x93go\x94");
+    }
+}
@@ -0,0 +1,193 @@
+//! How every hexagram relates to every other by its figure, for
+//! `i-ching relations`
+
+use crate::core::data::IChingData;
+use crate::core::king_wen;
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+/// A link from one hexagram's figure to another's
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Relation {
+    /// The figures differ in a single line
+    Adjacent,
+    /// Every line is changed (the opposite)
+    Complement,
+    /// The figure is turned upside down
+    Inverse,
+    /// The other is this one's nuclear hexagram, from lines 2-5; the only
+    /// relation that doesn't run both ways
+    Nuclear,
+}
+
+impl Relation {
+    pub const ALL: [Relation; 4] = [
+        Relation::Adjacent,
+        Relation::Complement,
+        Relation::Inverse,
+        Relation::Nuclear,
+    ];
+
+    pub fn label(&self) -> &'static str {
+        match self {
+            Relation::Adjacent => "adjacent",
+            Relation::Complement => "complement",
+            Relation::Inverse => "inverse",
+            Relation::Nuclear => "nuclear",
+        }
+    }
+
+    /// Graphviz edge attributes
+    fn dot_style(&self) -> &'static str {
+        match self {
+            Relation::Adjacent => "dir=none, color=gray60",
+            Relation::Complement => "dir=none, color=red, style=dashed",
+            Relation::Inverse => "dir=none, color=blue",
+            Relation::Nuclear => "color=darkgreen",
+        }
+    }
+
+    /// Whether `from` relates to `to` this way (King Wen numbers)
+    pub fn holds(&self, from: u8, to: u8) -> bool {
+        match self {
+            Relation::Adjacent => match (king_wen::to_binary(from), king_wen::to_binary(to)) {
+                (Some(a), Some(b)) => (a ^ b).count_ones() == 1,
+                _ => false,
+            },
+            Relation::Complement => king_wen::complement(from) == Some(to),
+            Relation::Inverse => king_wen::inverse(from) == Some(to),
+            Relation::Nuclear => king_wen::nuclear(from) == Some(to),
+        }
+    }
+}
+
+/// A hexagram as a node of the matrix
+#[derive(Debug, Serialize)]
+pub struct Node<'a> {
+    pub number: u8,
+    pub name: &'a str,
+    /// Lines bottom to top, 1 for yang and 0 for yin
+    pub lines: String,
+}
+
+/// The 64×64 relation matrix, in King Wen order
+///
+/// `matrix[i][j]` lists how hexagram `i + 1` relates to hexagram `j + 1`.
+/// A hexagram's relations to itself (such as Qian being its own nuclear
+/// hexagram) are left out.
+#[derive(Debug, Serialize)]
+pub struct Matrix<'a> {
+    pub hexagrams: Vec<Node<'a>>,
+    pub matrix: Vec<Vec<Vec<Relation>>>,
+}
+
+impl<'a> Matrix<'a> {
+    pub fn new(data: &'a IChingData) -> Result<Self> {
+        let hexagrams = (1..=64)
+            .map(|number| {
+                let hexagram = data
+                    .get_hexagram(number)
+                    .with_context(|| format!("Hexagram {} not found", number))?;
+                let bits = king_wen::to_binary(number).context("Hexagram out of range")?;
+                Ok(Node {
+                    number,
+                    name: &hexagram.name,
+                    lines: (0..6)
+                        .map(|i| if bits >> i & 1 == 1 { '1' } else { '0' })
+                        .collect(),
+                })
+            })
+            .collect::<Result<_>>()?;
+
+        let matrix = (1..=64)
+            .map(|from| (1..=64).map(|to| between(from, to)).collect())
+            .collect();
+
+        Ok(Self { hexagrams, matrix })
+    }
+
+    pub fn to_json(&self) -> Result<String> {
+        Ok(serde_json::to_string_pretty(self)?)
+    }
+
+    /// A Graphviz digraph: one node per hexagram, one undirected edge per
+    /// pair for the two-way relations, and an arrow to each nuclear hexagram
+    pub fn to_dot(&self) -> String {
+        let mut dot = String::from("digraph relations {\n    node [shape=box];\n");
+        for node in &self.hexagrams {
+            dot.push_str(&format!(
+                "    {} [label=\"{} {}\"];\n",
+                node.number,
+                node.number,
+                node.name.replace('"', "\\\"")
+            ));
+        }
+        for (i, row) in self.matrix.iter().enumerate() {
+            for (j, relations) in row.iter().enumerate() {
+                for relation in relations {
+                    // Two-way relations are drawn once, from the lower number
+                    if *relation != Relation::Nuclear && j < i {
+                        continue;
+                    }
+                    dot.push_str(&format!(
+                        "    {} -> {} [{}];\n",
+                        i + 1,
+                        j + 1,
+                        relation.dot_style()
+                    ));
+                }
+            }
+        }
+        dot.push_str("}\n");
+        dot
+    }
+}
+
+/// How `from` relates to `to`; nothing when they're the same hexagram
+pub fn between(from: u8, to: u8) -> Vec<Relation> {
+    if from == to {
+        return Vec::new();
+    }
+    Relation::ALL
+        .into_iter()
+        .filter(|relation| relation.holds(from, to))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_matrix() {
+        let data = IChingData::load().unwrap();
+        let matrix = Matrix::new(&data).unwrap();
+        assert_eq!(matrix.matrix.len(), 64);
+        assert_eq!(matrix.hexagrams[0].lines, "111111");
+
+        assert_eq!(between(1, 2), [Relation::Complement]);
+        assert_eq!(between(3, 4), [Relation::Inverse]);
+        assert_eq!(between(1, 44), [Relation::Adjacent]);
+        assert_eq!(
+            between(63, 64),
+            [Relation::Complement, Relation::Inverse, Relation::Nuclear]
+        );
+        assert!(between(1, 1).is_empty());
+
+        // Every hexagram has six neighbors a line away
+        for row in &matrix.matrix {
+            let adjacent = row
+                .iter()
+                .filter(|relations| relations.contains(&Relation::Adjacent))
+                .count();
+            assert_eq!(adjacent, 6);
+        }
+
+        let dot = matrix.to_dot();
+        assert!(dot.starts_with("digraph relations {\n"));
+        assert!(dot.contains("\n    1 -> 2 [dir=none, color=red, style=dashed];\n"));
+        assert!(!dot.contains("\n    2 -> 1 "));
+        assert_eq!(dot.matches("color=gray60").count(), 64 * 6 / 2);
+    }
+}
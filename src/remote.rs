@@ -0,0 +1,459 @@
+//! Casting over HTTP: `i-ching serve` answers with readings in the
+//! `--format json` schema, and `i-ching remote` asks for them and shows them
+//! without needing hexagram data of its own
+
+use crate::core::data::IChingData;
+use crate::core::{Diviner, Reading};
+use crate::format::{self, Block, RenderOptions};
+use crate::json::{JsonReading, json_reading_with_data};
+use anyhow::{Context, Result};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::io::{BufRead, BufReader, Read, Write};
+use std::net::{TcpListener, TcpStream};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Where `i-ching serve` listens unless told otherwise
+pub const DEFAULT_LISTEN: &str = "127.0.0.1:7464";
+
+/// Largest request body the server reads
+const MAX_BODY: usize = 64 * 1024;
+
+/// Largest request line and headers, together, that the server reads
+const MAX_HEADERS: u64 = 8 * 1024;
+
+/// How long the server waits on a quiet client before giving up on it
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Body of `POST /cast`; both fields may be left out
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CastRequest {
+    /// Any `--input` format; a random cast if absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub input: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub question: Option<String>,
+}
+
+/// How a reading is sent a section at a time, chosen by the `Accept` header
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Streaming {
+    /// `text/event-stream`: a `section` event per block, then a `done` event
+    Sse,
+    /// `application/x-ndjson`: a `{"section": ...}` line per block, then `{"done": true}`
+    Ndjson,
+}
+
+impl Streaming {
+    /// The streaming an `Accept` header asks for, if any
+    fn accepted(accept: &str) -> Option<Self> {
+        accept
+            .split(',')
+            .map(|media| media.split(';').next().unwrap_or_default().trim())
+            .find_map(|media| match media {
+                "text/event-stream" => Some(Streaming::Sse),
+                "application/x-ndjson" => Some(Streaming::Ndjson),
+                _ => None,
+            })
+    }
+
+    fn content_type(&self) -> &'static str {
+        match self {
+            Streaming::Sse => "text/event-stream",
+            Streaming::Ndjson => "application/x-ndjson",
+        }
+    }
+
+    /// Write `blocks` as events, flushing after each so a client can show
+    /// the reading as it arrives
+    fn write(&self, out: &mut impl Write, blocks: impl IntoIterator<Item = Block>) -> Result<()> {
+        for block in blocks {
+            let section = serde_json::to_string(&block)?;
+            match self {
+                Streaming::Sse => write!(out, "event: section\ndata: {}\n\n", section)?,
+                Streaming::Ndjson => writeln!(out, "{{\"section\":{}}}", section)?,
+            }
+            out.flush()?;
+        }
+        match self {
+            Streaming::Sse => out.write_all(b"event: done\ndata: {}\n\n")?,
+            Streaming::Ndjson => out.write_all(b"{\"done\":true}\n")?,
+        }
+        out.flush()?;
+        Ok(())
+    }
+}
+
+/// What a client sent: the request line, the `Accept` header, and the body
+struct Request {
+    method: String,
+    path: String,
+    accept: String,
+    body: Vec<u8>,
+}
+
+/// An HTTP status and JSON body
+#[derive(Debug, PartialEq)]
+struct Reply {
+    status: u16,
+    body: serde_json::Value,
+}
+
+impl Reply {
+    fn error(status: u16, message: impl ToString) -> Self {
+        Self {
+            status,
+            body: json!({ "error": message.to_string() }),
+        }
+    }
+
+    fn reason(&self) -> &'static str {
+        match self.status {
+            200 => "OK",
+            400 => "Bad Request",
+            404 => "Not Found",
+            405 => "Method Not Allowed",
+            413 => "Payload Too Large",
+            431 => "Request Header Fields Too Large",
+            _ => "Internal Server Error",
+        }
+    }
+}
+
+/// HTTP server for casts and lookups, keeping the data loaded
+///
+/// - `POST /cast` with an optional [`CastRequest`] body
+/// - `GET /hexagrams/N` for hexagram N with no changing lines
+///
+/// Both answer with a reading as `--format json` prints it, or, when the
+/// `Accept` header asks for `text/event-stream` or `application/x-ndjson`,
+/// with the full text a section at a time as the daemon streams it; errors
+/// are `{"error": "..."}`. Clones share the one copy of the data, so each
+/// connection's thread gets its own handle without loading anything.
+#[derive(Clone)]
+pub struct Server {
+    data: Arc<IChingData>,
+}
+
+impl Server {
+    pub fn new() -> Result<Self> {
+        let data = IChingData::load()
+            .map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+        Ok(Self::with_data(Arc::new(data)))
+    }
+
+    /// Serve data already loaded, parsing every hexagram up front
+    pub fn with_data(data: Arc<IChingData>) -> Self {
+        data.warm();
+        Self { data }
+    }
+
+    /// Listen on `addr`, one thread per connection
+    pub fn serve(self, addr: &str) -> Result<()> {
+        let listener =
+            TcpListener::bind(addr).with_context(|| format!("Failed to listen on {}", addr))?;
+        self.serve_listener(listener)
+    }
+
+    /// Accept connections on a socket that's already listening, such as one
+    /// passed by systemd
+    pub fn serve_listener(self, listener: TcpListener) -> Result<()> {
+        eprintln!("Listening on http://{}", listener.local_addr()?);
+
+        for stream in listener.incoming() {
+            let stream = match stream {
+                Ok(stream) => stream,
+                Err(e) => {
+                    eprintln!("Failed to accept connection: {}", e);
+                    continue;
+                }
+            };
+            let server = self.clone();
+            std::thread::spawn(move || {
+                if let Err(e) = server.serve_connection(stream) {
+                    eprintln!("Connection closed with error: {}", e);
+                }
+            });
+        }
+        Ok(())
+    }
+
+    /// Answer one request and close the connection
+    fn serve_connection(&self, stream: TcpStream) -> Result<()> {
+        stream.set_read_timeout(Some(READ_TIMEOUT))?;
+        let mut reader = BufReader::new(stream.try_clone()?);
+        let mut stream = stream;
+        let reply = match read_request(&mut reader) {
+            Ok(request) => match Streaming::accepted(&request.accept) {
+                Some(streaming) => {
+                    match self.streamed(&request.method, &request.path, &request.body) {
+                        Ok(blocks) => {
+                            // No Content-Length: the body ends when the connection closes
+                            write!(
+                                stream,
+                                "HTTP/1.1 200 OK\r\nContent-Type: {}\r\nCache-Control: no-cache\r\nConnection: close\r\n\r\n",
+                                streaming.content_type()
+                            )?;
+                            return streaming.write(&mut stream, blocks);
+                        }
+                        Err(reply) => reply,
+                    }
+                }
+                None => self.route(&request.method, &request.path, &request.body),
+            },
+            Err(reply) => reply,
+        };
+
+        let body = serde_json::to_string(&reply.body)? + "\n";
+        write!(
+            stream,
+            "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n{}",
+            reply.status,
+            reply.reason(),
+            body.len(),
+            body
+        )?;
+        stream.flush()?;
+        Ok(())
+    }
+
+    fn route(&self, method: &str, path: &str, body: &[u8]) -> Reply {
+        let reading = match self.reading(method, path, body) {
+            Ok(reading) => reading,
+            Err(reply) => return reply,
+        };
+        match json_reading_with_data(&reading, &self.data) {
+            Ok(json) => match serde_json::to_value(json) {
+                Ok(body) => Reply { status: 200, body },
+                Err(e) => Reply::error(500, e),
+            },
+            Err(e) => Reply::error(400, e),
+        }
+    }
+
+    /// The full text of the reading a request asks for, in blocks laid out
+    /// as they're taken
+    fn streamed(
+        &self,
+        method: &str,
+        path: &str,
+        body: &[u8],
+    ) -> Result<impl Iterator<Item = Block> + use<>, Reply> {
+        let reading = self.reading(method, path, body)?;
+        format::blocks(&reading, &self.data, &RenderOptions::default())
+            .map_err(|e| Reply::error(400, e))
+    }
+
+    /// The reading a request asks for, or the reply refusing it
+    fn reading(&self, method: &str, path: &str, body: &[u8]) -> Result<Reading, Reply> {
+        let path = path.split('?').next().unwrap_or_default();
+        let reading = match (method, path) {
+            ("POST", "/cast") => {
+                let request: CastRequest = if body.iter().all(u8::is_ascii_whitespace) {
+                    CastRequest::default()
+                } else {
+                    match serde_json::from_slice(body) {
+                        Ok(request) => request,
+                        Err(e) => return Err(Reply::error(400, format!("Invalid request: {}", e))),
+                    }
+                };
+                match request.input {
+                    Some(input) => input.parse::<Reading>(),
+                    None => Ok(Diviner::new().cast_reading(None)),
+                }
+                .map(|reading| reading.with_question(request.question))
+            }
+            ("GET", path) if path.starts_with("/hexagrams/") => path["/hexagrams/".len()..]
+                .parse::<u8>()
+                .map_err(|_| anyhow::anyhow!("Invalid hexagram number"))
+                .and_then(Reading::from_hexagram_number),
+            (_, "/cast") => return Err(Reply::error(405, "Use POST for /cast")),
+            _ => return Err(Reply::error(404, format!("No such endpoint: {}", path))),
+        };
+        reading.map_err(|e| Reply::error(400, e))
+    }
+}
+
+/// The method, path, `Accept` header, and body of an HTTP request, or the
+/// reply refusing it
+fn read_request(reader: &mut impl BufRead) -> Result<Request, Reply> {
+    let bad = |message: &str| Reply::error(400, message);
+    let too_large = || Reply::error(431, "Request headers too large");
+    let mut head = reader.by_ref().take(MAX_HEADERS);
+    let mut line = String::new();
+    head.read_line(&mut line)
+        .map_err(|_| bad("Unreadable request"))?;
+    if !line.ends_with('\n') && head.limit() == 0 {
+        return Err(too_large());
+    }
+    let mut parts = line.split_whitespace();
+    let (Some(method), Some(path)) = (parts.next(), parts.next()) else {
+        return Err(bad("Malformed request line"));
+    };
+    let (method, path) = (method.to_string(), path.to_string());
+
+    let mut length = 0;
+    let mut accept = String::new();
+    loop {
+        line.clear();
+        head.read_line(&mut line)
+            .map_err(|_| bad("Unreadable headers"))?;
+        if !line.ends_with('\n') && head.limit() == 0 {
+            return Err(too_large());
+        }
+        let header = line.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.trim().eq_ignore_ascii_case("content-length") {
+                length = value
+                    .trim()
+                    .parse()
+                    .map_err(|_| bad("Invalid Content-Length"))?;
+            } else if name.trim().eq_ignore_ascii_case("accept") {
+                accept = value.trim().to_string();
+            }
+        }
+    }
+    if length > MAX_BODY {
+        return Err(Reply::error(413, "Request body too large"));
+    }
+
+    let mut body = vec![0; length];
+    reader
+        .read_exact(&mut body)
+        .map_err(|_| bad("Request body shorter than its Content-Length"))?;
+    Ok(Request {
+        method,
+        path,
+        accept,
+        body,
+    })
+}
+
+/// A server to cast on, e.g. `https://oracle.example.com`
+pub struct Remote {
+    url: String,
+}
+
+impl Remote {
+    pub fn new(url: &str) -> Self {
+        Self {
+            url: url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Have the server cast a reading (or build one from `input`)
+    pub fn cast(&self, request: &CastRequest) -> Result<JsonReading> {
+        let url = format!("{}/cast", self.url);
+        let response = ureq::post(&url)
+            .config()
+            .http_status_as_error(false)
+            .build()
+            .send_json(request)
+            .with_context(|| format!("Failed to reach {}", url))?;
+        read_reading(response, &url)
+    }
+}
+
+fn read_reading(mut response: ureq::http::Response<ureq::Body>, url: &str) -> Result<JsonReading> {
+    let status = response.status();
+    let body: serde_json::Value = response
+        .body_mut()
+        .read_json()
+        .with_context(|| format!("Unexpected response from {}", url))?;
+    if !status.is_success() {
+        let message = body["error"].as_str().unwrap_or("no error message");
+        return Err(anyhow::anyhow!("{} answered {}: {}", url, status, message));
+    }
+    serde_json::from_value(body).with_context(|| format!("{} didn't answer with a reading", url))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_route() {
+        let server = Server::new().unwrap();
+
+        let reply = server.route(
+            "POST",
+            "/cast",
+            br#"{"input": "32->34", "question": "Stay?"}"#,
+        );
+        assert_eq!(reply.status, 200);
+        let reading: JsonReading = serde_json::from_value(reply.body).unwrap();
+        assert_eq!(reading.question.as_deref(), Some("Stay?"));
+        assert_eq!(reading.transformed_hexagram.unwrap().number, 34);
+
+        assert_eq!(server.route("POST", "/cast", b"").status, 200);
+        assert_eq!(
+            server.route("GET", "/hexagrams/29", b"").body["primary_hexagram"]["number"],
+            29
+        );
+        assert_eq!(server.route("GET", "/hexagrams/65", b"").status, 400);
+        assert_eq!(server.route("POST", "/cast", b"{\"op\": 1}").status, 400);
+        assert_eq!(server.route("GET", "/cast", b"").status, 405);
+        assert_eq!(server.route("GET", "/", b"").status, 404);
+
+        let request = b"POST /cast HTTP/1.1\r\nHost: x\r\nAccept: text/event-stream\r\nContent-Length: 2\r\n\r\n{}";
+        let request = read_request(&mut &request[..]).unwrap();
+        assert_eq!(
+            (
+                request.method.as_str(),
+                request.path.as_str(),
+                &request.body[..]
+            ),
+            ("POST", "/cast", &b"{}"[..])
+        );
+        assert_eq!(Streaming::accepted(&request.accept), Some(Streaming::Sse));
+        assert_eq!(
+            Streaming::accepted("application/json, application/x-ndjson;q=0.5"),
+            Some(Streaming::Ndjson)
+        );
+        assert_eq!(Streaming::accepted("application/json"), None);
+        let mut oversized = b"GET / HTTP/1.1\r\n".to_vec();
+        while oversized.len() <= MAX_HEADERS as usize {
+            oversized.extend_from_slice(b"X-Padding: 0123456789abcdef\r\n");
+        }
+        oversized.extend_from_slice(b"\r\n");
+        assert_eq!(
+            read_request(&mut &oversized[..])
+                .err()
+                .map(|reply| reply.status),
+            Some(431)
+        );
+
+        // Streamed sections join to the full text
+        let blocks: Vec<Block> = server
+            .streamed("POST", "/cast", br#"{"input": "32->34"}"#)
+            .unwrap()
+            .collect();
+        let mut sse = Vec::new();
+        Streaming::Sse.write(&mut sse, blocks.clone()).unwrap();
+        let sse = String::from_utf8(sse).unwrap();
+        assert!(sse.starts_with("event: section\ndata: {\"kind\":"));
+        assert!(sse.ends_with("event: done\ndata: {}\n\n"));
+        let mut ndjson = Vec::new();
+        Streaming::Ndjson.write(&mut ndjson, blocks).unwrap();
+        let lines: Vec<serde_json::Value> = String::from_utf8(ndjson)
+            .unwrap()
+            .lines()
+            .map(|line| serde_json::from_str(line).unwrap())
+            .collect();
+        let text: String = lines
+            .iter()
+            .filter_map(|line| line["section"]["text"].as_str())
+            .collect();
+        assert!(text.contains("Long Lasting"));
+        assert_eq!(lines.last().unwrap()["done"], true);
+        assert_eq!(
+            server.streamed("GET", "/", b"").err().map(|reply| reply.status),
+            Some(404)
+        );
+    }
+}
@@ -0,0 +1,968 @@
+//! A reading as titled sections of text, before any output format is applied
+//!
+//! [`Rendered::new`] gathers what a reading says once, from the reading in
+//! the `--format json` schema (which it keeps, for JSON output); full text,
+//! its streamed blocks, markdown, HTML, SSML, and Org (and the batch report
+//! and dashboard) only lay the sections out.
+
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::core::king_wen;
+use crate::format::{
+    FullOptions, TextOnly, describe_pair_relation, format_notes, format_pair_heading,
+    format_trigram, format_trigram_discussion, format_zhouyi, write_dynamics, write_figure,
+    write_see_also, write_spirits, write_structure,
+};
+use crate::glyphs::Glyphs;
+use crate::interpretation::framing::{self, Section as Order};
+use crate::json::{
+    JsonCrossReference, JsonHexagram, JsonReading, json_reading_with_data, reading_from_json,
+};
+use crate::lang::Lang;
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize, Serializer};
+use std::fmt;
+
+/// Room for a typical full reading, so the text is written without regrowing
+const FULL_CAPACITY: usize = 8 * 1024;
+
+/// What a section of a reading holds
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SectionKind {
+    Question,
+    Context,
+    /// The topic framing's introduction
+    Framing,
+    /// The figure, line numbers, trigrams, structure, elemental dynamics,
+    /// and six spirits (full text only)
+    Figure,
+    /// The primary hexagram's Chinese name and description
+    Hexagram,
+    Judgment,
+    Image,
+    /// One changing line; the focus line, then emphasized lines, come first
+    ChangingLine,
+    Transformation,
+    Wings,
+    Pair,
+    Notes,
+    /// Bare text (`--text-only`), which full text shows instead of the rest
+    Text,
+}
+
+/// A part of a reading that `--sections` can pick out
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Part {
+    /// The question and its context
+    Question,
+    /// The topic framing's introduction
+    Framing,
+    /// The figure, trigrams, line structure, and elemental dynamics (full
+    /// text only)
+    Figure,
+    /// The primary hexagram's name, Chinese name, and description
+    Hexagram,
+    Judgment,
+    Image,
+    /// The changing line texts
+    Lines,
+    /// Wilhelm's commentary on the judgment, image, and lines
+    Commentary,
+    Transformation,
+    Wings,
+    Pair,
+    Notes,
+}
+
+impl Part {
+    /// The part a section belongs to; bare text has none
+    pub fn of(kind: SectionKind) -> Option<Part> {
+        match kind {
+            SectionKind::Question | SectionKind::Context => Some(Part::Question),
+            SectionKind::Framing => Some(Part::Framing),
+            SectionKind::Figure => Some(Part::Figure),
+            SectionKind::Hexagram => Some(Part::Hexagram),
+            SectionKind::Judgment => Some(Part::Judgment),
+            SectionKind::Image => Some(Part::Image),
+            SectionKind::ChangingLine => Some(Part::Lines),
+            SectionKind::Transformation => Some(Part::Transformation),
+            SectionKind::Wings => Some(Part::Wings),
+            SectionKind::Pair => Some(Part::Pair),
+            SectionKind::Notes => Some(Part::Notes),
+            SectionKind::Text => None,
+        }
+    }
+}
+
+/// A titled passage of a reading
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Section {
+    pub kind: SectionKind,
+    pub title: String,
+    /// The text, a paragraph at a time; serialized as one `body` with
+    /// paragraphs separated by blank lines
+    #[serde(rename = "body", serialize_with = "joined")]
+    pub paragraphs: Vec<String>,
+    /// Wilhelm's commentary on the body, where there is one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub commentary: Option<String>,
+    /// A changing line's position
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<u8>,
+    /// Lines whose texts quote or mirror a changing line
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub see_also: Vec<JsonCrossReference>,
+    /// The heading full text gives the section: names and line labels in
+    /// the chosen language, linked if links are on
+    #[serde(skip)]
+    pub label: String,
+}
+
+impl Section {
+    fn new(kind: SectionKind, title: impl Into<String>, paragraphs: Vec<String>) -> Self {
+        let title = title.into();
+        Section {
+            kind,
+            label: title.clone(),
+            title,
+            paragraphs,
+            commentary: None,
+            line: None,
+            see_also: Vec::new(),
+        }
+    }
+
+    fn with_commentary(mut self, commentary: &str) -> Self {
+        self.commentary = Some(commentary.to_string());
+        self
+    }
+
+    fn with_label(mut self, label: String) -> Self {
+        self.label = label;
+        self
+    }
+
+    /// The paragraphs separated by blank lines
+    pub fn body(&self) -> String {
+        self.paragraphs.join("\n\n")
+    }
+
+    /// Paragraph `index`, or nothing if there are fewer
+    fn paragraph(&self, index: usize) -> &str {
+        self.paragraphs.get(index).map_or("", String::as_str)
+    }
+
+    /// Each paragraph, split again where the text itself has blank lines
+    fn split_paragraphs(&self) -> impl Iterator<Item = &str> {
+        self.paragraphs
+            .iter()
+            .flat_map(|paragraph| paragraph.split("\n\n"))
+            .map(str::trim)
+            .filter(|paragraph| !paragraph.is_empty())
+    }
+
+    /// Whether markdown, HTML, SSML, and Org lay the section out; the
+    /// figure and bare text are for the terminal
+    fn is_prose(&self) -> bool {
+        !matches!(self.kind, SectionKind::Figure | SectionKind::Text)
+    }
+}
+
+fn joined<S: Serializer>(paragraphs: &[String], serializer: S) -> Result<S::Ok, S::Error> {
+    serializer.serialize_str(&paragraphs.join("\n\n"))
+}
+
+/// One section of full text; joined in order, blocks give the whole text
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Block {
+    pub kind: SectionKind,
+    pub text: String,
+}
+
+/// A reading's summary line and its sections, in reading order
+#[derive(Debug, Clone, Serialize)]
+pub struct Rendered {
+    /// One-line summary, e.g. "䷟ 32 Long Lasting → ䷡ 34 Great Strength (lines: [1])"
+    pub title: String,
+    pub sections: Vec<Section>,
+    /// The reading in the `--format json` schema
+    #[serde(skip)]
+    pub json: JsonReading,
+    /// The changing line full text gives first, under its own heading
+    #[serde(skip)]
+    focus_line: Option<u8>,
+}
+
+impl Rendered {
+    /// The sections `options` asks for, ordered by the topic framing if any
+    pub fn new(
+        reading: &Reading,
+        data: &IChingData,
+        glyphs: Glyphs,
+        options: &FullOptions,
+    ) -> Result<Self> {
+        let json = json_reading_with_data(reading, data)?;
+        Ok(Rendered::build(json, reading, Some(data), glyphs, options))
+    }
+
+    /// A reading in the `--format json` schema, from its own texts and
+    /// names rather than the local data
+    pub fn from_json(json: JsonReading, glyphs: Glyphs) -> Result<Self> {
+        let reading = reading_from_json(&json)?;
+        Ok(Rendered::build(
+            json,
+            &reading,
+            None,
+            glyphs,
+            &FullOptions::default(),
+        ))
+    }
+
+    /// The sections of `json`, with what only `data` has (translated and
+    /// linked names, the Ten Wings, the pair, notes, and bare text) if it's
+    /// given
+    fn build(
+        json: JsonReading,
+        reading: &Reading,
+        data: Option<&IChingData>,
+        glyphs: Glyphs,
+        options: &FullOptions,
+    ) -> Self {
+        let name = |hexagram: &JsonHexagram| {
+            format!(
+                "{}{} {}",
+                glyphs.prefix(&hexagram.unicode),
+                hexagram.number,
+                hexagram.name
+            )
+        };
+        let local = |number: u8| data.and_then(|data| data.get_hexagram(number));
+        let heading = |hexagram: &JsonHexagram| {
+            let name = local(hexagram.number).map_or_else(
+                || hexagram.name.clone(),
+                |local| options.hexagram_name(local),
+            );
+            format!("{}{}", glyphs.prefix(&hexagram.unicode), name)
+        };
+
+        let primary = &json.primary_hexagram;
+        let mut title = name(primary);
+        if let Some(transformed) = &json.transformed_hexagram {
+            title = format!(
+                "{} {} {} (lines: {})",
+                title,
+                glyphs.arrow(),
+                name(transformed),
+                Lang::En.changing_lines(reading)
+            );
+        }
+        let mut sections = Vec::new();
+
+        if let Some(question) = &json.question {
+            sections.push(Section::new(
+                SectionKind::Question,
+                "Question",
+                vec![question.clone()],
+            ));
+        }
+        if let Some(context) = &json.context {
+            sections.push(Section::new(
+                SectionKind::Context,
+                "Context",
+                vec![context.clone()],
+            ));
+        }
+        let framing = options.topic.map(|topic| framing::frame(topic, reading));
+        if let Some(framing) = &framing {
+            sections.push(Section::new(
+                SectionKind::Framing,
+                format!("Framing: {}", framing.topic.label()),
+                vec![framing.intro.clone()],
+            ));
+        }
+        sections.push(figure(reading, glyphs, options));
+        sections.push(
+            Section::new(
+                SectionKind::Hexagram,
+                name(primary),
+                vec![
+                    format!("{} ({})", primary.chinese, primary.pinyin),
+                    primary.description.clone(),
+                ],
+            )
+            .with_label(heading(primary)),
+        );
+
+        let positions: Vec<u8> = json
+            .changing_lines
+            .iter()
+            .map(|line| line.position)
+            .collect();
+        let focus_line = options.focus_line.filter(|line| positions.contains(line));
+        let order = framing
+            .as_ref()
+            .map_or(framing::DEFAULT_ORDER, |framing| framing.order);
+        for section in order {
+            match section {
+                Order::Judgment => sections.push(
+                    Section::new(
+                        SectionKind::Judgment,
+                        "Judgment",
+                        vec![primary.judgment.text.clone()],
+                    )
+                    .with_commentary(&primary.judgment.commentary),
+                ),
+                Order::Image => sections.push(
+                    Section::new(
+                        SectionKind::Image,
+                        "Image",
+                        vec![primary.image.text.clone()],
+                    )
+                    .with_commentary(&primary.image.commentary),
+                ),
+                Order::ChangingLines => {
+                    let positions = match &framing {
+                        Some(framing) => framing.order_lines(&positions),
+                        None => positions.clone(),
+                    };
+                    for position in focus_first(positions, focus_line) {
+                        let Some(line) = json
+                            .changing_lines
+                            .iter()
+                            .find(|line| line.position == position)
+                        else {
+                            continue;
+                        };
+                        let emphasis = framing
+                            .as_ref()
+                            .and_then(|framing| framing.emphasis_for(position));
+                        let drawn = &reading.lines()[usize::from(position - 1)];
+                        let mut label = match local(primary.number) {
+                            Some(hexagram) => options.line_label(hexagram, position, drawn),
+                            None => options.lang.line_label(position, drawn),
+                        };
+                        let title = if focus_line == Some(position) {
+                            format!("Line {} (focus)", position)
+                        } else if let Some(emphasis) = emphasis {
+                            label = format!("{} ({})", label, emphasis.label);
+                            format!("Line {} ({})", position, emphasis.label)
+                        } else {
+                            format!("Line {}", position)
+                        };
+                        let mut section =
+                            Section::new(SectionKind::ChangingLine, title, vec![line.text.clone()])
+                                .with_commentary(&line.comments)
+                                .with_label(label);
+                        section.line = Some(position);
+                        section.see_also = line.see_also.clone();
+                        sections.push(section);
+                    }
+                }
+                Order::Transformation => {
+                    if let Some(transformed) = &json.transformed_hexagram {
+                        sections.push(
+                            Section::new(
+                                SectionKind::Transformation,
+                                format!("Transforms to {}", name(transformed)),
+                                vec![
+                                    format!("{} ({})", transformed.chinese, transformed.pinyin),
+                                    transformed.description.clone(),
+                                    format!("Judgment: {}", transformed.judgment.text),
+                                ],
+                            )
+                            .with_label(format!("Transforms to {}", heading(transformed))),
+                        );
+                    }
+                }
+            }
+        }
+
+        if let Some(data) = data {
+            sections.extend(local_sections(reading, data, glyphs, options));
+        }
+
+        Rendered {
+            title,
+            sections,
+            json,
+            focus_line,
+        }
+        .select(options)
+    }
+
+    /// Only the parts `options` selects
+    fn select(mut self, options: &FullOptions) -> Self {
+        self.sections
+            .retain(|section| Part::of(section.kind).is_none_or(|part| options.shows(part)));
+        if !options.shows(Part::Commentary) {
+            for section in &mut self.sections {
+                section.commentary = None;
+            }
+        }
+        self
+    }
+
+    /// Sections of these kinds, in order
+    pub fn of_kind<'a>(
+        &'a self,
+        kinds: &'a [SectionKind],
+    ) -> impl Iterator<Item = &'a Section> + 'a {
+        self.sections
+            .iter()
+            .filter(move |section| kinds.contains(&section.kind))
+    }
+
+    /// The sections markdown, HTML, SSML, and Org lay out
+    fn prose(&self) -> impl Iterator<Item = &Section> {
+        self.sections.iter().filter(|section| section.is_prose())
+    }
+
+    /// Full text, as `--format full` prints it (without the final newline)
+    pub fn to_text(&self) -> String {
+        let mut text = String::with_capacity(FULL_CAPACITY);
+        for block in self.blocks() {
+            text.push_str(&block.text);
+        }
+        text
+    }
+
+    /// Full text a section at a time, each laid out as it's asked for
+    ///
+    /// Full text puts the framing, the question, the focus line, and the
+    /// figure ahead of the rest; bare text, if there is any, stands alone.
+    /// Blank lines that led into a section are dropped where they'd start
+    /// the text or double up.
+    pub fn blocks(&self) -> impl Iterator<Item = Block> + '_ {
+        let mut layout = Layout::new();
+        self.text_order()
+            .into_iter()
+            .map(move |index| layout.block(self, &self.sections[index]))
+    }
+
+    /// [`Rendered::blocks`], owning the sections, for streaming a reading
+    /// out a section at a time
+    pub fn into_blocks(self) -> impl Iterator<Item = Block> {
+        let mut layout = Layout::new();
+        self.text_order()
+            .into_iter()
+            .map(move |index| layout.block(&self, &self.sections[index]))
+    }
+
+    /// Indices of the sections in full-text order
+    fn text_order(&self) -> Vec<usize> {
+        if let Some(index) = self
+            .sections
+            .iter()
+            .position(|section| section.kind == SectionKind::Text)
+        {
+            return vec![index];
+        }
+        let mut order: Vec<usize> = (0..self.sections.len()).collect();
+        order.sort_by_key(|&index| {
+            let section = &self.sections[index];
+            match section.kind {
+                SectionKind::Framing => 0,
+                SectionKind::Question | SectionKind::Context => 1,
+                SectionKind::ChangingLine if self.is_focus(section) => 2,
+                SectionKind::Figure => 3,
+                _ => 4,
+            }
+        });
+        order
+    }
+
+    fn is_focus(&self, section: &Section) -> bool {
+        section.line.is_some() && section.line == self.focus_line
+    }
+
+    /// One section of full text, with the changing lines' heading ahead of
+    /// the first of them
+    fn write_section(
+        &self,
+        out: &mut impl fmt::Write,
+        section: &Section,
+        in_lines: &mut bool,
+    ) -> fmt::Result {
+        let is_line = section.kind == SectionKind::ChangingLine && !self.is_focus(section);
+        if is_line && !*in_lines {
+            out.write_str("\n=== Changing Lines ===\n")?;
+        }
+        *in_lines = is_line;
+
+        match section.kind {
+            SectionKind::Question => write!(out, "Question: {}\n\n", section.body()),
+            SectionKind::Context => write!(out, "Context:\n{}\n\n", section.body()),
+            SectionKind::Framing => {
+                write!(out, "=== {} ===\n{}\n\n", section.title, section.body())
+            }
+            SectionKind::Figure => writeln!(out, "{}", section.body()),
+            SectionKind::Hexagram => write!(
+                out,
+                "\n=== {} ===\nChinese: {}\nDescription: {}\n",
+                section.label,
+                section.paragraph(0),
+                section.paragraph(1)
+            ),
+            SectionKind::Judgment => {
+                write!(out, "\nJudgment: {}\n", section.body())?;
+                if let Some(commentary) = &section.commentary {
+                    writeln!(out, "Commentary: {}", commentary)?;
+                }
+                Ok(())
+            }
+            SectionKind::Image => {
+                write!(out, "\nImage: {}\n", section.body())?;
+                if let Some(commentary) = &section.commentary {
+                    writeln!(out, "Image Commentary: {}", commentary)?;
+                }
+                Ok(())
+            }
+            SectionKind::ChangingLine => {
+                if let Some(line) = section.line.filter(|_| self.is_focus(section)) {
+                    writeln!(out, "=== Focus: Line {} ===", line)?;
+                }
+                writeln!(out, "{}: {}", section.label, section.body())?;
+                if let Some(comments) = &section.commentary {
+                    writeln!(out, "Comments: {}", comments)?;
+                }
+                write_see_also(out, &section.see_also)?;
+                out.write_str("\n")
+            }
+            SectionKind::Transformation => write!(
+                out,
+                "\n=== {} ===\nChinese: {}\nDescription: {}\n{}\n",
+                section.label,
+                section.paragraph(0),
+                section.paragraph(1),
+                section.paragraph(2)
+            ),
+            SectionKind::Wings | SectionKind::Pair | SectionKind::Notes => {
+                write!(out, "\n=== {} ===\n", section.title)?;
+                for paragraph in &section.paragraphs {
+                    writeln!(out, "{}", paragraph)?;
+                }
+                Ok(())
+            }
+            SectionKind::Text => out.write_str(&section.body()),
+        }
+    }
+
+    /// Markdown with a `##` heading per section, changing lines grouped
+    /// under one heading, and commentary as block quotes
+    pub fn to_markdown(&self) -> String {
+        let mut result = format!("# {}\n", self.title);
+        let mut in_lines = false;
+        for section in self.prose() {
+            let is_line = section.kind == SectionKind::ChangingLine;
+            if is_line && !in_lines {
+                result.push_str("\n## Changing Lines\n");
+            }
+            in_lines = is_line;
+
+            let level = if is_line { "###" } else { "##" };
+            result.push_str(&format!("\n{} {}\n", level, section.title));
+            // Verse keeps its line breaks as hard breaks
+            for paragraph in section.split_paragraphs() {
+                result.push_str(&format!("\n{}\n", paragraph.replace('\n', "  \n")));
+            }
+            if let Some(commentary) = &section.commentary {
+                for paragraph in commentary.split("\n\n") {
+                    result.push_str(&format!("\n> {}\n", paragraph.replace('\n', "\n> ")));
+                }
+            }
+        }
+        result
+    }
+
+    /// A standalone HTML page with an `<h2>` and paragraphs per section
+    pub fn to_html(&self) -> String {
+        let mut html = format!(
+            "<!DOCTYPE html>\n<html lang=\"en\">\n<head>\n<meta charset=\"utf-8\">\n\
+             <title>{}</title>\n<style>\n\
+             body {{ font-family: serif; max-width: 40em; margin: 2em auto; line-height: 1.5; }}\n\
+             .commentary {{ color: #555; font-style: italic; }}\n\
+             </style>\n</head>\n<body>\n<article>\n<h1>{}</h1>\n",
+            escape(&self.title),
+            escape(&self.title)
+        );
+        for section in self.prose() {
+            html.push_str(&format!(
+                "<section class=\"{}\">\n<h2>{}</h2>\n",
+                kind_name(section.kind),
+                escape(&section.title)
+            ));
+            for paragraph in section.split_paragraphs() {
+                html.push_str(&format!(
+                    "<p>{}</p>\n",
+                    escape(paragraph).replace('\n', "<br>\n")
+                ));
+            }
+            if let Some(commentary) = &section.commentary {
+                html.push_str(&format!(
+                    "<p class=\"commentary\">{}</p>\n",
+                    escape(commentary).replace('\n', "<br>\n")
+                ));
+            }
+            html.push_str("</section>\n");
+        }
+        html.push_str("</article>\n</body>\n</html>\n");
+        html
+    }
+
+    /// SSML for reading aloud: the summary, then each section's title and
+    /// text (without commentary), with pauses between sections
+    ///
+    /// The hexagram section is just its description, since the summary has
+    /// already named it.
+    pub fn to_ssml(&self) -> String {
+        let mut ssml = String::from(
+            "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"en-US\">\n",
+        );
+        ssml.push_str(&format!("  <p>{}</p>\n", escape(&spoken(&self.title))));
+        for section in self.prose() {
+            ssml.push_str("  <break time=\"700ms\"/>\n");
+            let mut paragraphs = section.split_paragraphs();
+            if section.kind != SectionKind::Hexagram {
+                ssml.push_str(&format!("  <p>{}.</p>\n", escape(&spoken(&section.title))));
+            }
+            if matches!(
+                section.kind,
+                SectionKind::Hexagram | SectionKind::Transformation
+            ) {
+                paragraphs.next();
+            }
+            for paragraph in paragraphs {
+                ssml.push_str(&format!("  <p>{}</p>\n", escape(paragraph)));
+            }
+        }
+        ssml.push_str("</speak>\n");
+        ssml
+    }
+
+    /// Org-mode subheadings at `level` (and commentary one level down)
+    pub fn to_org(&self, level: usize) -> String {
+        let heading = "*".repeat(level);
+        let mut result = String::new();
+        let mut in_lines = false;
+        for section in self.prose() {
+            let is_line = section.kind == SectionKind::ChangingLine;
+            if is_line && !in_lines {
+                result.push_str(&format!("{} Changing Lines\n", heading));
+            }
+            in_lines = is_line;
+
+            let level = if is_line { level + 1 } else { level };
+            result.push_str(&format!(
+                "{} {}\n{}\n",
+                "*".repeat(level),
+                section.title,
+                section.body()
+            ));
+            if let Some(commentary) = &section.commentary {
+                result.push_str(&format!(
+                    "{} Commentary\n{}\n",
+                    "*".repeat(level + 1),
+                    commentary
+                ));
+            }
+        }
+        result
+    }
+}
+
+/// How far full text has got, from one block to the next
+struct Layout {
+    /// Whether the text so far is empty or ends in a blank line
+    blank: bool,
+    /// Whether the last block was a changing line under the lines' heading
+    in_lines: bool,
+}
+
+impl Layout {
+    fn new() -> Self {
+        Layout {
+            blank: true,
+            in_lines: false,
+        }
+    }
+
+    /// `section` as the next block of `rendered`'s full text
+    fn block(&mut self, rendered: &Rendered, section: &Section) -> Block {
+        let mut text = String::new();
+        // Writing to a String can't fail
+        let _ = rendered.write_section(&mut text, section, &mut self.in_lines);
+        if self.blank {
+            text.drain(..text.len() - text.trim_start_matches('\n').len());
+        }
+        if !text.is_empty() {
+            self.blank = text.ends_with("\n\n");
+        }
+        Block {
+            kind: section.kind,
+            text,
+        }
+    }
+}
+
+/// Changing line positions with the `--focus-line` line, if it's one of
+/// them, moved to the front
+pub(crate) fn focus_first(mut positions: Vec<u8>, focus: Option<u8>) -> Vec<u8> {
+    if let Some(index) = positions
+        .iter()
+        .position(|&position| Some(position) == focus)
+    {
+        let focus = positions.remove(index);
+        positions.insert(0, focus);
+    }
+    positions
+}
+
+/// The figure, line numbers, trigrams, line structure, elemental dynamics,
+/// and any six spirits, a paragraph each
+fn figure(reading: &Reading, glyphs: Glyphs, options: &FullOptions) -> Section {
+    let written = |write: &dyn Fn(&mut String) -> fmt::Result| {
+        let mut text = String::new();
+        // Writing to a String can't fail
+        let _ = write(&mut text);
+        text.trim_matches('\n').to_string()
+    };
+    let mut paragraphs = vec![
+        written(&|out| write_figure(out, reading, glyphs, options.lang)),
+        format!(
+            "Traditional numbers: {:?}\nUpper trigram: {}\nLower trigram: {}",
+            reading.traditional_numbers(),
+            format_trigram(reading.upper_trigram(), glyphs),
+            format_trigram(reading.lower_trigram(), glyphs)
+        ),
+        written(&|out| write_structure(out, reading, glyphs)),
+        written(&|out| write_dynamics(out, reading)),
+    ];
+    if let Some(date) = options.spirits_date {
+        paragraphs.push(written(&|out| write_spirits(out, reading, date, glyphs)));
+    }
+    Section::new(SectionKind::Figure, "Figure", paragraphs)
+}
+
+/// The sections only the local data has: the Ten Wings, the King Wen pair,
+/// the user's notes, and bare text, as `options` asks for them
+fn local_sections(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+    options: &FullOptions,
+) -> Vec<Section> {
+    let mut sections = Vec::new();
+    let number = reading.primary_hexagram();
+    let Some(hexagram) = data.get_hexagram(number) else {
+        return sections;
+    };
+
+    if options.include_wings {
+        let mut paragraphs = Vec::new();
+        if let Some(sequence) = &hexagram.sequence {
+            paragraphs.push(format!("Sequence: {}", sequence));
+        }
+        if let Some(miscellaneous) = &hexagram.miscellaneous {
+            paragraphs.push(format!("Miscellaneous Notes: {}", miscellaneous));
+        }
+        for (position, trigram) in [
+            ("Upper", reading.upper_trigram()),
+            ("Lower", reading.lower_trigram()),
+        ] {
+            if let Some(discussion) = data
+                .get_trigram(trigram)
+                .and_then(|data| data.discussion.as_ref())
+            {
+                paragraphs.push(format!(
+                    "{} trigram, {}: {}",
+                    position,
+                    trigram,
+                    format_trigram_discussion(discussion)
+                ));
+            }
+        }
+        sections.push(Section::new(SectionKind::Wings, "Ten Wings", paragraphs));
+    }
+
+    if options.include_pair {
+        if let Some(pair) = king_wen::pair(number) {
+            if let Some(partner) = data.get_hexagram(pair.partner_of(number)) {
+                sections.push(Section::new(
+                    SectionKind::Pair,
+                    "King Wen Pair",
+                    vec![
+                        format_pair_heading(&pair, data, glyphs),
+                        describe_pair_relation(&pair),
+                        format!(
+                            "Partner judgment ({}{}): {}",
+                            glyphs.prefix(&partner.unicode),
+                            partner.name,
+                            partner.judgment.text
+                        ),
+                    ],
+                ));
+            }
+        }
+    }
+
+    let paragraphs: Vec<String> = std::iter::once(number)
+        .chain(reading.transformed_hexagram_number())
+        .filter_map(|number| {
+            let notes = options.notes.get(&number)?;
+            let notes = format_notes(number, notes, data, glyphs);
+            Some(notes.trim_end_matches('\n').to_string())
+        })
+        .collect();
+    if !paragraphs.is_empty() {
+        sections.push(Section::new(SectionKind::Notes, "Your Notes", paragraphs));
+    }
+
+    if options.text_only == Some(TextOnly::Zhouyi) {
+        sections.push(Section::new(
+            SectionKind::Text,
+            "Text",
+            vec![format_zhouyi(reading, data, glyphs)],
+        ));
+    }
+
+    sections
+}
+
+/// The section kind as it's named in JSON, for HTML classes
+fn kind_name(kind: SectionKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+/// A title with the hexagram characters and arrows a synthesizer can't say
+fn spoken(text: &str) -> String {
+    let text: String = text
+        .replace(" → ", " changing to ")
+        .replace(" -> ", " changing to ")
+        .chars()
+        .filter(|c| !crate::core::data::HEXAGRAM_CHARS.contains(c))
+        .collect();
+    text.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interpretation::framing::Topic;
+
+    fn rendered(input: &str, options: &FullOptions) -> Rendered {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = input.parse().unwrap();
+        let reading = reading.with_question(Some("Take <the> job?".to_string()));
+        Rendered::new(&reading, &data, Glyphs::Ascii, options).unwrap()
+    }
+
+    #[test]
+    fn test_sections() {
+        // The focus line leads the changing lines in every format
+        let options = FullOptions {
+            focus_line: Some(3),
+            ..FullOptions::default()
+        };
+        let focused = rendered("9,8,6,7,7,8", &options);
+        let markdown = focused.to_markdown();
+        let focus = markdown.find("### Line 3 (focus)\n").unwrap();
+        assert!(focus < markdown.find("### Line 1\n").unwrap());
+        assert!(markdown.contains("\n## Changing Lines\n\n### Line 3 (focus)\n"));
+        // Full text gives it ahead of the figure, under its own heading
+        let text = focused.to_text();
+        assert!(text.starts_with("Question: Take <the> job?\n\n=== Focus: Line 3 ===\nLine 3: "));
+        assert!(text.contains("\n=== Changing Lines ===\nLine 1: "));
+        assert!(!text.contains("(focus)"));
+
+        let rendered = rendered(
+            "9,8,6,7,7,8",
+            &FullOptions {
+                topic: Some(Topic::Career),
+                include_pair: true,
+                ..FullOptions::default()
+            },
+        );
+        let kinds: Vec<SectionKind> = rendered.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds[..4],
+            [
+                SectionKind::Question,
+                SectionKind::Framing,
+                SectionKind::Figure,
+                SectionKind::Hexagram
+            ]
+        );
+        assert_eq!(kinds.last(), Some(&SectionKind::Pair));
+        let lines: Vec<&str> = rendered
+            .of_kind(&[SectionKind::ChangingLine])
+            .map(|section| section.title.as_str())
+            .collect();
+        assert_eq!(lines.len(), 2);
+        assert!(rendered.sections[3].title.starts_with(char::is_numeric));
+    }
+
+    #[test]
+    fn test_selected_sections() {
+        let options = FullOptions {
+            only: Some(vec![Part::Judgment, Part::Lines]),
+            include_wings: true,
+            ..FullOptions::default()
+        };
+        let lines = rendered("9,8,6,7,7,8", &options);
+        let kinds: Vec<SectionKind> = lines.sections.iter().map(|s| s.kind).collect();
+        assert_eq!(
+            kinds,
+            [
+                SectionKind::Judgment,
+                SectionKind::ChangingLine,
+                SectionKind::ChangingLine
+            ]
+        );
+        assert!(lines.sections.iter().all(|s| s.commentary.is_none()));
+
+        let options = FullOptions {
+            only: Some(vec![Part::Image, Part::Commentary]),
+            ..FullOptions::default()
+        };
+        let image = rendered("9,8,6,7,7,8", &options);
+        assert_eq!(image.sections.len(), 1);
+        assert!(image.sections[0].commentary.is_some());
+    }
+
+    #[test]
+    fn test_formats() {
+        let rendered = rendered("32->34", &FullOptions::default());
+
+        let markdown = rendered.to_markdown();
+        assert!(markdown.starts_with("# 32 Long Lasting -> 34 Great Strength"));
+        assert!(markdown.contains("\n## Changing Lines\n\n### Line 1\n"));
+        assert!(markdown.contains("\n> "));
+
+        let html = rendered.to_html();
+        assert!(html.contains("<section class=\"changing_line\">\n<h2>Line 1</h2>\n"));
+        assert!(html.contains("<p>Take &lt;the&gt; job?</p>"));
+
+        let ssml = rendered.to_ssml();
+        assert!(ssml.contains("<p>32 Long Lasting changing to 34 Great Strength (lines: [1])</p>"));
+        assert!(!ssml.contains("Commentary"));
+        assert!(!ssml.contains("恆"));
+
+        let org = rendered.to_org(2);
+        assert!(org.starts_with("** Question\nTake <the> job?\n"));
+        assert!(org.contains("** Changing Lines\n*** Line 1\n"));
+        assert!(org.contains("*** Commentary\n"));
+    }
+}
@@ -0,0 +1,232 @@
+use crate::interpretation::framing::Topic;
+use crate::journal::JournalEntry;
+use chrono::{DateTime, Local, Utc};
+use std::collections::BTreeMap;
+
+/// Counts over journal entries, for `i-ching journal report`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Report {
+    pub readings: usize,
+    /// When the earliest and latest readings were cast
+    pub span: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    /// Readings per primary hexagram
+    pub hexagrams: BTreeMap<u8, usize>,
+    /// Readings per number of changing lines (0-6)
+    pub changing_lines: [usize; 7],
+    /// Readings per topic (`None` when untagged) in each local month, `YYYY-MM`
+    pub topics: BTreeMap<String, BTreeMap<Option<Topic>, usize>>,
+}
+
+impl Report {
+    pub fn new(entries: &[JournalEntry]) -> Self {
+        let mut report = Report {
+            readings: entries.len(),
+            ..Default::default()
+        };
+
+        for entry in entries {
+            let reading = &entry.reading;
+            *report
+                .hexagrams
+                .entry(reading.primary_hexagram())
+                .or_default() += 1;
+            report.changing_lines[reading.changing_line_positions().len()] += 1;
+
+            let month = entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m")
+                .to_string();
+            *report
+                .topics
+                .entry(month)
+                .or_default()
+                .entry(entry.topic())
+                .or_default() += 1;
+
+            report.span = Some(match report.span {
+                Some((first, last)) => (first.min(entry.timestamp), last.max(entry.timestamp)),
+                None => (entry.timestamp, entry.timestamp),
+            });
+        }
+
+        report
+    }
+
+    /// Hexagrams with their reading counts, most frequent first (ties by number)
+    pub fn most_frequent(&self) -> Vec<(u8, usize)> {
+        let mut hexagrams: Vec<(u8, usize)> = self
+            .hexagrams
+            .iter()
+            .map(|(&number, &count)| (number, count))
+            .collect();
+        hexagrams.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        hexagrams
+    }
+
+    /// Numbers of changing lines that occurred, with their reading counts,
+    /// most common first
+    pub fn changing_line_counts(&self) -> Vec<(usize, usize)> {
+        let mut counts: Vec<(usize, usize)> = self
+            .changing_lines
+            .iter()
+            .copied()
+            .enumerate()
+            .filter(|&(_, count)| count > 0)
+            .collect();
+        counts.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        counts
+    }
+}
+
+/// Outcomes recorded with `journal resolve`, by the local month readings
+/// were cast, for `i-ching journal report --accuracy`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Accuracy {
+    pub months: BTreeMap<String, OutcomeCounts>,
+    pub total: OutcomeCounts,
+}
+
+/// Counts of recorded outcomes over some readings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct OutcomeCounts {
+    pub readings: usize,
+    pub resolved: usize,
+    /// Readings given each accuracy rating, 1 to 5
+    pub ratings: [usize; 5],
+    /// Resolved readings with a resolution date, and the days from cast to
+    /// resolution summed over them
+    pub dated: usize,
+    pub days: i64,
+}
+
+impl OutcomeCounts {
+    fn add(&mut self, entry: &JournalEntry) {
+        self.readings += 1;
+        let Some(outcome) = &entry.outcome else {
+            return;
+        };
+        if let Some(rating) = outcome.accuracy.filter(|rating| (1..=5).contains(rating)) {
+            self.ratings[usize::from(rating - 1)] += 1;
+        }
+        if outcome.resolved {
+            self.resolved += 1;
+            if let Some(date) = outcome.resolved_on {
+                let cast = entry.timestamp.with_timezone(&Local).date_naive();
+                self.dated += 1;
+                self.days += (date - cast).num_days();
+            }
+        }
+    }
+
+    /// Mean accuracy rating, if any reading was rated
+    pub fn mean_accuracy(&self) -> Option<f64> {
+        let rated: usize = self.ratings.iter().sum();
+        let total: usize = (1..)
+            .zip(self.ratings)
+            .map(|(rating, count)| rating * count)
+            .sum();
+        (rated > 0).then(|| total as f64 / rated as f64)
+    }
+
+    /// Mean days from cast to resolution, if any resolution was dated
+    pub fn mean_days(&self) -> Option<f64> {
+        (self.dated > 0).then(|| self.days as f64 / self.dated as f64)
+    }
+}
+
+impl Accuracy {
+    pub fn new(entries: &[JournalEntry]) -> Self {
+        let mut accuracy = Accuracy::default();
+        for entry in entries {
+            let month = entry
+                .timestamp
+                .with_timezone(&Local)
+                .format("%Y-%m")
+                .to_string();
+            accuracy.months.entry(month).or_default().add(entry);
+            accuracy.total.add(entry);
+        }
+        accuracy
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Reading;
+    use crate::journal::Outcome;
+    use crate::wizard::{Agency, StructuredQuestion};
+    use chrono::{NaiveDate, TimeZone};
+
+    fn entry(input: &str, month: u32, topic: Option<Topic>) -> JournalEntry {
+        let reading: Reading = input.parse().unwrap();
+        let mut entry = JournalEntry::new(reading).with_topic(topic);
+        entry.timestamp = Utc.with_ymd_and_hms(2026, month, 15, 12, 0, 0).unwrap();
+        entry
+    }
+
+    #[test]
+    fn test_report() {
+        let structured = StructuredQuestion {
+            topic: Some(Topic::Career),
+            subject: "the offer".to_string(),
+            timeframe: None,
+            agency: Agency::Act,
+        };
+        let entries = [
+            entry("11", 3, Some(Topic::Career)),
+            entry("32->34", 3, None),
+            entry("9,7,7,7,7,7", 4, None).with_structured_question(structured),
+            entry("11", 4, Some(Topic::Timing)),
+        ];
+        let report = Report::new(&entries);
+
+        assert_eq!(report.readings, 4);
+        assert_eq!(report.most_frequent(), [(11, 2), (1, 1), (32, 1)]);
+        assert_eq!(report.changing_line_counts(), [(0, 2), (1, 2)]);
+        assert_eq!(report.topics["2026-03"][&Some(Topic::Career)], 1);
+        assert_eq!(report.topics["2026-03"][&None], 1);
+        // The wizard's topic counts when none was given with --topic
+        assert_eq!(report.topics["2026-04"][&Some(Topic::Career)], 1);
+        let (first, last) = report.span.unwrap();
+        assert_eq!(
+            (
+                first.format("%m").to_string(),
+                last.format("%m").to_string()
+            ),
+            ("03".into(), "04".into())
+        );
+
+        assert_eq!(Report::new(&[]), Report::default());
+    }
+
+    #[test]
+    fn test_accuracy() {
+        let resolved = |mut entry: JournalEntry, accuracy: Option<u8>, day: u32| {
+            entry.outcome = Some(Outcome {
+                resolved: true,
+                accuracy,
+                resolved_on: NaiveDate::from_ymd_opt(2026, 4, day),
+                note: None,
+            });
+            entry
+        };
+        let entries = [
+            resolved(entry("11", 3, None), Some(4), 14),
+            entry("32->34", 3, None),
+            resolved(entry("1", 4, None), Some(2), 25),
+            resolved(entry("2", 4, None), None, 15),
+        ];
+        let accuracy = Accuracy::new(&entries);
+
+        assert_eq!(accuracy.total.readings, 4);
+        assert_eq!(accuracy.total.resolved, 3);
+        assert_eq!(accuracy.total.ratings, [0, 1, 0, 1, 0]);
+        assert_eq!(accuracy.total.mean_accuracy(), Some(3.0));
+        assert_eq!(accuracy.months["2026-03"].resolved, 1);
+        assert_eq!(accuracy.months["2026-03"].mean_days(), Some(30.0));
+        assert_eq!(accuracy.months["2026-04"].mean_days(), Some(5.0));
+        assert_eq!(Accuracy::new(&[]).total.mean_accuracy(), None);
+    }
+}
@@ -0,0 +1,393 @@
+//! Ranked, forgiving search over journal entries for `journal search`
+//!
+//! An entry's searchable text is its question, context, outcome note, and
+//! the numbers and names of its hexagrams. Words are lowercased and stemmed
+//! with a light English suffix stripper, so "moving" finds "move" and "jobs"
+//! finds "job". A query word of three letters or more also matches words it
+//! begins, and one of four or more matches words a typo away (two for eight
+//! or more), each at a lower weight than an exact match. Every query word
+//! has to match somewhere; the entries that qualify are ranked by BM25.
+
+use crate::core::data::IChingData;
+use crate::journal::JournalEntry;
+use std::ops::Range;
+
+/// BM25 term-frequency saturation
+const K1: f64 = 1.2;
+/// BM25 length normalization
+const B: f64 = 0.75;
+
+/// Weight of a word that starts with the query word
+const PREFIX_WEIGHT: f64 = 0.75;
+/// Weight of a word within the allowed edit distance
+const FUZZY_WEIGHT: f64 = 0.5;
+
+/// Words kept on either side of the first match in a snippet
+const SNIPPET_WORDS: usize = 8;
+
+/// An entry that matched, with its score and where it matched
+#[derive(Debug, Clone)]
+pub struct Hit {
+    pub entry: JournalEntry,
+    pub score: f64,
+    /// The question, context, or note around the first match, if the match
+    /// wasn't only in a hexagram name
+    pub snippet: Option<Snippet>,
+}
+
+/// An excerpt of an entry's text with its matching words marked
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Snippet {
+    pub text: String,
+    /// Byte ranges of the matching words in `text`
+    pub matches: Vec<Range<usize>>,
+}
+
+impl Snippet {
+    /// `text` with each match between `open` and `close`
+    pub fn highlight(&self, open: &str, close: &str) -> String {
+        let mut result = String::with_capacity(self.text.len() + 16);
+        let mut end = 0;
+        for range in &self.matches {
+            result.push_str(&self.text[end..range.start]);
+            result.push_str(open);
+            result.push_str(&self.text[range.clone()]);
+            result.push_str(close);
+            end = range.end;
+        }
+        result.push_str(&self.text[end..]);
+        result
+    }
+}
+
+/// A word of an entry's text: its stem and where it is in the field
+struct Token {
+    stem: String,
+    span: Range<usize>,
+}
+
+/// An entry's searchable text, field by field
+struct Document {
+    /// Question, context, and note: the fields a snippet is taken from
+    fields: Vec<String>,
+    tokens: Vec<Vec<Token>>,
+    /// Stems of the hexagram numbers and names
+    hexagrams: Vec<String>,
+}
+
+impl Document {
+    fn new(entry: &JournalEntry, data: &IChingData) -> Self {
+        let reading = &entry.reading;
+        let fields: Vec<String> = [
+            reading.question(),
+            reading.context(),
+            entry
+                .outcome
+                .as_ref()
+                .and_then(|outcome| outcome.note.as_deref()),
+        ]
+        .into_iter()
+        .flatten()
+        .map(str::to_string)
+        .collect();
+        let tokens = fields.iter().map(|field| tokenize(field)).collect();
+
+        let mut hexagrams = String::new();
+        for number in
+            std::iter::once(reading.primary_hexagram()).chain(reading.transformed_hexagram_number())
+        {
+            if let Some(hexagram) = data.get_hexagram(number) {
+                hexagrams.push_str(&format!(" {} {}", number, hexagram.name));
+            }
+        }
+        let hexagrams = tokenize(&hexagrams)
+            .into_iter()
+            .map(|token| token.stem)
+            .collect();
+
+        Document {
+            fields,
+            tokens,
+            hexagrams,
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.tokens.iter().map(Vec::len).sum::<usize>() + self.hexagrams.len()
+    }
+
+    /// Summed match weight of `term` over every word
+    fn frequency(&self, term: &str) -> f64 {
+        self.tokens
+            .iter()
+            .flatten()
+            .map(|token| token.stem.as_str())
+            .chain(self.hexagrams.iter().map(String::as_str))
+            .map(|stem| weight(term, stem))
+            .sum()
+    }
+
+    /// The first field with a match, cut to the words around it
+    fn snippet(&self, terms: &[String]) -> Option<Snippet> {
+        let matches = |token: &Token| terms.iter().any(|term| weight(term, &token.stem) > 0.0);
+        let (field, tokens) = self
+            .fields
+            .iter()
+            .zip(&self.tokens)
+            .find(|(_, tokens)| tokens.iter().any(matches))?;
+
+        let first = tokens.iter().position(matches)?;
+        let start = first.saturating_sub(SNIPPET_WORDS);
+        let end = (first + SNIPPET_WORDS + 1).min(tokens.len());
+        let from = if start == 0 {
+            0
+        } else {
+            tokens[start].span.start
+        };
+        let to = if end == tokens.len() {
+            field.len()
+        } else {
+            tokens[end - 1].span.end
+        };
+
+        let mut text = String::new();
+        if from > 0 {
+            text.push('…');
+        }
+        let offset = text.len() as isize - from as isize;
+        text.push_str(&field[from..to]);
+        if to < field.len() {
+            text.push('…');
+        }
+        let shift = |span: &Range<usize>| {
+            (span.start as isize + offset) as usize..(span.end as isize + offset) as usize
+        };
+        Some(Snippet {
+            text,
+            matches: tokens[start..end]
+                .iter()
+                .filter(|token| matches(token))
+                .map(|token| shift(&token.span))
+                .collect(),
+        })
+    }
+}
+
+/// Entries matching every word of `query`, best first (ties oldest first)
+pub fn search(entries: Vec<JournalEntry>, query: &str, data: &IChingData) -> Vec<Hit> {
+    let terms: Vec<String> = tokenize(query)
+        .into_iter()
+        .map(|token| token.stem)
+        .collect();
+    if terms.is_empty() {
+        return entries
+            .into_iter()
+            .map(|entry| Hit {
+                entry,
+                score: 0.0,
+                snippet: None,
+            })
+            .collect();
+    }
+
+    let documents: Vec<Document> = entries
+        .iter()
+        .map(|entry| Document::new(entry, data))
+        .collect();
+    let count = documents.len() as f64;
+    let average = documents.iter().map(Document::len).sum::<usize>() as f64 / count.max(1.0);
+    // Each term's frequency in each document
+    let frequencies: Vec<Vec<f64>> = terms
+        .iter()
+        .map(|term| documents.iter().map(|doc| doc.frequency(term)).collect())
+        .collect();
+
+    let mut hits: Vec<Hit> = entries
+        .into_iter()
+        .zip(&documents)
+        .enumerate()
+        .filter(|(i, _)| frequencies.iter().all(|frequency| frequency[*i] > 0.0))
+        .map(|(i, (entry, document))| {
+            let length = document.len() as f64 / average.max(1.0);
+            let score = frequencies
+                .iter()
+                .map(|frequency| {
+                    let containing = frequency.iter().filter(|&&f| f > 0.0).count() as f64;
+                    let idf = ((count - containing + 0.5) / (containing + 0.5) + 1.0).ln();
+                    let tf = frequency[i];
+                    idf * tf * (K1 + 1.0) / (tf + K1 * (1.0 - B + B * length))
+                })
+                .sum();
+            Hit {
+                entry,
+                score,
+                snippet: document.snippet(&terms),
+            }
+        })
+        .collect();
+    hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+    hits
+}
+
+/// How well a document word matches a query word: 1 when their stems are
+/// equal, less for a prefix or a near miss, 0 otherwise
+fn weight(term: &str, stem: &str) -> f64 {
+    if term == stem {
+        return 1.0;
+    }
+    let length = term.chars().count();
+    if length >= 3 && stem.starts_with(term) {
+        return PREFIX_WEIGHT;
+    }
+    let allowed = match length {
+        0..4 => return 0.0,
+        4..8 => 1,
+        _ => 2,
+    };
+    if edit_distance(term, stem) <= allowed {
+        FUZZY_WEIGHT
+    } else {
+        0.0
+    }
+}
+
+/// Edit distance between two words, counting a swap of neighbouring
+/// letters as one edit (optimal string alignment)
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1)
+                .min(d[i][j - 1] + 1)
+                .min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// The words of `text` (runs of letters and digits), stemmed
+fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut start = None;
+    for (i, c) in text
+        .char_indices()
+        .chain(std::iter::once((text.len(), ' ')))
+    {
+        match (c.is_alphanumeric(), start) {
+            (true, None) => start = Some(i),
+            (false, Some(from)) => {
+                tokens.push(Token {
+                    stem: stem(&text[from..i].to_lowercase()),
+                    span: from..i,
+                });
+                start = None;
+            }
+            _ => {}
+        }
+    }
+    tokens
+}
+
+/// `word` without its common English inflections: plurals, -ing, -ed, -ly,
+/// and a final e, so "move", "moves", "moved", and "moving" share a stem
+fn stem(word: &str) -> String {
+    let mut stem = word.to_string();
+    let strip = |stem: &mut String, suffix: &str, replacement: &str| {
+        if stem.len() >= suffix.len() + 3 && stem.ends_with(suffix) {
+            stem.truncate(stem.len() - suffix.len());
+            stem.push_str(replacement);
+            true
+        } else {
+            false
+        }
+    };
+
+    let verb = strip(&mut stem, "ing", "") || strip(&mut stem, "ed", "");
+    if !verb
+        && !strip(&mut stem, "ies", "y")
+        && !strip(&mut stem, "sses", "ss")
+        && !strip(&mut stem, "ly", "")
+        && !stem.ends_with("ss")
+    {
+        strip(&mut stem, "s", "");
+    }
+    // "stopping" and "stop"
+    if verb {
+        let bytes = stem.as_bytes();
+        let n = bytes.len();
+        if n >= 2 && bytes[n - 1] == bytes[n - 2] && !b"aeioulsz".contains(&bytes[n - 1]) {
+            stem.pop();
+        }
+    }
+    strip(&mut stem, "e", "");
+    stem
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Reading;
+
+    #[test]
+    fn test_search() {
+        assert_eq!(stem("moving"), stem("move"));
+        assert_eq!(stem("moved"), stem("moves"));
+        assert_eq!(stem("stopping"), "stop");
+        assert_eq!(stem("studies"), "study");
+        assert_eq!(stem("business"), "business");
+        assert_eq!(edit_distance("carrer", "career"), 1);
+        assert_eq!(edit_distance("youth", "truth"), 2);
+        assert_eq!(edit_distance("hosue", "house"), 1);
+        assert_eq!(edit_distance("job", "jobs"), 1);
+
+        let data = IChingData::load().unwrap();
+        let entry = |input: &str, question: &str| {
+            let reading: Reading = input.parse().unwrap();
+            JournalEntry::new(reading.with_question(Some(question.to_string())))
+        };
+        let entries = vec![
+            entry("3", "Should I be moving house this year?"),
+            entry("11", "Should I take the job offer?"),
+            entry(
+                "5",
+                "Will the job move me to a new house, and is moving the job wise?",
+            ),
+        ];
+        let questions = |query: &str| -> Vec<String> {
+            search(entries.clone(), query, &data)
+                .into_iter()
+                .map(|hit| hit.entry.reading.question().unwrap().to_string())
+                .collect()
+        };
+
+        // Stems match, and the entry saying it most (for its length) ranks first
+        assert_eq!(questions("move").len(), 2);
+        assert!(questions("job")[0].starts_with("Will the job"));
+        // A typo still finds the entry; every word must match
+        assert_eq!(questions("hosue moving").len(), 2);
+        assert_eq!(questions("job house").len(), 1);
+        assert_eq!(questions("advance").len(), 1); // 11 is Advance
+        assert!(questions("jo").is_empty());
+        assert_eq!(questions("").len(), 3);
+
+        let hits = search(entries.clone(), "offer", &data);
+        let snippet = hits[0].snippet.as_ref().unwrap();
+        assert_eq!(
+            snippet.highlight("[", "]"),
+            "Should I take the job [offer]?"
+        );
+        let hits = search(vec![entry("5", &"word ".repeat(30))], "word", &data);
+        assert!(hits[0].snippet.as_ref().unwrap().text.ends_with("word…"));
+        assert!(search(entries, "advance", &data)[0].snippet.is_none());
+    }
+}
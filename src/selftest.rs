@@ -0,0 +1,183 @@
+use crate::core::Diviner;
+use crate::core::divination::Coin;
+use std::fmt;
+
+/// Two-sided critical z-score at a 0.001 significance level
+const Z_CRITICAL: f64 = 3.291;
+
+/// Chi-square critical value for 3 degrees of freedom at a 0.001 significance level
+const CHI_SQUARE_CRITICAL: f64 = 16.266;
+
+/// Traditional three-coin probabilities of lines 6, 7, 8, and 9
+const LINE_PROBABILITIES: [f64; 4] = [1.0 / 8.0, 3.0 / 8.0, 3.0 / 8.0, 1.0 / 8.0];
+
+/// Outcome of one statistical check
+#[derive(Debug, Clone, PartialEq)]
+pub struct TestResult {
+    pub name: &'static str,
+    pub statistic: f64,
+    pub critical: f64,
+    pub detail: String,
+}
+
+impl TestResult {
+    pub fn passed(&self) -> bool {
+        self.statistic.abs() <= self.critical
+    }
+}
+
+/// e.g. "PASS  coin frequency       0.31 (limit 3.29)  heads 50.0%"
+impl fmt::Display for TestResult {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}  {:<16} {:>8.2} (limit {:.2})  {}",
+            if self.passed() { "PASS" } else { "FAIL" },
+            self.name,
+            self.statistic,
+            self.critical,
+            self.detail
+        )
+    }
+}
+
+/// Run every check against the three-coin method over `lines` cast lines
+pub fn run_coins(lines: usize) -> Vec<TestResult> {
+    let mut diviner = Diviner::new();
+    run(lines, || diviner.toss_coins())
+}
+
+/// Run every check against `lines` tosses drawn from `toss`
+pub fn run(lines: usize, mut toss: impl FnMut() -> [Coin; 3]) -> Vec<TestResult> {
+    let tosses: Vec<[Coin; 3]> = (0..lines).map(|_| toss()).collect();
+    let coins: Vec<Coin> = tosses.iter().flatten().copied().collect();
+
+    vec![
+        coin_frequency(&coins),
+        coin_runs(&coins),
+        line_ratios(&tosses),
+    ]
+}
+
+/// Monobit test: heads and tails should come up equally often
+fn coin_frequency(coins: &[Coin]) -> TestResult {
+    let n = coins.len() as f64;
+    let heads = coins.iter().filter(|&&coin| coin == Coin::Heads).count() as f64;
+    let z = (heads - n / 2.0) / (n / 4.0).sqrt();
+
+    TestResult {
+        name: "coin frequency",
+        statistic: z,
+        critical: Z_CRITICAL,
+        detail: format!("heads {:.1}%", heads / n * 100.0),
+    }
+}
+
+/// Wald-Wolfowitz runs test: tosses should not clump or alternate
+fn coin_runs(coins: &[Coin]) -> TestResult {
+    let heads = coins.iter().filter(|&&coin| coin == Coin::Heads).count() as f64;
+    let tails = coins.len() as f64 - heads;
+    let n = heads + tails;
+    let runs = 1 + coins.windows(2).filter(|pair| pair[0] != pair[1]).count();
+
+    let expected = 2.0 * heads * tails / n + 1.0;
+    let variance = (expected - 1.0) * (expected - 2.0) / (n - 1.0);
+    let z = if variance > 0.0 {
+        (runs as f64 - expected) / variance.sqrt()
+    } else {
+        // All one side: as far from random as it gets
+        f64::INFINITY
+    };
+
+    TestResult {
+        name: "coin runs",
+        statistic: z,
+        critical: Z_CRITICAL,
+        detail: format!("{} runs, {:.0} expected", runs, expected),
+    }
+}
+
+/// Chi-square goodness of fit of lines 6/7/8/9 to 1:3:3:1
+fn line_ratios(tosses: &[[Coin; 3]]) -> TestResult {
+    let mut counts = [0usize; 4];
+    for &coins in tosses {
+        let total: u8 = coins.iter().map(Coin::value).sum();
+        counts[usize::from(total - 6)] += 1;
+    }
+
+    let n = tosses.len() as f64;
+    let chi_square = counts
+        .iter()
+        .zip(LINE_PROBABILITIES)
+        .map(|(&observed, p)| {
+            let expected = n * p;
+            (observed as f64 - expected).powi(2) / expected
+        })
+        .sum();
+
+    let shares: Vec<String> = counts
+        .iter()
+        .zip(6..)
+        .map(|(&count, line)| format!("{}: {:.1}%", line, count as f64 / n * 100.0))
+        .collect();
+
+    TestResult {
+        name: "line ratios",
+        statistic: chi_square,
+        critical: CHI_SQUARE_CRITICAL,
+        detail: shares.join(", "),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+
+    fn toss_with(rng: &mut StdRng, p_heads: f64) -> [Coin; 3] {
+        std::array::from_fn(|_| {
+            if rng.random_bool(p_heads) {
+                Coin::Heads
+            } else {
+                Coin::Tails
+            }
+        })
+    }
+
+    #[test]
+    fn test_fair_coins_pass() {
+        let mut rng = StdRng::seed_from_u64(64);
+        let results = run(20_000, || toss_with(&mut rng, 0.5));
+
+        assert_eq!(results.len(), 3);
+        assert!(results.iter().all(TestResult::passed), "{:?}", results);
+    }
+
+    #[test]
+    fn test_biased_coins_fail() {
+        let mut rng = StdRng::seed_from_u64(64);
+        let results = run(20_000, || toss_with(&mut rng, 0.55));
+
+        assert!(!results[0].passed());
+        assert!(!results[2].passed());
+    }
+
+    #[test]
+    fn test_alternating_coins_fail_runs() {
+        use Coin::{Heads, Tails};
+
+        let mut toggle = false;
+        let results = run(1_000, || {
+            toggle = !toggle;
+            if toggle {
+                [Heads, Tails, Heads]
+            } else {
+                [Tails, Heads, Tails]
+            }
+        });
+
+        assert!(results[0].passed());
+        assert!(!results[1].passed());
+    }
+}
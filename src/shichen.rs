@@ -0,0 +1,195 @@
+//! The hour hexagram: a reading for the current double hour (shichen)
+//!
+//! The day is twelve double hours, each named by an Earthly Branch, from the
+//! hour of the Rat (子, 23:00-00:59) on. Each branch has its sovereign
+//! hexagram (bigua), the twelve that wax from Return (復, one yang line) to
+//! the Creative and wane through Coming to Meet (姤) to the Receptive, as the
+//! Cantong qi lays them over the hours. That hexagram is the hour's reading.
+//! The moving line counts the day's branch and the hour's together, 1 for 子
+//! through 12 for 亥, round the six lines, as Plum Blossom numerology counts
+//! its moving line, so the same hour gives a different line from day to day.
+
+use crate::core::Reading;
+use crate::interpretation::liuyao::Stem;
+use anyhow::Result;
+use chrono::{Duration, NaiveDate, NaiveDateTime, Timelike};
+
+/// One of the twelve Earthly Branches (dizhi) that name the double hours
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Branch {
+    Zi,
+    Chou,
+    Yin,
+    Mao,
+    Chen,
+    Si,
+    Wu,
+    Wei,
+    Shen,
+    You,
+    Xu,
+    Hai,
+}
+
+const BRANCHES: [Branch; 12] = [
+    Branch::Zi,
+    Branch::Chou,
+    Branch::Yin,
+    Branch::Mao,
+    Branch::Chen,
+    Branch::Si,
+    Branch::Wu,
+    Branch::Wei,
+    Branch::Shen,
+    Branch::You,
+    Branch::Xu,
+    Branch::Hai,
+];
+
+/// Sovereign hexagrams in branch order: Return, Approach, Peace, Great
+/// Power, Breakthrough, the Creative, Coming to Meet, Retreat, Standstill,
+/// Contemplation, Splitting Apart, the Receptive
+const SOVEREIGN: [u8; 12] = [24, 19, 11, 34, 43, 1, 44, 33, 12, 20, 23, 2];
+
+impl Branch {
+    /// The branch of the double hour containing `hour` (0-23)
+    pub fn of_hour(hour: u32) -> Self {
+        BRANCHES[(hour.div_ceil(2) % 12) as usize]
+    }
+
+    /// The branch of a calendar day in the unbroken sexagenary day count
+    pub fn of_day(date: NaiveDate) -> Self {
+        // 1 January 2000 was a wuwu (戊午) day
+        let reference = NaiveDate::from_ymd_opt(2000, 1, 1).unwrap();
+        let days = (date - reference).num_days();
+        BRANCHES[(days + 6).rem_euclid(12) as usize]
+    }
+
+    /// 1 for 子 through 12 for 亥
+    pub fn number(self) -> u8 {
+        self as u8 + 1
+    }
+
+    /// Pinyin without tone marks
+    pub fn name(self) -> &'static str {
+        [
+            "Zi", "Chou", "Yin", "Mao", "Chen", "Si", "Wu", "Wei", "Shen", "You", "Xu", "Hai",
+        ][self as usize]
+    }
+
+    pub fn chinese(self) -> &'static str {
+        [
+            "子", "丑", "寅", "卯", "辰", "巳", "午", "未", "申", "酉", "戌", "亥",
+        ][self as usize]
+    }
+
+    /// The zodiac animal the hour is named for in English
+    pub fn animal(self) -> &'static str {
+        [
+            "Rat", "Ox", "Tiger", "Rabbit", "Dragon", "Snake", "Horse", "Goat", "Monkey",
+            "Rooster", "Dog", "Pig",
+        ][self as usize]
+    }
+
+    /// Clock hours the double hour spans, e.g. "23:00-00:59"
+    pub fn hours(self) -> String {
+        let start = (self as u32 * 2 + 23) % 24;
+        format!("{:02}:00-{:02}:59", start, (start + 1) % 24)
+    }
+
+    /// The branch's sovereign hexagram
+    pub fn sovereign_hexagram(self) -> u8 {
+        SOVEREIGN[self as usize]
+    }
+}
+
+/// The hour hexagram for a moment, with the calendar it came from
+#[derive(Debug, Clone)]
+pub struct HourGua {
+    pub hour: Branch,
+    /// The day the hour belongs to, which turns over at 23:00
+    pub day: NaiveDate,
+    pub day_stem: Stem,
+    pub day_branch: Branch,
+    pub moving_line: u8,
+    pub reading: Reading,
+}
+
+impl HourGua {
+    pub fn at(time: NaiveDateTime) -> Result<Self> {
+        let hour = Branch::of_hour(time.hour());
+        // The hour of the Rat opens the next day
+        let day = if time.hour() == 23 {
+            time.date() + Duration::days(1)
+        } else {
+            time.date()
+        };
+        let day_branch = Branch::of_day(day);
+        let moving_line = (day_branch.number() + hour.number() - 1) % 6 + 1;
+        let reading =
+            Reading::with_changing_lines(hour.sovereign_hexagram(), 1 << (moving_line - 1))?;
+
+        Ok(Self {
+            hour,
+            day,
+            day_stem: Stem::of_day(day),
+            day_branch,
+            moving_line,
+            reading,
+        })
+    }
+
+    /// e.g. "Hour of the Horse (午時, 11:00-12:59) on a 甲子 (jiazi) day, moving line 1"
+    pub fn describe(&self) -> String {
+        format!(
+            "Hour of the {} ({}時, {}) on a {}{} ({}{}) day, moving line {}",
+            self.hour.animal(),
+            self.hour.chinese(),
+            self.hour.hours(),
+            self.day_stem.chinese(),
+            self.day_branch.chinese(),
+            self.day_stem.name().to_lowercase(),
+            self.day_branch.name().to_lowercase(),
+            self.moving_line
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn at(text: &str) -> HourGua {
+        HourGua::at(NaiveDateTime::parse_from_str(text, "%Y-%m-%d %H:%M").unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_hour_gua() {
+        assert_eq!(Branch::of_hour(0), Branch::Zi);
+        assert_eq!(Branch::of_hour(23), Branch::Zi);
+        assert_eq!(Branch::of_hour(12), Branch::Wu);
+        assert_eq!(Branch::Zi.hours(), "23:00-00:59");
+        assert_eq!(Branch::Wu.hours(), "11:00-12:59");
+        assert_eq!(
+            Branch::of_day(NaiveDate::from_ymd_opt(2000, 1, 1).unwrap()),
+            Branch::Wu
+        );
+
+        // A wuwu day, the hour of the Horse: 7 + 7 = 14 counts to line 2
+        let noon = at("2000-01-01 12:00");
+        assert_eq!(noon.reading.primary_hexagram(), 44);
+        assert_eq!(noon.moving_line, 2);
+        assert_eq!(noon.day_stem, Stem::Wu);
+        assert_eq!(
+            noon.describe(),
+            "Hour of the Horse (午時, 11:00-12:59) on a 戊午 (wuwu) day, moving line 2"
+        );
+
+        // 23:00 is the first hour of the next day, a jiwei (己未) day
+        let late = at("2000-01-01 23:30");
+        assert_eq!(late.hour, Branch::Zi);
+        assert_eq!(late.day_branch, Branch::Wei);
+        assert_eq!(late.reading.primary_hexagram(), 24);
+        assert_eq!(late.reading.changing_line_positions(), [3]);
+    }
+}
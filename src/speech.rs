@@ -0,0 +1,205 @@
+use crate::core::data::{Hexagram, Pronunciation};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::env;
+use std::path::Path;
+use std::process::Command;
+
+/// Speech synthesizer used to say a hexagram's name
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum Engine {
+    /// eSpeak NG with its Mandarin voice, reading tone-numbered pinyin
+    EspeakNg,
+    /// Classic eSpeak with its Mandarin voice
+    Espeak,
+    /// macOS `say` with the Tingting voice, reading the characters
+    Say,
+}
+
+impl Engine {
+    const ALL: [Engine; 3] = [Engine::EspeakNg, Engine::Espeak, Engine::Say];
+
+    fn program(self) -> &'static str {
+        match self {
+            Engine::EspeakNg => "espeak-ng",
+            Engine::Espeak => "espeak",
+            Engine::Say => "say",
+        }
+    }
+
+    /// The first engine found on `PATH`
+    pub fn detect() -> Option<Engine> {
+        let path = env::var_os("PATH")?;
+        let dirs: Vec<_> = env::split_paths(&path).collect();
+        Engine::ALL.into_iter().find(|engine| {
+            dirs.iter()
+                .any(|dir| is_program(&dir.join(engine.program())))
+        })
+    }
+
+    /// The command that speaks `hexagram`'s name
+    pub fn command(self, hexagram: &Hexagram) -> Command {
+        let mut command = Command::new(self.program());
+        match self {
+            Engine::EspeakNg | Engine::Espeak => {
+                let voice = if self == Engine::EspeakNg {
+                    "cmn"
+                } else {
+                    "zh"
+                };
+                command
+                    .args(["-v", voice])
+                    .arg(tone_numbers(&pronunciation(hexagram).pinyin));
+            }
+            Engine::Say => {
+                command.args(["-v", "Tingting"]).arg(&hexagram.chinese);
+            }
+        }
+        command
+    }
+
+    /// Say `hexagram`'s name and wait for the engine to finish
+    pub fn speak(self, hexagram: &Hexagram) -> Result<()> {
+        let status = self
+            .command(hexagram)
+            .status()
+            .with_context(|| format!("Failed to run {}", self.program()))?;
+        if !status.success() {
+            return Err(anyhow::anyhow!("{} exited with {}", self.program(), status));
+        }
+        Ok(())
+    }
+}
+
+fn is_program(path: &Path) -> bool {
+    path.is_file() || path.with_extension("exe").is_file()
+}
+
+/// The hexagram's pronunciation, or its pinyin with no IPA if the data has none
+pub fn pronunciation(hexagram: &Hexagram) -> Pronunciation {
+    hexagram
+        .pronunciation
+        .clone()
+        .unwrap_or_else(|| Pronunciation {
+            pinyin: hexagram.pinyin.clone(),
+            ipa: String::new(),
+        })
+}
+
+/// Tone-marked pinyin rewritten with tone numbers (`xiǎo chù` → `xiao3 chu4`),
+/// ü as `v`, as eSpeak's Mandarin voice reads it
+pub fn tone_numbers(pinyin: &str) -> String {
+    pinyin
+        .split_whitespace()
+        .map(|syllable| {
+            let mut plain = String::new();
+            let mut tone = None;
+            for c in syllable.chars() {
+                let (base, mark) = untone(c);
+                plain.push(base);
+                tone = tone.or(mark);
+            }
+            match tone {
+                Some(tone) => format!("{}{}", plain, tone),
+                None => plain,
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// A pinyin letter without its tone mark, and the tone it marked
+fn untone(c: char) -> (char, Option<u8>) {
+    const TONED: [(char, [char; 4]); 6] = [
+        ('a', ['ā', 'á', 'ǎ', 'à']),
+        ('e', ['ē', 'é', 'ě', 'è']),
+        ('i', ['ī', 'í', 'ǐ', 'ì']),
+        ('o', ['ō', 'ó', 'ǒ', 'ò']),
+        ('u', ['ū', 'ú', 'ǔ', 'ù']),
+        ('v', ['ǖ', 'ǘ', 'ǚ', 'ǜ']),
+    ];
+    if c == 'ü' {
+        return ('v', None);
+    }
+    TONED
+        .iter()
+        .find_map(|(base, marks)| {
+            let tone = marks.iter().position(|&mark| mark == c)?;
+            Some((*base, Some(tone as u8 + 1)))
+        })
+        .unwrap_or((c, None))
+}
+
+/// SSML that says the hexagram's number and English name, with the Chinese
+/// name given by its IPA
+pub fn ssml(hexagram: &Hexagram) -> String {
+    let pronunciation = pronunciation(hexagram);
+    let name = if pronunciation.ipa.is_empty() {
+        escape(&pronunciation.pinyin)
+    } else {
+        format!(
+            "<phoneme alphabet=\"ipa\" ph=\"{}\">{}</phoneme>",
+            escape(&pronunciation.ipa),
+            escape(&pronunciation.pinyin)
+        )
+    };
+    format!(
+        "<speak version=\"1.0\" xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"en-US\">\n  Hexagram {}: {}, {}.\n</speak>",
+        hexagram.number,
+        name,
+        escape(&hexagram.name)
+    )
+}
+
+fn escape(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::data::IChingData;
+
+    #[test]
+    fn test_tone_numbers() {
+        assert_eq!(tone_numbers("xiǎo chù"), "xiao3 chu4");
+        assert_eq!(tone_numbers("lǚ"), "lv3");
+        assert_eq!(tone_numbers("qián"), "qian2");
+        assert_eq!(tone_numbers("ma"), "ma");
+    }
+
+    #[test]
+    fn test_ssml() {
+        let data = IChingData::load().unwrap();
+        let ssml = ssml(data.get_hexagram(9).unwrap());
+        assert!(ssml.starts_with("<speak "));
+        assert!(ssml.contains(
+            "Hexagram 9: <phoneme alphabet=\"ipa\" ph=\"ɕjàʊ ʈʂʰû\">xiǎo chù</phoneme>, "
+        ));
+        assert!(ssml.ends_with(".\n</speak>"));
+    }
+
+    #[test]
+    fn test_every_hexagram_has_a_pronunciation() {
+        let data = IChingData::load().unwrap();
+        for hexagram in data.hexagrams() {
+            let pronunciation = hexagram.pronunciation.as_ref().unwrap();
+            assert_eq!(pronunciation.pinyin.replace(' ', ""), hexagram.pinyin);
+            assert_eq!(
+                pronunciation.pinyin.split(' ').count(),
+                pronunciation.ipa.split(' ').count()
+            );
+        }
+    }
+
+    #[test]
+    fn test_espeak_command() {
+        let data = IChingData::load().unwrap();
+        let command = Engine::EspeakNg.command(data.get_hexagram(10).unwrap());
+        let args: Vec<_> = command.get_args().collect();
+        assert_eq!(args, ["-v", "cmn", "lv3"]);
+    }
+}
@@ -0,0 +1,116 @@
+//! How many changing lines each casting method gives, for `i-ching stats`
+//!
+//! Every method changes a line a quarter of the time, so where lines are
+//! cast independently the number of changing lines in a reading follows the
+//! same binomial distribution; marbles, drawn without being put back, bunch
+//! it closer to the middle. The methods also differ in which lines change:
+//! yarrow stalks give an old yang three times as often as an old yin, where
+//! coins give them evenly. See [`crate::casting`].
+
+pub use crate::casting::{Method, cast_yarrow, yarrow_line};
+use crate::core::Reading;
+
+/// Counts of line values and changing lines over some readings
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub readings: usize,
+    /// Lines of 6, 7, 8, and 9
+    pub lines: [usize; 4],
+    /// Readings with 0 to 6 changing lines
+    pub changing: [usize; 7],
+}
+
+impl Tally {
+    pub fn new<'a>(readings: impl IntoIterator<Item = &'a Reading>) -> Self {
+        let mut tally = Self::default();
+        for reading in readings {
+            tally.readings += 1;
+            for number in reading.traditional_numbers() {
+                tally.lines[usize::from(number - 6)] += 1;
+            }
+            tally.changing[reading.changing_line_positions().len()] += 1;
+        }
+        tally
+    }
+}
+
+/// Theoretical and observed percentages side by side, for line values and
+/// for the number of changing lines; `observed` are named columns
+pub fn table(method: Method, observed: &[(&str, &Tally)]) -> String {
+    let mut text = format!("{}\n\n", method.name());
+
+    let mut header = format!("{:<10}{:>12}", "Line", "Theoretical");
+    for (name, _) in observed {
+        header.push_str(&format!("{:>12}", name));
+    }
+    text.push_str(&format!("{}\n", header));
+    for (i, p) in method.line_probabilities().iter().enumerate() {
+        text.push_str(&format!("{:<10}{:>11.1}%", i + 6, p * 100.0));
+        for (_, tally) in observed {
+            let total = tally.readings * 6;
+            text.push_str(&percentage(tally.lines[i], total));
+        }
+        text.push('\n');
+    }
+
+    let mut header = format!("\n{:<10}{:>12}", "Changing", "Theoretical");
+    for (name, _) in observed {
+        header.push_str(&format!("{:>12}", name));
+    }
+    text.push_str(&format!("{}\n", header));
+    for (k, p) in method.changing_probabilities().iter().enumerate() {
+        text.push_str(&format!("{:<10}{:>11.1}%", k, p * 100.0));
+        for (_, tally) in observed {
+            text.push_str(&percentage(tally.changing[k], tally.readings));
+        }
+        text.push('\n');
+    }
+    text
+}
+
+/// A right-aligned percentage column, or a dash when there's nothing to count
+fn percentage(count: usize, total: usize) -> String {
+    if total == 0 {
+        format!("{:>12}", "-")
+    } else {
+        format!("{:>11.1}%", count as f64 * 100.0 / total as f64)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_probabilities() {
+        for method in Method::ALL {
+            let total: f64 = method.changing_probabilities().iter().sum();
+            assert!((total - 1.0).abs() < 1e-12);
+            // No changing lines: (3/4)^6, where lines are independent
+            if method != Method::Marbles {
+                assert!((method.changing_probabilities()[0] - 0.177_978_515_625).abs() < 1e-12);
+            }
+        }
+
+        let tally = Tally::new(&Method::Yarrow.simulate(4000));
+        assert_eq!(tally.readings, 4000);
+        assert_eq!(tally.changing.iter().sum::<usize>(), 4000);
+        let share = |i: usize| tally.lines[i] as f64 / 24000.0;
+        // Old yang comes up about three times as often as old yin
+        assert!((share(0) - 1.0 / 16.0).abs() < 0.01, "{:?}", tally);
+        assert!((2.5..4.5).contains(&(share(3) / share(0))), "{:?}", tally);
+
+        let reading: Reading = "9,6,8,7,7,7".parse().unwrap();
+        let tally = Tally::new([&reading]);
+        assert_eq!(tally.lines, [1, 3, 1, 1]);
+        assert_eq!(tally.changing[2], 1);
+
+        let text = table(Method::Coins, &[("Simulated", &tally)]);
+        assert!(
+            text.contains(
+                "Line       Theoretical   Simulated\n6                12.5%       16.7%\n"
+            )
+        );
+        assert!(text.contains("2                29.7%      100.0%\n"));
+    }
+}
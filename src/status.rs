@@ -0,0 +1,137 @@
+use crate::core::Diviner;
+use crate::core::Reading;
+use crate::core::data::hexagram_number_to_unicode;
+use crate::glyphs::Glyphs;
+use anyhow::Result;
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the status cache location
+pub const STATUS_CACHE_ENV: &str = "I_CHING_STATUS_CACHE";
+
+/// How long a status-bar reading stands before a new one is cast
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Refresh {
+    Daily,
+    Hourly,
+}
+
+impl Refresh {
+    /// Key for the period containing `now`, e.g. "2026-03-01" or "2026-03-01T14"
+    fn period(&self, now: DateTime<Local>) -> String {
+        match self {
+            Refresh::Daily => now.format("%Y-%m-%d").to_string(),
+            Refresh::Hourly => now.format("%Y-%m-%dT%H").to_string(),
+        }
+    }
+}
+
+/// `$I_CHING_STATUS_CACHE`, or `status.json` in the user cache directory
+pub fn default_cache_path() -> PathBuf {
+    if let Some(path) = env::var_os(STATUS_CACHE_ENV) {
+        return PathBuf::from(path);
+    }
+
+    dirs::cache_dir()
+        .unwrap_or_else(env::temp_dir)
+        .join("i-ching")
+        .join("status.json")
+}
+
+#[derive(Serialize, Deserialize)]
+struct Cached {
+    refresh: Refresh,
+    period: String,
+    lines: [u8; 6],
+}
+
+/// The reading for the period containing `now`: the cached one if it was cast
+/// in the same period, otherwise a fresh cast that replaces it
+pub fn reading(path: &Path, refresh: Refresh, now: DateTime<Local>) -> Result<Reading> {
+    let period = refresh.period(now);
+
+    let cached = fs::read_to_string(path)
+        .ok()
+        .and_then(|json| serde_json::from_str::<Cached>(&json).ok())
+        .filter(|cached| cached.refresh == refresh && cached.period == period);
+    if let Some(cached) = cached {
+        if let Ok(reading) = Diviner::new().cast_reading_from_numbers(cached.lines, None) {
+            return Ok(reading);
+        }
+    }
+
+    let reading = Diviner::new().cast_reading(None);
+    let cached = Cached {
+        refresh,
+        period,
+        lines: reading.traditional_numbers(),
+    };
+    // A status bar should still show something when the cache can't be
+    // written; the next invocation just casts again
+    let _ = write_cache(path, &cached);
+    Ok(reading)
+}
+
+/// Replace the cache in one rename so concurrent readers never see half a file
+fn write_cache(path: &Path, cached: &Cached) -> Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let temp = path.with_extension(format!("json.{}", std::process::id()));
+    fs::write(&temp, serde_json::to_string(cached)?)?;
+    fs::rename(&temp, path)?;
+    Ok(())
+}
+
+/// A compact segment for tmux, starship, or polybar, e.g. "䷊ 11" or "䷊ 11→19"
+pub fn segment(reading: &Reading, glyphs: Glyphs) -> String {
+    let primary = reading.primary_hexagram();
+    let glyph = hexagram_number_to_unicode(primary)
+        .map(|c| glyphs.hexagram_prefix(&c.to_string()))
+        .unwrap_or_default();
+
+    match reading.transformed_hexagram_number() {
+        Some(transformed) => format!("{}{}{}{}", glyph, primary, glyphs.arrow(), transformed),
+        None => format!("{}{}", glyph, primary),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_reading_is_cached_for_the_period() {
+        let path = env::temp_dir().join(format!("i-ching-status-{}.json", std::process::id()));
+        let at = |hour| Local.with_ymd_and_hms(2026, 3, 1, hour, 30, 0).unwrap();
+
+        let first = reading(&path, Refresh::Hourly, at(9)).unwrap();
+        let again = reading(&path, Refresh::Hourly, at(9)).unwrap();
+        assert_eq!(first.traditional_numbers(), again.traditional_numbers());
+
+        // A new hour, or switching to daily, casts afresh and replaces the cache
+        reading(&path, Refresh::Hourly, at(10)).unwrap();
+        let cached: Cached = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(cached.period, "2026-03-01T10");
+        reading(&path, Refresh::Daily, at(10)).unwrap();
+        let cached: Cached = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(cached.refresh, Refresh::Daily);
+        assert_eq!(cached.period, "2026-03-01");
+
+        fs::remove_file(path).unwrap();
+    }
+
+    #[test]
+    fn test_segment() {
+        let reading: Reading = "11".parse().unwrap();
+        assert_eq!(segment(&reading, Glyphs::Unicode), "䷊ 11");
+        let reading: Reading = "1→2".parse().unwrap();
+        assert_eq!(segment(&reading, Glyphs::Ascii), "1->2");
+    }
+}
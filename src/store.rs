@@ -0,0 +1,502 @@
+use crate::core::Trigram;
+use crate::core::data::{
+    Hexagram, HexagramImage, HexagramJudgment, IChingData, LineInterpretation, Pronunciation,
+};
+use crate::dump::{self, Corpus};
+use crate::journal::{self, JournalEntry, Migration, Outcome};
+use anyhow::{Context, Result};
+use rusqlite::{Connection, OptionalExtension, params};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// Environment variable that overrides the database location
+pub const DB_PATH_ENV: &str = "I_CHING_DB";
+
+/// How long a write waits for another process's to finish, as for the
+/// JSON lines journal, before failing with "database is locked"
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Journal tables
+const JOURNAL_SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS journal (
+    id INTEGER PRIMARY KEY,
+    timestamp TEXT NOT NULL,
+    hexagram INTEGER NOT NULL,
+    transformed INTEGER,
+    entry TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS journal_timestamp ON journal (timestamp);
+CREATE INDEX IF NOT EXISTS journal_hexagram ON journal (hexagram);
+";
+
+/// FTS5 index over each entry's question, context, outcome note, and
+/// hexagram numbers and names, keyed by the entry's rowid, with words
+/// stemmed as `journal search` stems them
+const SEARCH_SCHEMA: &str = "
+CREATE VIRTUAL TABLE journal_search USING fts5 (
+    question, context, note, hexagrams, tokenize = 'porter unicode61'
+);
+";
+
+/// Columns of the current `journal_search`; a database indexed by an older
+/// version is reindexed when opened
+const SEARCH_COLUMNS: [&str; 4] = ["question", "context", "note", "hexagrams"];
+
+/// `$I_CHING_DB`, or `i-ching.db` in the user data directory
+pub fn default_path() -> Result<PathBuf> {
+    if let Some(path) = env::var_os(DB_PATH_ENV) {
+        return Ok(PathBuf::from(path));
+    }
+
+    let data_dir = dirs::data_dir()
+        .ok_or_else(|| anyhow::anyhow!("Could not determine the user data directory"))?;
+    Ok(data_dir.join("i-ching").join("i-ching.db"))
+}
+
+/// One SQLite file holding the corpus, in the `i-ching dump` schema, and the journal
+pub struct Store {
+    conn: Connection,
+}
+
+impl Store {
+    /// Open the database at `path`, creating it and loading the corpus on first use
+    pub fn open(path: &Path) -> Result<Self> {
+        if let Some(parent) = path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+        let mut conn = Connection::open(path)
+            .with_context(|| format!("Failed to open database {}", path.display()))?;
+        conn.busy_timeout(BUSY_TIMEOUT)?;
+
+        let tx = conn.transaction()?;
+        // A corpus copied by a version without pronunciations is copied again
+        let stored = columns(&tx, "hexagrams")?;
+        if !stored.is_empty() && !stored.iter().any(|column| column == "pronunciation_ipa") {
+            tx.execute_batch(
+                "DROP TABLE lines; DROP TABLE relations; DROP TABLE hexagrams; DROP TABLE trigrams;",
+            )?;
+        }
+        tx.execute_batch(dump::SQLITE_SCHEMA)?;
+        tx.execute_batch(JOURNAL_SCHEMA)?;
+        let hexagrams: u32 =
+            tx.query_row("SELECT count(*) FROM hexagrams", [], |row| row.get(0))?;
+        if hexagrams == 0 {
+            let data = IChingData::load()
+                .map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+            Corpus::new(&data)?.insert_into(&tx)?;
+        }
+        if columns(&tx, "journal_search")? != SEARCH_COLUMNS {
+            reindex(&tx)?;
+        }
+        tx.commit()?;
+
+        Ok(Self { conn })
+    }
+
+    /// The corpus, with each hexagram looked up in this store by number the
+    /// first time it's asked for rather than parsed from the embedded data
+    pub fn data(self) -> Result<IChingData> {
+        let data = IChingData::load()
+            .map_err(|e| anyhow::anyhow!("Failed to load I Ching data: {}", e))?;
+        let store = Mutex::new(self);
+        Ok(data.with_lookup(Arc::new(move |number| {
+            store.lock().ok()?.hexagram(number).ok().flatten()
+        })))
+    }
+
+    /// Look up a hexagram by number without parsing the whole corpus
+    pub fn hexagram(&self, number: u8) -> Result<Option<Hexagram>> {
+        let Some(mut hexagram) = self
+            .conn
+            .prepare_cached(
+                "SELECT h.name, h.chinese, h.pinyin, h.pronunciation_pinyin, h.pronunciation_ipa,
+                        h.symbol, h.lines, r.complement, h.upper_trigram, h.lower_trigram,
+                        h.description, h.sequence, h.miscellaneous, h.judgment,
+                        h.judgment_commentary, h.image, h.image_commentary
+                 FROM hexagrams h JOIN relations r ON r.hexagram = h.number
+                 WHERE h.number = ?1",
+            )?
+            .query_row([number], |row| {
+                let lines: String = row.get(6)?;
+                let pronunciation = match (row.get(3)?, row.get(4)?) {
+                    (Some(pinyin), Some(ipa)) => Some(Pronunciation { pinyin, ipa }),
+                    _ => None,
+                };
+                Ok(Hexagram {
+                    number,
+                    name: row.get(0)?,
+                    chinese: row.get(1)?,
+                    pinyin: row.get(2)?,
+                    pronunciation,
+                    unicode: row.get(5)?,
+                    // The data files write the figure top to bottom
+                    binary: lines.chars().rev().collect(),
+                    opposite: format!("{:02}", row.get::<_, u8>(7)?),
+                    upper_trigram: trigram(&row.get::<_, String>(8)?)?,
+                    lower_trigram: trigram(&row.get::<_, String>(9)?)?,
+                    description: row.get(10)?,
+                    sequence: row.get(11)?,
+                    miscellaneous: row.get(12)?,
+                    judgment: HexagramJudgment {
+                        text: row.get(13)?,
+                        commentary: row.get(14)?,
+                    },
+                    image: HexagramImage {
+                        text: row.get(15)?,
+                        commentary: row.get(16)?,
+                    },
+                    lines: HashMap::new(),
+                })
+            })
+            .optional()?
+        else {
+            return Ok(None);
+        };
+
+        let mut statement = self
+            .conn
+            .prepare_cached("SELECT position, text, comments FROM lines WHERE hexagram = ?1")?;
+        let lines = statement.query_map([number], |row| {
+            Ok((
+                row.get::<_, u8>(0)?.to_string(),
+                LineInterpretation {
+                    text: row.get(1)?,
+                    comments: row.get(2)?,
+                },
+            ))
+        })?;
+        hexagram.lines = lines.collect::<rusqlite::Result<_>>()?;
+
+        Ok(Some(hexagram))
+    }
+
+    /// Record an entry and index it for search
+    pub fn append(&self, entry: &JournalEntry) -> Result<()> {
+        let tx = self.conn.unchecked_transaction()?;
+        insert_entry(&tx, entry)?;
+        tx.commit()?;
+        Ok(())
+    }
+
+    /// All entries in the order they were recorded
+    pub fn entries(&self) -> Result<Vec<JournalEntry>> {
+        self.query_entries("SELECT entry FROM journal ORDER BY id", [])
+    }
+
+    /// Entries whose question, context, outcome note, or hexagram names
+    /// contain every word of `query`, or a word it begins, after stemming,
+    /// oldest first, from the full-text index
+    ///
+    /// `Journal::search` ranks these, and everything if none match, since
+    /// the index can't see past a typo.
+    pub fn search(&self, query: &str) -> Result<Vec<JournalEntry>> {
+        // Quote each word so punctuation can't be read as FTS5 query syntax
+        let query = query
+            .split_whitespace()
+            .filter(|word| word.chars().any(char::is_alphanumeric))
+            .map(|word| format!("\"{}\"*", word.replace('"', "\"\"")))
+            .collect::<Vec<_>>()
+            .join(" ");
+        if query.is_empty() {
+            return self.entries();
+        }
+
+        self.query_entries(
+            "SELECT entry FROM journal
+             WHERE id IN (SELECT rowid FROM journal_search WHERE journal_search MATCH ?1)
+             ORDER BY id",
+            [query],
+        )
+    }
+
+    /// Record `outcome` for entry `number`, counting from 1 in the order
+    /// recorded, returning the entry
+    pub fn set_outcome(&self, number: usize, outcome: Outcome) -> Result<JournalEntry> {
+        let row: Option<(i64, String)> = match number.checked_sub(1) {
+            Some(offset) => self
+                .conn
+                .query_row(
+                    "SELECT id, entry FROM journal ORDER BY id LIMIT 1 OFFSET ?1",
+                    [offset as i64],
+                    |row| Ok((row.get(0)?, row.get(1)?)),
+                )
+                .optional()?,
+            None => None,
+        };
+        let Some((id, json)) = row else {
+            let count: i64 = self
+                .conn
+                .query_row("SELECT count(*) FROM journal", [], |row| row.get(0))?;
+            return Err(journal::no_entry(number, count as usize));
+        };
+
+        let mut entry: JournalEntry =
+            serde_json::from_str(&json).context("Invalid journal entry in the database")?;
+        entry.outcome = Some(outcome);
+        let tx = self.conn.unchecked_transaction()?;
+        tx.execute(
+            "UPDATE journal SET entry = ?1 WHERE id = ?2",
+            params![serde_json::to_string(&entry)?, id],
+        )?;
+        index_entry(&tx, id, &entry)?;
+        tx.commit()?;
+        Ok(entry)
+    }
+
+    /// Rewrite entries stored in an older reading format in the current one,
+    /// in one transaction (see `Journal::migrate`)
+    pub fn migrate(&mut self, dry_run: bool) -> Result<Migration> {
+        let tx = self.conn.transaction()?;
+        let rows: Vec<(i64, String)> = tx
+            .prepare("SELECT id, entry FROM journal ORDER BY id")?
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<rusqlite::Result<_>>()?;
+
+        let mut migration = Migration {
+            entries: rows.len(),
+            upgraded: 0,
+        };
+        for (id, json) in rows {
+            if !journal::is_outdated(&json)? {
+                continue;
+            }
+            migration.upgraded += 1;
+            let entry: JournalEntry =
+                serde_json::from_str(&json).context("Invalid journal entry in the database")?;
+            tx.execute(
+                "UPDATE journal SET entry = ?1 WHERE id = ?2",
+                params![serde_json::to_string(&entry)?, id],
+            )?;
+            index_entry(&tx, id, &entry)?;
+        }
+        if !dry_run {
+            tx.commit()?;
+        }
+        Ok(migration)
+    }
+
+    /// Whether the journal has no entries yet
+    pub fn journal_is_empty(&self) -> Result<bool> {
+        let count: i64 = self
+            .conn
+            .query_row("SELECT count(*) FROM journal", [], |row| row.get(0))?;
+        Ok(count == 0)
+    }
+
+    /// Record many entries in one transaction, e.g. when importing a journal file
+    pub fn import(&mut self, entries: &[JournalEntry]) -> Result<()> {
+        let tx = self.conn.transaction()?;
+        for entry in entries {
+            insert_entry(&tx, entry)?;
+        }
+        tx.commit()?;
+        Ok(())
+    }
+
+    fn query_entries<P: rusqlite::Params>(
+        &self,
+        sql: &str,
+        params: P,
+    ) -> Result<Vec<JournalEntry>> {
+        let mut statement = self.conn.prepare(sql)?;
+        let rows = statement.query_map(params, |row| row.get::<_, String>(0))?;
+        rows.map(|json| {
+            serde_json::from_str(&json?).context("Invalid journal entry in the database")
+        })
+        .collect()
+    }
+}
+
+fn insert_entry(conn: &Connection, entry: &JournalEntry) -> Result<()> {
+    let reading = &entry.reading;
+    conn.execute(
+        "INSERT INTO journal (timestamp, hexagram, transformed, entry) VALUES (?1, ?2, ?3, ?4)",
+        params![
+            entry.timestamp.to_rfc3339(),
+            reading.primary_hexagram(),
+            reading.transformed_hexagram_number(),
+            serde_json::to_string(entry)?,
+        ],
+    )?;
+    index_entry(conn, conn.last_insert_rowid(), entry)
+}
+
+/// Write entry `id`'s searchable text to `journal_search`, replacing any
+/// it had
+fn index_entry(conn: &Connection, id: i64, entry: &JournalEntry) -> Result<()> {
+    let reading = &entry.reading;
+    let mut hexagrams = Vec::new();
+    for number in
+        std::iter::once(reading.primary_hexagram()).chain(reading.transformed_hexagram_number())
+    {
+        let name: Option<String> = conn
+            .prepare_cached("SELECT name FROM hexagrams WHERE number = ?1")?
+            .query_row([number], |row| row.get(0))
+            .optional()?;
+        hexagrams.push(format!("{} {}", number, name.unwrap_or_default()));
+    }
+
+    conn.execute("DELETE FROM journal_search WHERE rowid = ?1", [id])?;
+    conn.execute(
+        "INSERT INTO journal_search (rowid, question, context, note, hexagrams)
+         VALUES (?1, ?2, ?3, ?4, ?5)",
+        params![
+            id,
+            reading.question(),
+            reading.context(),
+            entry
+                .outcome
+                .as_ref()
+                .and_then(|outcome| outcome.note.as_deref()),
+            hexagrams.join(" "),
+        ],
+    )?;
+    Ok(())
+}
+
+/// Build `journal_search` afresh from every entry
+fn reindex(conn: &Connection) -> Result<()> {
+    conn.execute_batch("DROP TABLE IF EXISTS journal_search;")?;
+    conn.execute_batch(SEARCH_SCHEMA)?;
+    let rows: Vec<(i64, String)> = conn
+        .prepare("SELECT id, entry FROM journal")?
+        .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+        .collect::<rusqlite::Result<_>>()?;
+    for (id, json) in rows {
+        let entry: JournalEntry =
+            serde_json::from_str(&json).context("Invalid journal entry in the database")?;
+        index_entry(conn, id, &entry)?;
+    }
+    Ok(())
+}
+
+/// Names of `table`'s columns, none if it doesn't exist
+fn columns(conn: &Connection, table: &str) -> Result<Vec<String>> {
+    let names = conn
+        .prepare("SELECT name FROM pragma_table_info(?1)")?
+        .query_map([table], |row| row.get(0))?
+        .collect::<rusqlite::Result<_>>()?;
+    Ok(names)
+}
+
+/// The trigram a `trigrams.key` names
+fn trigram(key: &str) -> rusqlite::Result<Trigram> {
+    Trigram::ALL
+        .into_iter()
+        .find(|trigram| trigram.name() == key)
+        .ok_or_else(|| {
+            rusqlite::Error::FromSqlConversionFailure(
+                0,
+                rusqlite::types::Type::Text,
+                format!("Unknown trigram {}", key).into(),
+            )
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core::Reading;
+
+    #[test]
+    fn test_corpus_lookup_and_journal_search() {
+        let path = env::temp_dir().join(format!("i-ching-store-{}.db", std::process::id()));
+        let mut store = Store::open(&path).unwrap();
+
+        let data = IChingData::load().unwrap();
+        let stored = store.hexagram(3).unwrap().unwrap();
+        let embedded = data.get_hexagram(3).unwrap();
+        assert_eq!(stored.name, embedded.name);
+        assert_eq!(stored.binary, embedded.binary);
+        assert_eq!(stored.opposite, embedded.opposite);
+        assert_eq!(stored.upper_trigram, embedded.upper_trigram);
+        assert_eq!(stored.pronunciation, embedded.pronunciation);
+        assert_eq!(stored.lines["6"].text, embedded.lines["6"].text);
+        assert!(store.hexagram(65).unwrap().is_none());
+
+        assert!(store.journal_is_empty().unwrap());
+        let entry = |input: &str, question: &str| {
+            let reading: Reading = input.parse().unwrap();
+            JournalEntry::new(reading.with_question(Some(question.to_string())))
+        };
+        store
+            .append(&entry("11", "Should I take the job?"))
+            .unwrap();
+        store
+            .import(&[entry("3", "Move house?"), entry("1→2", "Job \"offer\"")])
+            .unwrap();
+
+        assert_eq!(store.entries().unwrap().len(), 3);
+        let outcome = Outcome {
+            resolved: true,
+            accuracy: Some(2),
+            resolved_on: None,
+            note: Some("Stayed put".to_string()),
+        };
+        store.set_outcome(2, outcome.clone()).unwrap();
+        assert_eq!(store.entries().unwrap()[1].outcome, Some(outcome.clone()));
+        assert!(store.set_outcome(4, outcome).is_err());
+
+        // An entry stored before readings carried a version gets upgraded
+        let json: String = store
+            .conn
+            .query_row("SELECT entry FROM journal WHERE id = 1", [], |row| {
+                row.get(0)
+            })
+            .unwrap();
+        store
+            .conn
+            .execute(
+                "UPDATE journal SET entry = ?1 WHERE id = 1",
+                [json.replace("\"version\":1,", "")],
+            )
+            .unwrap();
+        assert_eq!(store.migrate(true).unwrap().upgraded, 1);
+        assert_eq!(store.migrate(false).unwrap().upgraded, 1);
+        assert_eq!(store.migrate(false).unwrap().upgraded, 0);
+        assert_eq!(store.search("job").unwrap().len(), 2);
+        assert_eq!(store.search("staying").unwrap().len(), 1);
+        assert_eq!(store.search("Advance").unwrap().len(), 1);
+        assert_eq!(store.search("responding job").unwrap().len(), 1);
+        // Stemmed, and a word matches the words it begins
+        assert_eq!(store.search("jobs hous").unwrap().len(), 0);
+        assert_eq!(store.search("hous").unwrap().len(), 1);
+        // Quotes and brackets are searched for, not parsed as query syntax
+        assert_eq!(store.search("\"offer\" (").unwrap().len(), 1);
+
+        drop(store);
+
+        // Reopening keeps the journal and doesn't reload the corpus
+        let store = Store::open(&path).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 3);
+
+        // A database from an older version, without the search index or
+        // the pronunciations, is brought up to date
+        store
+            .conn
+            .execute_batch(
+                "DROP TABLE journal_search; ALTER TABLE hexagrams DROP COLUMN pronunciation_ipa;",
+            )
+            .unwrap();
+        drop(store);
+        let store = Store::open(&path).unwrap();
+        assert_eq!(store.entries().unwrap().len(), 3);
+        assert_eq!(store.search("job").unwrap().len(), 2);
+        assert!(store.hexagram(3).unwrap().unwrap().pronunciation.is_some());
+
+        // Readings look hexagrams up in the store
+        store
+            .conn
+            .execute("UPDATE hexagrams SET name = 'Stored' WHERE number = 3", [])
+            .unwrap();
+        let data = store.data().unwrap();
+        assert_eq!(data.get_hexagram(3).unwrap().name, "Stored");
+        assert_eq!(data.get_hexagram(4).unwrap().lines.len(), 6);
+        fs::remove_file(path).unwrap();
+    }
+}
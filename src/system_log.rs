@@ -0,0 +1,311 @@
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use anyhow::{Context, Result};
+use chrono::{SecondsFormat, Utc};
+use clap::ValueEnum;
+use std::path::{Path, PathBuf};
+
+/// Where journald takes native-protocol datagrams
+pub const JOURNALD_SOCKET: &str = "/run/systemd/journal/socket";
+
+/// Local syslog sockets: Linux, then macOS and the BSDs
+const SYSLOG_SOCKETS: [&str; 2] = ["/dev/log", "/var/run/syslog"];
+
+/// Structured-data ID for the syslog fields. 32473 is the enterprise number
+/// RFC 5612 sets aside for examples and private use.
+const SD_ID: &str = "iching@32473";
+
+/// Facility user (1) at severity informational (6)
+const SYSLOG_PRIORITY: u8 = 8 + 6;
+
+/// Journald priority: informational
+const JOURNALD_PRIORITY: &str = "6";
+
+/// System log a cast can be recorded in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum LogTarget {
+    /// The local syslog socket, with the fields as RFC 5424 structured data
+    Syslog,
+    /// The systemd journal, with the fields as journal fields
+    Journald,
+}
+
+impl LogTarget {
+    pub fn sink(&self) -> Box<dyn LogSink> {
+        match self {
+            LogTarget::Syslog => Box::new(Syslog::new()),
+            LogTarget::Journald => Box::new(Journald::new()),
+        }
+    }
+}
+
+/// A cast as a log message plus structured fields
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Record {
+    pub message: String,
+    /// Lowercase field names and their values, e.g. ("hexagram", "11")
+    pub fields: Vec<(&'static str, String)>,
+}
+
+impl Record {
+    /// e.g. "Cast 11 Advance -> 19 Approach (changing lines 1)" with the
+    /// hexagram numbers and lines as fields
+    ///
+    /// The question is left out, since system logs are often readable by
+    /// other users and kept long after the journal entry is deleted; see
+    /// [`Record::with_question`].
+    pub fn for_reading(reading: &Reading, data: &IChingData) -> Self {
+        let name = |number: u8| {
+            data.get_hexagram(number)
+                .map(|hexagram| format!("{} {}", number, hexagram.name))
+                .unwrap_or_else(|| number.to_string())
+        };
+        let join = |values: &[u8]| {
+            values
+                .iter()
+                .map(u8::to_string)
+                .collect::<Vec<_>>()
+                .join(",")
+        };
+
+        let primary = reading.primary_hexagram();
+        let changing = reading.changing_line_positions();
+        let mut message = format!("Cast {}", name(primary));
+        let mut fields = vec![("hexagram", primary.to_string())];
+
+        if let Some(transformed) = reading.transformed_hexagram_number() {
+            message.push_str(&format!(" -> {}", name(transformed)));
+            fields.push(("transformed", transformed.to_string()));
+        }
+        if !changing.is_empty() {
+            message.push_str(&format!(" (changing lines {})", join(&changing)));
+            fields.push(("changing_lines", join(&changing)));
+        }
+        fields.push(("lines", join(&reading.traditional_numbers())));
+
+        Self { message, fields }
+    }
+
+    /// The record with the reading's question, if it has one, after the
+    /// message and as a field (`--log-question`)
+    pub fn with_question(mut self, reading: &Reading) -> Self {
+        if let Some(question) = reading.question() {
+            self.message.push_str(&format!(": {}", question));
+            self.fields.push(("question", question.to_string()));
+        }
+        self
+    }
+}
+
+/// A place casts are logged
+pub trait LogSink {
+    fn send(&self, record: &Record) -> Result<()>;
+}
+
+/// The local syslog daemon
+pub struct Syslog {
+    pub socket: PathBuf,
+}
+
+impl Syslog {
+    /// Syslog at the first of the usual socket paths that exists
+    pub fn new() -> Self {
+        let socket = SYSLOG_SOCKETS
+            .iter()
+            .map(PathBuf::from)
+            .find(|path| path.exists())
+            .unwrap_or_else(|| PathBuf::from(SYSLOG_SOCKETS[0]));
+        Self { socket }
+    }
+}
+
+impl Default for Syslog {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogSink for Syslog {
+    fn send(&self, record: &Record) -> Result<()> {
+        let timestamp = Utc::now().to_rfc3339_opts(SecondsFormat::Millis, true);
+        let message = format_syslog(record, &timestamp, std::process::id());
+        send_datagram(&self.socket, message.as_bytes())
+    }
+}
+
+/// The systemd journal
+pub struct Journald {
+    pub socket: PathBuf,
+}
+
+impl Journald {
+    pub fn new() -> Self {
+        Self {
+            socket: PathBuf::from(JOURNALD_SOCKET),
+        }
+    }
+}
+
+impl Default for Journald {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl LogSink for Journald {
+    fn send(&self, record: &Record) -> Result<()> {
+        send_datagram(&self.socket, &encode_journal(record))
+    }
+}
+
+/// An RFC 5424 message: header, the fields as structured data, then the message
+fn format_syslog(record: &Record, timestamp: &str, pid: u32) -> String {
+    let params: String = record
+        .fields
+        .iter()
+        .map(|(name, value)| {
+            // Structured-data values escape quote, backslash, and closing bracket
+            let value = value
+                .replace('\\', "\\\\")
+                .replace('"', "\\\"")
+                .replace(']', "\\]");
+            format!(" {}=\"{}\"", name, value)
+        })
+        .collect();
+
+    format!(
+        "<{}>1 {} - i-ching {} - [{}{}] {}",
+        SYSLOG_PRIORITY, timestamp, pid, SD_ID, params, record.message
+    )
+}
+
+/// Journald's native protocol: one `NAME=value` line per field, or for values
+/// with newlines the name, a little-endian 64-bit length, and the raw value
+fn encode_journal(record: &Record) -> Vec<u8> {
+    let mut fields = vec![
+        ("MESSAGE".to_string(), record.message.as_str()),
+        ("PRIORITY".to_string(), JOURNALD_PRIORITY),
+        ("SYSLOG_IDENTIFIER".to_string(), "i-ching"),
+    ];
+    fields.extend(record.fields.iter().map(|(name, value)| {
+        (
+            format!("I_CHING_{}", name.to_ascii_uppercase()),
+            value.as_str(),
+        )
+    }));
+
+    let mut payload = Vec::new();
+    for (name, value) in fields {
+        payload.extend_from_slice(name.as_bytes());
+        if value.contains('\n') {
+            payload.push(b'\n');
+            payload.extend_from_slice(&(value.len() as u64).to_le_bytes());
+        } else {
+            payload.push(b'=');
+        }
+        payload.extend_from_slice(value.as_bytes());
+        payload.push(b'\n');
+    }
+    payload
+}
+
+#[cfg(unix)]
+fn send_datagram(socket: &Path, payload: &[u8]) -> Result<()> {
+    use std::os::unix::net::UnixDatagram;
+
+    let sender = UnixDatagram::unbound()?;
+    sender
+        .send_to(payload, socket)
+        .with_context(|| format!("Failed to log to {}", socket.display()))?;
+    Ok(())
+}
+
+#[cfg(not(unix))]
+fn send_datagram(socket: &Path, _payload: &[u8]) -> Result<()> {
+    Err(anyhow::anyhow!(
+        "Logging to {} needs Unix domain sockets, which this platform lacks",
+        socket.display()
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn reading() -> Reading {
+        let reading: Reading = "9,7,7,7,7,7".parse().unwrap();
+        reading.with_question(Some("Go [now]?\nOr \"later\"".to_string()))
+    }
+
+    /// A record with the question, whose brackets, quotes, and newline need escaping
+    fn record() -> Record {
+        Record::for_reading(&reading(), &IChingData::load().unwrap()).with_question(&reading())
+    }
+
+    #[test]
+    fn test_record() {
+        // The question stays out unless asked for
+        let record = Record::for_reading(&reading(), &IChingData::load().unwrap());
+        assert!(record.message.starts_with("Cast 1 Initiating -> 44 "));
+        assert!(record.message.ends_with("(changing lines 1)"));
+        assert!(!record.message.contains("Go"));
+        assert_eq!(
+            record.fields,
+            [
+                ("hexagram", "1".to_string()),
+                ("transformed", "44".to_string()),
+                ("changing_lines", "1".to_string()),
+                ("lines", "9,7,7,7,7,7".to_string()),
+            ]
+        );
+
+        let record = record.with_question(&reading());
+        assert!(record.message.contains("(changing lines 1): Go"));
+        assert_eq!(record.fields.len(), 5);
+    }
+
+    #[test]
+    fn test_format_syslog() {
+        let message = format_syslog(&record(), "2026-03-01T08:00:00.000Z", 42);
+        assert!(message.starts_with(
+            "<14>1 2026-03-01T08:00:00.000Z - i-ching 42 - [iching@32473 hexagram=\"1\""
+        ));
+        assert!(message.contains(" question=\"Go [now\\]?\nOr \\\"later\\\"\"] Cast 1 "));
+    }
+
+    #[test]
+    fn test_encode_journal() {
+        let payload = encode_journal(&record());
+        let text = String::from_utf8_lossy(&payload);
+        assert!(text.contains("PRIORITY=6\nSYSLOG_IDENTIFIER=i-ching\nI_CHING_HEXAGRAM=1\n"));
+
+        // The question has a newline, so it goes in the length-prefixed form
+        let question = "Go [now]?\nOr \"later\"";
+        let mut expected = b"I_CHING_QUESTION\n".to_vec();
+        expected.extend_from_slice(&(question.len() as u64).to_le_bytes());
+        expected.extend_from_slice(question.as_bytes());
+        expected.push(b'\n');
+        assert!(payload.ends_with(&expected));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_journald_sends_a_datagram() {
+        use std::os::unix::net::UnixDatagram;
+
+        let socket = std::env::temp_dir().join(format!("i-ching-journal-{}", std::process::id()));
+        let _ = std::fs::remove_file(&socket);
+        let receiver = UnixDatagram::bind(&socket).unwrap();
+
+        Journald {
+            socket: socket.clone(),
+        }
+        .send(&record())
+        .unwrap();
+        let mut buf = [0u8; 4096];
+        let len = receiver.recv(&mut buf).unwrap();
+        assert_eq!(&buf[..len], encode_journal(&record()).as_slice());
+
+        std::fs::remove_file(socket).unwrap();
+    }
+}
@@ -0,0 +1,182 @@
+//! Socket activation for `serve` and `daemon`, and the units that set it up
+//!
+//! With `--systemd-socket` the server takes its listening socket from
+//! systemd instead of binding one: systemd opens the socket from a `.socket`
+//! unit, starts the service on the first connection, and passes the socket
+//! as file descriptor 3, announced in `LISTEN_PID` and `LISTEN_FDS`.
+
+use anyhow::Result;
+use std::env;
+use std::path::Path;
+
+/// First file descriptor systemd passes (`SD_LISTEN_FDS_START`)
+#[cfg(unix)]
+const LISTEN_FDS_START: std::os::fd::RawFd = 3;
+
+/// Which server a pair of units starts
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Service {
+    /// `i-ching serve`, over TCP
+    Http,
+    /// `i-ching daemon`, on a Unix socket
+    Daemon,
+}
+
+impl Service {
+    /// Base name of the unit files
+    pub fn unit_name(self) -> &'static str {
+        match self {
+            Service::Http => "i-ching",
+            Service::Daemon => "i-ching-daemon",
+        }
+    }
+}
+
+/// The descriptor systemd passed this process, checked against
+/// `LISTEN_PID` and `LISTEN_FDS`
+#[cfg(unix)]
+fn listen_fd() -> Result<std::os::fd::RawFd> {
+    listen_fd_from(|name| env::var(name).ok(), std::process::id())
+}
+
+/// `listen_fd` over an environment lookup (split out for testing)
+#[cfg(unix)]
+fn listen_fd_from(var: impl Fn(&str) -> Option<String>, pid: u32) -> Result<std::os::fd::RawFd> {
+    let not_activated = || {
+        anyhow::anyhow!(
+            "--systemd-socket needs a socket from systemd (LISTEN_PID and LISTEN_FDS are not set for this process)"
+        )
+    };
+    let listen_pid: u32 = var("LISTEN_PID")
+        .and_then(|value| value.parse().ok())
+        .ok_or_else(not_activated)?;
+    if listen_pid != pid {
+        return Err(not_activated());
+    }
+    match var("LISTEN_FDS").and_then(|value| value.parse::<u32>().ok()) {
+        Some(1) => Ok(LISTEN_FDS_START),
+        Some(0) | None => Err(not_activated()),
+        Some(count) => Err(anyhow::anyhow!(
+            "systemd passed {} sockets; the unit should listen on exactly one",
+            count
+        )),
+    }
+}
+
+/// The TCP socket systemd is listening on for this process
+#[cfg(unix)]
+pub fn tcp_listener() -> Result<std::net::TcpListener> {
+    use std::os::fd::FromRawFd;
+
+    let fd = listen_fd()?;
+    // SAFETY: systemd hands the process this descriptor, open and unowned by
+    // anything else in it, and LISTEN_PID confirms it is meant for us
+    Ok(unsafe { std::net::TcpListener::from_raw_fd(fd) })
+}
+
+/// The Unix socket systemd is listening on for this process
+#[cfg(unix)]
+pub fn unix_listener() -> Result<std::os::unix::net::UnixListener> {
+    use std::os::fd::FromRawFd;
+
+    let fd = listen_fd()?;
+    // SAFETY: as in `tcp_listener`
+    Ok(unsafe { std::os::unix::net::UnixListener::from_raw_fd(fd) })
+}
+
+#[cfg(not(unix))]
+pub fn tcp_listener() -> Result<std::net::TcpListener> {
+    Err(anyhow::anyhow!(
+        "Socket activation needs systemd, which this platform lacks"
+    ))
+}
+
+/// A `.socket` unit and the `.service` it starts, by file name
+///
+/// The HTTP server's units are system units listening on `listen`; the
+/// daemon's are user units listening on `i-ching.sock` in the runtime
+/// directory, where `i-ching client` looks for it. The HTTP server answers
+/// other machines and writes nothing, so its service runs as a throwaway
+/// user with the file system read-only.
+pub fn units(service: Service, exe: &Path, listen: &str) -> [(String, String); 2] {
+    let name = service.unit_name();
+    let (description, listen_stream, command, sandbox) = match service {
+        Service::Http => (
+            "I Ching oracle over HTTP",
+            listen,
+            "serve",
+            "DynamicUser=yes\n\
+             ProtectSystem=strict\n\
+             ProtectHome=read-only\n\
+             NoNewPrivileges=yes\n",
+        ),
+        Service::Daemon => ("I Ching daemon", "%t/i-ching.sock", "daemon", ""),
+    };
+    let socket = format!(
+        "[Unit]\n\
+         Description={description} (socket)\n\
+         \n\
+         [Socket]\n\
+         ListenStream={listen_stream}\n\
+         \n\
+         [Install]\n\
+         WantedBy=sockets.target\n"
+    );
+    let service = format!(
+        "[Unit]\n\
+         Description={description}\n\
+         Requires={name}.socket\n\
+         After={name}.socket\n\
+         \n\
+         [Service]\n\
+         ExecStart={} {command} --systemd-socket\n\
+         {sandbox}",
+        exe.display()
+    );
+    [
+        (format!("{}.socket", name), socket),
+        (format!("{}.service", name), service),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_listen_fd() {
+        use std::collections::HashMap;
+
+        let fd = |vars: &[(&str, &str)]| {
+            let vars: HashMap<&str, &str> = vars.iter().copied().collect();
+            listen_fd_from(|name| vars.get(name).map(|v| v.to_string()), 42)
+        };
+        assert_eq!(fd(&[("LISTEN_PID", "42"), ("LISTEN_FDS", "1")]).unwrap(), 3);
+        assert!(fd(&[("LISTEN_PID", "41"), ("LISTEN_FDS", "1")]).is_err());
+        assert!(fd(&[("LISTEN_PID", "42"), ("LISTEN_FDS", "2")]).is_err());
+        assert!(fd(&[]).is_err());
+    }
+
+    #[test]
+    fn test_units() {
+        let [(socket_name, socket), (service_name, service)] =
+            units(Service::Http, Path::new("/usr/bin/i-ching"), "0.0.0.0:7464");
+        assert_eq!(socket_name, "i-ching.socket");
+        assert!(socket.contains("\nListenStream=0.0.0.0:7464\n"));
+        assert_eq!(service_name, "i-ching.service");
+        assert!(service.contains("\nExecStart=/usr/bin/i-ching serve --systemd-socket\n"));
+        for directive in [
+            "DynamicUser=yes",
+            "ProtectSystem=strict",
+            "ProtectHome=read-only",
+            "NoNewPrivileges=yes",
+        ] {
+            assert!(service.contains(&format!("\n{}\n", directive)));
+        }
+
+        let [(_, socket), (_, service)] = units(Service::Daemon, Path::new("/usr/bin/i-ching"), "");
+        assert!(socket.contains("\nListenStream=%t/i-ching.sock\n"));
+        assert!(service.ends_with("daemon --systemd-socket\n"));
+    }
+}
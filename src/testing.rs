@@ -0,0 +1,110 @@
+//! Proptest strategies for lines and readings (the `testing` feature)
+//!
+//! Crates that embed the library can property-test their own code against
+//! any valid reading rather than a handful of fixed ones:
+//!
+//! ```ignore
+//! use proptest::prelude::*;
+//!
+//! proptest! {
+//!     #[test]
+//!     fn renders_every_reading(reading in i_ching::testing::reading()) {
+//!         prop_assert!(!my_renderer(&reading).is_empty());
+//!     }
+//! }
+//! ```
+//!
+//! [`Line`] and [`Reading`] also implement `Arbitrary`, so `any::<Reading>()`
+//! works too.
+
+use crate::core::Reading;
+use crate::core::reading::Line;
+use proptest::arbitrary::Arbitrary;
+use proptest::prelude::*;
+use proptest::strategy::BoxedStrategy;
+
+/// Any line: a 6, 7, 8, or 9, equally likely
+pub fn line() -> impl Strategy<Value = Line> {
+    (6u8..=9)
+        .prop_map(|number| Line::from_traditional_number(number).expect("6-9 are line numbers"))
+}
+
+/// Six lines, bottom first
+pub fn lines() -> impl Strategy<Value = [Line; 6]> {
+    proptest::array::uniform6(line())
+}
+
+/// A King Wen hexagram number, 1-64
+pub fn hexagram_number() -> impl Strategy<Value = u8> {
+    1u8..=64
+}
+
+/// Any reading, without a question
+pub fn reading() -> impl Strategy<Value = Reading> {
+    lines().prop_map(|lines| Reading::new(lines, None))
+}
+
+/// A reading with at least one changing line
+pub fn changing_reading() -> impl Strategy<Value = Reading> {
+    (hexagram_number(), 1u8..64).prop_map(|(number, changing)| {
+        Reading::with_changing_lines(number, changing).expect("valid hexagram and lines")
+    })
+}
+
+/// A reading with no changing lines
+pub fn unchanging_reading() -> impl Strategy<Value = Reading> {
+    hexagram_number()
+        .prop_map(|number| Reading::from_hexagram_number(number).expect("valid hexagram number"))
+}
+
+impl Arbitrary for Line {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Line>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        line().boxed()
+    }
+}
+
+impl Arbitrary for Reading {
+    type Parameters = ();
+    type Strategy = BoxedStrategy<Reading>;
+
+    fn arbitrary_with(_: ()) -> Self::Strategy {
+        reading().boxed()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    proptest! {
+        #[test]
+        fn test_reading_invariants(reading in any::<Reading>()) {
+            let numbers = reading.traditional_numbers();
+            let text = numbers.map(|n| n.to_string()).join(",");
+            prop_assert_eq!(text.parse::<Reading>().unwrap().traditional_numbers(), numbers);
+            prop_assert!((1..=64).contains(&reading.primary_hexagram()));
+            prop_assert_eq!(
+                reading.has_changing_lines(),
+                !reading.changing_line_positions().is_empty()
+            );
+        }
+
+        #[test]
+        fn test_changing_readings_transform(reading in changing_reading()) {
+            let transformed = reading.transformed_hexagram().unwrap();
+            prop_assert_eq!(
+                Some(transformed.primary_hexagram()),
+                reading.transformed_hexagram_number()
+            );
+            prop_assert!(!transformed.has_changing_lines());
+        }
+
+        #[test]
+        fn test_unchanging_readings(reading in unchanging_reading()) {
+            prop_assert_eq!(reading.transformed_hexagram_number(), None);
+        }
+    }
+}
@@ -0,0 +1,274 @@
+//! The six lines as a short tune, for `--format tones` and `i-ching play`
+//!
+//! The lines climb a pentatonic scale from the bottom. A yang line sounds its
+//! note once and held; a yin line, broken, sounds it twice. A changing line is
+//! ornamented with a grace note from the step above, and when lines change the
+//! hexagram they become is played after a bar's rest.
+
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::core::reading::{Age, Line, Polarity};
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use std::io::Write;
+use std::thread;
+use std::time::Duration;
+
+/// MIDI notes the lines sound, bottom line first (C D E G A c), and the step
+/// above the top line for its grace note
+const SCALE: [u8; 7] = [60, 62, 64, 67, 69, 72, 74];
+
+/// Eighth notes in each line's bar
+const LINE_EIGHTHS: u32 = 4;
+
+/// Quarter notes per minute
+const TEMPO: u32 = 96;
+
+/// MIDI ticks per quarter note
+const TICKS_PER_QUARTER: u32 = 480;
+
+/// How long a grace note takes from the note it ornaments, in MIDI ticks
+const GRACE_TICKS: u32 = 60;
+
+/// General MIDI pan flute, counted from zero
+const INSTRUMENT: u8 = 75;
+
+/// How `i-ching play` sounds a reading
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Sound {
+    /// Terminal bells, pitched on the Linux console
+    Beep,
+    /// ABC notation text
+    Abc,
+    /// A Standard MIDI File
+    Midi,
+}
+
+/// A note or rest of the tune
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Note {
+    /// MIDI note number, or `None` for a rest
+    pub pitch: Option<u8>,
+    /// Length in eighth notes
+    pub eighths: u32,
+    /// Grace note played quickly before this one
+    pub grace: Option<u8>,
+}
+
+/// The reading's tune: its lines bottom to top, then those of the hexagram
+/// it becomes
+pub fn melody(reading: &Reading) -> Vec<Note> {
+    let mut notes = phrase(reading.lines());
+    if let Some(transformed) = reading.transformed_hexagram() {
+        notes.push(Note {
+            pitch: None,
+            eighths: LINE_EIGHTHS,
+            grace: None,
+        });
+        notes.extend(phrase(transformed.lines()));
+    }
+    notes
+}
+
+fn phrase(lines: &[Line; 6]) -> Vec<Note> {
+    let mut notes = Vec::new();
+    for (i, line) in lines.iter().enumerate() {
+        let pitch = Some(SCALE[i]);
+        let grace = (line.age == Age::Old).then_some(SCALE[i + 1]);
+        match line.polarity {
+            Polarity::Yang => notes.push(Note {
+                pitch,
+                eighths: LINE_EIGHTHS,
+                grace,
+            }),
+            Polarity::Yin => {
+                let half = LINE_EIGHTHS / 2;
+                notes.push(Note {
+                    pitch,
+                    eighths: half,
+                    grace,
+                });
+                notes.push(Note {
+                    pitch,
+                    eighths: half,
+                    grace: None,
+                });
+            }
+        }
+    }
+    notes
+}
+
+/// The tune in ABC notation, titled with the hexagrams, one bar per line
+pub fn to_abc(reading: &Reading, data: &IChingData) -> String {
+    let title = std::iter::once(reading.primary_hexagram())
+        .chain(reading.transformed_hexagram_number())
+        .map(|number| match data.get_hexagram(number) {
+            Some(hexagram) => format!("{} {}", number, hexagram.name),
+            None => number.to_string(),
+        })
+        .collect::<Vec<_>>()
+        .join(" -> ");
+
+    let mut abc = format!("X:1\nT:{}\nM:2/4\nL:1/8\nQ:1/4={}\nK:C\n", title, TEMPO);
+    let mut elapsed = 0;
+    for note in melody(reading) {
+        if let Some(grace) = note.grace {
+            abc.push_str(&format!("{{{}}}", abc_pitch(grace)));
+        }
+        match note.pitch {
+            Some(pitch) => abc.push_str(&abc_pitch(pitch)),
+            None => abc.push('z'),
+        }
+        abc.push_str(&note.eighths.to_string());
+        elapsed += note.eighths;
+        abc.push_str(if elapsed % LINE_EIGHTHS == 0 {
+            " | "
+        } else {
+            " "
+        });
+    }
+    abc.truncate(abc.trim_end_matches([' ', '|']).len());
+    abc.push_str(" |]\n");
+    abc
+}
+
+/// A MIDI note as ABC writes it: C is middle C, c the octave above
+fn abc_pitch(pitch: u8) -> String {
+    const NAMES: [&str; 12] = [
+        "C", "^C", "D", "^D", "E", "F", "^F", "G", "^G", "A", "^A", "B",
+    ];
+    let name = NAMES[usize::from(pitch % 12)];
+    let octave = i32::from(pitch / 12) - 5;
+    let mut result = if octave >= 1 {
+        name.to_lowercase()
+    } else {
+        name.to_string()
+    };
+    for _ in 1..octave {
+        result.push('\'');
+    }
+    for _ in octave..0 {
+        result.push(',');
+    }
+    result
+}
+
+/// The tune as a single-track Standard MIDI File
+pub fn to_midi(reading: &Reading) -> Vec<u8> {
+    let ticks_per_eighth = TICKS_PER_QUARTER / 2;
+    let tempo = 60_000_000 / TEMPO;
+
+    let mut track = Vec::new();
+    push_event(&mut track, 0, &[0xFF, 0x51, 0x03]);
+    track.extend_from_slice(&tempo.to_be_bytes()[1..]);
+    push_event(&mut track, 0, &[0xC0, INSTRUMENT]);
+
+    let mut delay = 0;
+    for note in melody(reading) {
+        let length = note.eighths * ticks_per_eighth;
+        let Some(pitch) = note.pitch else {
+            delay += length;
+            continue;
+        };
+        let mut held = length;
+        if let Some(grace) = note.grace {
+            push_event(&mut track, delay, &[0x90, grace, 0x50]);
+            push_event(&mut track, GRACE_TICKS, &[0x80, grace, 0x40]);
+            delay = 0;
+            held -= GRACE_TICKS;
+        }
+        push_event(&mut track, delay, &[0x90, pitch, 0x60]);
+        push_event(&mut track, held, &[0x80, pitch, 0x40]);
+        delay = 0;
+    }
+    push_event(&mut track, delay, &[0xFF, 0x2F, 0x00]);
+
+    let mut midi = Vec::with_capacity(22 + track.len());
+    midi.extend_from_slice(b"MThd");
+    midi.extend_from_slice(&6u32.to_be_bytes());
+    midi.extend_from_slice(&0u16.to_be_bytes()); // format 0: one track
+    midi.extend_from_slice(&1u16.to_be_bytes());
+    midi.extend_from_slice(&(TICKS_PER_QUARTER as u16).to_be_bytes());
+    midi.extend_from_slice(b"MTrk");
+    midi.extend_from_slice(&(track.len() as u32).to_be_bytes());
+    midi.extend_from_slice(&track);
+    midi
+}
+
+/// Append an event after `delta` ticks, the delta as a MIDI variable-length number
+fn push_event(track: &mut Vec<u8>, delta: u32, event: &[u8]) {
+    let mut bytes = vec![(delta & 0x7F) as u8];
+    let mut rest = delta >> 7;
+    while rest > 0 {
+        bytes.push((rest & 0x7F) as u8 | 0x80);
+        rest >>= 7;
+    }
+    track.extend(bytes.iter().rev());
+    track.extend_from_slice(event);
+}
+
+/// Play the tune as terminal bells, waiting out each note
+///
+/// Before each bell the Linux console is told the note's pitch and length;
+/// other terminals ignore that and ring their usual bell in rhythm.
+pub fn beep(reading: &Reading, out: &mut impl Write) -> Result<()> {
+    let eighth = Duration::from_millis(u64::from(30_000 / TEMPO));
+    for note in melody(reading) {
+        let mut length = eighth * note.eighths;
+        if let Some(pitch) = note.pitch {
+            if let Some(grace) = note.grace {
+                let short = eighth / 4;
+                ring(out, grace, short)?;
+                thread::sleep(short);
+                length -= short;
+            }
+            ring(out, pitch, length)?;
+        }
+        thread::sleep(length);
+    }
+    Ok(())
+}
+
+fn ring(out: &mut impl Write, pitch: u8, length: Duration) -> Result<()> {
+    let hertz = 440.0 * 2f64.powf((f64::from(pitch) - 69.0) / 12.0);
+    write!(
+        out,
+        "\x1b[10;{}]\x1b[11;{}]\x07",
+        hertz.round(),
+        length.as_millis()
+    )
+    .and_then(|()| out.flush())
+    .context("Failed to ring the terminal bell")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tones() {
+        let data = IChingData::load().unwrap();
+        let reading: Reading = "7,8,9,6,7,8".parse().unwrap();
+
+        let notes = melody(&reading);
+        // Six lines with three yin, a rest, then six more with three yin
+        assert_eq!(notes.len(), 9 + 1 + 9);
+        assert_eq!(notes[0].pitch, Some(60));
+        assert_eq!(notes[3].grace, Some(67));
+
+        let abc = to_abc(&reading, &data);
+        assert!(abc.starts_with("X:1\nT:"));
+        assert!(abc.contains("\nK:C\nC4 | D2 D2 | {G}E4 | {A}G2 G2 | A4 | c2 c2 | z4 | C4"));
+        assert!(abc.ends_with(" |]\n"));
+
+        let midi = to_midi(&reading);
+        assert!(midi.starts_with(b"MThd\0\0\0\x06\0\0\0\x01"));
+        let track_length = u32::from_be_bytes(midi[18..22].try_into().unwrap());
+        assert_eq!(midi.len(), 22 + track_length as usize);
+        assert!(midi.ends_with(&[0xFF, 0x2F, 0x00]));
+
+        assert_eq!(abc_pitch(48), "C,");
+        assert_eq!(abc_pitch(84), "c'");
+    }
+}
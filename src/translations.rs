@@ -0,0 +1,416 @@
+//! Other translations of the texts, for `--compare-translations`
+//!
+//! Wilhelm's comes with the data set. Others are JSON files named after the
+//! translation in `$I_CHING_TRANSLATIONS`, or in `translations/` in the
+//! config directory, e.g. `legge.json`:
+//!
+//! ```json
+//! {"1": {"judgment": "...", "image": "...", "lines": {"1": "...", "2": "..."}}}
+//! ```
+//!
+//! A file may leave out hexagrams or texts; comparisons say they're missing.
+//! It may also say where it comes from under a `provenance` key, with the
+//! fields of [`Provenance`], for `i-ching sources`.
+
+use crate::format::format_brief_summary;
+use crate::core::Reading;
+use crate::core::data::{IChingData, Provenance};
+use crate::format::{display_width, pad};
+use crate::glyphs::Glyphs;
+use anyhow::{Context, Result};
+use clap::ValueEnum;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Environment variable that overrides the translations directory
+pub const TRANSLATIONS_DIR_ENV: &str = "I_CHING_TRANSLATIONS";
+
+/// The translation the data set carries
+pub const BUILT_IN: &str = "wilhelm";
+
+/// The Chinese corpus, `zh.json`: where it's installed, `--lang zh` shows
+/// its texts in place of Wilhelm's and `--bilingual` shows both
+pub const CHINESE: &str = "zh";
+
+/// Columns between side-by-side texts
+const GUTTER: &str = " | ";
+
+/// Shown where a translation lacks a text
+const MISSING: &str = "(not in this translation)";
+
+/// How `--compare-translations` sets the texts against each other
+#[derive(ValueEnum, Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum Layout {
+    /// Side by side in columns
+    #[default]
+    Columns,
+    /// One after another under each heading
+    Interleaved,
+}
+
+/// One hexagram's texts in a translation
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct Texts {
+    #[serde(default)]
+    judgment: Option<String>,
+    #[serde(default)]
+    image: Option<String>,
+    /// Line statements by position, "1" (bottom) to "6"
+    #[serde(default)]
+    lines: BTreeMap<String, String>,
+}
+
+/// A translation file: hexagrams by number, and where they come from
+#[derive(Deserialize)]
+struct TranslationFile {
+    #[serde(default)]
+    provenance: Option<Provenance>,
+    #[serde(flatten)]
+    hexagrams: HashMap<String, Texts>,
+}
+
+/// A passage of a hexagram's text
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Passage {
+    Judgment,
+    Image,
+    /// A line statement, 1 (bottom) to 6
+    Line(u8),
+}
+
+/// The judgment, image, and line texts of one translation
+pub struct Translation {
+    pub name: String,
+    /// Source, translator, and license, if the file gives them
+    pub provenance: Option<Provenance>,
+    hexagrams: HashMap<String, Texts>,
+}
+
+impl Translation {
+    /// Wilhelm's texts, from the data set
+    pub fn wilhelm(data: &IChingData) -> Self {
+        let hexagrams = data
+            .hexagrams()
+            .map(|hexagram| {
+                let texts = Texts {
+                    judgment: Some(hexagram.judgment.text.clone()),
+                    image: Some(hexagram.image.text.clone()),
+                    lines: hexagram
+                        .lines
+                        .iter()
+                        .map(|(position, line)| (position.clone(), line.text.clone()))
+                        .collect(),
+                };
+                (hexagram.number.to_string(), texts)
+            })
+            .collect();
+        Self {
+            name: BUILT_IN.to_string(),
+            provenance: data
+                .provenance()
+                .iter()
+                .find(|provenance| provenance.corpus == BUILT_IN)
+                .cloned(),
+            hexagrams,
+        }
+    }
+
+    /// The named translation: Wilhelm's, or `NAME.json` in the translations
+    /// directory
+    pub fn load(name: &str, data: &IChingData) -> Result<Self> {
+        let name = name.trim().to_lowercase();
+        if name == BUILT_IN {
+            return Ok(Self::wilhelm(data));
+        }
+
+        let dir = translations_dir().ok_or_else(|| {
+            anyhow::anyhow!("No translations directory; set ${}", TRANSLATIONS_DIR_ENV)
+        })?;
+        let path = dir.join(format!("{}.json", name));
+        let content = fs::read_to_string(&path).with_context(|| {
+            format!(
+                "No translation '{}': couldn't read {} (available: {})",
+                name,
+                path.display(),
+                available().join(", ")
+            )
+        })?;
+        Self::parse(&name, &content)
+            .with_context(|| format!("Invalid translation file {}", path.display()))
+    }
+
+    /// A translation from JSON in the shape the module docs give
+    pub fn parse(name: &str, content: &str) -> Result<Self> {
+        let file: TranslationFile = serde_json::from_str(content)?;
+        Ok(Self {
+            name: name.to_string(),
+            provenance: file.provenance,
+            hexagrams: file.hexagrams,
+        })
+    }
+
+    /// The Chinese corpus, if one is installed
+    pub fn chinese(data: &IChingData) -> Result<Option<Self>> {
+        if !available().iter().any(|name| name == CHINESE) {
+            return Ok(None);
+        }
+        Self::load(CHINESE, data).map(Some)
+    }
+
+    /// Put this translation's judgment, image, and line texts in place of
+    /// the ones in `data`, wherever it has them; commentary is left as is
+    pub fn apply(&self, data: &mut IChingData) {
+        for (number, texts) in &self.hexagrams {
+            let Some(hexagram) = number
+                .parse()
+                .ok()
+                .and_then(|number| data.hexagram_mut(number))
+            else {
+                continue;
+            };
+            if let Some(judgment) = &texts.judgment {
+                hexagram.judgment.text = judgment.clone();
+            }
+            if let Some(image) = &texts.image {
+                hexagram.image.text = image.clone();
+            }
+            for (position, text) in &texts.lines {
+                if let Some(line) = hexagram.lines.get_mut(position) {
+                    line.text = text.clone();
+                }
+            }
+        }
+    }
+
+    pub fn text(&self, number: u8, passage: Passage) -> Option<&str> {
+        let texts = self.hexagrams.get(&number.to_string())?;
+        match passage {
+            Passage::Judgment => texts.judgment.as_deref(),
+            Passage::Image => texts.image.as_deref(),
+            Passage::Line(position) => texts.lines.get(&position.to_string()).map(String::as_str),
+        }
+    }
+}
+
+/// `$I_CHING_TRANSLATIONS`, or `translations/` in the config directory
+pub fn translations_dir() -> Option<PathBuf> {
+    match env::var_os(TRANSLATIONS_DIR_ENV) {
+        Some(dir) => Some(PathBuf::from(dir)),
+        None => Some(dirs::config_dir()?.join("i-ching").join("translations")),
+    }
+}
+
+/// Names of the translations that can be compared, Wilhelm's first
+pub fn available() -> Vec<String> {
+    let mut names: Vec<String> = translations_dir()
+        .and_then(|dir| fs::read_dir(dir).ok())
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let path = entry.ok()?.path();
+            if path.extension()? != "json" {
+                return None;
+            }
+            Some(path.file_stem()?.to_str()?.to_string())
+        })
+        .filter(|name| name != BUILT_IN)
+        .collect();
+    names.sort();
+    names.insert(0, BUILT_IN.to_string());
+    names
+}
+
+/// The reading's judgment, image, and changing lines, and the judgment of
+/// the hexagram it becomes, in each translation
+pub fn compare(
+    reading: &Reading,
+    data: &IChingData,
+    translations: &[Translation],
+    layout: Layout,
+    width: usize,
+    glyphs: Glyphs,
+) -> String {
+    let number = reading.primary_hexagram();
+    let mut passages = vec![
+        ("Judgment".to_string(), number, Passage::Judgment),
+        ("Image".to_string(), number, Passage::Image),
+    ];
+    for position in reading.changing_line_positions() {
+        passages.push((
+            format!("Line {}", position),
+            number,
+            Passage::Line(position),
+        ));
+    }
+    if let Some(transformed) = reading.transformed_hexagram_number() {
+        let name = match data.get_hexagram(transformed) {
+            Some(hexagram) => format!(
+                "{}{} {}",
+                glyphs.prefix(&hexagram.unicode),
+                transformed,
+                hexagram.name
+            ),
+            None => transformed.to_string(),
+        };
+        passages.push((
+            format!("Judgment of {}", name),
+            transformed,
+            Passage::Judgment,
+        ));
+    }
+
+    let mut text = format!("{}\n", format_brief_summary(reading, data, glyphs));
+    for (heading, number, passage) in passages {
+        text.push_str(&format!("\n=== {} ===\n", heading));
+        let texts: Vec<&str> = translations
+            .iter()
+            .map(|translation| translation.text(number, passage).unwrap_or(MISSING))
+            .collect();
+        match layout {
+            Layout::Columns => text.push_str(&columns(translations, &texts, width)),
+            Layout::Interleaved => {
+                for (i, (translation, passage)) in translations.iter().zip(&texts).enumerate() {
+                    if i > 0 {
+                        text.push('\n');
+                    }
+                    text.push_str(&format!("[{}]\n{}\n", translation.name, passage));
+                }
+            }
+        }
+    }
+    text
+}
+
+/// The texts side by side under their translations' names, each wrapped to
+/// its share of `width`
+fn columns(translations: &[Translation], texts: &[&str], width: usize) -> String {
+    let count = translations.len().max(1);
+    let column = (width.saturating_sub(GUTTER.len() * (count - 1)) / count).max(12);
+
+    let wrapped: Vec<Vec<String>> = translations
+        .iter()
+        .zip(texts)
+        .map(|(translation, text)| {
+            let mut lines = vec![
+                translation.name.clone(),
+                "-".repeat(display_width(&translation.name)),
+            ];
+            lines.extend(wrap(text, column));
+            lines
+        })
+        .collect();
+    let height = wrapped.iter().map(Vec::len).max().unwrap_or_default();
+
+    let mut result = String::new();
+    for row in 0..height {
+        let cells: Vec<String> = wrapped
+            .iter()
+            .map(|lines| {
+                let cell = lines.get(row).map_or("", String::as_str);
+                pad(cell, column)
+            })
+            .collect();
+        result.push_str(cells.join(GUTTER).trim_end());
+        result.push('\n');
+    }
+    result
+}
+
+/// `text` broken at spaces into lines of at most `width` columns, keeping
+/// its own line breaks; a word longer than `width` gets a line to itself
+pub(crate) fn wrap(text: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    for paragraph in text.lines() {
+        let mut line = String::new();
+        for word in paragraph.split_whitespace() {
+            if !line.is_empty() && display_width(&line) + 1 + display_width(word) > width {
+                lines.push(std::mem::take(&mut line));
+            }
+            if !line.is_empty() {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        lines.push(line);
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_compare() {
+        let data = IChingData::load().unwrap();
+        let legge = Translation::parse(
+            "legge",
+            r#"{"32": {"judgment": "Hang intimates successful progress and no error.", "lines": {"1": "The first line, divided, shows its subject deeply desirous of long continuance."}}}"#,
+        )
+        .unwrap();
+        assert!(Translation::parse("legge", r#"{"1": {"judgement": ""}}"#).is_err());
+        assert_eq!(legge.text(32, Passage::Image), None);
+        assert_eq!(legge.provenance, None);
+        let with_provenance = Translation::parse(
+            "legge",
+            r#"{"provenance": {"corpus": "legge", "title": "The Yi King", "translator": "James Legge", "year": 1882, "license": "Public domain", "source": "Sacred Books of the East, vol. 16"}, "1": {}}"#,
+        )
+        .unwrap();
+        assert_eq!(with_provenance.provenance.unwrap().year, Some(1882));
+
+        let translations = [Translation::wilhelm(&data), legge];
+        let reading: Reading = "32->34".parse().unwrap();
+        let text = compare(
+            &reading,
+            &data,
+            &translations,
+            Layout::Columns,
+            80,
+            Glyphs::Ascii,
+        );
+        assert!(text.contains("\n=== Judgment ===\nwilhelm"));
+        assert!(text.contains("=== Line 1 ===\n"));
+        assert!(text.contains("=== Judgment of 34 Great Strength ===\n"));
+        assert!(text.contains(MISSING));
+        assert!(
+            text.lines().all(|line| line.chars().count() <= 80),
+            "{}",
+            text
+        );
+        let row = text
+            .lines()
+            .find(|line| line.starts_with("wilhelm"))
+            .unwrap();
+        assert!(row.ends_with(" | legge"));
+
+        let text = compare(
+            &reading,
+            &data,
+            &translations,
+            Layout::Interleaved,
+            80,
+            Glyphs::Ascii,
+        );
+        assert!(text.contains("\n[legge]\nHang intimates successful progress and no error.\n"));
+
+        assert_eq!(wrap("one two three\nfour", 7), ["one two", "three", "four"]);
+
+        let mut data = data;
+        let zh = Translation::parse(
+            CHINESE,
+            r#"{"32": {"judgment": "恆：亨，无咎，利貞，利有攸往。", "lines": {"1": "浚恆，貞凶，无攸利。"}}}"#,
+        )
+        .unwrap();
+        let image = data.get_hexagram(32).unwrap().image.text.clone();
+        zh.apply(&mut data);
+        let hexagram = data.get_hexagram(32).unwrap();
+        assert_eq!(hexagram.judgment.text, "恆：亨，无咎，利貞，利有攸往。");
+        assert_eq!(hexagram.lines["1"].text, "浚恆，貞凶，无攸利。");
+        assert_eq!(hexagram.image.text, image);
+        assert!(!hexagram.judgment.commentary.is_empty());
+    }
+}
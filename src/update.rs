@@ -0,0 +1,231 @@
+//! `i-ching self-update`: replace a standalone binary with the latest release
+//!
+//! Each GitHub release carries the `i-ching` binary for every platform it's
+//! built for, as a bare file named `i-ching-{platform}` (with `.exe` on
+//! Windows), and a `SHA256SUMS` file in `sha256sum` format. The download is
+//! checked against its sum before it replaces the running binary, so a
+//! truncated or corrupted file is never installed.
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Where the latest release is described
+const LATEST_URL: &str = "https://api.github.com/repos/threemachines/i-ching/releases/latest";
+
+/// Release asset listing each binary's SHA-256 sum
+const SUMS_ASSET: &str = "SHA256SUMS";
+
+/// Largest binary to download
+const MAX_DOWNLOAD: u64 = 256 * 1024 * 1024;
+
+/// A GitHub release, as the API describes it
+#[derive(Debug, Clone, Deserialize)]
+pub struct Release {
+    pub tag_name: String,
+    pub assets: Vec<Asset>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct Asset {
+    pub name: String,
+    pub browser_download_url: String,
+}
+
+impl Release {
+    /// The latest published release
+    pub fn latest() -> Result<Self> {
+        ureq::get(LATEST_URL)
+            .header("Accept", "application/vnd.github+json")
+            .header("User-Agent", concat!("i-ching/", env!("CARGO_PKG_VERSION")))
+            .call()
+            .with_context(|| format!("Failed to reach {}", LATEST_URL))?
+            .body_mut()
+            .read_json()
+            .with_context(|| format!("Unexpected response from {}", LATEST_URL))
+    }
+
+    /// The release's version, without the tag's `v`
+    pub fn version(&self) -> &str {
+        self.tag_name.trim_start_matches('v')
+    }
+
+    /// Whether the release is newer than `current`
+    pub fn is_newer_than(&self, current: &str) -> bool {
+        match (parse_version(self.version()), parse_version(current)) {
+            (Some(release), Some(current)) => release > current,
+            _ => false,
+        }
+    }
+
+    fn asset(&self, name: &str) -> Result<&Asset> {
+        self.assets
+            .iter()
+            .find(|asset| asset.name == name)
+            .ok_or_else(|| anyhow::anyhow!("Release {} has no {}", self.tag_name, name))
+    }
+
+    /// Download this platform's binary and check it against the release's
+    /// sums
+    pub fn download(&self) -> Result<Vec<u8>> {
+        let name = asset_name().ok_or_else(|| {
+            anyhow::anyhow!("Releases aren't built for this platform; build from source instead")
+        })?;
+        let sums = String::from_utf8(download(self.asset(SUMS_ASSET)?)?)
+            .context("SHA256SUMS is not text")?;
+        let expected = checksum(&sums, &name)
+            .ok_or_else(|| anyhow::anyhow!("SHA256SUMS lists no sum for {}", name))?;
+        let binary = download(self.asset(&name)?)?;
+        let actual = sha256_hex(&binary);
+        if actual != expected {
+            return Err(anyhow::anyhow!(
+                "{} failed its checksum (expected {}, got {}); nothing was replaced",
+                name,
+                expected,
+                actual
+            ));
+        }
+        Ok(binary)
+    }
+}
+
+fn download(asset: &Asset) -> Result<Vec<u8>> {
+    ureq::get(&asset.browser_download_url)
+        .header("User-Agent", concat!("i-ching/", env!("CARGO_PKG_VERSION")))
+        .call()
+        .with_context(|| format!("Failed to download {}", asset.name))?
+        .body_mut()
+        .with_config()
+        .limit(MAX_DOWNLOAD)
+        .read_to_vec()
+        .with_context(|| format!("Failed to download {}", asset.name))
+}
+
+/// The release asset name for this platform's binary, if releases build one
+pub fn asset_name() -> Option<String> {
+    let platform = match (std::env::consts::OS, std::env::consts::ARCH) {
+        ("linux", "x86_64") => "linux-x86_64",
+        ("macos", "x86_64") => "macos-x86_64",
+        ("macos", "aarch64") => "macos-arm64",
+        ("windows", "x86_64") => "windows-x86_64",
+        _ => return None,
+    };
+    Some(format!(
+        "i-ching-{}{}",
+        platform,
+        std::env::consts::EXE_SUFFIX
+    ))
+}
+
+/// `1.2.3` as numbers, ignoring any pre-release or build suffix
+fn parse_version(version: &str) -> Option<[u64; 3]> {
+    let core = version.split(['-', '+']).next()?;
+    let mut parts = core.split('.').map(|part| part.parse().ok());
+    let version = [parts.next()??, parts.next()??, parts.next()??];
+    parts.next().is_none().then_some(version)
+}
+
+/// The sum `sums` lists for `name`, from lines like `<hex>  <name>` (or
+/// `<hex> *<name>` for binary mode)
+fn checksum(sums: &str, name: &str) -> Option<String> {
+    sums.lines().find_map(|line| {
+        let (sum, file) = line.split_once(char::is_whitespace)?;
+        let file = file.trim_start().trim_start_matches('*');
+        (file == name).then(|| sum.to_lowercase())
+    })
+}
+
+fn sha256_hex(bytes: &[u8]) -> String {
+    Sha256::digest(bytes)
+        .iter()
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// Whether `exe` lives where `cargo install` puts binaries, which cargo
+/// should update instead
+pub fn installed_by_cargo(exe: &Path) -> bool {
+    let cargo_home = std::env::var_os("CARGO_HOME")
+        .map(PathBuf::from)
+        .or_else(|| dirs::home_dir().map(|home| home.join(".cargo")));
+    cargo_home.is_some_and(|home| exe.starts_with(home.join("bin")))
+}
+
+/// Put `binary` in place of the running executable, returning its path
+///
+/// The new binary is written beside the old one and renamed over it, so the
+/// executable is never half written. Windows won't replace a running
+/// executable, so there the old one is first moved aside to `.old`.
+pub fn replace_current_exe(binary: &[u8]) -> Result<PathBuf> {
+    let exe = std::env::current_exe()
+        .and_then(fs::canonicalize)
+        .context("Failed to find the running executable")?;
+    let with_suffix = |suffix: &str| {
+        let mut path = exe.clone().into_os_string();
+        path.push(suffix);
+        PathBuf::from(path)
+    };
+    let new = with_suffix(".new");
+    let denied = |e: std::io::Error| {
+        anyhow::anyhow!(
+            "Failed to write {}: {} (run self-update as the user who installed it)",
+            new.display(),
+            e
+        )
+    };
+
+    fs::write(&new, binary).map_err(denied)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        let mode = fs::metadata(&exe)?.permissions().mode();
+        fs::set_permissions(&new, fs::Permissions::from_mode(mode | 0o111)).map_err(denied)?;
+    }
+    if cfg!(windows) {
+        let old = with_suffix(".old");
+        let _ = fs::remove_file(&old);
+        fs::rename(&exe, &old)
+            .with_context(|| format!("Failed to move {} aside", exe.display()))?;
+    }
+    fs::rename(&new, &exe).with_context(|| format!("Failed to replace {}", exe.display()))?;
+    Ok(exe)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_release_checks() {
+        assert_eq!(parse_version("1.10.2"), Some([1, 10, 2]));
+        assert_eq!(parse_version("2.0.0-rc.1"), Some([2, 0, 0]));
+        assert_eq!(parse_version("1.2"), None);
+        assert_eq!(parse_version("1.2.3.4"), None);
+
+        let release = |tag: &str| Release {
+            tag_name: tag.to_string(),
+            assets: Vec::new(),
+        };
+        assert!(release("v1.10.0").is_newer_than("1.9.3"));
+        assert!(!release("v1.0.0").is_newer_than("1.0.0"));
+        assert!(!release("nightly").is_newer_than("1.0.0"));
+        assert!(release("v1.0.0").asset(SUMS_ASSET).is_err());
+
+        let sums = "AB12  i-ching-linux-x86_64\ncd34 *i-ching-windows-x86_64.exe\n";
+        assert_eq!(
+            checksum(sums, "i-ching-linux-x86_64").as_deref(),
+            Some("ab12")
+        );
+        assert_eq!(
+            checksum(sums, "i-ching-windows-x86_64.exe").as_deref(),
+            Some("cd34")
+        );
+        assert_eq!(checksum(sums, "i-ching-macos-arm64"), None);
+        assert_eq!(
+            sha256_hex(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+}
@@ -0,0 +1,130 @@
+use crate::core::Reading;
+use anyhow::{Context, Result};
+use std::collections::BTreeSet;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Environment variable that overrides the watchlist file location
+pub const WATCHLIST_PATH_ENV: &str = "I_CHING_WATCHLIST";
+
+/// Exit status for `--exit-if-watched` when a cast turns up a watched hexagram
+pub const WATCHED_EXIT_CODE: i32 = 3;
+
+/// Hexagrams the user wants to hear about, stored as a JSON array of numbers
+pub struct Watchlist {
+    path: PathBuf,
+}
+
+impl Watchlist {
+    /// Open the watchlist at `$I_CHING_WATCHLIST`, or in the user config directory
+    pub fn open_default() -> Result<Self> {
+        if let Some(path) = env::var_os(WATCHLIST_PATH_ENV) {
+            return Ok(Self::at(path));
+        }
+
+        let config_dir = dirs::config_dir()
+            .ok_or_else(|| anyhow::anyhow!("Could not determine the user config directory"))?;
+        Ok(Self::at(config_dir.join("i-ching").join("watchlist.json")))
+    }
+
+    /// Open a watchlist stored at a specific path
+    pub fn at(path: impl Into<PathBuf>) -> Self {
+        Self { path: path.into() }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// The watched hexagrams (none if no watchlist exists yet)
+    pub fn load(&self) -> Result<BTreeSet<u8>> {
+        if !self.path.exists() {
+            return Ok(BTreeSet::new());
+        }
+
+        let content = fs::read_to_string(&self.path)
+            .with_context(|| format!("Failed to read watchlist {}", self.path.display()))?;
+        serde_json::from_str(&content)
+            .with_context(|| format!("Invalid watchlist file {}", self.path.display()))
+    }
+
+    /// Watch these hexagrams, creating the file if needed
+    pub fn add(&self, hexagrams: &[u8]) -> Result<BTreeSet<u8>> {
+        if let Some(&number) = hexagrams.iter().find(|n| !(1..=64).contains(*n)) {
+            return Err(anyhow::anyhow!(
+                "Hexagram number must be between 1 and 64, got {}",
+                number
+            ));
+        }
+
+        let mut watched = self.load()?;
+        watched.extend(hexagrams);
+        self.save(&watched)?;
+        Ok(watched)
+    }
+
+    /// Stop watching these hexagrams
+    pub fn remove(&self, hexagrams: &[u8]) -> Result<BTreeSet<u8>> {
+        let mut watched = self.load()?;
+        watched.retain(|number| !hexagrams.contains(number));
+        self.save(&watched)?;
+        Ok(watched)
+    }
+
+    fn save(&self, watched: &BTreeSet<u8>) -> Result<()> {
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)
+                .with_context(|| format!("Failed to create {}", parent.display()))?;
+        }
+
+        let content = serde_json::to_string(watched)?;
+        fs::write(&self.path, content + "\n")
+            .with_context(|| format!("Failed to write watchlist {}", self.path.display()))
+    }
+}
+
+/// The reading's watched hexagrams, primary first, each with whether it's
+/// the primary hexagram
+pub fn matches(watched: &BTreeSet<u8>, reading: &Reading) -> Vec<(u8, bool)> {
+    std::iter::once((reading.primary_hexagram(), true))
+        .chain(
+            reading
+                .transformed_hexagram_number()
+                .map(|number| (number, false)),
+        )
+        .filter(|(number, _)| watched.contains(number))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    #[test]
+    fn test_watch_and_match() {
+        let watchlist = Watchlist::at(env::temp_dir().join(format!(
+            "i-ching-watchlist-{}-{}.json",
+            std::process::id(),
+            Utc::now().timestamp_nanos_opt().unwrap_or_default()
+        )));
+        assert!(watchlist.load().unwrap().is_empty());
+
+        watchlist.add(&[51, 29]).unwrap();
+        let watched = watchlist.add(&[29, 34]).unwrap();
+        assert_eq!(watched.iter().copied().collect::<Vec<_>>(), [29, 34, 51]);
+        assert!(watchlist.add(&[0]).is_err());
+
+        let reading: Reading = "32->34".parse().unwrap();
+        assert_eq!(matches(&watched, &reading), [(34, false)]);
+        let reading: Reading = "29".parse().unwrap();
+        assert_eq!(matches(&watched, &reading), [(29, true)]);
+
+        let watched = watchlist.remove(&[34, 51]).unwrap();
+        assert_eq!(watched.iter().copied().collect::<Vec<_>>(), [29]);
+        assert_eq!(watchlist.load().unwrap(), watched);
+
+        fs::remove_file(watchlist.path()).unwrap();
+    }
+}
@@ -0,0 +1,164 @@
+use crate::interpretation::framing::Topic;
+use serde::{Deserialize, Serialize};
+use std::io::{self, BufRead, Write};
+
+/// Topics offered by the wizard, in menu order
+const TOPICS: [Topic; 4] = [
+    Topic::Career,
+    Topic::Leadership,
+    Topic::Relationship,
+    Topic::Timing,
+];
+
+/// Whether the asker is deciding what to do or asking how things will go
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Agency {
+    /// "What should I do about ...?"
+    Act,
+    /// "How will ... unfold?"
+    Unfold,
+}
+
+/// A question put together with `i-ching wizard`, kept with the journal entry
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct StructuredQuestion {
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub topic: Option<Topic>,
+    /// The situation, in the asker's words
+    pub subject: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub timeframe: Option<String>,
+    pub agency: Agency,
+}
+
+impl StructuredQuestion {
+    /// The question in words, e.g. "What should I do about the job offer this month?"
+    pub fn compose(&self) -> String {
+        let timeframe = self
+            .timeframe
+            .as_ref()
+            .map(|timeframe| format!(" {}", timeframe))
+            .unwrap_or_default();
+        match self.agency {
+            Agency::Act => format!("What should I do about {}{}?", self.subject, timeframe),
+            Agency::Unfold => format!("How will {} unfold{}?", self.subject, timeframe),
+        }
+    }
+}
+
+/// Walk the user through topic, situation, timeframe, and agency, then let
+/// them accept or reword the composed question
+///
+/// Returns the structured answers and the final wording.
+pub fn ask(
+    input: &mut impl BufRead,
+    out: &mut impl Write,
+) -> io::Result<(StructuredQuestion, String)> {
+    writeln!(out, "Let's shape your question.\n")?;
+
+    writeln!(out, "What is it about?")?;
+    for (i, topic) in TOPICS.iter().enumerate() {
+        writeln!(out, "  {}) {}", i + 1, topic.label())?;
+    }
+    writeln!(out, "  {}) Something else", TOPICS.len() + 1)?;
+    let topic = loop {
+        match prompt(input, out)?.parse::<usize>() {
+            Ok(choice) if (1..=TOPICS.len()).contains(&choice) => break Some(TOPICS[choice - 1]),
+            Ok(choice) if choice == TOPICS.len() + 1 => break None,
+            _ => writeln!(out, "Choose 1-{}", TOPICS.len() + 1)?,
+        }
+    };
+
+    writeln!(
+        out,
+        "\nIn a few words, what situation are you asking about?"
+    )?;
+    let subject = loop {
+        let subject = prompt(input, out)?;
+        let subject = subject.trim_end_matches(['?', '.', '!']).trim();
+        if !subject.is_empty() {
+            break subject.to_string();
+        }
+        writeln!(out, "Describe the situation, e.g. \"the job offer\"")?;
+    };
+
+    writeln!(
+        out,
+        "\nOver what timeframe? (e.g. \"this month\"; leave blank for none)"
+    )?;
+    let timeframe = prompt(input, out)?;
+    let timeframe = (!timeframe.is_empty()).then_some(timeframe);
+
+    writeln!(out, "\nAre you asking")?;
+    writeln!(out, "  1) what you should do, or")?;
+    writeln!(out, "  2) how things will unfold?")?;
+    let agency = loop {
+        match prompt(input, out)?.as_str() {
+            "1" => break Agency::Act,
+            "2" => break Agency::Unfold,
+            _ => writeln!(out, "Choose 1 or 2")?,
+        }
+    };
+
+    let structured = StructuredQuestion {
+        topic,
+        subject,
+        timeframe,
+        agency,
+    };
+    let composed = structured.compose();
+    writeln!(out, "\nYour question: {}", composed)?;
+    writeln!(out, "Press Enter to ask it, or type a new wording:")?;
+    let reworded = prompt(input, out)?;
+    writeln!(out)?;
+
+    let question = if reworded.is_empty() {
+        composed
+    } else {
+        reworded
+    };
+    Ok((structured, question))
+}
+
+/// Read one trimmed line after a "> " prompt; end of input cancels the wizard
+fn prompt(input: &mut impl BufRead, out: &mut impl Write) -> io::Result<String> {
+    write!(out, "> ")?;
+    out.flush()?;
+
+    let mut line = String::new();
+    if input.read_line(&mut line)? == 0 {
+        return Err(io::Error::new(
+            io::ErrorKind::UnexpectedEof,
+            "Wizard cancelled before the question was finished",
+        ));
+    }
+    Ok(line.trim().to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn run(answers: &str) -> io::Result<(StructuredQuestion, String)> {
+        ask(&mut answers.as_bytes(), &mut Vec::new())
+    }
+
+    #[test]
+    fn test_wizard() {
+        let (structured, question) = run("1\nthe job offer?\nthis month\n1\n\n").unwrap();
+        assert_eq!(structured.topic, Some(Topic::Career));
+        assert_eq!(structured.timeframe.as_deref(), Some("this month"));
+        assert_eq!(question, "What should I do about the job offer this month?");
+
+        // Bad choices are asked again; the composed question can be reworded
+        let (structured, question) =
+            run("9\n5\n\nthe move\n\nx\n2\nWill the move go well?\n").unwrap();
+        assert_eq!(structured.topic, None);
+        assert_eq!(structured.agency, Agency::Unfold);
+        assert_eq!(structured.compose(), "How will the move unfold?");
+        assert_eq!(question, "Will the move go well?");
+
+        assert!(run("1\nthe job\n").is_err());
+    }
+}
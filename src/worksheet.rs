@@ -0,0 +1,252 @@
+//! `--format worksheet`: a reading laid out for journaling, with prompts to
+//! answer in the space below each
+//!
+//! The prompts come from `data/worksheet.json`; a file of the same shape at
+//! `$I_CHING_WORKSHEET` or `worksheet.json` in the config directory replaces
+//! them.
+
+use crate::format::{format_brief_summary, format_trigram};
+use crate::core::Reading;
+use crate::core::data::IChingData;
+use crate::glyphs::Glyphs;
+use crate::rendered::focus_first;
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use serde_json::{Map, Value};
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+/// Environment variable that overrides the worksheet prompts file location
+pub const WORKSHEET_PATH_ENV: &str = "I_CHING_WORKSHEET";
+
+const DEFAULT_PROMPTS: &str = include_str!("../data/worksheet.json");
+
+/// Width of the rules left for writing answers
+const ANSWER_WIDTH: usize = 60;
+
+/// Ruled lines under each prompt
+const ANSWER_LINES: usize = 3;
+
+/// Questions asked about each part of a reading
+///
+/// Prompts may use `{hexagram}`, `{upper}`, `{lower}`, and `{transformed}`,
+/// and changing-line prompts `{position}` and `{text}` too. A prompts file
+/// that leaves out a section keeps the built-in prompts for it.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Prompts {
+    /// Asked first, before the texts are read
+    pub opening: Vec<String>,
+    pub hexagram: Vec<String>,
+    pub trigrams: Vec<String>,
+    pub judgment: Vec<String>,
+    pub image: Vec<String>,
+    /// Asked once for each changing line
+    pub changing_line: Vec<String>,
+    /// Asked when lines change
+    pub transformation: Vec<String>,
+    /// Asked when no lines change
+    pub unchanging: Vec<String>,
+    pub closing: Vec<String>,
+}
+
+impl Default for Prompts {
+    /// The prompts in `data/worksheet.json`
+    fn default() -> Self {
+        serde_json::from_str(DEFAULT_PROMPTS).expect("data/worksheet.json is valid")
+    }
+}
+
+impl Prompts {
+    /// The user's prompts from `$I_CHING_WORKSHEET` or the config directory,
+    /// or the built-in ones if there's no such file
+    pub fn load_default() -> Result<Self> {
+        let path = match env::var_os(WORKSHEET_PATH_ENV) {
+            Some(path) => PathBuf::from(path),
+            None => match dirs::config_dir() {
+                Some(dir) => dir.join("i-ching").join("worksheet.json"),
+                None => return Ok(Self::default()),
+            },
+        };
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+
+        let content = fs::read_to_string(&path)
+            .with_context(|| format!("Failed to read worksheet prompts {}", path.display()))?;
+        Self::parse(&content)
+            .with_context(|| format!("Invalid worksheet prompts file {}", path.display()))
+    }
+
+    /// Prompts from a JSON object, the built-in ones filling any section it
+    /// leaves out
+    pub fn parse(content: &str) -> Result<Self> {
+        let mut sections: Map<String, Value> =
+            serde_json::from_str(DEFAULT_PROMPTS).context("Invalid built-in prompts")?;
+        sections.extend(serde_json::from_str::<Map<String, Value>>(content)?);
+        Ok(serde_json::from_value(Value::Object(sections))?)
+    }
+}
+
+/// The reading's figure and texts, each followed by its prompts and ruled
+/// space to answer them, with the `focus_line` first among the changing lines
+pub fn format_worksheet(
+    reading: &Reading,
+    data: &IChingData,
+    glyphs: Glyphs,
+    prompts: &Prompts,
+    focus_line: Option<u8>,
+) -> String {
+    let number = reading.primary_hexagram();
+    let hexagram = data.get_hexagram(number);
+    let name = |number: u8| match data.get_hexagram(number) {
+        Some(hexagram) => format!(
+            "{}{} {}",
+            glyphs.prefix(&hexagram.unicode),
+            number,
+            hexagram.name
+        ),
+        None => number.to_string(),
+    };
+    let fill = |prompt: &str| {
+        prompt
+            .replace("{hexagram}", &name(number))
+            .replace(
+                "{upper}",
+                &format_trigram(reading.upper_trigram(), glyphs).to_string(),
+            )
+            .replace(
+                "{lower}",
+                &format_trigram(reading.lower_trigram(), glyphs).to_string(),
+            )
+            .replace(
+                "{transformed}",
+                &reading
+                    .transformed_hexagram_number()
+                    .map(name)
+                    .unwrap_or_default(),
+            )
+    };
+
+    let mut worksheet = Worksheet::default();
+    worksheet.text("=== Worksheet ===\n");
+    if let Some(question) = reading.question() {
+        worksheet.text(&format!("Question: {}\n", question));
+    }
+    worksheet.text(&format!(
+        "Date: {}\n",
+        chrono::Local::now().format("%Y-%m-%d")
+    ));
+    worksheet.ask(prompts.opening.iter().map(|prompt| fill(prompt)));
+
+    worksheet.text(&format!(
+        "\n{}\n\n",
+        format_brief_summary(reading, data, glyphs)
+    ));
+    for (i, line) in reading.lines().iter().enumerate().rev() {
+        worksheet.text(&format!("{}: {}\n", i + 1, glyphs.line(line)));
+    }
+    worksheet.ask(prompts.hexagram.iter().map(|prompt| fill(prompt)));
+    worksheet.ask(prompts.trigrams.iter().map(|prompt| fill(prompt)));
+
+    if let Some(hexagram) = hexagram {
+        worksheet.text(&format!("\nJudgment: {}\n", hexagram.judgment.text));
+        worksheet.ask(prompts.judgment.iter().map(|prompt| fill(prompt)));
+        worksheet.text(&format!("\nImage: {}\n", hexagram.image.text));
+        worksheet.ask(prompts.image.iter().map(|prompt| fill(prompt)));
+    }
+
+    for position in focus_first(reading.changing_line_positions(), focus_line) {
+        if let Some(line) = data.get_line_interpretation(number, position) {
+            let marker = if focus_line == Some(position) {
+                " (focus)"
+            } else {
+                ""
+            };
+            worksheet.text(&format!("\nLine {}{}: {}\n", position, marker, line.text));
+            worksheet.ask(prompts.changing_line.iter().map(|prompt| {
+                fill(prompt)
+                    .replace("{position}", &position.to_string())
+                    .replace("{text}", &line.text)
+            }));
+        }
+    }
+
+    if let Some(transformed) = reading
+        .transformed_hexagram_number()
+        .and_then(|number| data.get_hexagram(number))
+    {
+        worksheet.text(&format!(
+            "\nTransforms to {}\nJudgment: {}\n",
+            name(transformed.number),
+            transformed.judgment.text
+        ));
+        worksheet.ask(prompts.transformation.iter().map(|prompt| fill(prompt)));
+    } else {
+        worksheet.ask(prompts.unchanging.iter().map(|prompt| fill(prompt)));
+    }
+
+    worksheet.text("\n=== Closing ===\n");
+    worksheet.ask(prompts.closing.iter().map(|prompt| fill(prompt)));
+    worksheet.text
+}
+
+/// Worksheet text with its prompts numbered throughout
+#[derive(Default)]
+struct Worksheet {
+    text: String,
+    asked: usize,
+}
+
+impl Worksheet {
+    fn text(&mut self, text: &str) {
+        self.text.push_str(text);
+    }
+
+    fn ask(&mut self, prompts: impl Iterator<Item = String>) {
+        for prompt in prompts {
+            self.asked += 1;
+            self.text
+                .push_str(&format!("\n{}. {}\n\n", self.asked, prompt));
+            for _ in 0..ANSWER_LINES {
+                self.text
+                    .push_str(&format!("   {}\n", "_".repeat(ANSWER_WIDTH)));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_worksheet() {
+        let data = IChingData::load().unwrap();
+        let prompts = Prompts::default();
+        assert!(!prompts.closing.is_empty());
+
+        let reading: Reading = "32->34".parse().unwrap();
+        let reading = reading.with_question(Some("Stay?".to_string()));
+        let worksheet = format_worksheet(&reading, &data, Glyphs::Ascii, &prompts, None);
+        assert!(worksheet.starts_with("=== Worksheet ===\nQuestion: Stay?\n"));
+        assert!(worksheet.contains("The lower trigram, Xun (Wind), stands for"));
+        assert!(worksheet.contains("\nLine 1: Seeking duration too hastily"));
+        assert!(worksheet.contains("moving toward 34 Great Strength."));
+        assert!(!worksheet.contains("No lines are changing"));
+        assert!(!worksheet.contains('{'));
+        let focused = format_worksheet(&reading, &data, Glyphs::Ascii, &prompts, Some(1));
+        assert!(focused.contains("\nLine 1 (focus): Seeking duration too hastily"));
+
+        // A prompts file replaces only the sections it has
+        let custom = Prompts::parse(r#"{"closing": ["Now what about {hexagram}?"]}"#).unwrap();
+        assert_eq!(custom.judgment, prompts.judgment);
+        assert!(Prompts::parse(r#"{"judgement": []}"#).is_err());
+        let worksheet = format_worksheet(&reading, &data, Glyphs::Ascii, &custom, None);
+        assert!(worksheet.ends_with(&format!(
+            ". Now what about 32 Long Lasting?\n\n{}",
+            format!("   {}\n", "_".repeat(ANSWER_WIDTH)).repeat(ANSWER_LINES)
+        )));
+    }
+}
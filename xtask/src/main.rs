@@ -0,0 +1,31 @@
+//! Development tasks run with `cargo xtask`, kept out of the i-ching binary
+//!
+//! `cargo xtask man [DIR]` writes the manual pages, `i-ching.1` and one per
+//! subcommand, into `DIR` (by default `target/man`). The pages cover the
+//! subcommands behind Cargo features too, since the alias builds i-ching
+//! with the `all-commands` feature of this crate.
+
+use anyhow::{Context, Result};
+use clap::CommandFactory;
+use i_ching::cli::Cli;
+use std::env;
+use std::fs;
+use std::path::PathBuf;
+
+fn main() -> Result<()> {
+    let mut args = env::args().skip(1);
+    match args.next().as_deref() {
+        Some("man") => {
+            let dir = args
+                .next()
+                .map_or_else(|| PathBuf::from("target").join("man"), PathBuf::from);
+            fs::create_dir_all(&dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+            clap_mangen::generate_to(Cli::command(), &dir)
+                .with_context(|| format!("Failed to write manual pages to {}", dir.display()))?;
+            println!("Wrote manual pages to {}", dir.display());
+            Ok(())
+        }
+        _ => Err(anyhow::anyhow!("Usage: cargo xtask man [DIR]")),
+    }
+}